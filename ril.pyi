@@ -1,19 +1,242 @@
 from __future__ import annotations
 
-from typing import Iterator, List, Optional, Tuple, Type, TypeAlias, Union
+from typing import Any, Awaitable, Callable, Dict, Iterator, List, Optional, Tuple, Type, TypeAlias, Union, overload
 
 Pixels: TypeAlias = Union[BitPixel, L, Rgb, Rgba]
 Xy: TypeAlias = Tuple[int, int]
+Point: TypeAlias = Tuple[float, float]
+EncodeOptions: TypeAlias = Union[PngOptions, JpegOptions, GifOptions, WebPOptions]
 
+__all__: List[str]
+"""List[str]: The names of every public class, function and constant of the module."""
+
+PARALLEL: bool
+"""bool: Whether ril was built with the `parallel` feature."""
+
+WEBP: bool
+"""bool: Whether ril was built with the `webp` feature, which is required to encode :class:`.WebPOptions`."""
+
+
+def set_parallel_threshold(pixels: int) -> None:
+    """
+    Sets the minimum amount of pixels an image must have before pixel operations are split across threads.
+
+    This has no effect unless ril was built with the `parallel` feature, see :data:`PARALLEL`.
+
+    Parameters
+    ----------
+    pixels: int
+        The threshold, in pixels.
+    """
+
+
+class UnknownFormatError(RuntimeError):
+    """Raised when the encoding format of some data can't be inferred."""
+
+
+class BufferTooSmallError(BufferError):
+    """Raised when encoded data does not fit into the given buffer. The size it needs is stored in `required_size`."""
+
+    required_size: int
+
+
+class UnsupportedFormatError(ValueError):
+    """Raised when encoding to a format this build can't encode to. The formats it can encode to are stored in `supported`."""
+
+    supported: List[str]
+
+
+def set_notebook_preview_size(size: int) -> None:
+    """
+    Sets the maximum width or height of images displayed in Jupyter notebooks.
+
+    Larger images are downscaled, keeping their aspect ratio, before being embedded in the notebook.
+
+    Parameters
+    ----------
+    size: int
+        The maximum dimension of the preview, in pixels. Defaults to 512.
+    """
+
+
+def set_trace_hook(hook: Optional[Callable[[Dict[str, object]], None]]) -> None:
+    """
+    Sets a callback invoked at coarse milestones of long operations, such as decoding, encoding and resizing.
+
+    This is meant for diagnosing slow or stuck operations. The hook is called with a single `dict` holding:
+
+    - ``operation``: The name of the operation, one of `decode`, `encode`, `encode_frames` or `resize`.
+    - ``event``: `start`, `end`, or `progress` after each frame of a sequence is processed.
+      GIF frames report `encode_frames` progress when they are quantized with :class:`.GifOptions` or
+      on more than one worker, otherwise ril encodes the sequence as a whole.
+    - ``dimensions``: The dimensions of the image or sequence, or `None` if not known yet.
+      The `end` event of `decode` and `resize` carries the resulting dimensions.
+    - ``elapsed_ms``: The milliseconds elapsed since the operation started, as a float.
+    - ``frame`` and ``frames``: The amount of frames processed so far and in total, only for `progress` events.
+
+    The GIL is acquired to call the hook, even from operations that otherwise run without it.
+    Exceptions raised by the hook are printed and otherwise ignored.
+
+    Parameters
+    ----------
+    hook: Optional[Callable[[dict], None]]
+        The callback, or `None` to disable tracing.
+    """
+
+
+def contrast_ratio(a: Pixel, b: Pixel) -> float:
+    """
+    Returns the contrast ratio between two colors as defined by WCAG 2, such as for checking that text is legible.
+
+    Translucent colors are composited first, `b` over white and then `a` over `b`, like text of color `a` on a background
+    of color `b`. Opaque colors can be given in either order.
+
+    Parameters
+    ----------
+    a: :class:`.Pixel`
+        The foreground color, such as of text.
+    b: :class:`.Pixel`
+        The background color.
+
+    Returns
+    -------
+    float
+        The contrast ratio, from `1` for colors of the same luminance to `21` for black on white.
+        WCAG level AA requires a ratio of at least 4.5 for normal text, and 3 for large text.
+
+    Examples
+    --------
+
+    .. code-block:: python3
+
+        background = image.average_color((x1, y1, x2, y2))
+        assert ril.contrast_ratio(text_color, background) >= 4.5
+    """
+
+
+def nearest_color(pixel: Pixel, palette: List[Pixel], metric: str = 'euclidean') -> int:
+    """
+    Finds the color of a palette that is closest to the given pixel, such as for snapping colors to a palette.
+
+    Pixels are compared as RGBA, see :meth:`Pixel.distance`.
+
+    Parameters
+    ----------
+    pixel: :class:`.Pixel`
+        The pixel to look up.
+    palette: List[:class:`.Pixel`]
+        The colors to choose from.
+    metric: str, default: "euclidean"
+        The metric to compare colors with, see :meth:`Pixel.distance`.
+
+    Returns
+    -------
+    int
+        The index of the closest color in `palette`. The first of equally close colors is chosen.
+
+    Raises
+    ------
+    ValueError
+        The palette is empty, or the metric is invalid.
+    """
+
+def pack_atlas(images: List[Image], *, max_width: int, padding: int = 0, power_of_two: bool = False) -> Tuple[Image, List[Tuple[int, int, int, int]]]:
+    """
+    Packs the given images into a single atlas image, such as a texture atlas of sprites.
+
+    Images are placed tallest first on shelves that are filled from left to right, and a new shelf is started
+    below once an image doesn't fit into the width of the current one. Equally tall images keep their order,
+    so the same images are always packed the same way.
+
+    The atlas has the richest mode of the images, and the space not covered by any image is transparent.
+    Images are copied into the atlas as is, converted to its mode, without blending.
+
+    Parameters
+    ----------
+    images: List[:class:`.Image`]
+        The images to pack, there must be at least one.
+    max_width: int
+        The maximum width of the atlas.
+    padding: int, default: 0
+        The amount of pixels left empty between neighboring images.
+    power_of_two: bool, default: False
+        Whether to round the width and height of the atlas up to powers of two, such as for older GPUs.
+        Images are then packed into the largest power of two not exceeding `max_width`.
+
+    Returns
+    -------
+    Tuple[:class:`.Image`, List[Tuple[int, int, int, int]]]
+        The atlas, and the bounding box ``(x1, y1, x2, y2)`` each image was placed at, in the order of `images`.
+
+    Raises
+    ------
+    ValueError
+        No images are given, or an image is wider than the atlas can be. The index of that image is named.
+
+    Examples
+    --------
+
+    .. code-block:: python3
+
+        atlas, rects = ril.pack_atlas(sprites, max_width=1024, padding=2)
+    """
+
+def thumbnail(data: bytes, max_size: int, format: str = 'jpeg', quality: int = 80) -> bytes:
+    """
+    Decodes an image, shrinks it to fit within a square of `max_size` pixels, and encodes it again,
+    such as for serving thumbnails.
+
+    This runs entirely without the GIL and without creating an intermediate :class:`.Image`.
+    JPEG images are decoded at a reduced scale of up to 1/8 where possible, so large photos are never fully decompressed.
+    The image keeps its aspect ratio and is resized with :attr:`.ResizeAlgorithm.Lanczos3`.
+    Images already fitting within `max_size` are not enlarged, and metadata is not carried over.
+
+    Parameters
+    ----------
+    data: bytes
+        The encoded image, its format is inferred.
+    max_size: int
+        The maximum width and height of the thumbnail.
+    format: str, default: 'jpeg'
+        The encoding of the thumbnail.
+    quality: int, default: 80
+        The quality of JPEG and WebP thumbnails, from 1 (smallest) to 100 (best). Other encodings ignore it.
+
+    Returns
+    -------
+    bytes
+        The encoded thumbnail.
+
+    Raises
+    ------
+    ValueError
+        `max_size` is zero, the quality is not from 1 to 100, or the encoding is invalid.
+    UnknownFormatError
+        The format of `data` can't be inferred.
+    RuntimeError
+        Failed to decode or encode the image.
+
+    Examples
+    --------
+
+    .. code-block:: python3
+
+        small = ril.thumbnail(request.body, 256, format='webp', quality=75)
+    """
 
 class Image:
     """
     A high-level image representation.
     
     This represents a static, single-frame image. See :class:`.ImageSequence` for information on opening animated or multi-frame images.
+
+    Images always have a non-zero width and height. Constructors and methods raise `ValueError` instead of creating an empty image.
+
+    Images can be made read-only with :meth:`freeze`, after which modifying them in place raises a `RuntimeError`.
+    Use :meth:`close`, or the image as a context manager, to release its pixels without waiting for garbage collection.
     """
     @classmethod
-    def new(cls: Type[Image], width: int, height: int, fill: Pixel) -> Image:
+    def new(cls: Type[Image], width: int, height: int, fill: Pixel, mode: Optional[Union[Mode, str]] = None) -> Image:
         """
         Creates a new image with the given width and height, with all pixels being set intially to `fill`.
         
@@ -25,6 +248,13 @@ class Image:
             The height of the Image.
         fill: :class:`.Pixel`
             The pixel used to fill the image.
+        mode: Optional[Union[:class:`.Mode`, str]], default: None
+            The mode of the image, `fill` is converted to this mode. Defaults to the mode of `fill`.
+
+        Raises
+        ------
+        ValueError
+            `width` or `height` is zero.
         
         Examples
         --------
@@ -35,25 +265,80 @@ class Image:
         """
 
     @classmethod
-    def from_bytes(cls: Type[Image], bytes: bytes, format: str | None = None) -> Image:
+    def from_bytes(
+        cls: Type[Image],
+        bytes: bytes,
+        format: Optional[Union[ImageFormat, str]] = None,
+        *,
+        tolerant: bool = False,
+        fill: Optional[Pixel] = None,
+    ) -> Image:
         """
         Decodes an image with the explicitly given image encoding from the raw bytes.
        
         if `format` is not provided then it will try to infer its encoding.
+        Animated GIFs decode to their first frame, use :class:`.ImageSequence` to decode every frame.
         
         Parameters
         ----------
         bytes: bytes
             The bytes of the Image.
-        format: Optional[str], default: None
+        format: Optional[Union[:class:`.ImageFormat`, str]], default: None
             The format of the image, defaults to `None`.
+        tolerant: bool, default: False
+            Whether to decode as much as possible of data that is cut off, such as a half-downloaded JPEG,
+            instead of raising. What could be recovered is described by :attr:`decode_warnings`.
+            Only JPEG data can be recovered, other data that fails to decode still raises.
+        fill: Optional[:class:`.Pixel`], default: None
+            Only used with `tolerant`. The color to fill the rows that couldn't be decoded with, defaults to black.
+            Progressive JPEG data is never filled, as every scan covers the whole image.
         
         Raises
         ------
         ValueError
             Raised if the format provided is invalid.
+        UnknownFormatError
+            Raised if `format` is not provided and it can't be inferred from the bytes.
+        RuntimeError
+            Raised if the image can't be decoded, or can't be recovered with `tolerant` set.
+
+        .. note::
+            The GIL is released while decoding.
+
+        Examples
+        --------
+
+        .. code-block:: python3
+
+            image = Image.from_bytes(partial, tolerant=True, fill=Pixel.from_rgb(128, 128, 128))
+            if image.decode_warnings:
+                print('Recovered a partial image:', *image.decode_warnings)
+        """
+
+    @classmethod
+    def peek_dimensions(cls, bytes: bytes) -> Tuple[int, int]:
+        """
+        Reads the dimensions of an encoded image from its header, without decoding it or allocating its pixels.
+
+        This is useful for cheaply rejecting oversized images before decoding them with :meth:`from_bytes`.
+        PNG, JPEG, GIF, WebP and BMP headers are supported.
+
+        Parameters
+        ----------
+        bytes: bytes
+            The bytes of the image, only the header is read.
+
+        Returns
+        -------
+        Tuple[int, int]
+            The width and height of the image.
+
+        Raises
+        ------
+        UnknownFormatError
+            The format can't be inferred from the bytes.
         RuntimeError
-            Raised if the image can't be decoded or the format is unknown.
+            The header is truncated or malformed, or the format is not supported.
         """
 
     @classmethod
@@ -62,681 +347,3974 @@ class Image:
         Creates a new image shaped with the given width
         and a 1-dimensional sequence of pixels which will be shaped according to the width.
         
+        Pixels of different modes are converted to the widest mode among them, ordered `BITPIXEL`, `L`, `RGB`
+        and `RGBA`, so that every pixel of the image has the same mode.
+
         Parameters
         ----------
         width: int
             The width of the image.
         pixels: List[:class:`.Pixel`]
             A List of pixels.
+
+        Raises
+        ------
+        ValueError
+            `width` is zero, `pixels` is empty, or the amount of pixels is not a multiple of `width`.
         """
 
     @classmethod
-    def open(cls: Type[Image], path: str) -> Image:
+    def from_packed_bits(cls: Type[Image], width: int, height: int, data: bytes, row_align: int = 1) -> Image:
         """
-        Opens a file from the given path and decodes it into an image.
-       
-        The encoding of the image is automatically inferred.
-        You can explicitly pass in an encoding by using the :meth:`from_bytes` method.
-        
+        Creates a new `BITPIXEL` image from packed bits, most significant bit first, where a set bit is an on pixel.
+
+        Each row starts on a new byte and is padded to a multiple of `row_align` bytes.
+
         Parameters
         ----------
-        path: str
-            The path to the image.
-        
+        width: int
+            The width of the image.
+        height: int
+            The height of the image.
+        data: bytes
+            The packed bits.
+        row_align: int, default: 1
+            The byte alignment of each row.
+
         Raises
         ------
         ValueError
-            The file extension is invalid.
-        RuntimeError
-            Failed to infer file format or Failed to decode image.
+            `width`, `height` or `row_align` is zero, or the length of `data` doesn't match the dimensions.
         """
 
-    @property
-    def overlay_mode(self) -> OverlayMode:
-        """:class:`.OverlayMode`: Returns the overlay mode of the image."""
-
-    @property
-    def mode(self) -> str:
-        """str: Returns the mode of the image."""
+    def to_packed_bits(self, row_align: int = 1) -> bytes:
+        """
+        Packs this `BITPIXEL` image into bits, most significant bit first, where a set bit is an on pixel.
 
-    @property
-    def width(self) -> int:
-        """int: Returns the width of the image."""
+        Each row starts on a new byte and is padded with zeros to a multiple of `row_align` bytes.
 
-    @property
-    def height(self) -> int:
-        """int: Returns the height of the image."""
+        Parameters
+        ----------
+        row_align: int, default: 1
+            The byte alignment of each row.
 
-    def bands(self) -> Pixels:
-        """
-        Return the bands of the image.
-        
         Returns
         -------
-        Tuple[:class:`.L`, ...]
-        
+        bytes
+            The packed bits.
+
         Raises
         ------
         TypeError
-            The image is not of mode `RGB` or `RGBA`.
+            The image is not of mode `BITPIXEL`.
+        ValueError
+            `row_align` is zero.
         """
 
     @classmethod
-    def from_bands(cls: Type[Image], *bands: Union[Tuple[Rgb, ...], Tuple[Rgba, ...]]) -> Image:
-        """
-        Creates a new image from the given bands.
-        
-        Parameters
-        ----------
-        bands: \\* :class:`.L`
-            The bands of the image.
+    def from_buffer(cls: Type[Image], width: int, height: int, mode: Union[Mode, str], data: Any) -> Image:
         """
+        Creates a new image from raw pixel data, such as a `numpy` array or the output of :meth:`to_bytes`.
 
-    def crop(self, x1: int, y1: int, x2: int, y2: int) -> None:
-        """
-        Crops this image in place to the given bounding box.
-        
-        Parameters
-        ----------
-        x1: int
-           The x axis of the upper-left corner
-        y1: int
-            The y axis of the upper-left corner
-        x2: int
-            The x axis of the lower-right corner
-        y2: int
-            The y axis of the lower-right corner
-        """
+        The data holds the channels of every pixel interleaved, row by row, where each pixel takes up as many bytes
+        as it has channels in the given mode. A `BITPIXEL` byte above ``127`` is an on pixel.
 
-    def draw(self, entity: Union[Rectangle, Ellipse]) -> None:
-        """
-        Draws an object or shape onto this image.
-        
         Parameters
         ----------
-        entity: Union[:class:`.Rectangle`, :class:`.Ellipse`]
-            The entity to draw on the image.
+        width: int
+            The width of the image.
+        height: int
+            The height of the image.
+        mode: Union[:class:`.Mode`, str]
+            The mode of the pixel data.
+        data: Union[bytes, bytearray, memoryview]
+            Any object supporting the buffer protocol with items of a single byte, such as a `numpy` array of dtype
+            `uint8`. Non-contiguous buffers are copied into a contiguous one first.
+
+        Raises
+        ------
+        ValueError
+            `width` or `height` is zero, or the length of `data` doesn't match the dimensions and mode.
+        BufferError
+            `data` doesn't support the buffer protocol with single bytes as items.
+
+        Examples
+        --------
+
+        .. code-block:: python3
+
+            array = np.frombuffer(image.to_bytes(), dtype=np.uint8).reshape(image.height, image.width, 4)
+            image = Image.from_buffer(image.width, image.height, 'RGBA', 255 - array)
         """
 
-    def resize(self, width: int, height: int, algorithm: ResizeAlgorithm) -> None:
+    def to_bytes(self) -> bytes:
         """
-        Resizes this image in place to the given dimensions using the given resizing algorithm in place.
-        
-        Parameters
-        ----------
-        width: int
-            The target width to resize to
-        height: int
-            The target height to resize to
-        algorithm: :class:`.ResizeAlgorithm`
-            The resize algorithm to use
+        Returns the raw pixel data of this image as a single contiguous buffer, row by row.
+
+        Each pixel takes up as many bytes as it has channels in the mode of this image,
+        where a :class:`.BitPixel` is either ``0`` or ``255``. See :meth:`from_buffer` to create an image from it.
+
+        Returns
+        -------
+        bytes
+            The raw pixel data.
+
+        Examples
+        --------
+
+        .. code-block:: python3
+
+            array = np.frombuffer(image.to_bytes(), dtype=np.uint8).reshape(image.height, image.width, 4)
         """
 
-    def encode(self, encoding: str) -> bytes:
+    @classmethod
+    def from_pillow(cls: Type[Image], image: Any) -> Image:
         """
-        Encodes the image with the given encoding and returns `bytes`.
-        
+        Creates a new image from a Pillow image, copying its raw pixel data without encoding it.
+
+        Pillow images of mode `1`, `L`, `RGB` and `RGBA` are supported, where `1` becomes a `BITPIXEL` image.
+        Images of any other mode, such as `P` or `CMYK`, must be converted first, such as with ``image.convert('RGBA')``.
+
+        Pillow is imported when this is called, so it is only needed when converting.
+
         Parameters
         ----------
-        encoding: str
-            The encoding of the image.
-        
+        image: PIL.Image.Image
+            The Pillow image.
+
         Returns
         -------
-        bytes
-            The encoded bytes of the image.
-        
+        :class:`.Image`
+            The new image.
+
         Raises
         ------
         ValueError
-            The encoding is invalid.
-        RuntimeError
-            Failed to encode the image.
-        """
+            The mode of the Pillow image is not supported.
 
-    def save(self, path: str, encoding: Optional[str] = None) -> None:
-        """
-        Saves the image to the given path.
-        If encoding is not provided, it will attempt to infer it by the path/filename's extension
-        You can try saving to a memory buffer by using the :meth:`encode` method.
+        Examples
+        --------
+
+        .. code-block:: python3
+
+            image = Image.from_pillow(PIL.Image.open('photo.png').convert('RGB'))
+        """
+
+    def to_pillow(self) -> Any:
+        """
+        Converts this image into a Pillow image, copying its raw pixel data without encoding it.
+
+        `BITPIXEL` images become Pillow images of mode `1`, and images of any other mode keep their mode.
+        Pillow is imported when this is called, so it is only needed when converting.
+
+        Returns
+        -------
+        PIL.Image.Image
+            The new Pillow image.
+
+        Raises
+        ------
+        ImportError
+            Pillow is not installed.
+        """
+
+    @classmethod
+    def open(cls: Type[Image], path: str) -> Image:
+        """
+        Opens a file from the given path and decodes it into an image.
+       
+        The encoding of the image is automatically inferred.
+        You can explicitly pass in an encoding by using the :meth:`from_bytes` method.
+        Animated GIFs decode to their first frame, use :class:`.ImageSequence` to decode every frame.
         
         Parameters
         ----------
         path: str
-            The path to save the image to.
-        encoding: Optional[str], default: None
-            The encoding of the image, defaults to `None`.
+            The path to the image.
         
         Raises
         ------
         ValueError
-            The encoding provided is invalid.
+            The file extension is invalid.
         RuntimeError
-            Failed to encode the image or Failed to infer the image format.
+            Failed to infer file format or Failed to decode image.
+
+        .. note::
+            The GIL is released while reading and decoding the file.
         """
 
-    def pixels(self) -> List[List[Pixels]]:
+    @classmethod
+    def open_async(cls, path: str) -> Awaitable[Image]:
         """
-        Returns a 2D list representing the pixels of the image. Each list in the list is a row.
-       
-        For example:
-       
-        [[Pixel, Pixel, Pixel], [Pixel, Pixel, Pixel]]
-       
-        where the width of the inner list is determined by the width of the image.
-       
-        .. warning:: **This function involves heavy operation**
-       
-            This function requires multiple iterations, so it is a heavy operation for larger image.
-        
+        Like :meth:`open`, but reads and decodes the file on a separate thread without blocking the event loop.
+
+        This must be called from a running :mod:`asyncio` event loop.
+
+        Parameters
+        ----------
+        path: str
+            The path to the image.
+
         Returns
         -------
-        List[List[Union[:class:`.BitPixel`, :class:`.L`, :class:`.Rgb`, :class:`.Rgba`]]]
-            The pixels of the image.
+        Awaitable[:class:`.Image`]
+            A future resolving to the decoded image, raising the same errors as :meth:`open`.
+
+        Raises
+        ------
+        RuntimeError
+            There is no running event loop.
+
+        Examples
+        --------
+
+        .. code-block:: python3
+
+            image = await Image.open_async('sample.png')
+        """
+
+    @property
+    def overlay_mode(self) -> OverlayMode:
+        """:class:`.OverlayMode`: Returns the overlay mode of the image."""
+
+    @property
+    def mode(self) -> str:
         """
+        str: Returns the mode of the image, one of `BITPIXEL`, `L`, `RGB` or `RGBA`.
 
-    def paste(self, x: int, y: int, image: Image, mask: Optional[Image]) -> None:
+        .. note::
+            The mode is always uppercase, use :attr:`mode_enum` to avoid string comparisons.
         """
-        Pastes the given image onto this image at the given x and y axiss.
+
+    @property
+    def mode_enum(self) -> Mode:
+        """:class:`.Mode`: Returns the mode of the image."""
+
+    @property
+    def width(self) -> int:
+        """int: Returns the width of the image."""
+
+    @property
+    def height(self) -> int:
+        """int: Returns the height of the image."""
+
+    def bands(self) -> Pixels:
+        """
+        Return the bands of the image.
         
-        If `maske` is provided it will be masked with the given masking image.
+        Returns
+        -------
+        Tuple[:class:`.L`, ...]
         
-        Currently, only BitPixel images are supported for the masking image.
+        Raises
+        ------
+        TypeError
+            The image is not of mode `RGB` or `RGBA`.
+        """
+
+    @classmethod
+    def from_bands(cls: Type[Image], *bands: Union[Tuple[Rgb, ...], Tuple[Rgba, ...]]) -> Image:
+        """
+        Creates a new image from the given bands.
         
         Parameters
         ----------
-        x: int
-            The x axis
-        y: int
-            The y axis
-        image: :class:`Image`
-            The image to paste.
-        mask: Optional[:class:`Image`], default: None
-            The mask to use, defaults to `None`
+        bands: \\* :class:`.L`
+            The bands of the image.
+        """
+
+    def crop(self, x1: int, y1: int, x2: int, y2: int) -> None:
+        """
+        Crops this image in place to the given bounding box.
         
+        Parameters
+        ----------
+        x1: int
+           The x axis of the upper-left corner
+        y1: int
+            The y axis of the upper-left corner
+        x2: int
+            The x axis of the lower-right corner
+        y2: int
+            The y axis of the lower-right corner
+
         Raises
         ------
         ValueError
-            The mask provided is not of mode `BitPixel`
+            The bounding box is empty or exceeds the bounds of the image.
         """
 
-    def mask_alpha(self, mask: Image) -> None:
+    def add_border(self, thickness: Union[int, Tuple[int, int, int, int]], color: Pixel) -> None:
         """
-        Masks the alpha values of this image with the luminance values of the given single-channel L image.
-       
-        If you want to mask using the alpha values of the image instead of providing an L image, you can split the bands of the image and extract the alpha band.
-       
-        This masking image must have the same dimensions as this image.
-        
+        Expands the canvas of this image in place by the given thickness on each side,
+        filling the new margin with `color` and keeping the original content in the middle.
+
         Parameters
         ----------
-        mask: :class:`Image`
-            The mask to use
-        
+        thickness: Union[int, Tuple[int, int, int, int]]
+            The thickness of the border in pixels, either for every side or per side as ``(left, top, right, bottom)``.
+        color: :class:`.Pixel`
+            The color of the border, converted to the mode of this image.
+
         Raises
         ------
         ValueError
-            The mask provided is not of mode `L`
-        """
+            A thickness is negative, or the enlarged image would be too large.
 
-    def mirror(self) -> None:
-        """Mirrors, or flips this image horizontally (about the y-axis) in place."""
+        Examples
+        --------
 
-    def flip(self) -> None:
-        """Flips this image vertically (about the x-axis) in place."""
+        .. code-block:: python3
 
-    @property
-    def format(self) -> str:
+            image.add_border(8, Pixel.from_rgb(255, 255, 255))
+            image.add_border((0, 4, 0, 4), Pixel.from_rgb(0, 0, 0))
         """
-        str: Returns the encoding format of the image.
-        
-        .. note::
-            This is nothing more but metadata about the image.
-            When saving the image, you will still have to explicitly specify the encoding format.
+
+    def copy(self) -> Image:
         """
+        Returns a copy of this image.
 
-    @property
-    def dimensions(self) -> Tuple[int, int]:
-        """Tuple[int, int]: Returns the dimensions of the image."""
+        Copies share their pixels until either of them is modified, so copying is cheap until then.
+        Copies of a frozen image are never frozen, see :meth:`freeze`, and copies start without draw transforms,
+        see :meth:`push_transform`.
+        """
 
-    def get_pixel(self, x: int, y: int) -> Pixels:
+    def __copy__(self) -> Image:
+        ...
+
+    def pipeline(self) -> Pipeline:
         """
-        Returns the pixel at the given coordinates.
-        
-        Parameters
-        ----------
-        x: int
-            The x axis
-        y: int
-            The y axis
-        
+        Starts a lazy pipeline of operations on this image, which are only run once it is executed.
+
+        The pipeline rearranges the recorded operations to do less work, such as cropping before resizing,
+        while giving exactly the same pixels as calling the methods of this image one by one.
+        This image is left untouched, see :class:`.Pipeline`.
+
         Returns
         -------
-        Union[:class:`.BitPixel`, :class:`.L`, :class:`.Rgb`, :class:`.Rgba`]
-            The pixel of that specific coordinate.
+        :class:`.Pipeline`
+            An empty pipeline on the current pixels of this image.
         """
 
-    def set_pixel(self, x: int, y: int, pixel: Pixel) -> None:
-        """
-        Sets the pixel at the given coordinates to the given pixel.
-        
-        Parameters
-        ---------
-        x: int
-            The x axis
-        y: int
-            The y axis
-        pixel: :class:`.Pixel`
-            The pixel to set it to
+    def freeze(self) -> None:
         """
+        Marks this image as read-only, such as an image that is cached and shared.
 
-    def invert(self) -> None:
-        """Inverts the image in-place."""
+        Every method that modifies the image in place, such as :meth:`resize`, :meth:`crop`, :meth:`draw`,
+        :meth:`paste` and :meth:`set_pixel`, raises a RuntimeError while the image is frozen, and so does :meth:`close`.
+        Use :meth:`copy` to get a mutable copy, or :meth:`thaw` to make the image mutable again.
+        """
 
+    def thaw(self) -> None:
+        """Makes a frozen image mutable again, see :meth:`freeze`."""
 
-class Border:
-    """
-    Represents a shape border.
-    """
-    color: Pixel
-    thickness: int
-    position: str
+    @property
+    def frozen(self) -> bool:
+        """bool: Whether the image is read-only, see :meth:`freeze`."""
 
-    def __init__(self, color: Pixel, thickness: int, position: str) -> None:
-        """
-    Parameters
-    ----------
-    color: :class:`.Pixel`
-        The color of the border
-    thickness: int
-        The thickness of the border
-    position: str
-        The position of the border
-    
-    Raises
-    ------
-    ValueError
-        The position is not one of `inset`, `center`, or `outset`
+    def close(self) -> None:
         """
+        Releases the pixels of this image right away instead of when it is garbage collected,
+        which keeps the memory use of batch jobs predictable.
 
+        Every other method and attribute of a closed image, except :attr:`closed`, raises a `ValueError`.
+        Copies of the image are unaffected, and closing an image that is already closed does nothing.
 
-class Ellipse:
-    """
-    An ellipse, which could be a circle.
-    
-    .. warning::
-        Using any of the predefined constructors will automatically set the position to (0, 0) and you must explicitly set the size of the ellipse with `.size` in order to set a size for the ellipse. 
-        A size must be set before drawing.
-    
-        This also does not set any border or fill for the ellipse, you must explicitly set either one of them.
-    """
-    position: Xy
-    radii: Xy
-    border: Optional[Border]
-    fill: Optional[Pixel]
-    overlay: Optional[OverlayMode]
+        Images can also be used as context managers, which close them on exit.
 
-    def __init__(
-        self,
-        position: Xy,
-        radii: Xy,
-        border: Optional[Border] = None,
-        fill: Optional[Pixel] = None,
-        overlay: Optional[str] = None
-    ) -> None:
-        """
-        Parameters
-        ---------
-        position: Tuple[int, int]
-            The position of the ellipse
-        radii: Tuple[int, int]
-            The radii of the ellipse
-        border: Optional[:class:`.Border`]
-            The border of the ellipse.
-        fill: Optional[:class:`.Pixel`]
-            The color to use for filling the ellipse
-        overlay: Optional[:class:`.OverlayMode`]
-            The overlay mode of the ellipse.
+        Raises
+        ------
+        RuntimeError
+            The image is frozen, see :meth:`freeze`.
+
+        Examples
+        --------
+
+        .. code-block:: python3
+
+            with Image.open('large.png') as image:
+                image.resize(256, 256, ResizeAlgorithm.Lanczos3)
+                image.save('small.png')
         """
 
-    @classmethod
-    def from_bounding_box(cls, x1: int, y1: int, x2: int, y2: int) -> Ellipse:
+    @property
+    def closed(self) -> bool:
+        """bool: Whether the image is closed, see :meth:`close`."""
+
+    def __enter__(self) -> Image: ...
+
+    def __exit__(self, exc_type: Optional[type], exc_value: Optional[BaseException], traceback: Optional[object]) -> None: ...
+
+    def view(self, x1: int, y1: int, x2: int, y2: int) -> ImageView:
         """
-        Creates a new ellipse from the given bounding box.
-        
+        Returns a read-only view of the given bounding box of this image, without copying it.
+
+        The view reads the current data of this image, so changes made to this image afterwards are visible through it.
+        Use :meth:`ImageView.to_image` to get a copy of the region instead.
+
         Parameters
         ----------
         x1: int
-            The x axis of the upper left corner
+            The x axis of the upper-left corner
         y1: int
-            The y axis of the upper left corner
+            The y axis of the upper-left corner
         x2: int
-            The x axis of the lower right corner
+            The x axis of the lower-right corner
         y2: int
-            The y axis of the lower right corner
-        
+            The y axis of the lower-right corner
+
         Returns
         -------
-        :class:`.Ellipse`
-            The newly created ellipse
+        :class:`.ImageView`
+            The view of the region.
+
+        Raises
+        ------
+        ValueError
+            The bounding box is empty or exceeds the bounds of the image.
         """
 
-    @classmethod
-    def circle(cls, x: int, y: int, radius: int) -> Ellipse:
+    def push_transform(self, translate: Tuple[int, int] = (0, 0), scale: float = 1.0) -> None:
         """
-        Creates a new circle with the given center position and radius.
+        Pushes a transform that moves and scales the coordinates of everything drawn onto this image afterwards,
+        until it is popped with :meth:`pop_transform`.
+
+        Transforms apply to :meth:`draw`, including text, to :meth:`draw_hline` and :meth:`draw_vline`, and to the position
+        of :meth:`paste`. A point ``(x, y)`` is drawn at ``(x * scale + dx, y * scale + dy)``. Nested transforms compose,
+        so the translation of a nested transform is scaled by the transforms around it.
+
+        Sizes are scaled as well, such as the size of rectangles, radii, border thicknesses, shadows and font sizes.
+        Coordinates and sizes are rounded to the nearest pixel, and sizes that aren't zero stay at least one pixel.
+        Pasted images keep their size. Text layouts are laid out as their text is added, so they can only be translated.
+
+        Parameters
+        ----------
+        translate: Tuple[int, int], default: (0, 0)
+            How far to move everything drawn, in the coordinates of the enclosing transform.
+        scale: float, default: 1.0
+            How much to scale everything drawn, which must be positive.
+
+        Raises
+        ------
+        ValueError
+            `scale` is not a positive number.
+
+        Examples
+        --------
+
+        .. code-block:: python3
+
+            image.push_transform(translate=(100, 50), scale=2.0)
+            image.draw(Rectangle(position=(0, 0), size=(10, 10), fill=Pixel.from_rgb(255, 0, 0)))  # Covers (100, 50) to (120, 70)
+            image.pop_transform()
+        """
+
+    def pop_transform(self) -> None:
+        """
+        Pops the transform last pushed with :meth:`push_transform`, restoring the transform around it.
+
+        Raises
+        ------
+        IndexError
+            No transform was pushed.
+        """
+
+    def draw(
+        self,
+        entity: Union[
+            Rectangle,
+            Ellipse,
+            TextSegment,
+            TextLayout,
+            Scene,
+            List[Union[Rectangle, Ellipse, Line, Polygon, TextSegment, TextLayout, Scene]],
+            Tuple[Union[Rectangle, Ellipse, Line, Polygon, TextSegment, TextLayout, Scene], ...],
+        ],
+        strict_mode: bool = False,
+    ) -> None:
+        """
+        Draws an object or shape onto this image.
+
+        Colors of a different mode than this image are converted to its mode first, see :meth:`paste` for the conversion rules.
+
+        A list or tuple of entities is drawn in order in a single call, which is much faster than drawing them one by one,
+        such as for the modules of a QR code or the cells of a heatmap.
         
         Parameters
         ----------
-        x: int
-            The x axis
+        entity: Union[:class:`.Rectangle`, :class:`.Ellipse`, :class:`.Line`, :class:`.Polygon`, :class:`.TextSegment`, :class:`.TextLayout`, :class:`.Scene`, List, Tuple]
+            The entity to draw on the image, or a list or tuple of entities.
+        strict_mode: bool, default: False
+            Whether to raise instead of converting colors of a different mode.
+
+        Raises
+        ------
+        TypeError
+            `strict_mode` is enabled and a color of the entity is not of the mode of this image.
+        RuntimeError
+            The entity, or any entity of the list or tuple, can't be drawn. Nothing is drawn then.
+
+        Examples
+        --------
+
+        .. code-block:: python3
+
+            image.draw([Rectangle(position=(x * 4, y * 4), size=(4, 4), fill=black) for x, y in modules])
+        """
+
+    def draw_hline(self, y: int, x1: int, x2: int, color: Pixel, thickness: int = 1) -> None:
+        """
+        Draws a horizontal line from `x1` to `x2`, both inclusive, at the given y axis.
+
+        This writes the pixels of the line directly instead of going through :meth:`draw`, which makes it much faster
+        for drawing many thin lines such as the bars of a chart. Whatever falls outside of this image is clipped.
+
+        A color of a different mode than this image is converted to its mode first, see :meth:`paste` for the conversion rules.
+        A translucent RGBA color is blended with the pixels it covers when the overlay mode is :attr:`.OverlayMode.Merge`.
+
+        Parameters
+        ----------
         y: int
-            The y axis
-        radius: int
-            The radius
+            The y axis of the line.
+        x1: int
+            The x axis the line starts at.
+        x2: int
+            The x axis the line ends at.
+        color: :class:`.Pixel`
+            The color of the line.
+        thickness: int, default: 1
+            The thickness of the line in pixels. Thicker lines are centered on `y`, with the extra row of an even thickness below it.
+
+        Raises
+        ------
+        ValueError
+            `thickness` is zero.
         """
 
+    def draw_vline(self, x: int, y1: int, y2: int, color: Pixel, thickness: int = 1) -> None:
+        """
+        Draws a vertical line from `y1` to `y2`, both inclusive, at the given x axis.
 
-class Rectangle:
-    """
-    A rectangle.
-    
-    .. warning::
-        Using any of the predefined construction methods will automatically set the position to (0, 0). 
-        If you want to specify a different position, you must set the position with `.position`
-    
-        You must specify a width and height for the rectangle with something such as with_size. 
-        If you don't, a panic will be raised during drawing. 
-        You can also try using from_bounding_box to create a rectangle from a bounding box, which automatically fills in the size.
-    
-        Additionally, a panic will be raised during drawing if you do not specify either a fill color or a border.
-        these can be set with `.fill` and `.border` respectively.
-    """
-    position: Xy
-    size: Xy
-    border: Optional[Border]
-    fill: Optional[Pixel]
-    overlay: Optional[OverlayMode]
+        This is the vertical counterpart of :meth:`draw_hline`, see it for how lines are clipped and colors are applied.
+
+        Parameters
+        ----------
+        x: int
+            The x axis of the line.
+        y1: int
+            The y axis the line starts at.
+        y2: int
+            The y axis the line ends at.
+        color: :class:`.Pixel`
+            The color of the line.
+        thickness: int, default: 1
+            The thickness of the line in pixels. Thicker lines are centered on `x`, with the extra column of an even thickness to its right.
+
+        Raises
+        ------
+        ValueError
+            `thickness` is zero.
+        """
+
+    def resize(
+        self,
+        width: int,
+        height: int,
+        algorithm: ResizeAlgorithm,
+        linear: bool = False,
+        preserve_aspect: bool = False,
+        exact: bool = True,
+        tolerance: float = 0.01,
+    ) -> None:
+        """
+        Resizes this image in place to the given dimensions using the given resizing algorithm in place.
+        
+        Parameters
+        ----------
+        width: int
+            The target width to resize to
+        height: int
+            The target height to resize to
+        algorithm: :class:`.ResizeAlgorithm`
+            The resize algorithm to use
+        linear: bool, default: False
+            Whether to resample an sRGB image in linear light, see :meth:`to_linear`, which avoids darkened edges
+            when downscaling. Channels are resampled in floating point, so shadows aren't posterized on the way.
+            Images already in the linear color space are always resampled as is.
+        preserve_aspect: bool, default: False
+            Whether to guard against distorting the image. When set, the aspect ratio of the target dimensions
+            may differ from the aspect ratio of this image by at most `tolerance`.
+        exact: bool, default: True
+            Only used with `preserve_aspect`. When True, dimensions that distort the image raise a ValueError.
+            When False, the dimension that constrains the image less is shrunk to keep the aspect ratio instead,
+            so the image fits within the target dimensions.
+        tolerance: float, default: 0.01
+            Only used with `preserve_aspect`. How far the target aspect ratio may differ from the aspect ratio
+            of this image, relative to it. The default allows a change of 1%, which covers rounding to whole pixels.
+
+        Raises
+        ------
+        ValueError
+            `width` or `height` is zero, `tolerance` is negative, or `preserve_aspect` and `exact` are set
+            and the target dimensions change the aspect ratio by more than `tolerance`.
+
+        .. note::
+            The GIL is released while resampling.
+        """
+
+    def resize_region(
+        self, src_box: Tuple[int, int, int, int], width: int, height: int, algorithm: ResizeAlgorithm
+    ) -> Image:
+        """
+        Resizes the given region of this image to the given dimensions, returning a new image.
+
+        This gives the same result as cropping a copy of this image and resizing it,
+        but the region is resampled where it is rather than copied first.
+
+        Parameters
+        ----------
+        src_box: Tuple[int, int, int, int]
+            The region to resize, as the upper-left and lower-right corners ``(x1, y1, x2, y2)``, see :meth:`crop`.
+        width: int
+            The target width to resize to
+        height: int
+            The target height to resize to
+        algorithm: :class:`.ResizeAlgorithm`
+            The resize algorithm to use
+
+        Returns
+        -------
+        :class:`.Image`
+            The resized region.
+
+        Raises
+        ------
+        ValueError
+            The region is empty or exceeds the bounds of the image, or `width` or `height` is zero.
+        """
+
+    def encode(self, encoding: Optional[str] = None, strip_metadata: bool = True, options: Optional[EncodeOptions] = None, deterministic: bool = False) -> bytes:
+        """
+        Encodes the image with the given encoding and returns `bytes`.
+        
+        Parameters
+        ----------
+        encoding: Optional[str], default: None
+            The encoding of the image, defaults to the format of `options`.
+        strip_metadata: bool, default: True
+            Whether to leave out the metadata (EXIF, ICC profiles, text chunks, comments) carried over from the decoded image.
+            Metadata is only ever written back to the format it was read from.
+            Kept EXIF data is updated to the current dimensions of the image, and its orientation is reset once
+            the image is rotated, mirrored or flipped.
+        options: Optional[Union[:class:`.PngOptions`, :class:`.JpegOptions`, :class:`.GifOptions`, :class:`.WebPOptions`]], default: None
+            The options to encode with, defaults to the encoder defaults of `encoding`.
+        deterministic: bool, default: False
+            Whether to pin the encoder settings so the output is byte-for-byte identical for the same image,
+            only supported for PNG and GIF. Without options, :meth:`PngOptions.deterministic` or
+            :meth:`GifOptions.deterministic` is used, and `tIME` chunks are left out of any kept metadata.
+            The output is only guaranteed to be stable for a given version of ril-py.
+        
+        Returns
+        -------
+        bytes
+            The encoded bytes of the image.
+        
+        Raises
+        ------
+        ValueError
+            The encoding is invalid, neither an encoding nor options are given, or they are for different formats.
+            This is also raised if `deterministic` is set for an encoding other than PNG or GIF,
+            or with :class:`.PngOptions` using the `adaptive` filter.
+        RuntimeError
+            Failed to encode the image.
+
+        .. note::
+            The GIL is released while encoding.
+
+        Examples
+        --------
+
+        .. code-block:: python3
+
+            data = image.encode(options=JpegOptions(quality=80, progressive=True))
+        """
+
+    def encode_into(self, encoding: str, buffer: Union[bytearray, memoryview], strip_metadata: bool = True) -> int:
+        """
+        Encodes the image with the given encoding into the given buffer, such as a reused `bytearray`,
+        and returns the amount of bytes written. The rest of the buffer is left untouched.
+
+        Parameters
+        ----------
+        encoding: str
+            The encoding of the image.
+        buffer: Union[bytearray, memoryview]
+            A writable, contiguous buffer of bytes to encode into.
+        strip_metadata: bool, default: True
+            Whether to leave out the metadata carried over from the decoded image, see :meth:`encode`.
+
+        Returns
+        -------
+        int
+            The amount of bytes written.
+
+        Raises
+        ------
+        ValueError
+            The encoding is invalid.
+        BufferError
+            The buffer is not writable or not contiguous.
+        BufferTooSmallError
+            The encoded image does not fit into the buffer, the size it needs is stored in its `required_size` attribute.
+        RuntimeError
+            Failed to encode the image.
+
+        Examples
+        --------
+
+        .. code-block:: python3
+
+            buffer = bytearray(64 * 1024)
+
+            try:
+                size = image.encode_into('png', buffer)
+            except BufferTooSmallError as e:
+                buffer = bytearray(e.required_size)
+                size = image.encode_into('png', buffer)
+
+            data = memoryview(buffer)[:size]
+        """
+
+    def encode_optimized(self, format: Union[ImageFormat, str], target_kb: Optional[int] = None) -> Tuple[bytes, Optional[int]]:
+        """
+        Encodes the image with settings tuned for a small output, optionally fitting it under a target size.
+
+        For JPEG and WebP, the highest quality whose output fits under `target_kb` is searched for,
+        or a quality of 85 is used if no target is given.
+        For PNG, a few filter and compression configurations are tried and the smallest output is kept.
+
+        The encoding runs without holding the GIL.
+
+        Parameters
+        ----------
+        format: Union[:class:`.ImageFormat`, str]
+            The format to encode to, either JPEG, PNG or WebP. WebP requires the `webp` feature.
+        target_kb: Optional[int], default: None
+            The maximum size of the output, in kilobytes (1024 bytes).
+
+        Returns
+        -------
+        Tuple[bytes, Optional[int]]
+            The encoded bytes, and the JPEG or WebP quality that was chosen or `None` for PNG.
+
+        Raises
+        ------
+        ValueError
+            The format is not JPEG, PNG or WebP.
+        RuntimeError
+            The output can't fit under `target_kb`, the message includes the smallest size achieved.
+            This is also raised for WebP if ril was built without the `webp` feature.
+        """
+
+    def save(self, path: str, encoding: Optional[str] = None, strip_metadata: bool = True, options: Optional[EncodeOptions] = None, deterministic: bool = False) -> None:
+        """
+        Saves the image to the given path.
+        If encoding is not provided, it will attempt to infer it by the path/filename's extension,
+        falling back to :attr:`source_format` if the path has no extension.
+        You can try saving to a memory buffer by using the :meth:`encode` method.
+        
+        Parameters
+        ----------
+        path: str
+            The path to save the image to.
+        encoding: Optional[str], default: None
+            The encoding of the image, defaults to `None`.
+        strip_metadata: bool, default: True
+            Whether to leave out the metadata carried over from the decoded image, see :meth:`encode`.
+        options: Optional[Union[:class:`.PngOptions`, :class:`.JpegOptions`, :class:`.GifOptions`, :class:`.WebPOptions`]], default: None
+            The options to encode with, see :meth:`encode`. If no encoding is given, the format of `options` is used.
+        deterministic: bool, default: False
+            Whether to pin the encoder settings for reproducible output, see :meth:`encode`.
+        
+        Raises
+        ------
+        ValueError
+            The encoding provided is invalid, or the encoding and options are for different formats.
+            This is also raised if the output can't be made deterministic, see :meth:`encode`.
+        RuntimeError
+            Failed to encode the image or Failed to infer the image format.
+            This is also raised if the path has no extension and the image was not decoded from any format.
+
+        .. note::
+            The GIL is released while encoding and writing the file.
+        """
+
+    def save_async(self, path: str, encoding: Optional[str] = None, strip_metadata: bool = True, options: Optional[EncodeOptions] = None, deterministic: bool = False) -> Awaitable[None]:
+        """
+        Like :meth:`save`, but encodes and writes a copy of the image on a separate thread without blocking the event loop.
+
+        This must be called from a running :mod:`asyncio` event loop.
+
+        Parameters
+        ----------
+        path: str
+            The path to save the image to.
+        encoding: Optional[str], default: None
+            The encoding of the image, defaults to `None`.
+        strip_metadata: bool, default: True
+            Whether to leave out the metadata carried over from the decoded image, see :meth:`encode`.
+        options: Optional[Union[:class:`.PngOptions`, :class:`.JpegOptions`, :class:`.GifOptions`, :class:`.WebPOptions`]], default: None
+            The options to encode with, see :meth:`encode`.
+        deterministic: bool, default: False
+            Whether to pin the encoder settings for reproducible output, see :meth:`encode`.
+
+        Returns
+        -------
+        Awaitable[None]
+            A future resolving once the image is saved, raising the same errors as :meth:`save`.
+
+        Raises
+        ------
+        RuntimeError
+            There is no running event loop.
+        """
+
+    def pixels(self, raw: bool = False) -> Union[List[List[Pixels]], List[bytes]]:
+        """
+        Returns a 2D list representing the pixels of the image. Each list in the list is a row.
+       
+        For example:
+       
+        [[Pixel, Pixel, Pixel], [Pixel, Pixel, Pixel]]
+       
+        where the width of the inner list is determined by the width of the image.
+       
+        .. warning:: **This function involves heavy operation**
+       
+            This function creates an object for every pixel, so it is a heavy operation for larger image.
+            Pass `raw` to get every row as `bytes` instead, which is orders of magnitude faster.
+
+        Parameters
+        ----------
+        raw: bool, default: False
+            Whether to return every row as the raw pixel data of its pixels, laid out like :meth:`to_bytes`.
+        
+        Returns
+        -------
+        Union[List[List[Union[:class:`.BitPixel`, :class:`.L`, :class:`.Rgb`, :class:`.Rgba`]]], List[bytes]]
+            The pixels of the image, or the raw data of every row if `raw` is set.
+
+        Examples
+        --------
+
+        .. code-block:: python3
+
+            for y, row in enumerate(image.pixels(raw=True)):
+                r, g, b, a = row[:4]
+        """
+
+    def paste(
+        self,
+        x: int,
+        y: int,
+        image: Image,
+        mask: Optional[Image] = None,
+        strict_mode: bool = False,
+        match_color_space: bool = False,
+        crop_mask: bool = False,
+    ) -> None:
+        """
+        Pastes the given image onto this image at the given x and y axiss.
+        
+        If `maske` is provided it will be masked with the given masking image.
+        
+        Currently, only BitPixel images are supported for the masking image.
+
+        An image of a different mode than this image is converted to its mode first:
+
+        - RGB is converted to L by its luminance, ``0.299 * r + 0.587 * g + 0.114 * b``.
+        - L is converted to BitPixel by thresholding, where values above ``127`` are on.
+        - BitPixel and L are converted to RGB by repeating the value on every channel, where a BitPixel is either ``0`` or ``255``.
+        - Pixels gain a fully opaque alpha channel when converted to RGBA.
+        - RGBA pixels are alpha blended onto this image as if it had an alpha channel, before being converted back to its mode.
+
+        An image in a different :attr:`color_space` than this image emits a warning, unless `match_color_space` is set.
+        
+        The parts of `image` that fall outside of this image are clipped, so pasting past the right or bottom edge
+        only pastes the part that fits, and pasting entirely outside of this image leaves it unchanged.
+
+        Parameters
+        ----------
+        x: int
+            The x axis
+        y: int
+            The y axis
+        image: :class:`Image`
+            The image to paste.
+        mask: Optional[:class:`Image`], default: None
+            The mask to use, which must have the dimensions of `image`. Defaults to `None`
+        strict_mode: bool, default: False
+            Whether to raise instead of converting an image of a different mode.
+        match_color_space: bool, default: False
+            Whether to convert an image in a different color space to the color space of this image first.
+        crop_mask: bool, default: False
+            Whether to accept a mask larger than `image`, using only its upper-left corner.
+        
+        Raises
+        ------
+        ValueError
+            The mask provided is not of mode `BitPixel`, or its dimensions don't match those of `image`.
+        TypeError
+            `strict_mode` is enabled and the image is not of the mode of this image.
+        """
+
+    def paste_tiled(
+        self, image: Image, region: Optional[Tuple[int, int, int, int]] = None, offset: Tuple[int, int] = (0, 0)
+    ) -> None:
+        """
+        Repeats the given image over a region of this image, like a texture.
+
+        Tiles are pasted like :meth:`paste` and partial tiles at the edges of the region are clipped.
+
+        Parameters
+        ----------
+        image: :class:`Image`
+            The image to tile.
+        region: Optional[Tuple[int, int, int, int]], default: None
+            The region to fill, as the upper-left and lower-right corners ``(x1, y1, x2, y2)``. Defaults to the whole image.
+        offset: Tuple[int, int], default: (0, 0)
+            The position within the tile that the upper-left corner of the region starts at.
+
+        Raises
+        ------
+        ValueError
+            The region is empty or exceeds the bounds of the image.
+        """
+
+    def blit(self, x: int, y: int, tile: Image) -> None:
+        """
+        Writes the given tile back onto this image at the given x and y axis, such as a tile from :meth:`iter_tiles`.
+
+        Unlike :meth:`paste`, the pixels of the tile replace the pixels of this image regardless of the overlay mode,
+        and the tile is converted to the mode of this image. Whatever falls outside of this image is clipped.
+
+        Parameters
+        ----------
+        x: int
+            The x axis
+        y: int
+            The y axis
+        tile: :class:`Image`
+            The tile to write.
+        """
+
+    def iter_tiles(
+        self, tile_w: int, tile_h: int, overlap: int = 0, pad: bool = False, fill: Optional[Pixel] = None
+    ) -> TileIterator:
+        """
+        Returns an iterator over the tiles of this image, for processing it block by block.
+
+        Tiles are yielded lazily as ``(x, y, tile)`` tuples, row by row, where ``x`` and ``y`` are the position of the tile within this image.
+        The iterator works on a copy of this image, so writing tiles back with :meth:`blit` while iterating is safe.
+
+        Parameters
+        ----------
+        tile_w: int
+            The width of the tiles.
+        tile_h: int
+            The height of the tiles.
+        overlap: int, default: 0
+            The amount of pixels neighboring tiles share on each axis.
+        pad: bool, default: False
+            Whether to pad the tiles at the right and bottom edges to the full tile size with `fill`.
+            Otherwise those tiles are smaller.
+        fill: Optional[:class:`.Pixel`], default: None
+            The pixel to pad with, converted to the mode of this image. Defaults to a transparent black pixel.
+
+        Returns
+        -------
+        :class:`.TileIterator`
+            The iterator over the tiles.
+
+        Raises
+        ------
+        ValueError
+            The tile size is zero, or the overlap is not smaller than the tile size.
+
+        Examples
+        --------
+
+        .. code-block:: python3
+
+            result = Image.new(image.width, image.height, Pixel.from_rgb(0, 0, 0))
+
+            for x, y, tile in image.iter_tiles(256, 256):
+                result.blit(x, y, process(tile))
+        """
+
+    def mask_alpha(self, mask: Image, crop_mask: bool = False) -> None:
+        """
+        Masks the alpha values of this image with the luminance values of the given single-channel L image.
+       
+        If you want to mask using the alpha values of the image instead of providing an L image, you can split the bands of the image and extract the alpha band.
+       
+        This masking image must have the same dimensions as this image.
+        
+        Parameters
+        ----------
+        mask: :class:`Image`
+            The mask to use
+        crop_mask: bool, default: False
+            Whether to accept a mask larger than this image, using only its upper-left corner.
+        
+        Raises
+        ------
+        ValueError
+            The mask provided is not of mode `L`, or its dimensions don't match those of this image.
+        """
+
+    def convert(self, mode: Union[Mode, str], dither: bool = False, levels: Optional[int] = None) -> None:
+        """
+        Converts this image in place to the given mode, see :meth:`converted` to get a converted copy instead.
+
+        When reducing to `BitPixel`, or to `L` with a limited amount of `levels`, pixels are snapped to the nearest
+        level of their luminance, which leaves hard edges in gradients. Pass `dither` to diffuse the rounding error
+        onto neighboring pixels with Floyd–Steinberg dithering instead.
+
+        Parameters
+        ----------
+        mode: Union[:class:`.Mode`, str]
+            The mode to convert to.
+        dither: bool, default: False
+            Whether to dither when reducing to `BitPixel` or to `L` with `levels`, it has no effect otherwise.
+        levels: Optional[int], default: None
+            The amount of evenly spaced luminance levels to reduce to, between 2 and 256. Only valid when converting to `L`.
+
+        Raises
+        ------
+        ValueError
+            `levels` is given when not converting to `L`, or it is out of range.
+        """
+
+    def converted(self, mode: Union[Mode, str], dither: bool = False, levels: Optional[int] = None) -> Image:
+        """
+        Returns a copy of this image converted to the given mode, leaving this image untouched.
+
+        This takes the same arguments as :meth:`convert`.
+
+        Returns
+        -------
+        :class:`.Image`
+            The converted image, which is never frozen.
+
+        Raises
+        ------
+        ValueError
+            The mode is unknown, or `levels` is given when not converting to `L`, or it is out of range.
+
+        Examples
+        --------
+
+        .. code-block:: python3
+
+            mask = screenshot.converted('L')
+        """
+
+    def flatten(self, background: Optional[Pixel] = None) -> Image:
+        """
+        Composites this image over a solid background and returns the result as an RGB image, such as before encoding to a format without alpha.
+
+        Images without an alpha channel are returned as an unchanged copy, keeping their mode.
+
+        Parameters
+        ----------
+        background: Optional[:class:`.Pixel`], default: None
+            The color of the background, defaults to white. Its alpha is ignored.
+
+        Returns
+        -------
+        :class:`.Image`
+            The flattened image.
+
+        Examples
+        --------
+
+        .. code-block:: python3
+
+            rgb = image.flatten(Pixel.from_rgb(0, 0, 0))
+        """
+
+    def remap_palette(self, palette: List[Pixel], dither: bool = False) -> Image:
+        """
+        Maps every pixel of this image to the nearest color of the given palette and returns the result, such as for
+        fixed palettes in pixel art.
+
+        Colors are compared by their RGB channels with the `redmean` metric, see :meth:`Pixel.distance`.
+        RGBA images keep their alpha channel and stay RGBA, images of any other mode become RGB.
+
+        Parameters
+        ----------
+        palette: List[:class:`.Pixel`]
+            The colors to map to. Their alpha is ignored.
+        dither: bool, default: False
+            Whether to diffuse the difference to the chosen color onto neighboring pixels with Floyd–Steinberg
+            dithering, which smooths out gradients while still only using colors of the palette.
+
+        Returns
+        -------
+        :class:`.Image`
+            The remapped image.
+
+        Raises
+        ------
+        ValueError
+            The palette is empty.
+
+        Examples
+        --------
+
+        .. code-block:: python3
+
+            game_boy = [Pixel.from_rgb(15, 56, 15), Pixel.from_rgb(48, 98, 48), Pixel.from_rgb(139, 172, 15), Pixel.from_rgb(155, 188, 15)]
+            sprite = image.remap_palette(game_boy, dither=True)
+        """
+
+    @property
+    def color_space(self) -> str:
+        """
+        str: Returns the color space of the image, either `srgb` or `linear`.
+
+        Images are in the `srgb` color space unless converted with :meth:`to_linear`.
+        Setting this only tags the image with the given color space, without converting its pixels.
+        """
+
+    @color_space.setter
+    def color_space(self, color_space: str) -> None: ...
+
+    def to_linear(self) -> None:
+        """
+        Converts this image in place from sRGB to linear light, by applying the sRGB transfer function to every color channel.
+
+        Alpha channels are left untouched. Since channels are stored as 8-bit values,
+        dark tones lose precision, so converting back with :meth:`to_srgb` may be off by more than one for those values.
+
+        Raises
+        ------
+        ValueError
+            The image is already in the linear color space.
+        """
+
+    def to_srgb(self) -> None:
+        """
+        Converts this image in place from linear light to sRGB, by applying the inverse sRGB transfer function to every color channel.
+
+        Alpha channels are left untouched.
+
+        Raises
+        ------
+        ValueError
+            The image is already in the sRGB color space.
+        """
+
+    def mirror(self) -> None:
+        """Mirrors, or flips this image horizontally (about the y-axis) in place."""
+
+    def flip(self) -> None:
+        """Flips this image vertically (about the x-axis) in place."""
+
+    def rotate(self, degrees: float, algorithm: Optional[ResizeAlgorithm] = None, fill: Optional[Pixel] = None) -> None:
+        """
+        Rotates this image clockwise by the given angle in degrees in place.
+
+        Multiples of 90 degrees are lossless and only rearrange pixels, swapping the width and height for quarter turns.
+        Any other angle resamples the image onto a canvas that is large enough to fit all of it, and fills the
+        corners exposed by the rotation with `fill`. Resampling blends in premultiplied alpha, so the alpha channel
+        of RGBA images is preserved and their edges blend smoothly into the corners.
+
+        Parameters
+        ----------
+        degrees: float
+            The angle to rotate by in degrees, clockwise. Negative angles rotate counterclockwise.
+        algorithm: Optional[:class:`.ResizeAlgorithm`], default: None
+            How to sample pixels for angles that aren't multiples of 90 degrees, defaults to bilinear.
+            :attr:`.ResizeAlgorithm.Nearest` picks the nearest pixel, the bicubic, Mitchell and Lanczos algorithms
+            use bicubic interpolation, and the others use bilinear interpolation.
+        fill: Optional[:class:`.Pixel`], default: None
+            The pixel to fill the exposed corners with, converted to the mode of this image.
+            Defaults to a transparent black pixel.
+
+        Raises
+        ------
+        ValueError
+            `degrees` is not finite.
+        """
+
+    @property
+    def format(self) -> str:
+        """
+        str: Returns the encoding format the image was decoded from, or `"none"` if it was created programmatically.
+        
+        .. note::
+            Prefer :attr:`source_format`, which is an :class:`.ImageFormat` or `None`.
+        """
+
+    @property
+    def source_format(self) -> Optional[ImageFormat]:
+        """
+        Optional[:class:`.ImageFormat`]: Returns the encoding format the image was decoded from.
+
+        This is `None` for images created programmatically, such as with :meth:`new`,
+        and is kept as-is by operations that modify the image in place.
+        :meth:`save` falls back to this format when the path has no extension and no encoding is given.
+        """
+
+    @property
+    def source_color_model(self) -> Optional[str]:
+        """
+        Optional[str]: Returns the color model of the source pixels if they were converted to RGB when decoding.
+
+        JPEG images with four components, common from print workflows, store their colors as `cmyk`, or as `ycck`
+        when compressed like other JPEG images. Their pixels are converted to RGB without a color profile,
+        so they may look slightly different than in software that applies one. This is `None` for every other image.
+        """
+
+    @property
+    def decode_warnings(self) -> List[str]:
+        """
+        List[str]: Returns the warnings from decoding this image with `tolerant` set, describing what was recovered.
+
+        This is empty if the data decoded cleanly, and for images that were not decoded with `tolerant` set.
+        See :meth:`from_bytes`.
+        """
+
+    @property
+    def dimensions(self) -> Tuple[int, int]:
+        """Tuple[int, int]: Returns the dimensions of the image."""
+
+    def color_count(self, max_count: Optional[int] = None) -> int:
+        """
+        Counts the distinct colors of the image, where pixels are compared after converting them to RGBA.
+
+        Parameters
+        ----------
+        max_count: Optional[int]
+            Stops counting as soon as more than this many colors were found,
+            which makes checking whether an image has at most, for example, 256 colors cheap.
+
+        Returns
+        -------
+        int
+            The amount of distinct colors, or ``max_count + 1`` if there are more than `max_count`.
+        """
+
+    def average_color(self, region: Optional[Tuple[int, int, int, int]] = None) -> Pixel:
+        """
+        Returns the average color of this image or a region of it, such as the background behind text.
+
+        Colors are weighted by their alpha, so transparent pixels don't tint the average, which has the mean alpha.
+        The average is in the mode of this image, where a `BITPIXEL` average is on if more than half of the pixels are.
+
+        Parameters
+        ----------
+        region: Optional[Tuple[int, int, int, int]], default: None
+            The region to average, see :meth:`invert`. Defaults to the whole image.
+
+        Returns
+        -------
+        :class:`.Pixel`
+            The average color.
+
+        Raises
+        ------
+        ValueError
+            The region is empty or exceeds the bounds of the image.
+        """
+
+    def get_pixel(self, x: int, y: int) -> Pixels:
+        """
+        Returns the pixel at the given coordinates.
+        
+        Parameters
+        ----------
+        x: int
+            The x axis
+        y: int
+            The y axis
+        
+        Returns
+        -------
+        Union[:class:`.BitPixel`, :class:`.L`, :class:`.Rgb`, :class:`.Rgba`]
+            The pixel of that specific coordinate.
+        """
+
+    def set_pixel(self, x: int, y: int, pixel: Pixel, strict: bool = False) -> None:
+        """
+        Sets the pixel at the given coordinates to the given pixel.
+
+        A pixel of a different mode than this image is converted to its mode first,
+        exactly like :meth:`convert` converts every pixel, see :meth:`paste` for the conversion rules.
+
+        This can also be written as ``image[x, y] = pixel``.
+        
+        Parameters
+        ---------
+        x: int
+            The x axis
+        y: int
+            The y axis
+        pixel: :class:`.Pixel`
+            The pixel to set it to
+        strict: bool, default: False
+            Whether to raise instead of converting a pixel of a different mode.
+
+        Raises
+        ------
+        IndexError
+            The coordinates are out of bounds.
+        TypeError
+            `strict` is enabled and the pixel is not of the mode of this image.
+        """
+
+    def __setitem__(self, xy: Tuple[int, int], pixel: Pixel) -> None:
+        ...
+
+    def invert(self, region: Optional[Tuple[int, int, int, int]] = None) -> None:
+        """
+        Inverts the image in-place.
+
+        Parameters
+        ----------
+        region: Optional[Tuple[int, int, int, int]], default: None
+            The region to invert, as the upper-left and lower-right corners ``(x1, y1, x2, y2)``, see :meth:`crop`.
+            Defaults to the whole image.
+
+        Raises
+        ------
+        ValueError
+            The region is empty or exceeds the bounds of the image.
+        """
+
+    def brighten(self, amount: int, region: Optional[Tuple[int, int, int, int]] = None) -> None:
+        """
+        Brightens the image in place by adding `amount` to every color channel, clamping at 255.
+
+        Alpha channels and `BitPixel` images are left untouched.
+
+        Parameters
+        ----------
+        amount: int
+            The amount to add, from 0 to 255.
+        region: Optional[Tuple[int, int, int, int]], default: None
+            The region to brighten, see :meth:`invert`. Defaults to the whole image.
+
+        Raises
+        ------
+        ValueError
+            The region is empty or exceeds the bounds of the image.
+        """
+
+    def darken(self, amount: int, region: Optional[Tuple[int, int, int, int]] = None) -> None:
+        """
+        Darkens the image in place by subtracting `amount` from every color channel, clamping at 0.
+
+        Alpha channels and `BitPixel` images are left untouched.
+
+        Parameters
+        ----------
+        amount: int
+            The amount to subtract, from 0 to 255.
+        region: Optional[Tuple[int, int, int, int]], default: None
+            The region to darken, see :meth:`invert`. Defaults to the whole image.
+
+        Raises
+        ------
+        ValueError
+            The region is empty or exceeds the bounds of the image.
+        """
+
+    def contrast(self, factor: float, region: Optional[Tuple[int, int, int, int]] = None) -> None:
+        """
+        Adjusts the contrast of the image in place by scaling the distance of every color channel from the midpoint, 128.
+
+        Alpha channels and `BitPixel` images are left untouched.
+
+        Parameters
+        ----------
+        factor: float
+            The factor to scale by, where values below 1 reduce the contrast and values above 1 increase it.
+        region: Optional[Tuple[int, int, int, int]], default: None
+            The region to adjust, see :meth:`invert`. Defaults to the whole image.
+
+        Raises
+        ------
+        ValueError
+            `factor` is negative, or the region is empty or exceeds the bounds of the image.
+        """
+
+    def gamma(self, gamma: float, region: Optional[Tuple[int, int, int, int]] = None) -> None:
+        """
+        Applies gamma correction to the image in place, raising every normalized color channel to the power of ``1 / gamma``.
+
+        Alpha channels and `BitPixel` images are left untouched.
+
+        Parameters
+        ----------
+        gamma: float
+            The gamma, where values above 1 brighten the midtones and values below 1 darken them.
+        region: Optional[Tuple[int, int, int, int]], default: None
+            The region to adjust, see :meth:`invert`. Defaults to the whole image.
+
+        Raises
+        ------
+        ValueError
+            `gamma` is not positive, or the region is empty or exceeds the bounds of the image.
+        """
+
+    def pixelate(self, block_size: int, region: Optional[Tuple[int, int, int, int]] = None) -> None:
+        """
+        Pixelates the image in place by replacing every block of pixels with its average color, such as for redacting faces or text.
+
+        Blocks start at the upper-left corner of the region, and the blocks at its right and bottom edges may be smaller.
+        Every block is averaged over its own pixels like :meth:`average_color`.
+
+        Parameters
+        ----------
+        block_size: int
+            The width and height of every block, in pixels.
+        region: Optional[Tuple[int, int, int, int]], default: None
+            The region to pixelate, see :meth:`invert`. Defaults to the whole image.
+
+        Raises
+        ------
+        ValueError
+            `block_size` is 0, or the region is empty or exceeds the bounds of the image.
+
+        Examples
+        --------
+
+        .. code-block:: python3
+
+            image.pixelate(12, region=(40, 30, 120, 90))
+        """
+
+    def mosaic(self, block_size: int) -> None:
+        """
+        Pixelates the whole image in place, see :meth:`pixelate`.
+
+        Parameters
+        ----------
+        block_size: int
+            The width and height of every block, in pixels.
+
+        Raises
+        ------
+        ValueError
+            `block_size` is 0.
+        """
+
+    def map_channel(self, channel: str, lut: List[int]) -> None:
+        """
+        Maps a single channel of every pixel in place through a lookup table, such as a curve from an image editor.
+
+        Parameters
+        ----------
+        channel: str
+            The channel to map, `l` for L images, or one of `r`, `g`, `b` and, for RGBA images, `a`.
+        lut: List[int]
+            The lookup table, where every channel value ``v`` is replaced with ``lut[v]``.
+            It must have exactly 256 entries from 0 to 255.
+
+        Raises
+        ------
+        ValueError
+            The image has no such channel, which is always the case for `BitPixel` images,
+            or the lookup table is invalid.
+
+        Examples
+        --------
+
+        .. code-block:: python3
+
+            image.map_channel('r', [255 - v for v in range(256)])
+        """
+
+    def map_channels(self, luts: Dict[str, List[int]]) -> None:
+        """
+        Maps several channels of every pixel in place through lookup tables in a single pass, see :meth:`map_channel`.
+
+        Parameters
+        ----------
+        luts: Dict[str, List[int]]
+            The lookup table of each channel to map, channels that are left out are untouched.
+
+        Raises
+        ------
+        ValueError
+            The image has no such channel, a channel is given more than once, or a lookup table is invalid.
+        """
+
+    def match_histogram(self, reference: Image) -> None:
+        """
+        Adjusts the distribution of every color channel of this image in place to match the given reference image,
+        such as to color-match a composite to its background.
+
+        Every channel is mapped so that its cumulative histogram follows that of the same channel of `reference`.
+        Alpha channels are left untouched, and `BitPixel` images have no channel to adjust.
+
+        Parameters
+        ----------
+        reference: :class:`.Image`
+            The image to match, which may have different dimensions. If it has a richer mode than this image,
+            this image is converted to its mode first, otherwise the reference is compared as if it had the mode of this image.
+
+        Examples
+        --------
+
+        .. code-block:: python3
+
+            overlay.match_histogram(background)
+            background.paste(0, 0, overlay)
+        """
+
+    def unsharp_mask(self, radius: float = 2.0, amount: float = 0.5, threshold: int = 0) -> None:
+        """
+        Sharpens the image in place with an unsharp mask.
+
+        A blurred copy of the image is subtracted from it, and `amount` times the difference is added back to every color channel,
+        which steepens edges. Alpha channels are left untouched.
+
+        Parameters
+        ----------
+        radius: float, default: 2.0
+            The standard deviation of the blur, which controls how wide the sharpened edges are.
+        amount: float, default: 0.5
+            The strength of the sharpening, where `0` leaves the image unchanged.
+        threshold: int, default: 0
+            The minimum difference between a channel and its blurred value for it to be sharpened,
+            which leaves noise in flat regions alone.
+
+        Raises
+        ------
+        ValueError
+            `radius` is negative or not finite.
+        TypeError
+            The image is of mode `BitPixel`.
+        """
+
+    def blur(self, sigma: float) -> None:
+        """
+        Blurs the image in place with a gaussian blur.
+
+        Every channel is blurred, including alpha, where colors are weighted by their alpha so that transparent pixels
+        don't darken the edges of opaque ones. Pixels past the edges of the image repeat the nearest edge pixel.
+
+        The gaussian is approximated by three successive box blurs, which is visually indistinguishable from it
+        and takes the same time for any `sigma`.
+
+        Parameters
+        ----------
+        sigma: float
+            The standard deviation of the blur in pixels. Values below about 0.6 leave the image unchanged.
+
+        Raises
+        ------
+        ValueError
+            `sigma` is negative or not finite.
+        TypeError
+            The image is of mode `BitPixel`.
+        """
+
+    def box_blur(self, radius: int) -> None:
+        """
+        Blurs the image in place with a box blur, which sets every pixel to the average of the square of
+        ``2 * radius + 1`` pixels around it.
+
+        Every channel is blurred the same way as with :meth:`blur`, including alpha.
+
+        Parameters
+        ----------
+        radius: int
+            The distance from each pixel to the edges of the square it is averaged over, where `0` leaves the image unchanged.
+
+        Raises
+        ------
+        TypeError
+            The image is of mode `BitPixel`.
+        """
+
+    def vignette(self, strength: float = 0.5, radius: float = 0.75) -> None:
+        """
+        Darkens the image in place toward its corners with a smooth radial falloff.
+
+        Distances are measured from the center of the image, relative to the distance of its corners.
+        Pixels within `radius` are left unchanged, and the color channels beyond it are scaled down smoothly
+        until they reach ``1 - strength`` of their value at the corners.
+        Alpha channels and `BitPixel` images are left untouched.
+
+        Parameters
+        ----------
+        strength: float, default: 0.5
+            How much the corners are darkened, from 0 (unchanged) to 1 (black).
+        radius: float, default: 0.75
+            The relative distance from the center at which the darkening starts, where 1 reaches the corners.
+
+        Raises
+        ------
+        ValueError
+            `strength` is not from 0 to 1, or `radius` is negative.
+        """
+
+    def fill_gradient(self, region: Tuple[int, int, int, int], start: Pixel, end: Pixel, direction: float) -> None:
+        """
+        Paints a linear gradient from `start` to `end` into the given region of this image in place,
+        replacing the pixels within it.
+
+        The gradient runs along `direction`, so that the pixels at either end of the region have exactly the colors of the stops.
+        Both colors are converted to the mode of the image.
+
+        Parameters
+        ----------
+        region: Tuple[int, int, int, int]
+            The region ``(x1, y1, x2, y2)`` to paint.
+        start: :class:`.Pixel`
+            The color at the start of the gradient.
+        end: :class:`.Pixel`
+            The color at the end of the gradient.
+        direction: float
+            The angle of the gradient in degrees, clockwise, where 0 runs from left to right and 90 from top to bottom.
+
+        Raises
+        ------
+        ValueError
+            The region is empty or exceeds the bounds of the image.
+
+        Examples
+        --------
+
+        .. code-block:: python3
+
+            image.fill_gradient((0, 0, image.width, 40), Colors.WHITE, Colors.BLACK, 90)
+        """
+
+    def __or__(self, other: Image) -> Image:
+        """
+        Returns a new image with `other` alpha-composited over this image.
+
+        Both images must have the same dimensions, otherwise a `ValueError` is raised.
+        """
+
+    def __and__(self, mask: Image) -> Image:
+        """
+        Returns a copy of this image with its alpha masked by the given `BITPIXEL` or `L` image.
+
+        Both images must have the same dimensions, otherwise a `ValueError` is raised.
+        """
+
+    def _repr_png_(self) -> bytes:
+        """
+        Returns the image encoded as PNG, used by Jupyter to display the image inline.
+
+        Images larger than the preview size are downscaled first, see :func:`set_notebook_preview_size`.
+        """
+
+
+class ImageView:
+    """
+    A read-only view of a region of an image, see :meth:`Image.view`.
+
+    The view keeps the image alive and reads its current data, nothing is copied until :meth:`to_image` is called.
+    """
+
+    @property
+    def width(self) -> int:
+        """int: Returns the width of the view."""
+
+    @property
+    def height(self) -> int:
+        """int: Returns the height of the view."""
+
+    def get_pixel(self, x: int, y: int) -> Union[BitPixel, L, Rgb, Rgba]:
+        """
+        Returns the pixel at the given coordinates, relative to the upper-left corner of the view.
+
+        Parameters
+        ----------
+        x: int
+            The x axis
+        y: int
+            The y axis
+
+        Returns
+        -------
+        Union[:class:`.BitPixel`, :class:`.L`, :class:`.Rgb`, :class:`.Rgba`]
+            The pixel of that specific coordinate.
+
+        Raises
+        ------
+        IndexError
+            The coordinates are outside of the view.
+        ValueError
+            The image was closed, or shrunk and no longer contains the region of the view.
+        """
+
+    def pixels_flat(self) -> List[Union[BitPixel, L, Rgb, Rgba]]:
+        """
+        Returns a flat list of the pixels of the view, row by row.
+
+        Returns
+        -------
+        List[Union[:class:`.BitPixel`, :class:`.L`, :class:`.Rgb`, :class:`.Rgba`]]
+            The pixels of the view.
+
+        Raises
+        ------
+        ValueError
+            The image was closed, or shrunk and no longer contains the region of the view.
+        """
+
+    def to_bytes(self) -> bytes:
+        """
+        Returns the raw pixel data of the view, row by row.
+
+        Each pixel takes up as many bytes as it has channels in the mode of the image,
+        where a :class:`.BitPixel` is either ``0`` or ``255``.
+
+        Returns
+        -------
+        bytes
+            The raw pixel data.
+
+        Raises
+        ------
+        ValueError
+            The image was closed, or shrunk and no longer contains the region of the view.
+        """
+
+    def to_image(self) -> Image:
+        """
+        Copies the region of the view into a new image.
+
+        Returns
+        -------
+        :class:`.Image`
+            The copied region.
+
+        Raises
+        ------
+        ValueError
+            The image was closed, or shrunk and no longer contains the region of the view.
+        """
+
+class TileIterator(Iterator[Tuple[int, int, Image]]):
+    """
+    An iterator over the tiles of an image, see :meth:`Image.iter_tiles`.
+
+    Yields ``(x, y, tile)`` tuples, where ``x`` and ``y`` are the position of the tile within the image.
+    """
+
+    def __iter__(self) -> TileIterator: ...
+
+    def __next__(self) -> Tuple[int, int, Image]: ...
+
+class Pipeline:
+    """
+    A lazy chain of operations on an image, created with :meth:`Image.pipeline`.
+
+    Calling the methods of a pipeline only records the operations, which are validated against the dimensions
+    the image will have by then. :meth:`execute` then runs them all at once without the GIL, after rearranging them
+    to do less work where that gives exactly the same result:
+
+    - Crops are moved before flips, mirrors, rotations and conversions, so those touch fewer pixels.
+    - A crop after a resize by a whole factor, such as from 400 to 100 pixels, only resizes the part of the image
+      the crop keeps, plus a margin for the resampling filter.
+    - Consecutive flips, mirrors and rotations are combined into a single pass, or dropped if they cancel out.
+    - Repeated conversions to the same mode are only done once.
+
+    The pipeline works on the pixels the image had when the pipeline was created, and every method except
+    :meth:`execute` returns the pipeline itself for chaining.
+
+    Examples
+    --------
+
+    .. code-block:: python3
+
+        thumbnail = image.pipeline().resize(400, 300, ResizeAlgorithm.Bilinear).crop(100, 50, 300, 250).execute()
+    """
+
+    def resize(self, width: int, height: int, algorithm: ResizeAlgorithm) -> Pipeline:
+        """
+        Records resizing the image to the given dimensions, see :meth:`Image.resize`.
+
+        Parameters
+        ----------
+        width: int
+            The width to resize to.
+        height: int
+            The height to resize to.
+        algorithm: :class:`.ResizeAlgorithm`
+            The resize algorithm to use.
+
+        Returns
+        -------
+        :class:`.Pipeline`
+            This pipeline, for chaining.
+
+        Raises
+        ------
+        ValueError
+            `width` or `height` is zero.
+        """
+
+    def crop(self, x1: int, y1: int, x2: int, y2: int) -> Pipeline:
+        """
+        Records cropping the image to the given bounding box, see :meth:`Image.crop`.
+
+        Returns
+        -------
+        :class:`.Pipeline`
+            This pipeline, for chaining.
+
+        Raises
+        ------
+        ValueError
+            The bounding box is empty or exceeds the bounds the image will have.
+        """
+
+    def rotate(self, degrees: int) -> Pipeline:
+        """
+        Records rotating the image clockwise by the given multiple of 90 degrees.
+
+        Parameters
+        ----------
+        degrees: int
+            The angle to rotate by, which may be negative to rotate counterclockwise.
+
+        Returns
+        -------
+        :class:`.Pipeline`
+            This pipeline, for chaining.
+
+        Raises
+        ------
+        ValueError
+            `degrees` is not a multiple of 90.
+        """
+
+    def convert(self, mode: Union[Mode, str]) -> Pipeline:
+        """
+        Records converting the image to the given mode, see :meth:`Image.convert`.
+
+        Parameters
+        ----------
+        mode: Union[:class:`.Mode`, str]
+            The mode to convert to.
+
+        Returns
+        -------
+        :class:`.Pipeline`
+            This pipeline, for chaining.
+        """
+
+    def flip(self) -> Pipeline:
+        """
+        Records flipping the image vertically, see :meth:`Image.flip`.
+
+        Returns
+        -------
+        :class:`.Pipeline`
+            This pipeline, for chaining.
+        """
+
+    def mirror(self) -> Pipeline:
+        """
+        Records mirroring the image horizontally, see :meth:`Image.mirror`.
+
+        Returns
+        -------
+        :class:`.Pipeline`
+            This pipeline, for chaining.
+        """
+
+    @property
+    def ops(self) -> List[tuple]:
+        """
+        The recorded operations in order, such as ``('resize', 400, 300, ResizeAlgorithm.Bilinear)``,
+        ``('crop', 0, 0, 100, 100)``, ``('rotate', 90)``, ``('convert', Mode.L)``, ``('flip',)`` or ``('mirror',)``.
+
+        These are the operations as they were recorded, not as they are executed.
+        """
+
+    @property
+    def dimensions(self) -> Tuple[int, int]:
+        """
+        The dimensions the image will have after the recorded operations.
+        """
+
+    def execute(self) -> Image:
+        """
+        Runs the recorded operations and returns the resulting image, leaving the original image untouched.
+
+        The pipeline can be executed again, and more operations can still be recorded afterwards.
+
+        Returns
+        -------
+        :class:`.Image`
+            The resulting image, which has the same pixels as if the operations were applied one by one.
+        """
+
+    def __len__(self) -> int: ...
+
+    def __repr__(self) -> str: ...
+
+class PngOptions:
+    """
+    Options for encoding PNG images, accepted by the `options` argument of :meth:`Image.encode` and :meth:`Image.save`.
+    """
+
+    def __init__(self, compression: str = 'default', interlaced: bool = False, filter: str = 'adaptive') -> None:
+        """
+        Parameters
+        ----------
+        compression: str, default: "default"
+            How hard to compress the image data, one of `fast`, `default` or `best`.
+        interlaced: bool, default: False
+            Whether to write an Adam7 interlaced image.
+            The PNG encoder can't write interlaced images yet, so this must be False.
+        filter: str, default: "adaptive"
+            The filter applied to each row before compression, one of `none`, `sub`, `up`, `average`, `paeth`,
+            or `adaptive` to pick the best one for every row.
+
+        Raises
+        ------
+        ValueError
+            The compression or filter is invalid, or `interlaced` is set.
+        """
+
+    @property
+    def compression(self) -> str:
+        """str: How hard the image data is compressed."""
+
+    @property
+    def interlaced(self) -> bool:
+        """bool: Whether the image is interlaced."""
+
+    @property
+    def filter(self) -> str:
+        """str: The filter applied to each row before compression."""
+
+    @classmethod
+    def deterministic(cls: Type[PngOptions]) -> PngOptions:
+        """
+        Returns the options used when encoding with `deterministic=True` and no options are given:
+        the default compression level with the `paeth` filter for every row.
+
+        Returns
+        -------
+        :class:`.PngOptions`
+            The pinned options.
+        """
+
+
+class JpegOptions:
+    """
+    Options for encoding JPEG images, accepted by the `options` argument of :meth:`Image.encode` and :meth:`Image.save`.
+
+    JPEG has no alpha channel, so RGBA images are composited over white first, like :meth:`Image.flatten`.
+    """
+
+    def __init__(self, quality: int = 90, progressive: bool = False, subsampling: str = '4:2:0') -> None:
+        """
+        Parameters
+        ----------
+        quality: int, default: 90
+            The quality of the image, from 1 (smallest) to 100 (best).
+        progressive: bool, default: False
+            Whether to write a progressive image, which loads in increasingly detailed passes.
+        subsampling: str, default: "4:2:0"
+            How much the chroma channels are subsampled, one of `4:4:4` (none), `4:2:2` or `4:2:0`.
+            This has no effect on grayscale images.
+
+        Raises
+        ------
+        ValueError
+            The quality is not from 1 to 100, or the subsampling is invalid.
+        """
+
+    @property
+    def quality(self) -> int:
+        """int: The quality of the image, from 1 to 100."""
+
+    @property
+    def progressive(self) -> bool:
+        """bool: Whether the image is progressive."""
+
+    @property
+    def subsampling(self) -> str:
+        """str: How much the chroma channels are subsampled."""
+
+
+class GifOptions:
+    """
+    Options for encoding GIF images, accepted by the `options` argument of
+    :meth:`Image.encode`, :meth:`Image.save`, :meth:`ImageSequence.encode` and :meth:`ImageSequence.save`.
+    """
+
+    def __init__(
+        self,
+        quality: int = 70,
+        dither: bool = False,
+        loop_count: Optional[int] = None,
+        alpha_threshold: int = 128,
+        matte: Optional[Pixel] = None,
+    ) -> None:
+        """
+        Parameters
+        ----------
+        quality: int, default: 70
+            How closely the palette of each frame is fit to its colors, from 1 (fastest) to 100 (best).
+            The default matches the quantization used when no options are given.
+        dither: bool, default: False
+            Whether to diffuse the quantization error across neighbouring pixels, which smooths out banding in gradients.
+        loop_count: Optional[int], default: None
+            How many times the sequence plays, where 0 loops forever.
+            Defaults to `None`, which keeps the loop count of the sequence. A single :class:`.Image` is not looped.
+        alpha_threshold: int, default: 128
+            GIF only supports fully transparent pixels, so pixels with an alpha below this become transparent and
+            the rest become opaque. 0 makes every pixel opaque.
+        matte: Optional[:class:`.Pixel`], default: None
+            The color partially transparent pixels that become opaque are composited over, such as the background
+            the GIF is shown on, which avoids halos around soft edges. Its alpha is ignored.
+            Defaults to `None`, which keeps the color of those pixels as is.
+
+        Raises
+        ------
+        ValueError
+            The quality is not from 1 to 100.
+        """
+
+    @property
+    def quality(self) -> int:
+        """int: How closely the palette of each frame is fit to its colors, from 1 to 100."""
+
+    @property
+    def dither(self) -> bool:
+        """bool: Whether the quantization error is diffused across neighbouring pixels."""
+
+    @property
+    def loop_count(self) -> Optional[int]:
+        """Optional[int]: How many times the sequence plays, where 0 loops forever, or None to keep the loop count of the sequence."""
+
+    @property
+    def alpha_threshold(self) -> int:
+        """int: The alpha below which pixels become transparent."""
+
+    @property
+    def matte(self) -> Optional[Pixel]:
+        """Optional[:class:`.Pixel`]: The RGB color partially transparent pixels are composited over, if any."""
+
+    @classmethod
+    def deterministic(cls: Type[GifOptions]) -> GifOptions:
+        """
+        Returns the options used when encoding with `deterministic=True` and no options are given,
+        which are the defaults. Quantization never depends on the amount of workers, so any GIF options are deterministic.
+
+        Returns
+        -------
+        :class:`.GifOptions`
+            The pinned options.
+        """
+
+
+class WebPOptions:
+    """
+    Options for encoding WebP images, accepted by the `options` argument of :meth:`Image.encode` and :meth:`Image.save`.
+
+    Encoding WebP requires ril to be built with the `webp` feature, see :data:`WEBP`.
+    """
+
+    def __init__(self, quality: int = 80, lossless: bool = False) -> None:
+        """
+        Parameters
+        ----------
+        quality: int, default: 80
+            The quality of the image, from 1 (smallest) to 100 (best). For lossless images, this is how hard it is compressed.
+        lossless: bool, default: False
+            Whether to encode the image without any loss.
+
+        Raises
+        ------
+        ValueError
+            The quality is not from 1 to 100.
+        """
+
+    @property
+    def quality(self) -> int:
+        """int: The quality of the image, from 1 to 100."""
+
+    @property
+    def lossless(self) -> bool:
+        """bool: Whether the image is encoded without any loss."""
+
+
+class StrokeStyle:
+    """
+    The style of a stroke, such as a border, which can be solid, dashed or dotted.
+
+    Use one of :meth:`solid`, :meth:`dashed` or :meth:`dotted` to create a stroke style.
+
+    Stroke styles are immutable, so they can be compared and hashed.
+    """
+    @property
+    def kind(self) -> str:
+        """str: The kind of stroke, one of `solid`, `dashed` or `dotted`."""
+
+    @property
+    def length(self) -> Optional[int]:
+        """Optional[int]: The length of each dash, or None if the stroke is not dashed."""
+
+    @property
+    def gap(self) -> Optional[int]:
+        """Optional[int]: The gap between dashes, or None if the stroke is not dashed."""
+
+    @property
+    def spacing(self) -> Optional[int]:
+        """Optional[int]: The spacing between dots, or None if the stroke is not dotted."""
+
+    @classmethod
+    def solid(cls) -> StrokeStyle:
+        """
+        Creates a solid stroke style.
+        """
+
+    @classmethod
+    def dashed(cls, length: int, gap: int) -> StrokeStyle:
+        """
+        Creates a dashed stroke style.
+
+        Dashes continue around the corners of rectangles and polygons.
+
+        Parameters
+        ----------
+        length: int
+            The length of each dash, in pixels.
+        gap: int
+            The length of the gap between dashes, in pixels.
+
+        Raises
+        ------
+        ValueError
+            `length` or `gap` is zero.
+        """
+
+    @classmethod
+    def dotted(cls, spacing: int) -> StrokeStyle:
+        """
+        Creates a dotted stroke style, with a single pixel dot every `spacing` pixels.
+
+        On the borders of rectangles and polygons, a dot is drawn on every corner and the dots of each side
+        are laid out from there, so the last gap before a corner can be up to twice as long.
+
+        Parameters
+        ----------
+        spacing: int
+            The distance between the start of each dot, in pixels.
+
+        Raises
+        ------
+        ValueError
+            `spacing` is zero.
+        """
+
+    def __hash__(self) -> int:
+        ...
+
+
+
+class Border:
+    """
+    Represents a shape border.
+    """
+    color: Pixel
+    thickness: int
+    position: str
+    style: StrokeStyle
+
+    def __init__(self, color: Pixel, thickness: int, position: str, style: Optional[StrokeStyle] = None) -> None:
+        """
+    Parameters
+    ----------
+    color: :class:`.Pixel`
+        The color of the border
+    thickness: int
+        The thickness of the border
+    position: str
+        The position of the border
+    style: Optional[:class:`.StrokeStyle`]
+        The stroke style of the border, defaults to solid.
+    
+    Raises
+    ------
+    ValueError
+        The position is not one of `inset`, `center`, or `outset`
+        """
+
+    def copy(self) -> Border:
+        """Returns a copy of this border."""
+
+    def __copy__(self) -> Border:
+        ...
+
+
+
+class Shadow:
+    """
+    A soft shadow or glow of a shape, see the `inner_shadow` and `outer_glow` options of :class:`.Rectangle` and :class:`.Ellipse`,
+    or the drop shadow of text, see the `shadow` option of :class:`.TextSegment` and :class:`.TextLayout`.
+
+    The shape is blurred, offset, and blended onto the image with the color of the shadow,
+    where the alpha of an RGBA color scales the opacity of the whole shadow.
+    """
+    color: Pixel
+    blur: int
+    offset: Tuple[int, int]
+
+    def __init__(self, *, color: Pixel, blur: int = 4, offset: Tuple[int, int] = (0, 0)) -> None:
+        """
+        Parameters
+        ----------
+        color: :class:`.Pixel`
+            The color of the shadow.
+        blur: int, default: 4
+            How far the shadow fades out, in pixels. Zero gives a hard edge.
+        offset: Tuple[int, int], default: (0, 0)
+            How far the shadow is moved on each axis, in pixels.
+        """
+
+    def copy(self) -> Shadow:
+        """Returns a copy of this shadow."""
+
+    def __copy__(self) -> Shadow:
+        ...
+
+
+
+class Ellipse:
+    """
+    An ellipse, which could be a circle.
+    
+    .. warning::
+        Using any of the predefined constructors will automatically set the position to (0, 0) and you must explicitly set the size of the ellipse with `.size` in order to set a size for the ellipse. 
+        A size must be set before drawing.
+    
+        This also does not set any border or fill for the ellipse, you must explicitly set either one of them.
+
+    The position and radii may lie between pixels, such as for smooth animations. They are rounded to the nearest pixel
+    when drawn, where values halfway between two pixels round to the even one, so ``10.5`` rounds to ``10`` and ``11.5`` to ``12``.
+
+    Coordinates are kept as 32-bit floats, which can't hold a fraction past ``2 ** 24`` (16777216), so larger
+    coordinates are only kept to the nearest float and lose any part between pixels.
+    """
+    position: Point
+    radii: Point
+    inner_radii: Optional[Xy]
+    border: Optional[Border]
+    fill: Optional[Pixel]
+    overlay: Optional[OverlayMode]
+    inner_shadow: Optional[Shadow]
+    outer_glow: Optional[Shadow]
+
+    def __init__(
+        self,
+        position: Point,
+        radii: Point,
+        inner_radii: Optional[Xy] = None,
+        border: Optional[Border] = None,
+        fill: Optional[Pixel] = None,
+        overlay: Optional[str] = None,
+        inner_shadow: Optional[Shadow] = None,
+        outer_glow: Optional[Shadow] = None
+    ) -> None:
+        """
+        Parameters
+        ---------
+        position: Tuple[float, float]
+            The position of the ellipse
+        radii: Tuple[float, float]
+            The radii of the ellipse
+        inner_radii: Optional[Tuple[int, int]]
+            The radii of the hole in the ellipse, which makes it a ring. Only the pixels between the inner and outer ellipse are filled,
+            and the border is applied to both edges.
+        border: Optional[:class:`.Border`]
+            The border of the ellipse.
+        fill: Optional[:class:`.Pixel`]
+            The color to use for filling the ellipse
+        overlay: Optional[:class:`.OverlayMode`]
+            The overlay mode of the ellipse.
+        inner_shadow: Optional[:class:`.Shadow`]
+            The shadow cast onto the inside of the ellipse, drawn over its fill.
+        outer_glow: Optional[:class:`.Shadow`]
+            The glow around the outside of the ellipse, drawn before it.
+
+        Raises
+        ------
+        ValueError
+            The border doesn't use the solid stroke style, the inner radii are zero or not smaller than the radii,
+            or the position or radii round to below zero.
+        """
+
+    @classmethod
+    def from_bounding_box(cls, x1: int, y1: int, x2: int, y2: int) -> Ellipse:
+        """
+        Creates a new ellipse from the given bounding box.
+        
+        Parameters
+        ----------
+        x1: int
+            The x axis of the upper left corner
+        y1: int
+            The y axis of the upper left corner
+        x2: int
+            The x axis of the lower right corner
+        y2: int
+            The y axis of the lower right corner
+        
+        Returns
+        -------
+        :class:`.Ellipse`
+            The newly created ellipse
+        """
+
+    @classmethod
+    def circle(cls, x: int, y: int, radius: int) -> Ellipse:
+        """
+        Creates a new circle with the given center position and radius.
+        
+        Parameters
+        ----------
+        x: int
+            The x axis
+        y: int
+            The y axis
+        radius: int
+            The radius
+        """
+
+    def copy(self) -> Ellipse:
+        """Returns a copy of this ellipse."""
+
+    def __copy__(self) -> Ellipse:
+        ...
+
+
+
+class Rectangle:
+    """
+    A rectangle.
+    
+    .. warning::
+        Using any of the predefined construction methods will automatically set the position to (0, 0). 
+        If you want to specify a different position, you must set the position with `.position`
+    
+        You must specify a width and height for the rectangle with something such as with_size. 
+        If you don't, a panic will be raised during drawing. 
+        You can also try using from_bounding_box to create a rectangle from a bounding box, which automatically fills in the size.
+    
+        Additionally, a panic will be raised during drawing if you do not specify either a fill color or a border.
+        these can be set with `.fill` and `.border` respectively.
+
+    The position and size may lie between pixels, such as for smooth animations. They are rounded to the nearest pixel
+    when drawn the same way as the position of an :class:`.Ellipse`, and sizes that aren't zero stay at least one pixel.
+
+    Corners can be rounded with `radius`, which the fill, border, shadows and overlay all follow. Each radius is
+    clamped to half of the smaller side of the rectangle, so a large radius turns a square into a circle.
+    """
+    position: Point
+    size: Point
+    border: Optional[Border]
+    fill: Optional[Pixel]
+    overlay: Optional[OverlayMode]
+    inner_shadow: Optional[Shadow]
+    outer_glow: Optional[Shadow]
+
+    @property
+    def radius(self) -> Tuple[int, int, int, int]:
+        """
+        The radius of each corner as ``(top_left, top_right, bottom_right, bottom_left)``.
+        Can be set to a single radius for every corner.
+        """
+
+    @radius.setter
+    def radius(self, radius: Union[int, Tuple[int, int, int, int]]) -> None:
+        ...
+
+    def __init__(
+        self,
+        position: Point,
+        size: Point,
+        border: Optional[Border] = None,
+        fill: Optional[Pixel] = None,
+        overlay: Optional[OverlayMode] = None,
+        inner_shadow: Optional[Shadow] = None,
+        outer_glow: Optional[Shadow] = None,
+        radius: Union[int, Tuple[int, int, int, int]] = 0
+    ) -> None:
+        """
+        Parameters
+        ----------
+        position: Tuple[float, float]
+            The position of the rectangle
+        size: Tuple[float, float]
+            The size of the rectangle
+        border: Optional[:class:`.Border`]
+            The border of the ellipse.
+        fill: Optional[:class:`.Pixel`]
+            The color to use for filling the rectangle
+        overlay: Optional[:class:`.OverlayMode`]
+            The overlay mode of the rectangle.
+        inner_shadow: Optional[:class:`.Shadow`]
+            The shadow cast onto the inside of the rectangle, drawn over its fill.
+        outer_glow: Optional[:class:`.Shadow`]
+            The glow around the outside of the rectangle, drawn before it.
+        radius: Union[int, Tuple[int, int, int, int]], default: 0
+            The radius of the rounded corners in pixels, either for every corner or per corner as
+            ``(top_left, top_right, bottom_right, bottom_left)``.
+
+        Raises
+        ------
+        ValueError
+            The position or size round to below zero, a radius is negative, or the corners are rounded and the
+            border isn't solid.
+        """
+
+    @classmethod
+    def from_bounding_box(cls, x1: int, y1: int, x2: int, y2: int) -> Rectangle:
+        """
+        Creates a new ellipse from the given bounding box.
+       
+        Parameters
+        ----------
+        x1: int
+            The x axis of the upper left corner
+        y1: int
+            The y axis of the upper left corner
+        x2: int
+            The x axis of the lower right corner
+        y2: int
+            The y axis of the lower right corner
+        
+        Returns
+        -------
+        :class:`.Rectangle`
+            The newly created rectangle
+        """
+
+    def copy(self) -> Rectangle:
+        """Returns a copy of this rectangle."""
+
+    def __copy__(self) -> Rectangle:
+        ...
+
+
+class Line:
+    """
+    A straight line segment between two points, such as for plotting or annotating.
+
+    Both end points are part of the line. Thicker lines are drawn as a rectangle along the direction of the line,
+    centered on it, with square ends at the end points. End points between pixels are rounded to the nearest pixel
+    the same way as the position of an :class:`.Ellipse`.
+    """
+    start: Point
+    end: Point
+    color: Pixels
+    thickness: int
+    overlay: Optional[OverlayMode]
+    style: StrokeStyle
+
+    def __init__(
+        self,
+        *,
+        start: Point,
+        end: Point,
+        color: Pixel,
+        thickness: int = 1,
+        overlay: Optional[OverlayMode] = None,
+        style: Optional[StrokeStyle] = None
+    ) -> None:
+        """
+        Parameters
+        ----------
+        start: Tuple[float, float]
+            The point the line starts at.
+        end: Tuple[float, float]
+            The point the line ends at.
+        color: :class:`.Pixel`
+            The color of the line.
+        thickness: int, default: 1
+            The thickness of the line in pixels.
+        overlay: Optional[:class:`.OverlayMode`]
+            The overlay mode of the line, defaults to the overlay mode of the image it is drawn on.
+        style: Optional[:class:`.StrokeStyle`]
+            The stroke style of the line, defaults to solid. Dashes and dots are measured from the upper end of the line.
+
+        Raises
+        ------
+        ValueError
+            `thickness` is zero, or an end point rounds to below zero.
+        """
+
+    def copy(self) -> Line:
+        """Returns a copy of this line."""
+
+    def __copy__(self) -> Line:
+        ...
+
+
+class Polygon:
+    """
+    A polygon with any number of vertices, such as a triangle, an arrow or the outline of a region on a map.
+
+    The vertices are the corners of the polygon, where ``(0, 0)`` is the upper-left corner of the upper-left pixel, so the
+    polygon ``[(0, 0), (4, 0), (4, 4), (0, 4)]`` covers the same pixels as a 4x4 :class:`.Rectangle` at ``(0, 0)``.
+    Vertices between pixels are rounded to the nearest pixel the same way as the position of an :class:`.Ellipse`.
+
+    Pixels are filled when their center lies within the polygon. Polygons may be concave or cross themselves,
+    where the areas that overlap an even amount of times are left out, like the center of a pentagram.
+    """
+    vertices: List[Point]
+    fill: Optional[Pixels]
+    border: Optional[Border]
+    overlay: Optional[OverlayMode]
+
+    def __init__(
+        self,
+        *,
+        vertices: List[Point],
+        fill: Optional[Pixel] = None,
+        border: Optional[Border] = None,
+        overlay: Optional[OverlayMode] = None
+    ) -> None:
+        """
+        Parameters
+        ----------
+        vertices: List[Tuple[float, float]]
+            The vertices of the polygon in order, the last one is connected to the first.
+        fill: Optional[:class:`.Pixel`]
+            The color to use for filling the polygon.
+        border: Optional[:class:`.Border`]
+            The border of the polygon. Dashed and dotted borders are measured along the perimeter from the first vertex.
+        overlay: Optional[:class:`.OverlayMode`]
+            The overlay mode of the polygon, defaults to the overlay mode of the image it is drawn on.
+
+        Raises
+        ------
+        ValueError
+            There are fewer than 3 vertices, all vertices lie on a single line, or a vertex rounds to below zero.
+        """
+
+    def copy(self) -> Polygon:
+        """Returns a copy of this polygon."""
+
+    def __copy__(self) -> Polygon:
+        ...
+
+
+class Scene:
+    """
+    A group of entities drawn together in z order, which can be rendered again after any of them change.
+
+    Entities are kept by reference, so changes made to them after they are added show up the next time
+    the scene is drawn. Scenes can be drawn with :meth:`Image.draw` like any other entity, and contain other scenes.
+
+    Examples
+    --------
+
+    .. code-block:: python3
+
+        card = Scene()
+        card.add(Rectangle(position=(0, 0), size=(200, 120), fill=Pixel.from_rgb(30, 30, 30)))
+        card.add(title, z=1)
+
+        small = card.render(200, 120, Pixel.from_rgb(0, 0, 0))
+        title.fill = Pixel.from_rgb(255, 0, 0)
+        highlighted = card.render(200, 120, Pixel.from_rgb(0, 0, 0))
+    """
+    def __init__(self) -> None: ...
+
+    def add(self, entity: Union[Rectangle, Ellipse, Line, Polygon, TextSegment, TextLayout, Scene], z: int = 0) -> None:
+        """
+        Adds an entity to this scene.
+
+        Entities are drawn from the lowest `z` to the highest, and in the order they were added for the same `z`.
+
+        Parameters
+        ----------
+        entity: Union[:class:`.Rectangle`, :class:`.Ellipse`, :class:`.Line`, :class:`.Polygon`, :class:`.TextSegment`, :class:`.TextLayout`, :class:`.Scene`]
+            The entity to add.
+        z: int, default: 0
+            The depth of the entity, higher values are drawn on top.
+
+        Raises
+        ------
+        RuntimeError
+            The entity can't be drawn.
+        ValueError
+            The entity is this scene or a scene containing it.
+        """
+
+    def remove(self, index: int) -> Union[Rectangle, Ellipse, Line, Polygon, TextSegment, TextLayout, Scene]:
+        """
+        Removes the entity at the given index, counted in the order entities were added, and returns it.
+
+        Parameters
+        ----------
+        index: int
+            The index of the entity, negative indices count from the end.
+
+        Raises
+        ------
+        IndexError
+            The index is out of range.
+        """
+
+    def render(self, width: int, height: int, background: Pixel) -> Image:
+        """
+        Renders this scene onto a new image.
+
+        This is the same as drawing the scene onto a new image with :meth:`Image.draw`.
+
+        Parameters
+        ----------
+        width: int
+            The width of the image.
+        height: int
+            The height of the image.
+        background: :class:`.Pixel`
+            The color the image is filled with before drawing, which also decides its mode.
+
+        Returns
+        -------
+        :class:`.Image`
+            The rendered image.
+
+        Raises
+        ------
+        ValueError
+            `width` or `height` is zero.
+        """
+
+    def __len__(self) -> int: ...
+
+    def __iter__(self) -> Iterator[Union[Rectangle, Ellipse, Line, Polygon, TextSegment, TextLayout, Scene]]: ...
+
+
+
+class BitPixel:
+    """Represents a single-bit pixel that represents either a pixel that is on or off."""
+    value: bool
+
+    def __init__(self, value: bool) -> None: ...
+
+
+class L:
+    """
+    Represents an L, or luminance pixel that is stored as only one single number representing how bright, or intense, the pixel is. 
+    
+    This can be thought of as the “unit channel” as this represents only a single channel in which other pixel types can be composed of.
+    """
+    value: int
+
+    def __init__(self, value: int) -> None: ...
+
+
+class Rgb:
+    """Represents an RGB pixel."""
+    r: int
+    g: int
+    b: int
+
+    def __init__(self, r: int, g: int, b: int) -> None: ...
+
+    def relative_luminance(self) -> float:
+        """
+        Returns the relative luminance of this pixel as defined by WCAG 2, see :meth:`Pixel.relative_luminance`.
+
+        Returns
+        -------
+        float
+            The relative luminance, from `0` for black to `1` for white.
+        """
+
+
+class Rgba:
+    """Represents an RGBA pixel."""
+    r: int
+    g: int
+    b: int
+    a: int
+
+    def __init__(self, r: int, g: int, b: int, a: int) -> None: ...
+
+    def relative_luminance(self, background: Optional[Pixel] = None) -> float:
+        """
+        Returns the relative luminance of this pixel as defined by WCAG 2, see :meth:`Pixel.relative_luminance`.
+
+        Parameters
+        ----------
+        background: Optional[:class:`.Pixel`], default: None
+            The color a translucent pixel is seen against, defaults to white. Its alpha is ignored.
+
+        Returns
+        -------
+        float
+            The relative luminance, from `0` for black to `1` for white.
+        """
+
+
+
+class Pixel:
+    """The user created Pixel type."""
+    @classmethod
+    def from_bitpixel(cls, value: bool) -> Pixel:
+        """
+        Create a bitpixel.
+        
+        Parameters
+        ----------
+        value: bool
+            Whether the pixel is on.
+        """
+
+    @classmethod
+    def from_l(cls, value: int) -> Pixel:
+        """
+        Create a L Pixel.
+        
+        Parameters
+        ----------
+        value: int
+            The luminance value of the pixel, between 0 and 255.
+        """
+
+    @classmethod
+    def from_rgb(cls, r: int, g: int, b: int) -> Pixel:
+        """
+        Creates a Rgb Pixel
+        
+        Parameters
+        ----------
+        r: int
+            The red component of the pixel.
+        g: int
+            The green component of the pixel.
+        b: int
+            The blue component of the pixel.
+        """
+
+    @classmethod
+    def from_rgba(cls, r: int, g: int, b: int, a: int) -> Pixel:
+        """
+        Creates a Rgba Pixel
+        
+        Parameters
+        ----------
+        r: int
+            The red component of the pixel.
+        g: int
+            The green component of the pixel.
+        b: int
+            The blue component of the pixel.
+        a: int
+            The alpha component of the pixel.
+        """
+
+    @classmethod
+    def from_name(cls, name: str) -> Pixel:
+        """
+        Creates a Pixel from a CSS color name, such as `rebeccapurple`.
+
+        Names are case-insensitive, and spaces, underscores and hyphens are ignored, so `Rebecca Purple` also works.
+        Every name gives an RGB pixel, except for `transparent` which gives a fully transparent RGBA pixel.
+        See :class:`.Colors` for the same colors as constants.
+
+        Parameters
+        ----------
+        name: str
+            The name of the color.
+
+        Raises
+        ------
+        ValueError
+            The name is not a CSS color name. The message suggests the closest names, if any.
+        """
+
+    def distance(self, other: Pixel, metric: str = 'euclidean') -> float:
+        """
+        Returns the distance between the colors of this pixel and another pixel.
+
+        Both pixels are converted to RGBA first, and their alpha channels are compared like a color channel.
+
+        Parameters
+        ----------
+        other: :class:`.Pixel`
+            The pixel to compare to.
+        metric: str, default: "euclidean"
+            The metric to use, either `euclidean` for the straight distance between the channels,
+            or `redmean` for a cheap approximation of perceived distance that weights the channels by the mean red value.
+
+        Returns
+        -------
+        float
+            The distance, which is `0` for identical colors.
+
+        Raises
+        ------
+        ValueError
+            The metric is invalid.
+        """
+
+    def relative_luminance(self, background: Optional[Pixel] = None) -> float:
+        """
+        Returns the relative luminance of this pixel as defined by WCAG 2, such as for checking the contrast of text.
+
+        The channels are linearized from sRGB and weighted by how bright they appear, see :func:`.contrast_ratio`.
+        A translucent pixel is composited over `background` first.
+
+        Parameters
+        ----------
+        background: Optional[:class:`.Pixel`], default: None
+            The color a translucent pixel is seen against, defaults to white. Its alpha is ignored.
+
+        Returns
+        -------
+        float
+            The relative luminance, from `0` for black to `1` for white.
+        """
+
+
+class Colors:
+    """
+    The CSS named colors as constants, such as ``Colors.WHITE`` or ``Colors.REBECCAPURPLE``.
+
+    Every constant is an RGB :class:`.Pixel`, except for ``Colors.TRANSPARENT`` which is a fully transparent RGBA pixel.
+    See :meth:`Pixel.from_name` to look colors up by name.
+    """
+    TRANSPARENT: Pixel
+    ALICEBLUE: Pixel
+    ANTIQUEWHITE: Pixel
+    AQUA: Pixel
+    AQUAMARINE: Pixel
+    AZURE: Pixel
+    BEIGE: Pixel
+    BISQUE: Pixel
+    BLACK: Pixel
+    BLANCHEDALMOND: Pixel
+    BLUE: Pixel
+    BLUEVIOLET: Pixel
+    BROWN: Pixel
+    BURLYWOOD: Pixel
+    CADETBLUE: Pixel
+    CHARTREUSE: Pixel
+    CHOCOLATE: Pixel
+    CORAL: Pixel
+    CORNFLOWERBLUE: Pixel
+    CORNSILK: Pixel
+    CRIMSON: Pixel
+    CYAN: Pixel
+    DARKBLUE: Pixel
+    DARKCYAN: Pixel
+    DARKGOLDENROD: Pixel
+    DARKGRAY: Pixel
+    DARKGREEN: Pixel
+    DARKGREY: Pixel
+    DARKKHAKI: Pixel
+    DARKMAGENTA: Pixel
+    DARKOLIVEGREEN: Pixel
+    DARKORANGE: Pixel
+    DARKORCHID: Pixel
+    DARKRED: Pixel
+    DARKSALMON: Pixel
+    DARKSEAGREEN: Pixel
+    DARKSLATEBLUE: Pixel
+    DARKSLATEGRAY: Pixel
+    DARKSLATEGREY: Pixel
+    DARKTURQUOISE: Pixel
+    DARKVIOLET: Pixel
+    DEEPPINK: Pixel
+    DEEPSKYBLUE: Pixel
+    DIMGRAY: Pixel
+    DIMGREY: Pixel
+    DODGERBLUE: Pixel
+    FIREBRICK: Pixel
+    FLORALWHITE: Pixel
+    FORESTGREEN: Pixel
+    FUCHSIA: Pixel
+    GAINSBORO: Pixel
+    GHOSTWHITE: Pixel
+    GOLD: Pixel
+    GOLDENROD: Pixel
+    GRAY: Pixel
+    GREEN: Pixel
+    GREENYELLOW: Pixel
+    GREY: Pixel
+    HONEYDEW: Pixel
+    HOTPINK: Pixel
+    INDIANRED: Pixel
+    INDIGO: Pixel
+    IVORY: Pixel
+    KHAKI: Pixel
+    LAVENDER: Pixel
+    LAVENDERBLUSH: Pixel
+    LAWNGREEN: Pixel
+    LEMONCHIFFON: Pixel
+    LIGHTBLUE: Pixel
+    LIGHTCORAL: Pixel
+    LIGHTCYAN: Pixel
+    LIGHTGOLDENRODYELLOW: Pixel
+    LIGHTGRAY: Pixel
+    LIGHTGREEN: Pixel
+    LIGHTGREY: Pixel
+    LIGHTPINK: Pixel
+    LIGHTSALMON: Pixel
+    LIGHTSEAGREEN: Pixel
+    LIGHTSKYBLUE: Pixel
+    LIGHTSLATEGRAY: Pixel
+    LIGHTSLATEGREY: Pixel
+    LIGHTSTEELBLUE: Pixel
+    LIGHTYELLOW: Pixel
+    LIME: Pixel
+    LIMEGREEN: Pixel
+    LINEN: Pixel
+    MAGENTA: Pixel
+    MAROON: Pixel
+    MEDIUMAQUAMARINE: Pixel
+    MEDIUMBLUE: Pixel
+    MEDIUMORCHID: Pixel
+    MEDIUMPURPLE: Pixel
+    MEDIUMSEAGREEN: Pixel
+    MEDIUMSLATEBLUE: Pixel
+    MEDIUMSPRINGGREEN: Pixel
+    MEDIUMTURQUOISE: Pixel
+    MEDIUMVIOLETRED: Pixel
+    MIDNIGHTBLUE: Pixel
+    MINTCREAM: Pixel
+    MISTYROSE: Pixel
+    MOCCASIN: Pixel
+    NAVAJOWHITE: Pixel
+    NAVY: Pixel
+    OLDLACE: Pixel
+    OLIVE: Pixel
+    OLIVEDRAB: Pixel
+    ORANGE: Pixel
+    ORANGERED: Pixel
+    ORCHID: Pixel
+    PALEGOLDENROD: Pixel
+    PALEGREEN: Pixel
+    PALETURQUOISE: Pixel
+    PALEVIOLETRED: Pixel
+    PAPAYAWHIP: Pixel
+    PEACHPUFF: Pixel
+    PERU: Pixel
+    PINK: Pixel
+    PLUM: Pixel
+    POWDERBLUE: Pixel
+    PURPLE: Pixel
+    REBECCAPURPLE: Pixel
+    RED: Pixel
+    ROSYBROWN: Pixel
+    ROYALBLUE: Pixel
+    SADDLEBROWN: Pixel
+    SALMON: Pixel
+    SANDYBROWN: Pixel
+    SEAGREEN: Pixel
+    SEASHELL: Pixel
+    SIENNA: Pixel
+    SILVER: Pixel
+    SKYBLUE: Pixel
+    SLATEBLUE: Pixel
+    SLATEGRAY: Pixel
+    SLATEGREY: Pixel
+    SNOW: Pixel
+    SPRINGGREEN: Pixel
+    STEELBLUE: Pixel
+    TAN: Pixel
+    TEAL: Pixel
+    THISTLE: Pixel
+    TOMATO: Pixel
+    TURQUOISE: Pixel
+    VIOLET: Pixel
+    WHEAT: Pixel
+    WHITE: Pixel
+    WHITESMOKE: Pixel
+    YELLOW: Pixel
+    YELLOWGREEN: Pixel
+
+
+class Frame:
+    """
+    Represents a frame in an image sequence. It encloses :class:`.Image` and extra metadata about the frame.
+
+    New frames have no delay and use :attr:`.DisposalMethod.Keep`, which leaves ghosting behind transparent frames.
+    """
+    def __init__(self, image: Image) -> None:
+        """
+    Parameters
+    ----------
+    image: :class:`.Image`
+        The image used for this frame.
+        """
+
+    @property
+    def delay(self) -> int:
+        """int: Returns the delay duration for this frame in milliseconds."""
+
+    @property
+    def dimensions(self) -> Xy:
+        """Tuple[int, int]: Returns the dimensions of this frame."""
+
+    @property
+    def disposal(self) -> DisposalMethod:
+        """:class:`.DisposalMethod`: Returns the disposal method for this frame."""
+
+    @property
+    def has_transparency(self) -> bool:
+        """
+        bool: Returns whether any pixel of this frame is not fully opaque.
+
+        Only `RGBA` frames can have transparency, and the check stops at the first transparent pixel.
+        """
+
+    @property
+    def image(self) -> Image:
+        """
+        :class:`.Image`: Returns the image this frame contains.
+
+        The image shares its pixels with this frame until either of them is modified, so this is cheap.
+        Changes made to the returned image don't affect this frame, assign it back to replace the image of this frame.
+        """
+
+    @delay.setter
+    def set_delay(self, delay: int) -> None: ...
+
+    @image.setter
+    def set_image(self, image: Image) -> None: ...
+
+    def diff(self, other: Frame) -> Tuple[bool, Optional[Tuple[int, int, int, int]]]:
+        """
+        Compares the pixels of this frame with another frame, ignoring their delays and disposal methods.
+
+        Parameters
+        ----------
+        other: :class:`.Frame`
+            The frame to compare with.
+
+        Returns
+        -------
+        Tuple[bool, Optional[Tuple[int, int, int, int]]]
+            Whether any pixel differs, and the bounding box ``(x1, y1, x2, y2)`` of the differing pixels.
+            The bounding box is `None` if no pixel differs, or if the frames have different dimensions.
+        """
+
+    def __eq__(self, other: object) -> bool:
+        """
+        Frames are equal if their delays, disposal methods and pixels are equal.
+        Frames with different dimensions are never equal.
+        """
+
+    __hash__: None  # type: ignore[assignment]
+
+
+class ImageSequence(Iterator[Frame]):
+    """
+    Represents a sequence of image frames such as an animated image.
+    
+    See :class:`.Image` for the static image counterpart, and see :class:`.Frame` to see how each frame is represented in an image sequence.
+    
+    Iterating through :class:`.ImageSequence` always starts at the first frame, so it can be iterated more than once
+    
+    .. code-block:: python3
+    
+        seq = ImageSequence.from_bytes(bytes)
+        list(seq) # [...]
+        # Doing it again returns the same frames
+        list(seq) # [...]
+    
+    Each iteration uses its own :class:`.ImageSequenceIterator`. ``next(seq)`` instead returns frames from the position
+    of the sequence itself, which can be moved with :meth:`seek` and read with :meth:`tell`.
+    
+    .. note::
+        Frames are copies, so changes made to a :class:`.Frame` are not reflected in the :class:`.ImageSequence`
+        until the frame is assigned back, such as with ``seq[i] = frame``. See also :meth:`insert`, :meth:`append`
+        and :meth:`remove`.
+
+    Examples
+    --------
+
+    .. code-block:: python3
+
+        seq = ImageSequence.open('animation.gif')
+        for i, frame in enumerate(seq):
+            image = frame.image
+            image.draw(watermark)
+            frame.image = image
+            seq[i] = frame
+        seq.save('watermarked.gif')
+    """
+    @classmethod
+    def from_bytes(
+        cls,
+        bytes: bytes,
+        format: Optional[Union[ImageFormat, str]] = None,
+        *,
+        tolerant: bool = False,
+        fill: Optional[Pixel] = None,
+    ) -> ImageSequence:
+        """
+        Decodes a sequence with the explicitly given image encoding from the raw bytes.
+       
+        if `format` is not provided then it will try to infer its encoding.
+        
+        Parameters
+        ----------
+        bytes: bytes
+            The bytes of the image.
+        format: Optional[Union[:class:`.ImageFormat`, str]], default: None
+            The format of the image.
+        tolerant: bool, default: False
+            Whether to keep the frames decoded before the data is cut off or corrupt instead of raising,
+            see :attr:`decode_warnings`. JPEG data is recovered like :meth:`Image.from_bytes` does.
+        fill: Optional[:class:`.Pixel`], default: None
+            Only used with `tolerant`. The color to fill the rows of JPEG data that couldn't be decoded with,
+            defaults to black.
+        
+        Raises
+        ------
+        ValueError
+            The format provided is invalid.
+        UnknownFormatError
+            `format` is not provided and it can't be inferred from the bytes.
+        RuntimeError
+            Failed to decode the image, or not a single frame could be recovered with `tolerant` set.
+
+        .. note::
+            The GIL is released while decoding.
+        """
+
+    @classmethod
+    def peek_frame_count(cls, bytes: bytes) -> int:
+        """
+        Reads the amount of frames of an encoded sequence from its header, without decoding it or allocating its pixels.
+
+        This is useful for cheaply rejecting overly long animations before decoding them with :meth:`from_bytes`.
+        PNG, JPEG, GIF, WebP and BMP data is supported, where anything but animated PNG, GIF and WebP data has 1 frame.
+        GIF data has no frame count in its header, so its blocks are skimmed instead.
+
+        Parameters
+        ----------
+        bytes: bytes
+            The bytes of the image.
+
+        Returns
+        -------
+        int
+            The amount of frames.
+
+        Raises
+        ------
+        UnknownFormatError
+            The format can't be inferred from the bytes.
+        RuntimeError
+            The header is truncated or malformed, or the format is not supported.
+        """
+
+    @classmethod
+    def from_frames(cls, frames: List[Frame], fix_disposal: bool = False, warn: bool = False) -> ImageSequence:
+        """
+        Creates a new image sequence from the given frames
+
+        Transparent frames that use :attr:`.DisposalMethod.Keep` leave ghosting behind.
+        With `fix_disposal` set, they are switched to :attr:`.DisposalMethod.Background`,
+        and their indices are kept in :attr:`adjusted_frames`.
+
+        Parameters
+        ----------
+        frames: List[:class:`Frame`]
+            The list of frames to create the sequence from
+        fix_disposal: bool, default: False
+            Whether to switch the disposal of transparent frames from `Keep` to `Background`.
+        warn: bool, default: False
+            Whether to emit a warning listing the transparent frames that use `Keep`, or that were switched
+            if `fix_disposal` is set.
+        """
+
+    @classmethod
+    def generate(cls, frame_count: int, func: Callable[[int], Image], delay: int = 100) -> ImageSequence:
+        """
+        Creates a new image sequence by calling the given function for every frame, such as for procedural animations.
+
+        The function is called with the index of the frame and must return an :class:`.Image`.
+        All of the returned images must have the same dimensions.
+
+        Parameters
+        ----------
+        frame_count: int
+            The amount of frames to generate.
+        func: Callable[[int], :class:`.Image`]
+            The function that generates a frame from its index.
+        delay: int, default: 100
+            The delay of every frame, in milliseconds.
+
+        Raises
+        ------
+        ValueError
+            `frame_count` is zero or the images don't all have the same dimensions.
+        TypeError
+            `func` returned something other than an :class:`.Image`.
+        RuntimeError
+            `func` raised an exception, which is set as the cause.
+
+        Examples
+        --------
+
+        .. code-block:: python3
+
+            seq = ImageSequence.generate(10, lambda i: Image.new(64, 64, Pixel.from_rgba(255, 0, 0, i * 25)))
+        """
+
+    @classmethod
+    def typewriter(
+        cls,
+        background: Image,
+        font: Font,
+        text: str,
+        *,
+        position: Tuple[int, int],
+        fill: Pixel,
+        chars_per_frame: int = 1,
+        delay: int = 50,
+        size: Optional[float] = None,
+        width: Optional[int] = None,
+        wrap: Optional[WrapStyle] = None,
+    ) -> ImageSequence:
+        """
+        Creates a typewriter animation, where every frame shows more of the text drawn onto a copy of `background`.
+
+        The text is drawn as a :class:`.TextSegment`, so colors of a different mode than the background are converted
+        to its mode as in :meth:`Image.draw`. Since every frame is laid out separately, a word that no longer fits
+        on its line moves to the next one as it is typed.
+
+        Parameters
+        ----------
+        background: :class:`.Image`
+            The image the text is drawn onto, which is left untouched.
+        font: :class:`.Font`
+            The font to render the text with.
+        text: str
+            The text to type.
+        position: Tuple[int, int]
+            The position of the text on the background.
+        fill: :class:`.Pixel`
+            The color of the text.
+        chars_per_frame: int, default: 1
+            The amount of characters typed every frame, which makes ``ceil(len(text) / chars_per_frame)`` frames.
+        delay: int, default: 50
+            The delay of every frame, in milliseconds.
+        size: Optional[float], default: None
+            The size of the text in pixels, defaults to the optimal size of the font.
+        width: Optional[int], default: None
+            The width the text is wrapped at, see :attr:`.TextSegment.width`.
+        wrap: Optional[:class:`.WrapStyle`], default: None
+            How the text is wrapped, defaults to :attr:`.WrapStyle.Word`.
+
+        Raises
+        ------
+        ValueError
+            `text` is empty or `chars_per_frame` is zero.
+
+        Examples
+        --------
+
+        .. code-block:: python3
+
+            seq = ImageSequence.typewriter(background, font, 'Hello, world!', position=(10, 10), fill=Pixel.from_rgb(0, 0, 0))
+        """
+
+    @classmethod
+    def from_spritesheet(
+        cls, image: Image, frame_width: int, frame_height: int, count: Optional[int] = None, delay: int = 100
+    ) -> ImageSequence:
+        """
+        Slices a sprite sheet into a sequence, reading frames left to right, then top to bottom.
+
+        See :meth:`to_spritesheet` for the inverse.
+
+        Parameters
+        ----------
+        image: :class:`.Image`
+            The sprite sheet, its dimensions must be multiples of the frame dimensions.
+        frame_width: int
+            The width of every frame.
+        frame_height: int
+            The height of every frame.
+        count: Optional[int], default: None
+            The amount of frames to read, which leaves out empty cells at the end of the sheet. Defaults to every cell.
+        delay: int, default: 100
+            The delay of every frame, in milliseconds.
+
+        Raises
+        ------
+        ValueError
+            The frame dimensions are zero or don't divide the dimensions of the sheet,
+            or `count` is zero or more than the amount of cells.
+
+        Examples
+        --------
+
+        .. code-block:: python3
+
+            seq = ImageSequence.from_spritesheet(Image.open('walk.png'), 32, 32, count=7)
+        """
+
+    @classmethod
+    def from_numpy(cls, array: Any, delay: int = 100) -> ImageSequence:
+        """
+        Creates a new image sequence from a numpy array of shape ``(frames, height, width, channels)``,
+        such as a batch of video frames.
+
+        The mode of the frames is inferred from the amount of channels: ``1`` for `L`, ``3`` for `RGB`
+        and ``4`` for `RGBA`. See :meth:`to_numpy` for the inverse.
+
+        Parameters
+        ----------
+        array: numpy.ndarray
+            The pixel data of every frame, of dtype `uint8`.
+        delay: int, default: 100
+            The delay of every frame, in milliseconds.
+
+        Raises
+        ------
+        ImportError
+            numpy is not installed.
+        TypeError
+            The array is not of dtype `uint8`.
+        ValueError
+            The array is not four-dimensional, has no frames, pixels, or an unsupported amount of channels.
+
+        Examples
+        --------
+
+        .. code-block:: python3
+
+            seq = ImageSequence.from_numpy(np.zeros((10, 64, 64, 3), dtype=np.uint8), delay=40)
+        """
+
+    @classmethod
+    def open(cls, path: str) -> ImageSequence:
+        """
+        Opens a file from the given path and decodes it into an :class:`.ImageSequence`.
+       
+        The encoding of the image is automatically inferred.
+        You can explicitly pass in an encoding by using the :meth:`from_bytes` method.
+        
+        Parameters
+        ----------
+        path: str
+            The path to the image.
+        
+        Raises
+        ------
+        ValueError
+            The file extension is invalid.
+        RuntimeError
+            Failed to infer file format or Failed to decode image.
+
+        .. note::
+            The GIL is released while reading and decoding the file.
+        """
+
+    @classmethod
+    def open_async(cls, path: str) -> Awaitable[ImageSequence]:
+        """
+        Like :meth:`open`, but reads and decodes the file on a separate thread without blocking the event loop.
+
+        This must be called from a running :mod:`asyncio` event loop.
+
+        Parameters
+        ----------
+        path: str
+            The path to the image.
+
+        Returns
+        -------
+        Awaitable[:class:`.ImageSequence`]
+            A future resolving to the decoded sequence, raising the same errors as :meth:`open`.
+
+        Raises
+        ------
+        RuntimeError
+            There is no running event loop.
+        """
+
+    @staticmethod
+    def supported_encodings(frames: Optional[int] = None) -> List[str]:
+        """
+        Returns the encodings this build of ril can encode image sequences to, such as for picking an export format.
+
+        These are the encodings :meth:`encode` and :meth:`save` accept, and any other encoding raises an
+        :class:`.UnsupportedFormatError`. `png` encodes an animated PNG (APNG). `jpeg` and `webp` can only encode
+        a sequence of a single frame, and `webp` is only supported when ril was built with the `webp` feature,
+        see :data:`WEBP`.
+
+        Parameters
+        ----------
+        frames: Optional[int], default: None
+            The amount of frames of the sequence to encode. If not given, only the encodings that can encode any amount
+            of frames are returned.
+
+        Returns
+        -------
+        List[str]
+            The names of the supported encodings.
+        """
+
+    def encode(self, encoding: Optional[str] = None, strip_metadata: bool = True, workers: Optional[int] = None, options: Optional[EncodeOptions] = None, deterministic: bool = False, reuse_palette: bool = False) -> bytes:
+        """
+        Encodes the image with the given encoding and returns `bytes`.
+        
+        Parameters
+        ----------
+        encoding: Optional[str], default: None
+            The encoding to encode to, defaults to the format of `options`.
+        strip_metadata: bool, default: True
+            Whether to leave out the metadata (EXIF, ICC profiles, text chunks, comments) carried over from the decoded sequence.
+            Metadata is only ever written back to the format it was read from.
+        workers: Optional[int], default: None
+            The amount of threads GIF frames are quantized on, defaults to one per core.
+            This has no effect on other encodings, or unless ril was built with the `parallel` feature, see :data:`PARALLEL`.
+        options: Optional[Union[:class:`.PngOptions`, :class:`.GifOptions`, :class:`.WebPOptions`]], default: None
+            The options to encode with. WebP options can only encode sequences of a single frame.
+        deterministic: bool, default: False
+            Whether to pin the encoder settings so the output is byte-for-byte identical for the same sequence,
+            see :meth:`Image.encode`. Only supported for PNG and GIF.
+        reuse_palette: bool, default: False
+            Whether to write frames decoded from a GIF with their original palettes and indices when encoding GIF,
+            rather than quantizing them again. This avoids bloating a GIF that is decoded and encoded again.
+            Only the frames before the first modified one are reused, since every later frame is drawn over it.
+            Any method that modifies the frames in place, such as :meth:`resize`, counts as modifying all of them.
+            This has no effect on other encodings, or on sequences that weren't decoded from a GIF.
+        
+        Returns
+        -------
+        bytes
+            The encoded bytes.
+
+        Raises
+        ------
+        UnsupportedFormatError
+            The encoding is not one of :meth:`supported_encodings` for the amount of frames of this sequence.
+        ValueError
+            `workers` is 0, neither an encoding nor options are given, or they are for different formats.
+            This is also raised if the output can't be made deterministic, see :meth:`Image.encode`.
+
+        .. note::
+            Every GIF frame is quantized independently, so the output is identical for any amount of workers.
+            The GIL is released while encoding.
+        """
+
+    def save(self, path: str, encoding: Optional[str] = None, strip_metadata: bool = True, options: Optional[EncodeOptions] = None, deterministic: bool = False, reuse_palette: bool = False) -> None:
+        """
+        Saves the image to the given path.
+        If encoding is not provided, it will attempt to infer it by the path/filename's extension
+        You can try saving to a memory buffer by using the :meth:`encode` method.
+        
+        Parameters
+        ----------
+        path: str
+            The path to the image.
+        encoding: Optional[str], default: None
+            The encoding of the image, defaults to `None`.
+        strip_metadata: bool, default: True
+            Whether to leave out the metadata carried over from the decoded sequence, see :meth:`encode`.
+        options: Optional[Union[:class:`.PngOptions`, :class:`.GifOptions`, :class:`.WebPOptions`]], default: None
+            The options to encode with, see :meth:`encode`. If no encoding is given, the format of `options` is used.
+        deterministic: bool, default: False
+            Whether to pin the encoder settings for reproducible output, see :meth:`encode`.
+        reuse_palette: bool, default: False
+            Whether to write unmodified GIF frames with their original palettes, see :meth:`encode`.
+        
+        Raises
+        ------
+        UnsupportedFormatError
+            The encoding, or the file extension if no encoding is given, is not one of :meth:`supported_encodings`
+            for the amount of frames of this sequence.
+        ValueError
+            The encoding and options are for different formats.
+            This is also raised if the output can't be made deterministic, see :meth:`Image.encode`.
+        RuntimeError
+            Failed to infer file format or Failed to decode image.
+
+        .. note::
+            The GIL is released while encoding and writing the file.
+        """
+
+    def save_async(self, path: str, encoding: Optional[str] = None, strip_metadata: bool = True, options: Optional[EncodeOptions] = None, deterministic: bool = False, reuse_palette: bool = False) -> Awaitable[None]:
+        """
+        Like :meth:`save`, but encodes and writes a copy of the sequence on a separate thread without blocking the event loop.
+
+        This must be called from a running :mod:`asyncio` event loop.
+
+        Parameters
+        ----------
+        path: str
+            The path to the image.
+        encoding: Optional[str], default: None
+            The encoding of the image, defaults to `None`.
+        strip_metadata: bool, default: True
+            Whether to leave out the metadata carried over from the decoded sequence, see :meth:`encode`.
+        options: Optional[Union[:class:`.PngOptions`, :class:`.GifOptions`, :class:`.WebPOptions`]], default: None
+            The options to encode with, see :meth:`encode`.
+        deterministic: bool, default: False
+            Whether to pin the encoder settings for reproducible output, see :meth:`encode`.
+        reuse_palette: bool, default: False
+            Whether to write unmodified GIF frames with their original palettes, see :meth:`encode`.
+
+        Returns
+        -------
+        Awaitable[None]
+            A future resolving once the sequence is saved, raising the same errors as :meth:`save`.
+
+        Raises
+        ------
+        RuntimeError
+            There is no running event loop.
+        """
 
-    def __init__(
-        self,
-        position: Xy,
-        size: Xy,
-        border: Optional[Border] = None,
-        fill: Optional[Pixel] = None,
-        overlay: Optional[OverlayMode] = None
-    ) -> None:
+    def seek(self, index: int) -> None:
         """
+        Moves the position of this sequence so that the next frame ``next(seq)`` returns is the frame at `index`.
+
+        The position is separate from iterating over the sequence, which always starts at the first frame.
+
         Parameters
         ----------
-        position: Tuple[int, int]
-            The position of the rectangle
-        size: Tuple[int, int]
-            The size of the rectangle
-        border: Optional[:class:`.Border`]
-            The border of the ellipse.
-        fill: Optional[:class:`.Pixel`]
-            The color to use for filling the rectangle
-        overlay: Optional[:class:`.OverlayMode`]
-            The overlay mode of the rectangle.
+        index: int
+            The index of the next frame to return. Seeking to the length of the sequence exhausts it.
+
+        Raises
+        ------
+        IndexError
+            `index` is greater than the length of the sequence.
         """
 
-    @classmethod
-    def from_bounding_box(cls, x1: int, y1: int, x2: int, y2: int) -> Rectangle:
+    def tell(self) -> int:
         """
-        Creates a new ellipse from the given bounding box.
-       
-        Parameters
-        ----------
-        x1: int
-            The x axis of the upper left corner
-        y1: int
-            The y axis of the upper left corner
-        x2: int
-            The x axis of the lower right corner
-        y2: int
-            The y axis of the lower right corner
-        
+        Returns the index of the next frame ``next(seq)`` returns, see :meth:`seek`.
+
         Returns
         -------
-        :class:`.Rectangle`
-            The newly created rectangle
+        int
         """
 
+    @property
+    def frames(self) -> List[Frame]:
+        """
+        List[:class:`.Frame`]: Returns every frame of this sequence, regardless of its position.
 
-class BitPixel:
-    """Represents a single-bit pixel that represents either a pixel that is on or off."""
-    value: bool
+        A new list of copied frames is returned on every access, so changes made to them are not reflected in this sequence.
+        Create a new sequence with :meth:`from_frames` to apply them.
+        """
 
-    def __init__(self, value: bool) -> None: ...
+    @property
+    def dimensions(self) -> Xy:
+        """
+        Tuple[int, int]: Returns the dimensions of the first frame of this sequence.
 
+        Frames of a sequence usually share their dimensions, unless it was created with :meth:`from_frames`.
 
-class L:
-    """
-    Represents an L, or luminance pixel that is stored as only one single number representing how bright, or intense, the pixel is. 
-    
-    This can be thought of as the “unit channel” as this represents only a single channel in which other pixel types can be composed of.
-    """
-    value: int
+        Raises
+        ------
+        ValueError
+            The sequence has no frames.
+        """
 
-    def __init__(self, value: int) -> None: ...
+    @property
+    def total_duration(self) -> int:
+        """int: Returns the sum of the delays of every frame of this sequence in milliseconds."""
 
+    def palette(self) -> Optional[List[Pixel]]:
+        """
+        Returns the global palette of the data this sequence was decoded from, which is the global color table of GIF data
+        and the palette of indexed PNG data.
 
-class Rgb:
-    """Represents an RGB pixel."""
-    r: int
-    g: int
-    b: int
+        The palette is read as is, it is not updated when the frames of this sequence change.
 
-    def __init__(self, r: int, g: int, b: int) -> None: ...
+        Returns
+        -------
+        Optional[List[:class:`.Pixel`]]
+            The colors of the palette in order, or ``None`` if the data had no global palette or this sequence was not decoded.
+        """
 
+    @property
+    def decode_warnings(self) -> List[str]:
+        """
+        List[str]: Returns the warnings from decoding this sequence with `tolerant` set, describing what was recovered.
 
-class Rgba:
-    """Represents an RGBA pixel."""
-    r: int
-    g: int
-    b: int
-    a: int
+        This is empty if the data decoded cleanly, and for sequences that were not decoded with `tolerant` set.
+        See :meth:`from_bytes`.
+        """
 
-    def __init__(self, r: int, g: int, b: int, a: int) -> None: ...
+    @property
+    def adjusted_frames(self) -> List[int]:
+        """
+        List[int]: Returns the indices of the transparent frames whose disposal was switched to
+        :attr:`.DisposalMethod.Background` when this sequence was created with `fix_disposal` set.
 
+        This is empty if no frame was switched, and for sequences that were not created with :meth:`from_frames`.
+        The indices are not updated when frames are inserted or removed afterwards.
+        """
 
+    def __contains__(self, item: object) -> bool:
+        """
+        Whether any frame has the same dimensions and pixels as the given :class:`.Frame` or :class:`.Image`.
 
-class Pixel:
-    """The user created Pixel type."""
-    @classmethod
-    def from_bitpixel(cls, value: bool) -> Pixel:
+        The delay and disposal of a frame are not compared.
         """
-        Create a bitpixel.
-        
+
+    def crop(self, x1: int, y1: int, x2: int, y2: int) -> None:
+        """
+        Crops every frame of this sequence in place to the given bounding box, see :meth:`Image.crop`.
+
         Parameters
         ----------
-        value: bool
-            Whether the pixel is on.
+        x1: int
+           The x axis of the upper-left corner
+        y1: int
+            The y axis of the upper-left corner
+        x2: int
+            The x axis of the lower-right corner
+        y2: int
+            The y axis of the lower-right corner
+
+        Raises
+        ------
+        ValueError
+            The bounding box is empty or exceeds the bounds of any frame.
         """
 
-    @classmethod
-    def from_l(cls, value: int) -> Pixel:
+    def resize(self, width: int, height: int, algorithm: ResizeAlgorithm) -> None:
         """
-        Create a L Pixel.
-        
+        Resizes every frame of this sequence in place to the given dimensions using the given resizing algorithm.
+
         Parameters
         ----------
-        value: int
-            The luminance value of the pixel, between 0 and 255.
+        width: int
+            The target width to resize to
+        height: int
+            The target height to resize to
+        algorithm: :class:`.ResizeAlgorithm`
+            The resize algorithm to use
+
+        Raises
+        ------
+        ValueError
+            `width` or `height` is zero.
         """
 
-    @classmethod
-    def from_rgb(cls, r: int, g: int, b: int) -> Pixel:
+    def convert(self, mode: Union[Mode, str]) -> None:
         """
-        Creates a Rgb Pixel
-        
+        Converts every frame of this sequence in place to the given mode.
+
         Parameters
         ----------
-        r: int
-            The red component of the pixel.
-        g: int
-            The green component of the pixel.
-        b: int
-            The blue component of the pixel.
+        mode: Union[:class:`.Mode`, str]
+            The mode to convert to.
         """
 
-    @classmethod
-    def from_rgba(cls, r: int, g: int, b: int, a: int) -> Pixel:
+    def mirror(self) -> None:
+        """Mirrors, or flips every frame of this sequence horizontally (about the y-axis) in place."""
+
+    def flip(self) -> None:
+        """Flips every frame of this sequence vertically (about the x-axis) in place."""
+
+    def invert(self) -> None:
+        """Inverts every frame of this sequence in place."""
+
+    def deduplicate(self, merge_delays: bool = True, tolerance: int = 0) -> int:
         """
-        Creates a Rgba Pixel
-        
+        Removes consecutive frames that are identical to the frame before them, such as the still parts of a screen recording.
+
+        Frames only count as identical if they have the same dimensions. The position is moved back to the first frame.
+
         Parameters
         ----------
-        r: int
-            The red component of the pixel.
-        g: int
-            The green component of the pixel.
-        b: int
-            The blue component of the pixel.
-        a: int
-            The alpha component of the pixel.
-        """
-
+        merge_delays: bool, default: True
+            Whether to add the delays of the removed frames onto the frame they duplicate, so the total duration is preserved.
+        tolerance: int, default: 0
+            The largest difference of any channel between two pixels for them to still count as identical,
+            so that visually identical frames are removed too. Pixels of different modes are compared as RGBA when a tolerance is given.
 
-class Frame:
-    """Represents a frame in an image sequence. It encloses :class:`.Image` and extra metadata about the frame."""
-    def __init__(self, image: Image) -> None:
+        Returns
+        -------
+        int
+            The amount of frames removed.
         """
-    Parameters
-    ----------
-    image: :class:`.Image`
-        The image used for this frame.
+
+    def subsequence(self, start: int, stop: Optional[int] = None, step: int = 1) -> ImageSequence:
         """
+        Returns a new sequence of the frames from `start` up to, but not including, `stop`, taking every `step`-th frame.
 
-    @property
-    def delay(self) -> int:
-        """int: Returns the delay duration for this frame."""
+        Negative indices count from the end like they do in slices, and indices out of range are clamped.
+        The loop count and metadata are kept. This is also what slicing the sequence, such as ``seq[10:40]``, does.
 
-    @property
-    def dimensions(self) -> Xy:
-        """Tuple[int, int]: Returns the dimensions of this frame."""
+        Parameters
+        ----------
+        start: int
+            The index of the first frame.
+        stop: Optional[int]
+            The index to stop before. Defaults to the length of the sequence.
+        step: int, default: 1
+            The distance between the indices of the selected frames.
 
-    @property
-    def disposal(self) -> DisposalMethod:
-        """:class:`.DisposalMethod`: Returns the disposal method for this frame."""
+        Returns
+        -------
+        :class:`.ImageSequence`
+            The selected frames.
 
-    @property
-    def image(self) -> Image:
-        """:class:`.Image`: Returns the image this frame contains."""
+        Raises
+        ------
+        ValueError
+            `step` is zero or negative.
+        """
 
-    @delay.setter
-    def set_delay(self, delay: int) -> None: ...
+    def trim(self, start_ms: float, stop_ms: float) -> ImageSequence:
+        """
+        Returns a new sequence of what is shown between the two timestamps, in milliseconds since the first frame.
 
+        Frames are selected by the timestamps their delays add up to. The delays of the first and last frame are shortened
+        to the part of them that lies between the timestamps, so the total duration is ``stop_ms - start_ms``.
+        Timestamps out of range are clamped to the duration of the sequence. The loop count and metadata are kept.
 
-class ImageSequence(Iterator[Frame]):
-    """
-    Represents a sequence of image frames such as an animated image.
-    
-    See :class:`.Image` for the static image counterpart, and see :class:`.Frame` to see how each frame is represented in an image sequence.
-    
-    The iterator is exhausive, so when you iterate through :class:`.ImageSequence` like
-    
-    .. code-block:: python3
-    
-        seq = ImageSequence.from_bytes(bytes)
-        list(seq) # [...]
-        # But if you do it again
-        list(seq) # []
-        # It will return a empty list
-    
-    .. note::
-        Any change made to the :class:`.Frame` will not be reflected to the :class:`.ImageSequence`, so you must create a new :class:`.ImageSequence` after you make changes to the frames.
-    """
-    @classmethod
-    def from_bytes(cls, bytes: bytes, format: Optional[str] = None) -> ImageSequence:
-        """
-        Decodes a sequence with the explicitly given image encoding from the raw bytes.
-       
-        if `format` is not provided then it will try to infer its encoding.
-        
         Parameters
         ----------
-        bytes: bytes
-            The bytes of the image.
-        format: Optional[str], default: None
-            The format of the image.
-        
+        start_ms: float
+            The timestamp to start at.
+        stop_ms: float
+            The timestamp to stop at.
+
+        Returns
+        -------
+        :class:`.ImageSequence`
+            The trimmed sequence.
+
         Raises
         ------
         ValueError
-            The format provided is invalid.
-        RuntimeError
-            Failed to decode the image or Failed to infer the image's format.
+            `stop_ms` is not after `start_ms` once both are clamped.
         """
 
-    @classmethod
-    def from_frames(cls, frames: List[Frame]) -> ImageSequence:
+    @overload
+    def __getitem__(self, index: int) -> Frame: ...
+
+    @overload
+    def __getitem__(self, index: slice) -> ImageSequence: ...
+
+    def __setitem__(self, index: int, frame: Frame) -> None:
         """
-        Creates a new image sequence from the given frames
-        
+        Replaces the frame at the given index in place, negative indices count from the end.
+
+        Frames yielded afterwards, including by an iteration in progress, reflect the change.
+        """
+
+    def insert(self, index: int, frame: Frame) -> None:
+        """
+        Inserts a frame before the given index, like :meth:`list.insert`.
+
+        Negative indices count from the end, and indices out of range insert at the start or the end.
+        The position of the sequence keeps pointing at the frame it pointed at before.
+
         Parameters
         ----------
-        frames: List[:class:`Frame`]
-            The list of frames to create the sequence from
+        index: int
+            The index to insert the frame at.
+        frame: :class:`.Frame`
+            The frame to insert.
+        """
+
+    def append(self, frame: Frame) -> None:
+        """
+        Appends a frame to the end of this sequence.
+
+        Parameters
+        ----------
+        frame: :class:`.Frame`
+            The frame to append.
+        """
+
+    def remove(self, index: int) -> Frame:
+        """
+        Removes the frame at the given index and returns it, like :meth:`list.pop`.
+
+        Negative indices count from the end. The position of the sequence keeps pointing at the frame it pointed at before.
+
+        Parameters
+        ----------
+        index: int
+            The index of the frame to remove.
+
+        Returns
+        -------
+        :class:`.Frame`
+            The removed frame.
+
+        Raises
+        ------
+        IndexError
+            The index is out of range.
         """
 
-    @classmethod
-    def open(cls, path: str) -> ImageSequence:
+    def overlay(self, other: Union[ImageSequence, Image], x: int, y: int, *, loop_shorter: bool = True) -> ImageSequence:
         """
-        Opens a file from the given path and decodes it into an :class:`.ImageSequence`.
-       
-        The encoding of the image is automatically inferred.
-        You can explicitly pass in an encoding by using the :meth:`from_bytes` method.
-        
+        Composites another sequence or a static image over every frame of this sequence, returning a new sequence.
+
+        Every output frame is a frame of this sequence with the corresponding frame of `other` pasted at the given position,
+        like :meth:`Image.paste`. The output has as many frames as the longer of the two,
+        and its delays, disposals and loop count come from this sequence.
+
         Parameters
         ----------
-        path: str
-            The path to the image.
-        
+        other: Union[:class:`.ImageSequence`, :class:`.Image`]
+            The sequence or image to paste. An image is pasted onto every frame.
+        x: int
+            The x axis
+        y: int
+            The y axis
+        loop_shorter: bool, default: True
+            Whether the shorter of the two sequences loops, instead of freezing on its last frame.
+
+        Returns
+        -------
+        :class:`.ImageSequence`
+            The composited sequence.
+
         Raises
         ------
+        TypeError
+            `other` is neither a sequence nor an image.
         ValueError
-            The file extension is invalid.
-        RuntimeError
-            Failed to infer file format or Failed to decode image.
+            Either sequence is empty.
+
+        Examples
+        --------
+
+        .. code-block:: python3
+
+            animated = background.overlay(ImageSequence.open('sticker.gif'), 10, 10)
         """
 
-    def encode(self, encoding: str) -> bytes:
+    def to_spritesheet(self, columns: int = 1) -> Image:
         """
-        Encodes the image with the given encoding and returns `bytes`.
-        
+        Lays the frames of this sequence out into a sprite sheet, left to right, then top to bottom.
+
+        Cells left over in the last row are transparent, or black for modes without alpha.
+        The sheet has the mode of the first frame, and delays and disposals are not kept.
+        See :meth:`from_spritesheet` for the inverse.
+
         Parameters
         ----------
-        encoding: str
-            The encoding to encode to.
-        
+        columns: int, default: 1
+            The amount of frames per row, the default lays the frames out in a vertical strip.
+            More columns than there are frames lay the frames out in a single row.
+
         Returns
         -------
-        bytes
-            The encoded bytes.
+        :class:`.Image`
+            The sprite sheet.
+
+        Raises
+        ------
+        ValueError
+            `columns` is zero, the sequence is empty, its frames don't all have the same dimensions,
+            or the sheet would be wider or higher than 4294967295 pixels.
         """
 
-    def save(self, path: str, encoding: Optional[str] = None) -> None:
+    def to_numpy(self, mode: Union[Mode, str] = 'RGBA') -> Any:
         """
-        Saves the image to the given path.
-        If encoding is not provided, it will attempt to infer it by the path/filename's extension
-        You can try saving to a memory buffer by using the :meth:`encode` method.
-        
+        Copies the frames of this sequence into a numpy array of shape ``(frames, height, width, channels)``,
+        such as for feeding them to a video model.
+
+        Frames are converted to `mode`, where `L` and `BITPIXEL` frames have a single channel and a
+        :class:`.BitPixel` is either ``0`` or ``255``. See :meth:`from_numpy` for the inverse.
+
         Parameters
         ----------
-        path: str
-            The path to the image.
-        
+        mode: Union[:class:`.Mode`, str], default: 'RGBA'
+            The mode to convert the frames to.
+
+        Returns
+        -------
+        numpy.ndarray
+            A new array of dtype `uint8`.
+
         Raises
         ------
+        ImportError
+            numpy is not installed.
         ValueError
-            The file extension is invalid.
-        RuntimeError
-            Failed to infer file format or Failed to decode image.
+            The sequence is empty, or its frames don't all have the same dimensions.
+        """
+
+    def _repr_html_(self) -> str:
+        """
+        Returns an ``<img>`` tag embedding the sequence as a GIF, used by Jupyter to display the sequence inline.
+
+        Frames larger than the preview size are downscaled first, see :func:`set_notebook_preview_size`.
+        """
+
+    def close(self) -> None:
+        """
+        Releases the frames of this sequence right away instead of when it is garbage collected, see :meth:`Image.close`.
+
+        Every other method and attribute of a closed sequence, except :attr:`closed`, raises a `ValueError`.
+        Frames and images taken from the sequence before closing it are unaffected.
+
+        Sequences can also be used as context managers, which close them on exit.
+
+        Examples
+        --------
+
+        .. code-block:: python3
+
+            with ImageSequence.open('large.gif') as seq:
+                seq.resize(128, 128, ResizeAlgorithm.Nearest)
+                seq.save('small.gif')
+        """
+
+    @property
+    def closed(self) -> bool:
+        """bool: Whether the sequence is closed, see :meth:`close`."""
+
+    def __enter__(self) -> ImageSequence: ...
+
+    def __exit__(self, exc_type: Optional[type], exc_value: Optional[BaseException], traceback: Optional[object]) -> None: ...
+
+    def __iter__(self) -> ImageSequenceIterator:
         """
+        Returns a new iterator over every frame of this sequence, starting at the first frame.
+        """
+
+    def __next__(self) -> Frame:
+        """
+        Returns the frame at the position of this sequence and advances it, see :meth:`seek`.
+        """
+
+class ImageSequenceIterator(Iterator[Frame]):
+    """
+    An iterator over the frames of an image sequence, returned when iterating through :class:`.ImageSequence`.
+
+    Every iterator starts at the first frame and advances independently of other iterators and of :meth:`ImageSequence.seek`.
+    Frames are read from the sequence as they are yielded, so frames replaced, inserted or removed during iteration are reflected.
+    """
 
-    def __iter__(self) -> ImageSequence: ...
+    def __iter__(self) -> ImageSequenceIterator: ...
 
     def __next__(self) -> Frame: ...
 
@@ -760,11 +4338,17 @@ class TextSegment:
         font: Font,
         text: str,
         fill: Pixel,
-        position: Optional[Tuple[int, int]] = None,
+        position: Optional[Tuple[float, float]] = None,
         size: Optional[float] = None,
         overlay: Optional[OverlayMode] = None,
         width: Optional[int] = None,
         wrap: Optional[WrapStyle] = None,
+        tab_width: int = 4,
+        render_mode: str = 'smooth',
+        mono_threshold: int = 128,
+        missing_glyph_policy: str = 'replace',
+        replacement_char: Optional[str] = None,
+        shadow: Optional[Shadow] = None,
     ) -> None:
         """
         Parameters
@@ -775,8 +4359,9 @@ class TextSegment:
             The text to render.
         fill: :class:`Pixel`
             The fill color the text will be in.
-        position: Optional[Tuple[int, int]]
-            The position the text will be rendered at.
+        position: Optional[Tuple[float, float]]
+            The position the text will be rendered at, rounded to the nearest pixel the same way as the position of
+            an :class:`.Ellipse`. Glyphs are always placed on whole pixels, also in the `smooth` render mode.
 
             **This must be set before adding any text segments!**
 
@@ -790,14 +4375,37 @@ class TextSegment:
         wrap: Optional[:class:`WrapStyle`]
             The wrapping style of the text. Note that text will only wrap if `width` is set.
             If this is used in a :class:`TextLayout`, this is ignored and :attr:`.WrapStyle.Wrap` is used instead.
+        tab_width: int, default: 4
+            The amount of spaces a tab expands to.
+            If this is used in a :class:`TextLayout`, this is ignored and :attr:`TextLayout.tab_width` is used instead.
+        render_mode: str, default: "smooth"
+            How glyphs are rendered, either `smooth` for anti-aliased text or `mono` for crisp text,
+            where pixels are either fully drawn with the fill color or not drawn at all.
+            If this is used in a :class:`TextLayout`, this is ignored and :attr:`TextLayout.render_mode` is used instead.
+        mono_threshold: int, default: 128
+            The glyph coverage, out of 255, at or above which a pixel is drawn when `render_mode` is `mono`.
+        missing_glyph_policy: str, default: "replace"
+            What to do with characters the font has no glyph for, one of `skip` to leave them out,
+            `replace` to render `replacement_char` in their place, or `error` to raise a :exc:`ValueError` when drawing.
+            If this is used in a :class:`TextLayout`, this is ignored and :attr:`TextLayout.missing_glyph_policy` is used instead.
+        replacement_char: Optional[str]
+            The character rendered in place of missing glyphs when `missing_glyph_policy` is `replace`, such as ``"□"``.
+            If not given, the missing glyph of the font is rendered.
+        shadow: Optional[:class:`.Shadow`]
+            The drop shadow of the text, drawn below it by blurring and offsetting what the text covers.
+            The shadow of a :class:`TextLayout` is used for every segment added to it instead.
+
+        Line breaks are normalized, ``"\r\n"`` and ``"\r"`` are rendered as ``"\n"``.
+
+        Text segments compare equal if all of their fields are equal, where fonts are compared by identity.
        
         .. warning::
             As this class contains the data of an entire font, copying this class is expensive.
         """
     
     @property
-    def position(self) -> Tuple[int, int]:
-        """Tuple[int, int]: The position of the text segment."""
+    def position(self) -> Tuple[float, float]:
+        """Tuple[float, float]: The position of the text segment."""
     
     @property
     def width(self) -> Optional[int]:
@@ -814,12 +4422,7 @@ class TextSegment:
     
     @property
     def font(self) -> Font:
-        """
-        :class:`Font`: The font of the text segment.
-       
-        .. warning::
-            Due to design limitation, accessing font requires a deep clone each time, which is expensive.
-        """
+        """:class:`Font`: The font of the text segment."""
     
     @property
     def fill(self) -> Pixels:
@@ -836,9 +4439,33 @@ class TextSegment:
     @property
     def wrap(self) -> WrapStyle:
         """:class:`WrapStyle`: The wrapping style of the text segment."""
+
+    @property
+    def tab_width(self) -> int:
+        """int: The amount of spaces a tab expands to."""
+
+    @property
+    def render_mode(self) -> str:
+        """str: How glyphs are rendered, either `smooth` or `mono`."""
+
+    @property
+    def mono_threshold(self) -> int:
+        """int: The glyph coverage at or above which a pixel is drawn when :attr:`render_mode` is `mono`."""
+
+    @property
+    def missing_glyph_policy(self) -> str:
+        """str: What to do with characters the font has no glyph for, either `skip`, `replace` or `error`."""
+
+    @property
+    def replacement_char(self) -> Optional[str]:
+        """Optional[str]: The character rendered in place of missing glyphs when :attr:`missing_glyph_policy` is `replace`."""
+
+    @property
+    def shadow(self) -> Optional[Shadow]:
+        """Optional[:class:`.Shadow`]: The drop shadow of the text."""
     
     @position.setter
-    def set_position(self, position: Tuple[int, int]) -> None:
+    def set_position(self, position: Tuple[float, float]) -> None:
         ...
     
     @width.setter
@@ -869,6 +4496,37 @@ class TextSegment:
     def set_wrap(self, wrap: WrapStyle) -> None:
         ...
 
+    @tab_width.setter
+    def set_tab_width(self, tab_width: int) -> None:
+        ...
+
+    @render_mode.setter
+    def set_render_mode(self, render_mode: str) -> None:
+        ...
+
+    @mono_threshold.setter
+    def set_mono_threshold(self, mono_threshold: int) -> None:
+        ...
+
+    @missing_glyph_policy.setter
+    def set_missing_glyph_policy(self, missing_glyph_policy: str) -> None:
+        ...
+
+    @replacement_char.setter
+    def set_replacement_char(self, replacement_char: Optional[str]) -> None:
+        ...
+
+    @shadow.setter
+    def set_shadow(self, shadow: Optional[Shadow]) -> None:
+        ...
+
+    def copy(self) -> TextSegment:
+        """Returns a copy of this text segment, which shares its :class:`.Font`."""
+
+    def __copy__(self) -> TextSegment:
+        ...
+
+
 
 class TextLayout:
     """
@@ -890,6 +4548,12 @@ class TextLayout:
         horizontal_anchor: Optional[HorizontalAnchor] = None,
         vertical_anchor: Optional[VerticalAnchor] = None,
         wrap: Optional[WrapStyle] = None,
+        tab_width: int = 4,
+        render_mode: str = 'smooth',
+        mono_threshold: int = 128,
+        missing_glyph_policy: str = 'replace',
+        replacement_char: Optional[str] = None,
+        shadow: Optional[Shadow] = None,
     ) -> None:
         """
         Parameters
@@ -911,15 +4575,40 @@ class TextLayout:
            Sets the wrapping style of the text. Make sure to also set the wrapping width using :attr:`width` for wrapping to work.
 
             **This must be set before adding any text segments!**
+        tab_width: int, default: 4
+            The amount of spaces a tab expands to, overriding the tab width of the added text segments.
+
+            **This must be set before adding any text segments!**
+        render_mode: str, default: "smooth"
+            How glyphs are rendered, overriding the render mode of the added text segments, see :attr:`TextSegment.render_mode`.
+        mono_threshold: int, default: 128
+            The glyph coverage at or above which a pixel is drawn when `render_mode` is `mono`.
+        missing_glyph_policy: str, default: "replace"
+            What to do with characters the font has no glyph for, overriding the policy of the added text segments,
+            see :attr:`TextSegment.missing_glyph_policy`. With `error`, adding text with missing glyphs raises a :exc:`ValueError`.
+
+            **This must be set before adding any text segments!**
+        replacement_char: Optional[str]
+            The character rendered in place of missing glyphs when `missing_glyph_policy` is `replace`.
+        shadow: Optional[:class:`.Shadow`]
+            The drop shadow of the text, overriding the shadow of the added text segments, see :attr:`TextSegment.shadow`.
+            Unlike most other settings, this can be changed after adding text segments.
+
+        Line breaks are normalized, ``"\r\n"`` and ``"\r"`` are laid out as ``"\n"``.
        
         .. warning::
             As this class contains the data of one or more font(s), copying this class can be extremely expensive.
         """
     
-    def center(self) -> None:
+    def centered(self) -> TextLayout:
         """
         Sets the horizontal anchor and vertial anchor of the text to be centered. 
         This makes the position of the text be the center as opposed to the top-left corner.
+
+        Returns
+        -------
+        :class:`.TextLayout`
+            This text layout, for chaining.
         """
     
     @property
@@ -927,6 +4616,38 @@ class TextLayout:
         """
         Tuple[int, int, int, int]: Returns the bounding box of the text. 
         Left and top bounds are inclusive; right and bottom bounds are exclusive.
+
+        This leaves out the shadow of the text, see :meth:`get_bounding_box` to include it.
+        """
+
+    def get_bounding_box(self, include_effects: bool = False) -> Tuple[int, int, int, int]:
+        """
+        Returns the bounding box of the text, like :attr:`bounding_box`.
+
+        Parameters
+        ----------
+        include_effects: bool, default: False
+            Whether to grow the bounding box to also cover the area painted by the :attr:`shadow` of the text.
+
+        Returns
+        -------
+        Tuple[int, int, int, int]
+            The bounding box. Left and top bounds are inclusive; right and bottom bounds are exclusive.
+        """
+
+    def get_dimensions(self, include_effects: bool = False) -> Tuple[int, int]:
+        """
+        Returns the width and height of the text, like :attr:`dimensions`.
+
+        Parameters
+        ----------
+        include_effects: bool, default: False
+            Whether to also cover the area painted by the :attr:`shadow` of the text.
+
+        Returns
+        -------
+        Tuple[int, int]
+            The width and height.
         """
     
     @property
@@ -934,8 +4655,8 @@ class TextLayout:
         """
         Tuple[int, int]: Returns the width and height of the text.
         
-        .. warning::
-            This is a slightly expensive operation and is not a simple getter.
+        .. note::
+            This is measured once and cached until text is added or a setting changes.
         
         .. note::
             If you want both width and height, use :attr:`dimensions`.
@@ -946,8 +4667,8 @@ class TextLayout:
         """
         int: Returns the height of the text.
         
-        .. warning::
-            This is a slightly expensive operation and is not a simple getter.
+        .. note::
+            This is measured once and cached until text is added or a setting changes.
         
         .. note::
             If you want both width and height, use :attr:`dimensions`.
@@ -958,17 +4679,142 @@ class TextLayout:
         """
         int: Returns the width of the text.
         
-        .. warning::
-           This is a slightly expensive operation and is not a simple getter.
+        .. note::
+            This is measured once and cached until text is added or a setting changes.
         
         .. note::
            If you want both width and height, use :attr:`dimensions`.
         """
 
+    @property
+    def line_count(self) -> int:
+        """int: Returns the amount of lines the text takes up, after wrapping."""
+
+    def lines(self) -> List[Tuple[int, int, int, int]]:
+        """
+        Returns the bounding box of every line of the text, after wrapping and anchoring.
+        Left and top bounds are inclusive; right and bottom bounds are exclusive.
+
+        Returns
+        -------
+        List[Tuple[int, int, int, int]]
+            The bounding boxes, from the first line to the last.
+        """
+
+    @property
+    def tab_width(self) -> int:
+        """int: The amount of spaces a tab expands to."""
+
+    @tab_width.setter
+    def set_tab_width(self, tab_width: int) -> None:
+        """
+        Sets the amount of spaces a tab expands to.
+
+        **This must be set before adding any text segments!**
+        """
+
+    @property
+    def render_mode(self) -> str:
+        """
+        str: How glyphs are rendered, either `smooth` or `mono`.
+
+        Unlike most other settings, this can be changed after adding text segments.
+        """
+
+    @render_mode.setter
+    def set_render_mode(self, render_mode: str) -> None:
+        """Sets how glyphs are rendered."""
+
+    @property
+    def mono_threshold(self) -> int:
+        """int: The glyph coverage at or above which a pixel is drawn when :attr:`render_mode` is `mono`."""
+
+    @mono_threshold.setter
+    def set_mono_threshold(self, mono_threshold: int) -> None:
+        """Sets the glyph coverage at or above which a pixel is drawn when :attr:`render_mode` is `mono`."""
+
+    @property
+    def missing_glyph_policy(self) -> str:
+        """str: What to do with characters the font has no glyph for, either `skip`, `replace` or `error`."""
+
+    @missing_glyph_policy.setter
+    def set_missing_glyph_policy(self, missing_glyph_policy: str) -> None:
+        """
+        Sets what to do with characters the font has no glyph for.
+
+        **This must be set before adding any text segments!**
+        """
+
+    @property
+    def replacement_char(self) -> Optional[str]:
+        """Optional[str]: The character rendered in place of missing glyphs when :attr:`missing_glyph_policy` is `replace`."""
+
+    @replacement_char.setter
+    def set_replacement_char(self, replacement_char: Optional[str]) -> None:
+        """
+        Sets the character rendered in place of missing glyphs.
+
+        **This must be set before adding any text segments!**
+        """
+
+    @property
+    def shadow(self) -> Optional[Shadow]:
+        """Optional[:class:`.Shadow`]: The drop shadow of the text."""
+
+    @shadow.setter
+    def set_shadow(self, shadow: Optional[Shadow]) -> None:
+        ...
+
+    @property
+    def position(self) -> Tuple[int, int]:
+        """Tuple[int, int]: The position the text is rendered at, which the anchors are relative to."""
+
+    @position.setter
+    def set_position(self, position: Tuple[int, int]) -> None:
+        """
+        Sets the position of the text layout.
+
+        **This must be set before adding any text segments!**
+        """
+
+    @property
+    def horizontal_anchor(self) -> HorizontalAnchor:
+        """:class:`.HorizontalAnchor`: The horizontal anchor of the text layout."""
+
+    @horizontal_anchor.setter
+    def set_horizontal_anchor(self, anchor: HorizontalAnchor) -> None:
+        """Sets the horizontal anchor of the text layout."""
+
+    @property
+    def vertical_anchor(self) -> VerticalAnchor:
+        """:class:`.VerticalAnchor`: The vertical anchor of the text layout."""
+
+    @vertical_anchor.setter
+    def set_vertical_anchor(self, anchor: VerticalAnchor) -> None:
+        """Sets the vertical anchor of the text layout."""
+
+    @property
+    def anchor(self) -> Tuple[HorizontalAnchor, VerticalAnchor]:
+        """Tuple[:class:`.HorizontalAnchor`, :class:`.VerticalAnchor`]: The horizontal and vertical anchor of the text layout."""
+
+    @property
+    def is_poisoned(self) -> bool:
+        """bool: Whether a panic while this text layout was being changed left it unusable, see :meth:`recover`."""
+
+    def recover(self) -> None:
+        """
+        Makes this text layout usable again after a panic poisoned it, which makes every other use of it raise
+        a :exc:`RuntimeError`. Its text is laid out again from the segments added so far, with the same settings.
+
+        This does nothing if the text layout isn't poisoned, see :attr:`is_poisoned`.
+        """
+
 
 class Font:
     """
     Represents a single font along with its alternatives used to render text. Currently, this supports TrueType and OpenType fonts.
+
+    Fonts can be pickled, the original font data is kept around and shared between copies of the font.
     """
     @classmethod
     def open(cls, path: str, optimal_size: float) -> Font:
@@ -1037,6 +4883,72 @@ class Font:
             It is best to set this to the size that will likely be the most used.
         """
 
+    def prewarm(self, text: str, size: float) -> None:
+        """
+        Rasterizes the glyphs of all characters in `text` at the given size ahead of time,
+        so that the first draw using them doesn't have to.
+
+        Rasterized glyphs are shared between copies of the font, and the least recently used glyphs of all fonts
+        are evicted once they take up more than 32 MiB.
+
+        Parameters
+        ----------
+        text: str
+            The characters to rasterize.
+        size: float
+            The size to rasterize them at, in pixels.
+        """
+
+    def cache_stats(self) -> Dict[str, int]:
+        """
+        Returns statistics about the rasterized glyphs cached for this font.
+
+        Returns
+        -------
+        Dict[str, int]
+            The amount of cached glyphs under ``"glyphs"``, and the approximate memory they take up in bytes under ``"bytes"``.
+        """
+
+    def missing_glyphs(self, text: str) -> List[str]:
+        """
+        Returns the characters of `text` this font has no glyph for, in the order they first appear.
+        Whitespace is never reported as missing.
+
+        Parameters
+        ----------
+        text: str
+            The text to check.
+
+        Returns
+        -------
+        List[str]
+            The missing characters, each listed once.
+        """
+
+
+M: TypeAlias = Mode
+
+
+class Mode:
+    """The pixel mode of an image."""
+    BitPixel: M
+    L: M
+    Rgb: M
+    Rgba: M
+
+
+F: TypeAlias = ImageFormat
+
+
+class ImageFormat:
+    """The encoding format of an image."""
+    Png: F
+    Jpeg: F
+    Gif: F
+    WebP: F
+    Bmp: F
+    Tiff: F
+
 
 R: TypeAlias = ResizeAlgorithm
 
@@ -0,0 +1,50 @@
+use std::thread;
+
+use pyo3::{prelude::*, types::PyCFunction};
+
+use crate::error::Error;
+
+/// Runs `work` on a new thread without holding the GIL and returns an :class:`asyncio.Future`
+/// of its result, which is resolved on the running event loop once the work is done.
+///
+/// Fails if there is no running event loop. If the future was cancelled in the meantime, the
+/// result is dropped.
+pub fn spawn_blocking<T, F>(py: Python<'_>, work: F) -> PyResult<&PyAny>
+where
+    T: IntoPy<PyObject> + Send + 'static,
+    F: FnOnce() -> Result<T, Error> + Send + 'static,
+{
+    let event_loop = py.import("asyncio")?.call_method0("get_running_loop")?;
+    let future = event_loop.call_method0("create_future")?;
+
+    let (event_loop_ref, future_ref): (PyObject, PyObject) = (event_loop.into(), future.into());
+
+    thread::spawn(move || {
+        let result = work();
+
+        Python::with_gil(|py| {
+            let (method, value) = match result {
+                Ok(value) => ("set_result", value.into_py(py)),
+                Err(err) => ("set_exception", PyErr::from(err).into_value(py).into_py(py)),
+            };
+
+            let resolve = PyCFunction::new_closure(py, None, None, move |args, _| -> PyResult<()> {
+                let future = future_ref.as_ref(args.py());
+
+                if !future.call_method0("done")?.is_true()? {
+                    future.call_method1(method, (value.clone_ref(args.py()),))?;
+                }
+
+                Ok(())
+            });
+
+            // The event loop may have been closed while the work was running, in which case
+            // nobody is waiting for the result anymore.
+            if let Ok(resolve) = resolve {
+                let _ = event_loop_ref.call_method1(py, "call_soon_threadsafe", (resolve,));
+            }
+        });
+    });
+
+    Ok(future)
+}
@@ -0,0 +1,162 @@
+use std::cmp::Reverse;
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+use ril::{Dynamic, Image as RilImage};
+
+use crate::image::{paste_clipped, Image};
+use crate::types::Mode;
+use crate::utils::ensure_open;
+use crate::Xy;
+
+/// A placement within an atlas, given as the upper-left and lower-right corners ``(x1, y1, x2, y2)``.
+type Rect = (u32, u32, u32, u32);
+
+/// Packs the given images into a single atlas image, such as a texture atlas of sprites.
+///
+/// Images are placed tallest first on shelves that are filled from left to right, and a new shelf is started
+/// below once an image doesn't fit into the width of the current one. Equally tall images keep their order,
+/// so the same images are always packed the same way.
+///
+/// The atlas has the richest mode of the images, and the space not covered by any image is transparent.
+/// Images are copied into the atlas as is, converted to its mode, without blending.
+///
+/// Parameters
+/// ----------
+/// images: List[:class:`.Image`]
+///     The images to pack, there must be at least one.
+/// max_width: int
+///     The maximum width of the atlas.
+/// padding: int, default: 0
+///     The amount of pixels left empty between neighboring images.
+/// power_of_two: bool, default: False
+///     Whether to round the width and height of the atlas up to powers of two, such as for older GPUs.
+///     Images are then packed into the largest power of two not exceeding `max_width`.
+///
+/// Returns
+/// -------
+/// Tuple[:class:`.Image`, List[Tuple[int, int, int, int]]]
+///     The atlas, and the bounding box ``(x1, y1, x2, y2)`` each image was placed at, in the order of `images`.
+///
+/// Raises
+/// ------
+/// ValueError
+///     No images are given, or an image is wider than the atlas can be. The index of that image is named.
+///
+/// Examples
+/// --------
+///
+/// .. code-block:: python3
+///
+///     atlas, rects = ril.pack_atlas(sprites, max_width=1024, padding=2)
+#[pyfunction]
+#[pyo3(signature = (images, *, max_width, padding = 0, power_of_two = false))]
+#[pyo3(text_signature = "(images, *, max_width, padding = 0, power_of_two = False)")]
+pub fn pack_atlas(
+    py: Python<'_>,
+    images: Vec<Image>,
+    max_width: u32,
+    padding: u32,
+    power_of_two: bool,
+) -> PyResult<(Image, Vec<Rect>)> {
+    for image in &images {
+        ensure_open!(image);
+    }
+
+    let mode = match images.iter().map(|image| Mode::of(&image.inner)).max() {
+        Some(mode) => mode,
+        None => return Err(PyValueError::new_err("Expected at least one image to pack")),
+    };
+
+    let max_width = if power_of_two {
+        max_width
+            .checked_ilog2()
+            .map_or(0, |exponent| 1 << exponent)
+    } else {
+        max_width
+    };
+
+    let sizes = images
+        .iter()
+        .map(|image| image.inner.dimensions())
+        .collect::<Vec<_>>();
+
+    if let Some((index, (width, _))) = sizes
+        .iter()
+        .enumerate()
+        .find(|(_, (width, _))| *width > max_width)
+    {
+        return Err(PyValueError::new_err(format!(
+            "The image at index {} is {} pixels wide, which exceeds the maximum atlas width of {}",
+            index, width, max_width
+        )));
+    }
+
+    let (positions, (width, height)) = pack_shelves(&sizes, max_width, padding);
+    let (width, height) = if power_of_two {
+        (width.next_power_of_two(), height.next_power_of_two())
+    } else {
+        (width, height)
+    };
+
+    let atlas = py.allow_threads(|| {
+        let mut atlas = RilImage::new(
+            width,
+            height,
+            mode.convert_pixel(Dynamic::Rgba(ril::Rgba {
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 0,
+            })),
+        );
+
+        for (image, &(x, y)) in images.iter().zip(&positions) {
+            if Mode::of(&image.inner) == mode {
+                paste_clipped(&mut atlas, x, y, &image.inner, ril::OverlayMode::Replace);
+            } else {
+                let image = mode.convert_image(RilImage::clone(&image.inner));
+                paste_clipped(&mut atlas, x, y, &image, ril::OverlayMode::Replace);
+            }
+        }
+
+        atlas
+    });
+
+    let rects = positions
+        .iter()
+        .zip(&sizes)
+        .map(|(&(x, y), &(width, height))| (x, y, x + width, y + height))
+        .collect();
+
+    Ok((Image::from_inner(atlas), rects))
+}
+
+/// Places rectangles of the given sizes on shelves no wider than `max_width`, tallest first.
+///
+/// Returns the upper-left corner of every rectangle in the order of `sizes`, and the size of the area they cover.
+/// Every rectangle must fit into `max_width` on its own.
+fn pack_shelves(sizes: &[Xy], max_width: u32, padding: u32) -> (Vec<Xy>, Xy) {
+    let mut order = (0..sizes.len()).collect::<Vec<_>>();
+    // The sort is stable, which keeps equally tall rectangles in order.
+    order.sort_by_key(|&index| Reverse(sizes[index].1));
+
+    let mut positions = vec![(0, 0); sizes.len()];
+    let (mut x, mut y, mut shelf_height, mut width) = (0_u32, 0_u32, 0, 0);
+
+    for index in order {
+        let (w, h) = sizes[index];
+
+        if x > 0 && x.saturating_add(w) > max_width {
+            y = y.saturating_add(shelf_height).saturating_add(padding);
+            x = 0;
+            shelf_height = 0;
+        }
+
+        positions[index] = (x, y);
+        width = width.max(x + w);
+        shelf_height = shelf_height.max(h);
+        x = x.saturating_add(w).saturating_add(padding);
+    }
+
+    (positions, (width, y.saturating_add(shelf_height)))
+}
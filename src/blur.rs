@@ -0,0 +1,78 @@
+use crate::parallel::for_each_row;
+
+/// The radii of three successive box blurs that together approximate a gaussian blur with the given standard deviation.
+///
+/// Box widths are picked from the two odd widths around the ideal one, so that the variances of the boxes add up to
+/// the variance of the gaussian as closely as possible.
+pub fn gaussian_box_radii(sigma: f32) -> [usize; 3] {
+    let variance = 12.0 * f64::from(sigma).powi(2);
+    let ideal = (variance / 3.0 + 1.0).sqrt().min(f64::from(u32::MAX));
+
+    let lower = (ideal.floor() as usize - 1) | 1;
+    let width = lower as f64;
+    let lower_count = ((variance - 3.0 * width * width - 12.0 * width - 9.0) / (-4.0 * width - 4.0))
+        .round() as usize;
+
+    [0, 1, 2].map(|pass| {
+        let width = if pass < lower_count { lower } else { lower + 2 };
+        width / 2
+    })
+}
+
+/// Blurs the row-major grid in place with a box blur of each of the given radii in turn, on both axes.
+/// Two or more passes of the same radius approximate a gaussian blur.
+///
+/// Values past the edges are clamped. Every pass keeps a running sum of its window, so it takes the same time
+/// for any radius.
+pub fn box_blur(grid: &mut [f32], width: usize, radii: &[usize]) {
+    let height = grid.len() / width;
+    let mut scratch = vec![0.0; grid.len()];
+
+    for &radius in radii.iter().filter(|&&radius| radius > 0) {
+        box_blur_rows(grid, &mut scratch, width, radius);
+        box_blur_columns(&scratch, grid, width, height, radius);
+    }
+}
+
+/// The sum of the window of `radius` around the first of `len` values, where indices past either end are clamped.
+fn first_window_sum(len: usize, radius: usize, value: impl Fn(usize) -> f64) -> f64 {
+    let last = radius.min(len - 1);
+    let clamped = (radius - last) as f64 * value(len - 1) + radius as f64 * value(0);
+
+    (0..=last).map(value).sum::<f64>() + clamped
+}
+
+fn box_blur_rows(src: &[f32], dst: &mut [f32], width: usize, radius: usize) {
+    let norm = 1.0 / (2 * radius + 1) as f64;
+
+    for_each_row(dst, width, |y, row| {
+        let src = &src[y * width..][..width];
+        let at = |x: usize| f64::from(src[x.min(width - 1)]);
+        let mut sum = first_window_sum(width, radius, &at);
+
+        for (x, value) in row.iter_mut().enumerate() {
+            *value = (sum * norm) as f32;
+            sum += at(x + radius + 1) - at(x.saturating_sub(radius));
+        }
+    });
+}
+
+fn box_blur_columns(src: &[f32], dst: &mut [f32], width: usize, height: usize, radius: usize) {
+    let norm = 1.0 / (2 * radius + 1) as f64;
+    let row = |y: usize| &src[y.min(height - 1) * width..][..width];
+
+    let mut sums = (0..width)
+        .map(|x| first_window_sum(height, radius, |y| f64::from(row(y)[x])))
+        .collect::<Vec<_>>();
+
+    for (y, out) in dst.chunks_mut(width).enumerate() {
+        let (added, removed) = (row(y + radius + 1), row(y.saturating_sub(radius)));
+
+        for (((value, sum), &added), &removed) in
+            out.iter_mut().zip(&mut sums).zip(added).zip(removed)
+        {
+            *value = (*sum * norm) as f32;
+            *sum += f64::from(added) - f64::from(removed);
+        }
+    }
+}
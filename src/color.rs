@@ -0,0 +1,211 @@
+use std::fmt::Display;
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+use ril::Dynamic;
+
+use crate::types::Mode;
+
+/// The transfer function the color channels of an image are encoded with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorSpace {
+    #[default]
+    Srgb,
+    Linear,
+}
+
+impl ColorSpace {
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        match name.to_lowercase().as_str() {
+            "srgb" => Ok(Self::Srgb),
+            "linear" => Ok(Self::Linear),
+            _ => Err(PyValueError::new_err(format!(
+                "Invalid color space `{}`, expected one of `srgb` or `linear`",
+                name
+            ))),
+        }
+    }
+
+    /// Builds the lookup table that converts channel values from this color space to `target`.
+    pub fn lut(self, target: Self) -> [u8; 256] {
+        let transfer: fn(f32) -> f32 = match (self, target) {
+            (Self::Srgb, Self::Linear) => srgb_to_linear,
+            (Self::Linear, Self::Srgb) => linear_to_srgb,
+            _ => |value| value,
+        };
+
+        let mut lut = [0; 256];
+        for (value, out) in lut.iter_mut().enumerate() {
+            *out = (transfer(value as f32 / 255.0) * 255.0).round().clamp(0.0, 255.0) as u8;
+        }
+
+        lut
+    }
+}
+
+impl Display for ColorSpace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Srgb => f.write_str("srgb"),
+            Self::Linear => f.write_str("linear"),
+        }
+    }
+}
+
+/// The relative luminance of normalized sRGB channels as defined by WCAG 2, from 0 for black to 1 for white.
+pub fn relative_luminance([r, g, b]: [f64; 3]) -> f64 {
+    let linear = |value: f64| f64::from(srgb_to_linear(value as f32));
+
+    0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+}
+
+/// The WCAG 2 contrast ratio between two relative luminances, from 1 for equal luminances to 21 for black and white.
+pub fn contrast_ratio(a: f64, b: f64) -> f64 {
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// The sRGB EOTF, decoding a normalized sRGB value to linear light.
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// The sRGB OETF, encoding a normalized linear light value to sRGB.
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Lookup tables for the channels of a pixel, where a missing table leaves its channel untouched.
+///
+/// `L` pixels are mapped through the table of their only channel, `l`.
+/// BitPixels are left as is, since they have no channel a table could apply to.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ChannelLuts {
+    pub l: Option<[u8; 256]>,
+    pub r: Option<[u8; 256]>,
+    pub g: Option<[u8; 256]>,
+    pub b: Option<[u8; 256]>,
+    pub a: Option<[u8; 256]>,
+}
+
+impl ChannelLuts {
+    /// Maps every color channel through `lut`, leaving alpha untouched.
+    pub fn color(lut: [u8; 256]) -> Self {
+        Self {
+            l: Some(lut),
+            r: Some(lut),
+            g: Some(lut),
+            b: Some(lut),
+            a: None,
+        }
+    }
+
+    /// Returns the table for the channel of the given name, or `None` if no pixel of `mode` has that channel.
+    pub fn channel_mut(&mut self, mode: Mode, name: &str) -> Option<&mut Option<[u8; 256]>> {
+        match (mode, name) {
+            (Mode::L, "l") => Some(&mut self.l),
+            (Mode::Rgb | Mode::Rgba, "r") => Some(&mut self.r),
+            (Mode::Rgb | Mode::Rgba, "g") => Some(&mut self.g),
+            (Mode::Rgb | Mode::Rgba, "b") => Some(&mut self.b),
+            (Mode::Rgba, "a") => Some(&mut self.a),
+            _ => None,
+        }
+    }
+
+    /// Builds the tables that map the distribution of every color channel of `source` onto the distribution of
+    /// the same channel of `reference`, by matching their cumulative histograms. Alpha is left untouched.
+    ///
+    /// Both must yield pixels of the same mode. Channels without any values in either of them are left untouched.
+    pub fn matching(
+        source: impl IntoIterator<Item = Dynamic>,
+        reference: impl IntoIterator<Item = Dynamic>,
+    ) -> Self {
+        let (source, reference) = (histograms(source), histograms(reference));
+        let [l, r, g, b] =
+            [0, 1, 2, 3].map(|channel| match_cdf(&source[channel], &reference[channel]));
+
+        Self {
+            l,
+            r,
+            g,
+            b,
+            a: None,
+        }
+    }
+
+    pub fn map(&self, pixel: Dynamic) -> Dynamic {
+        let map = |lut: &Option<[u8; 256]>, value: u8| lut.map_or(value, |lut| lut[value as usize]);
+
+        match pixel {
+            Dynamic::BitPixel(_) => pixel,
+            Dynamic::L(ril::L(value)) => Dynamic::L(ril::L(map(&self.l, value))),
+            Dynamic::Rgb(ril::Rgb { r, g, b }) => Dynamic::Rgb(ril::Rgb {
+                r: map(&self.r, r),
+                g: map(&self.g, g),
+                b: map(&self.b, b),
+            }),
+            Dynamic::Rgba(ril::Rgba { r, g, b, a }) => Dynamic::Rgba(ril::Rgba {
+                r: map(&self.r, r),
+                g: map(&self.g, g),
+                b: map(&self.b, b),
+                a: map(&self.a, a),
+            }),
+        }
+    }
+}
+
+/// Counts the values of the `l`, `r`, `g` and `b` channels of the given pixels, in that order.
+fn histograms(pixels: impl IntoIterator<Item = Dynamic>) -> [[u64; 256]; 4] {
+    let mut histograms = [[0; 256]; 4];
+
+    for pixel in pixels {
+        match pixel {
+            Dynamic::BitPixel(_) => (),
+            Dynamic::L(ril::L(value)) => histograms[0][value as usize] += 1,
+            Dynamic::Rgb(ril::Rgb { r, g, b }) | Dynamic::Rgba(ril::Rgba { r, g, b, .. }) => {
+                histograms[1][r as usize] += 1;
+                histograms[2][g as usize] += 1;
+                histograms[3][b as usize] += 1;
+            }
+        }
+    }
+
+    histograms
+}
+
+/// Maps every value to the smallest value whose cumulative frequency in `reference` reaches its cumulative frequency in `source`.
+fn match_cdf(source: &[u64; 256], reference: &[u64; 256]) -> Option<[u8; 256]> {
+    let cumulative = |histogram: &[u64; 256]| {
+        let mut total = 0;
+        histogram.map(|count| {
+            total += count;
+            total
+        })
+    };
+    let (source, reference) = (cumulative(source), cumulative(reference));
+    let (source_total, reference_total) = (source[255], reference[255]);
+
+    if source_total == 0 || reference_total == 0 {
+        return None;
+    }
+
+    // Frequencies are compared by cross-multiplying, which keeps them exact.
+    let mut lut = [0; 256];
+    let mut target = 0;
+    for (value, out) in lut.iter_mut().enumerate() {
+        let wanted = u128::from(source[value]) * u128::from(reference_total);
+        while target < 255 && u128::from(reference[target]) * u128::from(source_total) < wanted {
+            target += 1;
+        }
+
+        *out = target as u8;
+    }
+
+    Some(lut)
+}
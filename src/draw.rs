@@ -1,22 +1,32 @@
-use std::{fmt::Display, marker::PhantomData};
+use std::{
+    collections::hash_map::DefaultHasher,
+    fmt::Display,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    panic::{self, AssertUnwindSafe},
+};
 
 use pyo3::{
-    exceptions::{PyRuntimeError, PyValueError},
+    exceptions::{PyIndexError, PyRuntimeError, PyValueError},
     prelude::*,
-    types::PyType,
+    pyclass::CompareOp,
+    types::{IntoPyDict, PyIterator, PyList, PyTuple, PyType},
 };
 use ril::{
     draw::{
         Border as RilBorder, BorderPosition as RilBorderPosition, Ellipse as RilEllipse,
         Rectangle as RilRectangle,
     },
-    Dynamic,
+    Draw as _, Dynamic, Image as RilImage, Pixel as _,
 };
 
 use crate::{
+    blur::box_blur,
+    error::Error,
+    image::{draw_reconciled, Image},
     pixels::Pixel,
-    utils::{cast_pixel_to_pyobject},
-    Xy, text::{TextSegment, TextLayout}, types::OverlayMode,
+    utils::{cast_pixel_to_pyobject, compare_eq, ensure_dimensions},
+    Point, Xy, text::{TextSegment, TextLayout}, types::{Mode, OverlayMode},
 };
 
 fn get_border_position(position: &str) -> PyResult<RilBorderPosition> {
@@ -39,6 +49,257 @@ fn from_border_position(position: RilBorderPosition) -> String {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Stroke {
+    Solid,
+    Dashed { length: u32, gap: u32 },
+    Dotted { spacing: u32 },
+}
+
+/// The style of a stroke, such as a border, which can be solid, dashed or dotted.
+///
+/// Use one of :meth:`solid`, :meth:`dashed` or :meth:`dotted` to create a stroke style.
+///
+/// Stroke styles are immutable, so they can be compared and hashed.
+#[pyclass]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StrokeStyle {
+    inner: Stroke,
+}
+
+#[pymethods]
+impl StrokeStyle {
+    /// Creates a solid stroke style.
+    #[classmethod]
+    #[pyo3(text_signature = "($cls)")]
+    fn solid(_: &PyType) -> Self {
+        Self::default()
+    }
+
+    /// Creates a dashed stroke style.
+    ///
+    /// Dashes continue around the corners of rectangles and polygons.
+    ///
+    /// Parameters
+    /// ----------
+    /// length: int
+    ///     The length of each dash, in pixels.
+    /// gap: int
+    ///     The length of the gap between dashes, in pixels.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     `length` or `gap` is zero.
+    #[classmethod]
+    #[pyo3(text_signature = "($cls, length, gap)")]
+    fn dashed(_: &PyType, length: u32, gap: u32) -> PyResult<Self> {
+        if length == 0 || gap == 0 {
+            return Err(PyValueError::new_err(
+                "The dash length and gap must be at least 1",
+            ));
+        }
+
+        Ok(Self {
+            inner: Stroke::Dashed { length, gap },
+        })
+    }
+
+    /// Creates a dotted stroke style, with a single pixel dot every `spacing` pixels.
+    ///
+    /// On the borders of rectangles and polygons, a dot is drawn on every corner and the dots of each side
+    /// are laid out from there, so the last gap before a corner can be up to twice as long.
+    ///
+    /// Parameters
+    /// ----------
+    /// spacing: int
+    ///     The distance between the start of each dot, in pixels.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     `spacing` is zero.
+    #[classmethod]
+    #[pyo3(text_signature = "($cls, spacing)")]
+    fn dotted(_: &PyType, spacing: u32) -> PyResult<Self> {
+        if spacing == 0 {
+            return Err(PyValueError::new_err("The dot spacing must be at least 1"));
+        }
+
+        Ok(Self {
+            inner: Stroke::Dotted { spacing },
+        })
+    }
+
+    /// str: The kind of stroke, one of `solid`, `dashed` or `dotted`.
+    #[getter]
+    fn get_kind(&self) -> &'static str {
+        match self.inner {
+            Stroke::Solid => "solid",
+            Stroke::Dashed { .. } => "dashed",
+            Stroke::Dotted { .. } => "dotted",
+        }
+    }
+
+    /// Optional[int]: The length of each dash, or None if the stroke is not dashed.
+    #[getter]
+    fn get_length(&self) -> Option<u32> {
+        match self.inner {
+            Stroke::Dashed { length, .. } => Some(length),
+            _ => None,
+        }
+    }
+
+    /// Optional[int]: The gap between dashes, or None if the stroke is not dashed.
+    #[getter]
+    fn get_gap(&self) -> Option<u32> {
+        match self.inner {
+            Stroke::Dashed { gap, .. } => Some(gap),
+            _ => None,
+        }
+    }
+
+    /// Optional[int]: The spacing between dots, or None if the stroke is not dotted.
+    #[getter]
+    fn get_spacing(&self) -> Option<u32> {
+        match self.inner {
+            Stroke::Dotted { spacing } => Some(spacing),
+            _ => None,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        match self.inner {
+            Stroke::Solid => "<StrokeStyle solid>".to_string(),
+            Stroke::Dashed { length, gap } => {
+                format!("<StrokeStyle dashed length={} gap={}>", length, gap)
+            }
+            Stroke::Dotted { spacing } => format!("<StrokeStyle dotted spacing={}>", spacing),
+        }
+    }
+
+    fn __richcmp__(&self, py: Python<'_>, other: &PyAny, op: CompareOp) -> PyObject {
+        compare_eq(py, self, other, op)
+    }
+
+    fn __hash__(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+
+        hasher.finish()
+    }
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        Self {
+            inner: Stroke::Solid,
+        }
+    }
+}
+
+impl StrokeStyle {
+    pub fn is_solid(&self) -> bool {
+        self.inner == Stroke::Solid
+    }
+
+    /// Whether the stroke is drawn at the given distance along its path.
+    pub fn is_on(&self, distance: u64) -> bool {
+        match self.inner {
+            Stroke::Solid => true,
+            Stroke::Dashed { length, gap } => {
+                distance % (u64::from(length) + u64::from(gap)) < u64::from(length)
+            }
+            Stroke::Dotted { spacing } => distance % u64::from(spacing) == 0,
+        }
+    }
+
+    /// Whether the stroke is drawn at a point of a closed path with corners, given its distance along the path,
+    /// its offset from the corner that starts its edge, and the offset of the corner that ends it.
+    ///
+    /// Dashes continue across corners. Dots are laid out from each corner instead, with a dot on every corner and
+    /// the last dot of an edge left out if it would be closer than `spacing` to the next corner, so that no two dots
+    /// end up next to each other where edges meet.
+    pub fn is_on_corner(&self, distance: u64, offset: u64, end: u64) -> bool {
+        match self.inner {
+            Stroke::Dotted { spacing } => {
+                let spacing = u64::from(spacing);
+                offset >= end || (offset % spacing == 0 && offset + spacing <= end)
+            }
+            _ => self.is_on(distance),
+        }
+    }
+
+    /// Strokes the border of the rectangle at `position` with the given `size`.
+    ///
+    /// Every pixel of the border belongs to the side of the outer edge it is closest to, and is mapped to a distance
+    /// along the perimeter of the outer edge, clockwise from the top-left corner, so that corners are only visited
+    /// once and the pattern lines up across the thickness.
+    pub fn stroke_rectangle(
+        &self,
+        image: &mut RilImage,
+        position: Xy,
+        size: Xy,
+        border: &RilBorder<Dynamic>,
+        mode: ril::OverlayMode,
+    ) {
+        let thickness = i64::from(border.thickness);
+        if thickness == 0 || size.0 == 0 || size.1 == 0 {
+            return;
+        }
+
+        let offset = match border.position {
+            RilBorderPosition::Inset => 0,
+            RilBorderPosition::Center => thickness / 2,
+            RilBorderPosition::Outset => thickness,
+        };
+        let (x0, y0) = (i64::from(position.0) - offset, i64::from(position.1) - offset);
+        let (x1, y1) = (
+            i64::from(position.0) + i64::from(size.0) - 1 + offset,
+            i64::from(position.1) + i64::from(size.1) - 1 + offset,
+        );
+        let (w, h) = (x1 - x0, y1 - y0);
+
+        let (width, height) = (i64::from(image.width()), i64::from(image.height()));
+        let mut plot = |x: i64, y: i64| {
+            if x < 0 || y < 0 || x >= width || y >= height {
+                return;
+            }
+
+            // The distance along the perimeter, the offset from the corner starting the side, and its length.
+            let (top, right, bottom, left) = (y - y0, x1 - x, y1 - y, x - x0);
+            let nearest = top.min(right).min(bottom).min(left);
+            let (distance, offset, edge) = if top == nearest {
+                (left, left, w)
+            } else if right == nearest {
+                (w + top, top, h)
+            } else if bottom == nearest {
+                (w + h + right, right, w)
+            } else {
+                (2 * w + h + bottom, bottom, h)
+            };
+            // The square where two sides overlap is the corner itself.
+            let corner =
+                (top < thickness || bottom < thickness) && (left < thickness || right < thickness);
+            let offset = if corner { edge } else { offset };
+
+            if self.is_on_corner(distance as u64, offset as u64, edge as u64) {
+                let pixel = &mut image.data[(y * width + x) as usize];
+                *pixel = pixel.overlay_with_alpha(border.color, mode, 255);
+            }
+        };
+
+        for y in y0..=y1 {
+            if y < y0 + thickness || y > y1 - thickness {
+                (x0..=x1).for_each(|x| plot(x, y));
+            } else {
+                (x0..(x0 + thickness).min(x1 + 1)).for_each(|x| plot(x, y));
+                ((x1 - thickness + 1).max(x0 + thickness)..=x1).for_each(|x| plot(x, y));
+            }
+        }
+    }
+}
+
 /// Represents a shape border.
 ///
 /// Parameters
@@ -49,6 +310,8 @@ fn from_border_position(position: RilBorderPosition) -> String {
 ///     The thickness of the border
 /// position: str
 ///     The position of the border
+/// style: Optional[:class:`.StrokeStyle`]
+///     The stroke style of the border, defaults to solid.
 ///
 /// Raises
 /// ------
@@ -56,16 +319,22 @@ fn from_border_position(position: RilBorderPosition) -> String {
 ///     The position is not one of `inset`, `center`, or `outset`
 #[pyclass]
 #[derive(Clone)]
-#[pyo3(text_signature = "(color, thickness, position)")]
+#[pyo3(text_signature = "(*, color, thickness, position, style = None)")]
 pub struct Border {
     pub inner: RilBorder<Dynamic>,
+    pub style: StrokeStyle,
 }
 
 #[pymethods]
 impl Border {
     #[new]
-    #[args("*", color, thickness, position)]
-    fn new(color: Pixel, thickness: u32, position: &str) -> PyResult<Self> {
+    #[pyo3(signature = (*, color, thickness, position, style = None))]
+    fn new(
+        color: Pixel,
+        thickness: u32,
+        position: &str,
+        style: Option<StrokeStyle>,
+    ) -> PyResult<Self> {
         let position = get_border_position(position)?;
 
         Ok(Self {
@@ -74,6 +343,7 @@ impl Border {
                 thickness,
                 position,
             },
+            style: style.unwrap_or_default(),
         })
     }
 
@@ -95,6 +365,12 @@ impl Border {
         from_border_position(self.inner.position)
     }
 
+    /// :class:`.StrokeStyle`: The stroke style of the border.
+    #[getter]
+    fn get_style(&self) -> StrokeStyle {
+        self.style
+    }
+
     #[setter]
     fn set_color(&mut self, pixel: Pixel) {
         self.inner.color = pixel.inner;
@@ -112,22 +388,294 @@ impl Border {
         Ok(())
     }
 
+    #[setter]
+    fn set_style(&mut self, style: StrokeStyle) {
+        self.style = style;
+    }
+
+    /// Returns a copy of this border.
+    #[pyo3(text_signature = "($self)")]
+    fn copy(&self) -> Self {
+        self.clone()
+    }
+
+    fn __copy__(&self) -> Self {
+        self.clone()
+    }
+
+    fn __richcmp__(&self, py: Python<'_>, other: &PyAny, op: CompareOp) -> PyObject {
+        compare_eq(py, self, other, op)
+    }
+
     fn __repr__(&self) -> String {
         format!(
-            "<Border color={} thickness={} position={}>",
+            "<Border color={} thickness={} position={} style={}>",
             self.get_color(),
             self.get_thickness(),
-            self.get_position()
+            self.get_position(),
+            self.style.__repr__()
         )
     }
 }
 
+impl Border {
+    fn from_inner(inner: RilBorder<Dynamic>, style: StrokeStyle) -> Self {
+        Self { inner, style }
+    }
+
+    /// Ellipses are drawn by ril, which only supports solid borders.
+    fn ensure_solid(&self, shape: &str) -> PyResult<()> {
+        if !self.style.is_solid() {
+            return Err(PyValueError::new_err(format!(
+                "{} borders only support the solid stroke style",
+                shape
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl PartialEq for Border {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner.color == other.inner.color
+            && self.inner.thickness == other.inner.thickness
+            && self.get_position() == other.get_position()
+            && self.style == other.style
+    }
+}
+
 impl Display for Border {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(&self.__repr__())
     }
 }
 
+/// A soft shadow or glow of a shape, see the `inner_shadow` and `outer_glow` options of :class:`.Rectangle` and :class:`.Ellipse`,
+/// or the drop shadow of text, see the `shadow` option of :class:`.TextSegment` and :class:`.TextLayout`.
+///
+/// The shape is blurred, offset, and blended onto the image with the color of the shadow,
+/// where the alpha of an RGBA color scales the opacity of the whole shadow.
+///
+/// Parameters
+/// ----------
+/// color: :class:`.Pixel`
+///     The color of the shadow.
+/// blur: int, default: 4
+///     How far the shadow fades out, in pixels. Zero gives a hard edge.
+/// offset: Tuple[int, int], default: (0, 0)
+///     How far the shadow is moved on each axis, in pixels.
+#[pyclass]
+#[derive(Clone, PartialEq)]
+#[pyo3(text_signature = "(*, color, blur = 4, offset = (0, 0))")]
+pub struct Shadow {
+    pub color: Dynamic,
+    pub blur: u32,
+    pub offset: (i32, i32),
+}
+
+#[pymethods]
+impl Shadow {
+    #[new]
+    #[pyo3(signature = (*, color, blur = 4, offset = (0, 0)))]
+    fn new(color: Pixel, blur: u32, offset: (i32, i32)) -> Self {
+        Self {
+            color: color.inner,
+            blur,
+            offset,
+        }
+    }
+
+    /// :class:`.Pixel`: The color of the shadow.
+    #[getter]
+    fn get_color(&self) -> Pixel {
+        self.color.into()
+    }
+
+    /// int: How far the shadow fades out, in pixels.
+    #[getter]
+    fn get_blur(&self) -> u32 {
+        self.blur
+    }
+
+    /// Tuple[int, int]: How far the shadow is moved on each axis, in pixels.
+    #[getter]
+    fn get_offset(&self) -> (i32, i32) {
+        self.offset
+    }
+
+    #[setter]
+    fn set_color(&mut self, color: Pixel) {
+        self.color = color.inner;
+    }
+
+    #[setter]
+    fn set_blur(&mut self, blur: u32) {
+        self.blur = blur;
+    }
+
+    #[setter]
+    fn set_offset(&mut self, offset: (i32, i32)) {
+        self.offset = offset;
+    }
+
+    /// Returns a copy of this shadow.
+    #[pyo3(text_signature = "($self)")]
+    fn copy(&self) -> Self {
+        self.clone()
+    }
+
+    fn __copy__(&self) -> Self {
+        self.clone()
+    }
+
+    fn __richcmp__(&self, py: Python<'_>, other: &PyAny, op: CompareOp) -> PyObject {
+        compare_eq(py, self, other, op)
+    }
+
+    fn __getnewargs_ex__(&self, py: Python<'_>) -> ((), PyObject) {
+        let kwargs = [
+            ("color", self.get_color().into_py(py)),
+            ("blur", self.blur.into_py(py)),
+            ("offset", self.offset.into_py(py)),
+        ];
+
+        ((), kwargs.into_py_dict(py).into())
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "<Shadow color={} blur={} offset=({}, {})>",
+            self.get_color(),
+            self.blur,
+            self.offset.0,
+            self.offset.1
+        )
+    }
+}
+
+impl Display for Shadow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.__repr__())
+    }
+}
+
+impl Shadow {
+    /// Blends the shadow of the shape onto the image. `bounds` is the bounding box ``(x1, y1, x2, y2)``
+    /// of the shape and `contains` whether a pixel lies within it.
+    ///
+    /// An inner shadow is cast by everything outside of the shape and only drawn inside of it,
+    /// an outer glow the other way around.
+    fn draw(
+        &self,
+        image: &mut RilImage,
+        bounds: (i64, i64, i64, i64),
+        contains: impl Fn(i64, i64) -> bool,
+        inner: bool,
+    ) {
+        let (ox, oy) = (i64::from(self.offset.0), i64::from(self.offset.1));
+        let pad = i64::from(self.blur) + ox.abs().max(oy.abs()) + 1;
+        let (x0, y0) = (bounds.0 - pad, bounds.1 - pad);
+        let width = (bounds.2 - bounds.0 + 2 * pad) as usize;
+        let height = (bounds.3 - bounds.1 + 2 * pad) as usize;
+
+        let mut mask = vec![0.0; width * height];
+        for (i, value) in mask.iter_mut().enumerate() {
+            let (x, y) = (x0 + (i % width) as i64, y0 + (i / width) as i64);
+            *value = if contains(x, y) != inner { 1.0 } else { 0.0 };
+        }
+        box_blur(&mut mask, width, &[(self.blur / 2) as usize; 2]);
+
+        // Past the padding, the mask is what it is at its edges.
+        let outside = if inner { 1.0 } else { 0.0 };
+        let (image_width, image_height) = image.dimensions();
+
+        for y in y0.max(0)..(y0 + height as i64).min(i64::from(image_height)) {
+            for x in x0.max(0)..(x0 + width as i64).min(i64::from(image_width)) {
+                if contains(x, y) != inner {
+                    continue;
+                }
+
+                let (mx, my) = (x - ox - x0, y - oy - y0);
+                let value = if mx < 0 || my < 0 || mx >= width as i64 || my >= height as i64 {
+                    outside
+                } else {
+                    mask[my as usize * width + mx as usize]
+                };
+
+                let alpha = (value * 255.0).round() as u8;
+                if alpha > 0 {
+                    let pixel = &mut image.data[(y * i64::from(image_width) + x) as usize];
+                    *pixel = pixel.overlay_with_alpha(self.color, ril::OverlayMode::Merge, alpha);
+                }
+            }
+        }
+    }
+
+    /// Blends the shadow cast by the given coverage, the position and alpha of every covered pixel, onto the image.
+    ///
+    /// Unlike the shadows of shapes, this is not clipped to either side of what casts it,
+    /// which is expected to be drawn over it.
+    pub(crate) fn draw_coverage(&self, image: &mut RilImage, coverage: &[(i64, i64, u8)]) {
+        let bounds = coverage.iter().fold(None, |bounds, &(x, y, _)| {
+            Some(match bounds {
+                Some((x1, y1, x2, y2)) => (x.min(x1), y.min(y1), x.max(x2), y.max(y2)),
+                None => (x, y, x, y),
+            })
+        });
+        let (x1, y1, x2, y2) = match bounds {
+            Some(bounds) => bounds,
+            None => return,
+        };
+
+        let pad = i64::from(self.blur) + 1;
+        let (x0, y0) = (x1 - pad, y1 - pad);
+        let width = (x2 - x1 + 1 + 2 * pad) as usize;
+        let height = (y2 - y1 + 1 + 2 * pad) as usize;
+
+        let mut mask = vec![0.0; width * height];
+        for &(x, y, alpha) in coverage {
+            let value = &mut mask[(y - y0) as usize * width + (x - x0) as usize];
+            *value = f32::max(*value, f32::from(alpha) / 255.0);
+        }
+        box_blur(&mut mask, width, &[(self.blur / 2) as usize; 2]);
+
+        let (ox, oy) = (i64::from(self.offset.0), i64::from(self.offset.1));
+        let (image_width, image_height) = image.dimensions();
+
+        for (i, value) in mask.iter().enumerate() {
+            let x = x0 + ox + (i % width) as i64;
+            let y = y0 + oy + (i / width) as i64;
+            if x < 0 || y < 0 || x >= i64::from(image_width) || y >= i64::from(image_height) {
+                continue;
+            }
+
+            let alpha = (value * 255.0).round() as u8;
+            if alpha > 0 {
+                let pixel = &mut image.data[(y * i64::from(image_width) + x) as usize];
+                *pixel = pixel.overlay_with_alpha(self.color, ril::OverlayMode::Merge, alpha);
+            }
+        }
+    }
+
+    /// Grows the bounding box ``(x1, y1, x2, y2)`` of what casts this shadow to also cover the shadow.
+    pub(crate) fn extend_bounds(
+        &self,
+        (x1, y1, x2, y2): (u32, u32, u32, u32),
+    ) -> (u32, u32, u32, u32) {
+        let spread = i64::from(self.blur);
+        let (ox, oy) = (i64::from(self.offset.0), i64::from(self.offset.1));
+        let clamp = |value: i64| value.clamp(0, i64::from(u32::MAX)) as u32;
+
+        (
+            x1.min(clamp(i64::from(x1) + ox - spread)),
+            y1.min(clamp(i64::from(y1) + oy - spread)),
+            x2.max(clamp(i64::from(x2) + ox + spread)),
+            y2.max(clamp(i64::from(y2) + oy + spread)),
+        )
+    }
+}
+
 /// An ellipse, which could be a circle.
 ///
 /// .. warning::
@@ -136,39 +684,73 @@ impl Display for Border {
 ///
 ///     This also does not set any border or fill for the ellipse, you must explicitly set either one of them.
 ///
+/// The position and radii may lie between pixels, such as for smooth animations. They are rounded to the nearest pixel
+/// when drawn, where values halfway between two pixels round to the even one, so ``10.5`` rounds to ``10`` and ``11.5`` to ``12``.
+///
+/// Coordinates are kept as 32-bit floats, which can't hold a fraction past ``2 ** 24`` (16777216), so larger
+/// coordinates are only kept to the nearest float and lose any part between pixels.
+///
 /// Parameters
 /// ---------
-/// position: Tuple[int, int]
+/// position: Tuple[float, float]
 ///     The position of the ellipse
-/// radii: Tuple[int, int]
+/// radii: Tuple[float, float]
 ///     The radii of the ellipse
+/// inner_radii: Optional[Tuple[int, int]]
+///     The radii of the hole in the ellipse, which makes it a ring. Only the pixels between the inner and outer ellipse are filled,
+///     and the border is applied to both edges.
 /// border: Optional[:class:`.Border`]
 ///     The border of the ellipse.
 /// fill: Optional[:class:`.Pixel`]
 ///     The color to use for filling the ellipse
 /// overlay: Optional[str]
 ///     The overlay mode of the ellipse.
+/// inner_shadow: Optional[:class:`.Shadow`]
+///     The shadow cast onto the inside of the ellipse, drawn over its fill.
+/// outer_glow: Optional[:class:`.Shadow`]
+///     The glow around the outside of the ellipse, drawn before it.
+///
+/// Raises
+/// ------
+/// ValueError
+///     The border doesn't use the solid stroke style, the inner radii are zero or not smaller than the radii,
+///     or the position or radii round to below zero.
 #[pyclass]
 #[derive(Clone)]
-#[pyo3(text_signature = "(*, position, radii, border, fill, overlay)")]
+#[pyo3(
+    text_signature = "(*, position, radii, inner_radii = None, border = None, fill = None, overlay = None, inner_shadow = None, outer_glow = None)"
+)]
 pub struct Ellipse {
     pub inner: RilEllipse<Dynamic>,
+    position: Point,
+    radii: Point,
+    inner_radii: Option<Xy>,
+    inner_shadow: Option<Shadow>,
+    outer_glow: Option<Shadow>,
 }
 
 #[pymethods]
 impl Ellipse {
     #[new]
-    #[args("*", position, radii, border, fill, overlay)]
+    #[pyo3(signature = (*, position, radii, inner_radii = None, border = None, fill = None, overlay = None, inner_shadow = None, outer_glow = None))]
+    #[allow(clippy::too_many_arguments)]
     fn new(
-        position: Xy,
-        radii: Xy,
+        position: Point,
+        radii: Point,
+        inner_radii: Option<Xy>,
         border: Option<Border>,
         fill: Option<Pixel>,
         overlay: Option<OverlayMode>,
+        inner_shadow: Option<Shadow>,
+        outer_glow: Option<Shadow>,
     ) -> PyResult<Self> {
+        if let Some(border) = &border {
+            border.ensure_solid("Ellipse")?;
+        }
+
         let mut inner = RilEllipse::<Dynamic> {
-            position,
-            radii,
+            position: round_point("position", position)?,
+            radii: round_extent("radii", radii)?,
             border: None,
             fill: None,
             overlay: None,
@@ -180,7 +762,16 @@ impl Ellipse {
 
         inner.overlay = overlay.map(|i| i.into());
 
-        Ok(Self { inner })
+        ensure_inner_radii(inner.radii, inner_radii)?;
+
+        Ok(Self {
+            inner,
+            position,
+            radii,
+            inner_radii,
+            inner_shadow,
+            outer_glow,
+        })
     }
 
     /// Creates a new ellipse from the given bounding box.
@@ -200,11 +791,9 @@ impl Ellipse {
     /// -------
     /// :class:`.Ellipse`
     #[classmethod]
-    #[pyo3(text_signature = "(cls, x1, y1, x2, y2)")]
+    #[pyo3(text_signature = "($cls, x1, y1, x2, y2)")]
     fn from_bounding_box(_: &PyType, x1: u32, y1: u32, x2: u32, y2: u32) -> Self {
-        Self {
-            inner: RilEllipse::from_bounding_box(x1, y1, x2, y2),
-        }
+        Self::from_inner(RilEllipse::from_bounding_box(x1, y1, x2, y2))
     }
 
     /// Creates a new circle with the given center position and radius.
@@ -218,23 +807,28 @@ impl Ellipse {
     /// radius: int
     ///     The radius
     #[classmethod]
-    #[pyo3(text_signature = "(cls, x, y, radius)")]
+    #[pyo3(text_signature = "($cls, x, y, radius)")]
     fn circle(_: &PyType, x: u32, y: u32, radius: u32) -> Self {
-        Self {
-            inner: RilEllipse::circle(x, y, radius),
-        }
+        Self::from_inner(RilEllipse::circle(x, y, radius))
+    }
+
+    /// Tuple[float, float]: The center position of the ellipse. The center of this ellipse will be rendered at this position,
+    /// rounded to the nearest pixel.
+    #[getter]
+    fn get_position(&self) -> Point {
+        self.position
     }
 
-    /// Tuple[int, int]: The center position of the ellipse. The center of this ellipse will be rendered at this position.
+    /// Tuple[float, float]: The radii of the ellipse, in pixels; (horizontal, vertical).
     #[getter]
-    fn get_position(&self) -> Xy {
-        self.inner.position
+    fn get_radii(&self) -> Point {
+        self.radii
     }
 
-    /// Tuple[int, int]: The radii of the ellipse, in pixels; (horizontal, vertical).
+    /// Optional[Tuple[int, int]]: The radii of the hole in the ellipse, or None if the ellipse is not a ring.
     #[getter]
-    fn get_radii(&self) -> Xy {
-        self.inner.radii
+    fn get_inner_radii(&self) -> Option<Xy> {
+        self.inner_radii
     }
 
     /// Optional[:class:`.Border`]: The border of the ellipse.
@@ -243,7 +837,7 @@ impl Ellipse {
         self.inner
             .border
             .as_ref()
-            .map(|b| Border { inner: b.clone() })
+            .map(|b| Border::from_inner(b.clone(), StrokeStyle::default()))
     }
 
     /// Optional[Union[:class:`.BitPixel`, :class:`.L`, :class:`.Rgb`, :class:`.Rgba`]]: The color used to fill the ellipse.
@@ -260,19 +854,50 @@ impl Ellipse {
         self.inner.overlay.map(|i| i.into())
     }
 
+    /// Optional[:class:`.Shadow`]: The shadow cast onto the inside of the ellipse.
+    #[getter]
+    fn get_inner_shadow(&self) -> Option<Shadow> {
+        self.inner_shadow.clone()
+    }
+
+    /// Optional[:class:`.Shadow`]: The glow around the outside of the ellipse.
+    #[getter]
+    fn get_outer_glow(&self) -> Option<Shadow> {
+        self.outer_glow.clone()
+    }
+
+    #[setter]
+    fn set_position(&mut self, position: Point) -> PyResult<()> {
+        self.inner.position = round_point("position", position)?;
+        self.position = position;
+
+        Ok(())
+    }
+
     #[setter]
-    fn set_position(&mut self, position: Xy) {
-        self.inner.position = position;
+    fn set_radii(&mut self, radii: Point) -> PyResult<()> {
+        let rounded = round_extent("radii", radii)?;
+        ensure_inner_radii(rounded, self.inner_radii)?;
+        self.inner.radii = rounded;
+        self.radii = radii;
+
+        Ok(())
     }
 
     #[setter]
-    fn set_radii(&mut self, radii: Xy) {
-        self.inner.radii = radii;
+    fn set_inner_radii(&mut self, inner_radii: Option<Xy>) -> PyResult<()> {
+        ensure_inner_radii(self.inner.radii, inner_radii)?;
+        self.inner_radii = inner_radii;
+
+        Ok(())
     }
 
     #[setter]
-    fn set_border(&mut self, border: Border) {
+    fn set_border(&mut self, border: Border) -> PyResult<()> {
+        border.ensure_solid("Ellipse")?;
         self.inner.border = Some(border.inner);
+
+        Ok(())
     }
 
     #[setter]
@@ -287,23 +912,261 @@ impl Ellipse {
         Ok(())
     }
 
-    fn __repr__(&self, py: Python<'_>) -> String {
-        format!(
-            "<Ellipse position=({}, {}) radii=({}, {}) border={} fill={} overlay={}>",
-            self.get_position().0,
-            self.get_position().1,
-            self.get_radii().0,
-            self.get_radii().1,
-            self.get_border()
-                .map_or("None".to_string(), |f| f.to_string()),
-            self.get_fill(py)
-                .map_or("None".to_string(), |f| f.to_string()),
+    #[setter]
+    fn set_inner_shadow(&mut self, inner_shadow: Option<Shadow>) {
+        self.inner_shadow = inner_shadow;
+    }
+
+    #[setter]
+    fn set_outer_glow(&mut self, outer_glow: Option<Shadow>) {
+        self.outer_glow = outer_glow;
+    }
+
+    /// Returns a copy of this ellipse.
+    #[pyo3(text_signature = "($self)")]
+    fn copy(&self) -> Self {
+        self.clone()
+    }
+
+    fn __copy__(&self) -> Self {
+        self.clone()
+    }
+
+    fn __richcmp__(&self, py: Python<'_>, other: &PyAny, op: CompareOp) -> PyObject {
+        compare_eq(py, self, other, op)
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> String {
+        format!(
+            "<Ellipse position=({}, {}) radii=({}, {}) inner_radii={} border={} fill={} overlay={} inner_shadow={} outer_glow={}>",
+            self.get_position().0,
+            self.get_position().1,
+            self.get_radii().0,
+            self.get_radii().1,
+            self.get_inner_radii()
+                .map_or("None".to_string(), |r| format!("({}, {})", r.0, r.1)),
+            self.get_border()
+                .map_or("None".to_string(), |f| f.to_string()),
+            self.get_fill(py)
+                .map_or("None".to_string(), |f| f.to_string()),
             self.get_overlay()
                 .map_or("None".to_string(), |f| format!("{:?}", f)),
+            self.inner_shadow
+                .as_ref()
+                .map_or("None".to_string(), |f| f.to_string()),
+            self.outer_glow
+                .as_ref()
+                .map_or("None".to_string(), |f| f.to_string()),
         )
     }
 }
 
+impl PartialEq for Ellipse {
+    fn eq(&self, other: &Self) -> bool {
+        self.position == other.position
+            && self.radii == other.radii
+            && self.inner_radii == other.inner_radii
+            && self.get_border() == other.get_border()
+            && self.inner.fill == other.inner.fill
+            && self.get_overlay() == other.get_overlay()
+            && self.inner_shadow == other.inner_shadow
+            && self.outer_glow == other.outer_glow
+    }
+}
+
+impl Ellipse {
+    fn from_inner(inner: RilEllipse<Dynamic>) -> Self {
+        Self {
+            position: as_point(inner.position),
+            radii: as_point(inner.radii),
+            inner,
+            inner_radii: None,
+            inner_shadow: None,
+            outer_glow: None,
+        }
+    }
+
+    /// Draws the ellipse between its outer glow and inner shadow.
+    pub fn draw(&self, image: &mut RilImage) {
+        let (cx, cy) = (i64::from(self.inner.position.0), i64::from(self.inner.position.1));
+        let (rx, ry) = (i64::from(self.inner.radii.0), i64::from(self.inner.radii.1));
+        let bounds = (cx - rx, cy - ry, cx + rx + 1, cy + ry + 1);
+        let contains = |x: i64, y: i64| self.contains(x, y);
+
+        if let Some(glow) = &self.outer_glow {
+            glow.draw(image, bounds, contains, false);
+        }
+
+        self.draw_shape(image);
+
+        if let Some(shadow) = &self.inner_shadow {
+            shadow.draw(image, bounds, contains, true);
+        }
+    }
+
+    /// Whether the pixel lies within the ellipse, and outside of its hole if it is a ring.
+    fn contains(&self, x: i64, y: i64) -> bool {
+        let dx = (x - i64::from(self.inner.position.0)) as f64;
+        let dy = (y - i64::from(self.inner.position.1)) as f64;
+
+        in_ellipse(dx, dy, self.inner.radii)
+            && self.inner_radii.map_or(true, |inner_radii| !in_ellipse(dx, dy, inner_radii))
+    }
+
+    /// Draws the ellipse, filling only the ring between the inner and outer ellipse ourselves when it has inner radii.
+    fn draw_shape(&self, image: &mut RilImage) {
+        let inner_radii = match self.inner_radii {
+            Some(inner_radii) => inner_radii,
+            None => return self.inner.draw(image),
+        };
+
+        if let Some(fill) = self.inner.fill {
+            let mode = self.inner.overlay.unwrap_or_else(|| image.overlay_mode());
+            fill_ring(image, self.inner.position, self.inner.radii, inner_radii, fill, mode);
+        }
+
+        if let Some(border) = &self.inner.border {
+            RilEllipse {
+                border: Some(border.clone()),
+                fill: None,
+                ..self.inner.clone()
+            }
+            .draw(image);
+
+            // The inner edge faces the other way, so the border still ends up on the same side of the ring.
+            let position = match border.position {
+                RilBorderPosition::Inset => RilBorderPosition::Outset,
+                RilBorderPosition::Center => RilBorderPosition::Center,
+                RilBorderPosition::Outset => RilBorderPosition::Inset,
+            };
+
+            RilEllipse {
+                radii: inner_radii,
+                border: Some(RilBorder {
+                    position,
+                    ..border.clone()
+                }),
+                fill: None,
+                ..self.inner.clone()
+            }
+            .draw(image);
+        }
+    }
+}
+
+fn ensure_inner_radii(radii: Xy, inner_radii: Option<Xy>) -> PyResult<()> {
+    match inner_radii {
+        Some((x, y)) if x == 0 || y == 0 || x >= radii.0 || y >= radii.1 => {
+            Err(PyValueError::new_err(format!(
+                "Inner radii ({}, {}) must be at least 1 and smaller than the radii ({}, {})",
+                x, y, radii.0, radii.1
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Whether the offset from the center of an ellipse with the given radii lies within it.
+fn in_ellipse(dx: f64, dy: f64, (rx, ry): Xy) -> bool {
+    (dx / f64::from(rx)).powi(2) + (dy / f64::from(ry)).powi(2) <= 1.0
+}
+
+/// Fills the pixels within the outer ellipse but outside of the inner ellipse.
+fn fill_ring(
+    image: &mut RilImage,
+    (cx, cy): Xy,
+    radii: Xy,
+    inner_radii: Xy,
+    fill: Dynamic,
+    mode: ril::OverlayMode,
+) {
+    let (width, height) = image.dimensions();
+    let x_end = cx.saturating_add(radii.0).min(width.saturating_sub(1));
+    let y_end = cy.saturating_add(radii.1).min(height.saturating_sub(1));
+
+    for y in cy.saturating_sub(radii.1)..=y_end {
+        for x in cx.saturating_sub(radii.0)..=x_end {
+            let (dx, dy) = (f64::from(x) - f64::from(cx), f64::from(y) - f64::from(cy));
+
+            if in_ellipse(dx, dy, radii) && !in_ellipse(dx, dy, inner_radii) {
+                let pixel = &mut image.data[(y * width + x) as usize];
+                *pixel = pixel.overlay_with_alpha(fill, mode, 255);
+            }
+        }
+    }
+}
+
+/// The radii of the corners of a rectangle, clockwise from the top-left corner.
+type Corners = (u32, u32, u32, u32);
+
+/// A corner radius given either for every corner, or per corner as ``(top_left, top_right, bottom_right, bottom_left)``.
+#[derive(FromPyObject)]
+enum CornerRadius {
+    Uniform(i64),
+    Each((i64, i64, i64, i64)),
+}
+
+impl CornerRadius {
+    fn validate(self) -> PyResult<Corners> {
+        let (top_left, top_right, bottom_right, bottom_left) = match self {
+            Self::Uniform(radius) => (radius, radius, radius, radius),
+            Self::Each(corners) => corners,
+        };
+
+        let corner = |radius: i64| {
+            u32::try_from(radius).map_err(|_| {
+                PyValueError::new_err(format!(
+                    "Expected a non-negative corner radius, got {}",
+                    radius
+                ))
+            })
+        };
+
+        Ok((
+            corner(top_left)?,
+            corner(top_right)?,
+            corner(bottom_right)?,
+            corner(bottom_left)?,
+        ))
+    }
+}
+
+fn ensure_corner_stroke(radius: Corners, stroke: StrokeStyle) -> PyResult<()> {
+    if radius != (0, 0, 0, 0) && !stroke.is_solid() {
+        return Err(PyValueError::new_err(
+            "Rectangles with rounded corners only support solid borders",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Whether the center of the pixel at `(x, y)` lies within the box `(x1, y1, x2, y2)` with its corners rounded by
+/// the given radii, clockwise from the top-left corner.
+fn in_rounded_box(x: i64, y: i64, (x1, y1, x2, y2): (i64, i64, i64, i64), radii: [i64; 4]) -> bool {
+    if x < x1 || y < y1 || x >= x2 || y >= y2 {
+        return false;
+    }
+
+    let (px, py) = (x as f64 + 0.5, y as f64 + 0.5);
+    let corners = [
+        (x1 + radii[0], y1 + radii[0], -1.0, -1.0),
+        (x2 - radii[1], y1 + radii[1], 1.0, -1.0),
+        (x2 - radii[2], y2 - radii[2], 1.0, 1.0),
+        (x1 + radii[3], y2 - radii[3], -1.0, 1.0),
+    ];
+
+    corners
+        .iter()
+        .zip(radii)
+        .all(|(&(cx, cy, sx, sy), radius)| {
+            let (dx, dy) = (px - cx as f64, py - cy as f64);
+
+            // Only the quarter of the circle facing outwards is part of the outline.
+            dx * sx <= 0.0 || dy * sy <= 0.0 || dx.hypot(dy) <= radius as f64
+        })
+}
+
 /// A rectangle.
 ///
 /// .. warning::
@@ -317,11 +1180,17 @@ impl Ellipse {
 ///     Additionally, a panic will be raised during drawing if you do not specify either a fill color or a border.
 ///     these can be set with `.fill` and `.border` respectively.
 ///
+/// The position and size may lie between pixels, such as for smooth animations. They are rounded to the nearest pixel
+/// when drawn the same way as the position of an :class:`.Ellipse`, and sizes that aren't zero stay at least one pixel.
+///
+/// Corners can be rounded with `radius`, which the fill, border, shadows and overlay all follow. Each radius is
+/// clamped to half of the smaller side of the rectangle, so a large radius turns a square into a circle.
+///
 /// Parameters
 /// ----------
-/// position: Tuple[int, int]
+/// position: Tuple[float, float]
 ///     The position of the rectangle
-/// size: Tuple[int, int]
+/// size: Tuple[float, float]
 ///     The size of the rectangle
 /// border: Optional[:class:`.Border`]
 ///     The border of the ellipse.
@@ -329,37 +1198,66 @@ impl Ellipse {
 ///     The color to use for filling the rectangle
 /// overlay: Optional[:class:`.OverlayMode`]
 ///     The overlay mode of the rectangle.
+/// inner_shadow: Optional[:class:`.Shadow`]
+///     The shadow cast onto the inside of the rectangle, drawn over its fill.
+/// outer_glow: Optional[:class:`.Shadow`]
+///     The glow around the outside of the rectangle, drawn before it.
+/// radius: Union[int, Tuple[int, int, int, int]], default: 0
+///     The radius of the rounded corners in pixels, either for every corner or per corner as
+///     ``(top_left, top_right, bottom_right, bottom_left)``.
 ///
 /// Raises
 /// ------
 /// ValueError
-///     The overlay mode provided is not one of `replace`, or `merge`
+///     The overlay mode provided is not one of `replace` or `merge`, the position or size round to below zero,
+///     a radius is negative, or the corners are rounded and the border isn't solid.
 #[pyclass]
 #[derive(Clone)]
-#[pyo3(text_signature = "(*, position, size, border, fill, overlay)")]
+#[pyo3(
+    text_signature = "(*, position, size, border = None, fill = None, overlay = None, inner_shadow = None, outer_glow = None, radius = 0)"
+)]
 pub struct Rectangle {
     pub inner: RilRectangle<Dynamic>,
+    position: Point,
+    size: Point,
+    stroke: StrokeStyle,
+    inner_shadow: Option<Shadow>,
+    outer_glow: Option<Shadow>,
+    radius: Corners,
 }
 
 #[pymethods]
 impl Rectangle {
     #[new]
-    #[args("*", position, size, border, fill, overlay)]
+    #[pyo3(signature = (*, position, size, border = None, fill = None, overlay = None, inner_shadow = None, outer_glow = None, radius = CornerRadius::Uniform(0)))]
     fn new(
-        position: Xy,
-        size: Xy,
+        position: Point,
+        size: Point,
         border: Option<Border>,
         fill: Option<Pixel>,
         overlay: Option<OverlayMode>,
+        inner_shadow: Option<Shadow>,
+        outer_glow: Option<Shadow>,
+        radius: CornerRadius,
     ) -> PyResult<Self> {
+        let stroke = border.as_ref().map(|b| b.style).unwrap_or_default();
+        let radius = radius.validate()?;
+        ensure_corner_stroke(radius, stroke)?;
+
         Ok(Self {
             inner: RilRectangle {
-                position,
-                size,
+                position: round_point("position", position)?,
+                size: round_extent("size", size)?,
                 border: border.map(|b| b.inner),
                 fill: fill.map(|f| f.inner),
                 overlay: overlay.map(|o| o.into()),
             },
+            position,
+            size,
+            stroke,
+            inner_shadow,
+            outer_glow,
+            radius,
         })
     }
 
@@ -377,23 +1275,32 @@ impl Rectangle {
     /// y2: int
     ///     The y axis of the lower-right corner
     #[classmethod]
-    #[pyo3(text_signature = "(cls, x1, y1, x2, y2)")]
+    #[pyo3(text_signature = "($cls, x1, y1, x2, y2)")]
     fn from_bounding_box(_: &PyType, x1: u32, y1: u32, x2: u32, y2: u32) -> Self {
+        let inner = RilRectangle::from_bounding_box(x1, y1, x2, y2);
+
         Self {
-            inner: RilRectangle::from_bounding_box(x1, y1, x2, y2),
+            position: as_point(inner.position),
+            size: as_point(inner.size),
+            inner,
+            stroke: StrokeStyle::default(),
+            inner_shadow: None,
+            outer_glow: None,
+            radius: (0, 0, 0, 0),
         }
     }
 
-    /// Tuple[int, int]: The position of the rectangle. The top-left corner of the rectangle will be rendered at this position.
+    /// Tuple[float, float]: The position of the rectangle. The top-left corner of the rectangle will be rendered at this position,
+    /// rounded to the nearest pixel.
     #[getter]
-    fn get_position(&self) -> Xy {
-        self.inner.position
+    fn get_position(&self) -> Point {
+        self.position
     }
 
-    /// Tuple[int, int]: The dimensions of the rectangle, in pixels.
+    /// Tuple[float, float]: The dimensions of the rectangle, in pixels.
     #[getter]
-    fn get_size(&self) -> Xy {
-        self.inner.size
+    fn get_size(&self) -> Point {
+        self.size
     }
 
     /// :class:`.Border`: The border of the rectangle, or None if there is no border.
@@ -402,7 +1309,7 @@ impl Rectangle {
         self.inner
             .border
             .as_ref()
-            .map(|b| Border { inner: b.clone() })
+            .map(|b| Border::from_inner(b.clone(), self.stroke))
     }
 
     /// Optional[Union[:class:`.BitPixel`, :class:`.L`, :class:`.Rgb`, :class:`.Rgba`]]: The color used to fill the rectangle.
@@ -419,19 +1326,50 @@ impl Rectangle {
         self.inner.overlay.map(|i| i.into())
     }
 
+    /// Optional[:class:`.Shadow`]: The shadow cast onto the inside of the rectangle.
+    #[getter]
+    fn get_inner_shadow(&self) -> Option<Shadow> {
+        self.inner_shadow.clone()
+    }
+
+    /// Optional[:class:`.Shadow`]: The glow around the outside of the rectangle.
+    #[getter]
+    fn get_outer_glow(&self) -> Option<Shadow> {
+        self.outer_glow.clone()
+    }
+
+    /// Tuple[int, int, int, int]: The radius of each corner as ``(top_left, top_right, bottom_right, bottom_left)``.
+    /// Can be set to a single radius for every corner.
+    #[getter]
+    fn get_radius(&self) -> Corners {
+        self.radius
+    }
+
     #[setter]
-    fn set_position(&mut self, position: Xy) {
-        self.inner.position = position;
+    fn set_position(&mut self, position: Point) -> PyResult<()> {
+        self.inner.position = round_point("position", position)?;
+        self.position = position;
+
+        Ok(())
     }
 
     #[setter]
-    fn set_size(&mut self, size: Xy) {
-        self.inner.size = size;
+    fn set_size(&mut self, size: Point) -> PyResult<()> {
+        self.inner.size = round_extent("size", size)?;
+        self.size = size;
+
+        Ok(())
     }
 
     #[setter]
-    fn set_border(&mut self, border: Option<Border>) {
+    fn set_border(&mut self, border: Option<Border>) -> PyResult<()> {
+        let stroke = border.as_ref().map(|b| b.style).unwrap_or_default();
+        ensure_corner_stroke(self.radius, stroke)?;
+
+        self.stroke = stroke;
         self.inner.border = border.map(|b| b.inner);
+
+        Ok(())
     }
 
     #[setter]
@@ -446,9 +1384,42 @@ impl Rectangle {
         Ok(())
     }
 
+    #[setter]
+    fn set_inner_shadow(&mut self, inner_shadow: Option<Shadow>) {
+        self.inner_shadow = inner_shadow;
+    }
+
+    #[setter]
+    fn set_outer_glow(&mut self, outer_glow: Option<Shadow>) {
+        self.outer_glow = outer_glow;
+    }
+
+    #[setter]
+    fn set_radius(&mut self, radius: CornerRadius) -> PyResult<()> {
+        let radius = radius.validate()?;
+        ensure_corner_stroke(radius, self.stroke)?;
+        self.radius = radius;
+
+        Ok(())
+    }
+
+    /// Returns a copy of this rectangle.
+    #[pyo3(text_signature = "($self)")]
+    fn copy(&self) -> Self {
+        self.clone()
+    }
+
+    fn __copy__(&self) -> Self {
+        self.clone()
+    }
+
+    fn __richcmp__(&self, py: Python<'_>, other: &PyAny, op: CompareOp) -> PyObject {
+        compare_eq(py, self, other, op)
+    }
+
     fn __repr__(&self, py: Python<'_>) -> String {
         format!(
-            "<Rectangle position=({}, {}) size=({}, {}) border={} fill={} overlay={}>",
+            "<Rectangle position=({}, {}) size=({}, {}) border={} fill={} overlay={} inner_shadow={} outer_glow={} radius={:?}>",
             self.get_position().0,
             self.get_position().1,
             self.get_size().0,
@@ -459,36 +1430,1350 @@ impl Rectangle {
                 .map_or("None".to_string(), |f| f.to_string()),
             self.get_overlay()
                 .map_or("None".to_string(), |f| format!("{:?}", f)),
+            self.inner_shadow
+                .as_ref()
+                .map_or("None".to_string(), |f| f.to_string()),
+            self.outer_glow
+                .as_ref()
+                .map_or("None".to_string(), |f| f.to_string()),
+            self.radius,
         )
     }
 }
 
-macro_rules! impl_draw_entities {
-    ($obj:expr, $( $class:ident ),*) => {{
-        $(
-            match $obj.extract::<$class>() {
-                Ok(r) => return Ok(DrawEntity::$class(r)),
-                Err(_) => ()
+impl PartialEq for Rectangle {
+    fn eq(&self, other: &Self) -> bool {
+        self.position == other.position
+            && self.size == other.size
+            && self.get_border() == other.get_border()
+            && self.inner.fill == other.inner.fill
+            && self.get_overlay() == other.get_overlay()
+            && self.inner_shadow == other.inner_shadow
+            && self.outer_glow == other.outer_glow
+            && self.radius == other.radius
+    }
+}
+
+impl Rectangle {
+    /// Draws the rectangle between its outer glow and inner shadow.
+    pub fn draw(&self, image: &mut RilImage) {
+        let bounds = self.bounds();
+        let radii = self.corner_radii();
+        let contains = |x: i64, y: i64| in_rounded_box(x, y, bounds, radii);
+
+        if let Some(glow) = &self.outer_glow {
+            glow.draw(image, bounds, contains, false);
+        }
+
+        self.draw_shape(image);
+
+        if let Some(shadow) = &self.inner_shadow {
+            shadow.draw(image, bounds, contains, true);
+        }
+    }
+
+    /// The box covered by the rectangle as ``(x1, y1, x2, y2)``, where the lower-right corner is exclusive.
+    fn bounds(&self) -> (i64, i64, i64, i64) {
+        let (x, y) = (i64::from(self.inner.position.0), i64::from(self.inner.position.1));
+
+        (
+            x,
+            y,
+            x + i64::from(self.inner.size.0),
+            y + i64::from(self.inner.size.1),
+        )
+    }
+
+    /// The radius of each corner, clamped to half of the smaller side.
+    fn corner_radii(&self) -> [i64; 4] {
+        let max = i64::from(self.inner.size.0.min(self.inner.size.1) / 2);
+        let (top_left, top_right, bottom_right, bottom_left) = self.radius;
+
+        [top_left, top_right, bottom_right, bottom_left].map(|radius| i64::from(radius).min(max))
+    }
+
+    /// Draws the rectangle, stroking the border ourselves when it isn't solid or the corners are rounded.
+    fn draw_shape(&self, image: &mut RilImage) {
+        if self.radius != (0, 0, 0, 0) {
+            return self.draw_rounded(image);
+        }
+
+        match &self.inner.border {
+            Some(border) if !self.stroke.is_solid() => {
+                if self.inner.fill.is_some() {
+                    let mut inner = self.inner.clone();
+                    inner.border = None;
+                    inner.draw(image);
+                }
+
+                let mode = self.inner.overlay.unwrap_or_else(|| image.overlay_mode());
+                self.stroke
+                    .stroke_rectangle(image, self.inner.position, self.inner.size, border, mode);
             }
-        )*
+            _ => self.inner.draw(image),
+        }
+    }
 
-        Err(PyRuntimeError::new_err(
-            "Invalid argument for draw".to_string(),
-        ))
-    }};
+    /// Fills and strokes the rectangle with rounded corners.
+    ///
+    /// The edges of the border follow the corners: they are grown or shrunk along with the box by the part of the
+    /// border that lies outside or inside of the rectangle, and corners that aren't rounded stay square.
+    fn draw_rounded(&self, image: &mut RilImage) {
+        let mode = self.inner.overlay.unwrap_or_else(|| image.overlay_mode());
+        let bounds = self.bounds();
+        let radii = self.corner_radii();
+
+        let (inset, outset) = self.inner.border.as_ref().map_or((0, 0), |border| {
+            let thickness = i64::from(border.thickness);
+
+            match border.position {
+                RilBorderPosition::Inset => (thickness, 0),
+                RilBorderPosition::Center => (thickness - thickness / 2, thickness / 2),
+                RilBorderPosition::Outset => (0, thickness),
+            }
+        });
+
+        let grow = |by: i64| (bounds.0 - by, bounds.1 - by, bounds.2 + by, bounds.3 + by);
+        let (outer, inner) = (grow(outset), grow(-inset));
+        let outer_radii = radii.map(|radius| if radius > 0 { radius + outset } else { 0 });
+        let inner_radii = radii.map(|radius| (radius - inset).max(0));
+
+        let (width, height) = (i64::from(image.width()), i64::from(image.height()));
+        for y in outer.1.max(0)..outer.3.min(height) {
+            for x in outer.0.max(0)..outer.2.min(width) {
+                let pixel = &mut image.data[(y * width + x) as usize];
+
+                if let Some(fill) = self.inner.fill {
+                    if in_rounded_box(x, y, bounds, radii) {
+                        *pixel = pixel.overlay_with_alpha(fill, mode, 255);
+                    }
+                }
+
+                if let Some(border) = &self.inner.border {
+                    if in_rounded_box(x, y, outer, outer_radii)
+                        && !in_rounded_box(x, y, inner, inner_radii)
+                    {
+                        *pixel = pixel.overlay_with_alpha(border.color, mode, 255);
+                    }
+                }
+            }
+        }
+    }
 }
 
-#[allow(dead_code)]
-pub enum DrawEntity<'a> {
-    Rectangle(Rectangle),
-    Ellipse(Ellipse),
-    TextSegment(TextSegment),
-    TextLayout(TextLayout),
-    PhantomData(PhantomData<&'a ()>)
+/// A straight line segment between two points, such as for plotting or annotating.
+///
+/// Both end points are part of the line. Thicker lines are drawn as a rectangle along the direction of the line,
+/// centered on it, with square ends at the end points. End points between pixels are rounded to the nearest pixel
+/// the same way as the position of an :class:`.Ellipse`.
+///
+/// Parameters
+/// ----------
+/// start: Tuple[float, float]
+///     The point the line starts at.
+/// end: Tuple[float, float]
+///     The point the line ends at.
+/// color: :class:`.Pixel`
+///     The color of the line.
+/// thickness: int, default: 1
+///     The thickness of the line in pixels.
+/// overlay: Optional[:class:`.OverlayMode`]
+///     The overlay mode of the line, defaults to the overlay mode of the image it is drawn on.
+/// style: Optional[:class:`.StrokeStyle`]
+///     The stroke style of the line, defaults to solid. Dashes and dots are measured from the upper end of the line.
+///
+/// Raises
+/// ------
+/// ValueError
+///     `thickness` is zero, or an end point rounds to below zero.
+///
+/// Examples
+/// --------
+///
+/// .. code-block:: python3
+///
+///     image.draw(Line(start=(0, 0), end=(99, 49), color=Pixel.from_rgb(255, 0, 0), thickness=3))
+#[pyclass]
+#[derive(Clone, PartialEq)]
+#[pyo3(text_signature = "(*, start, end, color, thickness = 1, overlay = None, style = None)")]
+pub struct Line {
+    start: Point,
+    end: Point,
+    color: Dynamic,
+    thickness: u32,
+    overlay: Option<OverlayMode>,
+    style: StrokeStyle,
 }
 
-impl<'a> FromPyObject<'a> for DrawEntity<'a> {
-    fn extract(obj: &'a PyAny) -> PyResult<Self> {
-        impl_draw_entities!(obj, Rectangle, Ellipse, TextSegment, TextLayout)
+#[pymethods]
+impl Line {
+    #[new]
+    #[pyo3(signature = (*, start, end, color, thickness = 1, overlay = None, style = None))]
+    fn new(
+        start: Point,
+        end: Point,
+        color: Pixel,
+        thickness: u32,
+        overlay: Option<OverlayMode>,
+        style: Option<StrokeStyle>,
+    ) -> PyResult<Self> {
+        round_point("start", start)?;
+        round_point("end", end)?;
+
+        Ok(Self {
+            start,
+            end,
+            color: color.inner,
+            thickness: ensure_thickness(thickness)?,
+            overlay,
+            style: style.unwrap_or_default(),
+        })
+    }
+
+    /// Tuple[float, float]: The point the line starts at.
+    #[getter]
+    fn get_start(&self) -> Point {
+        self.start
+    }
+
+    /// Tuple[float, float]: The point the line ends at.
+    #[getter]
+    fn get_end(&self) -> Point {
+        self.end
+    }
+
+    /// Union[:class:`.BitPixel`, :class:`.L`, :class:`.Rgb`, :class:`.Rgba`]: The color of the line.
+    #[getter]
+    fn get_color(&self, py: Python<'_>) -> PyObject {
+        cast_pixel_to_pyobject(py, self.color)
+    }
+
+    /// int: The thickness of the line in pixels.
+    #[getter]
+    fn get_thickness(&self) -> u32 {
+        self.thickness
+    }
+
+    /// Optional[:class:`.OverlayMode`]: The overlay mode of the line.
+    #[getter]
+    fn get_overlay(&self) -> Option<OverlayMode> {
+        self.overlay.clone()
+    }
+
+    /// :class:`.StrokeStyle`: The stroke style of the line.
+    #[getter]
+    fn get_style(&self) -> StrokeStyle {
+        self.style
+    }
+
+    #[setter]
+    fn set_start(&mut self, start: Point) -> PyResult<()> {
+        round_point("start", start)?;
+        self.start = start;
+
+        Ok(())
+    }
+
+    #[setter]
+    fn set_end(&mut self, end: Point) -> PyResult<()> {
+        round_point("end", end)?;
+        self.end = end;
+
+        Ok(())
+    }
+
+    #[setter]
+    fn set_color(&mut self, color: Pixel) {
+        self.color = color.inner;
+    }
+
+    #[setter]
+    fn set_thickness(&mut self, thickness: u32) -> PyResult<()> {
+        self.thickness = ensure_thickness(thickness)?;
+
+        Ok(())
+    }
+
+    #[setter]
+    fn set_overlay(&mut self, overlay: Option<OverlayMode>) {
+        self.overlay = overlay;
+    }
+
+    #[setter]
+    fn set_style(&mut self, style: StrokeStyle) {
+        self.style = style;
+    }
+
+    /// Returns a copy of this line.
+    #[pyo3(text_signature = "($self)")]
+    fn copy(&self) -> Self {
+        self.clone()
+    }
+
+    fn __copy__(&self) -> Self {
+        self.clone()
+    }
+
+    fn __richcmp__(&self, py: Python<'_>, other: &PyAny, op: CompareOp) -> PyObject {
+        compare_eq(py, self, other, op)
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> String {
+        format!(
+            "<Line start=({}, {}) end=({}, {}) color={} thickness={} overlay={} style={}>",
+            self.start.0,
+            self.start.1,
+            self.end.0,
+            self.end.1,
+            self.get_color(py),
+            self.thickness,
+            self.overlay
+                .as_ref()
+                .map_or("None".to_string(), |f| format!("{:?}", f)),
+            self.style.__repr__(),
+        )
+    }
+}
+
+impl Line {
+    pub fn draw(&self, image: &mut RilImage) {
+        let mode = self
+            .overlay
+            .clone()
+            .map_or_else(|| image.overlay_mode(), Into::into);
+        let (width, height) = (i64::from(image.width()), i64::from(image.height()));
+        let mut plot = |x: i64, y: i64| {
+            if x >= 0 && y >= 0 && x < width && y < height {
+                let pixel = &mut image.data[(y * width + x) as usize];
+                *pixel = pixel.overlay_with_alpha(self.color, mode, 255);
+            }
+        };
+
+        // The end points were checked to not round to below zero when they were set.
+        let round = |(x, y): Point| (round_half_even(x) as i64, round_half_even(y) as i64);
+
+        // Lines are drawn the same way in either direction.
+        let (a, b) = (round(self.start), round(self.end));
+        let ((x0, y0), (x1, y1)) = if (b.1, b.0) < (a.1, a.0) {
+            (b, a)
+        } else {
+            (a, b)
+        };
+
+        if self.thickness == 1 {
+            // Every step to the next pixel counts as one pixel along the line, including diagonal ones.
+            let mut distance = 0;
+            bresenham((x0, y0), (x1, y1), |x, y| {
+                if self.style.is_on(distance) {
+                    plot(x, y);
+                }
+                distance += 1;
+            });
+            return;
+        }
+
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let length = ((dx * dx + dy * dy) as f64).sqrt();
+        let thickness = i64::from(self.thickness);
+        // Lines of an even thickness have their extra row or column on one side, which the ordered ends keep stable.
+        let (low, high) = (-(thickness - 1) / 2, thickness / 2);
+
+        if dx == 0 && dy == 0 {
+            for y in y0 + low..=y0 + high {
+                (x0 + low..=x0 + high).for_each(|x| plot(x, y));
+            }
+            return;
+        }
+
+        let half = f64::from(self.thickness) / 2.0;
+        let reach = half.ceil() as i64;
+        for y in (y0.min(y1) - reach).max(0)..=(y0.max(y1) + reach).min(height - 1) {
+            for x in (x0.min(x1) - reach).max(0)..=(x0.max(x1) + reach).min(width - 1) {
+                let (px, py) = (x - x0, y - y0);
+                // The signed distance from the line, and how far along the line the pixel is.
+                let across = (dx * py - dy * px) as f64 / length;
+                let along = dx * px + dy * py;
+
+                if across > -half
+                    && across <= half
+                    && (0..=dx * dx + dy * dy).contains(&along)
+                    && self.style.is_on((along as f64 / length) as u64)
+                {
+                    plot(x, y);
+                }
+            }
+        }
+    }
+}
+
+/// Plots every pixel of the one pixel thick line from `a` to `b` with Bresenham's algorithm, including both ends.
+fn bresenham(a: (i64, i64), b: (i64, i64), mut plot: impl FnMut(i64, i64)) {
+    let (dx, dy) = ((b.0 - a.0).abs(), -(b.1 - a.1).abs());
+    let (sx, sy) = ((b.0 - a.0).signum(), (b.1 - a.1).signum());
+    let (mut x, mut y, mut error) = (a.0, a.1, dx + dy);
+
+    loop {
+        plot(x, y);
+        if (x, y) == b {
+            break;
+        }
+
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += sx;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += sy;
+        }
+    }
+}
+
+fn ensure_thickness(thickness: u32) -> PyResult<u32> {
+    if thickness == 0 {
+        return Err(PyValueError::new_err("Expected a thickness of at least 1"));
+    }
+
+    Ok(thickness)
+}
+
+/// A polygon with any number of vertices, such as a triangle, an arrow or the outline of a region on a map.
+///
+/// The vertices are the corners of the polygon, where ``(0, 0)`` is the upper-left corner of the upper-left pixel, so the
+/// polygon ``[(0, 0), (4, 0), (4, 4), (0, 4)]`` covers the same pixels as a 4x4 :class:`.Rectangle` at ``(0, 0)``.
+/// Vertices between pixels are rounded to the nearest pixel the same way as the position of an :class:`.Ellipse`.
+///
+/// Pixels are filled when their center lies within the polygon. Polygons may be concave or cross themselves,
+/// where the areas that overlap an even amount of times are left out, like the center of a pentagram.
+///
+/// Parameters
+/// ----------
+/// vertices: List[Tuple[float, float]]
+///     The vertices of the polygon in order, the last one is connected to the first.
+/// fill: Optional[:class:`.Pixel`]
+///     The color to use for filling the polygon.
+/// border: Optional[:class:`.Border`]
+///     The border of the polygon. Dashed and dotted borders are measured along the perimeter from the first vertex.
+/// overlay: Optional[:class:`.OverlayMode`]
+///     The overlay mode of the polygon, defaults to the overlay mode of the image it is drawn on.
+///
+/// Raises
+/// ------
+/// ValueError
+///     There are fewer than 3 vertices, all vertices lie on a single line, or a vertex rounds to below zero.
+///
+/// Examples
+/// --------
+///
+/// .. code-block:: python3
+///
+///     image.draw(Polygon(vertices=[(10, 0), (20, 20), (0, 20)], fill=Pixel.from_rgb(255, 0, 0)))
+#[pyclass]
+#[derive(Clone)]
+#[pyo3(text_signature = "(*, vertices, fill = None, border = None, overlay = None)")]
+pub struct Polygon {
+    vertices: Vec<Point>,
+    fill: Option<Dynamic>,
+    border: Option<RilBorder<Dynamic>>,
+    stroke: StrokeStyle,
+    overlay: Option<OverlayMode>,
+}
+
+#[pymethods]
+impl Polygon {
+    #[new]
+    #[pyo3(signature = (*, vertices, fill = None, border = None, overlay = None))]
+    fn new(
+        vertices: Vec<Point>,
+        fill: Option<Pixel>,
+        border: Option<Border>,
+        overlay: Option<OverlayMode>,
+    ) -> PyResult<Self> {
+        ensure_vertices(&vertices)?;
+
+        Ok(Self {
+            vertices,
+            fill: fill.map(|f| f.inner),
+            stroke: border.as_ref().map(|b| b.style).unwrap_or_default(),
+            border: border.map(|b| b.inner),
+            overlay,
+        })
+    }
+
+    /// List[Tuple[float, float]]: The vertices of the polygon.
+    #[getter]
+    fn get_vertices(&self) -> Vec<Point> {
+        self.vertices.clone()
+    }
+
+    /// Optional[Union[:class:`.BitPixel`, :class:`.L`, :class:`.Rgb`, :class:`.Rgba`]]: The color used to fill the polygon.
+    #[getter]
+    fn get_fill(&self, py: Python<'_>) -> Option<PyObject> {
+        self.fill.map(|fill| cast_pixel_to_pyobject(py, fill))
+    }
+
+    /// Optional[:class:`.Border`]: The border of the polygon.
+    #[getter]
+    fn get_border(&self) -> Option<Border> {
+        self.border
+            .as_ref()
+            .map(|b| Border::from_inner(b.clone(), self.stroke))
+    }
+
+    /// Optional[:class:`.OverlayMode`]: The overlay mode of the polygon.
+    #[getter]
+    fn get_overlay(&self) -> Option<OverlayMode> {
+        self.overlay.clone()
+    }
+
+    #[setter]
+    fn set_vertices(&mut self, vertices: Vec<Point>) -> PyResult<()> {
+        ensure_vertices(&vertices)?;
+        self.vertices = vertices;
+
+        Ok(())
+    }
+
+    #[setter]
+    fn set_fill(&mut self, fill: Option<Pixel>) {
+        self.fill = fill.map(|f| f.inner);
+    }
+
+    #[setter]
+    fn set_border(&mut self, border: Option<Border>) {
+        self.stroke = border.as_ref().map(|b| b.style).unwrap_or_default();
+        self.border = border.map(|b| b.inner);
+    }
+
+    #[setter]
+    fn set_overlay(&mut self, overlay: Option<OverlayMode>) {
+        self.overlay = overlay;
+    }
+
+    /// Returns a copy of this polygon.
+    #[pyo3(text_signature = "($self)")]
+    fn copy(&self) -> Self {
+        self.clone()
+    }
+
+    fn __copy__(&self) -> Self {
+        self.clone()
+    }
+
+    fn __richcmp__(&self, py: Python<'_>, other: &PyAny, op: CompareOp) -> PyObject {
+        compare_eq(py, self, other, op)
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> String {
+        format!(
+            "<Polygon vertices={:?} fill={} border={} overlay={}>",
+            self.vertices,
+            self.get_fill(py)
+                .map_or("None".to_string(), |f| f.to_string()),
+            self.get_border()
+                .map_or("None".to_string(), |f| f.to_string()),
+            self.overlay
+                .as_ref()
+                .map_or("None".to_string(), |f| format!("{:?}", f)),
+        )
+    }
+}
+
+impl PartialEq for Polygon {
+    fn eq(&self, other: &Self) -> bool {
+        self.vertices == other.vertices
+            && self.fill == other.fill
+            && self.get_border() == other.get_border()
+            && self.overlay == other.overlay
+    }
+}
+
+impl Polygon {
+    /// Fills the polygon one row at a time, then strokes its border over the fill.
+    pub fn draw(&self, image: &mut RilImage) {
+        let mode = self
+            .overlay
+            .clone()
+            .map_or_else(|| image.overlay_mode(), Into::into);
+        let (width, height) = (i64::from(image.width()), i64::from(image.height()));
+        // The vertices were checked to not round to below zero when they were set.
+        let vertices = self
+            .vertices
+            .iter()
+            .map(|&(x, y)| (f64::from(round_half_even(x)), f64::from(round_half_even(y))))
+            .collect::<Vec<_>>();
+        let mut plot = |x: i64, y: i64, color: Dynamic| {
+            let pixel = &mut image.data[(y * width + x) as usize];
+            *pixel = pixel.overlay_with_alpha(color, mode, 255);
+        };
+
+        let top = vertices.iter().map(|v| v.1).fold(f64::INFINITY, f64::min);
+        let bottom = vertices.iter().map(|v| v.1).fold(0.0, f64::max);
+
+        let border = self.border.as_ref().map(|border| {
+            let thickness = f64::from(border.thickness);
+            let half = (thickness / 2.0).floor();
+            // How far the border reaches into and out of the polygon.
+            let (inset, outset) = match border.position {
+                RilBorderPosition::Inset => (thickness, 0.0),
+                RilBorderPosition::Center => (thickness - half, half),
+                RilBorderPosition::Outset => (0.0, thickness),
+            };
+
+            (border.color, inset, outset)
+        });
+        // How far from an edge the border reaches on either side.
+        let reach = border.map_or(0.0, |(_, inset, outset)| f64::max(inset, outset));
+        // How far along the perimeter each edge starts, from the first vertex, and how long it is.
+        let edges = vertices
+            .iter()
+            .zip(vertices.iter().cycle().skip(1))
+            .scan(0.0, |start, (a, b)| {
+                let length = (b.0 - a.0).hypot(b.1 - a.1);
+                let edge = (*start, length);
+                *start += length;
+                Some(edge)
+            })
+            .collect::<Vec<_>>();
+
+        let mut crossings = Vec::new();
+        // The distance from each pixel of the row to its closest edge, that edge, and how far along it
+        // the closest point is.
+        let mut closest = vec![(f64::INFINITY, 0, 0.0); width as usize];
+        let pad = reach.ceil() as i64;
+
+        for y in (top as i64 - pad).max(0)..(bottom.ceil() as i64 + pad).min(height) {
+            let center_y = y as f64 + 0.5;
+            crossings.clear();
+            crossings.extend(row_crossings(&vertices, center_y));
+            crossings.sort_by(f64::total_cmp);
+
+            if let Some(fill) = self.fill {
+                // Every other span between two crossings lies within the polygon.
+                for span in crossings.chunks_exact(2) {
+                    let start = ((span[0] - 0.5).ceil() as i64).max(0);
+                    let end = ((span[1] - 0.5).ceil() as i64).min(width);
+
+                    (start..end).for_each(|x| plot(x, y, fill));
+                }
+            }
+
+            if let Some((color, inset, outset)) = border {
+                let (mut first, mut last) = (width, 0);
+
+                // Each edge is only measured against the pixels of this row its border could reach.
+                for (i, (a, b)) in vertices
+                    .iter()
+                    .zip(vertices.iter().cycle().skip(1))
+                    .enumerate()
+                {
+                    if center_y < a.1.min(b.1) - reach || center_y > a.1.max(b.1) + reach {
+                        continue;
+                    }
+
+                    let (x0, x1) = if a.1 == b.1 {
+                        (a.0, b.0)
+                    } else {
+                        let at = |y: f64| {
+                            ((y - a.1) / (b.1 - a.1))
+                                .clamp(0.0, 1.0)
+                                .mul_add(b.0 - a.0, a.0)
+                        };
+                        (at(center_y - reach), at(center_y + reach))
+                    };
+                    let start_x = ((x0.min(x1) - reach).floor() as i64).max(0);
+                    let end_x = ((x0.max(x1) + reach).ceil() as i64).min(width);
+
+                    for x in start_x..end_x {
+                        let (distance, offset) =
+                            segment_distance((x as f64 + 0.5, center_y), *a, *b);
+                        let pixel = &mut closest[x as usize];
+
+                        if distance < pixel.0 {
+                            *pixel = (distance, i, offset);
+                        }
+                    }
+
+                    first = first.min(start_x);
+                    last = last.max(end_x);
+                }
+
+                for x in first..last {
+                    let (distance, edge, offset) =
+                        std::mem::replace(&mut closest[x as usize], (f64::INFINITY, 0, 0.0));
+                    let center_x = x as f64 + 0.5;
+                    // A pixel is inside the polygon if an odd number of crossings lie to its right.
+                    let right = crossings.len()
+                        - crossings.partition_point(|&crossing| crossing <= center_x);
+                    let limit = if right % 2 == 1 { inset } else { outset };
+
+                    let (start, length) = edges[edge];
+                    // The last pixel of the edge is the one its closing corner falls on.
+                    let end = (length.ceil() as u64).saturating_sub(1);
+
+                    if distance < limit
+                        && self
+                            .stroke
+                            .is_on_corner((start + offset) as u64, offset as u64, end)
+                    {
+                        plot(x, y, color);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Returns where the edges of the polygon cross the horizontal line at `y`.
+///
+/// Edges include their upper end but not their lower end, so a vertex shared by two edges is only crossed once.
+fn row_crossings(vertices: &[(f64, f64)], y: f64) -> impl Iterator<Item = f64> + '_ {
+    vertices
+        .iter()
+        .zip(vertices.iter().cycle().skip(1))
+        .filter(move |((_, y0), (_, y1))| (*y0 <= y) != (*y1 <= y))
+        .map(move |((x0, y0), (x1, y1))| x0 + (y - y0) / (y1 - y0) * (x1 - x0))
+}
+
+/// The distance from a point to the closest point on the line segment from `a` to `b`,
+/// and how far along the segment from `a` that closest point is.
+fn segment_distance(
+    (px, py): (f64, f64),
+    (x0, y0): (f64, f64),
+    (x1, y1): (f64, f64),
+) -> (f64, f64) {
+    let (dx, dy) = (x1 - x0, y1 - y0);
+    let length = dx * dx + dy * dy;
+    let t = if length == 0.0 {
+        0.0
+    } else {
+        (((px - x0) * dx + (py - y0) * dy) / length).clamp(0.0, 1.0)
+    };
+
+    (
+        (px - t.mul_add(dx, x0)).hypot(py - t.mul_add(dy, y0)),
+        t * length.sqrt(),
+    )
+}
+
+/// Fails if the vertices don't make up a polygon with an area, or a vertex rounds to below zero.
+fn ensure_vertices(vertices: &[Point]) -> PyResult<()> {
+    if vertices.len() < 3 {
+        return Err(PyValueError::new_err(format!(
+            "Expected at least 3 vertices for a polygon, got {}",
+            vertices.len()
+        )));
+    }
+
+    let rounded = vertices
+        .iter()
+        .map(|&vertex| round_point("vertex", vertex).map(|(x, y)| (i64::from(x), i64::from(y))))
+        .collect::<PyResult<Vec<_>>>()?;
+    let (ax, ay) = rounded[0];
+
+    // The vertices lie on a single line if every one of them is collinear with the first vertex and another
+    // vertex distinct from it. Products of two coordinate differences can overflow i64, so they are taken in i128.
+    let collinear = match rounded.iter().find(|&&vertex| vertex != (ax, ay)) {
+        Some(&(bx, by)) => rounded.iter().all(|&(cx, cy)| {
+            i128::from(bx - ax) * i128::from(cy - ay) == i128::from(by - ay) * i128::from(cx - ax)
+        }),
+        None => true,
+    };
+    if collinear {
+        return Err(PyValueError::new_err(
+            "The vertices of a polygon must not all lie on a single line",
+        ));
+    }
+
+    Ok(())
+}
+
+/// A group of entities drawn together in z order, which can be rendered again after any of them change.
+///
+/// Entities are kept by reference, so changes made to them after they are added show up the next time
+/// the scene is drawn. Scenes can be drawn with :meth:`Image.draw` like any other entity, and contain other scenes.
+///
+/// Examples
+/// --------
+///
+/// .. code-block:: python3
+///
+///     card = Scene()
+///     card.add(Rectangle(position=(0, 0), size=(200, 120), fill=Pixel.from_rgb(30, 30, 30)))
+///     card.add(title, z=1)
+///
+///     small = card.render(200, 120, Pixel.from_rgb(0, 0, 0))
+///     title.fill = Pixel.from_rgb(255, 0, 0)
+///     highlighted = card.render(200, 120, Pixel.from_rgb(0, 0, 0))
+#[pyclass]
+#[pyo3(text_signature = "()")]
+pub struct Scene {
+    entities: Vec<(PyObject, i64)>,
+}
+
+#[pymethods]
+impl Scene {
+    #[new]
+    fn new() -> Self {
+        Self {
+            entities: Vec::new(),
+        }
+    }
+
+    /// Adds an entity to this scene.
+    ///
+    /// Entities are drawn from the lowest `z` to the highest, and in the order they were added for the same `z`.
+    ///
+    /// Parameters
+    /// ----------
+    /// entity: Union[:class:`.Rectangle`, :class:`.Ellipse`, :class:`.Line`, :class:`.Polygon`, :class:`.TextSegment`, :class:`.TextLayout`, :class:`.Scene`]
+    ///     The entity to add.
+    /// z: int, default: 0
+    ///     The depth of the entity, higher values are drawn on top.
+    ///
+    /// Raises
+    /// ------
+    /// RuntimeError
+    ///     The entity can't be drawn.
+    /// ValueError
+    ///     The entity is this scene or a scene containing it.
+    #[pyo3(signature = (entity, z = 0))]
+    #[pyo3(text_signature = "($self, entity, z = 0)")]
+    fn add(slf: &PyCell<Self>, entity: &PyAny, z: i64) -> PyResult<()> {
+        if let Ok(scene) = entity.downcast::<PyCell<Self>>() {
+            if scene.is(slf) || scene.borrow().contains(slf) {
+                return Err(PyValueError::new_err("A scene can't contain itself"));
+            }
+        }
+
+        entity.extract::<DrawEntity>()?;
+        slf.borrow_mut().entities.push((entity.into(), z));
+
+        Ok(())
+    }
+
+    /// Removes the entity at the given index, counted in the order entities were added, and returns it.
+    ///
+    /// Parameters
+    /// ----------
+    /// index: int
+    ///     The index of the entity, negative indices count from the end.
+    ///
+    /// Raises
+    /// ------
+    /// IndexError
+    ///     The index is out of range.
+    #[pyo3(text_signature = "($self, index)")]
+    fn remove(&mut self, index: isize) -> PyResult<PyObject> {
+        let len = self.entities.len() as isize;
+        let index = if index < 0 { index + len } else { index };
+
+        if !(0..len).contains(&index) {
+            return Err(PyIndexError::new_err("Scene index out of range"));
+        }
+
+        Ok(self.entities.remove(index as usize).0)
+    }
+
+    /// Renders this scene onto a new image.
+    ///
+    /// This is the same as drawing the scene onto a new image with :meth:`Image.draw`.
+    ///
+    /// Parameters
+    /// ----------
+    /// width: int
+    ///     The width of the image.
+    /// height: int
+    ///     The height of the image.
+    /// background: :class:`.Pixel`
+    ///     The color the image is filled with before drawing, which also decides its mode.
+    ///
+    /// Returns
+    /// -------
+    /// :class:`.Image`
+    ///     The rendered image.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     `width` or `height` is zero.
+    #[pyo3(text_signature = "($self, width, height, background)")]
+    fn render(slf: &PyCell<Self>, width: u32, height: u32, background: Pixel) -> PyResult<Image> {
+        ensure_dimensions(width, height)?;
+
+        let mut entity = slf.extract::<DrawEntity>()?;
+        let modes = entity.modes()?;
+        let convertible = entity.is_convertible();
+
+        let mut image = RilImage::new(width, height, background.inner);
+        draw_reconciled(&mut image, &modes, false, convertible, |image, mode| {
+            entity.convert(mode);
+            Ok(entity.draw(image)?)
+        })?;
+
+        Ok(Image::from_inner(image))
+    }
+
+    fn __len__(&self) -> usize {
+        self.entities.len()
+    }
+
+    fn __iter__<'a>(&self, py: Python<'a>) -> PyResult<&'a PyIterator> {
+        let entities = self.entities.iter().map(|(entity, _)| entity);
+
+        PyIterator::from_object(py, PyList::new(py, entities))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<Scene entities={}>", self.entities.len())
+    }
+}
+
+impl Scene {
+    /// Whether `scene` is an entity of this scene or of any scene nested in it.
+    fn contains(&self, scene: &PyCell<Self>) -> bool {
+        self.entities.iter().any(|(entity, _)| {
+            match entity.as_ref(scene.py()).downcast::<PyCell<Self>>() {
+                Ok(nested) => nested.is(scene) || nested.borrow().contains(scene),
+                Err(_) => false,
+            }
+        })
+    }
+
+    /// Extracts the entities in the order they are drawn.
+    fn extract_entities<'a>(&self, py: Python<'a>) -> PyResult<Vec<DrawEntity<'a>>> {
+        let mut entities = self.entities.iter().collect::<Vec<_>>();
+        entities.sort_by_key(|(_, z)| *z);
+
+        entities
+            .into_iter()
+            .map(|(entity, _)| entity.clone_ref(py).into_ref(py).extract())
+            .collect()
+    }
+}
+
+/// Rounds to the nearest integer, where values halfway between two integers round to the even one.
+fn round_half_even(value: f32) -> f32 {
+    let rounded = value.round();
+
+    if (value - value.trunc()).abs() == 0.5 && rounded % 2.0 != 0.0 {
+        rounded - value.signum()
+    } else {
+        rounded
+    }
+}
+
+/// Rounds a point to the pixel it is drawn at, see [`round_half_even`].
+pub fn round_point(name: &str, (x, y): Point) -> PyResult<Xy> {
+    let (rx, ry) = (round_half_even(x), round_half_even(y));
+
+    // This also rejects NaN, which fails every comparison.
+    if !(rx >= 0.0 && ry >= 0.0 && rx <= u32::MAX as f32 && ry <= u32::MAX as f32) {
+        return Err(PyValueError::new_err(format!(
+            "The {} ({}, {}) is out of bounds, coordinates must be finite and not round to below zero",
+            name, x, y
+        )));
+    }
+
+    Ok((rx as u32, ry as u32))
+}
+
+/// Rounds a size like [`round_point`], keeping sizes that aren't zero at least one pixel.
+fn round_extent(name: &str, extent: Point) -> PyResult<Xy> {
+    let (x, y) = round_point(name, extent)?;
+
+    Ok((
+        if extent.0 > 0.0 { x.max(1) } else { x },
+        if extent.1 > 0.0 { y.max(1) } else { y },
+    ))
+}
+
+/// Returns the point at the given pixel.
+pub fn as_point((x, y): Xy) -> Point {
+    (x as f32, y as f32)
+}
+
+/// A translation and uniform scale applied to the coordinates of everything drawn, see :meth:`Image.push_transform`.
+///
+/// A point ``(x, y)`` is mapped to ``(x * scale + dx, y * scale + dy)``. The positions and sizes of entities are
+/// rounded after they are mapped, like any other coordinates between pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DrawTransform {
+    pub translate: (f64, f64),
+    pub scale: f64,
+}
+
+impl DrawTransform {
+    pub const IDENTITY: Self = Self {
+        translate: (0.0, 0.0),
+        scale: 1.0,
+    };
+
+    /// Returns the transform that applies this transform within `outer`.
+    pub fn within(self, outer: Self) -> Self {
+        Self {
+            translate: (
+                self.translate.0.mul_add(outer.scale, outer.translate.0),
+                self.translate.1.mul_add(outer.scale, outer.translate.1),
+            ),
+            scale: self.scale * outer.scale,
+        }
+    }
+
+    pub fn is_identity(self) -> bool {
+        self == Self::IDENTITY
+    }
+
+    /// Maps a point, which may end up outside of the image.
+    pub fn apply(self, (x, y): (i64, i64)) -> (i64, i64) {
+        (
+            (x as f64).mul_add(self.scale, self.translate.0).round() as i64,
+            (y as f64).mul_add(self.scale, self.translate.1).round() as i64,
+        )
+    }
+
+    /// Maps the position of an entity without rounding it, which keeps it between pixels if it was.
+    pub fn point(self, (x, y): Point) -> Point {
+        (
+            f64::from(x).mul_add(self.scale, self.translate.0) as f32,
+            f64::from(y).mul_add(self.scale, self.translate.1) as f32,
+        )
+    }
+
+    /// Scales the size of an entity without rounding it.
+    pub fn extent(self, (x, y): Point) -> Point {
+        (
+            (f64::from(x) * self.scale) as f32,
+            (f64::from(y) * self.scale) as f32,
+        )
+    }
+
+    /// Scales a size, keeping sizes that aren't zero at least one pixel.
+    pub fn length(self, length: u32) -> u32 {
+        if length == 0 {
+            return 0;
+        }
+
+        ((f64::from(length) * self.scale).round() as u32).max(1)
+    }
+
+    fn shadow(self, shadow: &mut Option<Shadow>) {
+        if let Some(shadow) = shadow {
+            shadow.blur = self.length(shadow.blur);
+            shadow.offset = (
+                (f64::from(shadow.offset.0) * self.scale).round() as i32,
+                (f64::from(shadow.offset.1) * self.scale).round() as i32,
+            );
+        }
+    }
+}
+
+macro_rules! impl_draw_entities {
+    ($obj:expr, $( $class:ident ),*) => {{
+        $(
+            match $obj.extract::<$class>() {
+                Ok(r) => return Ok(DrawEntity::$class(r)),
+                Err(_) => ()
+            }
+        )*
+
+        Err(PyRuntimeError::new_err(
+            "Invalid argument for draw".to_string(),
+        ))
+    }};
+}
+
+#[allow(dead_code)]
+pub enum DrawEntity<'a> {
+    Rectangle(Rectangle),
+    Ellipse(Ellipse),
+    Line(Line),
+    Polygon(Polygon),
+    TextSegment(TextSegment),
+    TextLayout(TextLayout),
+    Scene(Vec<DrawEntity<'a>>),
+    PhantomData(PhantomData<&'a ()>)
+}
+
+impl<'a> FromPyObject<'a> for DrawEntity<'a> {
+    fn extract(obj: &'a PyAny) -> PyResult<Self> {
+        if let Ok(scene) = obj.downcast::<PyCell<Scene>>() {
+            return Ok(DrawEntity::Scene(
+                scene.borrow().extract_entities(obj.py())?,
+            ));
+        }
+
+        impl_draw_entities!(
+            obj,
+            Rectangle,
+            Ellipse,
+            Line,
+            Polygon,
+            TextSegment,
+            TextLayout
+        )
+    }
+}
+
+impl<'a> DrawEntity<'a> {
+    /// Extracts a single entity, or a list or tuple of entities that are drawn in order like a scene.
+    ///
+    /// Every entity is extracted up front, so drawing them never returns to Python in between.
+    pub fn extract_many(obj: &'a PyAny) -> PyResult<Self> {
+        let items = if let Ok(list) = obj.downcast::<PyList>() {
+            list.iter().map(PyAny::extract).collect::<PyResult<_>>()?
+        } else if let Ok(tuple) = obj.downcast::<PyTuple>() {
+            tuple.iter().map(PyAny::extract).collect::<PyResult<_>>()?
+        } else {
+            return obj.extract();
+        };
+
+        Ok(Self::Scene(items))
+    }
+}
+
+impl DrawEntity<'_> {
+    /// Returns the distinct modes of the pixels this entity is drawn with.
+    pub fn modes(&self) -> Result<Vec<Mode>, Error> {
+        let pixels = match self {
+            Self::Rectangle(e) => shape_colors(e.inner.fill, &e.inner.border, [&e.inner_shadow, &e.outer_glow]),
+            Self::Ellipse(e) => shape_colors(e.inner.fill, &e.inner.border, [&e.inner_shadow, &e.outer_glow]),
+            Self::Line(e) => vec![e.color],
+            Self::Polygon(e) => shape_colors(e.fill, &e.border, [&None, &None]),
+            Self::TextSegment(e) => {
+                let shadow = e.shadow.as_ref().map(|shadow| shadow.color);
+
+                std::iter::once(e.inner.fill).chain(shadow).collect()
+            }
+            Self::TextLayout(e) => {
+                let inner = e.inner.read()?;
+                let shadow = e.shadow.as_ref().map(|shadow| shadow.color);
+
+                inner.fills().copied().chain(shadow).collect()
+            }
+            Self::Scene(entities) => {
+                let mut modes = Vec::new();
+                for mode in entities.iter().map(Self::modes) {
+                    for mode in mode? {
+                        if !modes.contains(&mode) {
+                            modes.push(mode);
+                        }
+                    }
+                }
+
+                return Ok(modes);
+            }
+            Self::PhantomData(_) => Vec::new(),
+        };
+
+        let mut modes = Vec::new();
+        for mode in pixels.iter().map(Mode::from) {
+            if !modes.contains(&mode) {
+                modes.push(mode);
+            }
+        }
+
+        Ok(modes)
+    }
+
+    /// Whether the pixels of this entity can be converted with [`Self::convert`]. The fills of a
+    /// text layout are baked into its glyphs, only its shadow is converted.
+    pub fn is_convertible(&self) -> bool {
+        match self {
+            Self::TextLayout(_) => false,
+            Self::Scene(entities) => entities.iter().all(Self::is_convertible),
+            _ => true,
+        }
+    }
+
+    /// Converts the pixels this entity is drawn with into the given mode.
+    pub fn convert(&mut self, mode: Mode) {
+        match self {
+            Self::Rectangle(e) => convert_shape_colors(
+                &mut e.inner.fill,
+                &mut e.inner.border,
+                [&mut e.inner_shadow, &mut e.outer_glow],
+                mode,
+            ),
+            Self::Ellipse(e) => convert_shape_colors(
+                &mut e.inner.fill,
+                &mut e.inner.border,
+                [&mut e.inner_shadow, &mut e.outer_glow],
+                mode,
+            ),
+            Self::Line(e) => e.color = mode.convert_pixel(e.color),
+            Self::Polygon(e) => {
+                convert_shape_colors(&mut e.fill, &mut e.border, [&mut None, &mut None], mode)
+            }
+            Self::TextSegment(e) => {
+                e.inner.fill = mode.convert_pixel(e.inner.fill);
+                convert_shadow_color(&mut e.shadow, mode);
+            }
+            Self::TextLayout(e) => convert_shadow_color(&mut e.shadow, mode),
+            Self::Scene(entities) => entities.iter_mut().for_each(|e| e.convert(mode)),
+            Self::PhantomData(_) => {}
+        }
+    }
+
+    /// Moves and scales this entity by the given transform, see :meth:`Image.push_transform`.
+    pub fn transform(&mut self, transform: DrawTransform) -> PyResult<()> {
+        match self {
+            Self::Rectangle(e) => {
+                e.set_position(transform.point(e.position))?;
+                e.set_size(transform.extent(e.size))?;
+                if let Some(border) = &mut e.inner.border {
+                    border.thickness = transform.length(border.thickness);
+                }
+                transform.shadow(&mut e.inner_shadow);
+                transform.shadow(&mut e.outer_glow);
+                let (top_left, top_right, bottom_right, bottom_left) = e.radius;
+                e.radius = (
+                    transform.length(top_left),
+                    transform.length(top_right),
+                    transform.length(bottom_right),
+                    transform.length(bottom_left),
+                );
+            }
+            Self::Ellipse(e) => {
+                e.position = transform.point(e.position);
+                e.inner.position = round_point("position", e.position)?;
+                e.radii = transform.extent(e.radii);
+                e.inner.radii = round_extent("radii", e.radii)?;
+                e.inner_radii = e
+                    .inner_radii
+                    .map(|(rx, ry)| (transform.length(rx), transform.length(ry)));
+                if let Some(border) = &mut e.inner.border {
+                    border.thickness = transform.length(border.thickness);
+                }
+                transform.shadow(&mut e.inner_shadow);
+                transform.shadow(&mut e.outer_glow);
+            }
+            Self::Line(e) => {
+                e.set_start(transform.point(e.start))?;
+                e.set_end(transform.point(e.end))?;
+                e.thickness = transform.length(e.thickness);
+            }
+            Self::Polygon(e) => {
+                let vertices = e
+                    .vertices
+                    .iter()
+                    .map(|&vertex| transform.point(vertex))
+                    .collect();
+                e.set_vertices(vertices)?;
+                if let Some(border) = &mut e.border {
+                    border.thickness = transform.length(border.thickness);
+                }
+            }
+            Self::TextSegment(e) => {
+                e.set_position(transform.point(e.position))?;
+                e.inner.size *= transform.scale as f32;
+                e.inner.width = e.inner.width.map(|width| transform.length(width));
+                transform.shadow(&mut e.shadow);
+            }
+            Self::TextLayout(e) => {
+                if transform.scale != 1.0 {
+                    return Err(PyValueError::new_err(
+                        "Text layouts can only be translated, since their text is laid out when it is added",
+                    ));
+                }
+
+                e.offset = (
+                    e.offset.0 + transform.translate.0.round() as f32,
+                    e.offset.1 + transform.translate.1.round() as f32,
+                );
+            }
+            Self::Scene(entities) => {
+                for e in entities {
+                    e.transform(transform)?;
+                }
+            }
+            Self::PhantomData(_) => {}
+        }
+
+        Ok(())
+    }
+
+    pub fn draw(&self, image: &mut RilImage) -> Result<(), Error> {
+        match self {
+            Self::Rectangle(e) => e.draw(image),
+            Self::Ellipse(e) => e.draw(image),
+            Self::Line(e) => e.draw(image),
+            Self::Polygon(e) => e.draw(image),
+            Self::TextSegment(e) => {
+                e.ensure_glyphs()?;
+                if let Some(shadow) = &e.shadow {
+                    shadow.draw_coverage(image, &e.inner.coverage());
+                }
+                e.inner.draw(image)
+            }
+            Self::TextLayout(e) => {
+                let inner = e.inner.write()?;
+                // A panic is caught while the lock is still held, since unwinding past it would poison it.
+                catch_draw_panic(|| {
+                    if let Some(shadow) = &e.shadow {
+                        shadow.draw_coverage(image, &inner.coverage_at(e.offset));
+                    }
+                    inner.draw_at(image, e.offset);
+                })?;
+            }
+            Self::Scene(entities) => {
+                for e in entities {
+                    e.draw(image)?;
+                }
+            }
+            Self::PhantomData(_) => {}
+        };
+
+        Ok(())
+    }
+}
+
+/// Runs the given draw, turning a panic into an error instead of unwinding.
+fn catch_draw_panic(draw: impl FnOnce()) -> Result<(), Error> {
+    panic::catch_unwind(AssertUnwindSafe(draw)).map_err(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(ToString::to_string)
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+
+        Error::DrawPanicked(message)
+    })
+}
+
+fn shape_colors(
+    fill: Option<Dynamic>,
+    border: &Option<RilBorder<Dynamic>>,
+    shadows: [&Option<Shadow>; 2],
+) -> Vec<Dynamic> {
+    fill.into_iter()
+        .chain(border.as_ref().map(|border| border.color))
+        .chain(shadows.into_iter().flatten().map(|shadow| shadow.color))
+        .collect()
+}
+
+fn convert_shape_colors(
+    fill: &mut Option<Dynamic>,
+    border: &mut Option<RilBorder<Dynamic>>,
+    shadows: [&mut Option<Shadow>; 2],
+    mode: Mode,
+) {
+    if let Some(fill) = fill {
+        *fill = mode.convert_pixel(*fill);
+    }
+
+    if let Some(border) = border {
+        border.color = mode.convert_pixel(border.color);
+    }
+
+    for shadow in shadows {
+        convert_shadow_color(shadow, mode);
+    }
+}
+
+fn convert_shadow_color(shadow: &mut Option<Shadow>, mode: Mode) {
+    if let Some(shadow) = shadow {
+        shadow.color = mode.convert_pixel(shadow.color);
     }
 }
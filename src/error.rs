@@ -1,16 +1,48 @@
 use std::sync::{PoisonError, RwLockReadGuard, RwLockWriteGuard};
 
 use pyo3::{
-    exceptions::{PyIOError, PyRuntimeError, PyTypeError, PyValueError},
+    create_exception,
+    exceptions::{PyBufferError, PyIOError, PyRuntimeError, PyTypeError, PyValueError},
     prelude::*,
 };
 use ril::{Error as RilError, Dynamic};
 
 use crate::workaround::OwnedTextLayout;
 
+create_exception!(
+    ril,
+    UnknownFormatError,
+    PyRuntimeError,
+    "Raised when the encoding format of some data can't be inferred."
+);
+
+create_exception!(
+    ril,
+    BufferTooSmallError,
+    PyBufferError,
+    "Raised when encoded data does not fit into the given buffer. The size it needs is stored in `required_size`."
+);
+
+create_exception!(
+    ril,
+    UnsupportedFormatError,
+    PyValueError,
+    "Raised when encoding to a format this build can't encode to. The formats it can encode to are stored in `supported`."
+);
+
 pub enum Error {
     Ril(RilError),
     UnexpectedFormat(String, String), // (Expected, Got)
+    UnknownFormat(Vec<u8>), // Leading bytes of the data
+    TargetSizeUnreachable(usize, usize), // (Target, Best), in bytes
+    BufferTooSmall(usize, usize), // (Required, Available), in bytes
+    OptionsMismatch(String, String), // (Options format, Encoding)
+    MissingEncoding,
+    UnsupportedFormat(String, Vec<&'static str>), // (Requested, Supported)
+    MissingGlyphs(Vec<(usize, char)>), // (Index, Character)
+    Nondeterministic(String), // Reason
+    Closed(&'static str), // Kind of object
+    DrawPanicked(String), // Panic message
     PoisionError
 }
 
@@ -39,7 +71,66 @@ impl From<Error> for PyErr {
                 "Invalid Image format, expected `{}`, got `{}`",
                 expected, got
             )),
-            Error::PoisionError => PyRuntimeError::new_err("The internal RwLock was poisoned."),
+            Error::UnknownFormat(magic) => UnknownFormatError::new_err(format!(
+                "Could not infer the image format from its leading bytes: [{}]",
+                magic
+                    .iter()
+                    .map(|b| format!("{:02x}", b))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            )),
+            Error::TargetSizeUnreachable(target, best) => PyRuntimeError::new_err(format!(
+                "Could not encode the image in {} bytes or less, the smallest achieved was {} bytes",
+                target, best
+            )),
+            Error::BufferTooSmall(required, available) => Python::with_gil(|py| {
+                let err = BufferTooSmallError::new_err(format!(
+                    "The encoded data needs a buffer of {} bytes, got one of {} bytes",
+                    required, available
+                ));
+
+                // Exception instances always accept new attributes.
+                let _ = err.value(py).setattr("required_size", required);
+
+                err
+            }),
+            Error::OptionsMismatch(options, encoding) => PyValueError::new_err(format!(
+                "The options are for `{}`, but the encoding is `{}`",
+                options, encoding
+            )),
+            Error::MissingEncoding => {
+                PyValueError::new_err("Expected an encoding or options to encode with")
+            }
+            Error::UnsupportedFormat(requested, supported) => Python::with_gil(|py| {
+                let err = UnsupportedFormatError::new_err(format!(
+                    "Can't encode an image sequence to `{}`, the supported encodings are {}",
+                    requested,
+                    supported.join(", ")
+                ));
+
+                let _ = err.value(py).setattr("supported", supported);
+
+                err
+            }),
+            Error::MissingGlyphs(missing) => PyValueError::new_err(format!(
+                "The font has no glyphs for the characters {}",
+                missing
+                    .iter()
+                    .map(|(index, character)| format!("{:?} at index {}", character, index))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+            Error::Nondeterministic(reason) => PyValueError::new_err(format!(
+                "Can't encode deterministically, {}",
+                reason
+            )),
+            Error::Closed(kind) => PyValueError::new_err(format!("operation on closed {}", kind)),
+            Error::DrawPanicked(message) => {
+                PyRuntimeError::new_err(format!("Drawing failed unexpectedly: {}", message))
+            }
+            Error::PoisionError => PyRuntimeError::new_err(
+                "The internal RwLock of the text layout was poisoned by a panic, call `TextLayout.recover()` to lay out its text again",
+            ),
         }
     }
 }
@@ -50,6 +141,12 @@ impl From<RilError> for Error {
     }
 }
 
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Self::Ril(RilError::IOError(err))
+    }
+}
+
 type ReadPoisionError<'a> = PoisonError<RwLockReadGuard<'a, OwnedTextLayout<Dynamic>>>;
 type WritePoisionError<'a> = PoisonError<RwLockWriteGuard<'a, OwnedTextLayout<Dynamic>>>;
 
@@ -0,0 +1,250 @@
+use ril::{Error as RilError, ImageFormat};
+
+use crate::{
+    error::Error,
+    metadata::{be_u16, be_u32, gif_blocks, jpeg_segments, png_chunks, GIF_IMAGE},
+    Xy,
+};
+
+const PNG_IHDR: &[u8] = b"IHDR";
+const PNG_ACTL: &[u8] = b"acTL";
+const PNG_PLTE: &[u8] = b"PLTE";
+
+const WEBP_ANIMATION: u8 = 0x02;
+
+const JPEG_APP14: u8 = 0xEE;
+/// The transform flag of an Adobe `APP14` segment for YCCK data.
+const ADOBE_YCCK: u8 = 2;
+
+fn le_u16(bytes: &[u8], offset: usize) -> Option<u32> {
+    let bytes = bytes.get(offset..offset + 2)?;
+
+    Some(u32::from(u16::from_le_bytes([bytes[0], bytes[1]])))
+}
+
+fn le_u24(bytes: &[u8], offset: usize) -> Option<u32> {
+    let bytes = bytes.get(offset..offset + 3)?;
+
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], 0]))
+}
+
+fn le_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    let bytes = bytes.get(offset..offset + 4)?;
+
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn malformed(format: ImageFormat) -> Error {
+    Error::Ril(RilError::DecodingError(format!(
+        "Truncated or malformed {:?} header",
+        format
+    )))
+}
+
+/// Reads the dimensions of encoded data from its header, without decoding any pixels.
+pub fn dimensions(format: ImageFormat, bytes: &[u8]) -> Result<Xy, Error> {
+    let dimensions = match format {
+        ImageFormat::Png => png_dimensions(bytes),
+        ImageFormat::Jpeg => jpeg_dimensions(bytes),
+        ImageFormat::Gif => gif_dimensions(bytes),
+        ImageFormat::WebP => webp_dimensions(bytes),
+        ImageFormat::Bmp => bmp_dimensions(bytes),
+        _ => {
+            return Err(Error::Ril(RilError::DecodingError(format!(
+                "Cannot read the header of {:?} data",
+                format
+            ))))
+        }
+    };
+
+    dimensions.ok_or_else(|| malformed(format))
+}
+
+/// Reads the amount of frames of encoded data from its header, without decoding any pixels.
+///
+/// GIF data has no frame count in its header, so its blocks are walked instead.
+pub fn frame_count(format: ImageFormat, bytes: &[u8]) -> Result<usize, Error> {
+    dimensions(format, bytes)?;
+
+    let count = match format {
+        ImageFormat::Png => png_chunks(bytes)
+            .find(|(_, kind, _)| *kind == PNG_ACTL)
+            .map_or(Some(1), |(offset, _, _)| be_u32(bytes, offset + 8)),
+        ImageFormat::Gif => Some(
+            gif_blocks(bytes)
+                .filter(|(_, introducer, _)| *introducer == GIF_IMAGE)
+                .count(),
+        )
+        .filter(|&count| count > 0),
+        ImageFormat::WebP => webp_frame_count(bytes),
+        _ => Some(1),
+    };
+
+    count.ok_or_else(|| malformed(format))
+}
+
+/// Reads the global palette of encoded data, which is the global color table of GIF data and the
+/// `PLTE` chunk of PNG data. Other formats, and data without one, have no palette.
+pub fn palette(format: ImageFormat, bytes: &[u8]) -> Option<Vec<[u8; 3]>> {
+    let (offset, length) = match format {
+        ImageFormat::Gif => {
+            gif_dimensions(bytes)?;
+
+            let flags = *bytes.get(10)?;
+            if flags & 0x80 == 0 {
+                return None;
+            }
+
+            (13, 3 * (1 << ((flags & 0x07) + 1)))
+        }
+        ImageFormat::Png => {
+            let (offset, _, total) = png_chunks(bytes).find(|(_, kind, _)| *kind == PNG_PLTE)?;
+
+            (offset + 8, total - 12)
+        }
+        _ => return None,
+    };
+
+    Some(
+        bytes
+            .get(offset..offset + length)?
+            .chunks_exact(3)
+            .map(|color| [color[0], color[1], color[2]])
+            .collect(),
+    )
+}
+
+fn png_dimensions(bytes: &[u8]) -> Option<Xy> {
+    let (offset, kind, _) = png_chunks(bytes).next()?;
+    if kind != PNG_IHDR {
+        return None;
+    }
+
+    Some((
+        be_u32(bytes, offset + 8)? as u32,
+        be_u32(bytes, offset + 12)? as u32,
+    ))
+}
+
+/// Reads the color model of JPEG data with four components, which is either `cmyk`, or `ycck` when an Adobe
+/// `APP14` segment says so. Data with fewer components has no such color model.
+pub fn jpeg_color_model(bytes: &[u8]) -> Option<&'static str> {
+    let offset = jpeg_frame(bytes)?;
+    if *bytes.get(offset + 9)? != 4 {
+        return None;
+    }
+
+    let ycck = jpeg_segments(bytes).any(|(offset, marker, _)| {
+        marker == JPEG_APP14
+            && bytes.get(offset + 4..offset + 9) == Some(&b"Adobe"[..])
+            && bytes.get(offset + 15) == Some(&ADOBE_YCCK)
+    });
+
+    Some(if ycck { "ycck" } else { "cmyk" })
+}
+
+/// Returns the offset of the start of frame segment of JPEG data.
+fn jpeg_frame(bytes: &[u8]) -> Option<usize> {
+    // Any start of frame marker, except for DHT, JPG and DAC which share the range.
+    let (offset, _, _) = jpeg_segments(bytes).find(|&(_, marker, _)| {
+        matches!(marker, 0xC0..=0xCF) && !matches!(marker, 0xC4 | 0xC8 | 0xCC)
+    })?;
+
+    Some(offset)
+}
+
+fn jpeg_dimensions(bytes: &[u8]) -> Option<Xy> {
+    let offset = jpeg_frame(bytes)?;
+
+    Some((
+        be_u16(bytes, offset + 7)? as u32,
+        be_u16(bytes, offset + 5)? as u32,
+    ))
+}
+
+fn gif_dimensions(bytes: &[u8]) -> Option<Xy> {
+    if !bytes.starts_with(b"GIF") {
+        return None;
+    }
+
+    Some((le_u16(bytes, 6)?, le_u16(bytes, 8)?))
+}
+
+/// Iterates over the RIFF chunks of WebP data as `(offset, fourcc)`, where `offset` is the start of the chunk data.
+fn webp_chunks(bytes: &[u8]) -> impl Iterator<Item = (usize, &[u8])> + '_ {
+    let mut offset = if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(&b"WEBP"[..]) {
+        12
+    } else {
+        bytes.len()
+    };
+
+    std::iter::from_fn(move || {
+        let fourcc = bytes.get(offset..offset + 4)?;
+        let size = le_u32(bytes, offset + 4)? as usize;
+
+        let chunk = (offset + 8, fourcc);
+        // Chunks are padded to an even size
+        offset += 8 + size + (size & 1);
+
+        Some(chunk)
+    })
+}
+
+fn webp_dimensions(bytes: &[u8]) -> Option<Xy> {
+    let (data, fourcc) = webp_chunks(bytes).next()?;
+
+    match fourcc {
+        b"VP8 " => {
+            if bytes.get(data + 3..data + 6)? != [0x9D, 0x01, 0x2A] {
+                return None;
+            }
+
+            Some((
+                le_u16(bytes, data + 6)? & 0x3FFF,
+                le_u16(bytes, data + 8)? & 0x3FFF,
+            ))
+        }
+        b"VP8L" => {
+            if *bytes.get(data)? != 0x2F {
+                return None;
+            }
+
+            let bits = le_u32(bytes, data + 1)?;
+            Some(((bits & 0x3FFF) + 1, ((bits >> 14) & 0x3FFF) + 1))
+        }
+        b"VP8X" => Some((le_u24(bytes, data + 4)? + 1, le_u24(bytes, data + 7)? + 1)),
+        _ => None,
+    }
+}
+
+fn webp_frame_count(bytes: &[u8]) -> Option<usize> {
+    let (data, fourcc) = webp_chunks(bytes).next()?;
+
+    if fourcc != b"VP8X" || *bytes.get(data)? & WEBP_ANIMATION == 0 {
+        return Some(1);
+    }
+
+    Some(
+        webp_chunks(bytes)
+            .filter(|(_, fourcc)| *fourcc == b"ANMF")
+            .count(),
+    )
+    .filter(|&count| count > 0)
+}
+
+fn bmp_dimensions(bytes: &[u8]) -> Option<Xy> {
+    if !bytes.starts_with(b"BM") {
+        return None;
+    }
+
+    // The OS/2 core header stores unsigned 16-bit dimensions, every later header signed 32-bit ones,
+    // where a negative height means the rows are stored top-down.
+    if le_u32(bytes, 14)? == 12 {
+        Some((le_u16(bytes, 18)?, le_u16(bytes, 20)?))
+    } else {
+        let width = le_u32(bytes, 18)? as i32;
+        let height = le_u32(bytes, 22)? as i32;
+
+        Some((width.unsigned_abs(), height.unsigned_abs()))
+    }
+}
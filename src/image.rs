@@ -1,25 +1,100 @@
-use std::path::PathBuf;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
 
-use crate::draw::DrawEntity;
+use crate::asyncio::spawn_blocking;
+use crate::blur::{box_blur, gaussian_box_radii};
+use crate::color::{linear_to_srgb, srgb_to_linear, ChannelLuts, ColorSpace};
+use crate::draw::{DrawEntity, DrawTransform};
 use crate::error::Error;
-use crate::pixels::{BitPixel, Pixel, Rgb, Rgba, L};
-use crate::types::{ResizeAlgorithm, OverlayMode};
-use crate::utils::cast_pixel_to_pyobject;
+use crate::header;
+use crate::metadata::Metadata;
+use crate::options::{EncodeOptions, GifOptions, WebPOptions};
+use crate::parallel::for_each_row;
+use crate::pipeline::{Pipeline, Transform};
+use crate::pixels::{BitPixel, DistanceMetric, Pixel, Rgb, Rgba, L};
+use crate::resample::resample;
+use crate::trace;
+use crate::types::{
+    FormatArg, ImageFormat as PyImageFormat, Mode, ModeArg, OverlayMode, ResizeAlgorithm,
+};
+use crate::utils::{
+    cast_pixel_to_pyobject, ensure_box, ensure_dimensions, ensure_open, format_from_path,
+    infer_format, notebook_preview, Shared,
+};
+use crate::Xy;
+use jpeg_decoder::{CodingProcess, Decoder as JpegDecoder};
+use pyo3::buffer::PyBuffer;
 use pyo3::types::PyBytes;
 use pyo3::{
-    exceptions::{PyTypeError, PyValueError},
+    exceptions::{
+        PyBufferError, PyIndexError, PyRuntimeError, PyTypeError, PyUserWarning, PyValueError,
+    },
     prelude::*,
     types::{PyTuple, PyType},
 };
-use ril::{Banded, Dynamic, Image as RilImage, ImageFormat, Draw as _};
+use ril::encodings::{
+    jpeg::JpegEncoder,
+    png::{AdaptiveFilterType, Compression, FilterType, PngEncoder},
+};
+use ril::{
+    Banded, Dynamic, Encoder as _, Error as RilError, Image as RilImage, ImageFormat,
+    ImageSequence as RilImageSequence, Pixel as _,
+};
+
+/// A bounding box given as the upper-left and lower-right corners ``(x1, y1, x2, y2)``.
+type Region = (u32, u32, u32, u32);
+
+/// A thickness given either for every side, or per side as ``(left, top, right, bottom)``.
+#[derive(FromPyObject)]
+enum Sides {
+    Uniform(i64),
+    Each((i64, i64, i64, i64)),
+}
+
+impl Sides {
+    fn validate(self) -> PyResult<(u32, u32, u32, u32)> {
+        let (left, top, right, bottom) = match self {
+            Self::Uniform(thickness) => (thickness, thickness, thickness, thickness),
+            Self::Each(sides) => sides,
+        };
+
+        let side = |thickness: i64| {
+            u32::try_from(thickness).map_err(|_| {
+                PyValueError::new_err(format!(
+                    "Expected a non-negative thickness, got {}",
+                    thickness
+                ))
+            })
+        };
+
+        Ok((side(left)?, side(top)?, side(right)?, side(bottom)?))
+    }
+}
 
 /// A high-level image representation.
 ///
 /// This represents a static, single-frame image. See :class:`.ImageSequence` for information on opening animated or multi-frame images.
+///
+/// Images always have a non-zero width and height. Constructors and methods raise `ValueError` instead of creating an empty image.
+///
+/// Images can be made read-only with :meth:`freeze`, after which modifying them in place raises a `RuntimeError`.
+/// Use :meth:`close`, or the image as a context manager, to release its pixels without waiting for garbage collection.
 #[pyclass]
 #[derive(Clone)]
 pub struct Image {
-    pub inner: RilImage<Dynamic>,
+    pub inner: Shared<RilImage<Dynamic>>,
+    pub(crate) metadata: Metadata,
+    pub(crate) source_format: Option<ImageFormat>,
+    pub(crate) source_color_model: Option<&'static str>,
+    pub(crate) decode_warnings: Vec<String>,
+    pub(crate) color_space: ColorSpace,
+    /// The stack of draw transforms, each composed with the ones below it.
+    transforms: Vec<DrawTransform>,
+    frozen: bool,
+    closed: bool,
 }
 
 macro_rules! cast_bands_to_pyobjects {
@@ -36,7 +111,7 @@ macro_rules! to_inner_bands {
     ($bands:expr, $($band:tt),*) => {{
         (
             $(
-                $bands.$band.inner.convert::<ril::L>(),
+                $bands.$band.inner.into_inner().convert::<ril::L>(),
             )*
         )
     }};
@@ -45,8 +120,8 @@ macro_rules! to_inner_bands {
 macro_rules! ensure_mode {
     ($bands:expr, $($band:tt),*) => {{
         $(
-            if $bands.$band.mode() != "L" {
-                return Err(PyTypeError::new_err(format!("Expected mode `L`, got `{}`", $bands.$band.mode())));
+            if $bands.$band.mode_enum() != Mode::L {
+                return Err(PyTypeError::new_err(format!("Expected mode `L`, got `{}`", $bands.$band.mode_enum())));
             }
         )*
 
@@ -66,6 +141,13 @@ impl Image {
     ///     The height of the Image.
     /// fill: :class:`.Pixel`
     ///     The pixel used to fill the image.
+    /// mode: Optional[Union[:class:`.Mode`, str]], default: None
+    ///     The mode of the image, `fill` is converted to this mode. Defaults to the mode of `fill`.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     `width` or `height` is zero.
     ///
     /// Examples
     /// --------
@@ -74,71 +156,455 @@ impl Image {
     ///
     ///     Image.new(100, 100, Pixel.from_rgb(255, 255, 255))
     #[classmethod]
-    #[pyo3(text_signature = "(cls, width, height, fill)")]
-    fn new(_: &PyType, width: u32, height: u32, fill: Pixel) -> Self {
-        Self {
-            inner: RilImage::new(width, height, fill.inner),
-        }
+    #[pyo3(signature = (width, height, fill, mode = None))]
+    #[pyo3(text_signature = "($cls, width, height, fill, mode = None)")]
+    fn new(
+        _: &PyType,
+        width: u32,
+        height: u32,
+        fill: Pixel,
+        mode: Option<ModeArg>,
+    ) -> PyResult<Self> {
+        ensure_dimensions(width, height)?;
+
+        let fill = match mode {
+            Some(ModeArg(mode)) => mode.convert_pixel(fill.inner),
+            None => fill.inner,
+        };
+
+        Ok(Self::from_inner(RilImage::new(width, height, fill)))
     }
 
     /// Decodes an image with the explicitly given image encoding from the raw bytes.
     ///
     /// if `format` is not provided then it will try to infer its encoding.
+    /// Animated GIFs decode to their first frame, use :class:`.ImageSequence` to decode every frame.
     ///
     /// Parameters
     /// ----------
     /// bytes: bytes
     ///     The bytes of the Image.
-    /// format: Optional[str], default: None
+    /// format: Optional[Union[:class:`.ImageFormat`, str]], default: None
     ///     The format of the image, defaults to `None`.
+    /// tolerant: bool, default: False
+    ///     Whether to decode as much as possible of data that is cut off, such as a half-downloaded JPEG,
+    ///     instead of raising. What could be recovered is described by :attr:`decode_warnings`.
+    ///     Only JPEG data can be recovered, other data that fails to decode still raises.
+    /// fill: Optional[:class:`.Pixel`], default: None
+    ///     Only used with `tolerant`. The color to fill the rows that couldn't be decoded with, defaults to black.
+    ///     Progressive JPEG data is never filled, as every scan covers the whole image.
     ///
     /// Raises
     /// ------
     /// ValueError
     ///     Raised if the format provided is invalid.
+    /// UnknownFormatError
+    ///     Raised if `format` is not provided and it can't be inferred from the bytes.
     /// RuntimeError
-    ///     Raised if the image can't be decoded or the format is unknown.
+    ///     Raised if the image can't be decoded, or can't be recovered with `tolerant` set.
+    ///
+    /// .. note::
+    ///     The GIL is released while decoding.
+    ///
+    /// Examples
+    /// --------
+    ///
+    /// .. code-block:: python3
+    ///
+    ///     image = Image.from_bytes(partial, tolerant=True, fill=Pixel.from_rgb(128, 128, 128))
+    ///     if image.decode_warnings:
+    ///         print('Recovered a partial image:', *image.decode_warnings)
     #[classmethod]
-    #[pyo3(text_signature = "(cls, bytes, format = None)")]
-    fn from_bytes(_: &PyType, bytes: &[u8], format: Option<&str>) -> Result<Self, Error> {
-        Ok(if let Some(format) = format {
-            Self {
-                inner: RilImage::from_bytes(ImageFormat::from_extension(format)?, bytes)?,
-            }
-        } else {
-            Self {
-                inner: RilImage::from_bytes_inferred(bytes)?,
-            }
+    #[pyo3(signature = (bytes, format = None, *, tolerant = false, fill = None))]
+    #[pyo3(text_signature = "($cls, bytes, format = None, *, tolerant = False, fill = None)")]
+    fn from_bytes(
+        _: &PyType,
+        py: Python<'_>,
+        bytes: &[u8],
+        format: Option<FormatArg>,
+        tolerant: bool,
+        fill: Option<Pixel>,
+    ) -> Result<Self, Error> {
+        let format = match format {
+            Some(FormatArg(format)) => format,
+            None => infer_format(bytes)?,
+        };
+
+        if !tolerant {
+            return py.allow_threads(|| Self::decoded(format, bytes));
+        }
+
+        let black = Dynamic::Rgb(ril::Rgb { r: 0, g: 0, b: 0 });
+        let fill = fill.map_or(black, |fill| fill.inner);
+        let (image, decode_warnings) = py.allow_threads(|| decode_tolerant(format, bytes, fill))?;
+
+        Ok(Self {
+            decode_warnings,
+            ..Self::from_source(image, format, bytes)
         })
     }
 
+    /// Reads the dimensions of an encoded image from its header, without decoding it or allocating its pixels.
+    ///
+    /// This is useful for cheaply rejecting oversized images before decoding them with :meth:`from_bytes`.
+    /// PNG, JPEG, GIF, WebP and BMP headers are supported.
+    ///
+    /// Parameters
+    /// ----------
+    /// bytes: bytes
+    ///     The bytes of the image, only the header is read.
+    ///
+    /// Returns
+    /// -------
+    /// Tuple[int, int]
+    ///     The width and height of the image.
+    ///
+    /// Raises
+    /// ------
+    /// UnknownFormatError
+    ///     The format can't be inferred from the bytes.
+    /// RuntimeError
+    ///     The header is truncated or malformed, or the format is not supported.
+    #[classmethod]
+    #[pyo3(text_signature = "($cls, bytes)")]
+    fn peek_dimensions(_: &PyType, bytes: &[u8]) -> Result<Xy, Error> {
+        header::dimensions(infer_format(bytes)?, bytes)
+    }
+
     /// Creates a new image shaped with the given width
     /// and a 1-dimensional sequence of pixels which will be shaped according to the width.
     ///
+    /// Pixels of different modes are converted to the widest mode among them, ordered `BITPIXEL`, `L`, `RGB`
+    /// and `RGBA`, so that every pixel of the image has the same mode.
+    ///
     /// Parameters
     /// ----------
     /// width: int
     ///     The width of the image.
     /// pixels: List[:class:`.Pixel`]
     ///     A List of pixels.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     `width` is zero, `pixels` is empty, or the amount of pixels is not a multiple of `width`.
     #[classmethod]
-    #[pyo3(text_signature = "(cls, width, pixels)")]
-    fn from_pixels(_: &PyType, width: u32, pixels: Vec<Pixel>) -> Self {
-        Self {
-            inner: RilImage::from_pixels(
+    #[pyo3(text_signature = "($cls, width, pixels)")]
+    fn from_pixels(_: &PyType, width: u32, pixels: Vec<Pixel>) -> PyResult<Self> {
+        ensure_dimensions(width, pixels.len() as u32 / width.max(1))?;
+
+        if pixels.len() % width as usize != 0 {
+            return Err(PyValueError::new_err(format!(
+                "Expected the amount of pixels to be a multiple of the width {}, got {}",
                 width,
-                pixels
-                    .into_iter()
-                    .map(|p| p.inner)
-                    .collect::<Vec<Dynamic>>(),
-            ),
+                pixels.len()
+            )));
+        }
+
+        let mode = pixels
+            .iter()
+            .map(|pixel| Mode::from(&pixel.inner))
+            .max()
+            .unwrap_or(Mode::Rgba);
+
+        Ok(Self::from_inner(RilImage::from_pixels(
+            width,
+            pixels
+                .into_iter()
+                .map(|p| mode.convert_pixel(p.inner))
+                .collect::<Vec<Dynamic>>(),
+        )))
+    }
+
+    /// Creates a new `BITPIXEL` image from packed bits, most significant bit first, where a set bit is an on pixel.
+    ///
+    /// Each row starts on a new byte and is padded to a multiple of `row_align` bytes.
+    ///
+    /// Parameters
+    /// ----------
+    /// width: int
+    ///     The width of the image.
+    /// height: int
+    ///     The height of the image.
+    /// data: bytes
+    ///     The packed bits.
+    /// row_align: int, default: 1
+    ///     The byte alignment of each row.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     `width`, `height` or `row_align` is zero, or the length of `data` doesn't match the dimensions.
+    #[classmethod]
+    #[pyo3(signature = (width, height, data, row_align = 1))]
+    #[pyo3(text_signature = "($cls, width, height, data, row_align = 1)")]
+    fn from_packed_bits(
+        _: &PyType,
+        width: u32,
+        height: u32,
+        data: &[u8],
+        row_align: usize,
+    ) -> PyResult<Self> {
+        ensure_dimensions(width, height)?;
+
+        let stride = packed_stride(width, row_align)?;
+        let expected = stride * height as usize;
+
+        if data.len() != expected {
+            return Err(PyValueError::new_err(format!(
+                "Expected {} bytes for a {}x{} image with a row alignment of {}, got {}",
+                expected,
+                width,
+                height,
+                row_align,
+                data.len()
+            )));
+        }
+
+        let pixels = data
+            .chunks(stride)
+            .flat_map(|row| {
+                (0..width as usize)
+                    .map(move |x| Dynamic::BitPixel(ril::BitPixel(row[x / 8] & (0x80 >> (x % 8)) != 0)))
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Self::from_inner(RilImage::from_pixels(width, pixels)))
+    }
+
+    /// Packs this `BITPIXEL` image into bits, most significant bit first, where a set bit is an on pixel.
+    ///
+    /// Each row starts on a new byte and is padded with zeros to a multiple of `row_align` bytes.
+    ///
+    /// Parameters
+    /// ----------
+    /// row_align: int, default: 1
+    ///     The byte alignment of each row.
+    ///
+    /// Returns
+    /// -------
+    /// bytes
+    ///     The packed bits.
+    ///
+    /// Raises
+    /// ------
+    /// TypeError
+    ///     The image is not of mode `BITPIXEL`.
+    /// ValueError
+    ///     `row_align` is zero.
+    #[pyo3(signature = (row_align = 1))]
+    #[pyo3(text_signature = "($self, row_align = 1)")]
+    fn to_packed_bits<'a>(&self, py: Python<'a>, row_align: usize) -> PyResult<&'a PyBytes> {
+        ensure_open!(self);
+
+        if self.mode_enum() != Mode::BitPixel {
+            return Err(Error::UnexpectedFormat(Mode::BitPixel.to_string(), self.mode()).into());
+        }
+
+        let width = self.inner.width();
+        let stride = packed_stride(width, row_align)?;
+
+        let mut buf = vec![0; stride * self.inner.height() as usize];
+        for (row, packed) in self.inner.data.chunks(width as usize).zip(buf.chunks_mut(stride)) {
+            for (x, pixel) in row.iter().enumerate() {
+                let pixel: ril::BitPixel = (*pixel).into();
+
+                if pixel.value() {
+                    packed[x / 8] |= 0x80 >> (x % 8);
+                }
+            }
+        }
+
+        Ok(PyBytes::new(py, &buf))
+    }
+
+    /// Creates a new image from raw pixel data, such as a `numpy` array or the output of :meth:`to_bytes`.
+    ///
+    /// The data holds the channels of every pixel interleaved, row by row, where each pixel takes up as many bytes
+    /// as it has channels in the given mode. A `BITPIXEL` byte above ``127`` is an on pixel.
+    ///
+    /// Parameters
+    /// ----------
+    /// width: int
+    ///     The width of the image.
+    /// height: int
+    ///     The height of the image.
+    /// mode: Union[:class:`.Mode`, str]
+    ///     The mode of the pixel data.
+    /// data: Union[bytes, bytearray, memoryview]
+    ///     Any object supporting the buffer protocol with items of a single byte, such as a `numpy` array of dtype
+    ///     `uint8`. Non-contiguous buffers are copied into a contiguous one first.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     `width` or `height` is zero, or the length of `data` doesn't match the dimensions and mode.
+    /// BufferError
+    ///     `data` doesn't support the buffer protocol with single bytes as items.
+    ///
+    /// Examples
+    /// --------
+    ///
+    /// .. code-block:: python3
+    ///
+    ///     array = np.frombuffer(image.to_bytes(), dtype=np.uint8).reshape(image.height, image.width, 4)
+    ///     image = Image.from_buffer(image.width, image.height, 'RGBA', 255 - array)
+    #[classmethod]
+    #[pyo3(text_signature = "($cls, width, height, mode, data)")]
+    fn from_buffer(
+        _: &PyType,
+        py: Python<'_>,
+        width: u32,
+        height: u32,
+        mode: ModeArg,
+        data: &PyAny,
+    ) -> PyResult<Self> {
+        ensure_dimensions(width, height)?;
+
+        let ModeArg(mode) = mode;
+        let data = PyBuffer::<u8>::get(data)?.to_vec(py)?;
+        let expected = width as usize * height as usize * mode.channels();
+
+        if data.len() != expected {
+            return Err(PyValueError::new_err(format!(
+                "Expected {} bytes for a {}x{} image of mode {}, got {}",
+                expected,
+                width,
+                height,
+                mode,
+                data.len()
+            )));
         }
+
+        let pixels = py.allow_threads(|| {
+            data.chunks_exact(mode.channels())
+                .map(|pixel| match *pixel {
+                    [r, g, b, a] => Dynamic::Rgba(ril::Rgba { r, g, b, a }),
+                    [r, g, b] => Dynamic::Rgb(ril::Rgb { r, g, b }),
+                    [value] if mode == Mode::BitPixel => {
+                        Dynamic::BitPixel(ril::BitPixel(value > 127))
+                    }
+                    _ => Dynamic::L(ril::L(pixel[0])),
+                })
+                .collect::<Vec<_>>()
+        });
+
+        Ok(Self::from_inner(RilImage::from_pixels(width, pixels)))
+    }
+
+    /// Returns the raw pixel data of this image as a single contiguous buffer, row by row.
+    ///
+    /// Each pixel takes up as many bytes as it has channels in the mode of this image,
+    /// where a :class:`.BitPixel` is either ``0`` or ``255``. See :meth:`from_buffer` to create an image from it.
+    ///
+    /// Returns
+    /// -------
+    /// bytes
+    ///     The raw pixel data.
+    ///
+    /// Examples
+    /// --------
+    ///
+    /// .. code-block:: python3
+    ///
+    ///     array = np.frombuffer(image.to_bytes(), dtype=np.uint8).reshape(image.height, image.width, 4)
+    #[pyo3(text_signature = "($self)")]
+    fn to_bytes<'a>(&self, py: Python<'a>) -> PyResult<&'a PyBytes> {
+        ensure_open!(self);
+
+        let inner = &self.inner;
+        let bytes = py.allow_threads(|| raw_bytes(inner.data.iter()));
+
+        Ok(PyBytes::new(py, &bytes))
+    }
+
+    /// Creates a new image from a Pillow image, copying its raw pixel data without encoding it.
+    ///
+    /// Pillow images of mode `1`, `L`, `RGB` and `RGBA` are supported, where `1` becomes a `BITPIXEL` image.
+    /// Images of any other mode, such as `P` or `CMYK`, must be converted first, such as with ``image.convert('RGBA')``.
+    ///
+    /// Pillow is imported when this is called, so it is only needed when converting.
+    ///
+    /// Parameters
+    /// ----------
+    /// image: PIL.Image.Image
+    ///     The Pillow image.
+    ///
+    /// Returns
+    /// -------
+    /// :class:`.Image`
+    ///     The new image.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The mode of the Pillow image is not supported.
+    ///
+    /// Examples
+    /// --------
+    ///
+    /// .. code-block:: python3
+    ///
+    ///     image = Image.from_pillow(PIL.Image.open('photo.png').convert('RGB'))
+    #[classmethod]
+    #[pyo3(text_signature = "($cls, image)")]
+    fn from_pillow(cls: &PyType, py: Python<'_>, image: &PyAny) -> PyResult<Self> {
+        let mode = image.getattr("mode")?.extract::<&str>()?;
+        let (mode, raw_mode) = match mode {
+            "1" => (Mode::BitPixel, "L"),
+            "L" => (Mode::L, "L"),
+            "RGB" => (Mode::Rgb, "RGB"),
+            "RGBA" => (Mode::Rgba, "RGBA"),
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Pillow images of mode `{}` are not supported, convert them to `1`, `L`, `RGB` or `RGBA` first, \
+                     such as with `image.convert('RGBA')`",
+                    mode
+                )))
+            }
+        };
+
+        let (width, height) = image.getattr("size")?.extract::<Xy>()?;
+        let data = image.call_method1("tobytes", ("raw", raw_mode))?;
+
+        Self::from_buffer(cls, py, width, height, ModeArg(mode), data)
+    }
+
+    /// Converts this image into a Pillow image, copying its raw pixel data without encoding it.
+    ///
+    /// `BITPIXEL` images become Pillow images of mode `1`, and images of any other mode keep their mode.
+    /// Pillow is imported when this is called, so it is only needed when converting.
+    ///
+    /// Returns
+    /// -------
+    /// PIL.Image.Image
+    ///     The new Pillow image.
+    ///
+    /// Raises
+    /// ------
+    /// ImportError
+    ///     Pillow is not installed.
+    #[pyo3(text_signature = "($self)")]
+    fn to_pillow<'a>(&self, py: Python<'a>) -> PyResult<&'a PyAny> {
+        let data = self.to_bytes(py)?;
+        let (mode, raw_mode) = match self.mode_enum() {
+            Mode::BitPixel => ("1", "1;8"),
+            Mode::L => ("L", "L"),
+            Mode::Rgb => ("RGB", "RGB"),
+            Mode::Rgba => ("RGBA", "RGBA"),
+        };
+
+        py.import("PIL.Image")?.call_method1(
+            "frombytes",
+            (mode, self.inner.dimensions(), data, "raw", raw_mode),
+        )
     }
 
     /// Opens a file from the given path and decodes it into an image.
     ///
     /// The encoding of the image is automatically inferred.
     /// You can explicitly pass in an encoding by using the :meth:`from_bytes` method.
+    /// Animated GIFs decode to their first frame, use :class:`.ImageSequence` to decode every frame.
     ///
     /// Parameters
     /// ----------
@@ -151,41 +617,87 @@ impl Image {
     ///     The file extension is invalid.
     /// RuntimeError
     ///     Failed to infer file format or Failed to decode image.
+    ///
+    /// .. note::
+    ///     The GIL is released while reading and decoding the file.
     #[classmethod]
-    #[pyo3(text_signature = "(cls, path)")]
-    fn open(_: &PyType, path: PathBuf) -> Result<Self, Error> {
-        Ok(Self {
-            inner: RilImage::open(path)?,
-        })
+    #[pyo3(text_signature = "($cls, path)")]
+    fn open(_: &PyType, py: Python<'_>, path: PathBuf) -> Result<Self, Error> {
+        py.allow_threads(|| Self::open_path(&path))
+    }
+
+    /// Like :meth:`open`, but reads and decodes the file on a separate thread without blocking the event loop.
+    ///
+    /// This must be called from a running :mod:`asyncio` event loop.
+    ///
+    /// Parameters
+    /// ----------
+    /// path: str
+    ///     The path to the image.
+    ///
+    /// Returns
+    /// -------
+    /// Awaitable[:class:`.Image`]
+    ///     A future resolving to the decoded image, raising the same errors as :meth:`open`.
+    ///
+    /// Raises
+    /// ------
+    /// RuntimeError
+    ///     There is no running event loop.
+    ///
+    /// Examples
+    /// --------
+    ///
+    /// .. code-block:: python3
+    ///
+    ///     image = await Image.open_async('sample.png')
+    #[classmethod]
+    #[pyo3(text_signature = "($cls, path)")]
+    fn open_async<'a>(_: &PyType, py: Python<'a>, path: PathBuf) -> PyResult<&'a PyAny> {
+        spawn_blocking(py, move || Self::open_path(&path))
     }
 
     /// :class:`.OverlayMode`: Returns the overlay mode of the image.
     #[getter]
-    fn overlay_mode(&self) -> OverlayMode {
-        self.inner.overlay_mode().into()
+    fn overlay_mode(&self) -> PyResult<OverlayMode> {
+        ensure_open!(self);
+
+        Ok(self.inner.overlay_mode().into())
     }
 
-    /// str: Returns the mode of the image.
+    /// str: Returns the mode of the image, one of `BITPIXEL`, `L`, `RGB` or `RGBA`.
+    ///
+    /// .. note::
+    ///     The mode is always uppercase, use :attr:`mode_enum` to avoid string comparisons.
     #[getter]
-    fn mode(&self) -> &str {
-        match self.inner.pixel(0, 0) {
-            Dynamic::BitPixel(_) => "bitpixel",
-            Dynamic::L(_) => "L",
-            Dynamic::Rgb(_) => "RGB",
-            Dynamic::Rgba(_) => "RGBA",
-        }
+    fn get_mode(&self) -> PyResult<String> {
+        ensure_open!(self);
+
+        Ok(self.mode())
+    }
+
+    /// :class:`.Mode`: Returns the mode of the image.
+    #[getter]
+    fn get_mode_enum(&self) -> PyResult<Mode> {
+        ensure_open!(self);
+
+        Ok(self.mode_enum())
     }
 
     /// int: Returns the width of the image.
     #[getter]
-    fn width(&self) -> u32 {
-        self.inner.width()
+    fn width(&self) -> PyResult<u32> {
+        ensure_open!(self);
+
+        Ok(self.inner.width())
     }
 
     /// int: Returns the height of the image.
     #[getter]
-    fn height(&self) -> u32 {
-        self.inner.height()
+    fn height(&self) -> PyResult<u32> {
+        ensure_open!(self);
+
+        Ok(self.inner.height())
     }
 
     /// Return the bands of the image.
@@ -198,21 +710,24 @@ impl Image {
     /// ------
     /// TypeError
     ///     The image is not of mode `RGB` or `RGBA`.
+    #[pyo3(text_signature = "($self)")]
     fn bands(&self, py: Python<'_>) -> Result<PyObject, Error> {
-        match self.mode() {
-            "RGB" => {
-                let (r, g, b) = self.inner.clone().convert::<ril::Rgb>().bands();
+        ensure_open!(self);
+
+        match self.mode_enum() {
+            Mode::Rgb => {
+                let (r, g, b) = RilImage::clone(&self.inner).convert::<ril::Rgb>().bands();
 
                 cast_bands_to_pyobjects!(py, r, g, b)
             }
-            "RGBA" => {
-                let (r, g, b, a) = self.inner.clone().convert::<ril::Rgba>().bands();
+            Mode::Rgba => {
+                let (r, g, b, a) = RilImage::clone(&self.inner).convert::<ril::Rgba>().bands();
 
                 cast_bands_to_pyobjects!(py, r, g, b, a)
             }
-            _ => Err(Error::UnexpectedFormat(
-                self.mode().to_string(),
-                "Rgb or Rgba".to_string(),
+            mode => Err(Error::UnexpectedFormat(
+                "RGB or RGBA".to_string(),
+                mode.to_string(),
             )),
         }
     }
@@ -224,12 +739,13 @@ impl Image {
     /// bands: \* :class:`.L`
     ///     The bands of the image.
     #[classmethod]
-    #[args(bands = "*")]
-    #[pyo3(text_signature = "(self, *bands)")]
+    #[pyo3(signature = (*bands))]
+    #[pyo3(text_signature = "($cls, *bands)")]
     fn from_bands(_: &PyType, bands: &PyTuple) -> PyResult<Self> {
         match bands.len() {
             3 => {
                 let bands: (Self, Self, Self) = bands.extract()?;
+                ensure_open!(bands.0, bands.1, bands.2);
 
                 ensure_mode!(bands, 0, 1, 2)?;
 
@@ -239,6 +755,7 @@ impl Image {
             }
             4 => {
                 let bands: (Self, Self, Self, Self) = bands.extract()?;
+                ensure_open!(bands.0, bands.1, bands.2, bands.3);
 
                 ensure_mode!(bands, 0, 1, 2, 3)?;
 
@@ -266,299 +783,4064 @@ impl Image {
     ///     The x axis of the lower-right corner
     /// y2: int
     ///     The y axis of the lower-right corner
-    #[pyo3(text_signature = "(self, x1, y1, x2, y2)")]
-    fn crop(&mut self, x1: u32, y1: u32, x2: u32, y2: u32) {
-        self.inner.crop(x1, y1, x2, y2);
-    }
-
-    /// Draws an object or shape onto this image.
     ///
-    /// Parameters
-    /// ----------
-    /// entity: Union[:class:`.Rectangle`, :class:`.Ellipse`]
-    ///     The entity to draw on the image.
-    #[pyo3(text_signature = "(self, entity)")]
-    fn draw(&mut self, entity: DrawEntity) -> Result<(), Error>{
-        match entity {
-            DrawEntity::Rectangle(e) => e.inner.draw(&mut self.inner),
-            DrawEntity::Ellipse(e) => e.inner.draw(&mut self.inner),
-            DrawEntity::TextSegment(e) => e.inner.draw(&mut self.inner),
-            DrawEntity::TextLayout(e) => e.inner.write()?.draw(&mut self.inner),
-            DrawEntity::PhantomData(_) => {},
-        };
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The bounding box is empty or exceeds the bounds of the image.
+    #[pyo3(text_signature = "($self, x1, y1, x2, y2)")]
+    fn crop(&mut self, x1: u32, y1: u32, x2: u32, y2: u32) -> PyResult<()> {
+        ensure_open!(self);
+        self.ensure_mutable()?;
+        self.ensure_box(x1, y1, x2, y2)?;
+        self.inner.crop(x1, y1, x2, y2);
+        self.metadata = self.metadata.with_dimensions(x2 - x1, y2 - y1);
 
         Ok(())
     }
 
-    /// Resizes this image in place to the given dimensions using the given resizing algorithm in place.
+    /// Expands the canvas of this image in place by the given thickness on each side,
+    /// filling the new margin with `color` and keeping the original content in the middle.
     ///
     /// Parameters
     /// ----------
-    /// width: int
-    ///     The target width to resize to
-    /// height: int
-    ///     The target height to resize to
-    /// algorithm: :class:`.ResizeAlgorithm`
-    ///     The resize algorithm to use
-    #[pyo3(text_signature = "(self, width, height, algorithm)")]
-    fn resize(&mut self, width: u32, height: u32, algorithm: ResizeAlgorithm) {
-        self.inner.resize(width, height, algorithm.into());
-    }
-
-    /// Encodes the image with the given encoding and returns `bytes`.
+    /// thickness: Union[int, Tuple[int, int, int, int]]
+    ///     The thickness of the border in pixels, either for every side or per side as ``(left, top, right, bottom)``.
+    /// color: :class:`.Pixel`
+    ///     The color of the border, converted to the mode of this image.
     ///
-    /// Parameters
-    /// ----------
-    /// encoding: str
-    ///     The encoding of the image.
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     A thickness is negative, or the enlarged image would be too large.
+    ///
+    /// Examples
+    /// --------
+    ///
+    /// .. code-block:: python3
+    ///
+    ///     image.add_border(8, Pixel.from_rgb(255, 255, 255))
+    ///     image.add_border((0, 4, 0, 4), Pixel.from_rgb(0, 0, 0))
+    #[pyo3(text_signature = "($self, thickness, color)")]
+    fn add_border(&mut self, py: Python<'_>, thickness: Sides, color: Pixel) -> PyResult<()> {
+        ensure_open!(self);
+        self.ensure_mutable()?;
+
+        let (left, top, right, bottom) = thickness.validate()?;
+        if (left, top, right, bottom) == (0, 0, 0, 0) {
+            return Ok(());
+        }
+
+        let (width, height) = self.inner.dimensions();
+        let (new_width, new_height) = match (
+            width.checked_add(left).and_then(|w| w.checked_add(right)),
+            height.checked_add(top).and_then(|h| h.checked_add(bottom)),
+        ) {
+            (Some(new_width), Some(new_height)) => (new_width, new_height),
+            _ => {
+                return Err(PyValueError::new_err(
+                    "The image with its border would be too large",
+                ))
+            }
+        };
+
+        let fill = Mode::of(&self.inner).convert_pixel(color.inner);
+        let inner = &self.inner;
+
+        let data = py.allow_threads(|| {
+            let mut data = Vec::with_capacity(new_width as usize * new_height as usize);
+            data.resize(new_width as usize * top as usize, fill);
+
+            for row in inner.data.chunks(width as usize) {
+                data.extend(std::iter::repeat(fill).take(left as usize));
+                data.extend_from_slice(row);
+                data.extend(std::iter::repeat(fill).take(right as usize));
+            }
+
+            data.resize(new_width as usize * new_height as usize, fill);
+            data
+        });
+
+        let overlay = self.inner.overlay_mode();
+        self.inner = RilImage::from_pixels(new_width, data)
+            .with_overlay_mode(overlay)
+            .into();
+        self.metadata = self.metadata.with_dimensions(new_width, new_height);
+
+        Ok(())
+    }
+
+    /// Returns a copy of this image.
+    ///
+    /// Copies share their pixels until either of them is modified, so copying is cheap until then.
+    /// Copies of a frozen image are never frozen, see :meth:`freeze`, and copies start without draw transforms,
+    /// see :meth:`push_transform`.
+    #[pyo3(text_signature = "($self)")]
+    fn copy(&self) -> PyResult<Self> {
+        ensure_open!(self);
+
+        Ok(Self {
+            frozen: false,
+            transforms: Vec::new(),
+            ..self.clone()
+        })
+    }
+
+    fn __copy__(&self) -> PyResult<Self> {
+        self.copy()
+    }
+
+    /// Starts a lazy pipeline of operations on this image, which are only run once it is executed.
+    ///
+    /// The pipeline rearranges the recorded operations to do less work, such as cropping before resizing,
+    /// while giving exactly the same pixels as calling the methods of this image one by one.
+    /// This image is left untouched, see :class:`.Pipeline`.
     ///
     /// Returns
     /// -------
-    /// bytes
-    ///     The encoded bytes of the image.
+    /// :class:`.Pipeline`
+    ///     An empty pipeline on the current pixels of this image.
+    #[pyo3(text_signature = "($self)")]
+    fn pipeline(&self) -> PyResult<Pipeline> {
+        Pipeline::new(self)
+    }
+
+    /// Marks this image as read-only, such as an image that is cached and shared.
+    ///
+    /// Every method that modifies the image in place, such as :meth:`resize`, :meth:`crop`, :meth:`draw`,
+    /// :meth:`paste` and :meth:`set_pixel`, raises a RuntimeError while the image is frozen, and so does :meth:`close`.
+    /// Use :meth:`copy` to get a mutable copy, or :meth:`thaw` to make the image mutable again.
+    #[pyo3(text_signature = "($self)")]
+    fn freeze(&mut self) -> PyResult<()> {
+        ensure_open!(self);
+
+        self.frozen = true;
+
+        Ok(())
+    }
+
+    /// Makes a frozen image mutable again, see :meth:`freeze`.
+    #[pyo3(text_signature = "($self)")]
+    fn thaw(&mut self) -> PyResult<()> {
+        ensure_open!(self);
+
+        self.frozen = false;
+
+        Ok(())
+    }
+
+    /// bool: Whether the image is read-only, see :meth:`freeze`.
+    #[getter]
+    fn frozen(&self) -> PyResult<bool> {
+        ensure_open!(self);
+
+        Ok(self.frozen)
+    }
+
+    /// Releases the pixels of this image right away instead of when it is garbage collected,
+    /// which keeps the memory use of batch jobs predictable.
+    ///
+    /// Every other method and attribute of a closed image, except :attr:`closed`, raises a `ValueError`.
+    /// Copies of the image are unaffected, and closing an image that is already closed does nothing.
+    ///
+    /// Images can also be used as context managers, which close them on exit.
     ///
     /// Raises
     /// ------
-    /// ValueError
-    ///     The encoding is invalid.
     /// RuntimeError
-    ///     Failed to encode the image.
-    #[pyo3(text_signature = "(self, encoding)")]
-    fn encode(&self, encoding: &str) -> Result<&PyBytes, Error> {
-        let encoding = ImageFormat::from_extension(encoding)?;
-
-        let mut buf = Vec::new();
-        self.inner.encode(encoding, &mut buf)?;
-
-        // SAFETY: We acquired the GIL before calling `assume_gil_acquired`.
-        // `assume_gil_acquired` is only used to ensure that PyBytes don't outlive the current function
-        unsafe {
-            Python::with_gil(|_| {
-                let buf = buf.as_slice();
-                let pyacq = Python::assume_gil_acquired();
-                Ok(PyBytes::new(pyacq, buf))
-            })
+    ///     The image is frozen, see :meth:`freeze`.
+    ///
+    /// Examples
+    /// --------
+    ///
+    /// .. code-block:: python3
+    ///
+    ///     with Image.open('large.png') as image:
+    ///         image.resize(256, 256, ResizeAlgorithm.Lanczos3)
+    ///         image.save('small.png')
+    #[pyo3(text_signature = "($self)")]
+    fn close(&mut self) -> PyResult<()> {
+        if self.closed {
+            return Ok(());
         }
+        self.ensure_mutable()?;
+
+        self.inner = Shared::new(RilImage::new(1, 1, Dynamic::BitPixel(ril::BitPixel(false))));
+        self.metadata = Metadata::default();
+        self.closed = true;
+
+        Ok(())
     }
 
-    /// Saves the image to the given path.
-    /// If encoding is not provided, it will attempt to infer it by the path/filename's extension
-    /// You can try saving to a memory buffer by using the :meth:`encode` method.
+    /// bool: Whether the image is closed, see :meth:`close`.
+    #[getter]
+    fn closed(&self) -> bool {
+        self.closed
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyResult<PyRef<'_, Self>> {
+        ensure_open!(slf);
+
+        Ok(slf)
+    }
+
+    fn __exit__(
+        &mut self,
+        _exc_type: &PyAny,
+        _exc_value: &PyAny,
+        _traceback: &PyAny,
+    ) -> PyResult<()> {
+        self.close()
+    }
+
+    /// Returns a read-only view of the given bounding box of this image, without copying it.
+    ///
+    /// The view reads the current data of this image, so changes made to this image afterwards are visible through it.
+    /// Use :meth:`ImageView.to_image` to get a copy of the region instead.
     ///
     /// Parameters
     /// ----------
-    /// path: str
-    ///     The path to save the image to.
-    /// encoding: Optional[str], default: None
-    ///     The encoding of the image, defaults to `None`.
+    /// x1: int
+    ///    The x axis of the upper-left corner
+    /// y1: int
+    ///     The y axis of the upper-left corner
+    /// x2: int
+    ///     The x axis of the lower-right corner
+    /// y2: int
+    ///     The y axis of the lower-right corner
+    ///
+    /// Returns
+    /// -------
+    /// :class:`.ImageView`
+    ///     The view of the region.
     ///
     /// Raises
     /// ------
     /// ValueError
-    ///     The encoding provided is invalid.
-    /// RuntimeError
-    ///     Failed to encode the image or Failed to infer the image format.
-    #[pyo3(text_signature = "(self, path, encoding = None)")]
-    fn save(&self, path: PathBuf, encoding: Option<&str>) -> Result<(), Error> {
-        if let Some(encoding) = encoding {
-            let encoding = ImageFormat::from_extension(encoding)?;
-            self.inner.save(encoding, path)?;
-        } else {
-            self.inner.save_inferred(path)?;
-        }
+    ///     The bounding box is empty or exceeds the bounds of the image.
+    #[pyo3(text_signature = "($self, x1, y1, x2, y2)")]
+    fn view(slf: PyRef<'_, Self>, x1: u32, y1: u32, x2: u32, y2: u32) -> PyResult<ImageView> {
+        ensure_open!(slf);
+        slf.ensure_box(x1, y1, x2, y2)?;
 
-        Ok(())
+        Ok(ImageView {
+            image: slf.into(),
+            region: (x1, y1, x2, y2),
+        })
     }
 
-    /// Returns a 2D list representing the pixels of the image. Each list in the list is a row.
+    /// Pushes a transform that moves and scales the coordinates of everything drawn onto this image afterwards,
+    /// until it is popped with :meth:`pop_transform`.
     ///
-    /// For example:
+    /// Transforms apply to :meth:`draw`, including text, to :meth:`draw_hline` and :meth:`draw_vline`, and to the position
+    /// of :meth:`paste`. A point ``(x, y)`` is drawn at ``(x * scale + dx, y * scale + dy)``. Nested transforms compose,
+    /// so the translation of a nested transform is scaled by the transforms around it.
     ///
-    /// [[Pixel, Pixel, Pixel], [Pixel, Pixel, Pixel]]
+    /// Sizes are scaled as well, such as the size of rectangles, radii, border thicknesses, shadows and font sizes.
+    /// Coordinates and sizes are rounded to the nearest pixel, and sizes that aren't zero stay at least one pixel.
+    /// Pasted images keep their size. Text layouts are laid out as their text is added, so they can only be translated.
     ///
-    /// where the width of the inner list is determined by the width of the image.
+    /// Parameters
+    /// ----------
+    /// translate: Tuple[int, int], default: (0, 0)
+    ///     How far to move everything drawn, in the coordinates of the enclosing transform.
+    /// scale: float, default: 1.0
+    ///     How much to scale everything drawn, which must be positive.
     ///
-    /// .. warning:: **This function involves heavy operation**
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     `scale` is not a positive number.
     ///
-    ///     This function requires multiple iterations, so it is a heavy operation for larger image.
+    /// Examples
+    /// --------
     ///
-    /// Returns
-    /// -------
-    /// List[List[Union[:class:`.BitPixel`, :class:`.L`, :class:`.Rgb`, :class:`.Rgba`]]]
-    ///     The pixels of the image.
-    fn pixels(&self, py: Python<'_>) -> Vec<Vec<PyObject>> {
-        self.inner
-            .pixels()
-            .into_iter()
-            .map(|p| {
-                p.into_iter()
-                    .map(|p| cast_pixel_to_pyobject(py, p.clone()))
-                    .collect::<Vec<PyObject>>()
-            })
-            .collect::<Vec<Vec<PyObject>>>()
+    /// .. code-block:: python3
+    ///
+    ///     image.push_transform(translate=(100, 50), scale=2.0)
+    ///     image.draw(Rectangle(position=(0, 0), size=(10, 10), fill=Pixel.from_rgb(255, 0, 0)))  # Covers (100, 50) to (120, 70)
+    ///     image.pop_transform()
+    #[pyo3(signature = (translate = (0, 0), scale = 1.0))]
+    #[pyo3(text_signature = "($self, translate = (0, 0), scale = 1.0)")]
+    fn push_transform(&mut self, translate: (i64, i64), scale: f64) -> PyResult<()> {
+        ensure_open!(self);
+
+        if !(scale.is_finite() && scale > 0.0) {
+            return Err(PyValueError::new_err(format!(
+                "Expected a positive scale, got {}",
+                scale
+            )));
+        }
+
+        let transform = DrawTransform {
+            translate: (translate.0 as f64, translate.1 as f64),
+            scale,
+        }
+        .within(self.transform());
+        self.transforms.push(transform);
+
+        Ok(())
     }
 
-    /// Pastes the given image onto this image at the given x and y axis.
+    /// Pops the transform last pushed with :meth:`push_transform`, restoring the transform around it.
     ///
-    /// If `mask` is provided it will be masked with the given masking image.
+    /// Raises
+    /// ------
+    /// IndexError
+    ///     No transform was pushed.
+    #[pyo3(text_signature = "($self)")]
+    fn pop_transform(&mut self) -> PyResult<()> {
+        ensure_open!(self);
+
+        match self.transforms.pop() {
+            Some(_) => Ok(()),
+            None => Err(PyIndexError::new_err("No transform to pop")),
+        }
+    }
+
+    /// Draws an object or shape onto this image.
     ///
-    /// Currently, only BitPixel images are supported for the masking image.
+    /// Colors of a different mode than this image are converted to its mode first, see :meth:`paste` for the conversion rules.
+    ///
+    /// A list or tuple of entities is drawn in order in a single call, which is much faster than drawing them one by one,
+    /// such as for the modules of a QR code or the cells of a heatmap.
     ///
     /// Parameters
     /// ----------
-    /// x: int
-    ///     The x axis
-    /// y: int
-    ///     The y axis
-    /// image: :class:`Image`
-    ///     The image to paste.
-    /// mask: Optional[:class:`Image`], default: None
-    ///     The mask to use, defaults to `None`
+    /// entity: Union[:class:`.Rectangle`, :class:`.Ellipse`, :class:`.Line`, :class:`.Polygon`, :class:`.TextSegment`, :class:`.TextLayout`, :class:`.Scene`, List, Tuple]
+    ///     The entity to draw on the image, or a list or tuple of entities.
+    /// strict_mode: bool, default: False
+    ///     Whether to raise instead of converting colors of a different mode.
     ///
     /// Raises
     /// ------
-    /// ValueError
-    ///     The mask provided is not of mode `BitPixel`
-    #[pyo3(text_signature = "(self, x, y, image, mask = None)")]
-    fn paste(&mut self, x: u32, y: u32, image: Self, mask: Option<Self>) -> Result<(), Error> {
-        if let Some(mask) = mask {
-            if mask.mode() != "bitpixel" {
-                return Err(Error::UnexpectedFormat(
-                    "bitpixel".to_string(),
-                    mask.mode().to_string(),
-                ));
-            }
+    /// TypeError
+    ///     `strict_mode` is enabled and a color of the entity is not of the mode of this image.
+    /// RuntimeError
+    ///     The entity, or any entity of the list or tuple, can't be drawn. Nothing is drawn then.
+    ///
+    /// Examples
+    /// --------
+    ///
+    /// .. code-block:: python3
+    ///
+    ///     image.draw([Rectangle(position=(x * 4, y * 4), size=(4, 4), fill=black) for x, y in modules])
+    #[pyo3(signature = (entity, strict_mode = false))]
+    #[pyo3(text_signature = "($self, entity, strict_mode = False)")]
+    fn draw(&mut self, entity: &PyAny, strict_mode: bool) -> PyResult<()> {
+        ensure_open!(self);
+        self.ensure_mutable()?;
 
-            self.inner
-                .paste_with_mask(x, y, image.inner, mask.inner.convert::<ril::BitPixel>());
-        } else {
-            self.inner.paste(x, y, image.inner);
+        let mut entity = DrawEntity::extract_many(entity)?;
+
+        let transform = self.transform();
+        if !transform.is_identity() {
+            entity.transform(transform)?;
         }
 
-        Ok(())
+        let modes = entity.modes()?;
+        let convertible = entity.is_convertible();
+
+        draw_reconciled(&mut self.inner, &modes, strict_mode, convertible, |image, mode| {
+            entity.convert(mode);
+            Ok(entity.draw(image)?)
+        })
     }
 
-    /// Masks the alpha values of this image with the luminance values of the given single-channel L image.
+    /// Draws a horizontal line from `x1` to `x2`, both inclusive, at the given y axis.
     ///
-    /// If you want to mask using the alpha values of the image instead of providing an L image, you can split the bands of the image and extract the alpha band.
+    /// This writes the pixels of the line directly instead of going through :meth:`draw`, which makes it much faster
+    /// for drawing many thin lines such as the bars of a chart. Whatever falls outside of this image is clipped.
     ///
-    /// This masking image must have the same dimensions as this image.
+    /// A color of a different mode than this image is converted to its mode first, see :meth:`paste` for the conversion rules.
+    /// A translucent RGBA color is blended with the pixels it covers when the overlay mode is :attr:`.OverlayMode.Merge`.
     ///
     /// Parameters
     /// ----------
-    /// mask: :class:`Image`
-    ///     The mask to use
+    /// y: int
+    ///     The y axis of the line.
+    /// x1: int
+    ///     The x axis the line starts at.
+    /// x2: int
+    ///     The x axis the line ends at.
+    /// color: :class:`.Pixel`
+    ///     The color of the line.
+    /// thickness: int, default: 1
+    ///     The thickness of the line in pixels. Thicker lines are centered on `y`, with the extra row of an even thickness below it.
     ///
     /// Raises
     /// ------
     /// ValueError
-    ///     The mask provided is not of mode `L`
-    #[pyo3(text_signature = "(self, mask)")]
-    fn mask_alpha(&mut self, mask: Self) -> Result<(), Error> {
-        if mask.mode() != "L" {
-            return Err(Error::UnexpectedFormat(
-                "L".to_string(),
-                mask.mode().to_string(),
-            ));
-        }
+    ///     `thickness` is zero.
+    #[pyo3(signature = (y, x1, x2, color, thickness = 1))]
+    #[pyo3(text_signature = "($self, y, x1, x2, color, thickness = 1)")]
+    fn draw_hline(
+        &mut self,
+        y: i64,
+        x1: i64,
+        x2: i64,
+        color: Pixel,
+        thickness: u32,
+    ) -> PyResult<()> {
+        ensure_open!(self);
+        self.ensure_mutable()?;
 
-        self.inner.mask_alpha(&mask.inner.convert::<ril::L>());
+        let (y1, y2) = line_span(y, thickness)?;
+        let region = self.transform_box((x1.min(x2), y1, x1.max(x2), y2));
+        fill_clipped(&mut self.inner, region, color.inner);
 
         Ok(())
     }
 
-    /// Mirrors, or flips this image horizontally (about the y-axis) in place.
-    fn mirror(&mut self) {
-        self.inner.mirror();
-    }
+    /// Draws a vertical line from `y1` to `y2`, both inclusive, at the given x axis.
+    ///
+    /// This is the vertical counterpart of :meth:`draw_hline`, see it for how lines are clipped and colors are applied.
+    ///
+    /// Parameters
+    /// ----------
+    /// x: int
+    ///     The x axis of the line.
+    /// y1: int
+    ///     The y axis the line starts at.
+    /// y2: int
+    ///     The y axis the line ends at.
+    /// color: :class:`.Pixel`
+    ///     The color of the line.
+    /// thickness: int, default: 1
+    ///     The thickness of the line in pixels. Thicker lines are centered on `x`, with the extra column of an even thickness to its right.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     `thickness` is zero.
+    #[pyo3(signature = (x, y1, y2, color, thickness = 1))]
+    #[pyo3(text_signature = "($self, x, y1, y2, color, thickness = 1)")]
+    fn draw_vline(
+        &mut self,
+        x: i64,
+        y1: i64,
+        y2: i64,
+        color: Pixel,
+        thickness: u32,
+    ) -> PyResult<()> {
+        ensure_open!(self);
+        self.ensure_mutable()?;
 
-    /// Flips this image vertically (about the x-axis) in place.
-    fn flip(&mut self) {
-        self.inner.flip();
+        let (x1, x2) = line_span(x, thickness)?;
+        let region = self.transform_box((x1, y1.min(y2), x2, y1.max(y2)));
+        fill_clipped(&mut self.inner, region, color.inner);
+
+        Ok(())
     }
 
-    /// str: Returns the encoding format of the image.
+    /// Resizes this image in place to the given dimensions using the given resizing algorithm in place.
+    ///
+    /// Parameters
+    /// ----------
+    /// width: int
+    ///     The target width to resize to
+    /// height: int
+    ///     The target height to resize to
+    /// algorithm: :class:`.ResizeAlgorithm`
+    ///     The resize algorithm to use
+    /// linear: bool, default: False
+    ///     Whether to resample an sRGB image in linear light, see :meth:`to_linear`, which avoids darkened edges
+    ///     when downscaling. Channels are resampled in floating point, so shadows aren't posterized on the way.
+    ///     Images already in the linear color space are always resampled as is.
+    /// preserve_aspect: bool, default: False
+    ///     Whether to guard against distorting the image. When set, the aspect ratio of the target dimensions
+    ///     may differ from the aspect ratio of this image by at most `tolerance`.
+    /// exact: bool, default: True
+    ///     Only used with `preserve_aspect`. When True, dimensions that distort the image raise a ValueError.
+    ///     When False, the dimension that constrains the image less is shrunk to keep the aspect ratio instead,
+    ///     so the image fits within the target dimensions.
+    /// tolerance: float, default: 0.01
+    ///     Only used with `preserve_aspect`. How far the target aspect ratio may differ from the aspect ratio
+    ///     of this image, relative to it. The default allows a change of 1%, which covers rounding to whole pixels.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     `width` or `height` is zero, `tolerance` is negative, or `preserve_aspect` and `exact` are set
+    ///     and the target dimensions change the aspect ratio by more than `tolerance`.
     ///
     /// .. note::
-    ///     This is nothing more but metadata about the image.
-    ///     When saving the image, you will still have to explicitly specify the encoding format.
-    #[getter]
-    fn format(&self) -> String {
-        format!("{}", self.inner.format())
-    }
+    ///     The GIL is released while resampling.
+    #[pyo3(signature = (width, height, algorithm, linear = false, preserve_aspect = false, exact = true, tolerance = 0.01))]
+    #[pyo3(
+        text_signature = "($self, width, height, algorithm, linear = False, preserve_aspect = False, exact = True, tolerance = 0.01)"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    fn resize(
+        &mut self,
+        py: Python<'_>,
+        width: u32,
+        height: u32,
+        algorithm: ResizeAlgorithm,
+        linear: bool,
+        preserve_aspect: bool,
+        exact: bool,
+        tolerance: f64,
+    ) -> PyResult<()> {
+        ensure_open!(self);
+        self.ensure_mutable()?;
 
-    /// Tuple[int, int]: Returns the dimensions of the image.
-    #[getter]
-    fn dimensions(&self) -> (u32, u32) {
-        self.inner.dimensions()
+        ensure_dimensions(width, height)?;
+
+        let (width, height) = if preserve_aspect {
+            fit_aspect(
+                (self.inner.width(), self.inner.height()),
+                (width, height),
+                exact,
+                tolerance,
+            )?
+        } else {
+            (width, height)
+        };
+
+        let span = trace::span("resize", Some(self.inner.dimensions()));
+        let linear = linear
+            && self.color_space == ColorSpace::Srgb
+            && algorithm != ResizeAlgorithm::Nearest
+            && Mode::of(&self.inner) != Mode::BitPixel;
+
+        let inner = &mut self.inner;
+        py.allow_threads(|| {
+            let (src_width, src_height) = inner.dimensions();
+            *inner = resample_region(
+                inner,
+                (0, 0, src_width, src_height),
+                (width, height),
+                algorithm,
+                linear,
+            )
+            .into();
+        });
+        span.end((width, height));
+        self.metadata = self.metadata.with_dimensions(width, height);
+
+        Ok(())
     }
 
-    /// Returns the pixel at the given coordinates.
+    /// Resizes the given region of this image to the given dimensions, returning a new image.
+    ///
+    /// This gives the same result as cropping a copy of this image and resizing it,
+    /// but the region is resampled where it is rather than copied first.
     ///
     /// Parameters
     /// ----------
-    /// x: int
-    ///     The x axis
-    /// y: int
-    ///     The y axis
+    /// src_box: Tuple[int, int, int, int]
+    ///     The region to resize, as the upper-left and lower-right corners ``(x1, y1, x2, y2)``, see :meth:`crop`.
+    /// width: int
+    ///     The target width to resize to
+    /// height: int
+    ///     The target height to resize to
+    /// algorithm: :class:`.ResizeAlgorithm`
+    ///     The resize algorithm to use
     ///
     /// Returns
     /// -------
-    /// Union[:class:`.BitPixel`, :class:`.L`, :class:`.Rgb`, :class:`.Rgba`]
-    ///     The pixel of that specific coordinate.
-    #[pyo3(text_signature = "(self, x, y)")]
-    fn get_pixel(&self, py: Python<'_>, x: u32, y: u32) -> PyObject {
-        match *self.inner.pixel(x, y) {
-            Dynamic::BitPixel(v) => BitPixel::from(v).into_py(py),
-            Dynamic::L(v) => L::from(v).into_py(py),
-            Dynamic::Rgb(v) => Rgb::from(v).into_py(py),
-            Dynamic::Rgba(v) => Rgba::from(v).into_py(py),
-        }
+    /// :class:`.Image`
+    ///     The resized region.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The region is empty or exceeds the bounds of the image, or `width` or `height` is zero.
+    #[pyo3(text_signature = "($self, src_box, width, height, algorithm)")]
+    fn resize_region(
+        &self,
+        py: Python<'_>,
+        src_box: (u32, u32, u32, u32),
+        width: u32,
+        height: u32,
+        algorithm: ResizeAlgorithm,
+    ) -> PyResult<Self> {
+        ensure_open!(self);
+
+        let (x1, y1, x2, y2) = src_box;
+        self.ensure_box(x1, y1, x2, y2)?;
+        ensure_dimensions(width, height)?;
+
+        let inner = &self.inner;
+        let region =
+            py.allow_threads(|| resample_region(inner, src_box, (width, height), algorithm, false));
+
+        Ok(self.derived(region))
     }
 
-    /// Sets the pixel at the given coordinates to the given pixel.
+    /// Encodes the image with the given encoding and returns `bytes`.
     ///
     /// Parameters
-    /// ---------
-    /// x: int
-    ///     The x axis
-    /// y: int
-    ///     The y axis
-    /// pixel: :class:`.Pixel`
-    ///     The pixel to set it to
-    #[pyo3(text_signature = "(self, x, y, pixel)")]
-    fn set_pixel(&mut self, x: u32, y: u32, pixel: Pixel) {
-        self.inner.set_pixel(x, y, pixel.inner)
-    }
+    /// ----------
+    /// encoding: Optional[str], default: None
+    ///     The encoding of the image, defaults to the format of `options`.
+    /// strip_metadata: bool, default: True
+    ///     Whether to leave out the metadata (EXIF, ICC profiles, text chunks, comments) carried over from the decoded image.
+    ///     Metadata is only ever written back to the format it was read from.
+    ///     Kept EXIF data is updated to the current dimensions of the image, and its orientation is reset once
+    ///     the image is rotated, mirrored or flipped.
+    /// options: Optional[Union[:class:`.PngOptions`, :class:`.JpegOptions`, :class:`.GifOptions`, :class:`.WebPOptions`]], default: None
+    ///     The options to encode with, defaults to the encoder defaults of `encoding`.
+    /// deterministic: bool, default: False
+    ///     Whether to pin the encoder settings so the output is byte-for-byte identical for the same image,
+    ///     only supported for PNG and GIF. Without options, :meth:`PngOptions.deterministic` or
+    ///     :meth:`GifOptions.deterministic` is used, and `tIME` chunks are left out of any kept metadata.
+    ///     The output is only guaranteed to be stable for a given version of ril-py.
+    ///
+    /// Returns
+    /// -------
+    /// bytes
+    ///     The encoded bytes of the image.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The encoding is invalid, neither an encoding nor options are given, or they are for different formats.
+    ///     This is also raised if `deterministic` is set for an encoding other than PNG or GIF,
+    ///     or with :class:`.PngOptions` using the `adaptive` filter.
+    /// RuntimeError
+    ///     Failed to encode the image.
+    ///
+    /// .. note::
+    ///     The GIL is released while encoding.
+    ///
+    /// Examples
+    /// --------
+    ///
+    /// .. code-block:: python3
+    ///
+    ///     data = image.encode(options=JpegOptions(quality=80, progressive=True))
+    #[pyo3(signature = (encoding = None, strip_metadata = true, options = None, deterministic = false))]
+    #[pyo3(
+        text_signature = "($self, encoding = None, strip_metadata = True, options = None, deterministic = False)"
+    )]
+    fn encode<'a>(
+        &self,
+        py: Python<'a>,
+        encoding: Option<&str>,
+        strip_metadata: bool,
+        options: Option<EncodeOptions>,
+        deterministic: bool,
+    ) -> Result<&'a PyBytes, Error> {
+        ensure_open!(self);
+
+        let options = options.as_ref();
+        let encoding =
+            EncodeOptions::resolve_format(encoding, options, || Err(Error::MissingEncoding))?;
+
+        let buf = py
+            .allow_threads(|| self.encode_with(encoding, options, strip_metadata, deterministic))?;
+
+        Ok(PyBytes::new(py, &buf))
+    }
+
+    /// Encodes the image with the given encoding into the given buffer, such as a reused `bytearray`,
+    /// and returns the amount of bytes written. The rest of the buffer is left untouched.
+    ///
+    /// Parameters
+    /// ----------
+    /// encoding: str
+    ///     The encoding of the image.
+    /// buffer: Union[bytearray, memoryview]
+    ///     A writable, contiguous buffer of bytes to encode into.
+    /// strip_metadata: bool, default: True
+    ///     Whether to leave out the metadata carried over from the decoded image, see :meth:`encode`.
+    ///
+    /// Returns
+    /// -------
+    /// int
+    ///     The amount of bytes written.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The encoding is invalid.
+    /// BufferError
+    ///     The buffer is not writable or not contiguous.
+    /// BufferTooSmallError
+    ///     The encoded image does not fit into the buffer, the size it needs is stored in its `required_size` attribute.
+    /// RuntimeError
+    ///     Failed to encode the image.
+    ///
+    /// Examples
+    /// --------
+    ///
+    /// .. code-block:: python3
+    ///
+    ///     buffer = bytearray(64 * 1024)
+    ///
+    ///     try:
+    ///         size = image.encode_into('png', buffer)
+    ///     except BufferTooSmallError as e:
+    ///         buffer = bytearray(e.required_size)
+    ///         size = image.encode_into('png', buffer)
+    ///
+    ///     data = memoryview(buffer)[:size]
+    #[pyo3(signature = (encoding, buffer, strip_metadata = true))]
+    #[pyo3(text_signature = "($self, encoding, buffer, strip_metadata = True)")]
+    fn encode_into(
+        &self,
+        py: Python<'_>,
+        encoding: &str,
+        buffer: &PyAny,
+        strip_metadata: bool,
+    ) -> PyResult<usize> {
+        ensure_open!(self);
+
+        let encoding = ImageFormat::from_extension(encoding).map_err(Error::from)?;
+        let buffer = PyBuffer::<u8>::get(buffer)?;
+        let cells = buffer
+            .as_mut_slice(py)
+            .ok_or_else(|| PyBufferError::new_err("Expected a writable, contiguous buffer"))?;
+
+        let buf = self.encode_with(encoding, None, strip_metadata, false)?;
+        if buf.len() > cells.len() {
+            return Err(Error::BufferTooSmall(buf.len(), cells.len()).into());
+        }
+
+        cells.iter().zip(&buf).for_each(|(cell, byte)| cell.set(*byte));
+
+        Ok(buf.len())
+    }
+
+    /// Encodes the image with settings tuned for a small output, optionally fitting it under a target size.
+    ///
+    /// For JPEG and WebP, the highest quality whose output fits under `target_kb` is searched for,
+    /// or a quality of 85 is used if no target is given.
+    /// For PNG, a few filter and compression configurations are tried and the smallest output is kept.
+    ///
+    /// The encoding runs without holding the GIL.
+    ///
+    /// Parameters
+    /// ----------
+    /// format: Union[:class:`.ImageFormat`, str]
+    ///     The format to encode to, either JPEG, PNG or WebP. WebP requires the `webp` feature.
+    /// target_kb: Optional[int], default: None
+    ///     The maximum size of the output, in kilobytes (1024 bytes).
+    ///
+    /// Returns
+    /// -------
+    /// Tuple[bytes, Optional[int]]
+    ///     The encoded bytes, and the JPEG or WebP quality that was chosen or `None` for PNG.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The format is not JPEG, PNG or WebP.
+    /// RuntimeError
+    ///     The output can't fit under `target_kb`, the message includes the smallest size achieved.
+    ///     This is also raised for WebP if ril was built without the `webp` feature.
+    #[pyo3(signature = (format, target_kb = None))]
+    #[pyo3(text_signature = "($self, format, target_kb = None)")]
+    fn encode_optimized<'a>(
+        &self,
+        py: Python<'a>,
+        format: FormatArg,
+        target_kb: Option<usize>,
+    ) -> PyResult<(&'a PyBytes, Option<u8>)> {
+        ensure_open!(self);
+
+        let target = target_kb.map(|kb| kb * 1024);
+        let inner = &self.inner;
+
+        let (buf, quality) = match format.0 {
+            ImageFormat::Jpeg => {
+                py.allow_threads(|| optimize_quality(inner, target, encode_jpeg))?
+            }
+            ImageFormat::WebP => py.allow_threads(|| {
+                optimize_quality(inner, target, |image, quality| {
+                    WebPOptions::lossy(quality).encode(image)
+                })
+            })?,
+            ImageFormat::Png => (py.allow_threads(|| optimize_png(inner, target))?, None),
+            format => {
+                return Err(PyValueError::new_err(format!(
+                    "Optimized encoding is only supported for JPEG, PNG and WebP, got {}",
+                    format
+                )))
+            }
+        };
+
+        Ok((PyBytes::new(py, &buf), quality))
+    }
+
+    /// Saves the image to the given path.
+    /// If encoding is not provided, it will attempt to infer it by the path/filename's extension,
+    /// falling back to :attr:`source_format` if the path has no extension.
+    /// You can try saving to a memory buffer by using the :meth:`encode` method.
+    ///
+    /// Parameters
+    /// ----------
+    /// path: str
+    ///     The path to save the image to.
+    /// encoding: Optional[str], default: None
+    ///     The encoding of the image, defaults to `None`.
+    /// strip_metadata: bool, default: True
+    ///     Whether to leave out the metadata carried over from the decoded image, see :meth:`encode`.
+    /// options: Optional[Union[:class:`.PngOptions`, :class:`.JpegOptions`, :class:`.GifOptions`, :class:`.WebPOptions`]], default: None
+    ///     The options to encode with, see :meth:`encode`. If no encoding is given, the format of `options` is used.
+    /// deterministic: bool, default: False
+    ///     Whether to pin the encoder settings for reproducible output, see :meth:`encode`.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The encoding provided is invalid, or the encoding and options are for different formats.
+    ///     This is also raised if the output can't be made deterministic, see :meth:`encode`.
+    /// RuntimeError
+    ///     Failed to encode the image or Failed to infer the image format.
+    ///     This is also raised if the path has no extension and the image was not decoded from any format.
+    ///
+    /// .. note::
+    ///     The GIL is released while encoding and writing the file.
+    #[pyo3(signature = (path, encoding = None, strip_metadata = true, options = None, deterministic = false))]
+    #[pyo3(
+        text_signature = "($self, path, encoding = None, strip_metadata = True, options = None, deterministic = False)"
+    )]
+    fn save(
+        &self,
+        py: Python<'_>,
+        path: PathBuf,
+        encoding: Option<&str>,
+        strip_metadata: bool,
+        options: Option<EncodeOptions>,
+        deterministic: bool,
+    ) -> Result<(), Error> {
+        ensure_open!(self);
+
+        py.allow_threads(|| {
+            self.save_path(
+                &path,
+                encoding,
+                options.as_ref(),
+                strip_metadata,
+                deterministic,
+            )
+        })
+    }
+
+    /// Like :meth:`save`, but encodes and writes a copy of the image on a separate thread without blocking the event loop.
+    ///
+    /// This must be called from a running :mod:`asyncio` event loop.
+    ///
+    /// Parameters
+    /// ----------
+    /// path: str
+    ///     The path to save the image to.
+    /// encoding: Optional[str], default: None
+    ///     The encoding of the image, defaults to `None`.
+    /// strip_metadata: bool, default: True
+    ///     Whether to leave out the metadata carried over from the decoded image, see :meth:`encode`.
+    /// options: Optional[Union[:class:`.PngOptions`, :class:`.JpegOptions`, :class:`.GifOptions`, :class:`.WebPOptions`]], default: None
+    ///     The options to encode with, see :meth:`encode`.
+    /// deterministic: bool, default: False
+    ///     Whether to pin the encoder settings for reproducible output, see :meth:`encode`.
+    ///
+    /// Returns
+    /// -------
+    /// Awaitable[None]
+    ///     A future resolving once the image is saved, raising the same errors as :meth:`save`.
+    ///
+    /// Raises
+    /// ------
+    /// RuntimeError
+    ///     There is no running event loop.
+    #[pyo3(signature = (path, encoding = None, strip_metadata = true, options = None, deterministic = false))]
+    #[pyo3(
+        text_signature = "($self, path, encoding = None, strip_metadata = True, options = None, deterministic = False)"
+    )]
+    fn save_async<'a>(
+        &self,
+        py: Python<'a>,
+        path: PathBuf,
+        encoding: Option<String>,
+        strip_metadata: bool,
+        options: Option<EncodeOptions>,
+        deterministic: bool,
+    ) -> PyResult<&'a PyAny> {
+        ensure_open!(self);
+
+        let image = self.clone();
+
+        spawn_blocking(py, move || {
+            image.save_path(
+                &path,
+                encoding.as_deref(),
+                options.as_ref(),
+                strip_metadata,
+                deterministic,
+            )
+        })
+    }
+
+    /// Returns a 2D list representing the pixels of the image. Each list in the list is a row.
+    ///
+    /// For example:
+    ///
+    /// [[Pixel, Pixel, Pixel], [Pixel, Pixel, Pixel]]
+    ///
+    /// where the width of the inner list is determined by the width of the image.
+    ///
+    /// .. warning:: **This function involves heavy operation**
+    ///
+    ///     This function creates an object for every pixel, so it is a heavy operation for larger image.
+    ///     Pass `raw` to get every row as `bytes` instead, which is orders of magnitude faster.
+    ///
+    /// Parameters
+    /// ----------
+    /// raw: bool, default: False
+    ///     Whether to return every row as the raw pixel data of its pixels, laid out like :meth:`to_bytes`.
+    ///
+    /// Returns
+    /// -------
+    /// Union[List[List[Union[:class:`.BitPixel`, :class:`.L`, :class:`.Rgb`, :class:`.Rgba`]]], List[bytes]]
+    ///     The pixels of the image, or the raw data of every row if `raw` is set.
+    ///
+    /// Examples
+    /// --------
+    ///
+    /// .. code-block:: python3
+    ///
+    ///     for y, row in enumerate(image.pixels(raw=True)):
+    ///         r, g, b, a = row[:4]
+    #[pyo3(signature = (raw = false))]
+    #[pyo3(text_signature = "($self, raw = False)")]
+    fn pixels(&self, py: Python<'_>, raw: bool) -> PyResult<PyObject> {
+        ensure_open!(self);
+
+        if raw {
+            let inner = &self.inner;
+            let width = inner.width() as usize;
+            let rows = py.allow_threads(|| {
+                inner
+                    .data
+                    .chunks(width.max(1))
+                    .map(|row| raw_bytes(row.iter()))
+                    .collect::<Vec<_>>()
+            });
+
+            return Ok(rows
+                .iter()
+                .map(|row| PyBytes::new(py, row))
+                .collect::<Vec<_>>()
+                .into_py(py));
+        }
+
+        Ok(self
+            .inner
+            .pixels()
+            .into_iter()
+            .map(|p| {
+                p.into_iter()
+                    .map(|p| cast_pixel_to_pyobject(py, p.clone()))
+                    .collect::<Vec<PyObject>>()
+            })
+            .collect::<Vec<Vec<PyObject>>>()
+            .into_py(py))
+    }
+
+    /// Pastes the given image onto this image at the given x and y axis.
+    ///
+    /// If `mask` is provided it will be masked with the given masking image.
+    ///
+    /// Currently, only BitPixel images are supported for the masking image.
+    ///
+    /// An image of a different mode than this image is converted to its mode first:
+    ///
+    /// - RGB is converted to L by its luminance, ``0.299 * r + 0.587 * g + 0.114 * b``.
+    /// - L is converted to BitPixel by thresholding, where values above ``127`` are on.
+    /// - BitPixel and L are converted to RGB by repeating the value on every channel, where a BitPixel is either ``0`` or ``255``.
+    /// - Pixels gain a fully opaque alpha channel when converted to RGBA.
+    /// - RGBA pixels are alpha blended onto this image as if it had an alpha channel, before being converted back to its mode.
+    ///
+    /// An image in a different :attr:`color_space` than this image emits a warning, unless `match_color_space` is set.
+    ///
+    /// The parts of `image` that fall outside of this image are clipped, so pasting past the right or bottom edge
+    /// only pastes the part that fits, and pasting entirely outside of this image leaves it unchanged.
+    ///
+    /// Parameters
+    /// ----------
+    /// x: int
+    ///     The x axis
+    /// y: int
+    ///     The y axis
+    /// image: :class:`Image`
+    ///     The image to paste.
+    /// mask: Optional[:class:`Image`], default: None
+    ///     The mask to use, which must have the dimensions of `image`. Defaults to `None`
+    /// strict_mode: bool, default: False
+    ///     Whether to raise instead of converting an image of a different mode.
+    /// match_color_space: bool, default: False
+    ///     Whether to convert an image in a different color space to the color space of this image first.
+    /// crop_mask: bool, default: False
+    ///     Whether to accept a mask larger than `image`, using only its upper-left corner.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The mask provided is not of mode `BitPixel`, or its dimensions don't match those of `image`.
+    /// TypeError
+    ///     `strict_mode` is enabled and the image is not of the mode of this image.
+    #[pyo3(signature = (x, y, image, mask = None, strict_mode = false, match_color_space = false, crop_mask = false))]
+    #[pyo3(
+        text_signature = "($self, x, y, image, mask = None, strict_mode = False, match_color_space = False, crop_mask = False)"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    fn paste(
+        &mut self,
+        py: Python<'_>,
+        x: u32,
+        y: u32,
+        mut image: Self,
+        mask: Option<Self>,
+        strict_mode: bool,
+        match_color_space: bool,
+        crop_mask: bool,
+    ) -> PyResult<()> {
+        ensure_open!(self, image);
+        if let Some(mask) = &mask {
+            ensure_open!(mask);
+        }
+        self.ensure_mutable()?;
+
+        let (x, y) = self.transform().position((x, y))?;
+
+        if image.color_space != self.color_space {
+            if match_color_space {
+                image.convert_color_space(py, self.color_space);
+            } else {
+                PyErr::warn(
+                    py,
+                    py.get_type::<PyUserWarning>(),
+                    &format!(
+                        "Pasting an image in the {} color space onto an image in the {} color space, \
+                        pass `match_color_space=True` to convert it",
+                        image.color_space, self.color_space
+                    ),
+                    1,
+                )?;
+            }
+        }
+
+        let mask = match mask {
+            Some(mask) if mask.mode_enum() != Mode::BitPixel => {
+                return Err(Error::UnexpectedFormat(Mode::BitPixel.to_string(), mask.mode()).into());
+            }
+            Some(mask) => Some(
+                fit_mask(mask.inner.into_inner(), image.inner.dimensions(), crop_mask)?
+                    .convert::<ril::BitPixel>(),
+            ),
+            None => None,
+        };
+
+        let source = Mode::of(&image.inner);
+        draw_reconciled(&mut self.inner, &[source], strict_mode, true, |inner, mode| {
+            let image = if source == mode {
+                image.inner.into_inner()
+            } else {
+                mode.convert_image(image.inner.into_inner())
+            };
+
+            match mask {
+                Some(mask) => paste_masked_clipped(inner, x, y, image, mask),
+                None => {
+                    let overlay = inner.overlay_mode();
+                    py.allow_threads(|| paste_clipped(inner, x, y, &image, overlay));
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Repeats the given image over a region of this image, like a texture.
+    ///
+    /// Tiles are pasted like :meth:`paste` and partial tiles at the edges of the region are clipped.
+    ///
+    /// Parameters
+    /// ----------
+    /// image: :class:`Image`
+    ///     The image to tile.
+    /// region: Optional[Tuple[int, int, int, int]], default: None
+    ///     The region to fill, as the upper-left and lower-right corners ``(x1, y1, x2, y2)``. Defaults to the whole image.
+    /// offset: Tuple[int, int], default: (0, 0)
+    ///     The position within the tile that the upper-left corner of the region starts at.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The region is empty or exceeds the bounds of the image.
+    #[pyo3(signature = (image, region = None, offset = (0, 0)))]
+    #[pyo3(text_signature = "($self, image, region = None, offset = (0, 0))")]
+    fn paste_tiled(
+        &mut self,
+        py: Python<'_>,
+        image: Self,
+        region: Option<(u32, u32, u32, u32)>,
+        offset: (i64, i64),
+    ) -> PyResult<()> {
+        ensure_open!(self, image);
+        self.ensure_mutable()?;
+
+        let region = region.unwrap_or((0, 0, self.inner.width(), self.inner.height()));
+        self.ensure_box(region.0, region.1, region.2, region.3)?;
+
+        let source = Mode::of(&image.inner);
+        draw_reconciled(&mut self.inner, &[source], false, true, |inner, mode| {
+            let image = if source == mode {
+                image.inner.into_inner()
+            } else {
+                mode.convert_image(image.inner.into_inner())
+            };
+
+            let overlay = inner.overlay_mode();
+            py.allow_threads(|| paste_tiled_with(inner, region, &image, offset, overlay));
+
+            Ok(())
+        })
+    }
+
+    /// Writes the given tile back onto this image at the given x and y axis, such as a tile from :meth:`iter_tiles`.
+    ///
+    /// Unlike :meth:`paste`, the pixels of the tile replace the pixels of this image regardless of the overlay mode,
+    /// and the tile is converted to the mode of this image. Whatever falls outside of this image is clipped.
+    ///
+    /// Parameters
+    /// ----------
+    /// x: int
+    ///     The x axis
+    /// y: int
+    ///     The y axis
+    /// tile: :class:`Image`
+    ///     The tile to write.
+    #[pyo3(text_signature = "($self, x, y, tile)")]
+    fn blit(&mut self, py: Python<'_>, x: u32, y: u32, tile: Self) -> PyResult<()> {
+        ensure_open!(self, tile);
+        self.ensure_mutable()?;
+
+        let mode = Mode::of(&self.inner);
+        let tile = if Mode::of(&tile.inner) == mode {
+            tile.inner.into_inner()
+        } else {
+            mode.convert_image(tile.inner.into_inner())
+        };
+
+        let inner = &mut self.inner;
+        py.allow_threads(|| paste_clipped(inner, x, y, &tile, ril::OverlayMode::Replace));
+
+        Ok(())
+    }
+
+    /// Returns an iterator over the tiles of this image, for processing it block by block.
+    ///
+    /// Tiles are yielded lazily as ``(x, y, tile)`` tuples, row by row, where ``x`` and ``y`` are the position of the tile within this image.
+    /// The iterator works on a copy of this image, so writing tiles back with :meth:`blit` while iterating is safe.
+    ///
+    /// Parameters
+    /// ----------
+    /// tile_w: int
+    ///     The width of the tiles.
+    /// tile_h: int
+    ///     The height of the tiles.
+    /// overlap: int, default: 0
+    ///     The amount of pixels neighboring tiles share on each axis.
+    /// pad: bool, default: False
+    ///     Whether to pad the tiles at the right and bottom edges to the full tile size with `fill`.
+    ///     Otherwise those tiles are smaller.
+    /// fill: Optional[:class:`.Pixel`], default: None
+    ///     The pixel to pad with, converted to the mode of this image. Defaults to a transparent black pixel.
+    ///
+    /// Returns
+    /// -------
+    /// :class:`.TileIterator`
+    ///     The iterator over the tiles.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The tile size is zero, or the overlap is not smaller than the tile size.
+    ///
+    /// Examples
+    /// --------
+    ///
+    /// .. code-block:: python3
+    ///
+    ///     result = Image.new(image.width, image.height, Pixel.from_rgb(0, 0, 0))
+    ///
+    ///     for x, y, tile in image.iter_tiles(256, 256):
+    ///         result.blit(x, y, process(tile))
+    #[pyo3(signature = (tile_w, tile_h, overlap = 0, pad = false, fill = None))]
+    #[pyo3(text_signature = "($self, tile_w, tile_h, overlap = 0, pad = False, fill = None)")]
+    fn iter_tiles(
+        &self,
+        tile_w: u32,
+        tile_h: u32,
+        overlap: u32,
+        pad: bool,
+        fill: Option<Pixel>,
+    ) -> PyResult<TileIterator> {
+        ensure_open!(self);
+
+        if tile_w == 0 || tile_h == 0 {
+            return Err(PyValueError::new_err("Tile dimensions must be at least 1"));
+        }
+
+        if overlap >= tile_w.min(tile_h) {
+            return Err(PyValueError::new_err(format!(
+                "Overlap {} must be smaller than the tile dimensions ({}, {})",
+                overlap, tile_w, tile_h
+            )));
+        }
+
+        let fill = pad.then(|| {
+            Mode::of(&self.inner).convert_pixel(
+                fill.map_or(Dynamic::Rgba(ril::Rgba { r: 0, g: 0, b: 0, a: 0 }), |fill| fill.inner),
+            )
+        });
+
+        Ok(TileIterator {
+            image: self.inner.clone(),
+            tile: (tile_w, tile_h),
+            overlap,
+            fill,
+            position: Some((0, 0)),
+        })
+    }
+
+    /// Masks the alpha values of this image with the luminance values of the given single-channel L image.
+    ///
+    /// If you want to mask using the alpha values of the image instead of providing an L image, you can split the bands of the image and extract the alpha band.
+    ///
+    /// This masking image must have the same dimensions as this image.
+    ///
+    /// Parameters
+    /// ----------
+    /// mask: :class:`Image`
+    ///     The mask to use
+    /// crop_mask: bool, default: False
+    ///     Whether to accept a mask larger than this image, using only its upper-left corner.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The mask provided is not of mode `L`, or its dimensions don't match those of this image.
+    #[pyo3(signature = (mask, crop_mask = false))]
+    #[pyo3(text_signature = "($self, mask, crop_mask = False)")]
+    fn mask_alpha(&mut self, py: Python<'_>, mask: Self, crop_mask: bool) -> PyResult<()> {
+        ensure_open!(self, mask);
+        self.ensure_mutable()?;
+
+        if mask.mode_enum() != Mode::L {
+            return Err(Error::UnexpectedFormat(Mode::L.to_string(), mask.mode()).into());
+        }
+
+        let mask = fit_mask(mask.inner.into_inner(), self.inner.dimensions(), crop_mask)?
+            .convert::<ril::L>();
+        let inner = &mut self.inner;
+        py.allow_threads(|| mask_alpha_with(inner, &mask));
+
+        Ok(())
+    }
+
+    /// Converts this image in place to the given mode, see :meth:`converted` to get a converted copy instead.
+    ///
+    /// When reducing to `BitPixel`, or to `L` with a limited amount of `levels`, pixels are snapped to the nearest
+    /// level of their luminance, which leaves hard edges in gradients. Pass `dither` to diffuse the rounding error
+    /// onto neighboring pixels with Floyd–Steinberg dithering instead.
+    ///
+    /// Parameters
+    /// ----------
+    /// mode: Union[:class:`.Mode`, str]
+    ///     The mode to convert to.
+    /// dither: bool, default: False
+    ///     Whether to dither when reducing to `BitPixel` or to `L` with `levels`, it has no effect otherwise.
+    /// levels: Optional[int], default: None
+    ///     The amount of evenly spaced luminance levels to reduce to, between 2 and 256. Only valid when converting to `L`.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     `levels` is given when not converting to `L`, or it is out of range.
+    #[pyo3(signature = (mode, dither = false, levels = None))]
+    #[pyo3(text_signature = "($self, mode, dither = False, levels = None)")]
+    fn convert(
+        &mut self,
+        py: Python<'_>,
+        mode: ModeArg,
+        dither: bool,
+        levels: Option<u16>,
+    ) -> PyResult<()> {
+        ensure_open!(self);
+        self.ensure_mutable()?;
+
+        let ModeArg(mode) = mode;
+
+        let levels = match (mode, levels) {
+            (Mode::L, Some(levels)) if (2..=256).contains(&levels) => levels,
+            (Mode::L, Some(levels)) => {
+                return Err(PyValueError::new_err(format!(
+                    "Expected between 2 and 256 levels, got {}",
+                    levels
+                )))
+            }
+            (_, Some(_)) => {
+                return Err(PyValueError::new_err(format!(
+                    "Levels can only be given when converting to `L`, not `{}`",
+                    mode
+                )))
+            }
+            (Mode::BitPixel, None) if dither => 2,
+            (_, None) => {
+                let data = &mut self.inner.data;
+                py.allow_threads(|| {
+                    data.iter_mut()
+                        .for_each(|pixel| *pixel = mode.convert_pixel(*pixel))
+                });
+
+                return Ok(());
+            }
+        };
+
+        let width = self.inner.width() as usize;
+        let data = &mut self.inner.data;
+
+        py.allow_threads(|| {
+            let mut luma = data
+                .iter()
+                .map(|pixel| i16::from(ril::L::from(*pixel).0))
+                .collect::<Vec<_>>();
+
+            quantize(&mut luma, width, levels, dither);
+
+            for (pixel, value) in data.iter_mut().zip(luma) {
+                *pixel = match mode {
+                    Mode::BitPixel => Dynamic::BitPixel(ril::BitPixel(value > 127)),
+                    _ => Dynamic::L(ril::L(value as u8)),
+                };
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Returns a copy of this image converted to the given mode, leaving this image untouched.
+    ///
+    /// This takes the same arguments as :meth:`convert`.
+    ///
+    /// Returns
+    /// -------
+    /// :class:`.Image`
+    ///     The converted image, which is never frozen.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The mode is unknown, or `levels` is given when not converting to `L`, or it is out of range.
+    ///
+    /// Examples
+    /// --------
+    ///
+    /// .. code-block:: python3
+    ///
+    ///     mask = screenshot.converted('L')
+    #[pyo3(signature = (mode, dither = false, levels = None))]
+    #[pyo3(text_signature = "($self, mode, dither = False, levels = None)")]
+    fn converted(
+        &self,
+        py: Python<'_>,
+        mode: ModeArg,
+        dither: bool,
+        levels: Option<u16>,
+    ) -> PyResult<Self> {
+        let mut image = self.copy()?;
+        image.convert(py, mode, dither, levels)?;
+
+        Ok(image)
+    }
+
+    /// Composites this image over a solid background and returns the result as an RGB image, such as before encoding to a format without alpha.
+    ///
+    /// Images without an alpha channel are returned as an unchanged copy, keeping their mode.
+    ///
+    /// Parameters
+    /// ----------
+    /// background: Optional[:class:`.Pixel`], default: None
+    ///     The color of the background, defaults to white. Its alpha is ignored.
+    ///
+    /// Returns
+    /// -------
+    /// :class:`.Image`
+    ///     The flattened image.
+    ///
+    /// Examples
+    /// --------
+    ///
+    /// .. code-block:: python3
+    ///
+    ///     rgb = image.flatten(Pixel.from_rgb(0, 0, 0))
+    #[pyo3(signature = (background = None))]
+    #[pyo3(text_signature = "($self, background = None)")]
+    fn flatten(&self, py: Python<'_>, background: Option<Pixel>) -> PyResult<Self> {
+        ensure_open!(self);
+
+        let background = background.map_or(
+            ril::Rgb {
+                r: 255,
+                g: 255,
+                b: 255,
+            },
+            |background| ril::Rgb::from(background.inner),
+        );
+
+        if Mode::of(&self.inner) != Mode::Rgba {
+            return Ok(self.derived(RilImage::clone(&self.inner)));
+        }
+
+        let inner = &self.inner;
+        let flattened = py.allow_threads(|| flatten_over(inner, background));
+
+        Ok(self.derived(flattened))
+    }
+
+    /// Maps every pixel of this image to the nearest color of the given palette and returns the result, such as for
+    /// fixed palettes in pixel art.
+    ///
+    /// Colors are compared by their RGB channels with the `redmean` metric, see :meth:`Pixel.distance`.
+    /// RGBA images keep their alpha channel and stay RGBA, images of any other mode become RGB.
+    ///
+    /// Parameters
+    /// ----------
+    /// palette: List[:class:`.Pixel`]
+    ///     The colors to map to. Their alpha is ignored.
+    /// dither: bool, default: False
+    ///     Whether to diffuse the difference to the chosen color onto neighboring pixels with Floyd–Steinberg
+    ///     dithering, which smooths out gradients while still only using colors of the palette.
+    ///
+    /// Returns
+    /// -------
+    /// :class:`.Image`
+    ///     The remapped image.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The palette is empty.
+    ///
+    /// Examples
+    /// --------
+    ///
+    /// .. code-block:: python3
+    ///
+    ///     game_boy = [Pixel.from_rgb(15, 56, 15), Pixel.from_rgb(48, 98, 48), Pixel.from_rgb(139, 172, 15), Pixel.from_rgb(155, 188, 15)]
+    ///     sprite = image.remap_palette(game_boy, dither=True)
+    #[pyo3(signature = (palette, dither = false))]
+    #[pyo3(text_signature = "($self, palette, dither = False)")]
+    fn remap_palette(&self, py: Python<'_>, palette: Vec<Pixel>, dither: bool) -> PyResult<Self> {
+        ensure_open!(self);
+
+        if palette.is_empty() {
+            return Err(PyValueError::new_err("Expected a non-empty palette"));
+        }
+
+        let palette = palette
+            .iter()
+            .map(|color| ril::Rgb::from(color.inner))
+            .collect::<Vec<_>>();
+        let inner = &self.inner;
+        let remapped = py.allow_threads(|| remap_palette_with(inner, &palette, dither));
+
+        Ok(self.derived(remapped))
+    }
+
+    /// str: Returns the color space of the image, either `srgb` or `linear`.
+    ///
+    /// Images are in the `srgb` color space unless converted with :meth:`to_linear`.
+    /// Setting this only tags the image with the given color space, without converting its pixels.
+    #[getter]
+    fn color_space(&self) -> PyResult<String> {
+        ensure_open!(self);
+
+        Ok(self.color_space.to_string())
+    }
+
+    #[setter]
+    fn set_color_space(&mut self, color_space: &str) -> PyResult<()> {
+        ensure_open!(self);
+        self.ensure_mutable()?;
+
+        self.color_space = ColorSpace::from_name(color_space)?;
+
+        Ok(())
+    }
+
+    /// Converts this image in place from sRGB to linear light, by applying the sRGB transfer function to every color channel.
+    ///
+    /// Alpha channels are left untouched. Since channels are stored as 8-bit values,
+    /// dark tones lose precision, so converting back with :meth:`to_srgb` may be off by more than one for those values.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The image is already in the linear color space.
+    #[pyo3(text_signature = "($self)")]
+    fn to_linear(&mut self, py: Python<'_>) -> PyResult<()> {
+        ensure_open!(self);
+        self.ensure_mutable()?;
+        self.ensure_color_space(ColorSpace::Srgb)?;
+        self.convert_color_space(py, ColorSpace::Linear);
+
+        Ok(())
+    }
+
+    /// Converts this image in place from linear light to sRGB, by applying the inverse sRGB transfer function to every color channel.
+    ///
+    /// Alpha channels are left untouched.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The image is already in the sRGB color space.
+    #[pyo3(text_signature = "($self)")]
+    fn to_srgb(&mut self, py: Python<'_>) -> PyResult<()> {
+        ensure_open!(self);
+        self.ensure_mutable()?;
+        self.ensure_color_space(ColorSpace::Linear)?;
+        self.convert_color_space(py, ColorSpace::Srgb);
+
+        Ok(())
+    }
+
+    /// Mirrors, or flips this image horizontally (about the y-axis) in place.
+    #[pyo3(text_signature = "($self)")]
+    fn mirror(&mut self) -> PyResult<()> {
+        ensure_open!(self);
+        self.ensure_mutable()?;
+        self.inner.mirror();
+        self.metadata = self.metadata.without_orientation();
+
+        Ok(())
+    }
+
+    /// Flips this image vertically (about the x-axis) in place.
+    #[pyo3(text_signature = "($self)")]
+    fn flip(&mut self) -> PyResult<()> {
+        ensure_open!(self);
+        self.ensure_mutable()?;
+        self.inner.flip();
+        self.metadata = self.metadata.without_orientation();
+
+        Ok(())
+    }
+
+    /// Rotates this image clockwise by the given angle in degrees in place.
+    ///
+    /// Multiples of 90 degrees are lossless and only rearrange pixels, swapping the width and height for quarter turns.
+    /// Any other angle resamples the image onto a canvas that is large enough to fit all of it, and fills the
+    /// corners exposed by the rotation with `fill`. Resampling blends in premultiplied alpha, so the alpha channel
+    /// of RGBA images is preserved and their edges blend smoothly into the corners.
+    ///
+    /// Parameters
+    /// ----------
+    /// degrees: float
+    ///     The angle to rotate by in degrees, clockwise. Negative angles rotate counterclockwise.
+    /// algorithm: Optional[:class:`.ResizeAlgorithm`], default: None
+    ///     How to sample pixels for angles that aren't multiples of 90 degrees, defaults to bilinear.
+    ///     :attr:`.ResizeAlgorithm.Nearest` picks the nearest pixel, the bicubic, Mitchell and Lanczos algorithms
+    ///     use bicubic interpolation, and the others use bilinear interpolation.
+    /// fill: Optional[:class:`.Pixel`], default: None
+    ///     The pixel to fill the exposed corners with, converted to the mode of this image.
+    ///     Defaults to a transparent black pixel.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     `degrees` is not finite.
+    #[pyo3(signature = (degrees, algorithm = None, fill = None))]
+    #[pyo3(text_signature = "($self, degrees, algorithm = None, fill = None)")]
+    fn rotate(
+        &mut self,
+        py: Python<'_>,
+        degrees: f64,
+        algorithm: Option<ResizeAlgorithm>,
+        fill: Option<Pixel>,
+    ) -> PyResult<()> {
+        ensure_open!(self);
+        self.ensure_mutable()?;
+
+        if !degrees.is_finite() {
+            return Err(PyValueError::new_err(format!(
+                "Expected a finite angle, got {}",
+                degrees
+            )));
+        }
+
+        let degrees = degrees.rem_euclid(360.0);
+        if degrees == 0.0 {
+            return Ok(());
+        }
+
+        let span = trace::span("rotate", Some(self.inner.dimensions()));
+        let algorithm = algorithm.unwrap_or(ResizeAlgorithm::Bilinear);
+        let fill = fill.map_or(
+            Dynamic::Rgba(ril::Rgba {
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 0,
+            }),
+            |fill| fill.inner,
+        );
+        let overlay = self.inner.overlay_mode();
+        let inner = &self.inner;
+        let rotated = py.allow_threads(|| {
+            if degrees % 90.0 == 0.0 {
+                Transform::rotation((degrees / 90.0) as u8).apply(inner)
+            } else {
+                rotate_with(inner, degrees, algorithm, fill)
+            }
+        });
+
+        span.end(rotated.dimensions());
+        self.metadata = self
+            .metadata
+            .with_dimensions(rotated.width(), rotated.height())
+            .without_orientation();
+        self.inner = rotated.with_overlay_mode(overlay).into();
+
+        Ok(())
+    }
+
+    /// str: Returns the encoding format the image was decoded from, or `"none"` if it was created programmatically.
+    ///
+    /// .. note::
+    ///     Prefer :attr:`source_format`, which is an :class:`.ImageFormat` or `None`.
+    #[getter]
+    fn get_format(&self) -> PyResult<String> {
+        ensure_open!(self);
+
+        Ok(self.format())
+    }
+
+    /// Optional[:class:`.ImageFormat`]: Returns the encoding format the image was decoded from.
+    ///
+    /// This is `None` for images created programmatically, such as with :meth:`new`,
+    /// and is kept as-is by operations that modify the image in place.
+    /// :meth:`save` falls back to this format when the path has no extension and no encoding is given.
+    #[getter]
+    fn source_format(&self) -> PyResult<Option<PyImageFormat>> {
+        ensure_open!(self);
+
+        Ok(self.source_format.and_then(PyImageFormat::from_ril))
+    }
+
+    /// Optional[str]: Returns the color model of the source pixels if they were converted to RGB when decoding.
+    ///
+    /// JPEG images with four components, common from print workflows, store their colors as `cmyk`, or as `ycck`
+    /// when compressed like other JPEG images. Their pixels are converted to RGB without a color profile,
+    /// so they may look slightly different than in software that applies one. This is `None` for every other image.
+    #[getter]
+    fn source_color_model(&self) -> PyResult<Option<&'static str>> {
+        ensure_open!(self);
+
+        Ok(self.source_color_model)
+    }
+
+    /// List[str]: Returns the warnings from decoding this image with `tolerant` set, describing what was recovered.
+    ///
+    /// This is empty if the data decoded cleanly, and for images that were not decoded with `tolerant` set.
+    /// See :meth:`from_bytes`.
+    #[getter]
+    fn decode_warnings(&self) -> PyResult<Vec<String>> {
+        ensure_open!(self);
+
+        Ok(self.decode_warnings.clone())
+    }
+
+    /// Tuple[int, int]: Returns the dimensions of the image.
+    #[getter]
+    fn dimensions(&self) -> PyResult<(u32, u32)> {
+        ensure_open!(self);
+
+        Ok(self.inner.dimensions())
+    }
+
+    /// Counts the distinct colors of the image, where pixels are compared after converting them to RGBA.
+    ///
+    /// Parameters
+    /// ----------
+    /// max_count: Optional[int]
+    ///     Stops counting as soon as more than this many colors were found,
+    ///     which makes checking whether an image has at most, for example, 256 colors cheap.
+    ///
+    /// Returns
+    /// -------
+    /// int
+    ///     The amount of distinct colors, or ``max_count + 1`` if there are more than `max_count`.
+    #[pyo3(signature = (max_count = None))]
+    #[pyo3(text_signature = "($self, max_count = None)")]
+    fn color_count(&self, py: Python<'_>, max_count: Option<usize>) -> PyResult<usize> {
+        ensure_open!(self);
+
+        let pixels = &self.inner.data;
+
+        Ok(py.allow_threads(|| count_colors(pixels, max_count)))
+    }
+
+    /// Returns the average color of this image or a region of it, such as the background behind text.
+    ///
+    /// Colors are weighted by their alpha, so transparent pixels don't tint the average, which has the mean alpha.
+    /// The average is in the mode of this image, where a `BITPIXEL` average is on if more than half of the pixels are.
+    ///
+    /// Parameters
+    /// ----------
+    /// region: Optional[Tuple[int, int, int, int]], default: None
+    ///     The region to average, see :meth:`invert`. Defaults to the whole image.
+    ///
+    /// Returns
+    /// -------
+    /// :class:`.Pixel`
+    ///     The average color.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The region is empty or exceeds the bounds of the image.
+    #[pyo3(signature = (region = None))]
+    #[pyo3(text_signature = "($self, region = None)")]
+    fn average_color(&self, py: Python<'_>, region: Option<Region>) -> PyResult<Pixel> {
+        ensure_open!(self);
+
+        let region = region.unwrap_or((0, 0, self.inner.width(), self.inner.height()));
+        self.ensure_box(region.0, region.1, region.2, region.3)?;
+
+        let inner = &self.inner;
+        let average = py.allow_threads(|| average_color_of(inner, region));
+
+        Ok(Pixel::from(average))
+    }
+
+    /// Returns the pixel at the given coordinates.
+    ///
+    /// Parameters
+    /// ----------
+    /// x: int
+    ///     The x axis
+    /// y: int
+    ///     The y axis
+    ///
+    /// Returns
+    /// -------
+    /// Union[:class:`.BitPixel`, :class:`.L`, :class:`.Rgb`, :class:`.Rgba`]
+    ///     The pixel of that specific coordinate.
+    #[pyo3(text_signature = "($self, x, y)")]
+    fn get_pixel(&self, py: Python<'_>, x: u32, y: u32) -> PyResult<PyObject> {
+        ensure_open!(self);
+
+        Ok(match *self.inner.pixel(x, y) {
+            Dynamic::BitPixel(v) => BitPixel::from(v).into_py(py),
+            Dynamic::L(v) => L::from(v).into_py(py),
+            Dynamic::Rgb(v) => Rgb::from(v).into_py(py),
+            Dynamic::Rgba(v) => Rgba::from(v).into_py(py),
+        })
+    }
+
+    /// Sets the pixel at the given coordinates to the given pixel.
+    ///
+    /// A pixel of a different mode than this image is converted to its mode first,
+    /// exactly like :meth:`convert` converts every pixel, see :meth:`paste` for the conversion rules.
+    ///
+    /// This can also be written as ``image[x, y] = pixel``.
+    ///
+    /// Parameters
+    /// ---------
+    /// x: int
+    ///     The x axis
+    /// y: int
+    ///     The y axis
+    /// pixel: :class:`.Pixel`
+    ///     The pixel to set it to
+    /// strict: bool, default: False
+    ///     Whether to raise instead of converting a pixel of a different mode.
+    ///
+    /// Raises
+    /// ------
+    /// IndexError
+    ///     The coordinates are out of bounds.
+    /// TypeError
+    ///     `strict` is enabled and the pixel is not of the mode of this image.
+    #[pyo3(signature = (x, y, pixel, strict = false))]
+    #[pyo3(text_signature = "($self, x, y, pixel, strict = False)")]
+    fn set_pixel(&mut self, x: u32, y: u32, pixel: Pixel, strict: bool) -> PyResult<()> {
+        ensure_open!(self);
+        self.ensure_mutable()?;
+
+        let (width, height) = self.inner.dimensions();
+        if x >= width || y >= height {
+            return Err(PyIndexError::new_err(format!(
+                "Pixel ({}, {}) is out of bounds for an image with dimensions ({}, {})",
+                x, y, width, height
+            )));
+        }
+
+        let (mode, source) = (Mode::of(&self.inner), Mode::from(&pixel.inner));
+        if strict && mode != source {
+            return Err(PyTypeError::new_err(format!(
+                "Expected a pixel of mode `{}`, got `{}`",
+                mode, source
+            )));
+        }
+
+        self.inner.set_pixel(x, y, mode.convert_pixel(pixel.inner));
+
+        Ok(())
+    }
+
+    fn __setitem__(&mut self, (x, y): Xy, pixel: Pixel) -> PyResult<()> {
+        self.set_pixel(x, y, pixel, false)
+    }
+
+    /// Inverts the image in-place.
+    ///
+    /// Parameters
+    /// ----------
+    /// region: Optional[Tuple[int, int, int, int]], default: None
+    ///     The region to invert, as the upper-left and lower-right corners ``(x1, y1, x2, y2)``, see :meth:`crop`.
+    ///     Defaults to the whole image.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The region is empty or exceeds the bounds of the image.
+    #[pyo3(signature = (region = None))]
+    #[pyo3(text_signature = "($self, region = None)")]
+    fn invert(&mut self, py: Python<'_>, region: Option<Region>) -> PyResult<()> {
+        ensure_open!(self);
+        self.ensure_mutable()?;
+
+        self.map_region(py, region, |pixel| *pixel = pixel.inverted())
+    }
+
+    /// Brightens the image in place by adding `amount` to every color channel, clamping at 255.
+    ///
+    /// Alpha channels and `BitPixel` images are left untouched.
+    ///
+    /// Parameters
+    /// ----------
+    /// amount: int
+    ///     The amount to add, from 0 to 255.
+    /// region: Optional[Tuple[int, int, int, int]], default: None
+    ///     The region to brighten, see :meth:`invert`. Defaults to the whole image.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The region is empty or exceeds the bounds of the image.
+    #[pyo3(signature = (amount, region = None))]
+    #[pyo3(text_signature = "($self, amount, region = None)")]
+    fn brighten(&mut self, py: Python<'_>, amount: u8, region: Option<Region>) -> PyResult<()> {
+        ensure_open!(self);
+        self.ensure_mutable()?;
+
+        self.map_tones(py, region, tone_lut(|value| value + f32::from(amount)))
+    }
+
+    /// Darkens the image in place by subtracting `amount` from every color channel, clamping at 0.
+    ///
+    /// Alpha channels and `BitPixel` images are left untouched.
+    ///
+    /// Parameters
+    /// ----------
+    /// amount: int
+    ///     The amount to subtract, from 0 to 255.
+    /// region: Optional[Tuple[int, int, int, int]], default: None
+    ///     The region to darken, see :meth:`invert`. Defaults to the whole image.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The region is empty or exceeds the bounds of the image.
+    #[pyo3(signature = (amount, region = None))]
+    #[pyo3(text_signature = "($self, amount, region = None)")]
+    fn darken(&mut self, py: Python<'_>, amount: u8, region: Option<Region>) -> PyResult<()> {
+        ensure_open!(self);
+        self.ensure_mutable()?;
+
+        self.map_tones(py, region, tone_lut(|value| value - f32::from(amount)))
+    }
+
+    /// Adjusts the contrast of the image in place by scaling the distance of every color channel from the midpoint, 128.
+    ///
+    /// Alpha channels and `BitPixel` images are left untouched.
+    ///
+    /// Parameters
+    /// ----------
+    /// factor: float
+    ///     The factor to scale by, where values below 1 reduce the contrast and values above 1 increase it.
+    /// region: Optional[Tuple[int, int, int, int]], default: None
+    ///     The region to adjust, see :meth:`invert`. Defaults to the whole image.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     `factor` is negative, or the region is empty or exceeds the bounds of the image.
+    #[pyo3(signature = (factor, region = None))]
+    #[pyo3(text_signature = "($self, factor, region = None)")]
+    fn contrast(&mut self, py: Python<'_>, factor: f32, region: Option<Region>) -> PyResult<()> {
+        ensure_open!(self);
+        self.ensure_mutable()?;
+
+        if factor.is_nan() || factor < 0.0 {
+            return Err(PyValueError::new_err(format!(
+                "Expected a non-negative contrast factor, got {}",
+                factor
+            )));
+        }
+
+        let lut = tone_lut(|value| (value - 128.0).mul_add(factor, 128.0));
+        self.map_tones(py, region, lut)
+    }
+
+    /// Applies gamma correction to the image in place, raising every normalized color channel to the power of ``1 / gamma``.
+    ///
+    /// Alpha channels and `BitPixel` images are left untouched.
+    ///
+    /// Parameters
+    /// ----------
+    /// gamma: float
+    ///     The gamma, where values above 1 brighten the midtones and values below 1 darken them.
+    /// region: Optional[Tuple[int, int, int, int]], default: None
+    ///     The region to adjust, see :meth:`invert`. Defaults to the whole image.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     `gamma` is not positive, or the region is empty or exceeds the bounds of the image.
+    #[pyo3(signature = (gamma, region = None))]
+    #[pyo3(text_signature = "($self, gamma, region = None)")]
+    fn gamma(&mut self, py: Python<'_>, gamma: f32, region: Option<Region>) -> PyResult<()> {
+        ensure_open!(self);
+        self.ensure_mutable()?;
+
+        if gamma.is_nan() || gamma <= 0.0 {
+            return Err(PyValueError::new_err(format!(
+                "Expected a positive gamma, got {}",
+                gamma
+            )));
+        }
+
+        let lut = tone_lut(|value| (value / 255.0).powf(gamma.recip()) * 255.0);
+        self.map_tones(py, region, lut)
+    }
+
+    /// Pixelates the image in place by replacing every block of pixels with its average color, such as for redacting faces or text.
+    ///
+    /// Blocks start at the upper-left corner of the region, and the blocks at its right and bottom edges may be smaller.
+    /// Every block is averaged over its own pixels like :meth:`average_color`.
+    ///
+    /// Parameters
+    /// ----------
+    /// block_size: int
+    ///     The width and height of every block, in pixels.
+    /// region: Optional[Tuple[int, int, int, int]], default: None
+    ///     The region to pixelate, see :meth:`invert`. Defaults to the whole image.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     `block_size` is 0, or the region is empty or exceeds the bounds of the image.
+    ///
+    /// Examples
+    /// --------
+    ///
+    /// .. code-block:: python3
+    ///
+    ///     image.pixelate(12, region=(40, 30, 120, 90))
+    #[pyo3(signature = (block_size, region = None))]
+    #[pyo3(text_signature = "($self, block_size, region = None)")]
+    fn pixelate(
+        &mut self,
+        py: Python<'_>,
+        block_size: u32,
+        region: Option<Region>,
+    ) -> PyResult<()> {
+        ensure_open!(self);
+        self.ensure_mutable()?;
+
+        if block_size == 0 {
+            return Err(PyValueError::new_err("Expected a block size of at least 1"));
+        }
+
+        let region = region.unwrap_or((0, 0, self.inner.width(), self.inner.height()));
+        self.ensure_box(region.0, region.1, region.2, region.3)?;
+
+        let inner = &mut self.inner;
+        py.allow_threads(|| pixelate_region(inner, region, block_size));
+
+        Ok(())
+    }
+
+    /// Pixelates the whole image in place, see :meth:`pixelate`.
+    ///
+    /// Parameters
+    /// ----------
+    /// block_size: int
+    ///     The width and height of every block, in pixels.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     `block_size` is 0.
+    #[pyo3(text_signature = "($self, block_size)")]
+    fn mosaic(&mut self, py: Python<'_>, block_size: u32) -> PyResult<()> {
+        self.pixelate(py, block_size, None)
+    }
+
+    /// Maps a single channel of every pixel in place through a lookup table, such as a curve from an image editor.
+    ///
+    /// Parameters
+    /// ----------
+    /// channel: str
+    ///     The channel to map, `l` for L images, or one of `r`, `g`, `b` and, for RGBA images, `a`.
+    /// lut: List[int]
+    ///     The lookup table, where every channel value ``v`` is replaced with ``lut[v]``.
+    ///     It must have exactly 256 entries from 0 to 255.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The image has no such channel, which is always the case for `BitPixel` images,
+    ///     or the lookup table is invalid.
+    ///
+    /// Examples
+    /// --------
+    ///
+    /// .. code-block:: python3
+    ///
+    ///     image.map_channel('r', [255 - v for v in range(256)])
+    #[pyo3(text_signature = "($self, channel, lut)")]
+    fn map_channel(&mut self, py: Python<'_>, channel: &str, lut: Vec<i64>) -> PyResult<()> {
+        self.map_channels(py, HashMap::from([(channel.to_string(), lut)]))
+    }
+
+    /// Maps several channels of every pixel in place through lookup tables in a single pass, see :meth:`map_channel`.
+    ///
+    /// Parameters
+    /// ----------
+    /// luts: Dict[str, List[int]]
+    ///     The lookup table of each channel to map, channels that are left out are untouched.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The image has no such channel, a channel is given more than once, or a lookup table is invalid.
+    #[pyo3(text_signature = "($self, luts)")]
+    fn map_channels(&mut self, py: Python<'_>, luts: HashMap<String, Vec<i64>>) -> PyResult<()> {
+        ensure_open!(self);
+        self.ensure_mutable()?;
+
+        let mode = Mode::of(&self.inner);
+        let mut channels = ChannelLuts::default();
+
+        for (name, lut) in luts {
+            let channel = channels
+                .channel_mut(mode, &name.to_lowercase())
+                .ok_or_else(|| {
+                    PyValueError::new_err(format!(
+                        "Invalid channel `{}` for a {} image, expected one of {}",
+                        name,
+                        mode,
+                        channel_names(mode)
+                    ))
+                })?;
+
+            if channel.replace(parse_lut(&lut)?).is_some() {
+                return Err(PyValueError::new_err(format!(
+                    "The channel `{}` is given more than once",
+                    name.to_lowercase()
+                )));
+            }
+        }
+
+        self.map_region(py, None, |pixel| *pixel = channels.map(*pixel))
+    }
+
+    /// Adjusts the distribution of every color channel of this image in place to match the given reference image,
+    /// such as to color-match a composite to its background.
+    ///
+    /// Every channel is mapped so that its cumulative histogram follows that of the same channel of `reference`.
+    /// Alpha channels are left untouched, and `BitPixel` images have no channel to adjust.
+    ///
+    /// Parameters
+    /// ----------
+    /// reference: :class:`.Image`
+    ///     The image to match, which may have different dimensions. If it has a richer mode than this image,
+    ///     this image is converted to its mode first, otherwise the reference is compared as if it had the mode of this image.
+    ///
+    /// Examples
+    /// --------
+    ///
+    /// .. code-block:: python3
+    ///
+    ///     overlay.match_histogram(background)
+    ///     background.paste(0, 0, overlay)
+    #[pyo3(text_signature = "($self, reference)")]
+    fn match_histogram(&mut self, py: Python<'_>, reference: Self) -> PyResult<()> {
+        ensure_open!(self, reference);
+        self.ensure_mutable()?;
+
+        let mode = self.mode_enum().max(reference.mode_enum());
+        let (data, reference) = (&mut self.inner.data, &reference.inner.data);
+
+        py.allow_threads(|| {
+            data.iter_mut()
+                .for_each(|pixel| *pixel = mode.convert_pixel(*pixel));
+
+            let luts = ChannelLuts::matching(
+                data.iter().copied(),
+                reference.iter().map(|pixel| mode.convert_pixel(*pixel)),
+            );
+            data.iter_mut().for_each(|pixel| *pixel = luts.map(*pixel));
+        });
+
+        Ok(())
+    }
+
+    /// Sharpens the image in place with an unsharp mask.
+    ///
+    /// A blurred copy of the image is subtracted from it, and `amount` times the difference is added back to every color channel,
+    /// which steepens edges. Alpha channels are left untouched.
+    ///
+    /// Parameters
+    /// ----------
+    /// radius: float, default: 2.0
+    ///     The standard deviation of the blur, which controls how wide the sharpened edges are.
+    /// amount: float, default: 0.5
+    ///     The strength of the sharpening, where `0` leaves the image unchanged.
+    /// threshold: int, default: 0
+    ///     The minimum difference between a channel and its blurred value for it to be sharpened,
+    ///     which leaves noise in flat regions alone.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     `radius` is negative or not finite.
+    /// TypeError
+    ///     The image is of mode `BitPixel`.
+    #[pyo3(signature = (radius = 2.0, amount = 0.5, threshold = 0))]
+    #[pyo3(text_signature = "($self, radius = 2.0, amount = 0.5, threshold = 0)")]
+    fn unsharp_mask(
+        &mut self,
+        py: Python<'_>,
+        radius: f32,
+        amount: f32,
+        threshold: u8,
+    ) -> PyResult<()> {
+        ensure_open!(self);
+        self.ensure_mutable()?;
+
+        if !radius.is_finite() || radius < 0.0 {
+            return Err(PyValueError::new_err(format!(
+                "Expected a finite, non-negative radius, got {}",
+                radius
+            )));
+        }
+
+        if self.mode_enum() == Mode::BitPixel {
+            return Err(Error::UnexpectedFormat("L, RGB or RGBA".to_string(), self.mode()).into());
+        }
+
+        // Two passes of a box blur of radius `r` have a variance of `((2r + 1)^2 - 1) / 6`.
+        // Windows wider than the image only repeat its edges, so the radius is capped at its larger dimension.
+        let extent = f64::from(self.inner.width().max(self.inner.height()));
+        let box_radius = (((6.0 * f64::from(radius).powi(2) + 1.0).sqrt() - 1.0) / 2.0)
+            .round()
+            .min(extent) as usize;
+        let inner = &mut self.inner;
+        py.allow_threads(|| unsharp_mask_with(inner, box_radius, amount, threshold));
+
+        Ok(())
+    }
+
+    /// Blurs the image in place with a gaussian blur.
+    ///
+    /// Every channel is blurred, including alpha, where colors are weighted by their alpha so that transparent pixels
+    /// don't darken the edges of opaque ones. Pixels past the edges of the image repeat the nearest edge pixel.
+    ///
+    /// The gaussian is approximated by three successive box blurs, which is visually indistinguishable from it
+    /// and takes the same time for any `sigma`.
+    ///
+    /// Parameters
+    /// ----------
+    /// sigma: float
+    ///     The standard deviation of the blur in pixels. Values below about 0.6 leave the image unchanged.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     `sigma` is negative or not finite.
+    /// TypeError
+    ///     The image is of mode `BitPixel`.
+    #[pyo3(text_signature = "($self, sigma)")]
+    fn blur(&mut self, py: Python<'_>, sigma: f32) -> PyResult<()> {
+        ensure_open!(self);
+        self.ensure_mutable()?;
+
+        if !sigma.is_finite() || sigma < 0.0 {
+            return Err(PyValueError::new_err(format!(
+                "Expected a finite, non-negative sigma, got {}",
+                sigma
+            )));
+        }
+
+        if self.mode_enum() == Mode::BitPixel {
+            return Err(Error::UnexpectedFormat("L, RGB or RGBA".to_string(), self.mode()).into());
+        }
+
+        let radii = gaussian_box_radii(sigma);
+        let inner = &mut self.inner;
+        py.allow_threads(|| blur_with(inner, &radii));
+
+        Ok(())
+    }
+
+    /// Blurs the image in place with a box blur, which sets every pixel to the average of the square of
+    /// ``2 * radius + 1`` pixels around it.
+    ///
+    /// Every channel is blurred the same way as with :meth:`blur`, including alpha.
+    ///
+    /// Parameters
+    /// ----------
+    /// radius: int
+    ///     The distance from each pixel to the edges of the square it is averaged over, where `0` leaves the image unchanged.
+    ///
+    /// Raises
+    /// ------
+    /// TypeError
+    ///     The image is of mode `BitPixel`.
+    #[pyo3(text_signature = "($self, radius)")]
+    fn box_blur(&mut self, py: Python<'_>, radius: u32) -> PyResult<()> {
+        ensure_open!(self);
+        self.ensure_mutable()?;
+
+        if self.mode_enum() == Mode::BitPixel {
+            return Err(Error::UnexpectedFormat("L, RGB or RGBA".to_string(), self.mode()).into());
+        }
+
+        let inner = &mut self.inner;
+        py.allow_threads(|| blur_with(inner, &[radius as usize]));
+
+        Ok(())
+    }
+
+    /// Darkens the image in place toward its corners with a smooth radial falloff.
+    ///
+    /// Distances are measured from the center of the image, relative to the distance of its corners.
+    /// Pixels within `radius` are left unchanged, and the color channels beyond it are scaled down smoothly
+    /// until they reach ``1 - strength`` of their value at the corners.
+    /// Alpha channels and `BitPixel` images are left untouched.
+    ///
+    /// Parameters
+    /// ----------
+    /// strength: float, default: 0.5
+    ///     How much the corners are darkened, from 0 (unchanged) to 1 (black).
+    /// radius: float, default: 0.75
+    ///     The relative distance from the center at which the darkening starts, where 1 reaches the corners.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     `strength` is not from 0 to 1, or `radius` is negative.
+    #[pyo3(signature = (strength = 0.5, radius = 0.75))]
+    #[pyo3(text_signature = "($self, strength = 0.5, radius = 0.75)")]
+    fn vignette(&mut self, py: Python<'_>, strength: f32, radius: f32) -> PyResult<()> {
+        ensure_open!(self);
+        self.ensure_mutable()?;
+
+        if !(0.0..=1.0).contains(&strength) {
+            return Err(PyValueError::new_err(format!(
+                "Expected a strength from 0 to 1, got {}",
+                strength
+            )));
+        }
+
+        if !radius.is_finite() || radius < 0.0 {
+            return Err(PyValueError::new_err(format!(
+                "Expected a finite, non-negative radius, got {}",
+                radius
+            )));
+        }
+
+        let inner = &mut self.inner;
+        py.allow_threads(|| vignette_with(inner, strength, radius));
+
+        Ok(())
+    }
+
+    /// Paints a linear gradient from `start` to `end` into the given region of this image in place,
+    /// replacing the pixels within it.
+    ///
+    /// The gradient runs along `direction`, so that the pixels at either end of the region have exactly the colors of the stops.
+    /// Both colors are converted to the mode of the image.
+    ///
+    /// Parameters
+    /// ----------
+    /// region: Tuple[int, int, int, int]
+    ///     The region ``(x1, y1, x2, y2)`` to paint.
+    /// start: :class:`.Pixel`
+    ///     The color at the start of the gradient.
+    /// end: :class:`.Pixel`
+    ///     The color at the end of the gradient.
+    /// direction: float
+    ///     The angle of the gradient in degrees, clockwise, where 0 runs from left to right and 90 from top to bottom.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The region is empty or exceeds the bounds of the image.
+    ///
+    /// Examples
+    /// --------
+    ///
+    /// .. code-block:: python3
+    ///
+    ///     image.fill_gradient((0, 0, image.width, 40), Colors.WHITE, Colors.BLACK, 90)
+    #[pyo3(text_signature = "($self, region, start, end, direction)")]
+    fn fill_gradient(
+        &mut self,
+        py: Python<'_>,
+        region: Region,
+        start: Pixel,
+        end: Pixel,
+        direction: f64,
+    ) -> PyResult<()> {
+        ensure_open!(self);
+        self.ensure_mutable()?;
+
+        let (x1, y1, x2, y2) = region;
+        self.ensure_box(x1, y1, x2, y2)?;
+
+        let inner = &mut self.inner;
+        py.allow_threads(|| {
+            fill_gradient_with(
+                inner,
+                region,
+                start.inner.into(),
+                end.inner.into(),
+                direction,
+            )
+        });
+
+        Ok(())
+    }
+
+    fn __len__(&self) -> PyResult<usize> {
+        ensure_open!(self);
+
+        Ok(self.inner.len() as usize)
+    }
+
+    /// Returns the image encoded as PNG, used by Jupyter to display the image inline.
+    ///
+    /// Images larger than the preview size are downscaled first, see :func:`set_notebook_preview_size`.
+    fn _repr_png_<'a>(&self, py: Python<'a>) -> Result<&'a PyBytes, Error> {
+        ensure_open!(self);
+
+        let preview = notebook_preview(&self.inner);
+
+        let mut buf = Vec::new();
+        preview
+            .as_ref()
+            .unwrap_or(&self.inner)
+            .encode(ImageFormat::Png, &mut buf)?;
+
+        Ok(PyBytes::new(py, &buf))
+    }
+
+    fn __repr__(&self) -> String {
+        if self.closed {
+            return "<Image closed>".to_string();
+        }
+
+        format!(
+            "<Image mode={} width={} height={} format={} dimensions=({}, {})>",
+            self.mode(),
+            self.inner.width(),
+            self.inner.height(),
+            self.format(),
+            self.inner.width(),
+            self.inner.height()
+        )
+    }
+
+    fn __bool__(&self) -> PyResult<bool> {
+        ensure_open!(self);
+
+        Ok(!self.inner.is_empty())
+    }
+
+    /// Returns a new image with `other` alpha-composited over this image.
+    ///
+    /// Both images must have the same dimensions.
+    fn __or__(&self, py: Python<'_>, other: Self) -> PyResult<Self> {
+        ensure_open!(self, other);
+
+        self.ensure_same_dimensions(&other)?;
+
+        let mut inner = RilImage::clone(&self.inner);
+        py.allow_threads(|| paste_clipped(&mut inner, 0, 0, &other.inner, ril::OverlayMode::Merge));
+
+        Ok(self.derived(inner))
+    }
+
+    /// Returns a copy of this image with its alpha masked by the given `BITPIXEL` or `L` image.
+    ///
+    /// Both images must have the same dimensions.
+    fn __and__(&self, py: Python<'_>, mask: Self) -> PyResult<Self> {
+        ensure_open!(self, mask);
+
+        self.ensure_same_dimensions(&mask)?;
+
+        let mask = match mask.mode_enum() {
+            Mode::BitPixel | Mode::L => mask.inner.into_inner().convert::<ril::L>(),
+            mode => {
+                return Err(
+                    Error::UnexpectedFormat("BITPIXEL or L".to_string(), mode.to_string()).into(),
+                )
+            }
+        };
+
+        let mut inner = RilImage::clone(&self.inner);
+        py.allow_threads(|| mask_alpha_with(&mut inner, &mask));
+
+        Ok(self.derived(inner))
+    }
+}
+
+impl Image {
+    pub(crate) fn from_inner(image: RilImage) -> Self {
+        Self::from_shared(image.into())
+    }
+
+    /// Creates an image that shares its pixels with another until either of them is mutated.
+    pub(crate) fn from_shared(inner: Shared<RilImage>) -> Self {
+        Self {
+            inner,
+            metadata: Metadata::default(),
+            source_format: None,
+            source_color_model: None,
+            decode_warnings: Vec::new(),
+            color_space: ColorSpace::default(),
+            transforms: Vec::new(),
+            frozen: false,
+            closed: false,
+        }
+    }
+
+    /// Returns the draw transform currently applied, see :meth:`push_transform`.
+    fn transform(&self) -> DrawTransform {
+        self.transforms
+            .last()
+            .copied()
+            .unwrap_or(DrawTransform::IDENTITY)
+    }
+
+    /// Applies the draw transform to the inclusive box `(x1, y1, x2, y2)`, keeping it at least one pixel in size.
+    fn transform_box(&self, (x1, y1, x2, y2): (i64, i64, i64, i64)) -> (i64, i64, i64, i64) {
+        let transform = self.transform();
+        let (x1, y1) = transform.apply((x1, y1));
+        let (x2, y2) = transform.apply((x2 + 1, y2 + 1));
+
+        (x1, y1, (x2 - 1).max(x1), (y2 - 1).max(y1))
+    }
+
+    /// Creates an image derived from this image, which keeps its color space.
+    pub(crate) fn derived(&self, image: RilImage) -> Self {
+        Self {
+            color_space: self.color_space,
+            ..Self::from_inner(image)
+        }
+    }
+
+    /// Calls `f` on every pixel within the given region of this image, or every pixel if there is none.
+    fn map_region(
+        &mut self,
+        py: Python<'_>,
+        region: Option<Region>,
+        f: impl Fn(&mut Dynamic) + Send + Sync,
+    ) -> PyResult<()> {
+        let region = region.unwrap_or((0, 0, self.inner.width(), self.inner.height()));
+        self.ensure_box(region.0, region.1, region.2, region.3)?;
+
+        let inner = &mut self.inner;
+        py.allow_threads(|| for_each_in_region(inner, region, f));
+
+        Ok(())
+    }
+
+    /// Maps every color channel within the given region of this image through `lut`, leaving alpha untouched.
+    fn map_tones(
+        &mut self,
+        py: Python<'_>,
+        region: Option<Region>,
+        lut: [u8; 256],
+    ) -> PyResult<()> {
+        let luts = ChannelLuts::color(lut);
+        self.map_region(py, region, |pixel| *pixel = luts.map(*pixel))
+    }
+
+    /// Converts the color channels of this image from its color space to `target` in place.
+    fn convert_color_space(&mut self, py: Python<'_>, target: ColorSpace) {
+        let luts = ChannelLuts::color(self.color_space.lut(target));
+        let data = &mut self.inner.data;
+
+        py.allow_threads(|| data.iter_mut().for_each(|pixel| *pixel = luts.map(*pixel)));
+
+        self.color_space = target;
+    }
+
+    fn ensure_color_space(&self, expected: ColorSpace) -> PyResult<()> {
+        if self.color_space != expected {
+            return Err(PyValueError::new_err(format!(
+                "The image is already in the {} color space",
+                self.color_space
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn open_path(path: &Path) -> Result<Self, Error> {
+        let format = format_from_path(path)?;
+        let bytes = fs::read(path)?;
+
+        Self::decoded(format, &bytes)
+    }
+
+    /// Decodes an image from encoded data of the given format, keeping its metadata.
+    fn decoded(format: ImageFormat, bytes: &[u8]) -> Result<Self, Error> {
+        Ok(Self::from_source(decode(format, bytes)?, format, bytes))
+    }
+
+    /// Creates an image decoded from the given encoded data, reading its metadata.
+    fn from_source(image: RilImage, format: ImageFormat, bytes: &[u8]) -> Self {
+        Self {
+            metadata: Metadata::read(format, bytes),
+            source_format: Some(format),
+            source_color_model: match format {
+                ImageFormat::Jpeg => header::jpeg_color_model(bytes),
+                _ => None,
+            },
+            ..Self::from_inner(image)
+        }
+    }
+
+    fn save_path(
+        &self,
+        path: &Path,
+        encoding: Option<&str>,
+        options: Option<&EncodeOptions>,
+        strip_metadata: bool,
+        deterministic: bool,
+    ) -> Result<(), Error> {
+        let encoding = EncodeOptions::resolve_format(encoding, options, || {
+            if path.extension().is_some() {
+                return format_from_path(path);
+            }
+
+            self.source_format.ok_or_else(|| {
+                Error::Ril(RilError::EncodingError(
+                    "The path has no extension and the image was not decoded from any format, \
+                    an encoding must be given explicitly"
+                        .to_string(),
+                ))
+            })
+        })?;
+
+        fs::write(
+            path,
+            self.encode_with(encoding, options, strip_metadata, deterministic)?,
+        )?;
+
+        Ok(())
+    }
+
+    fn encode_with(
+        &self,
+        format: ImageFormat,
+        options: Option<&EncodeOptions>,
+        strip_metadata: bool,
+        deterministic: bool,
+    ) -> Result<Vec<u8>, Error> {
+        let pinned = deterministic
+            .then(|| EncodeOptions::pin(format, options))
+            .transpose()?;
+
+        let _span = trace::span("encode", Some(self.inner.dimensions()));
+        let buf = match (format, pinned.as_ref().or(options)) {
+            (_, Some(options)) => options.encode_image(&self.inner)?,
+            (ImageFormat::Gif, None) => {
+                EncodeOptions::Gif(GifOptions::default()).encode_image(&self.inner)?
+            }
+            (_, None) => {
+                let mut buf = Vec::new();
+                self.inner.encode(format, &mut buf)?;
+
+                buf
+            }
+        };
+
+        match (strip_metadata, deterministic) {
+            (true, _) => Ok(buf),
+            (false, true) => Ok(self.metadata.without_timestamps().write(format, buf)),
+            (false, false) => Ok(self.metadata.write(format, buf)),
+        }
+    }
+
+    fn mode(&self) -> String {
+        self.mode_enum().to_string()
+    }
+
+    fn mode_enum(&self) -> Mode {
+        Mode::of(&self.inner)
+    }
+
+    fn format(&self) -> String {
+        match self.source_format {
+            Some(format) => format!("{}", format),
+            None => "none".to_string(),
+        }
+    }
+
+    pub(crate) fn ensure_open(&self) -> Result<(), Error> {
+        if self.closed {
+            return Err(Error::Closed("image"));
+        }
+
+        Ok(())
+    }
+
+    fn ensure_mutable(&self) -> PyResult<()> {
+        if self.frozen {
+            return Err(PyRuntimeError::new_err(
+                "The image is frozen, use copy() or thaw() to modify it",
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn ensure_box(&self, x1: u32, y1: u32, x2: u32, y2: u32) -> PyResult<()> {
+        ensure_box(self.inner.dimensions(), x1, y1, x2, y2)
+    }
+
+    fn ensure_same_dimensions(&self, other: &Self) -> PyResult<()> {
+        if self.inner.dimensions() != other.inner.dimensions() {
+            return Err(PyValueError::new_err(format!(
+                "Expected an image with dimensions {:?}, got {:?}",
+                self.inner.dimensions(),
+                other.inner.dimensions()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// A read-only view of a region of an image, see :meth:`Image.view`.
+///
+/// The view keeps the image alive and reads its current data, nothing is copied until :meth:`to_image` is called.
+#[pyclass]
+pub struct ImageView {
+    image: Py<Image>,
+    region: (u32, u32, u32, u32),
+}
+
+#[pymethods]
+impl ImageView {
+    /// int: Returns the width of the view.
+    #[getter]
+    fn width(&self) -> u32 {
+        self.region.2 - self.region.0
+    }
+
+    /// int: Returns the height of the view.
+    #[getter]
+    fn height(&self) -> u32 {
+        self.region.3 - self.region.1
+    }
+
+    /// Returns the pixel at the given coordinates, relative to the upper-left corner of the view.
+    ///
+    /// Parameters
+    /// ----------
+    /// x: int
+    ///     The x axis
+    /// y: int
+    ///     The y axis
+    ///
+    /// Returns
+    /// -------
+    /// Union[:class:`.BitPixel`, :class:`.L`, :class:`.Rgb`, :class:`.Rgba`]
+    ///     The pixel of that specific coordinate.
+    ///
+    /// Raises
+    /// ------
+    /// IndexError
+    ///     The coordinates are outside of the view.
+    /// ValueError
+    ///     The image was closed, or shrunk and no longer contains the region of the view.
+    #[pyo3(text_signature = "($self, x, y)")]
+    fn get_pixel(&self, py: Python<'_>, x: u32, y: u32) -> PyResult<PyObject> {
+        if x >= self.width() || y >= self.height() {
+            return Err(PyIndexError::new_err(format!(
+                "Pixel ({}, {}) is outside of the view of size {}x{}",
+                x,
+                y,
+                self.width(),
+                self.height()
+            )));
+        }
+
+        let image = self.image.borrow(py);
+        ensure_open!(image);
+        image.ensure_box(self.region.0, self.region.1, self.region.2, self.region.3)?;
+
+        Ok(cast_pixel_to_pyobject(
+            py,
+            *image.inner.pixel(self.region.0 + x, self.region.1 + y),
+        ))
+    }
+
+    /// Returns a flat list of the pixels of the view, row by row.
+    ///
+    /// Returns
+    /// -------
+    /// List[Union[:class:`.BitPixel`, :class:`.L`, :class:`.Rgb`, :class:`.Rgba`]]
+    ///     The pixels of the view.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The image was closed, or shrunk and no longer contains the region of the view.
+    #[pyo3(text_signature = "($self)")]
+    fn pixels_flat(&self, py: Python<'_>) -> PyResult<Vec<PyObject>> {
+        let image = self.image.borrow(py);
+
+        Ok(self
+            .rows(&image)?
+            .flatten()
+            .map(|pixel| cast_pixel_to_pyobject(py, *pixel))
+            .collect())
+    }
+
+    /// Returns the raw pixel data of the view, row by row.
+    ///
+    /// Each pixel takes up as many bytes as it has channels in the mode of the image,
+    /// where a :class:`.BitPixel` is either ``0`` or ``255``.
+    ///
+    /// Returns
+    /// -------
+    /// bytes
+    ///     The raw pixel data.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The image was closed, or shrunk and no longer contains the region of the view.
+    #[pyo3(text_signature = "($self)")]
+    fn to_bytes<'a>(&self, py: Python<'a>) -> PyResult<&'a PyBytes> {
+        let image = self.image.borrow(py);
+        let bytes = raw_bytes(self.rows(&image)?.flatten());
+
+        Ok(PyBytes::new(py, &bytes))
+    }
+
+    /// Copies the region of the view into a new image.
+    ///
+    /// Returns
+    /// -------
+    /// :class:`.Image`
+    ///     The copied region.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The image was closed, or shrunk and no longer contains the region of the view.
+    #[pyo3(text_signature = "($self)")]
+    fn to_image(&self, py: Python<'_>) -> PyResult<Image> {
+        let image = self.image.borrow(py);
+        ensure_open!(image);
+        image.ensure_box(self.region.0, self.region.1, self.region.2, self.region.3)?;
+
+        Ok(image.derived(copy_region(&image.inner, self.region)))
+    }
+
+    fn __repr__(&self) -> String {
+        let (x1, y1, x2, y2) = self.region;
+
+        format!("<ImageView x1={} y1={} x2={} y2={}>", x1, y1, x2, y2)
+    }
+}
+
+impl ImageView {
+    /// Returns the rows of the region within the given image, which must be the image of this view.
+    fn rows<'a>(&self, image: &'a Image) -> PyResult<impl Iterator<Item = &'a [Dynamic]>> {
+        let (x1, y1, x2, y2) = self.region;
+        ensure_open!(image);
+        image.ensure_box(x1, y1, x2, y2)?;
+
+        Ok(image
+            .inner
+            .data
+            .chunks(image.inner.width() as usize)
+            .skip(y1 as usize)
+            .take((y2 - y1) as usize)
+            .map(move |row| &row[x1 as usize..x2 as usize]))
+    }
+}
+
+/// An iterator over the tiles of an image, see :meth:`Image.iter_tiles`.
+///
+/// Yields ``(x, y, tile)`` tuples, where ``x`` and ``y`` are the position of the tile within the image.
+#[pyclass]
+pub struct TileIterator {
+    image: Shared<RilImage>,
+    tile: Xy,
+    overlap: u32,
+    fill: Option<Dynamic>,
+    position: Option<Xy>,
+}
+
+#[pymethods]
+impl TileIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<'_, Self>) -> Option<(u32, u32, Image)> {
+        let (x, y) = slf.position?;
+        slf.position = slf.next_position(x, y);
+
+        Some((x, y, Image::from_inner(slf.tile_at(x, y))))
+    }
+}
+
+impl TileIterator {
+    /// Returns the position of the tile after the one at the given position,
+    /// stopping once a tile has reached the right and bottom edges.
+    fn next_position(&self, x: u32, y: u32) -> Option<Xy> {
+        let (width, height) = self.image.dimensions();
+        let (tile_width, tile_height) = self.tile;
+
+        if x.saturating_add(tile_width) < width {
+            Some((x + tile_width - self.overlap, y))
+        } else if y.saturating_add(tile_height) < height {
+            Some((0, y + tile_height - self.overlap))
+        } else {
+            None
+        }
+    }
+
+    fn tile_at(&self, x: u32, y: u32) -> RilImage {
+        let (width, height) = self.image.dimensions();
+        let x2 = x.saturating_add(self.tile.0).min(width);
+        let y2 = y.saturating_add(self.tile.1).min(height);
+        let region = copy_region(&self.image, (x, y, x2, y2));
+
+        match self.fill {
+            Some(fill) if region.dimensions() != self.tile => {
+                let mut tile = RilImage::new(self.tile.0, self.tile.1, fill);
+                paste_clipped(&mut tile, 0, 0, &region, ril::OverlayMode::Replace);
+
+                tile
+            }
+            _ => region,
+        }
+    }
+}
+
+/// Draws pixels of the given source modes onto `inner`, reconciling them with the mode of the image.
+///
+/// `draw` is given the mode to convert its pixels to. Sources with an alpha channel, or pixels that
+/// aren't `convertible`, are drawn onto a temporary RGBA copy of the image that is converted back afterwards.
+pub(crate) fn draw_reconciled(
+    inner: &mut RilImage,
+    sources: &[Mode],
+    strict_mode: bool,
+    convertible: bool,
+    draw: impl FnOnce(&mut RilImage, Mode) -> PyResult<()>,
+) -> PyResult<()> {
+    let mode = Mode::of(inner);
+
+    match sources.iter().find(|&&source| source != mode) {
+        None => return draw(inner, mode),
+        Some(source) if strict_mode => {
+            return Err(PyTypeError::new_err(format!(
+                "Expected pixels of mode `{}`, got `{}`",
+                mode, source
+            )))
+        }
+        Some(_) => {}
+    }
+
+    if mode == Mode::Rgba || (convertible && !sources.contains(&Mode::Rgba)) {
+        return draw(inner, mode);
+    }
+
+    let overlay = inner.overlay_mode();
+    let mut widened = Mode::Rgba.convert_image(inner.clone());
+    draw(&mut widened, Mode::Rgba)?;
+    *inner = mode.convert_image(widened).with_overlay_mode(overlay);
+
+    Ok(())
+}
+
+/// Pastes `image` onto `inner` like :meth:`Image.paste` without a mask, converting it to the mode of `inner`.
+pub(crate) fn paste_reconciled(inner: &mut RilImage, x: u32, y: u32, image: &RilImage) {
+    let source = Mode::of(image);
+
+    // Outside of strict mode, reconciling the modes never fails.
+    let _ = draw_reconciled(inner, &[source], false, true, |inner, mode| {
+        let converted;
+        let image = if source == mode {
+            image
+        } else {
+            converted = mode.convert_image(image.clone());
+            &converted
+        };
+
+        let overlay = inner.overlay_mode();
+        paste_clipped(inner, x, y, image, overlay);
+
+        Ok(())
+    });
+}
+
+/// Snaps every luminance value to the nearest of `levels` evenly spaced levels in place.
+///
+/// With `dither`, the rounding error is diffused onto the pixels that haven't been visited yet with
+/// Floyd–Steinberg dithering, in a single serpentine pass. The error is kept as `i16` so that it can
+/// push values past the range of a `u8` without clipping.
+fn quantize(luma: &mut [i16], width: usize, levels: u16, dither: bool) {
+    let step = 255.0 / f32::from(levels - 1);
+    let snap = |value: i16| ((f32::from(value.clamp(0, 255)) / step).round() * step).round() as i16;
+
+    if !dither {
+        luma.iter_mut().for_each(|value| *value = snap(*value));
+        return;
+    }
+
+    let height = luma.len() / width;
+
+    for y in 0..height {
+        // Alternate the direction of every row to avoid directional artifacts.
+        let ahead: isize = if y % 2 == 0 { 1 } else { -1 };
+
+        for i in 0..width {
+            let x = if ahead > 0 { i } else { width - 1 - i };
+            let old = luma[y * width + x];
+            let new = snap(old);
+            luma[y * width + x] = new;
+
+            let error = i32::from(old - new);
+            for (dx, dy, weight) in [(ahead, 0, 7), (-ahead, 1, 3), (0, 1, 5), (ahead, 1, 1)] {
+                let nx = x as isize + dx;
+
+                if nx >= 0 && (nx as usize) < width && y + dy < height {
+                    let value = &mut luma[(y + dy) * width + nx as usize];
+                    *value = value.saturating_add((error * weight / 16) as i16);
+                }
+            }
+        }
+    }
+}
+
+/// Decodes an image, where a GIF decodes to its first frame.
+pub(crate) fn decode(format: ImageFormat, bytes: &[u8]) -> Result<RilImage, Error> {
+    let span = trace::span("decode", None);
+    let image = if format == ImageFormat::Gif {
+        RilImageSequence::<Dynamic>::from_bytes(format, bytes)?
+            .next()
+            .ok_or(RilError::EmptyImageError)??
+            .into_image()
+    } else if format == ImageFormat::Jpeg && header::jpeg_color_model(bytes).is_some() {
+        decode_cmyk_jpeg(bytes)?
+    } else {
+        RilImage::from_bytes(format, bytes)?
+    };
+    span.end(image.dimensions());
+
+    Ok(image)
+}
+
+/// Decodes as much as possible of data that is cut off or corrupt, filling what couldn't be decoded with `fill`.
+///
+/// Returns the image along with warnings describing what was recovered, which are empty if the data is intact.
+/// Only JPEG data can be recovered, other data that fails to decode gives the same error as [`decode`].
+pub(crate) fn decode_tolerant(
+    format: ImageFormat,
+    bytes: &[u8],
+    fill: Dynamic,
+) -> Result<(RilImage, Vec<String>), Error> {
+    match decode(format, bytes) {
+        Ok(image) => Ok((image, Vec::new())),
+        Err(err) if format == ImageFormat::Jpeg => recover_jpeg(bytes, fill).ok_or(err),
+        Err(err) => Err(err),
+    }
+}
+
+/// The marker JPEG data ends with.
+const JPEG_END: [u8; 2] = [0xFF, 0xD9];
+
+/// Recovers the image from JPEG data that is cut off, see [`decode_tolerant`].
+///
+/// Once the decoder reaches an end marker within entropy-coded data, it reads zero bits for the rest of it.
+/// The data is decoded once followed by just that marker, and once with a set bit before it. Rows decoded from
+/// actual data come out the same both times, so the rows from the first one that differs on are filled.
+/// A set bit after the last scan is skipped like any other stray byte, so complete scans never differ.
+pub(crate) fn recover_jpeg(bytes: &[u8], fill: Dynamic) -> Option<(RilImage, Vec<String>)> {
+    let terminated = |padding: &[u8]| [bytes, padding, &JPEG_END].concat();
+
+    let data = terminated(&[]);
+    let mut image = decode(ImageFormat::Jpeg, &data).ok()?;
+
+    let mut decoder = JpegDecoder::new(data.as_slice());
+    decoder.read_info().ok()?;
+    if decoder.info()?.coding_process == CodingProcess::DctProgressive {
+        return Some((
+            image,
+            vec!["The progressive JPEG data is cut off, the image lacks the detail of its remaining scans".to_string()],
+        ));
+    }
+
+    let height = image.height();
+    let width = image.width() as usize;
+    let warning = match decode(ImageFormat::Jpeg, &terminated(&[0x80])) {
+        Ok(other) => {
+            let decoded = image
+                .data
+                .chunks(width)
+                .zip(other.data.chunks(width))
+                .take_while(|(row, other)| row == other)
+                .count();
+
+            if decoded == height as usize {
+                "The JPEG data is missing its end marker, every row was decoded".to_string()
+            } else {
+                let fill = Mode::of(&image).convert_pixel(fill);
+                image.data[decoded * width..].fill(fill);
+
+                format!(
+                    "The JPEG data is cut off, decoded {} of {} rows and filled the rest",
+                    decoded, height
+                )
+            }
+        }
+        Err(_) => "The JPEG data is cut off, the rows past the cut couldn't be located and hold placeholder data"
+            .to_string(),
+    };
+
+    Some((image, vec![warning]))
+}
+
+/// Decodes a JPEG image with four components, which ril can't decode, converting its CMYK or YCCK pixels to RGB.
+fn decode_cmyk_jpeg(bytes: &[u8]) -> Result<RilImage, Error> {
+    let ycck = header::jpeg_color_model(bytes) == Some("ycck");
+    let mut decoder = JpegDecoder::new(bytes);
+    let data = decoder.decode().map_err(decoding_error)?;
+    let info = decoder.info().ok_or(RilError::EmptyImageError)?;
+
+    let pixels = data
+        .chunks_exact(4)
+        .map(|cmyk| {
+            // The decoder inverts the color channels of YCCK data, but not the black channel.
+            let ink = |value: u8| if ycck { 255 - value } else { value };
+            let white = 255 - u16::from(cmyk[3]);
+            let channel = |value: u8| ((255 - u16::from(ink(value))) * white / 255) as u8;
+
+            Dynamic::Rgb(ril::Rgb {
+                r: channel(cmyk[0]),
+                g: channel(cmyk[1]),
+                b: channel(cmyk[2]),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(RilImage::from_pixels(u32::from(info.width), pixels))
+}
+
+pub(crate) fn decoding_error(err: jpeg_decoder::Error) -> Error {
+    Error::Ril(RilError::DecodingError(err.to_string()))
+}
+
+/// Copies the given region of `inner`, which must be within its bounds, into a new image.
+pub(crate) fn copy_region(inner: &RilImage, (x1, y1, x2, y2): (u32, u32, u32, u32)) -> RilImage {
+    let data = inner
+        .data
+        .chunks(inner.width() as usize)
+        .skip(y1 as usize)
+        .take((y2 - y1) as usize)
+        .flat_map(|row| &row[x1 as usize..x2 as usize])
+        .copied()
+        .collect::<Vec<_>>();
+
+    RilImage::from_pixels(x2 - x1, data)
+}
+
+/// Averages the pixels within the given region of `inner`, which must be within its bounds, see `Image.average_color`.
+fn average_color_of(inner: &RilImage, (x1, y1, x2, y2): Region) -> Dynamic {
+    let width = inner.width() as usize;
+    // The sums of the color channels premultiplied by alpha, and of alpha itself.
+    let mut sums = [0_u64; 4];
 
-    /// Inverts the image in-place.
-    fn invert(&mut self) {
-        self.inner.invert();
+    for row in inner.data[y1 as usize * width..y2 as usize * width].chunks(width) {
+        for &pixel in &row[x1 as usize..x2 as usize] {
+            let ril::Rgba { r, g, b, a } = pixel.into();
+            let alpha = u64::from(a);
+
+            sums[0] += u64::from(r) * alpha;
+            sums[1] += u64::from(g) * alpha;
+            sums[2] += u64::from(b) * alpha;
+            sums[3] += alpha;
+        }
     }
 
-    fn __len__(&self) -> usize {
-        self.inner.len() as usize
+    let count = u64::from(x2 - x1) * u64::from(y2 - y1);
+    let channel = |sum: u64| (sum + sums[3] / 2).checked_div(sums[3]).unwrap_or(0) as u8;
+
+    Mode::of(inner).convert_pixel(Dynamic::Rgba(ril::Rgba {
+        r: channel(sums[0]),
+        g: channel(sums[1]),
+        b: channel(sums[2]),
+        a: ((sums[3] + count / 2) / count) as u8,
+    }))
+}
+
+/// Replaces every block of `block_size` pixels within the given region of `inner` with its average color, see `Image.pixelate`.
+fn pixelate_region(inner: &mut RilImage, (x1, y1, x2, y2): Region, block_size: u32) {
+    let width = inner.width() as usize;
+
+    for by in (y1..y2).step_by(block_size as usize) {
+        for bx in (x1..x2).step_by(block_size as usize) {
+            let block = (
+                bx,
+                by,
+                bx.saturating_add(block_size).min(x2),
+                by.saturating_add(block_size).min(y2),
+            );
+            let average = average_color_of(inner, block);
+
+            for y in block.1..block.3 {
+                let row = y as usize * width;
+                inner.data[row + block.0 as usize..row + block.2 as usize].fill(average);
+            }
+        }
     }
+}
 
-    fn __repr__(&self) -> String {
-        format!(
-            "<Image mode={} width={} height={} format={} dimensions=({}, {})>",
-            self.mode(),
-            self.width(),
-            self.height(),
-            self.format(),
-            self.dimensions().0,
-            self.dimensions().1
-        )
+/// Counts the distinct RGBA colors of the pixels, stopping once there are more than `max_count`.
+fn count_colors(pixels: &[Dynamic], max_count: Option<usize>) -> usize {
+    let limit = max_count.map_or(usize::MAX, |max| max.saturating_add(1));
+    let mut colors = HashSet::new();
+
+    for &pixel in pixels {
+        let ril::Rgba { r, g, b, a } = ril::Rgba::from(pixel);
+        if colors.insert([r, g, b, a]) && colors.len() >= limit {
+            break;
+        }
+    }
+
+    colors.len()
+}
+
+/// Returns the raw bytes of the given pixels, where each pixel takes up as many bytes as it has channels,
+/// see `Image.to_bytes`.
+fn raw_bytes<'a>(pixels: impl Iterator<Item = &'a Dynamic>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+
+    for pixel in pixels {
+        match *pixel {
+            Dynamic::BitPixel(v) => bytes.push(if v.0 { u8::MAX } else { 0 }),
+            Dynamic::L(v) => bytes.push(v.0),
+            Dynamic::Rgb(v) => bytes.extend([v.r, v.g, v.b]),
+            Dynamic::Rgba(v) => bytes.extend([v.r, v.g, v.b, v.a]),
+        }
     }
 
-    fn __bool__(&self) -> bool {
-        !self.inner.is_empty()
+    bytes
+}
+
+/// Returns the amount of bytes a row of packed bits takes up, padded to `row_align` bytes.
+fn packed_stride(width: u32, row_align: usize) -> PyResult<usize> {
+    if row_align == 0 {
+        return Err(PyValueError::new_err("Row alignment must be at least 1"));
     }
+
+    let bytes = (width as usize + 7) / 8;
+
+    Ok((bytes + row_align - 1) / row_align * row_align)
 }
 
-impl Image {
-    fn from_inner(image: RilImage) -> Self {
-        Self { inner: image }
+/// The JPEG or WebP quality used by :meth:`Image.encode_optimized` when no target size is given.
+const OPTIMIZED_QUALITY: u8 = 85;
+
+fn encode_jpeg(image: &RilImage, quality: u8) -> Result<Vec<u8>, Error> {
+    let mut buf = Vec::new();
+    JpegEncoder::new()
+        .with_quality(quality)
+        .encode(image, &mut buf)?;
+
+    Ok(buf)
+}
+
+/// Encodes with the highest quality from 1 to 100 that fits under `target` bytes.
+fn optimize_quality(
+    image: &RilImage,
+    target: Option<usize>,
+    encode: impl Fn(&RilImage, u8) -> Result<Vec<u8>, Error>,
+) -> Result<(Vec<u8>, Option<u8>), Error> {
+    let target = match target {
+        Some(target) => target,
+        None => return Ok((encode(image, OPTIMIZED_QUALITY)?, Some(OPTIMIZED_QUALITY))),
+    };
+
+    let smallest = encode(image, 1)?;
+    if smallest.len() > target {
+        return Err(Error::TargetSizeUnreachable(target, smallest.len()));
+    }
+
+    // Binary search for the highest quality that fits, `best` always fits.
+    let (mut best, mut best_quality) = (smallest, 1);
+    let (mut low, mut high) = (2, 100);
+    while low <= high {
+        let quality = low + (high - low) / 2;
+        let buf = encode(image, quality)?;
+
+        if buf.len() <= target {
+            best = buf;
+            best_quality = quality;
+            low = quality + 1;
+        } else {
+            high = quality - 1;
+        }
+    }
+
+    Ok((best, Some(best_quality)))
+}
+
+/// Encodes as PNG with a few configurations, keeping the smallest output.
+fn optimize_png(image: &RilImage, target: Option<usize>) -> Result<Vec<u8>, Error> {
+    let configs = [
+        (AdaptiveFilterType::Adaptive, FilterType::Sub),
+        (AdaptiveFilterType::NonAdaptive, FilterType::Paeth),
+        (AdaptiveFilterType::NonAdaptive, FilterType::NoFilter),
+    ];
+
+    let mut best: Option<Vec<u8>> = None;
+    for (adaptive_filter, filter) in configs {
+        let mut buf = Vec::new();
+        PngEncoder::new()
+            .with_adaptive_filter(adaptive_filter)
+            .with_filter(filter)
+            .with_compression(Compression::Best)
+            .encode(image, &mut buf)?;
+
+        if best.as_ref().map_or(true, |best| buf.len() < best.len()) {
+            best = Some(buf);
+        }
+    }
+
+    let best = best.unwrap_or_default();
+    match target {
+        Some(target) if best.len() > target => Err(Error::TargetSizeUnreachable(target, best.len())),
+        _ => Ok(best),
+    }
+}
+
+/// Calls `f` on every pixel within the given region of `inner`.
+fn for_each_in_region(
+    inner: &mut RilImage,
+    (x1, y1, x2, y2): Region,
+    f: impl Fn(&mut Dynamic) + Send + Sync,
+) {
+    let width = inner.width() as usize;
+    let (start, end) = (y1 as usize * width, y2 as usize * width);
+
+    for_each_row(&mut inner.data[start..end], width, |_, row| {
+        row[x1 as usize..x2 as usize].iter_mut().for_each(&f);
+    });
+}
+
+/// Builds a lookup table that maps every channel value through `f`, rounding and clamping the result.
+fn tone_lut(f: impl Fn(f32) -> f32) -> [u8; 256] {
+    let mut lut = [0; 256];
+    for (value, out) in lut.iter_mut().enumerate() {
+        *out = f(value as f32).round().clamp(0.0, 255.0) as u8;
+    }
+
+    lut
+}
+
+/// Resizes the whole of `inner` to the given dimensions, like `Image.resize` without `linear`.
+pub(crate) fn resized(inner: &RilImage, dimensions: Xy, algorithm: ResizeAlgorithm) -> RilImage {
+    let (width, height) = inner.dimensions();
+    resample_region(inner, (0, 0, width, height), dimensions, algorithm, false)
+}
+
+/// Resamples the region `(x1, y1, x2, y2)` of `inner` to the given dimensions, reading it in place.
+/// This is how every resize is done, so that resizing a region is the same as cropping and then resizing it.
+///
+/// Channels are decoded to f32 and resampled with alpha premultiplied, so neither dark shadows nor
+/// transparent pixels are rounded through 8 bits before they are blended. sRGB channels are resampled in
+/// linear light if `linear` is set, see `Image.resize`.
+pub(crate) fn resample_region(
+    inner: &RilImage,
+    (x1, y1, x2, y2): (u32, u32, u32, u32),
+    (width, height): Xy,
+    algorithm: ResizeAlgorithm,
+    linear: bool,
+) -> RilImage {
+    let mode = Mode::of(inner);
+    let decode: [f32; 256] = std::array::from_fn(|value| {
+        let value = value as f32 / 255.0;
+        if linear {
+            srgb_to_linear(value)
+        } else {
+            value
+        }
+    });
+    let encode = |value: f32| {
+        let value = value.clamp(0.0, 1.0);
+        let value = if linear { linear_to_srgb(value) } else { value };
+
+        (value * 255.0).round() as u8
+    };
+
+    let channels = match mode {
+        Mode::BitPixel | Mode::L => 1,
+        Mode::Rgb | Mode::Rgba => 4,
+    };
+    let stride = inner.width() as usize;
+    let source = |y: usize, values: &mut Vec<f32>| {
+        let row = &inner.data[(y1 as usize + y) * stride..][x1 as usize..x2 as usize];
+
+        if channels == 1 {
+            values.extend(row.iter().map(|&pixel| {
+                let ril::L(value) = pixel.into();
+                decode[value as usize]
+            }));
+        } else {
+            values.extend(row.iter().flat_map(|&pixel| {
+                let ril::Rgba { r, g, b, a } = pixel.into();
+                let alpha = f32::from(a) / 255.0;
+
+                [
+                    decode[r as usize] * alpha,
+                    decode[g as usize] * alpha,
+                    decode[b as usize] * alpha,
+                    alpha,
+                ]
+            }));
+        }
+    };
+
+    let resampled = resample(
+        channels,
+        ((x2 - x1) as usize, (y2 - y1) as usize),
+        (width as usize, height as usize),
+        algorithm,
+        source,
+    );
+
+    let data = resampled
+        .chunks_exact(channels)
+        .map(|pixel| match *pixel {
+            [l] if mode == Mode::BitPixel => Dynamic::BitPixel(ril::BitPixel(l >= 0.5)),
+            [l] => Dynamic::L(ril::L(encode(l))),
+            [r, g, b, alpha] => {
+                let alpha = alpha.clamp(0.0, 1.0);
+                let unpremultiply = |value: f32| {
+                    if alpha > 0.0 {
+                        encode(value / alpha)
+                    } else {
+                        0
+                    }
+                };
+                let (r, g, b) = (unpremultiply(r), unpremultiply(g), unpremultiply(b));
+
+                if mode == Mode::Rgba {
+                    let a = (alpha * 255.0).round() as u8;
+                    Dynamic::Rgba(ril::Rgba { r, g, b, a })
+                } else {
+                    Dynamic::Rgb(ril::Rgb { r, g, b })
+                }
+            }
+            _ => unreachable!(),
+        })
+        .collect::<Vec<_>>();
+
+    RilImage::from_pixels(width, data).with_overlay_mode(inner.overlay_mode())
+}
+
+/// Alpha composites every pixel of `inner` over `background`, returning an RGB image.
+pub(crate) fn flatten_over(inner: &RilImage, background: ril::Rgb) -> RilImage {
+    let data = inner
+        .data
+        .iter()
+        .map(|&pixel| {
+            let ril::Rgba { r, g, b, a } = pixel.into();
+
+            Dynamic::Rgb(ril::Rgb {
+                r: blend_over(r, background.r, a),
+                g: blend_over(g, background.g, a),
+                b: blend_over(b, background.b, a),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    RilImage::from_pixels(inner.width(), data)
+}
+
+/// Composites a channel `value` with the given alpha over the same channel of an opaque `background`.
+pub(crate) fn blend_over(value: u8, background: u8, alpha: u8) -> u8 {
+    let (value, background, alpha) = (u16::from(value), u16::from(background), u16::from(alpha));
+
+    ((value * alpha + background * (255 - alpha) + 127) / 255) as u8
+}
+
+/// Maps every pixel of `inner` to the nearest color of the non-empty `palette` by its RGB channels, see `Image.remap_palette`.
+///
+/// With `dither`, the difference to the chosen color is diffused like in `quantize`, per channel.
+fn remap_palette_with(inner: &RilImage, palette: &[ril::Rgb], dither: bool) -> RilImage {
+    let (width, height) = (inner.width() as usize, inner.height() as usize);
+    let nearest = |[r, g, b]: [i16; 3]| {
+        let color = Dynamic::Rgb(ril::Rgb {
+            r: r.clamp(0, 255) as u8,
+            g: g.clamp(0, 255) as u8,
+            b: b.clamp(0, 255) as u8,
+        });
+
+        palette
+            .iter()
+            .map(|&entry| DistanceMetric::Redmean.distance_squared(color, Dynamic::Rgb(entry)))
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map_or(0, |(index, _)| index)
+    };
+
+    let mut colors = inner
+        .data
+        .iter()
+        .map(|&pixel| {
+            let ril::Rgb { r, g, b } = pixel.into();
+            [i16::from(r), i16::from(g), i16::from(b)]
+        })
+        .collect::<Vec<_>>();
+    let mut indices = vec![0; colors.len()];
+
+    for y in 0..height {
+        // Alternate the direction of every row to avoid directional artifacts.
+        let ahead: isize = if y % 2 == 0 { 1 } else { -1 };
+
+        for i in 0..width {
+            let x = if ahead > 0 { i } else { width - 1 - i };
+            let old = colors[y * width + x];
+            let index = nearest(old);
+            indices[y * width + x] = index;
+
+            if !dither {
+                continue;
+            }
+
+            let ril::Rgb { r, g, b } = palette[index];
+            let new = [i16::from(r), i16::from(g), i16::from(b)];
+
+            for (dx, dy, weight) in [(ahead, 0, 7), (-ahead, 1, 3), (0, 1, 5), (ahead, 1, 1)] {
+                let nx = x as isize + dx;
+
+                if nx >= 0 && (nx as usize) < width && y + dy < height {
+                    let color = &mut colors[(y + dy) * width + nx as usize];
+
+                    for ((value, old), new) in color.iter_mut().zip(old).zip(new) {
+                        let error = i32::from(old) - i32::from(new);
+                        *value = value.saturating_add((error * weight / 16) as i16);
+                    }
+                }
+            }
+        }
+    }
+
+    let keep_alpha = Mode::of(inner) == Mode::Rgba;
+    let data = inner
+        .data
+        .iter()
+        .zip(indices)
+        .map(|(&pixel, index)| {
+            let ril::Rgb { r, g, b } = palette[index];
+
+            if keep_alpha {
+                let ril::Rgba { a, .. } = pixel.into();
+                Dynamic::Rgba(ril::Rgba { r, g, b, a })
+            } else {
+                Dynamic::Rgb(ril::Rgb { r, g, b })
+            }
+        })
+        .collect::<Vec<_>>();
+
+    RilImage::from_pixels(inner.width(), data).with_overlay_mode(inner.overlay_mode())
+}
+
+/// Validates a lookup table given from Python, which must map every channel value to another.
+fn parse_lut(lut: &[i64]) -> PyResult<[u8; 256]> {
+    let lut = <[i64; 256]>::try_from(lut).map_err(|_| {
+        PyValueError::new_err(format!(
+            "Expected a lookup table of 256 entries, got {}",
+            lut.len()
+        ))
+    })?;
+
+    let mut out = [0; 256];
+    for (index, (entry, out)) in lut.into_iter().zip(&mut out).enumerate() {
+        *out = u8::try_from(entry).map_err(|_| {
+            PyValueError::new_err(format!(
+                "Expected lookup table entries from 0 to 255, got {} at index {}",
+                entry, index
+            ))
+        })?;
+    }
+
+    Ok(out)
+}
+
+/// Lists the names of the channels of the given mode for error messages.
+fn channel_names(mode: Mode) -> &'static str {
+    match mode {
+        Mode::BitPixel => "none, since BitPixel images have no channels to map",
+        Mode::L => "`l`",
+        Mode::Rgb => "`r`, `g` or `b`",
+        Mode::Rgba => "`r`, `g`, `b` or `a`",
+    }
+}
+
+/// Returns the dimensions to resize an image of the given `source` dimensions to, keeping its aspect ratio
+/// within `tolerance`. Unless `exact`, the dimension that constrains the image less is shrunk to fit.
+fn fit_aspect(
+    (src_width, src_height): (u32, u32),
+    (width, height): (u32, u32),
+    exact: bool,
+    tolerance: f64,
+) -> PyResult<(u32, u32)> {
+    if tolerance.is_nan() || tolerance < 0.0 {
+        return Err(PyValueError::new_err(format!(
+            "Expected a non-negative tolerance, got {}",
+            tolerance
+        )));
+    }
+
+    let source = f64::from(src_width) / f64::from(src_height);
+    let target = f64::from(width) / f64::from(height);
+    let change = (target / source - 1.0).abs();
+    if change <= tolerance {
+        return Ok((width, height));
+    }
+
+    if exact {
+        return Err(PyValueError::new_err(format!(
+            "Resizing from ({}, {}) to ({}, {}) changes the aspect ratio by {:.1}%, more than the tolerance of {:.1}%",
+            src_width,
+            src_height,
+            width,
+            height,
+            change * 100.0,
+            tolerance * 100.0
+        )));
+    }
+
+    let scaled = |length: u32, to: u32, from: u32| {
+        let length = (u64::from(length) * u64::from(to) + u64::from(from) / 2) / u64::from(from);
+
+        length.clamp(1, u64::from(u32::MAX)) as u32
+    };
+
+    // Whichever axis has to shrink more keeps its target length.
+    Ok(if target > source {
+        (scaled(src_width, height, src_height), height)
+    } else {
+        (width, scaled(src_height, width, src_width))
+    })
+}
+
+/// Returns the inclusive range of a line of the given thickness centered on `center`.
+fn line_span(center: i64, thickness: u32) -> PyResult<(i64, i64)> {
+    if thickness == 0 {
+        return Err(PyValueError::new_err("Line thickness must be at least 1"));
     }
+
+    let start = center - i64::from(thickness - 1) / 2;
+    Ok((start, start + i64::from(thickness) - 1))
+}
+
+/// Fills the inclusive box `(x1, y1, x2, y2)` of `inner` with `color`, clipping whatever falls outside of `inner`.
+///
+/// Opaque colors are written row by row, translucent RGBA colors are blended onto every pixel
+/// as if it had an alpha channel when the overlay mode is merge.
+fn fill_clipped(inner: &mut RilImage, (x1, y1, x2, y2): (i64, i64, i64, i64), color: Dynamic) {
+    let (width, height) = inner.dimensions();
+    let (x1, x2) = (x1.max(0), x2.min(i64::from(width) - 1));
+    let (y1, y2) = (y1.max(0), y2.min(i64::from(height) - 1));
+    if x1 > x2 || y1 > y2 {
+        return;
+    }
+
+    let (x1, x2) = (x1 as usize, x2 as usize);
+    let mode = Mode::of(inner);
+    let blend = matches!(color, Dynamic::Rgba(ril::Rgba { a, .. }) if a < 255)
+        && matches!(inner.overlay_mode(), ril::OverlayMode::Merge);
+    let converted = mode.convert_pixel(color);
+    let merge = ril::OverlayMode::Merge;
+
+    for y in y1 as usize..=y2 as usize {
+        let row = &mut inner.data[y * width as usize..][x1..=x2];
+
+        if blend {
+            for dst in row {
+                let widened = Mode::Rgba.convert_pixel(*dst);
+                *dst = mode.convert_pixel(widened.overlay_with_alpha(color, merge, 255));
+            }
+        } else {
+            row.fill(converted);
+        }
+    }
+}
+
+/// Pastes `image` onto `inner` at the given position using the given overlay mode,
+/// clipping whatever falls outside of `inner`.
+pub(crate) fn paste_clipped(inner: &mut RilImage, x: u32, y: u32, image: &RilImage, mode: ril::OverlayMode) {
+    let (width, height) = inner.dimensions();
+    if x >= width || y >= height {
+        return;
+    }
+
+    let src_width = image.width() as usize;
+    let copy_width = (width - x).min(image.width()) as usize;
+    let rows = (height - y).min(image.height()) as usize;
+
+    let start = (y * width) as usize;
+    let end = start + rows * width as usize;
+
+    for_each_row(&mut inner.data[start..end], width as usize, |dy, row| {
+        let src = &image.data[dy * src_width..][..copy_width];
+
+        for (dst, src) in row[x as usize..][..copy_width].iter_mut().zip(src) {
+            *dst = dst.overlay_with_alpha(*src, mode, 255);
+        }
+    });
+}
+
+/// Pastes `image` onto `inner` at the given position through `mask` like `Image::paste_with_mask`,
+/// which panics on anything past the edges, so `image` and `mask` are cropped to the part within `inner` first.
+fn paste_masked_clipped(
+    inner: &mut RilImage,
+    x: u32,
+    y: u32,
+    mut image: RilImage,
+    mut mask: RilImage<ril::BitPixel>,
+) {
+    let (width, height) = inner.dimensions();
+    if x >= width || y >= height {
+        return;
+    }
+
+    let visible = (
+        (width - x).min(image.width()),
+        (height - y).min(image.height()),
+    );
+    if visible != image.dimensions() {
+        image.crop(0, 0, visible.0, visible.1);
+        mask.crop(0, 0, visible.0, visible.1);
+    }
+
+    inner.paste_with_mask(x, y, image, mask);
+}
+
+/// Repeats `image` over the given region of `inner`, starting at `offset` within the tile.
+fn paste_tiled_with(
+    inner: &mut RilImage,
+    (x1, y1, x2, y2): (u32, u32, u32, u32),
+    image: &RilImage,
+    offset: (i64, i64),
+    mode: ril::OverlayMode,
+) {
+    let width = inner.width() as usize;
+    let (tile_width, tile_height) = (i64::from(image.width()), i64::from(image.height()));
+
+    let start = y1 as usize * width;
+    let end = y2 as usize * width;
+
+    for_each_row(&mut inner.data[start..end], width, |dy, row| {
+        let ty = (dy as i64 + offset.1).rem_euclid(tile_height) as usize;
+        let src = &image.data[ty * tile_width as usize..][..tile_width as usize];
+
+        for (dx, dst) in row[x1 as usize..x2 as usize].iter_mut().enumerate() {
+            let tx = (dx as i64 + offset.0).rem_euclid(tile_width) as usize;
+            *dst = dst.overlay_with_alpha(src[tx], mode, 255);
+        }
+    });
+}
+
+/// Sharpens every color channel of `inner` in place, blurring it with a box blur of the given radius.
+fn unsharp_mask_with(inner: &mut RilImage, radius: usize, amount: f32, threshold: u8) {
+    let width = inner.width() as usize;
+    let channels = if Mode::of(inner) == Mode::L { 1 } else { 3 };
+
+    for channel in 0..channels {
+        let original = inner
+            .data
+            .iter()
+            .map(|&pixel| f32::from(color_channel(pixel, channel)))
+            .collect::<Vec<_>>();
+        let mut blurred = original.clone();
+        box_blur(&mut blurred, width, &[radius; 2]);
+
+        for ((pixel, value), blurred) in inner.data.iter_mut().zip(original).zip(blurred) {
+            let difference = value - blurred;
+            if difference.abs() < f32::from(threshold) {
+                continue;
+            }
+
+            let sharpened = (value + amount * difference).round().clamp(0.0, 255.0) as u8;
+            set_color_channel(pixel, channel, sharpened);
+        }
+    }
+}
+
+/// Blurs every channel of `inner` in place, including alpha, with a box blur of each of the given radii in turn.
+///
+/// Colors are premultiplied by their alpha while blurring, so fully transparent pixels don't bleed their color.
+fn blur_with(inner: &mut RilImage, radii: &[usize]) {
+    if radii.iter().all(|&radius| radius == 0) {
+        return;
+    }
+
+    let width = inner.width() as usize;
+    let channels = match Mode::of(inner) {
+        Mode::Rgb => 3,
+        Mode::Rgba => 4,
+        _ => 1,
+    };
+
+    let mut planes = vec![Vec::with_capacity(inner.data.len()); channels];
+    for &pixel in &inner.data {
+        let alpha = match pixel {
+            Dynamic::Rgba(ril::Rgba { a, .. }) => {
+                planes[3].push(f32::from(a));
+                f32::from(a) / 255.0
+            }
+            _ => 1.0,
+        };
+
+        for (channel, plane) in planes.iter_mut().enumerate().take(3) {
+            plane.push(f32::from(color_channel(pixel, channel)) * alpha);
+        }
+    }
+
+    for plane in &mut planes {
+        box_blur(plane, width, radii);
+    }
+
+    let channel = |value: f32| value.round().clamp(0.0, 255.0) as u8;
+    for (index, pixel) in inner.data.iter_mut().enumerate() {
+        let scale = match pixel {
+            Dynamic::Rgba(ril::Rgba { a, .. }) => {
+                let alpha = planes[3][index];
+                *a = channel(alpha);
+
+                if alpha > 0.0 {
+                    255.0 / alpha
+                } else {
+                    0.0
+                }
+            }
+            _ => 1.0,
+        };
+
+        for (color, plane) in planes.iter().enumerate().take(3) {
+            set_color_channel(pixel, color, channel(plane[index] * scale));
+        }
+    }
+}
+
+/// Scales the color channels of `inner` down with the distance of each pixel from the center, see `Image.vignette`.
+fn vignette_with(inner: &mut RilImage, strength: f32, radius: f32) {
+    if radius >= 1.0 {
+        return;
+    }
+
+    let (width, height) = inner.dimensions();
+    let (cx, cy) = (width as f32 / 2.0, height as f32 / 2.0);
+    let corner = cx.hypot(cy);
+
+    for_each_row(&mut inner.data, width as usize, |y, row| {
+        let dy = y as f32 + 0.5 - cy;
+
+        for (x, pixel) in row.iter_mut().enumerate() {
+            let distance = (x as f32 + 0.5 - cx).hypot(dy) / corner;
+            let t = ((distance - radius) / (1.0 - radius)).clamp(0.0, 1.0);
+            if t <= 0.0 {
+                continue;
+            }
+
+            // Smoothstep, so the falloff has no visible edge at `radius`.
+            let factor = 1.0 - strength * t * t * 2.0_f32.mul_add(-t, 3.0);
+            let scale = |value: &mut u8| *value = (f32::from(*value) * factor).round() as u8;
+
+            match pixel {
+                Dynamic::BitPixel(_) => {}
+                Dynamic::L(ril::L(l)) => scale(l),
+                Dynamic::Rgb(ril::Rgb { r, g, b }) | Dynamic::Rgba(ril::Rgba { r, g, b, .. }) => {
+                    scale(r);
+                    scale(g);
+                    scale(b);
+                }
+            }
+        }
+    });
+}
+
+/// Rotates `inner` clockwise by the given angle in degrees onto a canvas that fits all of it, see `Image.rotate`.
+///
+/// Pixels are sampled in premultiplied alpha, and everything outside of `inner` is sampled as `fill`,
+/// so the edges of the image blend into the exposed corners.
+fn rotate_with(
+    inner: &RilImage,
+    degrees: f64,
+    algorithm: ResizeAlgorithm,
+    fill: Dynamic,
+) -> RilImage {
+    let mode = Mode::of(inner);
+    let (width, height) = inner.dimensions();
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    // Shaves off rounding errors of the trigonometry, which would otherwise add a pixel to exact extents.
+    let extent = |extent: f64| (extent - 1e-6).ceil().max(1.0) as u32;
+    let new_width = extent(f64::from(width) * cos.abs() + f64::from(height) * sin.abs());
+    let new_height = extent(f64::from(width) * sin.abs() + f64::from(height) * cos.abs());
+
+    let premultiply = |pixel: Dynamic| {
+        let ril::Rgba { r, g, b, a } = ril::Rgba::from(pixel);
+        let alpha = f64::from(a) / 255.0;
+
+        [
+            f64::from(r) * alpha,
+            f64::from(g) * alpha,
+            f64::from(b) * alpha,
+            f64::from(a),
+        ]
+    };
+
+    let fill = mode.convert_pixel(fill);
+    let fill_premultiplied = premultiply(fill);
+    let source = inner
+        .data
+        .iter()
+        .copied()
+        .map(premultiply)
+        .collect::<Vec<_>>();
+    let sample = |x: i64, y: i64| {
+        if x < 0 || y < 0 || x >= i64::from(width) || y >= i64::from(height) {
+            fill_premultiplied
+        } else {
+            source[(y * i64::from(width) + x) as usize]
+        }
+    };
+
+    let (cx, cy) = (f64::from(width) / 2.0, f64::from(height) / 2.0);
+    let (new_cx, new_cy) = (f64::from(new_width) / 2.0, f64::from(new_height) / 2.0);
+    let mut data = vec![fill; new_width as usize * new_height as usize];
+
+    for_each_row(&mut data, new_width as usize, |y, row| {
+        let dy = y as f64 + 0.5 - new_cy;
+
+        for (x, pixel) in row.iter_mut().enumerate() {
+            let dx = x as f64 + 0.5 - new_cx;
+            // The inverse rotation maps the center of this pixel back into `inner`, where y points down.
+            let sx = dx.mul_add(cos, dy * sin) + cx - 0.5;
+            let sy = dy.mul_add(cos, -dx * sin) + cy - 0.5;
+
+            let [r, g, b, a] = match algorithm {
+                ResizeAlgorithm::Nearest => sample(sx.round() as i64, sy.round() as i64),
+                ResizeAlgorithm::Bicubic
+                | ResizeAlgorithm::Mitchell
+                | ResizeAlgorithm::Lanczos3 => interpolate(sx, sy, 2, catmull_rom, &sample),
+                _ => interpolate(sx, sy, 1, |t| (1.0 - t.abs()).max(0.0), &sample),
+            };
+
+            let a = a.clamp(0.0, 255.0);
+            let unpremultiply = |value: f64| {
+                if a == 0.0 {
+                    0
+                } else {
+                    (value * 255.0 / a).round().clamp(0.0, 255.0) as u8
+                }
+            };
+
+            *pixel = mode.convert_pixel(Dynamic::Rgba(ril::Rgba {
+                r: unpremultiply(r),
+                g: unpremultiply(g),
+                b: unpremultiply(b),
+                a: a.round() as u8,
+            }));
+        }
+    });
+
+    RilImage::from_pixels(new_width, data)
+}
+
+/// Interpolates the pixels returned by `sample` around the given position with a separable `kernel`,
+/// which spans `radius` pixels to either side.
+fn interpolate(
+    x: f64,
+    y: f64,
+    radius: i64,
+    kernel: impl Fn(f64) -> f64,
+    sample: impl Fn(i64, i64) -> [f64; 4],
+) -> [f64; 4] {
+    let (x0, y0) = (x.floor() as i64, y.floor() as i64);
+    let mut sum = [0.0; 4];
+
+    for j in y0 + 1 - radius..=y0 + radius {
+        let weight_y = kernel(y - j as f64);
+
+        for i in x0 + 1 - radius..=x0 + radius {
+            let weight = weight_y * kernel(x - i as f64);
+
+            for (sum, value) in sum.iter_mut().zip(sample(i, j)) {
+                *sum += weight * value;
+            }
+        }
+    }
+
+    sum
+}
+
+/// The Catmull-Rom cubic kernel, whose weights sum to 1 and which passes through the sampled pixels.
+fn catmull_rom(t: f64) -> f64 {
+    let t = t.abs();
+
+    if t < 1.0 {
+        (1.5 * t - 2.5) * t * t + 1.0
+    } else if t < 2.0 {
+        ((-0.5 * t + 2.5) * t - 4.0) * t + 2.0
+    } else {
+        0.0
+    }
+}
+
+/// Paints a linear gradient at the given angle in degrees into the given region of `inner`, see `Image.fill_gradient`.
+fn fill_gradient_with(
+    inner: &mut RilImage,
+    (x1, y1, x2, y2): Region,
+    start: ril::Rgba,
+    end: ril::Rgba,
+    angle: f64,
+) {
+    let mode = Mode::of(inner);
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let project = |x: f64, y: f64| x.mul_add(cos, y * sin);
+
+    // Pixels are positioned by their index, so the outermost pixels land exactly on the stops.
+    let (w, h) = (f64::from(x2 - x1 - 1), f64::from(y2 - y1 - 1));
+    let corners = [
+        project(0.0, 0.0),
+        project(w, 0.0),
+        project(0.0, h),
+        project(w, h),
+    ];
+    let min = corners.iter().copied().fold(f64::INFINITY, f64::min);
+    let span = corners.iter().copied().fold(f64::NEG_INFINITY, f64::max) - min;
+
+    let mix = |a: u8, b: u8, t: f64| {
+        (f64::from(b) - f64::from(a))
+            .mul_add(t, f64::from(a))
+            .round() as u8
+    };
+    let width = inner.width() as usize;
+    let (begin, finish) = (y1 as usize * width, y2 as usize * width);
+
+    for_each_row(&mut inner.data[begin..finish], width, |dy, row| {
+        for (dx, pixel) in row[x1 as usize..x2 as usize].iter_mut().enumerate() {
+            let t = if span > f64::EPSILON {
+                ((project(dx as f64, dy as f64) - min) / span).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+
+            *pixel = mode.convert_pixel(Dynamic::Rgba(ril::Rgba {
+                r: mix(start.r, end.r, t),
+                g: mix(start.g, end.g, t),
+                b: mix(start.b, end.b, t),
+                a: mix(start.a, end.a, t),
+            }));
+        }
+    });
+}
+
+/// Returns the value of the given color channel of `pixel`, where `L` pixels only have one.
+fn color_channel(pixel: Dynamic, channel: usize) -> u8 {
+    match pixel {
+        Dynamic::BitPixel(ril::BitPixel(value)) => u8::from(value) * 255,
+        Dynamic::L(ril::L(value)) => value,
+        Dynamic::Rgb(ril::Rgb { r, g, b }) | Dynamic::Rgba(ril::Rgba { r, g, b, .. }) => {
+            [r, g, b][channel]
+        }
+    }
+}
+
+fn set_color_channel(pixel: &mut Dynamic, channel: usize, value: u8) {
+    match pixel {
+        Dynamic::BitPixel(ril::BitPixel(bit)) => *bit = value > 127,
+        Dynamic::L(ril::L(l)) => *l = value,
+        Dynamic::Rgb(ril::Rgb { r, g, b }) | Dynamic::Rgba(ril::Rgba { r, g, b, .. }) => {
+            *[r, g, b][channel] = value
+        }
+    }
+}
+
+/// Ensures `mask` has the given dimensions, cropping a larger mask to its upper-left corner if `crop` is set.
+fn fit_mask(mask: RilImage, (width, height): Xy, crop: bool) -> PyResult<RilImage> {
+    let (mask_width, mask_height) = mask.dimensions();
+    if (mask_width, mask_height) == (width, height) {
+        return Ok(mask);
+    }
+
+    let larger = mask_width >= width && mask_height >= height;
+    if crop && larger {
+        return Ok(copy_region(&mask, (0, 0, width, height)));
+    }
+
+    Err(PyValueError::new_err(format!(
+        "Expected a mask with the dimensions of the image {:?}, got {:?}{}",
+        (width, height),
+        (mask_width, mask_height),
+        if larger {
+            ", pass `crop_mask=True` to crop it"
+        } else {
+            ""
+        }
+    )))
+}
+
+/// Replaces the alpha of every pixel with the corresponding value of `mask`, converting them to RGBA.
+fn mask_alpha_with(inner: &mut RilImage, mask: &RilImage<ril::L>) {
+    let width = inner.width() as usize;
+
+    for_each_row(&mut inner.data, width, |y, row| {
+        let mask = &mask.data[y * width..][..width];
+
+        for (pixel, alpha) in row.iter_mut().zip(mask) {
+            let mut rgba: ril::Rgba = (*pixel).into();
+            rgba.a = alpha.value();
+            *pixel = Dynamic::Rgba(rgba);
+        }
+    });
 }
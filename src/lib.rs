@@ -4,26 +4,44 @@
 #![allow(clippy::borrow_deref_ref)]
 #![allow(clippy::use_self)]
 
+mod asyncio;
+mod atlas;
+mod blur;
+mod color;
 mod draw;
 mod error;
+mod header;
 mod image;
+mod metadata;
+mod options;
+mod parallel;
+mod pipeline;
 mod pixels;
+mod resample;
 mod sequence;
 mod types;
 mod utils;
 mod text;
+mod trace;
 mod workaround;
 
-use draw::{Border, Ellipse, Rectangle};
-use image::Image;
-use pixels::{BitPixel, Pixel, Rgb, Rgba, L};
+use draw::{Border, Ellipse, Line, Polygon, Rectangle, Scene, Shadow, StrokeStyle};
+use image::{Image, ImageView, TileIterator};
+use options::{GifOptions, JpegOptions, PngOptions, WebPOptions};
+use pipeline::Pipeline;
+use pixels::{BitPixel, Colors, Pixel, Rgb, Rgba, L};
 use pyo3::prelude::*;
-use sequence::{Frame, ImageSequence};
-use types::{DisposalMethod, ResizeAlgorithm};
+use sequence::{Frame, ImageSequence, ImageSequenceIterator};
+use types::{
+    DisposalMethod, HorizontalAnchor, ImageFormat, Mode, OverlayMode, ResizeAlgorithm,
+    VerticalAnchor, WrapStyle,
+};
 
 use text::{TextLayout, TextSegment, Font};
 
 type Xy = (u32, u32);
+/// A point that may lie between pixels, rounded to the pixel it is drawn at.
+type Point = (f32, f32);
 
 macro_rules! add_classes {
     ($m:expr, $($class:ty),*) => {{
@@ -33,27 +51,62 @@ macro_rules! add_classes {
     }};
 }
 
+/// Every name added to the module is also appended to its `__all__`, so it must list every public class.
 #[pymodule]
-fn ril(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
+fn ril(py: Python<'_>, m: &PyModule) -> PyResult<()> {
     add_classes!(
         m,
         BitPixel,
         Image,
+        ImageView,
+        TileIterator,
+        Pipeline,
         L,
         Pixel,
+        Colors,
         Rgb,
         Rgba,
         Border,
         Rectangle,
+        Line,
+        Polygon,
+        Scene,
+        Shadow,
+        StrokeStyle,
         DisposalMethod,
+        ImageFormat,
+        Mode,
         ResizeAlgorithm,
+        WrapStyle,
+        OverlayMode,
+        HorizontalAnchor,
+        VerticalAnchor,
         Frame,
         Ellipse,
         ImageSequence,
+        ImageSequenceIterator,
+        PngOptions,
+        JpegOptions,
+        GifOptions,
+        WebPOptions,
         TextSegment,
         TextLayout,
         Font
     );
 
+    m.add("UnknownFormatError", py.get_type::<error::UnknownFormatError>())?;
+    m.add("BufferTooSmallError", py.get_type::<error::BufferTooSmallError>())?;
+    m.add("UnsupportedFormatError", py.get_type::<error::UnsupportedFormatError>())?;
+
+    m.add("PARALLEL", cfg!(feature = "parallel"))?;
+    m.add("WEBP", cfg!(feature = "webp"))?;
+    m.add_function(wrap_pyfunction!(parallel::set_parallel_threshold, m)?)?;
+    m.add_function(wrap_pyfunction!(utils::set_notebook_preview_size, m)?)?;
+    m.add_function(wrap_pyfunction!(utils::contrast_ratio, m)?)?;
+    m.add_function(wrap_pyfunction!(utils::nearest_color, m)?)?;
+    m.add_function(wrap_pyfunction!(trace::set_trace_hook, m)?)?;
+    m.add_function(wrap_pyfunction!(atlas::pack_atlas, m)?)?;
+    m.add_function(wrap_pyfunction!(pipeline::thumbnail, m)?)?;
+
     Ok(())
 }
@@ -0,0 +1,440 @@
+use ril::ImageFormat;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+const PNG_METADATA_CHUNKS: [&[u8; 4]; 6] = [b"tEXt", b"zTXt", b"iTXt", b"eXIf", b"iCCP", b"tIME"];
+
+const JPEG_SOI: [u8; 2] = [0xFF, 0xD8];
+const JPEG_APP0: u8 = 0xE0;
+const JPEG_APP1: u8 = 0xE1;
+const JPEG_APP2: u8 = 0xE2;
+const JPEG_APP13: u8 = 0xED;
+const JPEG_COM: u8 = 0xFE;
+const JPEG_SOS: u8 = 0xDA;
+
+const GIF_EXTENSION: u8 = 0x21;
+pub(crate) const GIF_IMAGE: u8 = 0x2C;
+const GIF_COMMENT: u8 = 0xFE;
+const GIF_APPLICATION: u8 = 0xFF;
+const GIF_NETSCAPE: &[u8] = b"NETSCAPE2.0";
+
+const EXIF_HEADER: &[u8] = b"Exif\0\0";
+const TIFF_SHORT: u32 = 3;
+const TIFF_LONG: u32 = 4;
+const TAG_IMAGE_WIDTH: u32 = 0x0100;
+const TAG_IMAGE_LENGTH: u32 = 0x0101;
+const TAG_ORIENTATION: u32 = 0x0112;
+const TAG_EXIF_IFD: u32 = 0x8769;
+const TAG_PIXEL_X_DIMENSION: u32 = 0xA002;
+const TAG_PIXEL_Y_DIMENSION: u32 = 0xA003;
+
+/// Metadata (EXIF, ICC profiles, text chunks, comments) carried over from decoded data.
+///
+/// Chunks are kept exactly as they were read, including their framing, so they can only be written
+/// back out to the format they were read from.
+#[derive(Clone, Default)]
+pub struct Metadata {
+    format: Option<ImageFormat>,
+    chunks: Vec<Vec<u8>>,
+}
+
+impl Metadata {
+    /// Reads the metadata of the given encoded data. Malformed data is tolerated, in which case
+    /// whatever was read up to that point is kept.
+    pub fn read(format: ImageFormat, bytes: &[u8]) -> Self {
+        let chunks = match format {
+            ImageFormat::Png => read_png(bytes),
+            ImageFormat::Jpeg => read_jpeg(bytes),
+            ImageFormat::Gif => read_gif(bytes),
+            _ => Vec::new(),
+        };
+
+        Self {
+            format: Some(format),
+            chunks,
+        }
+    }
+
+    /// Whether there is no metadata.
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Returns a copy without the PNG `tIME` chunks, which record when the image was last modified.
+    pub fn without_timestamps(&self) -> Self {
+        Self {
+            format: self.format,
+            chunks: self
+                .chunks
+                .iter()
+                .filter(|chunk| {
+                    self.format != Some(ImageFormat::Png) || chunk.get(4..8) != Some(&b"tIME"[..])
+                })
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Returns a copy whose EXIF data describes an image of the given dimensions.
+    ///
+    /// The width and height tags are rewritten, and the embedded thumbnail is unlinked since it
+    /// no longer matches the image. The orientation is kept, see [`Self::without_orientation`].
+    pub fn with_dimensions(&self, width: u32, height: u32) -> Self {
+        self.map_exif(|tiff| {
+            let Some(ifd0) = tiff.read(4, 4) else {
+                return;
+            };
+
+            tiff.set(ifd0, TAG_IMAGE_WIDTH, width);
+            tiff.set(ifd0, TAG_IMAGE_LENGTH, height);
+
+            if let Some(exif) = tiff
+                .find(ifd0, TAG_EXIF_IFD)
+                .and_then(|entry| tiff.read(entry + 8, 4))
+            {
+                tiff.set(exif, TAG_PIXEL_X_DIMENSION, width);
+                tiff.set(exif, TAG_PIXEL_Y_DIMENSION, height);
+            }
+
+            // The offset of the next IFD, which holds the thumbnail, follows the entries.
+            if let Some(count) = tiff.read(ifd0, 2) {
+                tiff.write(ifd0 + 2 + 12 * count, 4, 0);
+            }
+        })
+    }
+
+    /// Returns a copy whose EXIF orientation is reset to upright, for once the pixels themselves
+    /// have been rotated or flipped and the recorded orientation no longer applies to them.
+    pub fn without_orientation(&self) -> Self {
+        self.map_exif(|tiff| {
+            if let Some(ifd0) = tiff.read(4, 4) {
+                tiff.set(ifd0, TAG_ORIENTATION, 1);
+            }
+        })
+    }
+
+    /// Returns a copy with `f` applied to the TIFF data of every EXIF chunk.
+    fn map_exif(&self, f: impl Fn(&mut Tiff)) -> Self {
+        let mut chunks = self.chunks.clone();
+
+        for chunk in &mut chunks {
+            let data = match self.format {
+                Some(ImageFormat::Png) if chunk.get(4..8) == Some(&b"eXIf"[..]) => {
+                    let end = chunk.len() - 4;
+                    8..end
+                }
+                Some(ImageFormat::Jpeg)
+                    if chunk.get(1) == Some(&JPEG_APP1)
+                        && chunk.get(4..10) == Some(EXIF_HEADER) =>
+                {
+                    10..chunk.len()
+                }
+                _ => continue,
+            };
+
+            if let Some(mut tiff) = Tiff::new(&mut chunk[data]) {
+                f(&mut tiff);
+            }
+
+            if self.format == Some(ImageFormat::Png) {
+                let end = chunk.len() - 4;
+                let crc = crc32(&chunk[4..end]);
+                chunk[end..].copy_from_slice(&crc.to_be_bytes());
+            }
+        }
+
+        Self {
+            format: self.format,
+            chunks,
+        }
+    }
+
+    /// Writes the metadata into freshly encoded data. Metadata read from a different format is dropped.
+    pub fn write(&self, format: ImageFormat, encoded: Vec<u8>) -> Vec<u8> {
+        if self.is_empty() || self.format != Some(format) {
+            return encoded;
+        }
+
+        let offset = match format {
+            ImageFormat::Png => png_insert_offset(&encoded),
+            ImageFormat::Jpeg => jpeg_insert_offset(&encoded),
+            ImageFormat::Gif => gif_insert_offset(&encoded),
+            _ => None,
+        };
+
+        match offset {
+            Some(offset) => {
+                let mut out = Vec::with_capacity(
+                    encoded.len() + self.chunks.iter().map(Vec::len).sum::<usize>(),
+                );
+                out.extend_from_slice(&encoded[..offset]);
+                self.chunks.iter().for_each(|chunk| out.extend_from_slice(chunk));
+                out.extend_from_slice(&encoded[offset..]);
+
+                out
+            }
+            None => encoded,
+        }
+    }
+}
+
+/// TIFF data, as embedded in EXIF chunks, edited in place. Offsets are relative to its start.
+struct Tiff<'a> {
+    bytes: &'a mut [u8],
+    big_endian: bool,
+}
+
+impl<'a> Tiff<'a> {
+    fn new(bytes: &'a mut [u8]) -> Option<Self> {
+        let big_endian = match bytes.get(..4)? {
+            b"MM\0*" => true,
+            b"II*\0" => false,
+            _ => return None,
+        };
+
+        Some(Self { bytes, big_endian })
+    }
+
+    /// Reads the 2 or 4 byte integer at the given offset.
+    fn read(&self, offset: usize, size: usize) -> Option<usize> {
+        let bytes = self.bytes.get(offset..offset + size)?;
+
+        Some(if self.big_endian {
+            bytes.iter().fold(0, |n, &b| (n << 8) | usize::from(b))
+        } else {
+            bytes
+                .iter()
+                .rev()
+                .fold(0, |n, &b| (n << 8) | usize::from(b))
+        })
+    }
+
+    /// Writes the 2 or 4 byte integer at the given offset, doing nothing if it is out of bounds.
+    fn write(&mut self, offset: usize, size: usize, value: u32) {
+        let bytes = if self.big_endian {
+            value.to_be_bytes()
+        } else {
+            value.to_le_bytes()
+        };
+        let bytes = if self.big_endian {
+            &bytes[4 - size..]
+        } else {
+            &bytes[..size]
+        };
+
+        if let Some(slot) = self.bytes.get_mut(offset..offset + size) {
+            slot.copy_from_slice(bytes);
+        }
+    }
+
+    /// Returns the offset of the entry with the given tag in the IFD at `ifd`.
+    fn find(&self, ifd: usize, tag: u32) -> Option<usize> {
+        let count = self.read(ifd, 2)?;
+
+        (0..count)
+            .map(|i| ifd + 2 + 12 * i)
+            .take_while(|&entry| entry + 12 <= self.bytes.len())
+            .find(|&entry| self.read(entry, 2) == Some(tag as usize))
+    }
+
+    /// Sets the single integer value of the entry with the given tag, if there is one.
+    /// Short entries are widened to longs when the value doesn't fit.
+    fn set(&mut self, ifd: usize, tag: u32, value: u32) {
+        let Some(entry) = self.find(ifd, tag) else {
+            return;
+        };
+        if self.read(entry + 4, 4) != Some(1) {
+            return;
+        }
+
+        match self.read(entry + 2, 2).map(|kind| kind as u32) {
+            Some(TIFF_SHORT) if value <= u32::from(u16::MAX) => {
+                self.write(entry + 8, 4, 0);
+                self.write(entry + 8, 2, value);
+            }
+            Some(TIFF_SHORT | TIFF_LONG) => {
+                self.write(entry + 2, 2, TIFF_LONG);
+                self.write(entry + 8, 4, value);
+            }
+            _ => (),
+        }
+    }
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0, |crc, &byte| {
+        (0..8).fold(crc ^ u32::from(byte), |crc, _| {
+            (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg())
+        })
+    })
+}
+
+pub(crate) fn be_u32(bytes: &[u8], offset: usize) -> Option<usize> {
+    let bytes = bytes.get(offset..offset + 4)?;
+
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+}
+
+pub(crate) fn be_u16(bytes: &[u8], offset: usize) -> Option<usize> {
+    let bytes = bytes.get(offset..offset + 2)?;
+
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]) as usize)
+}
+
+/// Iterates over the PNG chunks as `(offset, type, total length)`.
+pub(crate) fn png_chunks(bytes: &[u8]) -> impl Iterator<Item = (usize, &[u8], usize)> + '_ {
+    let mut offset = if bytes.starts_with(PNG_SIGNATURE) {
+        PNG_SIGNATURE.len()
+    } else {
+        bytes.len()
+    };
+
+    std::iter::from_fn(move || {
+        let length = be_u32(bytes, offset)?;
+        let kind = bytes.get(offset + 4..offset + 8)?;
+        // Length, type, data and CRC
+        let total = 12 + length;
+        bytes.get(offset..offset + total)?;
+
+        let chunk = (offset, kind, total);
+        offset += total;
+
+        Some(chunk)
+    })
+}
+
+fn read_png(bytes: &[u8]) -> Vec<Vec<u8>> {
+    png_chunks(bytes)
+        .filter(|(_, kind, _)| PNG_METADATA_CHUNKS.iter().any(|k| &k[..] == *kind))
+        .map(|(offset, _, total)| bytes[offset..offset + total].to_vec())
+        .collect()
+}
+
+fn png_insert_offset(bytes: &[u8]) -> Option<usize> {
+    // Right after IHDR, which keeps iCCP ahead of PLTE and IDAT as required.
+    png_chunks(bytes)
+        .find(|(_, kind, _)| *kind == b"IHDR")
+        .map(|(offset, _, total)| offset + total)
+}
+
+/// Iterates over the JPEG marker segments before the scan data as `(offset, marker, total length)`.
+pub(crate) fn jpeg_segments(bytes: &[u8]) -> impl Iterator<Item = (usize, u8, usize)> + '_ {
+    let mut offset = if bytes.starts_with(&JPEG_SOI) {
+        JPEG_SOI.len()
+    } else {
+        bytes.len()
+    };
+
+    std::iter::from_fn(move || {
+        if *bytes.get(offset)? != 0xFF {
+            return None;
+        }
+
+        let marker = *bytes.get(offset + 1)?;
+        if marker == JPEG_SOS {
+            return None;
+        }
+
+        // Marker and length, the length includes itself
+        let total = 2 + be_u16(bytes, offset + 2)?;
+        bytes.get(offset..offset + total)?;
+
+        let segment = (offset, marker, total);
+        offset += total;
+
+        Some(segment)
+    })
+}
+
+fn read_jpeg(bytes: &[u8]) -> Vec<Vec<u8>> {
+    jpeg_segments(bytes)
+        .filter(|(_, marker, _)| {
+            matches!(*marker, JPEG_APP1 | JPEG_APP2 | JPEG_APP13 | JPEG_COM)
+        })
+        .map(|(offset, _, total)| bytes[offset..offset + total].to_vec())
+        .collect()
+}
+
+fn jpeg_insert_offset(bytes: &[u8]) -> Option<usize> {
+    if !bytes.starts_with(&JPEG_SOI) {
+        return None;
+    }
+
+    // After the JFIF header if there is one, since it must directly follow SOI.
+    Some(
+        jpeg_segments(bytes)
+            .next()
+            .filter(|(_, marker, _)| *marker == JPEG_APP0)
+            .map_or(JPEG_SOI.len(), |(offset, _, total)| offset + total),
+    )
+}
+
+/// Returns the offset right after the header, logical screen descriptor and global color table.
+fn gif_insert_offset(bytes: &[u8]) -> Option<usize> {
+    let flags = *bytes.get(10)?;
+    let mut offset = 13;
+
+    if flags & 0x80 != 0 {
+        offset += 3 * (1 << ((flags & 0x07) + 1));
+    }
+
+    (offset <= bytes.len()).then_some(offset)
+}
+
+/// Returns the offset after the data sub-blocks starting at `offset`.
+fn gif_skip_sub_blocks(bytes: &[u8], mut offset: usize) -> Option<usize> {
+    loop {
+        let size = *bytes.get(offset)? as usize;
+        offset += 1 + size;
+
+        if size == 0 {
+            return Some(offset);
+        }
+    }
+}
+
+/// Iterates over the GIF blocks after the global color table as `(offset, introducer, end)`,
+/// stopping at the trailer or at malformed data.
+pub(crate) fn gif_blocks(bytes: &[u8]) -> impl Iterator<Item = (usize, u8, usize)> + '_ {
+    let mut offset = gif_insert_offset(bytes);
+
+    std::iter::from_fn(move || {
+        let start = offset?;
+        let introducer = *bytes.get(start)?;
+
+        let end = match introducer {
+            GIF_EXTENSION => {
+                bytes.get(start + 1)?;
+                gif_skip_sub_blocks(bytes, start + 2).filter(|&end| end <= bytes.len())?
+            }
+            GIF_IMAGE => {
+                let flags = *bytes.get(start + 9)?;
+
+                // Image descriptor, local color table and LZW minimum code size
+                let mut data = start + 10;
+                if flags & 0x80 != 0 {
+                    data += 3 * (1 << ((flags & 0x07) + 1));
+                }
+
+                gif_skip_sub_blocks(bytes, data + 1)?
+            }
+            _ => return None,
+        };
+
+        offset = Some(end);
+        Some((start, introducer, end))
+    })
+}
+
+fn read_gif(bytes: &[u8]) -> Vec<Vec<u8>> {
+    gif_blocks(bytes)
+        .filter(|&(offset, introducer, _)| {
+            introducer == GIF_EXTENSION
+                && match bytes[offset + 1] {
+                    GIF_COMMENT => true,
+                    GIF_APPLICATION => bytes.get(offset + 3..offset + 14) != Some(GIF_NETSCAPE),
+                    _ => false,
+                }
+        })
+        .map(|(offset, _, end)| bytes[offset..end].to_vec())
+        .collect()
+}
@@ -0,0 +1,721 @@
+use pyo3::{exceptions::PyValueError, prelude::*, pyclass::CompareOp, types::PyType};
+use ril::{
+    encodings::png::{AdaptiveFilterType, Compression, FilterType, PngEncoder},
+    Dynamic, Encoder as _, Error as RilError, Frame as RilFrame, Image as RilImage, ImageFormat,
+    ImageSequence as RilImageSequence,
+};
+
+use crate::{
+    error::Error,
+    image::{blend_over, flatten_over},
+    pixels::Pixel,
+    sequence::encode_gif,
+    types::Mode,
+    utils::compare_eq,
+};
+
+fn encoding_error(message: &str) -> Error {
+    Error::Ril(RilError::EncodingError(message.to_string()))
+}
+
+fn ensure_quality(quality: u8) -> PyResult<u8> {
+    if !(1..=100).contains(&quality) {
+        return Err(PyValueError::new_err(format!(
+            "Expected a quality from 1 to 100, got {}",
+            quality
+        )));
+    }
+
+    Ok(quality)
+}
+
+/// Options for encoding PNG images, accepted by the `options` argument of :meth:`Image.encode` and :meth:`Image.save`.
+///
+/// Parameters
+/// ----------
+/// compression: str, default: "default"
+///     How hard to compress the image data, one of `fast`, `default` or `best`.
+/// interlaced: bool, default: False
+///     Whether to write an Adam7 interlaced image.
+///     The PNG encoder can't write interlaced images yet, so this must be False.
+/// filter: str, default: "adaptive"
+///     The filter applied to each row before compression, one of `none`, `sub`, `up`, `average`, `paeth`,
+///     or `adaptive` to pick the best one for every row.
+///
+/// Raises
+/// ------
+/// ValueError
+///     The compression or filter is invalid, or `interlaced` is set.
+#[pyclass]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[pyo3(text_signature = "(compression = 'default', interlaced = False, filter = 'adaptive')")]
+pub struct PngOptions {
+    compression: &'static str,
+    interlaced: bool,
+    filter: &'static str,
+}
+
+#[pymethods]
+impl PngOptions {
+    #[new]
+    #[pyo3(signature = (compression = "default", interlaced = false, filter = "adaptive"))]
+    fn new(compression: &str, interlaced: bool, filter: &str) -> PyResult<Self> {
+        if interlaced {
+            return Err(PyValueError::new_err(
+                "Interlaced PNG encoding is not supported, `interlaced` must be False",
+            ));
+        }
+
+        let compression = match compression.to_lowercase().as_str() {
+            "fast" => "fast",
+            "default" => "default",
+            "best" => "best",
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Invalid compression `{}`, expected one of `fast`, `default` or `best`",
+                    compression
+                )))
+            }
+        };
+
+        let filter = match filter.to_lowercase().as_str() {
+            "none" => "none",
+            "sub" => "sub",
+            "up" => "up",
+            "average" => "average",
+            "paeth" => "paeth",
+            "adaptive" => "adaptive",
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Invalid filter `{}`, expected one of `none`, `sub`, `up`, `average`, `paeth` or `adaptive`",
+                    filter
+                )))
+            }
+        };
+
+        Ok(Self {
+            compression,
+            interlaced,
+            filter,
+        })
+    }
+
+    /// str: How hard the image data is compressed.
+    #[getter]
+    fn get_compression(&self) -> &'static str {
+        self.compression
+    }
+
+    /// bool: Whether the image is interlaced.
+    #[getter]
+    fn get_interlaced(&self) -> bool {
+        self.interlaced
+    }
+
+    /// str: The filter applied to each row before compression.
+    #[getter]
+    fn get_filter(&self) -> &'static str {
+        self.filter
+    }
+
+    /// Returns the options used when encoding with `deterministic=True` and no options are given:
+    /// the default compression level with the `paeth` filter for every row.
+    ///
+    /// Returns
+    /// -------
+    /// :class:`.PngOptions`
+    ///     The pinned options.
+    #[classmethod]
+    #[pyo3(text_signature = "($cls)")]
+    fn deterministic(_: &PyType) -> Self {
+        Self::pinned()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "<PngOptions compression={} interlaced={} filter={}>",
+            self.compression,
+            if self.interlaced { "True" } else { "False" },
+            self.filter
+        )
+    }
+
+    fn __richcmp__(&self, py: Python<'_>, other: &PyAny, op: CompareOp) -> PyObject {
+        compare_eq(py, self, other, op)
+    }
+}
+
+impl PngOptions {
+    fn pinned() -> Self {
+        Self {
+            compression: "default",
+            interlaced: false,
+            filter: "paeth",
+        }
+    }
+
+    fn encoder(&self) -> Result<PngEncoder, Error> {
+        let compression = match self.compression {
+            "fast" => Compression::Fast,
+            "best" => Compression::Best,
+            _ => Compression::Default,
+        };
+
+        let (adaptive_filter, filter) = match self.filter {
+            "none" => (AdaptiveFilterType::NonAdaptive, FilterType::NoFilter),
+            "sub" => (AdaptiveFilterType::NonAdaptive, FilterType::Sub),
+            "up" => (AdaptiveFilterType::NonAdaptive, FilterType::Up),
+            "average" => (AdaptiveFilterType::NonAdaptive, FilterType::Avg),
+            "paeth" => (AdaptiveFilterType::NonAdaptive, FilterType::Paeth),
+            _ => (AdaptiveFilterType::Adaptive, FilterType::Sub),
+        };
+
+        Ok(PngEncoder::new()
+            .with_compression(compression)
+            .with_adaptive_filter(adaptive_filter)
+            .with_filter(filter))
+    }
+}
+
+/// Options for encoding JPEG images, accepted by the `options` argument of :meth:`Image.encode` and :meth:`Image.save`.
+///
+/// JPEG has no alpha channel, so RGBA images are composited over white first, like :meth:`Image.flatten`.
+///
+/// Parameters
+/// ----------
+/// quality: int, default: 90
+///     The quality of the image, from 1 (smallest) to 100 (best).
+/// progressive: bool, default: False
+///     Whether to write a progressive image, which loads in increasingly detailed passes.
+/// subsampling: str, default: "4:2:0"
+///     How much the chroma channels are subsampled, one of `4:4:4` (none), `4:2:2` or `4:2:0`.
+///     This has no effect on grayscale images.
+///
+/// Raises
+/// ------
+/// ValueError
+///     The quality is not from 1 to 100, or the subsampling is invalid.
+#[pyclass]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[pyo3(text_signature = "(quality = 90, progressive = False, subsampling = '4:2:0')")]
+pub struct JpegOptions {
+    quality: u8,
+    progressive: bool,
+    subsampling: &'static str,
+}
+
+#[pymethods]
+impl JpegOptions {
+    #[new]
+    #[pyo3(signature = (quality = 90, progressive = false, subsampling = "4:2:0"))]
+    fn new(quality: u8, progressive: bool, subsampling: &str) -> PyResult<Self> {
+        let subsampling = match subsampling.replace(':', "").as_str() {
+            "444" => "4:4:4",
+            "422" => "4:2:2",
+            "420" => "4:2:0",
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Invalid subsampling `{}`, expected one of `4:4:4`, `4:2:2` or `4:2:0`",
+                    subsampling
+                )))
+            }
+        };
+
+        Ok(Self {
+            quality: ensure_quality(quality)?,
+            progressive,
+            subsampling,
+        })
+    }
+
+    /// int: The quality of the image, from 1 to 100.
+    #[getter]
+    fn get_quality(&self) -> u8 {
+        self.quality
+    }
+
+    /// bool: Whether the image is progressive.
+    #[getter]
+    fn get_progressive(&self) -> bool {
+        self.progressive
+    }
+
+    /// str: How much the chroma channels are subsampled.
+    #[getter]
+    fn get_subsampling(&self) -> &'static str {
+        self.subsampling
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "<JpegOptions quality={} progressive={} subsampling={}>",
+            self.quality,
+            if self.progressive { "True" } else { "False" },
+            self.subsampling
+        )
+    }
+
+    fn __richcmp__(&self, py: Python<'_>, other: &PyAny, op: CompareOp) -> PyObject {
+        compare_eq(py, self, other, op)
+    }
+}
+
+impl JpegOptions {
+    fn encode(&self, image: &RilImage) -> Result<Vec<u8>, Error> {
+        let (width, height) = match (u16::try_from(image.width()), u16::try_from(image.height())) {
+            (Ok(width), Ok(height)) => (width, height),
+            _ => {
+                return Err(encoding_error(
+                    "JPEG images can be at most 65535 pixels wide and high",
+                ))
+            }
+        };
+
+        let flattened;
+        let image = if Mode::of(image) == Mode::Rgba {
+            flattened = flatten_over(
+                image,
+                ril::Rgb {
+                    r: 255,
+                    g: 255,
+                    b: 255,
+                },
+            );
+            &flattened
+        } else {
+            image
+        };
+
+        let (data, color_type) = match Mode::of(image) {
+            Mode::BitPixel | Mode::L => (
+                image
+                    .data
+                    .iter()
+                    .map(|&pixel| ril::L::from(pixel).0)
+                    .collect::<Vec<_>>(),
+                jpeg_encoder::ColorType::Luma,
+            ),
+            Mode::Rgb | Mode::Rgba => (
+                image
+                    .data
+                    .iter()
+                    .flat_map(|&pixel| {
+                        let ril::Rgb { r, g, b } = ril::Rgb::from(pixel);
+                        [r, g, b]
+                    })
+                    .collect::<Vec<_>>(),
+                jpeg_encoder::ColorType::Rgb,
+            ),
+        };
+
+        let mut buf = Vec::new();
+        let mut encoder = jpeg_encoder::Encoder::new(&mut buf, self.quality);
+        encoder.set_progressive(self.progressive);
+        encoder.set_sampling_factor(match self.subsampling {
+            "4:4:4" => jpeg_encoder::SamplingFactor::R_4_4_4,
+            "4:2:2" => jpeg_encoder::SamplingFactor::R_4_2_2,
+            _ => jpeg_encoder::SamplingFactor::R_4_2_0,
+        });
+        encoder
+            .encode(&data, width, height, color_type)
+            .map_err(|err| encoding_error(&err.to_string()))?;
+
+        Ok(buf)
+    }
+}
+
+/// Options for encoding GIF images, accepted by the `options` argument of
+/// :meth:`Image.encode`, :meth:`Image.save`, :meth:`ImageSequence.encode` and :meth:`ImageSequence.save`.
+///
+/// Parameters
+/// ----------
+/// quality: int, default: 70
+///     How closely the palette of each frame is fit to its colors, from 1 (fastest) to 100 (best).
+///     The default matches the quantization used when no options are given.
+/// dither: bool, default: False
+///     Whether to diffuse the quantization error across neighbouring pixels, which smooths out banding in gradients.
+/// loop_count: Optional[int], default: None
+///     How many times the sequence plays, where 0 loops forever.
+///     Defaults to `None`, which keeps the loop count of the sequence. A single :class:`.Image` is not looped.
+/// alpha_threshold: int, default: 128
+///     GIF only supports fully transparent pixels, so pixels with an alpha below this become transparent and
+///     the rest become opaque. 0 makes every pixel opaque.
+/// matte: Optional[:class:`.Pixel`], default: None
+///     The color partially transparent pixels that become opaque are composited over, such as the background
+///     the GIF is shown on, which avoids halos around soft edges. Its alpha is ignored.
+///     Defaults to `None`, which keeps the color of those pixels as is.
+///
+/// Raises
+/// ------
+/// ValueError
+///     The quality is not from 1 to 100.
+#[pyclass]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[pyo3(
+    text_signature = "(quality = 70, dither = False, loop_count = None, alpha_threshold = 128, matte = None)"
+)]
+pub struct GifOptions {
+    quality: u8,
+    dither: bool,
+    loop_count: Option<u16>,
+    alpha_threshold: u8,
+    matte: Option<ril::Rgb>,
+}
+
+#[pymethods]
+impl GifOptions {
+    #[new]
+    #[pyo3(signature = (quality = 70, dither = false, loop_count = None, alpha_threshold = 128, matte = None))]
+    fn new(
+        quality: u8,
+        dither: bool,
+        loop_count: Option<u16>,
+        alpha_threshold: u8,
+        matte: Option<Pixel>,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            quality: ensure_quality(quality)?,
+            dither,
+            loop_count,
+            alpha_threshold,
+            matte: matte.map(|matte| ril::Rgb::from(matte.inner)),
+        })
+    }
+
+    /// int: How closely the palette of each frame is fit to its colors, from 1 to 100.
+    #[getter]
+    fn get_quality(&self) -> u8 {
+        self.quality
+    }
+
+    /// bool: Whether the quantization error is diffused across neighbouring pixels.
+    #[getter]
+    fn get_dither(&self) -> bool {
+        self.dither
+    }
+
+    /// Optional[int]: How many times the sequence plays, where 0 loops forever, or None to keep the loop count of the sequence.
+    #[getter]
+    fn get_loop_count(&self) -> Option<u16> {
+        self.loop_count
+    }
+
+    /// int: The alpha below which pixels become transparent.
+    #[getter]
+    fn get_alpha_threshold(&self) -> u8 {
+        self.alpha_threshold
+    }
+
+    /// Optional[:class:`.Pixel`]: The RGB color partially transparent pixels are composited over, if any.
+    #[getter]
+    fn get_matte(&self) -> Option<Pixel> {
+        self.matte.map(|matte| Pixel::from(Dynamic::Rgb(matte)))
+    }
+
+    /// Returns the options used when encoding with `deterministic=True` and no options are given,
+    /// which are the defaults. Quantization never depends on the amount of workers, so any GIF options are deterministic.
+    ///
+    /// Returns
+    /// -------
+    /// :class:`.GifOptions`
+    ///     The pinned options.
+    #[classmethod]
+    #[pyo3(text_signature = "($cls)")]
+    fn deterministic(_: &PyType) -> Self {
+        Self::default()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "<GifOptions quality={} dither={} loop_count={} alpha_threshold={} matte={}>",
+            self.quality,
+            if self.dither { "True" } else { "False" },
+            self.loop_count
+                .map_or_else(|| "None".to_string(), |count| count.to_string()),
+            self.alpha_threshold,
+            self.matte.map_or_else(
+                || "None".to_string(),
+                |ril::Rgb { r, g, b }| format!("({}, {}, {})", r, g, b)
+            )
+        )
+    }
+
+    fn __richcmp__(&self, py: Python<'_>, other: &PyAny, op: CompareOp) -> PyObject {
+        compare_eq(py, self, other, op)
+    }
+}
+
+impl Default for GifOptions {
+    fn default() -> Self {
+        Self {
+            quality: 70,
+            dither: false,
+            loop_count: None,
+            alpha_threshold: 128,
+            matte: None,
+        }
+    }
+}
+
+impl GifOptions {
+    /// The NeuQuant sampling factor frames are quantized with, from 1 (slowest, best) to 30.
+    pub fn speed(&self) -> i32 {
+        30 - (i32::from(self.quality) - 1) * 29 / 99
+    }
+
+    pub fn dither(&self) -> bool {
+        self.dither
+    }
+
+    /// Makes the pixels of the RGBA data either fully transparent or opaque, following `alpha_threshold` and `matte`.
+    ///
+    /// Fully transparent pixels keep their color, since the quantizer picks the transparent color from them.
+    pub fn apply_alpha(&self, rgba: &mut [u8]) {
+        for pixel in rgba.chunks_exact_mut(4) {
+            let alpha = pixel[3];
+
+            if alpha < self.alpha_threshold {
+                pixel[3] = 0;
+                continue;
+            }
+
+            if let Some(ril::Rgb { r, g, b }) = self.matte {
+                for (value, matte) in pixel.iter_mut().zip([r, g, b]) {
+                    *value = blend_over(*value, matte, alpha);
+                }
+            }
+            pixel[3] = 0xFF;
+        }
+    }
+
+    /// Returns how often the sequence repeats, falling back to the loop count of the sequence.
+    /// Returns `None` when neither is given, in which case the sequence is played once.
+    pub fn repeat(&self, fallback: Option<ril::LoopCount>) -> Option<gif::Repeat> {
+        match (self.loop_count, fallback) {
+            (Some(0), _) => Some(gif::Repeat::Infinite),
+            (Some(count), _) => Some(gif::Repeat::Finite(count)),
+            (None, Some(ril::LoopCount::Infinite)) => Some(gif::Repeat::Infinite),
+            (None, Some(ril::LoopCount::Exactly(count))) => Some(gif::Repeat::Finite(
+                u16::try_from(count).unwrap_or(u16::MAX),
+            )),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Options for encoding WebP images, accepted by the `options` argument of :meth:`Image.encode` and :meth:`Image.save`.
+///
+/// Encoding WebP requires ril to be built with the `webp` feature, see :data:`WEBP`.
+///
+/// Parameters
+/// ----------
+/// quality: int, default: 80
+///     The quality of the image, from 1 (smallest) to 100 (best). For lossless images, this is how hard it is compressed.
+/// lossless: bool, default: False
+///     Whether to encode the image without any loss.
+///
+/// Raises
+/// ------
+/// ValueError
+///     The quality is not from 1 to 100.
+#[pyclass]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[pyo3(text_signature = "(quality = 80, lossless = False)")]
+pub struct WebPOptions {
+    quality: u8,
+    lossless: bool,
+}
+
+#[pymethods]
+impl WebPOptions {
+    #[new]
+    #[pyo3(signature = (quality = 80, lossless = false))]
+    fn new(quality: u8, lossless: bool) -> PyResult<Self> {
+        Ok(Self {
+            quality: ensure_quality(quality)?,
+            lossless,
+        })
+    }
+
+    /// int: The quality of the image, from 1 to 100.
+    #[getter]
+    fn get_quality(&self) -> u8 {
+        self.quality
+    }
+
+    /// bool: Whether the image is encoded without any loss.
+    #[getter]
+    fn get_lossless(&self) -> bool {
+        self.lossless
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "<WebPOptions quality={} lossless={}>",
+            self.quality,
+            if self.lossless { "True" } else { "False" }
+        )
+    }
+
+    fn __richcmp__(&self, py: Python<'_>, other: &PyAny, op: CompareOp) -> PyObject {
+        compare_eq(py, self, other, op)
+    }
+}
+
+impl WebPOptions {
+    /// Lossy options encoding at the given quality, which must be from 1 to 100.
+    pub fn lossy(quality: u8) -> Self {
+        Self {
+            quality,
+            lossless: false,
+        }
+    }
+
+    #[cfg(feature = "webp")]
+    pub fn encode(&self, image: &RilImage) -> Result<Vec<u8>, Error> {
+        use ril::encodings::webp::{WebPEncoderOptions, WebPStaticEncoder};
+
+        let mut buf = Vec::new();
+        WebPStaticEncoder::new()
+            .with_options(WebPEncoderOptions {
+                quality: f32::from(self.quality),
+                lossless: self.lossless,
+            })
+            .encode(image, &mut buf)?;
+
+        Ok(buf)
+    }
+
+    #[cfg(not(feature = "webp"))]
+    pub fn encode(&self, _image: &RilImage) -> Result<Vec<u8>, Error> {
+        Err(encoding_error(
+            "Encoding WebP requires ril to be built with the `webp` feature",
+        ))
+    }
+}
+
+/// Any of the encoding options classes, accepted by the `options` argument of the encoding methods.
+#[derive(Clone, FromPyObject)]
+pub enum EncodeOptions {
+    Png(PngOptions),
+    Jpeg(JpegOptions),
+    Gif(GifOptions),
+    WebP(WebPOptions),
+}
+
+impl EncodeOptions {
+    /// The format these options encode to.
+    pub fn format(&self) -> ImageFormat {
+        match self {
+            Self::Png(_) => ImageFormat::Png,
+            Self::Jpeg(_) => ImageFormat::Jpeg,
+            Self::Gif(_) => ImageFormat::Gif,
+            Self::WebP(_) => ImageFormat::WebP,
+        }
+    }
+
+    /// Resolves the format to encode to from an explicit `encoding`, these options, or `fallback` if neither is given.
+    pub fn resolve_format(
+        encoding: Option<&str>,
+        options: Option<&Self>,
+        fallback: impl FnOnce() -> Result<ImageFormat, Error>,
+    ) -> Result<ImageFormat, Error> {
+        let format = match (encoding, options) {
+            (Some(encoding), _) => ImageFormat::from_extension(encoding)?,
+            (None, Some(options)) => return Ok(options.format()),
+            (None, None) => fallback()?,
+        };
+
+        match options {
+            Some(options) if options.format() != format => Err(Error::OptionsMismatch(
+                options.format().to_string(),
+                format.to_string(),
+            )),
+            _ => Ok(format),
+        }
+    }
+
+    /// Resolves the options to encode with when the output must be reproducible.
+    /// Only PNG and GIF are supported, and PNG options must use a fixed filter since
+    /// the adaptive filter heuristic may change between encoder versions.
+    pub fn pin(format: ImageFormat, options: Option<&Self>) -> Result<Self, Error> {
+        match (format, options) {
+            (ImageFormat::Png, None) => Ok(Self::Png(PngOptions::pinned())),
+            (ImageFormat::Gif, None) => Ok(Self::Gif(GifOptions::default())),
+            (_, Some(Self::Png(options))) if options.filter == "adaptive" => {
+                Err(Error::Nondeterministic(
+                    "PNG options must use a fixed filter rather than `adaptive`".to_string(),
+                ))
+            }
+            (_, Some(options @ (Self::Png(_) | Self::Gif(_)))) => Ok(options.clone()),
+            _ => Err(Error::Nondeterministic(format!(
+                "only PNG and GIF are supported, got {}",
+                format
+            ))),
+        }
+    }
+
+    /// Resolves the options to encode to `format` at the given quality.
+    /// Returns `None` for formats that are encoded without options, such as PNG.
+    pub fn with_quality(format: ImageFormat, quality: u8) -> PyResult<Option<Self>> {
+        let quality = ensure_quality(quality)?;
+
+        Ok(match format {
+            ImageFormat::Jpeg => Some(Self::Jpeg(JpegOptions {
+                quality,
+                progressive: false,
+                subsampling: "4:2:0",
+            })),
+            ImageFormat::Gif => Some(Self::Gif(GifOptions::default())),
+            ImageFormat::WebP => Some(Self::WebP(WebPOptions::lossy(quality))),
+            _ => None,
+        })
+    }
+
+    pub fn encode_image(&self, image: &RilImage) -> Result<Vec<u8>, Error> {
+        match self {
+            Self::Png(options) => {
+                let mut buf = Vec::new();
+                options.encoder()?.encode(image, &mut buf)?;
+
+                Ok(buf)
+            }
+            Self::Jpeg(options) => options.encode(image),
+            Self::Gif(options) => {
+                // A still image is a single frame with no delay, which isn't looped unless `loop_count` is given.
+                let sequence =
+                    RilImageSequence::from_frames(vec![RilFrame::from_image(image.clone())]);
+
+                encode_gif(&sequence, None, None, options)
+            }
+            Self::WebP(options) => options.encode(image),
+        }
+    }
+
+    /// Encodes a sequence, where JPEG and WebP can only encode sequences of a single frame.
+    pub fn encode_sequence(
+        &self,
+        sequence: &RilImageSequence<Dynamic>,
+        workers: Option<usize>,
+    ) -> Result<Vec<u8>, Error> {
+        match self {
+            Self::Png(options) => {
+                let mut buf = Vec::new();
+                options.encoder()?.encode_sequence(sequence, &mut buf)?;
+
+                Ok(buf)
+            }
+            Self::Gif(options) => {
+                encode_gif(sequence, Some(sequence.loop_count()), workers, options)
+            }
+            Self::Jpeg(_) | Self::WebP(_) if sequence.len() > 1 => Err(encoding_error(
+                "Only PngOptions and GifOptions can encode a sequence of more than one frame",
+            )),
+            Self::Jpeg(_) | Self::WebP(_) => {
+                let frame = sequence.iter().next().ok_or(RilError::EmptyImageError)?;
+
+                self.encode_image(frame.image())
+            }
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "parallel")]
+use std::sync::{Arc, Mutex, PoisonError};
+
+use pyo3::prelude::*;
+
+/// Images with at least this many pixels are processed in parallel when the `parallel` feature is enabled.
+static PARALLEL_THRESHOLD: AtomicUsize = AtomicUsize::new(1 << 18);
+
+/// Sets the minimum amount of pixels an image must have before pixel operations are split across threads.
+///
+/// This has no effect unless ril was built with the `parallel` feature, see :data:`PARALLEL`.
+///
+/// Parameters
+/// ----------
+/// pixels: int
+///     The threshold, in pixels.
+#[pyfunction]
+#[pyo3(text_signature = "(pixels)")]
+pub fn set_parallel_threshold(pixels: usize) {
+    PARALLEL_THRESHOLD.store(pixels, Ordering::Relaxed);
+}
+
+/// Calls `f` with the index and contents of each row of `data`, which is shaped according to `width`.
+///
+/// Rows are distributed across the rayon thread pool when the `parallel` feature is enabled and
+/// `data` is above the parallel threshold. The result is identical either way, since each row is
+/// only ever touched by a single call.
+pub fn for_each_row<P, F>(data: &mut [P], width: usize, f: F)
+where
+    P: Send,
+    F: Fn(usize, &mut [P]) + Send + Sync,
+{
+    #[cfg(feature = "parallel")]
+    if data.len() >= PARALLEL_THRESHOLD.load(Ordering::Relaxed) {
+        use rayon::prelude::*;
+
+        data.par_chunks_mut(width)
+            .enumerate()
+            .for_each(|(y, row)| f(y, row));
+
+        return;
+    }
+
+    data.chunks_mut(width)
+        .enumerate()
+        .for_each(|(y, row)| f(y, row));
+}
+
+/// Thread pools built for a given amount of workers, kept so they are only built once per worker count.
+#[cfg(feature = "parallel")]
+static POOLS: Mutex<Vec<(usize, Arc<rayon::ThreadPool>)>> = Mutex::new(Vec::new());
+
+/// Returns the thread pool of `workers` threads, which is the global pool if it has as many threads.
+/// Returns `None` if the pool can't be built.
+#[cfg(feature = "parallel")]
+fn pool(workers: usize) -> Option<Arc<rayon::ThreadPool>> {
+    let mut pools = POOLS.lock().unwrap_or_else(PoisonError::into_inner);
+
+    if let Some((_, pool)) = pools.iter().find(|(count, _)| *count == workers) {
+        return Some(Arc::clone(pool));
+    }
+
+    let pool = Arc::new(
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(workers)
+            .build()
+            .ok()?,
+    );
+    pools.push((workers, Arc::clone(&pool)));
+
+    Some(pool)
+}
+
+/// Whether `map_ordered` spreads items across threads for the given amount of `workers`.
+pub fn maps_in_parallel(workers: Option<usize>) -> bool {
+    cfg!(feature = "parallel") && workers != Some(1)
+}
+
+/// Maps every item with `f`, returning the results in the same order as `items`.
+///
+/// Items are distributed across a rayon thread pool of `workers` threads when the `parallel` feature
+/// is enabled, or the global pool if `workers` is `None` or matches its size. Pools of other sizes are built once
+/// and kept. Each item is mapped independently, so the result is identical to mapping them serially,
+/// which is also the fallback if the pool can't be built.
+pub fn map_ordered<T, R, F>(items: &[T], workers: Option<usize>, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Send + Sync,
+{
+    #[cfg(feature = "parallel")]
+    if maps_in_parallel(workers) {
+        use rayon::prelude::*;
+
+        let map = || items.par_iter().map(&f).collect();
+        match workers {
+            Some(workers) if workers != rayon::current_num_threads() => {
+                if let Some(pool) = pool(workers) {
+                    return pool.install(map);
+                }
+            }
+            _ => return map(),
+        }
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    let _ = workers;
+
+    items.iter().map(f).collect()
+}
@@ -0,0 +1,664 @@
+use jpeg_decoder::{Decoder as JpegDecoder, PixelFormat};
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyBytes};
+use ril::{Dynamic, Image as RilImage, ImageFormat};
+
+use crate::{
+    error::Error,
+    image::{decode, decoding_error, resized, Image},
+    options::EncodeOptions,
+    trace,
+    types::{Mode, ModeArg, ResizeAlgorithm},
+    utils::{ensure_box, ensure_dimensions, ensure_open, infer_format},
+    Xy,
+};
+
+type Region = (u32, u32, u32, u32);
+
+/// Decodes an image, shrinks it to fit within a square of `max_size` pixels, and encodes it again,
+/// such as for serving thumbnails.
+///
+/// This runs entirely without the GIL and without creating an intermediate :class:`.Image`.
+/// JPEG images are decoded at a reduced scale of up to 1/8 where possible, so large photos are never fully decompressed.
+/// The image keeps its aspect ratio and is resized with :attr:`.ResizeAlgorithm.Lanczos3`.
+/// Images already fitting within `max_size` are not enlarged, and metadata is not carried over.
+///
+/// Parameters
+/// ----------
+/// data: bytes
+///     The encoded image, its format is inferred.
+/// max_size: int
+///     The maximum width and height of the thumbnail.
+/// format: str, default: 'jpeg'
+///     The encoding of the thumbnail.
+/// quality: int, default: 80
+///     The quality of JPEG and WebP thumbnails, from 1 (smallest) to 100 (best). Other encodings ignore it.
+///
+/// Returns
+/// -------
+/// bytes
+///     The encoded thumbnail.
+///
+/// Raises
+/// ------
+/// ValueError
+///     `max_size` is zero, the quality is not from 1 to 100, or the encoding is invalid.
+/// UnknownFormatError
+///     The format of `data` can't be inferred.
+/// RuntimeError
+///     Failed to decode or encode the image.
+///
+/// Examples
+/// --------
+///
+/// .. code-block:: python3
+///
+///     small = ril.thumbnail(request.body, 256, format='webp', quality=75)
+#[pyfunction]
+#[pyo3(signature = (data, max_size, format = "jpeg", quality = 80))]
+#[pyo3(text_signature = "(data, max_size, format = 'jpeg', quality = 80)")]
+pub fn thumbnail<'a>(
+    py: Python<'a>,
+    data: &[u8],
+    max_size: u32,
+    format: &str,
+    quality: u8,
+) -> PyResult<&'a PyBytes> {
+    if max_size == 0 {
+        return Err(PyValueError::new_err("Expected a max_size of at least 1"));
+    }
+
+    let encoding = ImageFormat::from_extension(format).map_err(Error::from)?;
+    let options = EncodeOptions::with_quality(encoding, quality)?;
+    let source = infer_format(data)?;
+
+    let buf = py.allow_threads(|| -> Result<Vec<u8>, Error> {
+        let (mut image, (width, height)) = match source {
+            ImageFormat::Jpeg => decode_jpeg_scaled(data, max_size)?,
+            _ => {
+                let image = decode(source, data)?;
+                let target = fit_within(image.dimensions(), max_size);
+
+                (image, target)
+            }
+        };
+
+        if image.dimensions() != (width, height) {
+            let span = trace::span("resize", Some(image.dimensions()));
+            image = resized(&image, (width, height), ResizeAlgorithm::Lanczos3);
+            span.end((width, height));
+        }
+
+        let _span = trace::span("encode", Some((width, height)));
+        match &options {
+            Some(options) => options.encode_image(&image),
+            None => {
+                let mut buf = Vec::new();
+                image.encode(encoding, &mut buf)?;
+
+                Ok(buf)
+            }
+        }
+    })?;
+
+    Ok(PyBytes::new(py, &buf))
+}
+
+/// Decodes a JPEG image at the smallest DCT scale that is still at least as large as its thumbnail,
+/// and returns it along with the dimensions of the thumbnail.
+///
+/// Images with pixel formats that can't be scaled are fully decoded instead.
+fn decode_jpeg_scaled(bytes: &[u8], max_size: u32) -> Result<(RilImage, Xy), Error> {
+    let mut decoder = JpegDecoder::new(bytes);
+    decoder.read_info().map_err(decoding_error)?;
+
+    let info = match decoder.info() {
+        Some(info) if matches!(info.pixel_format, PixelFormat::L8 | PixelFormat::RGB24) => info,
+        _ => {
+            let image = decode(ImageFormat::Jpeg, bytes)?;
+            let target = fit_within(image.dimensions(), max_size);
+
+            return Ok((image, target));
+        }
+    };
+
+    let span = trace::span("decode", None);
+    let (width, height) = fit_within((u32::from(info.width), u32::from(info.height)), max_size);
+    // The target never exceeds the original dimensions, which fit into a u16.
+    let (scaled_width, _) = decoder
+        .scale(width as u16, height as u16)
+        .map_err(decoding_error)?;
+    let data = decoder.decode().map_err(decoding_error)?;
+
+    let pixels: Vec<Dynamic> = match info.pixel_format {
+        PixelFormat::L8 => data.into_iter().map(|l| Dynamic::L(ril::L(l))).collect(),
+        _ => data
+            .chunks_exact(3)
+            .map(|rgb| {
+                Dynamic::Rgb(ril::Rgb {
+                    r: rgb[0],
+                    g: rgb[1],
+                    b: rgb[2],
+                })
+            })
+            .collect(),
+    };
+    let image = RilImage::from_pixels(u32::from(scaled_width), pixels);
+    span.end(image.dimensions());
+
+    Ok((image, (width, height)))
+}
+
+/// Returns the dimensions to shrink an image to so it fits within a square of `max_size` pixels, keeping its aspect ratio.
+fn fit_within((width, height): Xy, max_size: u32) -> Xy {
+    if width <= max_size && height <= max_size {
+        return (width, height);
+    }
+
+    let scale = f64::from(max_size) / f64::from(width.max(height));
+    let fit = |side: u32| ((f64::from(side) * scale).round() as u32).clamp(1, max_size);
+
+    (fit(width), fit(height))
+}
+
+/// A lazy chain of operations on an image, created with :meth:`Image.pipeline`.
+///
+/// Calling the methods of a pipeline only records the operations, which are validated against the dimensions
+/// the image will have by then. :meth:`execute` then runs them all at once without the GIL, after rearranging them
+/// to do less work where that gives exactly the same result:
+///
+/// - Crops are moved before flips, mirrors, rotations and conversions, so those touch fewer pixels.
+/// - A crop after a resize by a whole factor, such as from 400 to 100 pixels, only resizes the part of the image
+///   the crop keeps, plus a margin for the resampling filter.
+/// - Consecutive flips, mirrors and rotations are combined into a single pass, or dropped if they cancel out.
+/// - Repeated conversions to the same mode are only done once.
+///
+/// The pipeline works on the pixels the image had when the pipeline was created, and every method except
+/// :meth:`execute` returns the pipeline itself for chaining.
+///
+/// Examples
+/// --------
+///
+/// .. code-block:: python3
+///
+///     thumbnail = image.pipeline().resize(400, 300, ResizeAlgorithm.Bilinear).crop(100, 50, 300, 250).execute()
+#[pyclass]
+pub struct Pipeline {
+    source: Image,
+    ops: Vec<Op>,
+    dimensions: Xy,
+}
+
+/// An operation as recorded by a [`Pipeline`].
+#[derive(Clone, Copy)]
+enum Op {
+    Resize(Xy, ResizeAlgorithm),
+    Crop(Region),
+    /// Clockwise quarter turns, from 1 to 3.
+    Rotate(u8),
+    Convert(Mode),
+    Flip,
+    Mirror,
+}
+
+/// An operation as executed by a [`Pipeline`], after its recorded operations are rearranged.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Step {
+    Resize(Xy, ResizeAlgorithm),
+    Crop(Region),
+    Convert(Mode),
+    Transform(Transform),
+}
+
+#[pymethods]
+impl Pipeline {
+    /// Records resizing the image to the given dimensions, see :meth:`Image.resize`.
+    ///
+    /// Parameters
+    /// ----------
+    /// width: int
+    ///     The width to resize to.
+    /// height: int
+    ///     The height to resize to.
+    /// algorithm: :class:`.ResizeAlgorithm`
+    ///     The resize algorithm to use.
+    ///
+    /// Returns
+    /// -------
+    /// :class:`.Pipeline`
+    ///     This pipeline, for chaining.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     `width` or `height` is zero.
+    #[pyo3(text_signature = "($self, width, height, algorithm)")]
+    fn resize(
+        mut slf: PyRefMut<'_, Self>,
+        width: u32,
+        height: u32,
+        algorithm: ResizeAlgorithm,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        ensure_dimensions(width, height)?;
+        slf.record(Op::Resize((width, height), algorithm));
+
+        Ok(slf)
+    }
+
+    /// Records cropping the image to the given bounding box, see :meth:`Image.crop`.
+    ///
+    /// Returns
+    /// -------
+    /// :class:`.Pipeline`
+    ///     This pipeline, for chaining.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The bounding box is empty or exceeds the bounds the image will have.
+    #[pyo3(text_signature = "($self, x1, y1, x2, y2)")]
+    fn crop(
+        mut slf: PyRefMut<'_, Self>,
+        x1: u32,
+        y1: u32,
+        x2: u32,
+        y2: u32,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        ensure_box(slf.dimensions, x1, y1, x2, y2)?;
+        slf.record(Op::Crop((x1, y1, x2, y2)));
+
+        Ok(slf)
+    }
+
+    /// Records rotating the image clockwise by the given multiple of 90 degrees.
+    ///
+    /// Parameters
+    /// ----------
+    /// degrees: int
+    ///     The angle to rotate by, which may be negative to rotate counterclockwise.
+    ///
+    /// Returns
+    /// -------
+    /// :class:`.Pipeline`
+    ///     This pipeline, for chaining.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     `degrees` is not a multiple of 90.
+    #[pyo3(text_signature = "($self, degrees)")]
+    fn rotate(mut slf: PyRefMut<'_, Self>, degrees: i64) -> PyResult<PyRefMut<'_, Self>> {
+        if degrees % 90 != 0 {
+            return Err(PyValueError::new_err(format!(
+                "Expected a multiple of 90 degrees, got {}",
+                degrees
+            )));
+        }
+
+        match (degrees / 90).rem_euclid(4) {
+            0 => {}
+            turns => slf.record(Op::Rotate(turns as u8)),
+        }
+
+        Ok(slf)
+    }
+
+    /// Records converting the image to the given mode, see :meth:`Image.convert`.
+    ///
+    /// Parameters
+    /// ----------
+    /// mode: Union[:class:`.Mode`, str]
+    ///     The mode to convert to.
+    ///
+    /// Returns
+    /// -------
+    /// :class:`.Pipeline`
+    ///     This pipeline, for chaining.
+    #[pyo3(text_signature = "($self, mode)")]
+    fn convert(mut slf: PyRefMut<'_, Self>, mode: ModeArg) -> PyRefMut<'_, Self> {
+        slf.record(Op::Convert(mode.0));
+
+        slf
+    }
+
+    /// Records flipping the image vertically, see :meth:`Image.flip`.
+    ///
+    /// Returns
+    /// -------
+    /// :class:`.Pipeline`
+    ///     This pipeline, for chaining.
+    #[pyo3(text_signature = "($self)")]
+    fn flip(mut slf: PyRefMut<'_, Self>) -> PyRefMut<'_, Self> {
+        slf.record(Op::Flip);
+
+        slf
+    }
+
+    /// Records mirroring the image horizontally, see :meth:`Image.mirror`.
+    ///
+    /// Returns
+    /// -------
+    /// :class:`.Pipeline`
+    ///     This pipeline, for chaining.
+    #[pyo3(text_signature = "($self)")]
+    fn mirror(mut slf: PyRefMut<'_, Self>) -> PyRefMut<'_, Self> {
+        slf.record(Op::Mirror);
+
+        slf
+    }
+
+    /// List[tuple]: The recorded operations in order, such as ``('resize', 400, 300, ResizeAlgorithm.Bilinear)``,
+    /// ``('crop', 0, 0, 100, 100)``, ``('rotate', 90)``, ``('convert', Mode.L)``, ``('flip',)`` or ``('mirror',)``.
+    ///
+    /// These are the operations as they were recorded, not as they are executed.
+    #[getter]
+    fn ops(&self, py: Python<'_>) -> Vec<PyObject> {
+        self.ops
+            .iter()
+            .map(|op| match *op {
+                Op::Resize((width, height), algorithm) => {
+                    ("resize", width, height, algorithm).into_py(py)
+                }
+                Op::Crop((x1, y1, x2, y2)) => ("crop", x1, y1, x2, y2).into_py(py),
+                Op::Rotate(turns) => ("rotate", u32::from(turns) * 90).into_py(py),
+                Op::Convert(mode) => ("convert", mode).into_py(py),
+                Op::Flip => ("flip",).into_py(py),
+                Op::Mirror => ("mirror",).into_py(py),
+            })
+            .collect()
+    }
+
+    /// Tuple[int, int]: The dimensions the image will have after the recorded operations.
+    #[getter]
+    fn dimensions(&self) -> Xy {
+        self.dimensions
+    }
+
+    /// Runs the recorded operations and returns the resulting image, leaving the original image untouched.
+    ///
+    /// The pipeline can be executed again, and more operations can still be recorded afterwards.
+    ///
+    /// Returns
+    /// -------
+    /// :class:`.Image`
+    ///     The resulting image, which has the same pixels as if the operations were applied one by one.
+    #[pyo3(text_signature = "($self)")]
+    fn execute(&self, py: Python<'_>) -> Image {
+        let steps = plan(&self.ops, self.source.inner.dimensions());
+        let source = &self.source.inner;
+        let image = py.allow_threads(|| run(RilImage::clone(source), &steps));
+
+        self.source.derived(image)
+    }
+
+    fn __len__(&self) -> usize {
+        self.ops.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "<Pipeline ops={} dimensions=({}, {})>",
+            self.ops.len(),
+            self.dimensions.0,
+            self.dimensions.1
+        )
+    }
+}
+
+impl Pipeline {
+    pub fn new(source: &Image) -> PyResult<Self> {
+        ensure_open!(source);
+
+        Ok(Self {
+            source: source.clone(),
+            ops: Vec::new(),
+            dimensions: source.inner.dimensions(),
+        })
+    }
+
+    fn record(&mut self, op: Op) {
+        self.dimensions = match op {
+            Op::Resize(dimensions, _) => dimensions,
+            Op::Crop((x1, y1, x2, y2)) => (x2 - x1, y2 - y1),
+            Op::Rotate(turns) => Transform::rotation(turns).dimensions(self.dimensions),
+            Op::Convert(_) | Op::Flip | Op::Mirror => self.dimensions,
+        };
+        self.ops.push(op);
+    }
+}
+
+/// Rearranges the recorded operations into the steps to execute on an image of the given dimensions.
+///
+/// Pairs of adjacent steps are rewritten until none can be, where every rewrite gives exactly the same pixels.
+fn plan(ops: &[Op], source: Xy) -> Vec<Step> {
+    let mut steps = ops
+        .iter()
+        .map(|op| match *op {
+            Op::Resize(dimensions, algorithm) => Step::Resize(dimensions, algorithm),
+            Op::Crop(region) => Step::Crop(region),
+            Op::Rotate(turns) => Step::Transform(Transform::rotation(turns)),
+            Op::Convert(mode) => Step::Convert(mode),
+            Op::Flip => Step::Transform(Transform::FLIP),
+            Op::Mirror => Step::Transform(Transform::MIRROR),
+        })
+        .collect::<Vec<_>>();
+
+    let mut i = 0;
+    while i < steps.len() {
+        let input = steps[..i]
+            .iter()
+            .fold(source, |dimensions, step| step.dimensions(dimensions));
+
+        if steps[i] == Step::Transform(Transform::IDENTITY) {
+            steps.remove(i);
+            i = i.saturating_sub(1);
+            continue;
+        }
+
+        let rewritten = match steps.get(i..i + 2) {
+            Some(&[first, second]) => rewrite(first, second, input),
+            _ => None,
+        };
+
+        match rewritten {
+            Some(replacement) => {
+                steps.splice(i..i + 2, replacement);
+                // The replacement may allow rewriting the step before it.
+                i = i.saturating_sub(1);
+            }
+            None => i += 1,
+        }
+    }
+
+    steps
+}
+
+/// Rewrites two adjacent steps applied to an image of the given dimensions, or returns `None` if they can't be.
+fn rewrite(first: Step, second: Step, input: Xy) -> Option<Vec<Step>> {
+    match (first, second) {
+        (Step::Transform(a), Step::Transform(b)) => Some(vec![Step::Transform(a.then(b))]),
+        (Step::Crop((x1, y1, _, _)), Step::Crop((a1, b1, a2, b2))) => {
+            Some(vec![Step::Crop((x1 + a1, y1 + b1, x1 + a2, y1 + b2))])
+        }
+        (Step::Convert(a), Step::Convert(b)) if a == b => Some(vec![first]),
+        (Step::Convert(_), Step::Crop(_)) => Some(vec![second, first]),
+        (Step::Transform(transform), Step::Crop(region)) => Some(vec![
+            Step::Crop(transform.source_region(region, input)),
+            first,
+        ]),
+        (Step::Resize(output, algorithm), Step::Crop(region)) => {
+            crop_before_resize(input, output, algorithm, region)
+        }
+        _ => None,
+    }
+}
+
+/// Splits a resize from `input` to `output` dimensions followed by a crop into a crop of the input, a smaller resize,
+/// and a crop of the remaining margin. Returns `None` unless this gives exactly the same pixels with less work.
+///
+/// Resampling filters only read the pixels near each output pixel, so the resize gives the same pixels if its input is
+/// cropped with a margin around the kept region, as long as every output pixel still samples the same input pixels.
+/// That only holds when both dimensions are shrunk by a whole factor, or kept.
+fn crop_before_resize(
+    (src_width, src_height): Xy,
+    (width, height): Xy,
+    algorithm: ResizeAlgorithm,
+    (x1, y1, x2, y2): Region,
+) -> Option<Vec<Step>> {
+    if src_width % width != 0 || src_height % height != 0 {
+        return None;
+    }
+    let (fx, fy) = (src_width / width, src_height / height);
+
+    // The margin is in output pixels, generously covering the radius of each filter.
+    let margin = match algorithm {
+        ResizeAlgorithm::Nearest
+        | ResizeAlgorithm::Box
+        | ResizeAlgorithm::Bilinear
+        | ResizeAlgorithm::Hamming => 3,
+        ResizeAlgorithm::Bicubic | ResizeAlgorithm::Mitchell => 4,
+        ResizeAlgorithm::Lanczos3 => 5,
+    };
+    let (ox1, oy1) = (x1.saturating_sub(margin), y1.saturating_sub(margin));
+    let (ox2, oy2) = ((x2 + margin).min(width), (y2 + margin).min(height));
+
+    if (ox1, oy1, ox2, oy2) == (0, 0, width, height) {
+        return None;
+    }
+
+    Some(vec![
+        Step::Crop((ox1 * fx, oy1 * fy, ox2 * fx, oy2 * fy)),
+        Step::Resize((ox2 - ox1, oy2 - oy1), algorithm),
+        Step::Crop((x1 - ox1, y1 - oy1, x2 - ox1, y2 - oy1)),
+    ])
+}
+
+impl Step {
+    /// Returns the dimensions of an image of the given dimensions after this step.
+    fn dimensions(self, input: Xy) -> Xy {
+        match self {
+            Self::Resize(dimensions, _) => dimensions,
+            Self::Crop((x1, y1, x2, y2)) => (x2 - x1, y2 - y1),
+            Self::Convert(_) => input,
+            Self::Transform(transform) => transform.dimensions(input),
+        }
+    }
+}
+
+fn run(mut image: RilImage, steps: &[Step]) -> RilImage {
+    for step in steps {
+        match *step {
+            Step::Resize((width, height), algorithm) => {
+                let span = trace::span("resize", Some(image.dimensions()));
+                image = resized(&image, (width, height), algorithm);
+                span.end((width, height));
+            }
+            Step::Crop((x1, y1, x2, y2)) => image.crop(x1, y1, x2, y2),
+            Step::Convert(mode) => image
+                .data
+                .iter_mut()
+                .for_each(|pixel| *pixel = mode.convert_pixel(*pixel)),
+            Step::Transform(transform) => image = transform.apply(&image),
+        }
+    }
+
+    image
+}
+
+/// One of the eight ways to flip and rotate an image by quarter turns.
+///
+/// The pixel at `(x, y)` of the result is taken from the source after reflecting `x` if `reflect_x` and `y` if `reflect_y`,
+/// and then swapping the two coordinates if `transpose`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Transform {
+    transpose: bool,
+    reflect_x: bool,
+    reflect_y: bool,
+}
+
+impl Transform {
+    const IDENTITY: Self = Self::new(false, false, false);
+    const MIRROR: Self = Self::new(false, true, false);
+    const FLIP: Self = Self::new(false, false, true);
+
+    const fn new(transpose: bool, reflect_x: bool, reflect_y: bool) -> Self {
+        Self {
+            transpose,
+            reflect_x,
+            reflect_y,
+        }
+    }
+
+    /// Returns the rotation by the given amount of clockwise quarter turns.
+    pub fn rotation(turns: u8) -> Self {
+        match turns % 4 {
+            0 => Self::IDENTITY,
+            1 => Self::new(true, true, false),
+            2 => Self::new(false, true, true),
+            _ => Self::new(true, false, true),
+        }
+    }
+
+    /// Returns the transform that applies this one, then `next`.
+    fn then(self, next: Self) -> Self {
+        let (x, y) = if next.transpose {
+            (self.reflect_y, self.reflect_x)
+        } else {
+            (self.reflect_x, self.reflect_y)
+        };
+
+        Self::new(
+            self.transpose ^ next.transpose,
+            next.reflect_x ^ x,
+            next.reflect_y ^ y,
+        )
+    }
+
+    /// Returns the dimensions of an image of the given dimensions after this transform.
+    pub fn dimensions(self, (width, height): Xy) -> Xy {
+        if self.transpose {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    /// Maps a region of the transformed image back to the region of the source it is taken from.
+    fn source_region(self, (x1, y1, x2, y2): Region, input: Xy) -> Region {
+        let (width, height) = self.dimensions(input);
+        let (x1, x2) = if self.reflect_x {
+            (width - x2, width - x1)
+        } else {
+            (x1, x2)
+        };
+        let (y1, y2) = if self.reflect_y {
+            (height - y2, height - y1)
+        } else {
+            (y1, y2)
+        };
+
+        if self.transpose {
+            (y1, x1, y2, x2)
+        } else {
+            (x1, y1, x2, y2)
+        }
+    }
+
+    /// Applies this transform to the given image in a single pass.
+    pub fn apply(self, image: &RilImage) -> RilImage {
+        let (src_width, _) = image.dimensions();
+        let (width, height) = self.dimensions(image.dimensions());
+
+        let data = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let x = if self.reflect_x { width - 1 - x } else { x };
+                let y = if self.reflect_y { height - 1 - y } else { y };
+                let (x, y) = if self.transpose { (y, x) } else { (x, y) };
+
+                image.data[(y * src_width + x) as usize]
+            })
+            .collect::<Vec<_>>();
+
+        RilImage::from_pixels(width, data).with_overlay_mode(image.overlay_mode())
+    }
+}
@@ -1,8 +1,10 @@
 use std::fmt::Display;
 
-use pyo3::{prelude::*, pyclass::CompareOp, types::PyType};
+use pyo3::{exceptions::PyValueError, prelude::*, pyclass::CompareOp, types::PyType};
 use ril::Dynamic;
 
+use crate::color::relative_luminance;
+
 /// Represents a single-bit pixel that represents either a pixel that is on or off.
 #[pyclass]
 #[derive(Clone, Eq, PartialEq)]
@@ -78,7 +80,7 @@ impl Pixel {
     /// value: bool
     ///     Whether the pixel is on.
     #[classmethod]
-    #[pyo3(text_signature = "(cls, value)")]
+    #[pyo3(text_signature = "($cls, value)")]
     fn from_bitpixel(_: &PyType, value: bool) -> Self {
         Self {
             inner: Dynamic::BitPixel(ril::BitPixel(value)),
@@ -92,7 +94,7 @@ impl Pixel {
     /// value: int
     ///     The luminance value of the pixel, between 0 and 255.
     #[classmethod]
-    #[pyo3(text_signature = "(cls, value)")]
+    #[pyo3(text_signature = "($cls, value)")]
     fn from_l(_: &PyType, value: u8) -> Self {
         Self {
             inner: Dynamic::L(ril::L(value)),
@@ -110,7 +112,7 @@ impl Pixel {
     /// b: int
     ///     The blue component of the pixel.
     #[classmethod]
-    #[pyo3(text_signature = "(cls, r, g, b)")]
+    #[pyo3(text_signature = "($cls, r, g, b)")]
     fn from_rgb(_: &PyType, r: u8, g: u8, b: u8) -> Self {
         Self {
             inner: Dynamic::Rgb(ril::Rgb { r, g, b }),
@@ -130,13 +132,85 @@ impl Pixel {
     /// a: int
     ///     The alpha component of the pixel.
     #[classmethod]
-    #[pyo3(text_signature = "(cls, r, g, b, a)")]
+    #[pyo3(text_signature = "($cls, r, g, b, a)")]
     fn from_rgba(_: &PyType, r: u8, g: u8, b: u8, a: u8) -> Self {
         Self {
             inner: Dynamic::Rgba(ril::Rgba { r, g, b, a }),
         }
     }
 
+    /// Creates a Pixel from a CSS color name, such as `rebeccapurple`.
+    ///
+    /// Names are case-insensitive, and spaces, underscores and hyphens are ignored, so `Rebecca Purple` also works.
+    /// Every name gives an RGB pixel, except for `transparent` which gives a fully transparent RGBA pixel.
+    /// See :class:`.Colors` for the same colors as constants.
+    ///
+    /// Parameters
+    /// ----------
+    /// name: str
+    ///     The name of the color.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The name is not a CSS color name. The message suggests the closest names, if any.
+    #[classmethod]
+    #[pyo3(text_signature = "($cls, name)")]
+    fn from_name(_: &PyType, name: &str) -> PyResult<Self> {
+        Self::named(name)
+    }
+
+    /// Returns the distance between the colors of this pixel and another pixel.
+    ///
+    /// Both pixels are converted to RGBA first, and their alpha channels are compared like a color channel.
+    ///
+    /// Parameters
+    /// ----------
+    /// other: :class:`.Pixel`
+    ///     The pixel to compare to.
+    /// metric: str, default: "euclidean"
+    ///     The metric to use, either `euclidean` for the straight distance between the channels,
+    ///     or `redmean` for a cheap approximation of perceived distance that weights the channels by the mean red value.
+    ///
+    /// Returns
+    /// -------
+    /// float
+    ///     The distance, which is `0` for identical colors.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The metric is invalid.
+    #[pyo3(signature = (other, metric = "euclidean"))]
+    #[pyo3(text_signature = "($self, other, metric = 'euclidean')")]
+    fn distance(&self, other: &Self, metric: &str) -> PyResult<f64> {
+        let metric = DistanceMetric::from_name(metric)?;
+
+        Ok(metric.distance_squared(self.inner, other.inner).sqrt())
+    }
+
+    /// Returns the relative luminance of this pixel as defined by WCAG 2, such as for checking the contrast of text.
+    ///
+    /// The channels are linearized from sRGB and weighted by how bright they appear, see :func:`.contrast_ratio`.
+    /// A translucent pixel is composited over `background` first.
+    ///
+    /// Parameters
+    /// ----------
+    /// background: Optional[:class:`.Pixel`], default: None
+    ///     The color a translucent pixel is seen against, defaults to white. Its alpha is ignored.
+    ///
+    /// Returns
+    /// -------
+    /// float
+    ///     The relative luminance, from `0` for black to `1` for white.
+    #[pyo3(signature = (background = None))]
+    #[pyo3(text_signature = "($self, background = None)")]
+    fn relative_luminance(&self, background: Option<Self>) -> f64 {
+        let background = background.map_or(WHITE, |background| normalized(background.inner));
+
+        relative_luminance(composite(self.inner, background))
+    }
+
     fn __richcmp__(&self, py: Python<'_>, other: PyObject, op: CompareOp) -> PyObject {
         match op {
             CompareOp::Eq => {
@@ -160,6 +234,26 @@ impl Pixel {
         }
     }
 
+    fn __reduce__(&self, py: Python<'_>) -> PyResult<(PyObject, PyObject)> {
+        let cls = py.get_type::<Self>();
+
+        Ok(match self.inner {
+            Dynamic::BitPixel(v) => (
+                cls.getattr("from_bitpixel")?.into_py(py),
+                (v.value(),).into_py(py),
+            ),
+            Dynamic::L(v) => (cls.getattr("from_l")?.into_py(py), (v.value(),).into_py(py)),
+            Dynamic::Rgb(v) => (
+                cls.getattr("from_rgb")?.into_py(py),
+                (v.r, v.g, v.b).into_py(py),
+            ),
+            Dynamic::Rgba(v) => (
+                cls.getattr("from_rgba")?.into_py(py),
+                (v.r, v.g, v.b, v.a).into_py(py),
+            ),
+        })
+    }
+
     fn __repr__(&self) -> String {
         let out = match self.inner {
             Dynamic::BitPixel(v) => format!("BitPixel({})", v.value()),
@@ -172,6 +266,324 @@ impl Pixel {
     }
 }
 
+impl Pixel {
+    /// Looks up a CSS color name, see :meth:`from_name`.
+    pub fn named(name: &str) -> PyResult<Self> {
+        let key = name
+            .chars()
+            .filter(|c| !matches!(c, ' ' | '_' | '-'))
+            .collect::<String>()
+            .to_lowercase();
+
+        if key == "transparent" {
+            return Ok(Colors::TRANSPARENT);
+        }
+
+        if let Ok(index) = NAMED_COLORS.binary_search_by(|(name, _)| name.cmp(&key.as_str())) {
+            return Ok(Self {
+                inner: Dynamic::Rgb(NAMED_COLORS[index].1),
+            });
+        }
+
+        let mut message = format!("Unknown color name `{}`", name);
+        let suggestions = suggest_names(&key);
+        if let Some((last, rest)) = suggestions.split_last() {
+            message.push_str(", did you mean ");
+            if !rest.is_empty() {
+                message.push_str(&format!("`{}` or ", rest.join("`, `")));
+            }
+            message.push_str(&format!("`{}`?", last));
+        }
+
+        Err(PyValueError::new_err(message))
+    }
+}
+
+/// How the distance between two colors is measured, see `Pixel.distance`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DistanceMetric {
+    Euclidean,
+    Redmean,
+}
+
+impl DistanceMetric {
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        match name.to_lowercase().as_str() {
+            "euclidean" => Ok(Self::Euclidean),
+            "redmean" => Ok(Self::Redmean),
+            _ => Err(PyValueError::new_err(format!(
+                "Invalid metric `{}`, expected one of `euclidean` or `redmean`",
+                name
+            ))),
+        }
+    }
+
+    /// The squared distance between two pixels compared as RGBA, which orders pixels like the distance itself.
+    pub fn distance_squared(self, a: Dynamic, b: Dynamic) -> f64 {
+        let (a, b) = (ril::Rgba::from(a), ril::Rgba::from(b));
+        let delta = |a: u8, b: u8| f64::from(a) - f64::from(b);
+        let (dr, dg, db, da) = (
+            delta(a.r, b.r),
+            delta(a.g, b.g),
+            delta(a.b, b.b),
+            delta(a.a, b.a),
+        );
+
+        match self {
+            Self::Euclidean => dr * dr + dg * dg + db * db + da * da,
+            Self::Redmean => {
+                let mean = (f64::from(a.r) + f64::from(b.r)) / 2.0;
+
+                (2.0 + mean / 256.0) * dr * dr
+                    + 4.0 * dg * dg
+                    + (2.0 + (255.0 - mean) / 256.0) * db * db
+                    + da * da
+            }
+        }
+    }
+}
+
+/// White as normalized RGB channels, the default background translucent colors are composited over.
+pub const WHITE: [f64; 3] = [1.0; 3];
+
+/// Returns the RGB channels of a pixel normalized to between 0 and 1, ignoring its alpha.
+pub fn normalized(pixel: Dynamic) -> [f64; 3] {
+    let ril::Rgb { r, g, b } = pixel.into();
+
+    [r, g, b].map(|value| f64::from(value) / 255.0)
+}
+
+/// Composites a pixel over an opaque background given as normalized RGB channels, see `normalized`.
+pub fn composite(pixel: Dynamic, background: [f64; 3]) -> [f64; 3] {
+    let alpha = f64::from(ril::Rgba::from(pixel).a) / 255.0;
+    let color = normalized(pixel);
+
+    [0, 1, 2].map(|i| color[i].mul_add(alpha, background[i] * (1.0 - alpha)))
+}
+
+/// Returns up to three names closest to `key`, closest first, that are at most a few edits away from it.
+fn suggest_names(key: &str) -> Vec<&'static str> {
+    let max_distance = (key.chars().count() / 3).clamp(1, 3);
+    let mut candidates = NAMED_COLORS
+        .iter()
+        .map(|(name, _)| *name)
+        .chain(std::iter::once("transparent"))
+        .map(|name| (edit_distance(key, name), name))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect::<Vec<_>>();
+    candidates.sort_unstable();
+
+    candidates
+        .into_iter()
+        .take(3)
+        .map(|(_, name)| name)
+        .collect()
+}
+
+/// The Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+
+        for (j, &cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = (diagonal + usize::from(ca != cb))
+                .min(above + 1)
+                .min(row[j] + 1);
+            diagonal = above;
+        }
+    }
+
+    row[b.len()]
+}
+
+/// Defines both the constants of `Colors` and the lookup table of `Pixel::named` from one list, which must be sorted by name.
+macro_rules! named_colors {
+    ($(($constant:ident, $name:literal, $r:literal, $g:literal, $b:literal)),* $(,)?) => {
+        const NAMED_COLORS: &[(&str, ril::Rgb)] = &[
+            $(($name, ril::Rgb { r: $r, g: $g, b: $b })),*
+        ];
+
+        #[pymethods]
+        impl Colors {
+            /// :class:`.Pixel`: A fully transparent RGBA pixel.
+            #[classattr]
+            const TRANSPARENT: Pixel = Pixel {
+                inner: Dynamic::Rgba(ril::Rgba { r: 0, g: 0, b: 0, a: 0 }),
+            };
+
+            $(
+                #[classattr]
+                const $constant: Pixel = Pixel {
+                    inner: Dynamic::Rgb(ril::Rgb { r: $r, g: $g, b: $b }),
+                };
+            )*
+        }
+    };
+}
+
+/// The CSS named colors as constants, such as ``Colors.WHITE`` or ``Colors.REBECCAPURPLE``.
+///
+/// Every constant is an RGB :class:`.Pixel`, except for ``Colors.TRANSPARENT`` which is a fully transparent RGBA pixel.
+/// See :meth:`Pixel.from_name` to look colors up by name.
+#[pyclass]
+pub struct Colors;
+
+named_colors! {
+    (ALICEBLUE, "aliceblue", 0xf0, 0xf8, 0xff),
+    (ANTIQUEWHITE, "antiquewhite", 0xfa, 0xeb, 0xd7),
+    (AQUA, "aqua", 0x00, 0xff, 0xff),
+    (AQUAMARINE, "aquamarine", 0x7f, 0xff, 0xd4),
+    (AZURE, "azure", 0xf0, 0xff, 0xff),
+    (BEIGE, "beige", 0xf5, 0xf5, 0xdc),
+    (BISQUE, "bisque", 0xff, 0xe4, 0xc4),
+    (BLACK, "black", 0x00, 0x00, 0x00),
+    (BLANCHEDALMOND, "blanchedalmond", 0xff, 0xeb, 0xcd),
+    (BLUE, "blue", 0x00, 0x00, 0xff),
+    (BLUEVIOLET, "blueviolet", 0x8a, 0x2b, 0xe2),
+    (BROWN, "brown", 0xa5, 0x2a, 0x2a),
+    (BURLYWOOD, "burlywood", 0xde, 0xb8, 0x87),
+    (CADETBLUE, "cadetblue", 0x5f, 0x9e, 0xa0),
+    (CHARTREUSE, "chartreuse", 0x7f, 0xff, 0x00),
+    (CHOCOLATE, "chocolate", 0xd2, 0x69, 0x1e),
+    (CORAL, "coral", 0xff, 0x7f, 0x50),
+    (CORNFLOWERBLUE, "cornflowerblue", 0x64, 0x95, 0xed),
+    (CORNSILK, "cornsilk", 0xff, 0xf8, 0xdc),
+    (CRIMSON, "crimson", 0xdc, 0x14, 0x3c),
+    (CYAN, "cyan", 0x00, 0xff, 0xff),
+    (DARKBLUE, "darkblue", 0x00, 0x00, 0x8b),
+    (DARKCYAN, "darkcyan", 0x00, 0x8b, 0x8b),
+    (DARKGOLDENROD, "darkgoldenrod", 0xb8, 0x86, 0x0b),
+    (DARKGRAY, "darkgray", 0xa9, 0xa9, 0xa9),
+    (DARKGREEN, "darkgreen", 0x00, 0x64, 0x00),
+    (DARKGREY, "darkgrey", 0xa9, 0xa9, 0xa9),
+    (DARKKHAKI, "darkkhaki", 0xbd, 0xb7, 0x6b),
+    (DARKMAGENTA, "darkmagenta", 0x8b, 0x00, 0x8b),
+    (DARKOLIVEGREEN, "darkolivegreen", 0x55, 0x6b, 0x2f),
+    (DARKORANGE, "darkorange", 0xff, 0x8c, 0x00),
+    (DARKORCHID, "darkorchid", 0x99, 0x32, 0xcc),
+    (DARKRED, "darkred", 0x8b, 0x00, 0x00),
+    (DARKSALMON, "darksalmon", 0xe9, 0x96, 0x7a),
+    (DARKSEAGREEN, "darkseagreen", 0x8f, 0xbc, 0x8f),
+    (DARKSLATEBLUE, "darkslateblue", 0x48, 0x3d, 0x8b),
+    (DARKSLATEGRAY, "darkslategray", 0x2f, 0x4f, 0x4f),
+    (DARKSLATEGREY, "darkslategrey", 0x2f, 0x4f, 0x4f),
+    (DARKTURQUOISE, "darkturquoise", 0x00, 0xce, 0xd1),
+    (DARKVIOLET, "darkviolet", 0x94, 0x00, 0xd3),
+    (DEEPPINK, "deeppink", 0xff, 0x14, 0x93),
+    (DEEPSKYBLUE, "deepskyblue", 0x00, 0xbf, 0xff),
+    (DIMGRAY, "dimgray", 0x69, 0x69, 0x69),
+    (DIMGREY, "dimgrey", 0x69, 0x69, 0x69),
+    (DODGERBLUE, "dodgerblue", 0x1e, 0x90, 0xff),
+    (FIREBRICK, "firebrick", 0xb2, 0x22, 0x22),
+    (FLORALWHITE, "floralwhite", 0xff, 0xfa, 0xf0),
+    (FORESTGREEN, "forestgreen", 0x22, 0x8b, 0x22),
+    (FUCHSIA, "fuchsia", 0xff, 0x00, 0xff),
+    (GAINSBORO, "gainsboro", 0xdc, 0xdc, 0xdc),
+    (GHOSTWHITE, "ghostwhite", 0xf8, 0xf8, 0xff),
+    (GOLD, "gold", 0xff, 0xd7, 0x00),
+    (GOLDENROD, "goldenrod", 0xda, 0xa5, 0x20),
+    (GRAY, "gray", 0x80, 0x80, 0x80),
+    (GREEN, "green", 0x00, 0x80, 0x00),
+    (GREENYELLOW, "greenyellow", 0xad, 0xff, 0x2f),
+    (GREY, "grey", 0x80, 0x80, 0x80),
+    (HONEYDEW, "honeydew", 0xf0, 0xff, 0xf0),
+    (HOTPINK, "hotpink", 0xff, 0x69, 0xb4),
+    (INDIANRED, "indianred", 0xcd, 0x5c, 0x5c),
+    (INDIGO, "indigo", 0x4b, 0x00, 0x82),
+    (IVORY, "ivory", 0xff, 0xff, 0xf0),
+    (KHAKI, "khaki", 0xf0, 0xe6, 0x8c),
+    (LAVENDER, "lavender", 0xe6, 0xe6, 0xfa),
+    (LAVENDERBLUSH, "lavenderblush", 0xff, 0xf0, 0xf5),
+    (LAWNGREEN, "lawngreen", 0x7c, 0xfc, 0x00),
+    (LEMONCHIFFON, "lemonchiffon", 0xff, 0xfa, 0xcd),
+    (LIGHTBLUE, "lightblue", 0xad, 0xd8, 0xe6),
+    (LIGHTCORAL, "lightcoral", 0xf0, 0x80, 0x80),
+    (LIGHTCYAN, "lightcyan", 0xe0, 0xff, 0xff),
+    (LIGHTGOLDENRODYELLOW, "lightgoldenrodyellow", 0xfa, 0xfa, 0xd2),
+    (LIGHTGRAY, "lightgray", 0xd3, 0xd3, 0xd3),
+    (LIGHTGREEN, "lightgreen", 0x90, 0xee, 0x90),
+    (LIGHTGREY, "lightgrey", 0xd3, 0xd3, 0xd3),
+    (LIGHTPINK, "lightpink", 0xff, 0xb6, 0xc1),
+    (LIGHTSALMON, "lightsalmon", 0xff, 0xa0, 0x7a),
+    (LIGHTSEAGREEN, "lightseagreen", 0x20, 0xb2, 0xaa),
+    (LIGHTSKYBLUE, "lightskyblue", 0x87, 0xce, 0xfa),
+    (LIGHTSLATEGRAY, "lightslategray", 0x77, 0x88, 0x99),
+    (LIGHTSLATEGREY, "lightslategrey", 0x77, 0x88, 0x99),
+    (LIGHTSTEELBLUE, "lightsteelblue", 0xb0, 0xc4, 0xde),
+    (LIGHTYELLOW, "lightyellow", 0xff, 0xff, 0xe0),
+    (LIME, "lime", 0x00, 0xff, 0x00),
+    (LIMEGREEN, "limegreen", 0x32, 0xcd, 0x32),
+    (LINEN, "linen", 0xfa, 0xf0, 0xe6),
+    (MAGENTA, "magenta", 0xff, 0x00, 0xff),
+    (MAROON, "maroon", 0x80, 0x00, 0x00),
+    (MEDIUMAQUAMARINE, "mediumaquamarine", 0x66, 0xcd, 0xaa),
+    (MEDIUMBLUE, "mediumblue", 0x00, 0x00, 0xcd),
+    (MEDIUMORCHID, "mediumorchid", 0xba, 0x55, 0xd3),
+    (MEDIUMPURPLE, "mediumpurple", 0x93, 0x70, 0xdb),
+    (MEDIUMSEAGREEN, "mediumseagreen", 0x3c, 0xb3, 0x71),
+    (MEDIUMSLATEBLUE, "mediumslateblue", 0x7b, 0x68, 0xee),
+    (MEDIUMSPRINGGREEN, "mediumspringgreen", 0x00, 0xfa, 0x9a),
+    (MEDIUMTURQUOISE, "mediumturquoise", 0x48, 0xd1, 0xcc),
+    (MEDIUMVIOLETRED, "mediumvioletred", 0xc7, 0x15, 0x85),
+    (MIDNIGHTBLUE, "midnightblue", 0x19, 0x19, 0x70),
+    (MINTCREAM, "mintcream", 0xf5, 0xff, 0xfa),
+    (MISTYROSE, "mistyrose", 0xff, 0xe4, 0xe1),
+    (MOCCASIN, "moccasin", 0xff, 0xe4, 0xb5),
+    (NAVAJOWHITE, "navajowhite", 0xff, 0xde, 0xad),
+    (NAVY, "navy", 0x00, 0x00, 0x80),
+    (OLDLACE, "oldlace", 0xfd, 0xf5, 0xe6),
+    (OLIVE, "olive", 0x80, 0x80, 0x00),
+    (OLIVEDRAB, "olivedrab", 0x6b, 0x8e, 0x23),
+    (ORANGE, "orange", 0xff, 0xa5, 0x00),
+    (ORANGERED, "orangered", 0xff, 0x45, 0x00),
+    (ORCHID, "orchid", 0xda, 0x70, 0xd6),
+    (PALEGOLDENROD, "palegoldenrod", 0xee, 0xe8, 0xaa),
+    (PALEGREEN, "palegreen", 0x98, 0xfb, 0x98),
+    (PALETURQUOISE, "paleturquoise", 0xaf, 0xee, 0xee),
+    (PALEVIOLETRED, "palevioletred", 0xdb, 0x70, 0x93),
+    (PAPAYAWHIP, "papayawhip", 0xff, 0xef, 0xd5),
+    (PEACHPUFF, "peachpuff", 0xff, 0xda, 0xb9),
+    (PERU, "peru", 0xcd, 0x85, 0x3f),
+    (PINK, "pink", 0xff, 0xc0, 0xcb),
+    (PLUM, "plum", 0xdd, 0xa0, 0xdd),
+    (POWDERBLUE, "powderblue", 0xb0, 0xe0, 0xe6),
+    (PURPLE, "purple", 0x80, 0x00, 0x80),
+    (REBECCAPURPLE, "rebeccapurple", 0x66, 0x33, 0x99),
+    (RED, "red", 0xff, 0x00, 0x00),
+    (ROSYBROWN, "rosybrown", 0xbc, 0x8f, 0x8f),
+    (ROYALBLUE, "royalblue", 0x41, 0x69, 0xe1),
+    (SADDLEBROWN, "saddlebrown", 0x8b, 0x45, 0x13),
+    (SALMON, "salmon", 0xfa, 0x80, 0x72),
+    (SANDYBROWN, "sandybrown", 0xf4, 0xa4, 0x60),
+    (SEAGREEN, "seagreen", 0x2e, 0x8b, 0x57),
+    (SEASHELL, "seashell", 0xff, 0xf5, 0xee),
+    (SIENNA, "sienna", 0xa0, 0x52, 0x2d),
+    (SILVER, "silver", 0xc0, 0xc0, 0xc0),
+    (SKYBLUE, "skyblue", 0x87, 0xce, 0xeb),
+    (SLATEBLUE, "slateblue", 0x6a, 0x5a, 0xcd),
+    (SLATEGRAY, "slategray", 0x70, 0x80, 0x90),
+    (SLATEGREY, "slategrey", 0x70, 0x80, 0x90),
+    (SNOW, "snow", 0xff, 0xfa, 0xfa),
+    (SPRINGGREEN, "springgreen", 0x00, 0xff, 0x7f),
+    (STEELBLUE, "steelblue", 0x46, 0x82, 0xb4),
+    (TAN, "tan", 0xd2, 0xb4, 0x8c),
+    (TEAL, "teal", 0x00, 0x80, 0x80),
+    (THISTLE, "thistle", 0xd8, 0xbf, 0xd8),
+    (TOMATO, "tomato", 0xff, 0x63, 0x47),
+    (TURQUOISE, "turquoise", 0x40, 0xe0, 0xd0),
+    (VIOLET, "violet", 0xee, 0x82, 0xee),
+    (WHEAT, "wheat", 0xf5, 0xde, 0xb3),
+    (WHITE, "white", 0xff, 0xff, 0xff),
+    (WHITESMOKE, "whitesmoke", 0xf5, 0xf5, 0xf5),
+    (YELLOW, "yellow", 0xff, 0xff, 0x00),
+    (YELLOWGREEN, "yellowgreen", 0x9a, 0xcd, 0x32),
+}
+
 impl Display for Pixel {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(&self.__repr__())
@@ -278,6 +690,21 @@ impl Rgb {
         }
     }
 
+    /// Returns the relative luminance of this pixel as defined by WCAG 2, see :meth:`Pixel.relative_luminance`.
+    ///
+    /// Returns
+    /// -------
+    /// float
+    ///     The relative luminance, from `0` for black to `1` for white.
+    #[pyo3(text_signature = "($self)")]
+    fn relative_luminance(&self) -> f64 {
+        relative_luminance(normalized(Dynamic::Rgb(ril::Rgb {
+            r: self.r,
+            g: self.g,
+            b: self.b,
+        })))
+    }
+
     fn __repr__(&self) -> String {
         format!("<Rgb r={} g={} b={}>", self.r, self.g, self.b)
     }
@@ -313,6 +740,31 @@ impl Rgba {
         }
     }
 
+    /// Returns the relative luminance of this pixel as defined by WCAG 2, see :meth:`Pixel.relative_luminance`.
+    ///
+    /// Parameters
+    /// ----------
+    /// background: Optional[:class:`.Pixel`], default: None
+    ///     The color a translucent pixel is seen against, defaults to white. Its alpha is ignored.
+    ///
+    /// Returns
+    /// -------
+    /// float
+    ///     The relative luminance, from `0` for black to `1` for white.
+    #[pyo3(signature = (background = None))]
+    #[pyo3(text_signature = "($self, background = None)")]
+    fn relative_luminance(&self, background: Option<Pixel>) -> f64 {
+        let background = background.map_or(WHITE, |background| normalized(background.inner));
+        let pixel = Dynamic::Rgba(ril::Rgba {
+            r: self.r,
+            g: self.g,
+            b: self.b,
+            a: self.a,
+        });
+
+        relative_luminance(composite(pixel, background))
+    }
+
     fn __repr__(&self) -> String {
         format!("<Rgba r={} g={} b={} a={}>", self.r, self.g, self.b, self.a)
     }
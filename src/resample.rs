@@ -0,0 +1,155 @@
+use std::f64::consts::PI;
+
+use crate::{parallel::for_each_row, types::ResizeAlgorithm};
+
+/// The filter of the given algorithm, and how far from its center it reaches before it is zero.
+///
+/// Nearest neighbour picks a single pixel rather than blending them, see `weights`, and is treated like `Box` here
+/// only so the match is exhaustive.
+fn filter(algorithm: ResizeAlgorithm) -> (fn(f64) -> f64, f64) {
+    match algorithm {
+        ResizeAlgorithm::Nearest | ResizeAlgorithm::Box => (box_filter, 0.5),
+        ResizeAlgorithm::Bilinear => (|x| (1.0 - x.abs()).max(0.0), 1.0),
+        ResizeAlgorithm::Hamming => (hamming, 1.0),
+        ResizeAlgorithm::Bicubic => (|x| cubic(x, 0.0, 0.5), 2.0),
+        ResizeAlgorithm::Mitchell => (|x| cubic(x, 1.0 / 3.0, 1.0 / 3.0), 2.0),
+        ResizeAlgorithm::Lanczos3 => (|x| sinc(x) * sinc(x / 3.0), 3.0),
+    }
+}
+
+fn box_filter(x: f64) -> f64 {
+    if x > -0.5 && x <= 0.5 {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 {
+        1.0
+    } else {
+        (x * PI).sin() / (x * PI)
+    }
+}
+
+fn hamming(x: f64) -> f64 {
+    if x.abs() >= 1.0 {
+        0.0
+    } else {
+        sinc(x) * (0.54 + 0.46 * (x * PI).cos())
+    }
+}
+
+/// The Mitchell-Netravali family of cubic filters, where `b = 0, c = 0.5` is Catmull-Rom.
+fn cubic(x: f64, b: f64, c: f64) -> f64 {
+    let x = x.abs();
+
+    if x < 1.0 {
+        ((12.0 - 9.0 * b - 6.0 * c) * x.powi(3)
+            + (-18.0 + 12.0 * b + 6.0 * c) * x.powi(2)
+            + (6.0 - 2.0 * b))
+            / 6.0
+    } else if x < 2.0 {
+        ((-b - 6.0 * c) * x.powi(3)
+            + (6.0 * b + 30.0 * c) * x.powi(2)
+            + (-12.0 * b - 48.0 * c) * x
+            + (8.0 * b + 24.0 * c))
+            / 6.0
+    } else {
+        0.0
+    }
+}
+
+/// The index of the first source value and the weights of the source values every target value along an axis is
+/// resampled from. When downscaling, the filter is stretched to cover every source value.
+fn weights(src: usize, dst: usize, algorithm: ResizeAlgorithm) -> Vec<(usize, Vec<f32>)> {
+    let scale = src as f64 / dst as f64;
+    if algorithm == ResizeAlgorithm::Nearest {
+        return (0..dst)
+            .map(|i| {
+                let nearest = ((i as f64 + 0.5) * scale) as usize;
+                (nearest.min(src - 1), vec![1.0])
+            })
+            .collect();
+    }
+
+    let (filter, support) = filter(algorithm);
+    let stretch = scale.max(1.0);
+    let support = support * stretch;
+
+    (0..dst)
+        .map(|i| {
+            let center = (i as f64 + 0.5) * scale;
+            let start = (center - support + 0.5).max(0.0) as usize;
+            let end = ((center + support + 0.5) as usize).min(src).max(start + 1);
+
+            let weights = (start..end)
+                .map(|j| filter((j as f64 - center + 0.5) / stretch))
+                .collect::<Vec<_>>();
+            let total = weights.iter().sum::<f64>();
+
+            let weights = weights
+                .into_iter()
+                .map(|weight| {
+                    if total == 0.0 {
+                        0.0
+                    } else {
+                        (weight / total) as f32
+                    }
+                })
+                .collect();
+
+            (start, weights)
+        })
+        .collect()
+}
+
+/// Resamples an image of `channels` interleaved values per pixel to the given dimensions,
+/// first along rows and then along columns.
+///
+/// `source` fills the given buffer with the values of a row of the image, so that rows are read where they are
+/// rather than copied up front.
+pub fn resample<F>(
+    channels: usize,
+    (src_width, src_height): (usize, usize),
+    (width, height): (usize, usize),
+    algorithm: ResizeAlgorithm,
+    source: F,
+) -> Vec<f32>
+where
+    F: Fn(usize, &mut Vec<f32>) + Sync,
+{
+    let columns = weights(src_width, width, algorithm);
+    let rows = weights(src_height, height, algorithm);
+
+    let mut resampled_rows = vec![0.0; width * channels * src_height];
+    for_each_row(&mut resampled_rows, width * channels, |y, row| {
+        let mut values = Vec::with_capacity(src_width * channels);
+        source(y, &mut values);
+
+        for (pixel, (start, weights)) in row.chunks_exact_mut(channels).zip(&columns) {
+            for (j, weight) in weights.iter().enumerate() {
+                let value = &values[(start + j) * channels..][..channels];
+                pixel
+                    .iter_mut()
+                    .zip(value)
+                    .for_each(|(out, value)| *out += weight * value);
+            }
+        }
+    });
+
+    let mut resampled = vec![0.0; width * channels * height];
+    for_each_row(&mut resampled, width * channels, |y, row| {
+        let (start, weights) = &rows[y];
+
+        for (j, weight) in weights.iter().enumerate() {
+            let source = &resampled_rows[(start + j) * width * channels..][..width * channels];
+            row.iter_mut()
+                .zip(source)
+                .for_each(|(out, value)| *out += weight * value);
+        }
+    });
+
+    resampled
+}
@@ -1,17 +1,50 @@
-use std::{path::PathBuf, time::Duration};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 
 use pyo3::{
+    buffer::PyBuffer,
+    exceptions::{
+        PyBufferError, PyIndexError, PyRuntimeError, PyTypeError, PyUserWarning, PyValueError,
+    },
     prelude::*,
-    types::{PyBytes, PyType},
+    pyclass::CompareOp,
+    types::{PyBytes, PySlice, PyType},
 };
 use ril::{
-    Dynamic, Frame as RilFrame, FrameIterator, ImageFormat, ImageSequence as RilImageSequence,
+    Dynamic, Error as RilError, Frame as RilFrame, FrameIterator, Image as RilImage, ImageFormat,
+    ImageSequence as RilImageSequence, Pixel as _,
 };
 
-use crate::{error::Error, image::Image, types::DisposalMethod, Xy};
+use crate::{
+    asyncio::spawn_blocking,
+    draw::{as_point, DrawEntity},
+    error::Error,
+    header,
+    image::{
+        copy_region, draw_reconciled, paste_clipped, paste_reconciled, recover_jpeg, resized, Image,
+    },
+    metadata::Metadata,
+    options::{EncodeOptions, GifOptions},
+    parallel::{map_ordered, maps_in_parallel},
+    pixels::Pixel,
+    text::{Font, TextSegment},
+    trace,
+    types::{DisposalMethod, FormatArg, Mode, ModeArg, ResizeAlgorithm, WrapStyle},
+    utils::{
+        compare_eq, ensure_box, ensure_dimensions, ensure_open, format_from_path, infer_format,
+        notebook_preview, Shared,
+    },
+    Xy,
+};
 
 /// Represents a frame in an image sequence. It encloses :class:`.Image` and extra metadata about the frame.
 ///
+/// New frames have no delay and use :attr:`.DisposalMethod.Keep`, which leaves ghosting behind transparent frames.
+///
 /// Parameters
 /// ----------
 /// image: :class:`.Image`
@@ -20,85 +53,195 @@ use crate::{error::Error, image::Image, types::DisposalMethod, Xy};
 #[pyclass]
 #[pyo3(text_signature = "(image)")]
 pub struct Frame {
-    inner: RilFrame<Dynamic>,
+    image: Shared<RilImage>,
+    delay: Duration,
+    disposal: ril::DisposalMethod,
 }
 
 #[pymethods]
 impl Frame {
     #[new]
-    fn new(image: Image) -> Self {
-        Self {
-            inner: RilFrame::from_image(image.inner),
-        }
+    fn new(image: Image) -> PyResult<Self> {
+        ensure_open!(image);
+
+        Ok(Self::from_inner(RilFrame::from_image(
+            image.inner.into_inner(),
+        )))
     }
 
-    /// int: Returns the delay duration for this frame.
+    /// int: Returns the delay duration for this frame in milliseconds.
     #[getter]
     fn get_delay(&self) -> u128 {
-        self.inner.delay().as_millis()
+        self.delay.as_millis()
     }
 
     /// Tuple[int, int]: Returns the dimensions of this frame.
     #[getter]
     fn get_dimensions(&self) -> Xy {
-        self.inner.dimensions()
+        self.image.dimensions()
     }
 
     /// :class:`.DisposalMethod`: Returns the disposal method for this frame.
     #[getter]
     fn get_disposal(&self) -> DisposalMethod {
-        self.inner.disposal().into()
+        self.disposal.into()
+    }
+
+    /// bool: Returns whether any pixel of this frame is not fully opaque.
+    ///
+    /// Only `RGBA` frames can have transparency, and the check stops at the first transparent pixel.
+    #[getter]
+    fn get_has_transparency(&self) -> bool {
+        self.image
+            .data
+            .iter()
+            .any(|pixel| matches!(pixel, Dynamic::Rgba(p) if p.a < u8::MAX))
     }
 
     /// :class:`.Image`: Returns the image this frame contains.
+    ///
+    /// The image shares its pixels with this frame until either of them is modified, so this is cheap.
+    /// Changes made to the returned image don't affect this frame, assign it back to replace the image of this frame.
     #[getter]
     fn get_image(&self) -> Image {
-        Image {
-            inner: self.inner.image().clone(),
-        }
+        Image::from_shared(self.image.clone())
+    }
+
+    #[setter]
+    fn set_image(&mut self, image: PyRef<'_, Image>) -> PyResult<()> {
+        ensure_open!(image);
+
+        self.image = image.inner.clone();
+
+        Ok(())
     }
 
     #[setter]
     fn set_delay(&mut self, delay: u64) {
-        self.inner.set_delay(Duration::from_millis(delay));
+        self.delay = Duration::from_millis(delay);
     }
 
     #[setter]
     fn set_disposal(&mut self, disposal: DisposalMethod) {
-        self.inner.set_disposal(disposal.into())
+        self.disposal = disposal.into();
+    }
+
+    /// Compares the pixels of this frame with another frame, ignoring their delays and disposal methods.
+    ///
+    /// Parameters
+    /// ----------
+    /// other: :class:`.Frame`
+    ///     The frame to compare with.
+    ///
+    /// Returns
+    /// -------
+    /// Tuple[bool, Optional[Tuple[int, int, int, int]]]
+    ///     Whether any pixel differs, and the bounding box ``(x1, y1, x2, y2)`` of the differing pixels.
+    ///     The bounding box is `None` if no pixel differs, or if the frames have different dimensions.
+    #[pyo3(text_signature = "($self, other)")]
+    fn diff(&self, py: Python<'_>, other: &Self) -> (bool, Option<(u32, u32, u32, u32)>) {
+        if self.image.dimensions() != other.image.dimensions() {
+            return (true, None);
+        }
+
+        let (a, b) = (&self.image, &other.image);
+        let bbox = py.allow_threads(|| diff_bbox(a, b));
+
+        (bbox.is_some(), bbox)
     }
 
     fn __repr__(&self) -> String {
         format!(
-            "<Frame delay={} dimensions=({}, {}) disposal={}>",
+            "<Frame delay={}ms dimensions=({}, {}) disposal=DisposalMethod.{}>",
             self.get_delay(),
             self.get_dimensions().0,
             self.get_dimensions().1,
             self.get_disposal()
         )
     }
+
+    /// Frames are equal if their delays, disposal methods and pixels are equal.
+    /// Frames with different dimensions are never equal.
+    fn __richcmp__(&self, py: Python<'_>, other: &PyAny, op: CompareOp) -> PyObject {
+        compare_eq(py, self, other, op)
+    }
+
+    // Frames are mutable, so they are explicitly unhashable.
+    #[classattr]
+    #[allow(non_upper_case_globals)]
+    const __hash__: Option<PyObject> = None;
+}
+
+impl PartialEq for Frame {
+    fn eq(&self, other: &Self) -> bool {
+        self.delay == other.delay
+            && self.get_disposal() == other.get_disposal()
+            && frames_match(&self.image, &other.image, 0)
+    }
+}
+
+impl Frame {
+    pub(crate) fn from_inner(frame: RilFrame<Dynamic>) -> Self {
+        Self {
+            delay: frame.delay(),
+            disposal: frame.disposal(),
+            image: frame.into_image().into(),
+        }
+    }
+
+    /// Converts this frame back into a frame of a sequence, copying its image only if it is still shared.
+    pub(crate) fn into_inner(self) -> RilFrame<Dynamic> {
+        let mut frame = RilFrame::from_image(self.image.into_inner());
+        frame.set_delay(self.delay);
+        frame.set_disposal(self.disposal);
+
+        frame
+    }
 }
 
 /// Represents a sequence of image frames such as an animated image.
 ///
 /// See :class:`.Image` for the static image counterpart, and see :class:`.Frame` to see how each frame is represented in an image sequence.
 ///
-/// The iterator is exhausive, so when you iterate through :class:`.ImageSequence` like
+/// Iterating through :class:`.ImageSequence` always starts at the first frame, so it can be iterated more than once
 ///
 /// .. code-block:: python3
 ///
 ///     seq = ImageSequence.from_bytes(bytes)
 ///     list(seq) # [...]
-///     # But if you do it again
-///     list(seq) # []
-///     # It will return a empty list
+///     # Doing it again returns the same frames
+///     list(seq) # [...]
+///
+/// Each iteration uses its own :class:`.ImageSequenceIterator`. ``next(seq)`` instead returns frames from the position
+/// of the sequence itself, which can be moved with :meth:`seek` and read with :meth:`tell`.
 ///
 /// .. note::
-///     Any change made to the :class:`.Frame` will not be reflected to the :class:`.ImageSequence`, so you must create a new :class:`.ImageSequence` after you make changes to the frames.
+///     Frames are copies, so changes made to a :class:`.Frame` are not reflected in the :class:`.ImageSequence`
+///     until the frame is assigned back, such as with ``seq[i] = frame``. See also :meth:`insert`, :meth:`append`
+///     and :meth:`remove`.
+///
+/// Examples
+/// --------
+///
+/// .. code-block:: python3
+///
+///     seq = ImageSequence.open('animation.gif')
+///     for i, frame in enumerate(seq):
+///         image = frame.image
+///         image.draw(watermark)
+///         frame.image = image
+///         seq[i] = frame
+///     seq.save('watermarked.gif')
 #[pyclass]
 pub struct ImageSequence {
     inner: RilImageSequence<Dynamic>,
-    iter: Box<dyn Iterator<Item = ril::Frame<Dynamic>> + Send>,
+    position: usize,
+    metadata: Metadata,
+    palette: Option<Vec<[u8; 3]>>,
+    gif_source: Option<GifSource>,
+    decode_warnings: Vec<String>,
+    adjusted_frames: Vec<usize>,
+    closed: bool,
 }
 
 #[pymethods]
@@ -111,46 +254,595 @@ impl ImageSequence {
     /// ----------
     /// bytes: bytes
     ///     The bytes of the image.
-    /// format: Optional[str], default: None
+    /// format: Optional[Union[:class:`.ImageFormat`, str]], default: None
     ///     The format of the image.
+    /// tolerant: bool, default: False
+    ///     Whether to keep the frames decoded before the data is cut off or corrupt instead of raising,
+    ///     see :attr:`decode_warnings`. JPEG data is recovered like :meth:`Image.from_bytes` does.
+    /// fill: Optional[:class:`.Pixel`], default: None
+    ///     Only used with `tolerant`. The color to fill the rows of JPEG data that couldn't be decoded with,
+    ///     defaults to black.
     ///
     /// Raises
     /// ------
     /// ValueError
     ///     The format provided is invalid.
+    /// UnknownFormatError
+    ///     `format` is not provided and it can't be inferred from the bytes.
     /// RuntimeError
-    ///     Failed to decode the image or Failed to infer the image's format.
+    ///     Failed to decode the image, or not a single frame could be recovered with `tolerant` set.
+    ///
+    /// .. note::
+    ///     The GIL is released while decoding.
     #[classmethod]
-    #[pyo3(text_signature = "(cls, bytes, format)")]
-    fn from_bytes(_: &PyType, bytes: &[u8], format: Option<&str>) -> Result<Self, Error> {
-        Ok(if let Some(format) = format {
-            let inner = RilImageSequence::from_bytes(ImageFormat::from_extension(format)?, bytes)?
-                .into_sequence()?;
-            let iter = Box::new(inner.clone().into_iter());
-
-            Self { inner, iter }
+    #[pyo3(signature = (bytes, format = None, *, tolerant = false, fill = None))]
+    #[pyo3(text_signature = "($cls, bytes, format = None, *, tolerant = False, fill = None)")]
+    fn from_bytes(
+        _: &PyType,
+        py: Python<'_>,
+        bytes: &[u8],
+        format: Option<FormatArg>,
+        tolerant: bool,
+        fill: Option<Pixel>,
+    ) -> Result<Self, Error> {
+        let format = match format {
+            Some(FormatArg(format)) => format,
+            None => infer_format(bytes)?,
+        };
+
+        let (inner, decode_warnings) = if tolerant {
+            let black = Dynamic::Rgb(ril::Rgb { r: 0, g: 0, b: 0 });
+            let fill = fill.map_or(black, |fill| fill.inner);
+            py.allow_threads(|| decode_sequence_tolerant(format, bytes, fill))?
         } else {
-            let inner = RilImageSequence::from_bytes(ImageFormat::infer_encoding(bytes), bytes)?
-                .into_sequence()?;
-            let iter = Box::new(inner.clone().into_iter());
+            let inner = py.allow_threads(|| decode_sequence(format, bytes))?;
+            (inner, Vec::new())
+        };
+        let metadata = Metadata::read(format, bytes);
+        let palette = header::palette(format, bytes);
+        let gif_source = GifSource::new(format, bytes, inner.len());
 
-            Self { inner, iter }
+        Ok(Self {
+            inner,
+            position: 0,
+            metadata,
+            palette,
+            gif_source,
+            decode_warnings,
+            adjusted_frames: Vec::new(),
+            closed: false,
         })
     }
 
+    /// Reads the amount of frames of an encoded sequence from its header, without decoding it or allocating its pixels.
+    ///
+    /// This is useful for cheaply rejecting overly long animations before decoding them with :meth:`from_bytes`.
+    /// PNG, JPEG, GIF, WebP and BMP data is supported, where anything but animated PNG, GIF and WebP data has 1 frame.
+    /// GIF data has no frame count in its header, so its blocks are skimmed instead.
+    ///
+    /// Parameters
+    /// ----------
+    /// bytes: bytes
+    ///     The bytes of the image.
+    ///
+    /// Returns
+    /// -------
+    /// int
+    ///     The amount of frames.
+    ///
+    /// Raises
+    /// ------
+    /// UnknownFormatError
+    ///     The format can't be inferred from the bytes.
+    /// RuntimeError
+    ///     The header is truncated or malformed, or the format is not supported.
+    #[classmethod]
+    #[pyo3(text_signature = "($cls, bytes)")]
+    fn peek_frame_count(_: &PyType, bytes: &[u8]) -> Result<usize, Error> {
+        header::frame_count(infer_format(bytes)?, bytes)
+    }
+
     /// Creates a new image sequence from the given frames
     ///
+    /// Transparent frames that use :attr:`.DisposalMethod.Keep` leave ghosting behind.
+    /// With `fix_disposal` set, they are switched to :attr:`.DisposalMethod.Background`,
+    /// and their indices are kept in :attr:`adjusted_frames`.
+    ///
     /// Parameters
     /// ----------
     /// frames: List[:class:`Frame`]
     ///     The list of frames to create the sequence from
+    /// fix_disposal: bool, default: False
+    ///     Whether to switch the disposal of transparent frames from `Keep` to `Background`.
+    /// warn: bool, default: False
+    ///     Whether to emit a warning listing the transparent frames that use `Keep`, or that were switched
+    ///     if `fix_disposal` is set.
+    #[classmethod]
+    #[pyo3(signature = (frames, fix_disposal = false, warn = false))]
+    #[pyo3(text_signature = "($cls, frames, fix_disposal = False, warn = False)")]
+    fn from_frames(
+        _: &PyType,
+        py: Python<'_>,
+        mut frames: Vec<Frame>,
+        fix_disposal: bool,
+        warn: bool,
+    ) -> PyResult<Self> {
+        let ghosting = frames
+            .iter()
+            .enumerate()
+            .filter(|(_, frame)| {
+                frame.get_disposal() == DisposalMethod::Keep && frame.get_has_transparency()
+            })
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+
+        if fix_disposal {
+            ghosting.iter().for_each(|&i| {
+                frames[i].set_disposal(DisposalMethod::Background);
+            });
+        }
+
+        if warn && !ghosting.is_empty() {
+            let message = if fix_disposal {
+                format!(
+                    "Switched the disposal of transparent frames {:?} to DisposalMethod.Background",
+                    ghosting
+                )
+            } else {
+                format!(
+                    "Transparent frames {:?} use DisposalMethod.Keep, which causes ghosting, pass `fix_disposal=True` to fix them",
+                    ghosting
+                )
+            };
+
+            PyErr::warn(py, py.get_type::<PyUserWarning>(), &message, 1)?;
+        }
+
+        let frames = frames
+            .into_iter()
+            .map(Frame::into_inner)
+            .collect::<Vec<_>>();
+        let inner = RilImageSequence::from_frames(frames);
+
+        Ok(Self {
+            inner,
+            position: 0,
+            metadata: Metadata::default(),
+            palette: None,
+            gif_source: None,
+            decode_warnings: Vec::new(),
+            adjusted_frames: if fix_disposal { ghosting } else { Vec::new() },
+            closed: false,
+        })
+    }
+
+    /// Creates a new image sequence by calling the given function for every frame, such as for procedural animations.
+    ///
+    /// The function is called with the index of the frame and must return an :class:`.Image`.
+    /// All of the returned images must have the same dimensions.
+    ///
+    /// Parameters
+    /// ----------
+    /// frame_count: int
+    ///     The amount of frames to generate.
+    /// func: Callable[[int], :class:`.Image`]
+    ///     The function that generates a frame from its index.
+    /// delay: int, default: 100
+    ///     The delay of every frame, in milliseconds.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     `frame_count` is zero or the images don't all have the same dimensions.
+    /// TypeError
+    ///     `func` returned something other than an :class:`.Image`.
+    /// RuntimeError
+    ///     `func` raised an exception, which is set as the cause.
+    ///
+    /// Examples
+    /// --------
+    ///
+    /// .. code-block:: python3
+    ///
+    ///     seq = ImageSequence.generate(10, lambda i: Image.new(64, 64, Pixel.from_rgba(255, 0, 0, i * 25)))
+    #[classmethod]
+    #[pyo3(signature = (frame_count, func, delay = 100))]
+    #[pyo3(text_signature = "($cls, frame_count, func, delay = 100)")]
+    fn generate(
+        _: &PyType,
+        py: Python<'_>,
+        frame_count: usize,
+        func: &PyAny,
+        delay: u64,
+    ) -> PyResult<Self> {
+        if frame_count == 0 {
+            return Err(PyValueError::new_err("Expected at least 1 frame"));
+        }
+
+        let mut frames = Vec::with_capacity(frame_count);
+        let mut dimensions = None;
+
+        for i in 0..frame_count {
+            let result = func.call1((i,)).map_err(|err| {
+                let wrapped =
+                    PyRuntimeError::new_err(format!("Failed to generate frame {}: {}", i, err));
+                wrapped.set_cause(py, Some(err));
+
+                wrapped
+            })?;
+
+            let image = match result.extract::<Image>() {
+                Ok(image) => image,
+                Err(_) => {
+                    return Err(PyTypeError::new_err(format!(
+                        "Expected frame {} to be an Image, got {}",
+                        i,
+                        result.get_type().name()?
+                    )))
+                }
+            };
+
+            let expected = *dimensions.get_or_insert(image.inner.dimensions());
+            if image.inner.dimensions() != expected {
+                return Err(PyValueError::new_err(format!(
+                    "Expected frame {} to have dimensions {:?}, got {:?}",
+                    i,
+                    expected,
+                    image.inner.dimensions()
+                )));
+            }
+
+            let mut frame = RilFrame::from_image(image.inner.into_inner());
+            frame.set_delay(Duration::from_millis(delay));
+            frames.push(frame);
+        }
+
+        let inner = RilImageSequence::from_frames(frames);
+
+        Ok(Self {
+            inner,
+            position: 0,
+            metadata: Metadata::default(),
+            palette: None,
+            gif_source: None,
+            decode_warnings: Vec::new(),
+            adjusted_frames: Vec::new(),
+            closed: false,
+        })
+    }
+
+    /// Creates a typewriter animation, where every frame shows more of the text drawn onto a copy of `background`.
+    ///
+    /// The text is drawn as a :class:`.TextSegment`, so colors of a different mode than the background are converted
+    /// to its mode as in :meth:`Image.draw`. Since every frame is laid out separately, a word that no longer fits
+    /// on its line moves to the next one as it is typed.
+    ///
+    /// Parameters
+    /// ----------
+    /// background: :class:`.Image`
+    ///     The image the text is drawn onto, which is left untouched.
+    /// font: :class:`.Font`
+    ///     The font to render the text with.
+    /// text: str
+    ///     The text to type.
+    /// position: Tuple[int, int]
+    ///     The position of the text on the background.
+    /// fill: :class:`.Pixel`
+    ///     The color of the text.
+    /// chars_per_frame: int, default: 1
+    ///     The amount of characters typed every frame, which makes ``ceil(len(text) / chars_per_frame)`` frames.
+    /// delay: int, default: 50
+    ///     The delay of every frame, in milliseconds.
+    /// size: Optional[float], default: None
+    ///     The size of the text in pixels, defaults to the optimal size of the font.
+    /// width: Optional[int], default: None
+    ///     The width the text is wrapped at, see :attr:`.TextSegment.width`.
+    /// wrap: Optional[:class:`.WrapStyle`], default: None
+    ///     How the text is wrapped, defaults to :attr:`.WrapStyle.Word`.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     `text` is empty or `chars_per_frame` is zero.
+    ///
+    /// Examples
+    /// --------
+    ///
+    /// .. code-block:: python3
+    ///
+    ///     seq = ImageSequence.typewriter(background, font, 'Hello, world!', position=(10, 10), fill=Pixel.from_rgb(0, 0, 0))
+    #[classmethod]
+    #[pyo3(signature = (background, font, text, *, position, fill, chars_per_frame = 1, delay = 50, size = None, width = None, wrap = None))]
+    #[pyo3(
+        text_signature = "($cls, background, font, text, *, position, fill, chars_per_frame = 1, delay = 50, size = None, width = None, wrap = None)"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    fn typewriter(
+        _: &PyType,
+        py: Python<'_>,
+        background: PyRef<'_, Image>,
+        font: Py<Font>,
+        text: &str,
+        position: Xy,
+        fill: Pixel,
+        chars_per_frame: usize,
+        delay: u64,
+        size: Option<f32>,
+        width: Option<u32>,
+        wrap: Option<WrapStyle>,
+    ) -> PyResult<Self> {
+        if chars_per_frame == 0 {
+            return Err(PyValueError::new_err(
+                "Expected at least 1 character per frame",
+            ));
+        }
+
+        // The byte offset the text is cut at after every character.
+        let ends = text
+            .char_indices()
+            .map(|(index, character)| index + character.len_utf8())
+            .collect::<Vec<_>>();
+        if ends.is_empty() {
+            return Err(PyValueError::new_err("Expected some text to type"));
+        }
+
+        let mut segment = TextSegment::from_font(py, font, text, fill);
+        segment.set_position(as_point(position))?;
+        segment.inner.size = size.unwrap_or(segment.inner.size);
+        segment.inner.width = width;
+        segment.inner.wrap = wrap.unwrap_or(WrapStyle::Word).into();
+
+        let mut entity = DrawEntity::TextSegment(segment);
+        let modes = entity.modes()?;
+        let convertible = entity.is_convertible();
+
+        let frame_count = ends.len().div_ceil(chars_per_frame);
+        let mut frames = Vec::with_capacity(frame_count);
+
+        for i in 1..=frame_count {
+            if let DrawEntity::TextSegment(segment) = &mut entity {
+                let end = ends[(i * chars_per_frame).min(ends.len()) - 1];
+                segment.inner.text = text[..end].to_string();
+            }
+
+            let mut image = RilImage::clone(&background.inner);
+            draw_reconciled(&mut image, &modes, false, convertible, |image, mode| {
+                entity.convert(mode);
+                Ok(entity.draw(image)?)
+            })?;
+
+            let mut frame = RilFrame::from_image(image);
+            frame.set_delay(Duration::from_millis(delay));
+            frames.push(frame);
+        }
+
+        let inner = RilImageSequence::from_frames(frames);
+
+        Ok(Self {
+            inner,
+            position: 0,
+            metadata: Metadata::default(),
+            palette: None,
+            gif_source: None,
+            decode_warnings: Vec::new(),
+            adjusted_frames: Vec::new(),
+            closed: false,
+        })
+    }
+
+    /// Slices a sprite sheet into a sequence, reading frames left to right, then top to bottom.
+    ///
+    /// See :meth:`to_spritesheet` for the inverse.
+    ///
+    /// Parameters
+    /// ----------
+    /// image: :class:`.Image`
+    ///     The sprite sheet, its dimensions must be multiples of the frame dimensions.
+    /// frame_width: int
+    ///     The width of every frame.
+    /// frame_height: int
+    ///     The height of every frame.
+    /// count: Optional[int], default: None
+    ///     The amount of frames to read, which leaves out empty cells at the end of the sheet. Defaults to every cell.
+    /// delay: int, default: 100
+    ///     The delay of every frame, in milliseconds.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The frame dimensions are zero or don't divide the dimensions of the sheet,
+    ///     or `count` is zero or more than the amount of cells.
+    ///
+    /// Examples
+    /// --------
+    ///
+    /// .. code-block:: python3
+    ///
+    ///     seq = ImageSequence.from_spritesheet(Image.open('walk.png'), 32, 32, count=7)
+    #[classmethod]
+    #[pyo3(signature = (image, frame_width, frame_height, count = None, delay = 100))]
+    #[pyo3(text_signature = "($cls, image, frame_width, frame_height, count = None, delay = 100)")]
+    fn from_spritesheet(
+        _: &PyType,
+        py: Python<'_>,
+        image: Image,
+        frame_width: u32,
+        frame_height: u32,
+        count: Option<usize>,
+        delay: u64,
+    ) -> PyResult<Self> {
+        ensure_open!(image);
+        ensure_dimensions(frame_width, frame_height)?;
+
+        let (width, height) = image.inner.dimensions();
+        if width % frame_width != 0 || height % frame_height != 0 {
+            return Err(PyValueError::new_err(format!(
+                "Frame dimensions ({}, {}) don't divide the sprite sheet dimensions ({}, {})",
+                frame_width, frame_height, width, height
+            )));
+        }
+
+        let columns = width / frame_width;
+        let cells = (columns * (height / frame_height)) as usize;
+        let count = count.unwrap_or(cells);
+        if count == 0 || count > cells {
+            return Err(PyValueError::new_err(format!(
+                "Expected between 1 and {} frames, got {}",
+                cells, count
+            )));
+        }
+
+        let sheet = &image.inner;
+        let frames = py.allow_threads(|| {
+            (0..count as u32)
+                .map(|i| {
+                    let (x, y) = (i % columns * frame_width, i / columns * frame_height);
+                    let region = copy_region(sheet, (x, y, x + frame_width, y + frame_height));
+
+                    let mut frame = RilFrame::from_image(region);
+                    frame.set_delay(Duration::from_millis(delay));
+
+                    frame
+                })
+                .collect::<Vec<_>>()
+        });
+
+        let inner = RilImageSequence::from_frames(frames);
+
+        Ok(Self {
+            inner,
+            position: 0,
+            metadata: Metadata::default(),
+            palette: None,
+            gif_source: None,
+            decode_warnings: Vec::new(),
+            adjusted_frames: Vec::new(),
+            closed: false,
+        })
+    }
+
+    /// Creates a new image sequence from a numpy array of shape ``(frames, height, width, channels)``,
+    /// such as a batch of video frames.
+    ///
+    /// The mode of the frames is inferred from the amount of channels: ``1`` for `L`, ``3`` for `RGB`
+    /// and ``4`` for `RGBA`. See :meth:`to_numpy` for the inverse.
+    ///
+    /// Parameters
+    /// ----------
+    /// array: numpy.ndarray
+    ///     The pixel data of every frame, of dtype `uint8`.
+    /// delay: int, default: 100
+    ///     The delay of every frame, in milliseconds.
+    ///
+    /// Raises
+    /// ------
+    /// ImportError
+    ///     numpy is not installed.
+    /// TypeError
+    ///     The array is not of dtype `uint8`.
+    /// ValueError
+    ///     The array is not four-dimensional, has no frames, pixels, or an unsupported amount of channels.
+    ///
+    /// Examples
+    /// --------
+    ///
+    /// .. code-block:: python3
+    ///
+    ///     seq = ImageSequence.from_numpy(np.zeros((10, 64, 64, 3), dtype=np.uint8), delay=40)
     #[classmethod]
-    fn from_frames(_: &PyType, frames: Vec<Frame>) -> Self {
-        let inner =
-            RilImageSequence::from_frames(frames.into_iter().map(|x| x.inner).collect::<Vec<_>>());
-        let iter = Box::new(inner.clone().into_iter());
+    #[pyo3(signature = (array, delay = 100))]
+    #[pyo3(text_signature = "($cls, array, delay = 100)")]
+    fn from_numpy(_: &PyType, py: Python<'_>, array: &PyAny, delay: u64) -> PyResult<Self> {
+        let array = py
+            .import("numpy")?
+            .call_method1("ascontiguousarray", (array,))?;
+
+        let dtype = array.getattr("dtype")?.str()?.to_str()?;
+        if dtype != "uint8" {
+            return Err(PyTypeError::new_err(format!(
+                "Expected an array of dtype uint8, got {}",
+                dtype
+            )));
+        }
+
+        let buffer = PyBuffer::<u8>::get(array)?;
+        let (frames, height, width, channels) = match *buffer.shape() {
+            [frames, height, width, channels] => (frames, height, width, channels),
+            ref shape => {
+                return Err(PyValueError::new_err(format!(
+                    "Expected an array of shape (frames, height, width, channels), got {:?}",
+                    shape
+                )))
+            }
+        };
+
+        if frames == 0 {
+            return Err(PyValueError::new_err("Expected at least 1 frame"));
+        }
+
+        let (width, height) = match (u32::try_from(width), u32::try_from(height)) {
+            (Ok(width), Ok(height)) => (width, height),
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Frame dimensions ({}, {}) are too large",
+                    width, height
+                )))
+            }
+        };
+        ensure_dimensions(width, height)?;
+
+        if !matches!(channels, 1 | 3 | 4) {
+            return Err(PyValueError::new_err(format!(
+                "Expected 1, 3 or 4 channels, got {}",
+                channels
+            )));
+        }
+
+        let cells = buffer
+            .as_slice(py)
+            .ok_or_else(|| PyBufferError::new_err("Expected a contiguous array"))?;
+        let frame_len = cells.len() / frames;
+
+        let frames = cells
+            .chunks(frame_len)
+            .map(|data| {
+                let pixels = data
+                    .chunks(channels)
+                    .map(|pixel| match pixel {
+                        [r, g, b, a] => Dynamic::Rgba(ril::Rgba {
+                            r: r.get(),
+                            g: g.get(),
+                            b: b.get(),
+                            a: a.get(),
+                        }),
+                        [r, g, b] => Dynamic::Rgb(ril::Rgb {
+                            r: r.get(),
+                            g: g.get(),
+                            b: b.get(),
+                        }),
+                        _ => Dynamic::L(ril::L(pixel[0].get())),
+                    })
+                    .collect::<Vec<_>>();
+
+                let mut frame = RilFrame::from_image(RilImage::from_pixels(width, pixels));
+                frame.set_delay(Duration::from_millis(delay));
 
-        Self { inner, iter }
+                frame
+            })
+            .collect::<Vec<_>>();
+
+        let inner = RilImageSequence::from_frames(frames);
+
+        Ok(Self {
+            inner,
+            position: 0,
+            metadata: Metadata::default(),
+            palette: None,
+            gif_source: None,
+            decode_warnings: Vec::new(),
+            adjusted_frames: Vec::new(),
+            closed: false,
+        })
     }
 
     /// Opens a file from the given path and decodes it into an :class:`.ImageSequence`.
@@ -169,40 +861,143 @@ impl ImageSequence {
     ///     The file extension is invalid.
     /// RuntimeError
     ///     Failed to infer file format or Failed to decode image.
+    ///
+    /// .. note::
+    ///     The GIL is released while reading and decoding the file.
+    #[classmethod]
+    #[pyo3(text_signature = "($cls, path)")]
+    fn open(_: &PyType, py: Python<'_>, path: PathBuf) -> Result<Self, Error> {
+        py.allow_threads(|| Self::open_path(&path))
+    }
+
+    /// Like :meth:`open`, but reads and decodes the file on a separate thread without blocking the event loop.
+    ///
+    /// This must be called from a running :mod:`asyncio` event loop.
+    ///
+    /// Parameters
+    /// ----------
+    /// path: str
+    ///     The path to the image.
+    ///
+    /// Returns
+    /// -------
+    /// Awaitable[:class:`.ImageSequence`]
+    ///     A future resolving to the decoded sequence, raising the same errors as :meth:`open`.
+    ///
+    /// Raises
+    /// ------
+    /// RuntimeError
+    ///     There is no running event loop.
     #[classmethod]
-    #[pyo3(text_signature = "(cls, path)")]
-    fn open(_: &PyType, path: PathBuf) -> Result<Self, Error> {
-        let inner = RilImageSequence::open(path)?.into_sequence()?;
-        let iter = Box::new(inner.clone().into_iter());
-        Ok(Self { inner, iter })
+    #[pyo3(text_signature = "($cls, path)")]
+    fn open_async<'a>(_: &PyType, py: Python<'a>, path: PathBuf) -> PyResult<&'a PyAny> {
+        spawn_blocking(py, move || Self::open_path(&path))
+    }
+
+    /// Returns the encodings this build of ril can encode image sequences to, such as for picking an export format.
+    ///
+    /// These are the encodings :meth:`encode` and :meth:`save` accept, and any other encoding raises an
+    /// :class:`.UnsupportedFormatError`. `png` encodes an animated PNG (APNG). `jpeg` and `webp` can only encode
+    /// a sequence of a single frame, and `webp` is only supported when ril was built with the `webp` feature,
+    /// see :data:`WEBP`.
+    ///
+    /// Parameters
+    /// ----------
+    /// frames: Optional[int], default: None
+    ///     The amount of frames of the sequence to encode. If not given, only the encodings that can encode any amount
+    ///     of frames are returned.
+    ///
+    /// Returns
+    /// -------
+    /// List[str]
+    ///     The names of the supported encodings.
+    #[staticmethod]
+    #[pyo3(signature = (frames = None))]
+    #[pyo3(text_signature = "(frames = None)")]
+    fn supported_encodings(frames: Option<usize>) -> Vec<&'static str> {
+        sequence_encodings(frames)
     }
 
     /// Encodes the image with the given encoding and returns `bytes`.
     ///
     /// Parameters
     /// ----------
-    /// encoding: str
-    ///     The encoding to encode to.
+    /// encoding: Optional[str], default: None
+    ///     The encoding to encode to, defaults to the format of `options`.
+    /// strip_metadata: bool, default: True
+    ///     Whether to leave out the metadata (EXIF, ICC profiles, text chunks, comments) carried over from the decoded sequence.
+    ///     Metadata is only ever written back to the format it was read from.
+    /// workers: Optional[int], default: None
+    ///     The amount of threads GIF frames are quantized on, defaults to one per core.
+    ///     This has no effect on other encodings, or unless ril was built with the `parallel` feature, see :data:`PARALLEL`.
+    /// options: Optional[Union[:class:`.PngOptions`, :class:`.GifOptions`, :class:`.WebPOptions`]], default: None
+    ///     The options to encode with. WebP options can only encode sequences of a single frame.
+    /// deterministic: bool, default: False
+    ///     Whether to pin the encoder settings so the output is byte-for-byte identical for the same sequence,
+    ///     see :meth:`Image.encode`. Only supported for PNG and GIF.
+    /// reuse_palette: bool, default: False
+    ///     Whether to write frames decoded from a GIF with their original palettes and indices when encoding GIF,
+    ///     rather than quantizing them again. This avoids bloating a GIF that is decoded and encoded again.
+    ///     Only the frames before the first modified one are reused, since every later frame is drawn over it.
+    ///     Any method that modifies the frames in place, such as :meth:`resize`, counts as modifying all of them.
+    ///     This has no effect on other encodings, or on sequences that weren't decoded from a GIF.
     ///
     /// Returns
     /// -------
     /// bytes
     ///     The encoded bytes.
-    fn encode(&self, encoding: &str) -> Result<&PyBytes, Error> {
-        let encoding = ImageFormat::from_extension(encoding)?;
+    ///
+    /// Raises
+    /// ------
+    /// UnsupportedFormatError
+    ///     The encoding is not one of :meth:`supported_encodings` for the amount of frames of this sequence.
+    /// ValueError
+    ///     `workers` is 0, neither an encoding nor options are given, or they are for different formats.
+    ///     This is also raised if the output can't be made deterministic, see :meth:`Image.encode`.
+    ///
+    /// .. note::
+    ///     Every GIF frame is quantized independently, so the output is identical for any amount of workers.
+    ///     The GIL is released while encoding.
+    #[pyo3(signature = (encoding = None, strip_metadata = true, workers = None, options = None, deterministic = false, reuse_palette = false))]
+    #[pyo3(
+        text_signature = "($self, encoding = None, strip_metadata = True, workers = None, options = None, deterministic = False, reuse_palette = False)"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    fn encode<'a>(
+        &self,
+        py: Python<'a>,
+        encoding: Option<&str>,
+        strip_metadata: bool,
+        workers: Option<usize>,
+        options: Option<EncodeOptions>,
+        deterministic: bool,
+        reuse_palette: bool,
+    ) -> PyResult<&'a PyBytes> {
+        ensure_open!(self);
 
-        let mut buf = Vec::new();
-        self.inner.encode(encoding, &mut buf)?;
-
-        // SAFETY: We acquired the GIL before calling `assume_gil_acquired`.
-        // `assume_gil_acquired` is only used to ensure that PyBytes don't outlive the current function
-        unsafe {
-            Python::with_gil(|_| {
-                let buf = buf.as_slice();
-                let pyacq = Python::assume_gil_acquired();
-                Ok(PyBytes::new(pyacq, buf))
-            })
+        if workers == Some(0) {
+            return Err(PyValueError::new_err("Expected at least one worker"));
         }
+
+        let options = options.as_ref();
+        let encoding = resolve_sequence_format(encoding, options, None, self.inner.len())?;
+        let (inner, metadata) = (&self.inner, &self.metadata);
+        let gif_source = self.gif_source.as_ref().filter(|_| reuse_palette);
+
+        let buf = py.allow_threads(|| {
+            encode_sequence(
+                inner,
+                metadata,
+                encoding,
+                options,
+                strip_metadata,
+                deterministic,
+                workers,
+                gif_source,
+            )
+        })?;
+
+        Ok(PyBytes::new(py, &buf))
     }
 
     /// Saves the image to the given path.
@@ -213,37 +1008,1634 @@ impl ImageSequence {
     /// ----------
     /// path: str
     ///     The path to the image.
+    /// encoding: Optional[str], default: None
+    ///     The encoding of the image, defaults to `None`.
+    /// strip_metadata: bool, default: True
+    ///     Whether to leave out the metadata carried over from the decoded sequence, see :meth:`encode`.
+    /// options: Optional[Union[:class:`.PngOptions`, :class:`.GifOptions`, :class:`.WebPOptions`]], default: None
+    ///     The options to encode with, see :meth:`encode`. If no encoding is given, the format of `options` is used.
+    /// deterministic: bool, default: False
+    ///     Whether to pin the encoder settings for reproducible output, see :meth:`encode`.
+    /// reuse_palette: bool, default: False
+    ///     Whether to write unmodified GIF frames with their original palettes, see :meth:`encode`.
     ///
     /// Raises
     /// ------
+    /// UnsupportedFormatError
+    ///     The encoding, or the file extension if no encoding is given, is not one of :meth:`supported_encodings`
+    ///    for the amount of frames of this sequence.
     /// ValueError
-    ///     The file extension is invalid.
+    ///     The encoding and options are for different formats.
+    ///     This is also raised if the output can't be made deterministic, see :meth:`Image.encode`.
     /// RuntimeError
     ///     Failed to infer file format or Failed to decode image.
-    fn save(&self, path: PathBuf, encoding: Option<&str>) -> Result<(), Error> {
-        if let Some(encoding) = encoding {
-            let encoding = ImageFormat::from_extension(encoding)?;
-            self.inner.save(encoding, path)?;
-        } else {
-            self.inner.save_inferred(path)?;
-        }
+    ///
+    /// .. note::
+    ///     The GIL is released while encoding and writing the file.
+    #[pyo3(signature = (path, encoding = None, strip_metadata = true, options = None, deterministic = false, reuse_palette = false))]
+    #[pyo3(
+        text_signature = "($self, path, encoding = None, strip_metadata = True, options = None, deterministic = False, reuse_palette = False)"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    fn save(
+        &self,
+        py: Python<'_>,
+        path: PathBuf,
+        encoding: Option<&str>,
+        strip_metadata: bool,
+        options: Option<EncodeOptions>,
+        deterministic: bool,
+        reuse_palette: bool,
+    ) -> Result<(), Error> {
+        ensure_open!(self);
 
-        Ok(())
-    }
+        let (inner, metadata) = (&self.inner, &self.metadata);
+        let gif_source = self.gif_source.as_ref().filter(|_| reuse_palette);
 
-    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
-        slf
+        py.allow_threads(|| {
+            save_sequence(
+                inner,
+                metadata,
+                &path,
+                encoding,
+                options.as_ref(),
+                strip_metadata,
+                deterministic,
+                gif_source,
+            )
+        })
     }
 
-    fn __next__(mut slf: PyRefMut<'_, Self>) -> Option<Frame> {
-        slf.iter.next().map(|x| Frame { inner: x })
+    /// Like :meth:`save`, but encodes and writes a copy of the sequence on a separate thread without blocking the event loop.
+    ///
+    /// This must be called from a running :mod:`asyncio` event loop.
+    ///
+    /// Parameters
+    /// ----------
+    /// path: str
+    ///     The path to the image.
+    /// encoding: Optional[str], default: None
+    ///     The encoding of the image, defaults to `None`.
+    /// strip_metadata: bool, default: True
+    ///     Whether to leave out the metadata carried over from the decoded sequence, see :meth:`encode`.
+    /// options: Optional[Union[:class:`.PngOptions`, :class:`.GifOptions`, :class:`.WebPOptions`]], default: None
+    ///     The options to encode with, see :meth:`encode`.
+    /// deterministic: bool, default: False
+    ///     Whether to pin the encoder settings for reproducible output, see :meth:`encode`.
+    /// reuse_palette: bool, default: False
+    ///     Whether to write unmodified GIF frames with their original palettes, see :meth:`encode`.
+    ///
+    /// Returns
+    /// -------
+    /// Awaitable[None]
+    ///     A future resolving once the sequence is saved, raising the same errors as :meth:`save`.
+    ///
+    /// Raises
+    /// ------
+    /// RuntimeError
+    ///     There is no running event loop.
+    #[pyo3(signature = (path, encoding = None, strip_metadata = true, options = None, deterministic = false, reuse_palette = false))]
+    #[pyo3(
+        text_signature = "($self, path, encoding = None, strip_metadata = True, options = None, deterministic = False, reuse_palette = False)"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    fn save_async<'a>(
+        &self,
+        py: Python<'a>,
+        path: PathBuf,
+        encoding: Option<String>,
+        strip_metadata: bool,
+        options: Option<EncodeOptions>,
+        deterministic: bool,
+        reuse_palette: bool,
+    ) -> PyResult<&'a PyAny> {
+        ensure_open!(self);
+
+        let (inner, metadata) = (self.inner.clone(), self.metadata.clone());
+        let gif_source = self.gif_source.clone().filter(|_| reuse_palette);
+
+        spawn_blocking(py, move || {
+            let (encoding, options) = (encoding.as_deref(), options.as_ref());
+
+            save_sequence(
+                &inner,
+                &metadata,
+                &path,
+                encoding,
+                options,
+                strip_metadata,
+                deterministic,
+                gif_source.as_ref(),
+            )
+        })
     }
 
-    fn __len__(&self) -> usize {
-        self.inner.len()
+    /// Returns a new iterator over every frame of this sequence, starting at the first frame.
+    fn __iter__(slf: PyRef<'_, Self>) -> PyResult<ImageSequenceIterator> {
+        ensure_open!(slf);
+
+        Ok(ImageSequenceIterator {
+            sequence: slf.into(),
+            index: 0,
+        })
     }
 
-    fn __repr__(&self) -> String {
-        format!("<ImageSequence len={}>", self.__len__())
+    /// Returns the frame at the position of this sequence and advances it, see :meth:`seek`.
+    fn __next__(mut slf: PyRefMut<'_, Self>) -> PyResult<Option<Frame>> {
+        ensure_open!(slf);
+
+        let frame = slf.inner.iter().nth(slf.position).cloned();
+        if frame.is_some() {
+            slf.position += 1;
+        }
+
+        Ok(frame.map(Frame::from_inner))
     }
+
+    /// Moves the position of this sequence so that the next frame ``next(seq)`` returns is the frame at `index`.
+    ///
+    /// The position is separate from iterating over the sequence, which always starts at the first frame.
+    ///
+    /// Parameters
+    /// ----------
+    /// index: int
+    ///     The index of the next frame to return. Seeking to the length of the sequence exhausts it.
+    ///
+    /// Raises
+    /// ------
+    /// IndexError
+    ///     `index` is greater than the length of the sequence.
+    #[pyo3(text_signature = "($self, index)")]
+    fn seek(&mut self, index: usize) -> PyResult<()> {
+        ensure_open!(self);
+
+        if index > self.inner.len() {
+            return Err(PyIndexError::new_err(format!(
+                "Cannot seek to frame {} of a sequence with {} frames",
+                index,
+                self.inner.len()
+            )));
+        }
+
+        self.position = index;
+
+        Ok(())
+    }
+
+    /// Returns the index of the next frame ``next(seq)`` returns, see :meth:`seek`.
+    ///
+    /// Returns
+    /// -------
+    /// int
+    #[pyo3(text_signature = "($self)")]
+    fn tell(&self) -> PyResult<usize> {
+        ensure_open!(self);
+
+        Ok(self.position)
+    }
+
+    /// List[:class:`.Frame`]: Returns every frame of this sequence, regardless of its position.
+    ///
+    /// A new list of copied frames is returned on every access, so changes made to them are not reflected in this sequence.
+    /// Create a new sequence with :meth:`from_frames` to apply them.
+    #[getter]
+    fn frames(&self) -> PyResult<Vec<Frame>> {
+        ensure_open!(self);
+
+        Ok(self
+            .inner
+            .iter()
+            .map(|frame| Frame::from_inner(frame.clone()))
+            .collect())
+    }
+
+    /// Tuple[int, int]: Returns the dimensions of the first frame of this sequence.
+    ///
+    /// Frames of a sequence usually share their dimensions, unless it was created with :meth:`from_frames`.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The sequence has no frames.
+    #[getter]
+    fn dimensions(&self) -> PyResult<Xy> {
+        ensure_open!(self);
+
+        self.inner
+            .iter()
+            .next()
+            .map(RilFrame::dimensions)
+            .ok_or_else(|| PyValueError::new_err("An empty sequence has no dimensions"))
+    }
+
+    /// int: Returns the sum of the delays of every frame of this sequence in milliseconds.
+    #[getter]
+    fn total_duration(&self) -> PyResult<u128> {
+        ensure_open!(self);
+
+        Ok(self
+            .inner
+            .iter()
+            .map(|frame| frame.delay().as_millis())
+            .sum())
+    }
+
+    /// Returns the global palette of the data this sequence was decoded from, which is the global color table of GIF data
+    /// and the palette of indexed PNG data.
+    ///
+    /// The palette is read as is, it is not updated when the frames of this sequence change.
+    ///
+    /// Returns
+    /// -------
+    /// Optional[List[:class:`.Pixel`]]
+    ///     The colors of the palette in order, or ``None`` if the data had no global palette or this sequence was not decoded.
+    #[pyo3(text_signature = "($self)")]
+    fn palette(&self) -> PyResult<Option<Vec<Pixel>>> {
+        ensure_open!(self);
+
+        Ok(self.palette.as_ref().map(|palette| {
+            palette
+                .iter()
+                .map(|&[r, g, b]| Pixel::from(Dynamic::Rgb(ril::Rgb { r, g, b })))
+                .collect()
+        }))
+    }
+
+    /// List[str]: Returns the warnings from decoding this sequence with `tolerant` set, describing what was recovered.
+    ///
+    /// This is empty if the data decoded cleanly, and for sequences that were not decoded with `tolerant` set.
+    /// See :meth:`from_bytes`.
+    #[getter]
+    fn decode_warnings(&self) -> PyResult<Vec<String>> {
+        ensure_open!(self);
+
+        Ok(self.decode_warnings.clone())
+    }
+
+    /// List[int]: Returns the indices of the transparent frames whose disposal was switched to
+    /// :attr:`.DisposalMethod.Background` when this sequence was created with `fix_disposal` set.
+    ///
+    /// This is empty if no frame was switched, and for sequences that were not created with :meth:`from_frames`.
+    /// The indices are not updated when frames are inserted or removed afterwards.
+    #[getter]
+    fn adjusted_frames(&self) -> PyResult<Vec<usize>> {
+        ensure_open!(self);
+
+        Ok(self.adjusted_frames.clone())
+    }
+
+    /// Crops every frame of this sequence in place to the given bounding box, see :meth:`Image.crop`.
+    ///
+    /// Parameters
+    /// ----------
+    /// x1: int
+    ///    The x axis of the upper-left corner
+    /// y1: int
+    ///     The y axis of the upper-left corner
+    /// x2: int
+    ///     The x axis of the lower-right corner
+    /// y2: int
+    ///     The y axis of the lower-right corner
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     The bounding box is empty or exceeds the bounds of any frame.
+    #[pyo3(text_signature = "($self, x1, y1, x2, y2)")]
+    fn crop(&mut self, py: Python<'_>, x1: u32, y1: u32, x2: u32, y2: u32) -> PyResult<()> {
+        ensure_open!(self);
+
+        for frame in self.inner.iter() {
+            ensure_box(frame.dimensions(), x1, y1, x2, y2)?;
+        }
+
+        self.map_frames(py, |image| image.crop(x1, y1, x2, y2));
+
+        Ok(())
+    }
+
+    /// Resizes every frame of this sequence in place to the given dimensions using the given resizing algorithm.
+    ///
+    /// Parameters
+    /// ----------
+    /// width: int
+    ///     The target width to resize to
+    /// height: int
+    ///     The target height to resize to
+    /// algorithm: :class:`.ResizeAlgorithm`
+    ///     The resize algorithm to use
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     `width` or `height` is zero.
+    #[pyo3(text_signature = "($self, width, height, algorithm)")]
+    fn resize(
+        &mut self,
+        py: Python<'_>,
+        width: u32,
+        height: u32,
+        algorithm: ResizeAlgorithm,
+    ) -> PyResult<()> {
+        ensure_open!(self);
+
+        ensure_dimensions(width, height)?;
+
+        let span = trace::span("resize", self.dimensions().ok());
+        self.map_frames(py, |image| {
+            *image = resized(image, (width, height), algorithm);
+        });
+        span.end((width, height));
+
+        Ok(())
+    }
+
+    /// Converts every frame of this sequence in place to the given mode.
+    ///
+    /// Parameters
+    /// ----------
+    /// mode: Union[:class:`.Mode`, str]
+    ///     The mode to convert to.
+    #[pyo3(text_signature = "($self, mode)")]
+    fn convert(&mut self, py: Python<'_>, mode: ModeArg) -> PyResult<()> {
+        ensure_open!(self);
+
+        let ModeArg(mode) = mode;
+
+        self.map_frames(py, |image| {
+            image
+                .data
+                .iter_mut()
+                .for_each(|pixel| *pixel = mode.convert_pixel(*pixel));
+        });
+
+        Ok(())
+    }
+
+    /// Mirrors, or flips every frame of this sequence horizontally (about the y-axis) in place.
+    #[pyo3(text_signature = "($self)")]
+    fn mirror(&mut self, py: Python<'_>) -> PyResult<()> {
+        ensure_open!(self);
+
+        self.map_frames(py, |image| {
+            image.mirror();
+        });
+
+        Ok(())
+    }
+
+    /// Flips every frame of this sequence vertically (about the x-axis) in place.
+    #[pyo3(text_signature = "($self)")]
+    fn flip(&mut self, py: Python<'_>) -> PyResult<()> {
+        ensure_open!(self);
+
+        self.map_frames(py, |image| {
+            image.flip();
+        });
+
+        Ok(())
+    }
+
+    /// Inverts every frame of this sequence in place.
+    #[pyo3(text_signature = "($self)")]
+    fn invert(&mut self, py: Python<'_>) -> PyResult<()> {
+        ensure_open!(self);
+
+        self.map_frames(py, |image| {
+            image
+                .data
+                .iter_mut()
+                .for_each(|pixel| *pixel = pixel.inverted());
+        });
+
+        Ok(())
+    }
+
+    /// Removes consecutive frames that are identical to the frame before them, such as the still parts of a screen recording.
+    ///
+    /// Frames only count as identical if they have the same dimensions. The position is moved back to the first frame.
+    ///
+    /// Parameters
+    /// ----------
+    /// merge_delays: bool, default: True
+    ///     Whether to add the delays of the removed frames onto the frame they duplicate, so the total duration is preserved.
+    /// tolerance: int, default: 0
+    ///     The largest difference of any channel between two pixels for them to still count as identical,
+    ///     so that visually identical frames are removed too. Pixels of different modes are compared as RGBA when a tolerance is given.
+    ///
+    /// Returns
+    /// -------
+    /// int
+    ///     The amount of frames removed.
+    #[pyo3(signature = (merge_delays = true, tolerance = 0))]
+    #[pyo3(text_signature = "($self, merge_delays = True, tolerance = 0)")]
+    fn deduplicate(
+        &mut self,
+        py: Python<'_>,
+        merge_delays: bool,
+        tolerance: u8,
+    ) -> PyResult<usize> {
+        ensure_open!(self);
+
+        let inner = &self.inner;
+        let frames = py.allow_threads(|| {
+            let mut frames: Vec<RilFrame<Dynamic>> = Vec::with_capacity(inner.len());
+
+            for frame in inner.iter() {
+                match frames.last_mut() {
+                    Some(last) if frames_match(last.image(), frame.image(), tolerance) => {
+                        if merge_delays {
+                            last.set_delay(last.delay() + frame.delay());
+                        }
+                    }
+                    _ => frames.push(frame.clone()),
+                }
+            }
+
+            frames
+        });
+
+        let removed = self.inner.len() - frames.len();
+        self.inner = RilImageSequence::from_frames(frames).with_loop_count(self.inner.loop_count());
+        self.position = 0;
+        self.gif_source = None;
+
+        Ok(removed)
+    }
+
+    /// Returns a new sequence of the frames from `start` up to, but not including, `stop`, taking every `step`-th frame.
+    ///
+    /// Negative indices count from the end like they do in slices, and indices out of range are clamped.
+    /// The loop count and metadata are kept. This is also what slicing the sequence, such as ``seq[10:40]``, does.
+    ///
+    /// Parameters
+    /// ----------
+    /// start: int
+    ///     The index of the first frame.
+    /// stop: Optional[int]
+    ///     The index to stop before. Defaults to the length of the sequence.
+    /// step: int, default: 1
+    ///     The distance between the indices of the selected frames.
+    ///
+    /// Returns
+    /// -------
+    /// :class:`.ImageSequence`
+    ///     The selected frames.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     `step` is zero or negative.
+    #[pyo3(signature = (start, stop = None, step = 1))]
+    #[pyo3(text_signature = "($self, start, stop = None, step = 1)")]
+    fn subsequence(&self, start: isize, stop: Option<isize>, step: isize) -> PyResult<Self> {
+        ensure_open!(self);
+
+        if step <= 0 {
+            return Err(PyValueError::new_err("Step must be positive"));
+        }
+
+        let len = self.inner.len() as isize;
+        let clamp = |index: isize| {
+            let index = if index < 0 { index + len } else { index };
+
+            index.clamp(0, len) as usize
+        };
+        let (start, stop) = (clamp(start), clamp(stop.unwrap_or(len)));
+
+        Ok(self.select((start..stop.max(start)).step_by(step as usize)))
+    }
+
+    /// Returns a new sequence of what is shown between the two timestamps, in milliseconds since the first frame.
+    ///
+    /// Frames are selected by the timestamps their delays add up to. The delays of the first and last frame are shortened
+    /// to the part of them that lies between the timestamps, so the total duration is ``stop_ms - start_ms``.
+    /// Timestamps out of range are clamped to the duration of the sequence. The loop count and metadata are kept.
+    ///
+    /// Parameters
+    /// ----------
+    /// start_ms: float
+    ///     The timestamp to start at.
+    /// stop_ms: float
+    ///     The timestamp to stop at.
+    ///
+    /// Returns
+    /// -------
+    /// :class:`.ImageSequence`
+    ///     The trimmed sequence.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     `stop_ms` is not after `start_ms` once both are clamped.
+    #[pyo3(text_signature = "($self, start_ms, stop_ms)")]
+    fn trim(&self, start_ms: f64, stop_ms: f64) -> PyResult<Self> {
+        ensure_open!(self);
+
+        let total: Duration = self.inner.iter().map(RilFrame::delay).sum();
+        let clamp = |ms: f64| Duration::from_nanos((ms * 1e6).round().max(0.0) as u64).min(total);
+        let (start, stop) = (clamp(start_ms), clamp(stop_ms));
+
+        if stop <= start {
+            return Err(PyValueError::new_err(format!(
+                "Cannot trim a sequence of {}ms from {}ms to {}ms",
+                total.as_millis(),
+                start_ms,
+                stop_ms
+            )));
+        }
+
+        let mut frames = Vec::new();
+        let mut timestamp = Duration::ZERO;
+
+        for frame in self.inner.iter() {
+            let (begin, end) = (timestamp, timestamp + frame.delay());
+            timestamp = end;
+
+            // Frames without a delay are shown at an instant, which is kept if it lies in range.
+            if begin >= stop || (end <= start && !(end == begin && begin >= start)) {
+                continue;
+            }
+
+            let mut frame = frame.clone();
+            frame.set_delay(end.min(stop).saturating_sub(begin.max(start)));
+            frames.push(frame);
+        }
+
+        Ok(self.derived(frames))
+    }
+
+    fn __getitem__(&self, py: Python<'_>, index: SequenceIndex<'_>) -> PyResult<PyObject> {
+        ensure_open!(self);
+
+        let len = self.inner.len();
+
+        match index {
+            SequenceIndex::Index(index) => {
+                let index = self.frame_index(index)?;
+                let frame = self.inner.iter().nth(index).cloned();
+
+                Ok(frame.map(Frame::from_inner).into_py(py))
+            }
+            SequenceIndex::Slice(slice) => {
+                let indices = slice.indices(len as _)?;
+                let selected = (0..indices.slicelength as isize)
+                    .map(|i| (indices.start + i * indices.step) as usize);
+
+                Ok(self.select(selected).into_py(py))
+            }
+        }
+    }
+
+    /// Replaces the frame at the given index in place, negative indices count from the end.
+    ///
+    /// Frames yielded afterwards, including by an iteration in progress, reflect the change.
+    fn __setitem__(&mut self, index: isize, frame: Frame) -> PyResult<()> {
+        ensure_open!(self);
+
+        let index = self.frame_index(index)?;
+        if let Some(slot) = self.inner.iter_mut().nth(index) {
+            *slot = frame.into_inner();
+        }
+        self.frames_changed(index);
+
+        Ok(())
+    }
+
+    /// Inserts a frame before the given index, like :meth:`list.insert`.
+    ///
+    /// Negative indices count from the end, and indices out of range insert at the start or the end.
+    /// The position of the sequence keeps pointing at the frame it pointed at before.
+    ///
+    /// Parameters
+    /// ----------
+    /// index: int
+    ///     The index to insert the frame at.
+    /// frame: :class:`.Frame`
+    ///     The frame to insert.
+    #[pyo3(text_signature = "($self, index, frame)")]
+    fn insert(&mut self, index: isize, frame: Frame) -> PyResult<()> {
+        ensure_open!(self);
+
+        let len = self.inner.len() as isize;
+        let index = if index < 0 { index + len } else { index };
+        let index = index.clamp(0, len) as usize;
+
+        let mut frames = self.inner.iter().cloned().collect::<Vec<_>>();
+        frames.insert(index, frame.into_inner());
+        if index < self.position {
+            self.position += 1;
+        }
+        self.set_frames(frames, index);
+
+        Ok(())
+    }
+
+    /// Appends a frame to the end of this sequence.
+    ///
+    /// Parameters
+    /// ----------
+    /// frame: :class:`.Frame`
+    ///     The frame to append.
+    #[pyo3(text_signature = "($self, frame)")]
+    fn append(&mut self, frame: Frame) -> PyResult<()> {
+        ensure_open!(self);
+
+        self.insert(self.inner.len() as isize, frame)
+    }
+
+    /// Removes the frame at the given index and returns it, like :meth:`list.pop`.
+    ///
+    /// Negative indices count from the end. The position of the sequence keeps pointing at the frame it pointed at before.
+    ///
+    /// Parameters
+    /// ----------
+    /// index: int
+    ///     The index of the frame to remove.
+    ///
+    /// Returns
+    /// -------
+    /// :class:`.Frame`
+    ///     The removed frame.
+    ///
+    /// Raises
+    /// ------
+    /// IndexError
+    ///     The index is out of range.
+    #[pyo3(text_signature = "($self, index)")]
+    fn remove(&mut self, index: isize) -> PyResult<Frame> {
+        ensure_open!(self);
+
+        let index = self.frame_index(index)?;
+        let mut frames = self.inner.iter().cloned().collect::<Vec<_>>();
+        let frame = frames.remove(index);
+        if index < self.position {
+            self.position -= 1;
+        }
+        self.set_frames(frames, index);
+
+        Ok(Frame::from_inner(frame))
+    }
+
+    /// Composites another sequence or a static image over every frame of this sequence, returning a new sequence.
+    ///
+    /// Every output frame is a frame of this sequence with the corresponding frame of `other` pasted at the given position,
+    /// like :meth:`Image.paste`. The output has as many frames as the longer of the two,
+    /// and its delays, disposals and loop count come from this sequence.
+    ///
+    /// Parameters
+    /// ----------
+    /// other: Union[:class:`.ImageSequence`, :class:`.Image`]
+    ///     The sequence or image to paste. An image is pasted onto every frame.
+    /// x: int
+    ///     The x axis
+    /// y: int
+    ///     The y axis
+    /// loop_shorter: bool, default: True
+    ///     Whether the shorter of the two sequences loops, instead of freezing on its last frame.
+    ///
+    /// Returns
+    /// -------
+    /// :class:`.ImageSequence`
+    ///     The composited sequence.
+    ///
+    /// Raises
+    /// ------
+    /// TypeError
+    ///     `other` is neither a sequence nor an image.
+    /// ValueError
+    ///     Either sequence is empty.
+    ///
+    /// Examples
+    /// --------
+    ///
+    /// .. code-block:: python3
+    ///
+    ///     animated = background.overlay(ImageSequence.open('sticker.gif'), 10, 10)
+    #[pyo3(signature = (other, x, y, *, loop_shorter = true))]
+    #[pyo3(text_signature = "($self, other, x, y, *, loop_shorter = True)")]
+    fn overlay(
+        &self,
+        py: Python<'_>,
+        other: &PyAny,
+        x: u32,
+        y: u32,
+        loop_shorter: bool,
+    ) -> PyResult<Self> {
+        ensure_open!(self);
+
+        let overlays = if let Ok(other) = other.extract::<PyRef<'_, Self>>() {
+            ensure_open!(other);
+
+            other
+                .inner
+                .iter()
+                .map(|frame| frame.image().clone())
+                .collect::<Vec<_>>()
+        } else if let Ok(image) = other.extract::<Image>() {
+            ensure_open!(image);
+
+            vec![image.inner.into_inner()]
+        } else {
+            return Err(PyTypeError::new_err(format!(
+                "Expected an ImageSequence or an Image, got {}",
+                other.get_type().name()?
+            )));
+        };
+
+        if self.inner.is_empty() || overlays.is_empty() {
+            return Err(PyValueError::new_err("Cannot overlay an empty sequence"));
+        }
+
+        let base = &self.inner;
+        let frames = py.allow_threads(|| {
+            let frames = base.iter().collect::<Vec<_>>();
+            let pick = |len: usize, i: usize| if loop_shorter { i % len } else { i.min(len - 1) };
+
+            (0..frames.len().max(overlays.len()))
+                .map(|i| {
+                    let mut frame = frames[pick(frames.len(), i)].clone();
+                    paste_reconciled(frame.image_mut(), x, y, &overlays[pick(overlays.len(), i)]);
+
+                    frame
+                })
+                .collect::<Vec<_>>()
+        });
+
+        Ok(self.derived(frames))
+    }
+
+    /// Lays the frames of this sequence out into a sprite sheet, left to right, then top to bottom.
+    ///
+    /// Cells left over in the last row are transparent, or black for modes without alpha.
+    /// The sheet has the mode of the first frame, and delays and disposals are not kept.
+    /// See :meth:`from_spritesheet` for the inverse.
+    ///
+    /// Parameters
+    /// ----------
+    /// columns: int, default: 1
+    ///     The amount of frames per row, the default lays the frames out in a vertical strip.
+    ///     More columns than there are frames lay the frames out in a single row.
+    ///
+    /// Returns
+    /// -------
+    /// :class:`.Image`
+    ///     The sprite sheet.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     `columns` is zero, the sequence is empty, its frames don't all have the same dimensions,
+    ///     or the sheet would be wider or higher than 4294967295 pixels.
+    #[pyo3(signature = (columns = 1))]
+    #[pyo3(text_signature = "($self, columns = 1)")]
+    fn to_spritesheet(&self, py: Python<'_>, columns: u32) -> PyResult<Image> {
+        ensure_open!(self);
+
+        if columns == 0 {
+            return Err(PyValueError::new_err("Expected at least 1 column"));
+        }
+
+        let first = self.ensure_uniform("lay out")?;
+        let (frame_width, frame_height) = first.dimensions();
+        let mode = Mode::of(first);
+
+        // There are never more columns than frames, so a sheet is never wider than it needs to be.
+        let len = u32::try_from(self.inner.len()).unwrap_or(u32::MAX);
+        let columns = columns.min(len);
+        let rows = (len - 1) / columns + 1;
+        let (width, height) = columns
+            .checked_mul(frame_width)
+            .zip(rows.checked_mul(frame_height))
+            .ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "A sprite sheet of {} by {} frames of {}x{} is too large",
+                    columns, rows, frame_width, frame_height
+                ))
+            })?;
+
+        let inner = &self.inner;
+        let sheet = py.allow_threads(|| {
+            let mut sheet = RilImage::new(
+                width,
+                height,
+                mode.convert_pixel(Dynamic::Rgba(ril::Rgba { r: 0, g: 0, b: 0, a: 0 })),
+            );
+
+            for (i, frame) in (0..).zip(inner.iter()) {
+                let image = mode.convert_image(frame.image().clone());
+                let (x, y) = (i % columns * frame_width, i / columns * frame_height);
+
+                paste_clipped(&mut sheet, x, y, &image, ril::OverlayMode::Replace);
+            }
+
+            sheet
+        });
+
+        Ok(Image::from_inner(sheet))
+    }
+
+    /// Copies the frames of this sequence into a numpy array of shape ``(frames, height, width, channels)``,
+    /// such as for feeding them to a video model.
+    ///
+    /// Frames are converted to `mode`, where `L` and `BITPIXEL` frames have a single channel and a
+    /// :class:`.BitPixel` is either ``0`` or ``255``. See :meth:`from_numpy` for the inverse.
+    ///
+    /// Parameters
+    /// ----------
+    /// mode: Union[:class:`.Mode`, str], default: 'RGBA'
+    ///     The mode to convert the frames to.
+    ///
+    /// Returns
+    /// -------
+    /// numpy.ndarray
+    ///     A new array of dtype `uint8`.
+    ///
+    /// Raises
+    /// ------
+    /// ImportError
+    ///     numpy is not installed.
+    /// ValueError
+    ///     The sequence is empty, or its frames don't all have the same dimensions.
+    #[pyo3(signature = (mode = ModeArg(Mode::Rgba)))]
+    #[pyo3(text_signature = "($self, mode = 'RGBA')")]
+    fn to_numpy<'a>(&self, py: Python<'a>, mode: ModeArg) -> PyResult<&'a PyAny> {
+        ensure_open!(self);
+
+        let ModeArg(mode) = mode;
+        let (width, height) = self.ensure_uniform("convert")?.dimensions();
+        let channels = mode.channels();
+
+        let array = py.import("numpy")?.call_method1(
+            "empty",
+            ((self.inner.len(), height, width, channels), "uint8"),
+        )?;
+        let buffer = PyBuffer::<u8>::get(array)?;
+        let cells = buffer
+            .as_mut_slice(py)
+            .ok_or_else(|| PyBufferError::new_err("Expected a writable, contiguous array"))?;
+
+        let frame_len = (width * height) as usize * channels;
+        for (frame, cells) in self.inner.iter().zip(cells.chunks(frame_len)) {
+            let mut cells = cells.iter();
+            let mut write = |value: u8| {
+                if let Some(cell) = cells.next() {
+                    cell.set(value);
+                }
+            };
+
+            for &pixel in &frame.image().data {
+                match mode.convert_pixel(pixel) {
+                    Dynamic::BitPixel(v) => write(if v.0 { u8::MAX } else { 0 }),
+                    Dynamic::L(v) => write(v.0),
+                    Dynamic::Rgb(v) => [v.r, v.g, v.b].into_iter().for_each(&mut write),
+                    Dynamic::Rgba(v) => [v.r, v.g, v.b, v.a].into_iter().for_each(&mut write),
+                }
+            }
+        }
+
+        Ok(array)
+    }
+
+    /// Releases the frames of this sequence right away instead of when it is garbage collected, see :meth:`Image.close`.
+    ///
+    /// Every other method and attribute of a closed sequence, except :attr:`closed`, raises a `ValueError`.
+    /// Frames and images taken from the sequence before closing it are unaffected.
+    ///
+    /// Sequences can also be used as context managers, which close them on exit.
+    ///
+    /// Examples
+    /// --------
+    ///
+    /// .. code-block:: python3
+    ///
+    ///     with ImageSequence.open('large.gif') as seq:
+    ///         seq.resize(128, 128, ResizeAlgorithm.Nearest)
+    ///         seq.save('small.gif')
+    #[pyo3(text_signature = "($self)")]
+    fn close(&mut self) {
+        if self.closed {
+            return;
+        }
+
+        self.inner = RilImageSequence::from_frames(Vec::new());
+        self.position = 0;
+        self.metadata = Metadata::default();
+        self.palette = None;
+        self.gif_source = None;
+        self.closed = true;
+    }
+
+    /// bool: Whether the sequence is closed, see :meth:`close`.
+    #[getter]
+    fn closed(&self) -> bool {
+        self.closed
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyResult<PyRef<'_, Self>> {
+        ensure_open!(slf);
+
+        Ok(slf)
+    }
+
+    fn __exit__(&mut self, _exc_type: &PyAny, _exc_value: &PyAny, _traceback: &PyAny) {
+        self.close();
+    }
+
+    fn __len__(&self) -> PyResult<usize> {
+        ensure_open!(self);
+
+        Ok(self.inner.len())
+    }
+
+    /// Whether any frame has the same dimensions and pixels as the given :class:`.Frame` or :class:`.Image`.
+    ///
+    /// The delay and disposal of a frame are not compared.
+    fn __contains__(&self, item: &PyAny) -> PyResult<bool> {
+        ensure_open!(self);
+
+        let image = if let Ok(frame) = item.extract::<PyRef<'_, Frame>>() {
+            frame.image.clone()
+        } else if let Ok(image) = item.extract::<PyRef<'_, Image>>() {
+            ensure_open!(image);
+
+            image.inner.clone()
+        } else {
+            return Ok(false);
+        };
+
+        Ok(self
+            .inner
+            .iter()
+            .any(|frame| frames_match(frame.image(), &image, 0)))
+    }
+
+    /// Returns an ``<img>`` tag embedding the sequence as a GIF, used by Jupyter to display the sequence inline.
+    ///
+    /// Frames larger than the preview size are downscaled first, see :func:`set_notebook_preview_size`.
+    fn _repr_html_(&self, py: Python<'_>) -> PyResult<String> {
+        ensure_open!(self);
+
+        let frames = self
+            .inner
+            .iter()
+            .map(|frame| {
+                let mut preview = RilFrame::from_image(
+                    notebook_preview(frame.image()).unwrap_or_else(|| frame.image().clone()),
+                );
+                preview.set_delay(frame.delay());
+                preview.set_disposal(frame.disposal());
+
+                preview
+            })
+            .collect::<Vec<_>>();
+
+        let mut buf = Vec::new();
+        RilImageSequence::from_frames(frames)
+            .encode(ImageFormat::Gif, &mut buf)
+            .map_err(Error::from)?;
+
+        let encoded: String = py
+            .import("base64")?
+            .call_method1("b64encode", (PyBytes::new(py, &buf),))?
+            .call_method1("decode", ("ascii",))?
+            .extract()?;
+
+        Ok(format!("<img src=\"data:image/gif;base64,{}\" />", encoded))
+    }
+
+    fn __repr__(&self) -> String {
+        if self.closed {
+            return "<ImageSequence closed>".to_string();
+        }
+
+        let total_duration = self
+            .inner
+            .iter()
+            .map(|frame| frame.delay().as_millis())
+            .sum::<u128>();
+
+        match self.dimensions() {
+            Ok((width, height)) => format!(
+                "<ImageSequence len={} dimensions=({}, {}) total_duration={}ms>",
+                self.inner.len(),
+                width,
+                height,
+                total_duration
+            ),
+            Err(_) => format!("<ImageSequence len={}>", self.inner.len()),
+        }
+    }
+}
+
+/// An index or slice of the frames of a sequence.
+#[derive(FromPyObject)]
+enum SequenceIndex<'a> {
+    Index(isize),
+    Slice(&'a PySlice),
+}
+
+impl ImageSequence {
+    /// Creates a new sequence of the given frames, keeping the loop count, metadata and palette of this sequence.
+    fn derived(&self, frames: Vec<RilFrame<Dynamic>>) -> Self {
+        let inner = RilImageSequence::from_frames(frames).with_loop_count(self.inner.loop_count());
+
+        Self {
+            inner,
+            position: 0,
+            metadata: self.metadata.clone(),
+            palette: self.palette.clone(),
+            gif_source: None,
+            decode_warnings: Vec::new(),
+            adjusted_frames: Vec::new(),
+            closed: false,
+        }
+    }
+
+    /// Creates a new sequence of the frames at the given indices, which must be in range.
+    fn select(&self, indices: impl Iterator<Item = usize>) -> Self {
+        let frames = self.inner.iter().collect::<Vec<_>>();
+
+        self.derived(indices.map(|index| frames[index].clone()).collect())
+    }
+
+    /// Ensures the sequence has frames that all have the same dimensions, and returns the image of the first frame.
+    ///
+    /// `action` names what can't be done with an empty sequence in the error.
+    fn ensure_uniform(&self, action: &str) -> PyResult<&RilImage> {
+        let first = self
+            .inner
+            .iter()
+            .next()
+            .ok_or_else(|| PyValueError::new_err(format!("Cannot {} an empty sequence", action)))?
+            .image();
+        let dimensions = first.dimensions();
+
+        if let Some((i, frame)) = self
+            .inner
+            .iter()
+            .enumerate()
+            .find(|(_, frame)| frame.image().dimensions() != dimensions)
+        {
+            return Err(PyValueError::new_err(format!(
+                "Expected frame {} to have dimensions {:?}, got {:?}",
+                i,
+                dimensions,
+                frame.image().dimensions()
+            )));
+        }
+
+        Ok(first)
+    }
+
+    fn ensure_open(&self) -> Result<(), Error> {
+        if self.closed {
+            return Err(Error::Closed("image sequence"));
+        }
+
+        Ok(())
+    }
+
+    /// Resolves an index of a frame, where negative indices count from the end.
+    fn frame_index(&self, index: isize) -> PyResult<usize> {
+        let len = self.inner.len();
+        let position = if index < 0 {
+            index + len as isize
+        } else {
+            index
+        };
+
+        usize::try_from(position)
+            .ok()
+            .filter(|&position| position < len)
+            .ok_or_else(|| {
+                PyIndexError::new_err(format!(
+                    "Frame {} is out of range for a sequence with {} frames",
+                    index, len
+                ))
+            })
+    }
+
+    /// Replaces the frames of this sequence, whose frames from `index` on changed, keeping its loop count.
+    fn set_frames(&mut self, frames: Vec<RilFrame<Dynamic>>, index: usize) {
+        self.inner = RilImageSequence::from_frames(frames).with_loop_count(self.inner.loop_count());
+        self.frames_changed(index);
+    }
+
+    /// Marks the frames from `index` on as modified, so their source is no longer reused when encoding.
+    fn frames_changed(&mut self, index: usize) {
+        if let Some(gif_source) = &mut self.gif_source {
+            gif_source.modified_from(index, self.inner.len());
+        }
+    }
+
+    /// Applies `f` to the image of every frame in place, keeping the delays, disposals and loop count.
+    fn map_frames(&mut self, py: Python<'_>, f: impl Fn(&mut RilImage) + Send + Sync) {
+        let inner = &mut self.inner;
+        py.allow_threads(|| inner.iter_mut().for_each(|frame| f(frame.image_mut())));
+        self.gif_source = None;
+    }
+
+    fn open_path(path: &Path) -> Result<Self, Error> {
+        let format = format_from_path(path)?;
+        let bytes = fs::read(path)?;
+
+        let inner = decode_sequence(format, &bytes)?;
+        let metadata = Metadata::read(format, &bytes);
+        let palette = header::palette(format, &bytes);
+        let gif_source = GifSource::new(format, &bytes, inner.len());
+
+        Ok(Self {
+            inner,
+            position: 0,
+            metadata,
+            palette,
+            gif_source,
+            decode_warnings: Vec::new(),
+            adjusted_frames: Vec::new(),
+            closed: false,
+        })
+    }
+}
+
+/// An iterator over the frames of an image sequence, returned when iterating through :class:`.ImageSequence`.
+///
+/// Every iterator starts at the first frame and advances independently of other iterators and of :meth:`ImageSequence.seek`.
+/// Frames are read from the sequence as they are yielded, so frames replaced, inserted or removed during iteration are reflected.
+#[pyclass]
+pub struct ImageSequenceIterator {
+    sequence: Py<ImageSequence>,
+    index: usize,
+}
+
+#[pymethods]
+impl ImageSequenceIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<Frame>> {
+        let sequence = self.sequence.borrow(py);
+        ensure_open!(sequence);
+
+        let frame = sequence.inner.iter().nth(self.index).cloned();
+        if frame.is_some() {
+            self.index += 1;
+        }
+
+        Ok(frame.map(Frame::from_inner))
+    }
+}
+
+/// Whether the images have the same dimensions and no channel of any pixel differs by more than `tolerance`.
+fn frames_match(a: &RilImage, b: &RilImage, tolerance: u8) -> bool {
+    if a.dimensions() != b.dimensions() {
+        return false;
+    }
+
+    if tolerance == 0 {
+        return a.data == b.data;
+    }
+
+    a.data.iter().zip(&b.data).all(|(&a, &b)| {
+        let (a, b) = (ril::Rgba::from(a), ril::Rgba::from(b));
+
+        a.r.abs_diff(b.r) <= tolerance
+            && a.g.abs_diff(b.g) <= tolerance
+            && a.b.abs_diff(b.b) <= tolerance
+            && a.a.abs_diff(b.a) <= tolerance
+    })
+}
+
+/// The bounding box ``(x1, y1, x2, y2)`` of the pixels that differ between two images of the same dimensions.
+fn diff_bbox(a: &RilImage, b: &RilImage) -> Option<(u32, u32, u32, u32)> {
+    let width = a.width() as usize;
+    let mut bbox: Option<(u32, u32, u32, u32)> = None;
+
+    for (y, (a, b)) in a.data.chunks(width).zip(b.data.chunks(width)).enumerate() {
+        let first = match a.iter().zip(b).position(|(a, b)| a != b) {
+            Some(first) => first,
+            None => continue,
+        };
+        let last = a.iter().zip(b).rposition(|(a, b)| a != b).unwrap_or(first);
+
+        let (x1, x2, y) = (first as u32, last as u32 + 1, y as u32);
+        bbox = Some(match bbox {
+            Some((bx1, by1, bx2, _)) => (bx1.min(x1), by1, bx2.max(x2), y + 1),
+            None => (x1, y, x2, y + 1),
+        });
+    }
+
+    bbox
+}
+
+fn gif_error(err: impl std::fmt::Display) -> Error {
+    Error::Ril(RilError::EncodingError(err.to_string()))
+}
+
+/// Quantizes a frame to its own palette, independent of every other frame.
+fn quantize_gif_frame(
+    frame: &RilFrame<Dynamic>,
+    options: &GifOptions,
+) -> Result<gif::Frame<'static>, Error> {
+    let image = frame.image();
+    let (width, height) = image.dimensions();
+    let (width, height) = match (u16::try_from(width), u16::try_from(height)) {
+        (Ok(width), Ok(height)) => (width, height),
+        _ => return Err(gif_error("GIF frames can be at most 65535 pixels wide and high")),
+    };
+
+    let mut rgba = image
+        .data
+        .iter()
+        .flat_map(|&pixel| {
+            let ril::Rgba { r, g, b, a } = ril::Rgba::from(pixel);
+            [r, g, b, a]
+        })
+        .collect::<Vec<_>>();
+    options.apply_alpha(&mut rgba);
+
+    let mut out = if options.dither() {
+        quantize_dithered(width, height, &mut rgba, options.speed())
+    } else {
+        gif::Frame::from_rgba_speed(width, height, &mut rgba, options.speed())
+    };
+    out.delay = gif_delay(frame);
+    out.dispose = match frame.disposal() {
+        ril::DisposalMethod::None => gif::DisposalMethod::Keep,
+        ril::DisposalMethod::Background => gif::DisposalMethod::Background,
+        ril::DisposalMethod::Previous => gif::DisposalMethod::Previous,
+    };
+
+    Ok(out)
+}
+
+/// Returns the delay of a frame in hundredths of a second, which is how GIF delays are stored.
+fn gif_delay(frame: &RilFrame<Dynamic>) -> u16 {
+    u16::try_from(frame.delay().as_millis() / 10).unwrap_or(u16::MAX)
+}
+
+/// Like `gif::Frame::from_rgba_speed`, but diffuses the quantization error of opaque pixels with Floyd-Steinberg dithering.
+fn quantize_dithered(width: u16, height: u16, rgba: &mut [u8], speed: i32) -> gif::Frame<'static> {
+    let mut transparent = None;
+    for pixel in rgba.chunks_exact_mut(4) {
+        if pixel[3] == 0 {
+            transparent = Some([pixel[0], pixel[1], pixel[2], pixel[3]]);
+        } else {
+            pixel[3] = 0xFF;
+        }
+    }
+
+    let quantizer = color_quant::NeuQuant::new(speed, 256, rgba);
+    let (width, height) = (width as usize, height as usize);
+    let mut errors = vec![[0.0_f32; 3]; width * height];
+    let mut buffer = Vec::with_capacity(width * height);
+
+    for (i, pixel) in rgba.chunks_exact(4).enumerate() {
+        if pixel[3] == 0 {
+            buffer.push(quantizer.index_of(pixel) as u8);
+            continue;
+        }
+
+        let wanted: [f32; 3] =
+            std::array::from_fn(|c| (f32::from(pixel[c]) + errors[i][c]).clamp(0.0, 255.0));
+        let index = quantizer.index_of(&[
+            wanted[0].round() as u8,
+            wanted[1].round() as u8,
+            wanted[2].round() as u8,
+            0xFF,
+        ]);
+        buffer.push(index as u8);
+
+        let got = quantizer.lookup(index).unwrap_or([0; 4]);
+        let error: [f32; 3] = std::array::from_fn(|c| wanted[c] - f32::from(got[c]));
+        let (x, y) = (i % width, i / width);
+
+        let mut diffuse = |x: usize, y: usize, weight: f32| {
+            if x < width && y < height {
+                for (target, error) in errors[y * width + x].iter_mut().zip(error) {
+                    *target += error * weight;
+                }
+            }
+        };
+
+        diffuse(x + 1, y, 7.0 / 16.0);
+        if x > 0 {
+            diffuse(x - 1, y + 1, 3.0 / 16.0);
+        }
+        diffuse(x, y + 1, 5.0 / 16.0);
+        diffuse(x + 1, y + 1, 1.0 / 16.0);
+    }
+
+    gif::Frame {
+        width: width as u16,
+        height: height as u16,
+        buffer: buffer.into(),
+        palette: Some(quantizer.color_map_rgb()),
+        transparent: transparent.map(|pixel| quantizer.index_of(&pixel) as u8),
+        ..gif::Frame::default()
+    }
+}
+
+/// Encodes a sequence as GIF, quantizing the frames on up to `workers` threads and writing them in order.
+/// The GIF loops `loop_count` times unless the options override it, without either it has no loop extension and is played once.
+pub(crate) fn encode_gif(
+    inner: &RilImageSequence<Dynamic>,
+    loop_count: Option<ril::LoopCount>,
+    workers: Option<usize>,
+    options: &GifOptions,
+) -> Result<Vec<u8>, Error> {
+    write_gif(inner, ReusedFrames::default(), loop_count, workers, options)
+}
+
+/// Like `encode_gif`, but writes the `reused` frames as is in place of the first frames of the sequence.
+fn write_gif(
+    inner: &RilImageSequence<Dynamic>,
+    reused: ReusedFrames,
+    loop_count: Option<ril::LoopCount>,
+    workers: Option<usize>,
+    options: &GifOptions,
+) -> Result<Vec<u8>, Error> {
+    let frames = inner.iter().collect::<Vec<_>>();
+    let (width, height) = frames
+        .first()
+        .ok_or(RilError::EmptyImageError)?
+        .image()
+        .dimensions();
+
+    let span = trace::span("encode_frames", Some((width, height)));
+    let reused_count = reused.frames.len();
+    let quantized = map_ordered(&frames[reused_count..], workers, |frame| {
+        quantize_gif_frame(frame, options)
+    });
+
+    let mut buf = Vec::new();
+    let mut encoder = gif::Encoder::new(
+        &mut buf,
+        u16::try_from(width).unwrap_or(u16::MAX),
+        u16::try_from(height).unwrap_or(u16::MAX),
+        &reused.global_palette,
+    )
+    .map_err(gif_error)?;
+
+    if let Some(repeat) = options.repeat(loop_count) {
+        encoder.set_repeat(repeat).map_err(gif_error)?;
+    }
+
+    let written = reused.frames.into_iter().map(Ok).chain(quantized);
+    for (index, frame) in written.enumerate() {
+        encoder.write_frame(&frame?).map_err(gif_error)?;
+        span.progress(index + 1, frames.len());
+    }
+
+    encoder.into_inner().map_err(gif_error)?;
+
+    Ok(buf)
+}
+
+/// The GIF a sequence was decoded from, and which of its frames are unmodified since.
+///
+/// The bytes are kept rather than the indexed frames, so decoding isn't slowed down for sequences that are never
+/// re-encoded with `reuse_palette`. They are decoded again to palette indices when encoding.
+#[derive(Clone)]
+struct GifSource {
+    bytes: Arc<[u8]>,
+    clean: Vec<bool>,
+}
+
+/// Frames decoded to their original palette indices, along with the global color table they may refer to.
+#[derive(Default)]
+struct ReusedFrames {
+    global_palette: Vec<u8>,
+    frames: Vec<gif::Frame<'static>>,
+}
+
+impl GifSource {
+    fn new(format: ImageFormat, bytes: &[u8], frames: usize) -> Option<Self> {
+        (format == ImageFormat::Gif).then(|| Self {
+            bytes: bytes.into(),
+            clean: vec![true; frames],
+        })
+    }
+
+    /// Marks the frames from `index` on as modified, after frames were replaced, inserted or removed
+    /// so the sequence has `len` frames. Only the leading unmodified frames are reused, so this covers shifted frames.
+    fn modified_from(&mut self, index: usize, len: usize) {
+        self.clean.resize(len, false);
+        self.clean[index.min(len)..].fill(false);
+    }
+
+    /// Decodes the leading frames of the source that are unmodified in `inner`.
+    ///
+    /// Frames after the first modified one are never reused, since GIF frames are drawn over the frames before them.
+    /// Returns nothing to reuse if the source can't be decoded or its dimensions differ from `inner`.
+    fn reusable_frames(&self, inner: &RilImageSequence<Dynamic>) -> ReusedFrames {
+        self.decode_clean(inner).unwrap_or_default()
+    }
+
+    fn decode_clean(&self, inner: &RilImageSequence<Dynamic>) -> Option<ReusedFrames> {
+        let clean = self.clean.iter().take_while(|&&clean| clean).count();
+        if clean == 0 || self.clean.len() != inner.len() {
+            return None;
+        }
+
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::Indexed);
+        let mut decoder = options.read_info(&self.bytes[..]).ok()?;
+
+        let dimensions = (u32::from(decoder.width()), u32::from(decoder.height()));
+        if Some(dimensions) != inner.iter().next().map(RilFrame::dimensions) {
+            return None;
+        }
+
+        let global_palette = decoder.global_palette().unwrap_or_default().to_vec();
+        let mut frames = Vec::with_capacity(clean);
+        for frame in inner.iter().take(clean) {
+            let mut indexed = decoder.read_next_frame().ok()??.clone();
+            indexed.delay = gif_delay(frame);
+            frames.push(indexed);
+        }
+
+        Some(ReusedFrames {
+            global_palette,
+            frames,
+        })
+    }
+}
+
+fn decode_sequence(format: ImageFormat, bytes: &[u8]) -> Result<RilImageSequence<Dynamic>, Error> {
+    let span = trace::span("decode", None);
+    let inner = RilImageSequence::from_bytes(format, bytes)?.into_sequence()?;
+    if let Some(frame) = inner.iter().next() {
+        span.end(frame.dimensions());
+    }
+
+    Ok(inner)
+}
+
+/// Decodes as many frames as possible of data that is cut off or corrupt, keeping the frames before the first one
+/// that fails to decode. JPEG data is recovered like [`crate::image::decode_tolerant`] does, filling with `fill`.
+///
+/// Returns the sequence along with warnings describing what was recovered, which are empty if the data is intact.
+fn decode_sequence_tolerant(
+    format: ImageFormat,
+    bytes: &[u8],
+    fill: Dynamic,
+) -> Result<(RilImageSequence<Dynamic>, Vec<String>), Error> {
+    let err = match decode_sequence(format, bytes) {
+        Ok(inner) => return Ok((inner, Vec::new())),
+        Err(err) => err,
+    };
+
+    if format == ImageFormat::Jpeg {
+        let (image, warnings) = recover_jpeg(bytes, fill).ok_or(err)?;
+        let inner = RilImageSequence::from_frames(vec![RilFrame::from_image(image)]);
+
+        return Ok((inner, warnings));
+    }
+
+    let frames = RilImageSequence::<Dynamic>::from_bytes(format, bytes)
+        .into_iter()
+        .flatten()
+        .map_while(Result::ok)
+        .collect::<Vec<_>>();
+    if frames.is_empty() {
+        return Err(err);
+    }
+
+    let warning = format!(
+        "The data is cut off or corrupt after frame {}, the remaining frames were left out",
+        frames.len()
+    );
+
+    Ok((RilImageSequence::from_frames(frames), vec![warning]))
+}
+
+/// The encodings a sequence can be encoded to, by the names `ImageSequence.encode` accepts,
+/// and whether they can encode any amount of frames rather than only a single one.
+const SEQUENCE_ENCODINGS: &[(&str, ImageFormat, bool)] = &[
+    ("gif", ImageFormat::Gif, true),
+    ("png", ImageFormat::Png, true),
+    ("jpeg", ImageFormat::Jpeg, false),
+    #[cfg(feature = "webp")]
+    ("webp", ImageFormat::WebP, false),
+];
+
+/// The encodings a sequence of `frames` frames can be encoded to, or of any amount of frames if `None`.
+fn sequence_encodings(frames: Option<usize>) -> Vec<&'static str> {
+    SEQUENCE_ENCODINGS
+        .iter()
+        .filter(|&&(_, _, any_frames)| any_frames || frames.map_or(false, |frames| frames <= 1))
+        .map(|&(name, _, _)| name)
+        .collect()
+}
+
+/// Resolves the format to encode a sequence of `frames` frames to like `EncodeOptions::resolve_format`, falling back
+/// to the extension of `path`, and raises an `UnsupportedFormatError` for anything but the `SEQUENCE_ENCODINGS`
+/// that can encode that many frames.
+fn resolve_sequence_format(
+    encoding: Option<&str>,
+    options: Option<&EncodeOptions>,
+    path: Option<&Path>,
+    frames: usize,
+) -> Result<ImageFormat, Error> {
+    let requested = encoding.or_else(|| path?.extension()?.to_str());
+    let format = EncodeOptions::resolve_format(encoding, options, || {
+        path.map_or(Err(Error::MissingEncoding), format_from_path)
+    });
+
+    let is_supported = |format: ImageFormat| {
+        SEQUENCE_ENCODINGS
+            .iter()
+            .any(|&(_, supported, any_frames)| supported == format && (any_frames || frames <= 1))
+    };
+
+    match format {
+        Ok(format) if is_supported(format) => Ok(format),
+        Ok(format) => Err(Error::UnsupportedFormat(
+            requested.map_or_else(|| format.to_string(), str::to_string),
+            sequence_encodings(Some(frames)),
+        )),
+        Err(Error::Ril(RilError::InvalidExtension(_))) => Err(Error::UnsupportedFormat(
+            requested.unwrap_or_default().to_string(),
+            sequence_encodings(Some(frames)),
+        )),
+        Err(err) => Err(err),
+    }
+}
+
+/// Encodes a sequence, where GIF frames that are unmodified in `gif_source` are written with their original palette indices.
+#[allow(clippy::too_many_arguments)]
+fn encode_sequence(
+    inner: &RilImageSequence<Dynamic>,
+    metadata: &Metadata,
+    format: ImageFormat,
+    options: Option<&EncodeOptions>,
+    strip_metadata: bool,
+    deterministic: bool,
+    workers: Option<usize>,
+    gif_source: Option<&GifSource>,
+) -> Result<Vec<u8>, Error> {
+    let pinned = deterministic
+        .then(|| EncodeOptions::pin(format, options))
+        .transpose()?;
+
+    let _span = trace::span("encode", inner.iter().next().map(RilFrame::dimensions));
+    let reused = match gif_source {
+        Some(source) if format == ImageFormat::Gif => source.reusable_frames(inner),
+        _ => ReusedFrames::default(),
+    };
+    let loop_count = Some(inner.loop_count());
+
+    let buf = match (format, pinned.as_ref().or(options)) {
+        (ImageFormat::Gif, Some(EncodeOptions::Gif(options))) => {
+            write_gif(inner, reused, loop_count, workers, options)?
+        }
+        (_, Some(options)) => options.encode_sequence(inner, workers)?,
+        // Frames are only quantized here to spread them across threads or to reuse decoded frames,
+        // otherwise ril encodes the sequence.
+        (ImageFormat::Gif, None) if maps_in_parallel(workers) || !reused.frames.is_empty() => {
+            write_gif(inner, reused, loop_count, workers, &GifOptions::default())?
+        }
+        (format, None) => {
+            let mut buf = Vec::new();
+            inner.encode(format, &mut buf)?;
+
+            buf
+        }
+    };
+
+    match (strip_metadata, deterministic) {
+        (true, _) => Ok(buf),
+        (false, true) => Ok(metadata.without_timestamps().write(format, buf)),
+        (false, false) => Ok(metadata.write(format, buf)),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn save_sequence(
+    inner: &RilImageSequence<Dynamic>,
+    metadata: &Metadata,
+    path: &Path,
+    encoding: Option<&str>,
+    options: Option<&EncodeOptions>,
+    strip_metadata: bool,
+    deterministic: bool,
+    gif_source: Option<&GifSource>,
+) -> Result<(), Error> {
+    let encoding = resolve_sequence_format(encoding, options, Some(path), inner.len())?;
+    let buf = encode_sequence(
+        inner,
+        metadata,
+        encoding,
+        options,
+        strip_metadata,
+        deterministic,
+        None,
+        gif_source,
+    )?;
+
+    fs::write(path, buf)?;
+
+    Ok(())
 }
@@ -1,15 +1,20 @@
-use pyo3::{prelude::*, types::PyType};
+use pyo3::{exceptions::PyValueError, prelude::*, pyclass::CompareOp, types::{PyBytes, PyType}};
 use ril::{Dynamic, Font as RilFont};
 
-use std::{path::PathBuf, sync::{Arc, RwLock}};
+use std::{collections::HashMap, fs, path::PathBuf, sync::{Arc, PoisonError, RwLock}};
 
 use crate::{
+    draw::{as_point, round_point, Shadow},
     error::Error,
     pixels::Pixel,
-    workaround::{OwnedTextSegment as RilTextSegment, OwnedTextLayout as RilTextLayout},
+    workaround::{
+        glyph_cache_stats, missing_glyphs, prewarm_glyphs, MissingGlyphPolicy,
+        OwnedTextLayout as RilTextLayout, OwnedTextSegment as RilTextSegment, RenderMode,
+        DEFAULT_MONO_THRESHOLD, DEFAULT_TAB_WIDTH,
+    },
     types::{HorizontalAnchor, OverlayMode, VerticalAnchor, WrapStyle},
-    utils::cast_pixel_to_pyobject,
-    Xy,
+    utils::{cast_pixel_to_pyobject, compare_eq},
+    Point, Xy,
 };
 
 /// Represents a text segment that can be drawn.
@@ -32,8 +37,9 @@ use crate::{
 ///     The text to render.
 /// fill: :class:`Pixel`
 ///     The fill color the text will be in.
-/// position: Optional[Tuple[int, int]]
-///     The position the text will be rendered at.
+/// position: Optional[Tuple[float, float]]
+///     The position the text will be rendered at, rounded to the nearest pixel the same way as the position of
+///     an :class:`.Ellipse`. Glyphs are always placed on whole pixels, also in the `smooth` render mode.
 /// 
 ///     **This must be set before adding any text segments!**
 /// 
@@ -47,46 +53,97 @@ use crate::{
 /// wrap: Optional[:class:`WrapStyle`]
 ///     The wrapping style of the text. Note that text will only wrap if `width` is set.
 ///     If this is used in a :class:`TextLayout`, this is ignored and :attr:`.WrapStyle.Wrap` is used instead.
+/// tab_width: int, default: 4
+///     The amount of spaces a tab expands to.
+///     If this is used in a :class:`TextLayout`, this is ignored and :attr:`TextLayout.tab_width` is used instead.
+/// render_mode: str, default: "smooth"
+///     How glyphs are rendered, either `smooth` for anti-aliased text or `mono` for crisp text,
+///     where pixels are either fully drawn with the fill color or not drawn at all.
+///     If this is used in a :class:`TextLayout`, this is ignored and :attr:`TextLayout.render_mode` is used instead.
+/// mono_threshold: int, default: 128
+///     The glyph coverage, out of 255, at or above which a pixel is drawn when `render_mode` is `mono`.
+/// missing_glyph_policy: str, default: "replace"
+///     What to do with characters the font has no glyph for, one of `skip` to leave them out,
+///     `replace` to render `replacement_char` in their place, or `error` to raise a :exc:`ValueError` when drawing.
+///     If this is used in a :class:`TextLayout`, this is ignored and :attr:`TextLayout.missing_glyph_policy` is used instead.
+/// replacement_char: Optional[str]
+///     The character rendered in place of missing glyphs when `missing_glyph_policy` is `replace`, such as ``"□"``.
+///     If not given, the missing glyph of the font is rendered.
+/// shadow: Optional[:class:`.Shadow`]
+///     The drop shadow of the text, drawn below it by blurring and offsetting what the text covers.
+///     The shadow of a :class:`TextLayout` is used for every segment added to it instead.
+///
+/// Line breaks are normalized, ``"\r\n"`` and ``"\r"`` are rendered as ``"\n"``.
+///
+/// Text segments compare equal if all of their fields are equal, where fonts are compared by identity.
 ///
 /// 
 /// .. warning::
 ///     As this class contains the data of an entire font, copying this class is expensive.
 #[pyclass]
 #[derive(Clone)]
+#[pyo3(
+    text_signature = "(font, text, fill, position = None, size = None, overlay = None, width = None, wrap = None, tab_width = 4, render_mode = 'smooth', mono_threshold = 128, missing_glyph_policy = 'replace', replacement_char = None, shadow = None)"
+)]
 pub struct TextSegment {
     pub(crate) inner: RilTextSegment<Dynamic>,
+    position: Point,
+    font: Py<Font>,
+    pub(crate) shadow: Option<Shadow>,
 }
 
 #[pymethods]
 impl TextSegment {
     #[new]
+    #[pyo3(signature = (font, text, fill, position = None, size = None, overlay = None, width = None, wrap = None, tab_width = DEFAULT_TAB_WIDTH, render_mode = "smooth", mono_threshold = DEFAULT_MONO_THRESHOLD, missing_glyph_policy = "replace", replacement_char = None, shadow = None))]
+    #[allow(clippy::too_many_arguments)]
     fn new(
-        font: Font,
+        py: Python<'_>,
+        font: Py<Font>,
         text: &str,
         fill: Pixel,
-        position: Option<Xy>,
+        position: Option<Point>,
         size: Option<f32>,
         overlay: Option<OverlayMode>,
         width: Option<u32>,
         wrap: Option<WrapStyle>,
-    ) -> Self {
-        let font_size = font.optimal_size();
-
-        let mut inner = RilTextSegment::new(font.inner, text, fill.inner);
-
-        inner.position = position.unwrap_or((0, 0));
-        inner.size = size.unwrap_or(font_size);
+        tab_width: usize,
+        render_mode: &str,
+        mono_threshold: u8,
+        missing_glyph_policy: &str,
+        replacement_char: Option<char>,
+        shadow: Option<Shadow>,
+    ) -> PyResult<Self> {
+        let mut inner = {
+            let font = font.borrow(py);
+
+            RilTextSegment::new(font.inner.clone(), text, fill.inner)
+        };
+
+        let position = position.unwrap_or((0.0, 0.0));
+        inner.position = round_point("position", position)?;
+        inner.size = size.unwrap_or(inner.size);
         inner.overlay = overlay.unwrap_or(OverlayMode::Merge).into();
         inner.width = width;
         inner.wrap = wrap.unwrap_or(WrapStyle::Word).into();
+        inner.tab_width = tab_width;
+        inner.render_mode = render_mode_from_name(render_mode)?;
+        inner.mono_threshold = mono_threshold;
+        inner.missing_glyph_policy = missing_glyph_policy_from_name(missing_glyph_policy)?;
+        inner.replacement_char = replacement_char;
 
-        Self { inner }
+        Ok(Self {
+            inner,
+            position,
+            font,
+            shadow,
+        })
     }
 
-    /// Tuple[int, int]: The position of the text segment.
+    /// Tuple[float, float]: The position of the text segment.
     #[getter]
-    fn position(&self) -> Xy {
-        self.inner.position
+    fn position(&self) -> Point {
+        self.position
     }
 
     /// float: The width of the text box.
@@ -105,14 +162,9 @@ impl TextSegment {
     }
 
     /// :class:`Font`: The font of the text segment.
-    ///
-    /// .. warning::
-    ///     Due to design limitation, accessing font requires a deep clone each time, which is expensive.
     #[getter]
-    fn font(&self) -> Font {
-        Font {
-            inner: self.inner.font.clone(),
-        }
+    fn font(&self, py: Python<'_>) -> Py<Font> {
+        self.font.clone_ref(py)
     }
 
     /// List[List[Union[:class:`.BitPixel`, :class:`.L`, :class:`.Rgb`, :class:`.Rgba`]]]: The fill color of the text segment.
@@ -139,9 +191,48 @@ impl TextSegment {
         self.inner.wrap.into()
     }
 
+    /// int: The amount of spaces a tab expands to.
+    #[getter]
+    fn tab_width(&self) -> usize {
+        self.inner.tab_width
+    }
+
+    /// str: How glyphs are rendered, either `smooth` or `mono`.
+    #[getter]
+    fn render_mode(&self) -> &'static str {
+        render_mode_name(self.inner.render_mode)
+    }
+
+    /// int: The glyph coverage at or above which a pixel is drawn when :attr:`render_mode` is `mono`.
+    #[getter]
+    fn mono_threshold(&self) -> u8 {
+        self.inner.mono_threshold
+    }
+
+    /// str: What to do with characters the font has no glyph for, either `skip`, `replace` or `error`.
+    #[getter]
+    fn missing_glyph_policy(&self) -> &'static str {
+        missing_glyph_policy_name(self.inner.missing_glyph_policy)
+    }
+
+    /// Optional[str]: The character rendered in place of missing glyphs when :attr:`missing_glyph_policy` is `replace`.
+    #[getter]
+    fn replacement_char(&self) -> Option<char> {
+        self.inner.replacement_char
+    }
+
+    /// Optional[:class:`.Shadow`]: The drop shadow of the text.
+    #[getter]
+    fn shadow(&self) -> Option<Shadow> {
+        self.shadow.clone()
+    }
+
     #[setter]
-    fn set_position(&mut self, position: Xy) {
-        self.inner.position = position;
+    pub(crate) fn set_position(&mut self, position: Point) -> PyResult<()> {
+        self.inner.position = round_point("position", position)?;
+        self.position = position;
+
+        Ok(())
     }
 
     #[setter]
@@ -155,8 +246,9 @@ impl TextSegment {
     }
 
     #[setter]
-    fn set_font(&mut self, font: Font) {
-        self.inner.font = font.inner;
+    fn set_font(&mut self, py: Python<'_>, font: Py<Font>) {
+        self.inner.font = font.borrow(py).inner.clone();
+        self.font = font;
     }
 
     #[setter]
@@ -179,6 +271,75 @@ impl TextSegment {
         self.inner.wrap = wrap.into();
     }
 
+    #[setter]
+    fn set_tab_width(&mut self, tab_width: usize) {
+        self.inner.tab_width = tab_width;
+    }
+
+    #[setter]
+    fn set_render_mode(&mut self, render_mode: &str) -> PyResult<()> {
+        self.inner.render_mode = render_mode_from_name(render_mode)?;
+
+        Ok(())
+    }
+
+    #[setter]
+    fn set_mono_threshold(&mut self, mono_threshold: u8) {
+        self.inner.mono_threshold = mono_threshold;
+    }
+
+    #[setter]
+    fn set_missing_glyph_policy(&mut self, missing_glyph_policy: &str) -> PyResult<()> {
+        self.inner.missing_glyph_policy = missing_glyph_policy_from_name(missing_glyph_policy)?;
+
+        Ok(())
+    }
+
+    #[setter]
+    fn set_replacement_char(&mut self, replacement_char: Option<char>) {
+        self.inner.replacement_char = replacement_char;
+    }
+
+    #[setter]
+    fn set_shadow(&mut self, shadow: Option<Shadow>) {
+        self.shadow = shadow;
+    }
+
+    fn __reduce__(&self, py: Python<'_>) -> PyResult<(PyObject, PyObject)> {
+        let args = (
+            self.font.clone_ref(py),
+            self.text(),
+            Pixel::from(self.inner.fill),
+            self.position(),
+            self.size(),
+            self.overlay(),
+            self.width(),
+            self.wrap(),
+            self.tab_width(),
+            self.render_mode(),
+            self.mono_threshold(),
+            self.missing_glyph_policy(),
+            self.replacement_char(),
+            self.shadow(),
+        );
+
+        Ok((py.get_type::<Self>().into_py(py), args.into_py(py)))
+    }
+
+    /// Returns a copy of this text segment, which shares its :class:`.Font`.
+    #[pyo3(text_signature = "($self)")]
+    fn copy(&self) -> Self {
+        self.clone()
+    }
+
+    fn __copy__(&self) -> Self {
+        self.clone()
+    }
+
+    fn __richcmp__(&self, py: Python<'_>, other: &PyAny, op: CompareOp) -> PyObject {
+        compare_eq(py, self, other, op)
+    }
+
     fn __repr__(&self, py: Python<'_>) -> String {
         format!(
             "<TextSegment fill={}, position=({}, {}), size={}, overlay={:?}, width={}, wrap={:?}>",
@@ -193,6 +354,49 @@ impl TextSegment {
     }
 }
 
+impl PartialEq for TextSegment {
+    // Fonts are compared by identity, comparing their data would be too expensive.
+    fn eq(&self, other: &Self) -> bool {
+        self.font.is(&other.font)
+            && self.inner.text == other.inner.text
+            && self.inner.fill == other.inner.fill
+            && self.position == other.position
+            && self.inner.size == other.inner.size
+            && self.overlay() == other.overlay()
+            && self.inner.width == other.inner.width
+            && self.wrap() == other.wrap()
+            && self.inner.tab_width == other.inner.tab_width
+            && self.inner.render_mode == other.inner.render_mode
+            && self.inner.mono_threshold == other.inner.mono_threshold
+            && self.inner.missing_glyph_policy == other.inner.missing_glyph_policy
+            && self.inner.replacement_char == other.inner.replacement_char
+            && self.shadow == other.shadow
+    }
+}
+
+impl TextSegment {
+    /// Creates a text segment with the defaults of its constructor.
+    pub(crate) fn from_font(py: Python<'_>, font: Py<Font>, text: &str, fill: Pixel) -> Self {
+        let inner = RilTextSegment::new(font.borrow(py).inner.clone(), text, fill.inner);
+
+        Self {
+            position: as_point(inner.position),
+            inner,
+            font,
+            shadow: None,
+        }
+    }
+
+    /// Fails if the policy is to error on missing glyphs and the font is missing some.
+    pub(crate) fn ensure_glyphs(&self) -> Result<(), Error> {
+        ensure_glyphs(
+            self.inner.missing_glyph_policy,
+            self.inner.font.inner(),
+            &self.inner.text,
+        )
+    }
+}
+
 /// Represents a high-level text layout that can layout text segments, maybe with different fonts.
 ///
 /// This is a high-level layout that can be used to layout text segments.
@@ -222,6 +426,26 @@ impl TextSegment {
 ///    Sets the wrapping style of the text. Make sure to also set the wrapping width using :attr:`width` for wrapping to work.
 /// 
 ///     **This must be set before adding any text segments!**
+/// tab_width: int, default: 4
+///     The amount of spaces a tab expands to, overriding the tab width of the added text segments.
+///
+///     **This must be set before adding any text segments!**
+/// render_mode: str, default: "smooth"
+///     How glyphs are rendered, overriding the render mode of the added text segments, see :attr:`TextSegment.render_mode`.
+/// mono_threshold: int, default: 128
+///     The glyph coverage at or above which a pixel is drawn when `render_mode` is `mono`.
+/// missing_glyph_policy: str, default: "replace"
+///     What to do with characters the font has no glyph for, overriding the policy of the added text segments,
+///     see :attr:`TextSegment.missing_glyph_policy`. With `error`, adding text with missing glyphs raises a :exc:`ValueError`.
+///
+///     **This must be set before adding any text segments!**
+/// replacement_char: Optional[str]
+///     The character rendered in place of missing glyphs when `missing_glyph_policy` is `replace`.
+/// shadow: Optional[:class:`.Shadow`]
+///     The drop shadow of the text, overriding the shadow of the added text segments, see :attr:`TextSegment.shadow`.
+///     Unlike most other settings, this can be changed after adding text segments.
+///
+/// Line breaks are normalized, ``"\r\n"`` and ``"\r"`` are laid out as ``"\n"``.
 ///
 /// 
 /// .. warning::
@@ -229,23 +453,39 @@ impl TextSegment {
 #[pyclass]
 #[derive(Clone)]
 #[pyo3(
-    text_signature = "(font, text, fill, position = None, size = None, overlay = None, width = None, wrap = None)"
+    text_signature = "(position = None, width = None, horizontal_anchor = None, vertical_anchor = None, wrap = None, tab_width = 4, render_mode = 'smooth', mono_threshold = 128, missing_glyph_policy = 'replace', replacement_char = None, shadow = None)"
 )]
 pub struct TextLayout {
     pub(crate) inner: Arc<RwLock<RilTextLayout<Dynamic>>>,
+    pub(crate) shadow: Option<Shadow>,
+    /// How far the layout is moved when drawn, set on the copy that is drawn under a transform.
+    pub(crate) offset: (f32, f32),
 }
 
 #[pymethods]
 impl TextLayout {
     #[new]
+    #[pyo3(signature = (position = None, width = None, horizontal_anchor = None, vertical_anchor = None, wrap = None, tab_width = DEFAULT_TAB_WIDTH, render_mode = "smooth", mono_threshold = DEFAULT_MONO_THRESHOLD, missing_glyph_policy = "replace", replacement_char = None, shadow = None))]
+    #[allow(clippy::too_many_arguments)]
     fn new(
         position: Option<Xy>,
         width: Option<u32>,
         horizontal_anchor: Option<HorizontalAnchor>,
         vertical_anchor: Option<VerticalAnchor>,
         wrap: Option<WrapStyle>,
-    ) -> Self {
+        tab_width: usize,
+        render_mode: &str,
+        mono_threshold: u8,
+        missing_glyph_policy: &str,
+        replacement_char: Option<char>,
+        shadow: Option<Shadow>,
+    ) -> PyResult<Self> {
         let mut inner = RilTextLayout::new();
+        inner.tab_width = tab_width;
+        inner.render_mode = render_mode_from_name(render_mode)?;
+        inner.mono_threshold = mono_threshold;
+        inner.missing_glyph_policy = missing_glyph_policy_from_name(missing_glyph_policy)?;
+        inner.replacement_char = replacement_char;
 
         if let Some(position) = position {
             inner.set_position(position.0, position.1);
@@ -267,30 +507,85 @@ impl TextLayout {
             inner.set_wrap(wrap.into());
         }
 
-        Self {
+        Ok(Self {
             inner: Arc::new(RwLock::new(inner)),
-        }
+            shadow,
+            offset: (0.0, 0.0),
+        })
     }
 
     /// Sets the horizontal anchor and vertial anchor of the text to be centered. 
     /// This makes the position of the text be the center as opposed to the top-left corner.
-    fn centered(&mut self) -> Result<(), Error>{
-        self.inner.write()?.centered();
-
-        Ok(())
+    ///
+    /// Returns
+    /// -------
+    /// :class:`.TextLayout`
+    ///     This text layout, for chaining.
+    #[pyo3(text_signature = "($self)")]
+    fn centered(slf: PyRefMut<'_, Self>) -> Result<PyRefMut<'_, Self>, Error> {
+        slf.inner.write()?.centered();
+
+        Ok(slf)
     }
 
     /// Tuple[int, int, int, int]: Returns the bounding box of the text. 
     /// Left and top bounds are inclusive; right and bottom bounds are exclusive.
+    ///
+    /// This leaves out the shadow of the text, see :meth:`get_bounding_box` to include it.
     #[getter]
     fn bounding_box(&self) -> Result<(u32, u32, u32, u32), Error> {
         Ok(self.inner.read()?.bounding_box())
     }
 
+    /// Returns the bounding box of the text, like :attr:`bounding_box`.
+    ///
+    /// Parameters
+    /// ----------
+    /// include_effects: bool, default: False
+    ///     Whether to grow the bounding box to also cover the area painted by the :attr:`shadow` of the text.
+    ///
+    /// Returns
+    /// -------
+    /// Tuple[int, int, int, int]
+    ///     The bounding box. Left and top bounds are inclusive; right and bottom bounds are exclusive.
+    #[pyo3(signature = (include_effects = false))]
+    #[pyo3(text_signature = "($self, include_effects = False)")]
+    fn get_bounding_box(&self, include_effects: bool) -> Result<(u32, u32, u32, u32), Error> {
+        let bounds = self.inner.read()?.bounding_box();
+
+        Ok(match &self.shadow {
+            Some(shadow) if include_effects => shadow.extend_bounds(bounds),
+            _ => bounds,
+        })
+    }
+
+    /// Returns the width and height of the text, like :attr:`dimensions`.
+    ///
+    /// Parameters
+    /// ----------
+    /// include_effects: bool, default: False
+    ///     Whether to also cover the area painted by the :attr:`shadow` of the text.
+    ///
+    /// Returns
+    /// -------
+    /// Tuple[int, int]
+    ///     The width and height.
+    #[pyo3(signature = (include_effects = false))]
+    #[pyo3(text_signature = "($self, include_effects = False)")]
+    fn get_dimensions(&self, include_effects: bool) -> Result<Xy, Error> {
+        if !include_effects {
+            return Ok(self.inner.read()?.dimensions());
+        }
+
+        let (x1, y1, x2, y2) = self.get_bounding_box(true)?;
+
+        Ok((x2 - x1, y2 - y1))
+    }
+
     /// Tuple[int, int]: Returns the width and height of the text.
     /// 
-    /// .. warning::
-    ///     This is a slightly expensive operation and is not a simple getter.
+    /// .. note::
+    ///     This is measured once and cached until text is added or a setting changes.
     /// 
     /// .. note::
     ///     If you want both width and height, use :attr:`dimensions`.
@@ -301,8 +596,8 @@ impl TextLayout {
 
     /// int: Returns the height of the text.
     /// 
-    /// .. warning::
-    ///     This is a slightly expensive operation and is not a simple getter.
+    /// .. note::
+    ///     This is measured once and cached until text is added or a setting changes.
     /// 
     /// .. note::
     ///     If you want both width and height, use :attr:`dimensions`.
@@ -313,8 +608,8 @@ impl TextLayout {
 
     /// int: Returns the width of the text.
     /// 
-    /// .. warning::
-    ///    This is a slightly expensive operation and is not a simple getter.
+    /// .. note::
+    ///     This is measured once and cached until text is added or a setting changes.
     /// 
     /// .. note::
     ///    If you want both width and height, use :attr:`dimensions`.
@@ -323,6 +618,86 @@ impl TextLayout {
         Ok(self.inner.read()?.width())
     }
 
+    /// int: Returns the amount of lines the text takes up, after wrapping.
+    #[getter]
+    fn line_count(&self) -> Result<usize, Error> {
+        Ok(self.inner.read()?.line_count())
+    }
+
+    /// Returns the bounding box of every line of the text, after wrapping and anchoring.
+    /// Left and top bounds are inclusive; right and bottom bounds are exclusive.
+    ///
+    /// Returns
+    /// -------
+    /// List[Tuple[int, int, int, int]]
+    ///     The bounding boxes, from the first line to the last.
+    #[pyo3(text_signature = "($self)")]
+    fn lines(&self) -> Result<Vec<(u32, u32, u32, u32)>, Error> {
+        Ok(self.inner.read()?.line_boxes())
+    }
+
+    /// int: The amount of spaces a tab expands to.
+    #[getter]
+    fn tab_width(&self) -> Result<usize, Error> {
+        Ok(self.inner.read()?.tab_width)
+    }
+
+    /// str: How glyphs are rendered, either `smooth` or `mono`.
+    ///
+    /// Unlike most other settings, this can be changed after adding text segments.
+    #[getter]
+    fn render_mode(&self) -> Result<&'static str, Error> {
+        Ok(render_mode_name(self.inner.read()?.render_mode))
+    }
+
+    /// int: The glyph coverage at or above which a pixel is drawn when :attr:`render_mode` is `mono`.
+    #[getter]
+    fn mono_threshold(&self) -> Result<u8, Error> {
+        Ok(self.inner.read()?.mono_threshold)
+    }
+
+    /// str: What to do with characters the font has no glyph for, either `skip`, `replace` or `error`.
+    #[getter]
+    fn missing_glyph_policy(&self) -> Result<&'static str, Error> {
+        Ok(missing_glyph_policy_name(
+            self.inner.read()?.missing_glyph_policy,
+        ))
+    }
+
+    /// Optional[str]: The character rendered in place of missing glyphs when :attr:`missing_glyph_policy` is `replace`.
+    #[getter]
+    fn replacement_char(&self) -> Result<Option<char>, Error> {
+        Ok(self.inner.read()?.replacement_char)
+    }
+
+    /// Tuple[int, int]: The position the text is rendered at, which the anchors are relative to.
+    #[getter]
+    fn position(&self) -> Result<Xy, Error> {
+        let inner = self.inner.read()?;
+
+        Ok((inner.settings.x as u32, inner.settings.y as u32))
+    }
+
+    /// :class:`.HorizontalAnchor`: The horizontal anchor of the text layout.
+    #[getter]
+    fn horizontal_anchor(&self) -> Result<HorizontalAnchor, Error> {
+        Ok(self.inner.read()?.x_anchor.into())
+    }
+
+    /// :class:`.VerticalAnchor`: The vertical anchor of the text layout.
+    #[getter]
+    fn vertical_anchor(&self) -> Result<VerticalAnchor, Error> {
+        Ok(self.inner.read()?.y_anchor.into())
+    }
+
+    /// Tuple[:class:`.HorizontalAnchor`, :class:`.VerticalAnchor`]: The horizontal and vertical anchor of the text layout.
+    #[getter]
+    fn anchor(&self) -> Result<(HorizontalAnchor, VerticalAnchor), Error> {
+        let inner = self.inner.read()?;
+
+        Ok((inner.x_anchor.into(), inner.y_anchor.into()))
+    }
+
     /// Sets the position of the text layout.
     /// 
     /// **This must be set before adding any text segments!**
@@ -371,6 +746,68 @@ impl TextLayout {
         Ok(())
     }
 
+    /// Sets the amount of spaces a tab expands to.
+    ///
+    /// **This must be set before adding any text segments!**
+    #[setter]
+    fn set_tab_width(&mut self, tab_width: usize) -> Result<(), Error> {
+        self.inner.write()?.tab_width = tab_width;
+
+        Ok(())
+    }
+
+    /// Sets how glyphs are rendered.
+    #[setter]
+    fn set_render_mode(&mut self, render_mode: &str) -> PyResult<()> {
+        let render_mode = render_mode_from_name(render_mode)?;
+        self.inner.write().map_err(Error::from)?.render_mode = render_mode;
+
+        Ok(())
+    }
+
+    /// Sets the glyph coverage at or above which a pixel is drawn when :attr:`render_mode` is `mono`.
+    #[setter]
+    fn set_mono_threshold(&mut self, mono_threshold: u8) -> Result<(), Error> {
+        self.inner.write()?.mono_threshold = mono_threshold;
+
+        Ok(())
+    }
+
+    /// Sets what to do with characters the font has no glyph for.
+    ///
+    /// **This must be set before adding any text segments!**
+    #[setter]
+    fn set_missing_glyph_policy(&mut self, missing_glyph_policy: &str) -> PyResult<()> {
+        let policy = missing_glyph_policy_from_name(missing_glyph_policy)?;
+        self.inner
+            .write()
+            .map_err(Error::from)?
+            .missing_glyph_policy = policy;
+
+        Ok(())
+    }
+
+    /// Sets the character rendered in place of missing glyphs.
+    ///
+    /// **This must be set before adding any text segments!**
+    #[setter]
+    fn set_replacement_char(&mut self, replacement_char: Option<char>) -> Result<(), Error> {
+        self.inner.write()?.replacement_char = replacement_char;
+
+        Ok(())
+    }
+
+    /// Optional[:class:`.Shadow`]: The drop shadow of the text.
+    #[getter]
+    fn shadow(&self) -> Option<Shadow> {
+        self.shadow.clone()
+    }
+
+    #[setter]
+    fn set_shadow(&mut self, shadow: Option<Shadow>) {
+        self.shadow = shadow;
+    }
+
     /// Pushes a basic text to the text layout.
     /// Adds basic text to the text layout. This is a convenience method that creates a :class:`TextSegment` with the given font, text, and fill and adds it to the text layout.
     /// The size of the text is determined by the font’s optimal size.
@@ -383,9 +820,16 @@ impl TextLayout {
     ///     The text to add.
     /// fill: :class:`Pixel`
     ///     The color of the text.
-    #[pyo3(text_signature = "(self, font, text, fill)")]
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     :attr:`missing_glyph_policy` is `error` and the font has no glyphs for some of the characters.
+    #[pyo3(text_signature = "($self, font, text, fill)")]
     fn push_basic_text(&mut self, font: Font, text: &str, fill: Pixel) -> Result<(), Error> {
-        self.inner.write()?.push_basic_text(font.inner, text, fill.inner);
+        let mut inner = self.inner.write()?;
+        ensure_glyphs(inner.missing_glyph_policy, font.inner.inner(), text)?;
+        inner.push_basic_text(font.inner, text, fill.inner);
 
         Ok(())
     }
@@ -396,13 +840,57 @@ impl TextLayout {
     /// ----------
     /// segment: :class:`TextSegment`
     ///    The text segment to add.
-    #[pyo3(text_signature = "(self, segment)")]
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     :attr:`missing_glyph_policy` is `error` and the font has no glyphs for some of the characters.
+    #[pyo3(text_signature = "($self, segment)")]
     fn push_segment(&mut self, segment: TextSegment) -> Result<(), Error> {
-        self.inner.write()?.push_segment(segment.inner);
+        let mut inner = self.inner.write()?;
+        ensure_glyphs(
+            inner.missing_glyph_policy,
+            segment.inner.font.inner(),
+            &segment.inner.text,
+        )?;
+        inner.push_segment(segment.inner);
 
         Ok(())
     }
 
+    /// bool: Whether a panic while this text layout was being changed left it unusable, see :meth:`recover`.
+    #[getter]
+    fn is_poisoned(&self) -> bool {
+        self.inner.is_poisoned()
+    }
+
+    /// Makes this text layout usable again after a panic poisoned it, which makes every other use of it raise
+    /// a :exc:`RuntimeError`. Its text is laid out again from the segments added so far, with the same settings.
+    ///
+    /// This does nothing if the text layout isn't poisoned, see :attr:`is_poisoned`.
+    #[pyo3(text_signature = "($self)")]
+    fn recover(&mut self) {
+        if !self.inner.is_poisoned() {
+            return;
+        }
+
+        let mut layout = {
+            let mut inner = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+            std::mem::replace(&mut *inner, RilTextLayout::new())
+        };
+        layout.relayout();
+
+        // A lock stays poisoned once it is, so the layout is moved into a new one.
+        self.inner = Arc::new(RwLock::new(layout));
+    }
+
+    /// Panics while holding the lock of this text layout, which poisons it. Only meant for testing :meth:`recover`.
+    #[cfg(feature = "test-hooks")]
+    fn _poison(&self) {
+        let _inner = self.inner.write();
+        panic!("Poisoned on purpose");
+    }
+
     fn __repr__(&self) -> Result<String, Error> {
         let inner = self.inner.read()?;
         let bound = inner.bounding_box();
@@ -422,11 +910,78 @@ impl TextLayout {
     }
 }
 
+fn render_mode_from_name(name: &str) -> PyResult<RenderMode> {
+    match name.to_lowercase().as_str() {
+        "smooth" => Ok(RenderMode::Smooth),
+        "mono" => Ok(RenderMode::Mono),
+        _ => Err(PyValueError::new_err(format!(
+            "Invalid render mode `{}`, expected one of `smooth` or `mono`",
+            name
+        ))),
+    }
+}
+
+const fn render_mode_name(mode: RenderMode) -> &'static str {
+    match mode {
+        RenderMode::Smooth => "smooth",
+        RenderMode::Mono => "mono",
+    }
+}
+
+fn missing_glyph_policy_from_name(name: &str) -> PyResult<MissingGlyphPolicy> {
+    match name.to_lowercase().as_str() {
+        "skip" => Ok(MissingGlyphPolicy::Skip),
+        "replace" => Ok(MissingGlyphPolicy::Replace),
+        "error" => Ok(MissingGlyphPolicy::Error),
+        _ => Err(PyValueError::new_err(format!(
+            "Invalid missing glyph policy `{}`, expected one of `skip`, `replace` or `error`",
+            name
+        ))),
+    }
+}
+
+const fn missing_glyph_policy_name(policy: MissingGlyphPolicy) -> &'static str {
+    match policy {
+        MissingGlyphPolicy::Skip => "skip",
+        MissingGlyphPolicy::Replace => "replace",
+        MissingGlyphPolicy::Error => "error",
+    }
+}
+
+fn ensure_glyphs(
+    policy: MissingGlyphPolicy,
+    font: &fontdue::Font,
+    text: &str,
+) -> Result<(), Error> {
+    if policy != MissingGlyphPolicy::Error {
+        return Ok(());
+    }
+
+    let missing = missing_glyphs(font, text);
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::MissingGlyphs(missing))
+    }
+}
+
 /// Represents a single font along with its alternatives used to render text. Currently, this supports TrueType and OpenType fonts.
+///
+/// Fonts can be pickled, the original font data is kept around and shared between copies of the font.
 #[pyclass]
 #[derive(Clone)]
 pub struct Font {
     inner: RilFont,
+    data: Arc<Vec<u8>>,
+}
+
+impl Font {
+    fn from_data(data: Vec<u8>, optimal_size: f32) -> Result<Self, Error> {
+        Ok(Self {
+            inner: RilFont::from_bytes(&data, optimal_size)?,
+            data: Arc::new(data),
+        })
+    }
 }
 
 #[pymethods]
@@ -458,11 +1013,9 @@ impl Font {
     /// .. seealso::
     ///     :meth:`from_bytes`
     #[classmethod]
-    #[pyo3(text_signature = "(cls, path, optimal_size)")]
+    #[pyo3(text_signature = "($cls, path, optimal_size)")]
     fn open(_: &PyType, path: PathBuf, optimal_size: f32) -> Result<Self, Error> {
-        Ok(Self {
-            inner: RilFont::open(path, optimal_size)?,
-        })
+        Self::from_data(fs::read(path)?, optimal_size)
     }
 
     /// Loads the font from the given bytes.
@@ -487,11 +1040,9 @@ impl Font {
     /// RuntimeError
     ///     Fails to load the font.
     #[classmethod]
-    #[pyo3(text_signature = "(cls, bytes, optimal_size)")]
+    #[pyo3(text_signature = "($cls, bytes, optimal_size)")]
     fn from_bytes(_: &PyType, bytes: &[u8], optimal_size: f32) -> Result<Self, Error> {
-        Ok(Self {
-            inner: RilFont::from_bytes(bytes, optimal_size)?,
-        })
+        Self::from_data(bytes.to_vec(), optimal_size)
     }
 
     /// float: Returns the optimal size, in pixels, of this font.
@@ -506,6 +1057,71 @@ impl Font {
         self.inner.optimal_size()
     }
 
+    /// Rasterizes the glyphs of all characters in `text` at the given size ahead of time,
+    /// so that the first draw using them doesn't have to.
+    ///
+    /// Rasterized glyphs are shared between copies of the font, and the least recently used glyphs of all fonts
+    /// are evicted once they take up more than 32 MiB.
+    ///
+    /// Parameters
+    /// ----------
+    /// text: str
+    ///     The characters to rasterize.
+    /// size: float
+    ///     The size to rasterize them at, in pixels.
+    #[pyo3(text_signature = "($self, text, size)")]
+    fn prewarm(&self, py: Python<'_>, text: &str, size: f32) {
+        let font = self.inner.inner();
+
+        py.allow_threads(|| prewarm_glyphs(font, text, size));
+    }
+
+    /// Returns statistics about the rasterized glyphs cached for this font.
+    ///
+    /// Returns
+    /// -------
+    /// Dict[str, int]
+    ///     The amount of cached glyphs under ``"glyphs"``, and the approximate memory they take up in bytes under ``"bytes"``.
+    #[pyo3(text_signature = "($self)")]
+    fn cache_stats(&self) -> HashMap<&'static str, usize> {
+        let (glyphs, bytes) = glyph_cache_stats(self.inner.inner());
+
+        HashMap::from([("glyphs", glyphs), ("bytes", bytes)])
+    }
+
+    /// Returns the characters of `text` this font has no glyph for, in the order they first appear.
+    /// Whitespace is never reported as missing.
+    ///
+    /// Parameters
+    /// ----------
+    /// text: str
+    ///     The text to check.
+    ///
+    /// Returns
+    /// -------
+    /// List[str]
+    ///     The missing characters, each listed once.
+    #[pyo3(text_signature = "($self, text)")]
+    fn missing_glyphs(&self, text: &str) -> Vec<char> {
+        let mut missing = Vec::new();
+        for (_, character) in missing_glyphs(self.inner.inner(), text) {
+            if !missing.contains(&character) {
+                missing.push(character);
+            }
+        }
+
+        missing
+    }
+
+    fn __reduce__(&self, py: Python<'_>) -> PyResult<(PyObject, (PyObject, f32))> {
+        let from_bytes = py.get_type::<Self>().getattr("from_bytes")?.into_py(py);
+
+        Ok((
+            from_bytes,
+            (PyBytes::new(py, &self.data).into_py(py), self.optimal_size()),
+        ))
+    }
+
     fn __repr__(&self) -> String {
         format!(
             "<Font optimal_size={}>",
@@ -0,0 +1,124 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::Instant,
+};
+
+use pyo3::{prelude::*, types::PyDict};
+
+use crate::Xy;
+
+/// Whether a hook is installed, checked before anything else so tracing costs nothing while disabled.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static HOOK: Mutex<Option<PyObject>> = Mutex::new(None);
+
+/// Sets a callback invoked at coarse milestones of long operations, such as decoding, encoding and resizing.
+///
+/// This is meant for diagnosing slow or stuck operations. The hook is called with a single `dict` holding:
+///
+/// - ``operation``: The name of the operation, one of `decode`, `encode`, `encode_frames` or `resize`.
+/// - ``event``: `start`, `end`, or `progress` after each frame of a sequence is processed.
+///   GIF frames report `encode_frames` progress when they are quantized with :class:`.GifOptions` or
+///   on more than one worker, otherwise ril encodes the sequence as a whole.
+/// - ``dimensions``: The dimensions of the image or sequence, or `None` if not known yet.
+///   The `end` event of `decode` and `resize` carries the resulting dimensions.
+/// - ``elapsed_ms``: The milliseconds elapsed since the operation started, as a float.
+/// - ``frame`` and ``frames``: The amount of frames processed so far and in total, only for `progress` events.
+///
+/// The GIL is acquired to call the hook, even from operations that otherwise run without it.
+/// Exceptions raised by the hook are printed and otherwise ignored.
+///
+/// Parameters
+/// ----------
+/// hook: Optional[Callable[[dict], None]]
+///     The callback, or `None` to disable tracing.
+#[pyfunction]
+#[pyo3(text_signature = "(hook)")]
+pub fn set_trace_hook(hook: Option<PyObject>) {
+    let mut current = HOOK.lock().unwrap_or_else(|err| err.into_inner());
+    ENABLED.store(hook.is_some(), Ordering::Relaxed);
+    *current = hook;
+}
+
+/// Starts tracing an operation, which does nothing unless a hook is installed.
+///
+/// The `end` event is emitted when the span is dropped, so it is also emitted when the operation fails.
+pub fn span(operation: &'static str, dimensions: Option<Xy>) -> Span {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return Span(None);
+    }
+
+    let span = Span(Some(Started {
+        operation,
+        dimensions,
+        start: Instant::now(),
+    }));
+    span.emit("start", None);
+
+    span
+}
+
+/// An operation being traced, see `set_trace_hook`.
+pub struct Span(Option<Started>);
+
+struct Started {
+    operation: &'static str,
+    dimensions: Option<Xy>,
+    start: Instant,
+}
+
+impl Span {
+    /// Emits a `progress` event after `frame` of `frames` frames are processed.
+    pub fn progress(&self, frame: usize, frames: usize) {
+        self.emit("progress", Some((frame, frames)));
+    }
+
+    /// Ends the span, reporting the given dimensions in the `end` event.
+    pub fn end(mut self, dimensions: Xy) {
+        if let Some(started) = &mut self.0 {
+            started.dimensions = Some(dimensions);
+        }
+    }
+
+    fn emit(&self, event: &str, progress: Option<(usize, usize)>) {
+        let started = match &self.0 {
+            Some(started) => started,
+            None => return,
+        };
+        let elapsed_ms = started.start.elapsed().as_secs_f64() * 1000.0;
+
+        Python::with_gil(|py| {
+            // The hook is cloned out so it can replace itself without deadlocking.
+            let hook = match &*HOOK.lock().unwrap_or_else(|err| err.into_inner()) {
+                Some(hook) => hook.clone_ref(py),
+                None => return,
+            };
+
+            let result = (|| {
+                let record = PyDict::new(py);
+                record.set_item("operation", started.operation)?;
+                record.set_item("event", event)?;
+                record.set_item("dimensions", started.dimensions)?;
+                record.set_item("elapsed_ms", elapsed_ms)?;
+                if let Some((frame, frames)) = progress {
+                    record.set_item("frame", frame)?;
+                    record.set_item("frames", frames)?;
+                }
+
+                hook.call1(py, (record,))
+            })();
+
+            if let Err(err) = result {
+                err.print(py);
+            }
+        });
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        self.emit("end", None);
+    }
+}
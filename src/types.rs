@@ -1,6 +1,9 @@
 use std::fmt::Display;
 
-use pyo3::prelude::*;
+use pyo3::{exceptions::PyValueError, prelude::*};
+use ril::{Dynamic, Image as RilImage};
+
+use crate::error::Error;
 
 macro_rules! cast_enum {
     ($from:ty, $to:ty, $item:expr, $($var:tt),*) => {{
@@ -12,6 +15,22 @@ macro_rules! cast_enum {
     }};
 }
 
+/// Makes a fieldless pyclass enum picklable by looking its variant up by name on unpickling.
+macro_rules! impl_enum_reduce {
+    ($($enum:ty),*) => {
+        $(
+            #[pymethods]
+            impl $enum {
+                fn __reduce__(&self, py: Python<'_>) -> PyResult<(PyObject, (PyObject, String))> {
+                    let getattr = py.import("builtins")?.getattr("getattr")?.into_py(py);
+
+                    Ok((getattr, (py.get_type::<Self>().into_py(py), format!("{:?}", self))))
+                }
+            }
+        )*
+    };
+}
+
 /// A filtering algorithm that is used to resize an image.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[pyclass]
@@ -75,9 +94,162 @@ impl From<ril::ResizeAlgorithm> for ResizeAlgorithm {
     }
 }
 
+/// The pixel mode of an image.
+///
+/// Modes are ordered from the poorest to the richest, so the mode both of two pixels convert to losslessly is their maximum.
+#[pyclass]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Mode {
+    /// A single-bit pixel, either on or off.
+    BitPixel,
+    /// A single luminance channel.
+    L,
+    /// Red, green and blue channels.
+    Rgb,
+    /// Red, green, blue and alpha channels.
+    Rgba,
+}
+
+impl Mode {
+    /// Returns the mode of the given image.
+    ///
+    /// Every pixel of an image has the same mode, since pixels are converted to the mode of the image wherever they
+    /// come from Python, see `Image.from_pixels` and `Image.set_pixel`. Images are never empty, so the mode is taken
+    /// from the first pixel.
+    pub fn of(image: &RilImage<Dynamic>) -> Self {
+        image.pixel(0, 0).into()
+    }
+
+    /// Parses a mode from its name, case-insensitively.
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        match name.to_ascii_uppercase().as_str() {
+            "BITPIXEL" => Ok(Self::BitPixel),
+            "L" => Ok(Self::L),
+            "RGB" => Ok(Self::Rgb),
+            "RGBA" => Ok(Self::Rgba),
+            _ => Err(PyValueError::new_err(format!(
+                "Invalid mode `{}`, expected one of `BITPIXEL`, `L`, `RGB` or `RGBA`",
+                name
+            ))),
+        }
+    }
+
+    /// Returns the amount of bytes a pixel of this mode takes up in raw pixel data.
+    pub fn channels(self) -> usize {
+        match self {
+            Self::BitPixel | Self::L => 1,
+            Self::Rgb => 3,
+            Self::Rgba => 4,
+        }
+    }
+
+    /// Converts the pixel into this mode.
+    pub fn convert_pixel(self, pixel: Dynamic) -> Dynamic {
+        match self {
+            Self::BitPixel => Dynamic::BitPixel(pixel.into()),
+            Self::L => Dynamic::L(pixel.into()),
+            Self::Rgb => Dynamic::Rgb(pixel.into()),
+            Self::Rgba => Dynamic::Rgba(pixel.into()),
+        }
+    }
+
+    /// Converts every pixel of the image into this mode.
+    pub fn convert_image(self, image: RilImage<Dynamic>) -> RilImage<Dynamic> {
+        match self {
+            Self::BitPixel => image.convert::<ril::BitPixel>().convert::<Dynamic>(),
+            Self::L => image.convert::<ril::L>().convert::<Dynamic>(),
+            Self::Rgb => image.convert::<ril::Rgb>().convert::<Dynamic>(),
+            Self::Rgba => image.convert::<ril::Rgba>().convert::<Dynamic>(),
+        }
+    }
+}
+
+impl Display for Mode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BitPixel => f.write_str("BITPIXEL"),
+            Self::L => f.write_str("L"),
+            Self::Rgb => f.write_str("RGB"),
+            Self::Rgba => f.write_str("RGBA"),
+        }
+    }
+}
+
+impl From<&Dynamic> for Mode {
+    fn from(pixel: &Dynamic) -> Self {
+        match pixel {
+            Dynamic::BitPixel(_) => Self::BitPixel,
+            Dynamic::L(_) => Self::L,
+            Dynamic::Rgb(_) => Self::Rgb,
+            Dynamic::Rgba(_) => Self::Rgba,
+        }
+    }
+}
+
+/// A :class:`Mode` or its name, accepted wherever a mode is expected.
+pub struct ModeArg(pub Mode);
+
+impl<'a> FromPyObject<'a> for ModeArg {
+    fn extract(obj: &'a PyAny) -> PyResult<Self> {
+        if let Ok(mode) = obj.extract::<Mode>() {
+            return Ok(Self(mode));
+        }
+
+        Mode::from_name(obj.extract::<&str>()?).map(Self)
+    }
+}
+
+/// The encoding format of an image.
+#[pyclass]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Gif,
+    WebP,
+    Bmp,
+    Tiff,
+}
+
+impl From<ImageFormat> for ril::ImageFormat {
+    fn from(format: ImageFormat) -> Self {
+        cast_enum!(ImageFormat, Self, format, Png, Jpeg, Gif, WebP, Bmp, Tiff)
+    }
+}
+
+impl ImageFormat {
+    /// Converts from ril's format, which is `None` for unknown formats.
+    pub fn from_ril(format: ril::ImageFormat) -> Option<Self> {
+        match format {
+            ril::ImageFormat::Png => Some(Self::Png),
+            ril::ImageFormat::Jpeg => Some(Self::Jpeg),
+            ril::ImageFormat::Gif => Some(Self::Gif),
+            ril::ImageFormat::WebP => Some(Self::WebP),
+            ril::ImageFormat::Bmp => Some(Self::Bmp),
+            ril::ImageFormat::Tiff => Some(Self::Tiff),
+            _ => None,
+        }
+    }
+}
+
+/// An :class:`ImageFormat` or a file extension such as `"png"`, accepted wherever a format is expected.
+pub struct FormatArg(pub ril::ImageFormat);
+
+impl<'a> FromPyObject<'a> for FormatArg {
+    fn extract(obj: &'a PyAny) -> PyResult<Self> {
+        if let Ok(format) = obj.extract::<ImageFormat>() {
+            return Ok(Self(format.into()));
+        }
+
+        ril::ImageFormat::from_extension(obj.extract::<&str>()?)
+            .map(Self)
+            .map_err(|err| Error::from(err).into())
+    }
+}
+
 /// The method used to dispose a frame before transitioning to the next frame in an image sequence.
 #[pyclass]
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub enum DisposalMethod {
     /// Do not dispose the current frame. Usually not desired for transparent images.
     Keep,
@@ -118,7 +290,7 @@ impl From<ril::DisposalMethod> for DisposalMethod {
 }
 
 #[pyclass]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum WrapStyle {
     NoWrap,
     Word,
@@ -145,7 +317,7 @@ impl From<ril::WrapStyle> for WrapStyle {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 #[pyclass]
 pub enum OverlayMode {
     Replace,
@@ -209,3 +381,5 @@ impl From<ril::VerticalAnchor> for VerticalAnchor {
         cast_enum!(ril::VerticalAnchor, Self, anchor, Top, Center, Bottom)
     }
 }
+
+impl_enum_reduce!(Mode, ImageFormat, WrapStyle, OverlayMode);
@@ -1,6 +1,73 @@
-use crate::pixels::{BitPixel, Rgb, Rgba, L};
-use pyo3::prelude::*;
-use ril::Dynamic;
+use std::{
+    ops::{Deref, DerefMut},
+    path::Path,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+};
+
+use crate::color::{self, relative_luminance};
+use crate::error::Error;
+use crate::image::resized;
+use crate::pixels::{composite, BitPixel, DistanceMetric, Pixel, Rgb, Rgba, L, WHITE};
+use crate::types::ResizeAlgorithm;
+use pyo3::{exceptions::PyValueError, prelude::*, pyclass::CompareOp, PyClass};
+use ril::{Dynamic, Error as RilError, Image as RilImage, ImageFormat};
+
+/// Returns early with a `ValueError` if any of the given images or sequences is closed, see :meth:`Image.close`.
+macro_rules! ensure_open {
+    ($($value:expr),+) => {
+        $(
+            $value.ensure_open()?;
+        )+
+    };
+}
+
+pub(crate) use ensure_open;
+
+/// The amount of leading bytes reported when the format of some data can't be inferred.
+const MAGIC_BYTES_SHOWN: usize = 16;
+
+static NOTEBOOK_PREVIEW_SIZE: AtomicU32 = AtomicU32::new(512);
+
+/// A value that is shared between clones until one of them is mutated, which then copies it.
+///
+/// Mutably dereferencing is what copies the value, so anything that mutates through it is copy-on-write,
+/// and clones that are never mutated cost no more than a reference count.
+#[derive(Clone, Debug, Default)]
+pub struct Shared<T>(Arc<T>);
+
+impl<T: Clone> Shared<T> {
+    pub fn new(value: T) -> Self {
+        Self(Arc::new(value))
+    }
+
+    /// Takes the value out, copying it only if it is still shared.
+    pub fn into_inner(self) -> T {
+        Arc::unwrap_or_clone(self.0)
+    }
+}
+
+impl<T: Clone> From<T> for Shared<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T> Deref for Shared<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Clone> DerefMut for Shared<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        Arc::make_mut(&mut self.0)
+    }
+}
 
 pub fn cast_pixel_to_pyobject(py: Python<'_>, pixel: Dynamic) -> PyObject {
     match pixel {
@@ -10,3 +77,170 @@ pub fn cast_pixel_to_pyobject(py: Python<'_>, pixel: Dynamic) -> PyObject {
         Dynamic::Rgba(v) => Rgba::from(v).into_py(py),
     }
 }
+
+/// Implements `__richcmp__` for equality only, where objects of any other type are never equal.
+pub fn compare_eq<T: PyClass + PartialEq>(
+    py: Python<'_>,
+    this: &T,
+    other: &PyAny,
+    op: CompareOp,
+) -> PyObject {
+    let eq = other
+        .extract::<PyRef<'_, T>>()
+        .map_or(false, |other| *this == *other);
+
+    match op {
+        CompareOp::Eq => eq.into_py(py),
+        CompareOp::Ne => (!eq).into_py(py),
+        _ => py.NotImplemented(),
+    }
+}
+
+/// Infers the encoding format of the given data, failing before any decoding is attempted.
+pub fn infer_format(bytes: &[u8]) -> Result<ImageFormat, Error> {
+    match ImageFormat::infer_encoding(bytes) {
+        ImageFormat::Unknown => Err(Error::UnknownFormat(
+            bytes[..bytes.len().min(MAGIC_BYTES_SHOWN)].to_vec(),
+        )),
+        format => Ok(format),
+    }
+}
+
+/// Infers the encoding format of a file from its extension.
+pub fn format_from_path(path: &Path) -> Result<ImageFormat, Error> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .ok_or(RilError::UnknownEncodingFormat)?;
+
+    Ok(ImageFormat::from_extension(extension)?)
+}
+
+/// Ensures the bounding box is not empty and lies within the given dimensions.
+pub fn ensure_box(dimensions: (u32, u32), x1: u32, y1: u32, x2: u32, y2: u32) -> PyResult<()> {
+    if x1 >= x2 || y1 >= y2 || x2 > dimensions.0 || y2 > dimensions.1 {
+        return Err(PyValueError::new_err(format!(
+            "Invalid bounding box ({}, {}, {}, {}) for an image with dimensions {:?}",
+            x1, y1, x2, y2, dimensions
+        )));
+    }
+
+    Ok(())
+}
+
+/// Ensures neither dimension is zero, since empty images are never constructible from Python.
+pub fn ensure_dimensions(width: u32, height: u32) -> PyResult<()> {
+    if width == 0 || height == 0 {
+        return Err(PyValueError::new_err(format!(
+            "Image dimensions must be non-zero, got ({}, {})",
+            width, height
+        )));
+    }
+
+    Ok(())
+}
+
+/// Sets the maximum width or height of images displayed in Jupyter notebooks.
+///
+/// Larger images are downscaled, keeping their aspect ratio, before being embedded in the notebook.
+///
+/// Parameters
+/// ----------
+/// size: int
+///     The maximum dimension of the preview, in pixels. Defaults to 512.
+#[pyfunction]
+#[pyo3(text_signature = "(size)")]
+pub fn set_notebook_preview_size(size: u32) {
+    NOTEBOOK_PREVIEW_SIZE.store(size.max(1), Ordering::Relaxed);
+}
+
+/// Finds the color of a palette that is closest to the given pixel, such as for snapping colors to a palette.
+///
+/// Pixels are compared as RGBA, see :meth:`Pixel.distance`.
+///
+/// Parameters
+/// ----------
+/// pixel: :class:`.Pixel`
+///     The pixel to look up.
+/// palette: List[:class:`.Pixel`]
+///     The colors to choose from.
+/// metric: str, default: "euclidean"
+///     The metric to compare colors with, see :meth:`Pixel.distance`.
+///
+/// Returns
+/// -------
+/// int
+///     The index of the closest color in `palette`. The first of equally close colors is chosen.
+///
+/// Raises
+/// ------
+/// ValueError
+///     The palette is empty, or the metric is invalid.
+#[pyfunction]
+#[pyo3(signature = (pixel, palette, metric = "euclidean"))]
+#[pyo3(text_signature = "(pixel, palette, metric = 'euclidean')")]
+pub fn nearest_color(pixel: Pixel, palette: Vec<Pixel>, metric: &str) -> PyResult<usize> {
+    let metric = DistanceMetric::from_name(metric)?;
+
+    palette
+        .iter()
+        .map(|color| metric.distance_squared(pixel.inner, color.inner))
+        .enumerate()
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(index, _)| index)
+        .ok_or_else(|| PyValueError::new_err("Expected a non-empty palette"))
+}
+
+/// Returns the contrast ratio between two colors as defined by WCAG 2, such as for checking that text is legible.
+///
+/// Translucent colors are composited first, `b` over white and then `a` over `b`, like text of color `a` on a background
+/// of color `b`. Opaque colors can be given in either order.
+///
+/// Parameters
+/// ----------
+/// a: :class:`.Pixel`
+///     The foreground color, such as of text.
+/// b: :class:`.Pixel`
+///     The background color.
+///
+/// Returns
+/// -------
+/// float
+///     The contrast ratio, from `1` for colors of the same luminance to `21` for black on white.
+///     WCAG level AA requires a ratio of at least 4.5 for normal text, and 3 for large text.
+///
+/// Examples
+/// --------
+///
+/// .. code-block:: python3
+///
+///     background = image.average_color((x1, y1, x2, y2))
+///     assert ril.contrast_ratio(text_color, background) >= 4.5
+#[pyfunction]
+#[pyo3(text_signature = "(a, b)")]
+pub fn contrast_ratio(a: Pixel, b: Pixel) -> f64 {
+    let background = composite(b.inner, WHITE);
+
+    color::contrast_ratio(
+        relative_luminance(composite(a.inner, background)),
+        relative_luminance(background),
+    )
+}
+
+/// Returns a copy of the image downscaled to fit the notebook preview size, or `None` if it already fits.
+pub fn notebook_preview(image: &RilImage) -> Option<RilImage> {
+    let max = NOTEBOOK_PREVIEW_SIZE.load(Ordering::Relaxed);
+    let (width, height) = image.dimensions();
+
+    if width <= max && height <= max {
+        return None;
+    }
+
+    let scale = max as f64 / width.max(height) as f64;
+    let dimensions = (
+        ((width as f64 * scale).round() as u32).max(1),
+        ((height as f64 * scale).round() as u32).max(1),
+    );
+
+    Some(resized(image, dimensions, ResizeAlgorithm::Bilinear))
+}
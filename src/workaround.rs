@@ -1,7 +1,244 @@
-use std::ops::DerefMut;
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    ops::DerefMut,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, OnceLock, RwLock,
+    },
+};
 
 use ril::{Font, Draw, Pixel, OverlayMode, WrapStyle, Image, HorizontalAnchor, VerticalAnchor};
-use fontdue::layout::{CoordinateSystem, TextStyle, Layout, LayoutSettings};
+use fontdue::{
+    layout::{CoordinateSystem, GlyphRasterConfig, TextStyle, Layout, LayoutSettings},
+    Metrics,
+};
+
+type RasterizedGlyph = Arc<(Metrics, Vec<u8>)>;
+
+/// The approximate memory the cached glyphs of all fonts may take up, in bytes,
+/// before the least recently used ones are evicted.
+const GLYPH_CACHE_BUDGET: usize = 32 * 1024 * 1024;
+
+/// Rasterized glyphs of every font, keyed by glyph, size and font hash.
+static GLYPH_CACHE: RwLock<Option<GlyphCache>> = RwLock::new(None);
+
+/// The approximate memory a cached glyph takes up, in bytes.
+fn glyph_size(glyph: &RasterizedGlyph) -> usize {
+    std::mem::size_of::<(GlyphRasterConfig, Metrics)>() + glyph.1.len()
+}
+
+/// Glyphs along with when they were last used, which is updated while only holding the read lock.
+#[derive(Default)]
+struct GlyphCache {
+    glyphs: HashMap<GlyphRasterConfig, (RasterizedGlyph, AtomicU64)>,
+    bytes: usize,
+    clock: AtomicU64,
+}
+
+impl GlyphCache {
+    fn get(&self, key: &GlyphRasterConfig) -> Option<RasterizedGlyph> {
+        let (glyph, used) = self.glyphs.get(key)?;
+        let now = self.clock.fetch_add(1, Ordering::Relaxed);
+        used.store(now, Ordering::Relaxed);
+
+        Some(Arc::clone(glyph))
+    }
+
+    fn insert(&mut self, key: GlyphRasterConfig, glyph: RasterizedGlyph) {
+        self.bytes += glyph_size(&glyph);
+        let used = AtomicU64::new(self.clock.fetch_add(1, Ordering::Relaxed));
+
+        if let Some((replaced, _)) = self.glyphs.insert(key, (glyph, used)) {
+            self.bytes -= glyph_size(&replaced);
+        }
+        if self.bytes > GLYPH_CACHE_BUDGET {
+            self.evict();
+        }
+    }
+
+    /// Evicts the least recently used glyphs until the rest take up at most three quarters of the budget,
+    /// so that the next few insertions don't have to evict again.
+    fn evict(&mut self) {
+        let mut glyphs = self
+            .glyphs
+            .iter()
+            .map(|(key, (_, used))| (used.load(Ordering::Relaxed), *key))
+            .collect::<Vec<_>>();
+        glyphs.sort_unstable_by_key(|&(used, _)| used);
+
+        for (_, key) in glyphs {
+            if self.bytes <= GLYPH_CACHE_BUDGET / 4 * 3 {
+                break;
+            }
+            if let Some((glyph, _)) = self.glyphs.remove(&key) {
+                self.bytes -= glyph_size(&glyph);
+            }
+        }
+    }
+}
+
+/// Rasterizes the glyph, reusing the result of previous rasterizations.
+pub fn rasterize_cached(font: &fontdue::Font, key: GlyphRasterConfig) -> RasterizedGlyph {
+    if let Some(glyph) = GLYPH_CACHE
+        .read()
+        .ok()
+        .and_then(|cache| cache.as_ref()?.get(&key))
+    {
+        return glyph;
+    }
+
+    let glyph = Arc::new(font.rasterize_config(key));
+    if let Ok(mut cache) = GLYPH_CACHE.write() {
+        cache
+            .get_or_insert_with(GlyphCache::default)
+            .insert(key, Arc::clone(&glyph));
+    }
+
+    glyph
+}
+
+/// Rasterizes the glyphs of all characters in `text` at the given size ahead of time.
+pub fn prewarm_glyphs(font: &fontdue::Font, text: &str, size: f32) {
+    for character in text.chars() {
+        rasterize_cached(
+            font,
+            GlyphRasterConfig {
+                glyph_index: font.lookup_glyph_index(character),
+                px: size,
+                font_hash: font.file_hash(),
+            },
+        );
+    }
+}
+
+/// Returns the amount of cached glyphs of the font and the approximate memory they take up, in bytes.
+pub fn glyph_cache_stats(font: &fontdue::Font) -> (usize, usize) {
+    let cache = match GLYPH_CACHE.read() {
+        Ok(cache) => cache,
+        Err(_) => return (0, 0),
+    };
+
+    cache
+        .iter()
+        .flat_map(|cache| &cache.glyphs)
+        .filter(|(key, _)| key.font_hash == font.file_hash())
+        .fold((0, 0), |(count, bytes), (_, (glyph, _))| {
+            (count + 1, bytes + glyph_size(glyph))
+        })
+}
+
+/// The amount of spaces a tab expands to by default.
+pub const DEFAULT_TAB_WIDTH: usize = 4;
+
+/// The coverage at or above which a pixel is drawn by default in [`RenderMode::Mono`].
+pub const DEFAULT_MONO_THRESHOLD: u8 = 128;
+
+/// How the coverage of glyphs is turned into the alpha they are drawn with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RenderMode {
+    /// Anti-aliased text, where partially covered pixels are blended.
+    #[default]
+    Smooth,
+    /// Aliased text, where pixels are either fully drawn or not drawn at all.
+    Mono,
+}
+
+impl RenderMode {
+    /// Returns the alpha to draw a pixel with the given coverage with. In [`RenderMode::Mono`],
+    /// pixels with a coverage of at least `threshold` are fully drawn.
+    pub const fn alpha(self, coverage: u8, threshold: u8) -> u8 {
+        match self {
+            Self::Smooth => coverage,
+            Self::Mono if coverage >= threshold => u8::MAX,
+            Self::Mono => 0,
+        }
+    }
+}
+
+/// Normalizes `\r\n` and `\r` line breaks to `\n` and expands tabs to `tab_width` spaces,
+/// since fontdue renders neither tabs nor carriage returns.
+pub fn normalize_text(text: &str, tab_width: usize) -> Cow<'_, str> {
+    if !text.contains(['\r', '\t']) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut normalized = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(character) = chars.next() {
+        match character {
+            '\r' => {
+                chars.next_if_eq(&'\n');
+                normalized.push('\n');
+            }
+            '\t' => normalized.extend(std::iter::repeat(' ').take(tab_width)),
+            _ => normalized.push(character),
+        }
+    }
+
+    Cow::Owned(normalized)
+}
+
+/// What to do with characters that have no glyph in the font.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MissingGlyphPolicy {
+    /// Leave the characters out of the text.
+    Skip,
+    /// Render a replacement character instead, or the missing glyph of the font if there is none.
+    #[default]
+    Replace,
+    /// Refuse to render the text.
+    Error,
+}
+
+/// Whether the font has no glyph for the character. Whitespace and control characters are never
+/// missing, since they are laid out rather than drawn.
+fn is_missing_glyph(font: &fontdue::Font, character: char) -> bool {
+    !character.is_whitespace() && !character.is_control() && font.lookup_glyph_index(character) == 0
+}
+
+/// Returns the characters of `text` the font has no glyph for, along with their character indices.
+pub fn missing_glyphs(font: &fontdue::Font, text: &str) -> Vec<(usize, char)> {
+    text.chars()
+        .enumerate()
+        .filter(|(_, character)| is_missing_glyph(font, *character))
+        .collect()
+}
+
+/// Skips or replaces the characters of `text` the font has no glyph for. [`MissingGlyphPolicy::Error`]
+/// leaves the text untouched, since it has to be checked for with [`missing_glyphs`] beforehand.
+pub fn apply_missing_glyph_policy<'a>(
+    font: &fontdue::Font,
+    text: Cow<'a, str>,
+    policy: MissingGlyphPolicy,
+    replacement: Option<char>,
+) -> Cow<'a, str> {
+    let replacement = match (policy, replacement) {
+        (MissingGlyphPolicy::Skip, _) => None,
+        (MissingGlyphPolicy::Replace, Some(replacement)) => Some(replacement),
+        (MissingGlyphPolicy::Replace, None) | (MissingGlyphPolicy::Error, _) => return text,
+    };
+
+    if !text
+        .chars()
+        .any(|character| is_missing_glyph(font, character))
+    {
+        return text;
+    }
+
+    Cow::Owned(
+        text.chars()
+            .filter_map(|character| {
+                if is_missing_glyph(font, character) {
+                    replacement
+                } else {
+                    Some(character)
+                }
+            })
+            .collect(),
+    )
+}
 
 /// Represents a text segment that can be drawn.
 ///
@@ -41,6 +278,20 @@ pub struct OwnedTextSegment<P: Pixel> {
     /// If this is used in a [`TextLayout`], this is ignored and [`TextLayout::with_wrap`] is
     /// used instead.
     pub wrap: WrapStyle,
+    /// The amount of spaces a tab expands to. If this is used in a [`TextLayout`], this is ignored
+    /// and the tab width of the layout is used instead.
+    pub tab_width: usize,
+    /// How glyphs are rendered. If this is used in a [`TextLayout`], this is ignored and the
+    /// render mode of the layout is used instead.
+    pub render_mode: RenderMode,
+    /// The coverage at or above which a pixel is drawn in [`RenderMode::Mono`].
+    pub mono_threshold: u8,
+    /// What to do with characters the font has no glyph for. If this is used in a [`TextLayout`],
+    /// this is ignored and the policy of the layout is used instead.
+    pub missing_glyph_policy: MissingGlyphPolicy,
+    /// The character rendered in place of missing glyphs under [`MissingGlyphPolicy::Replace`].
+    /// If `None`, the missing glyph of the font is rendered.
+    pub replacement_char: Option<char>,
 }
 
 impl<P: Pixel> OwnedTextSegment<P> {
@@ -66,6 +317,11 @@ impl<P: Pixel> OwnedTextSegment<P> {
             overlay: OverlayMode::Merge,
             size,
             wrap: WrapStyle::Word,
+            tab_width: DEFAULT_TAB_WIDTH,
+            render_mode: RenderMode::Smooth,
+            mono_threshold: DEFAULT_MONO_THRESHOLD,
+            missing_glyph_policy: MissingGlyphPolicy::default(),
+            replacement_char: None,
         }
     }
 
@@ -124,16 +380,79 @@ impl<P: Pixel> OwnedTextSegment<P> {
         });
         layout.append(
             &[self.font.inner()],
-            &TextStyle::with_user_data(&self.text, self.size, 0, (self.fill, self.overlay)),
+            &TextStyle::with_user_data(
+                &apply_missing_glyph_policy(
+                    self.font.inner(),
+                    normalize_text(&self.text, self.tab_width),
+                    self.missing_glyph_policy,
+                    self.replacement_char,
+                ),
+                self.size,
+                0,
+                (self.fill, self.overlay),
+            ),
         );
         layout
     }
+
+    /// Returns the position and alpha of every pixel the text covers when drawn.
+    #[must_use]
+    pub fn coverage(&self) -> Vec<(i64, i64, u8)> {
+        coverage(
+            &[self.font.inner()],
+            &self.layout(),
+            |_| (0.0, 0.0),
+            (self.render_mode, self.mono_threshold),
+        )
+    }
+}
+
+/// Returns the position and alpha of every pixel covered by the glyphs of the layout, where the glyphs
+/// of every line are moved by the offset `line_offset` returns for the index of the line.
+fn coverage<U: Copy>(
+    fonts: &[&fontdue::Font],
+    layout: &Layout<U>,
+    line_offset: impl Fn(usize) -> (f32, f32),
+    (render_mode, threshold): (RenderMode, u8),
+) -> Vec<(i64, i64, u8)> {
+    let glyphs = layout.glyphs();
+    let lines = match layout.lines() {
+        Some(lines) if !glyphs.is_empty() => lines,
+        _ => return Vec::new(),
+    };
+
+    let mut covered = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let (ox, oy) = line_offset(i);
+
+        for glyph in &glyphs[line.glyph_start..=line.glyph_end] {
+            let glyph_bitmap = rasterize_cached(fonts[glyph.font_index], glyph.key);
+            let (metrics, bitmap) = (&glyph_bitmap.0, &glyph_bitmap.1);
+
+            if metrics.width == 0 || glyph.char_data.is_whitespace() || metrics.height == 0 {
+                continue;
+            }
+
+            let (x, y) = ((glyph.x + ox) as i64, (glyph.y + oy) as i64);
+            for (row, y) in bitmap.chunks_exact(metrics.width).zip(y..) {
+                for (value, x) in row.iter().zip(x..) {
+                    let value = render_mode.alpha(*value, threshold);
+                    if value > 0 {
+                        covered.push((x, y, value));
+                    }
+                }
+            }
+        }
+    }
+
+    covered
 }
 
 fn render_layout_as_ref<P: Pixel>(
     image: &mut Image<P>,
     font: &fontdue::Font,
     layout: &Layout<(P, OverlayMode)>,
+    (render_mode, threshold): (RenderMode, u8),
 ) {
     let glyphs = layout.glyphs();
     if glyphs.is_empty() {
@@ -145,7 +464,8 @@ fn render_layout_as_ref<P: Pixel>(
     for line in lines {
         for glyph in &glyphs[line.glyph_start..=line.glyph_end] {
             let (fill, overlay) = glyph.user_data;
-            let (metrics, bitmap) = font.rasterize_config(glyph.key);
+            let glyph_bitmap = rasterize_cached(font, glyph.key);
+            let (metrics, bitmap) = (&glyph_bitmap.0, &glyph_bitmap.1);
 
             if metrics.width == 0 || glyph.char_data.is_whitespace() || metrics.height == 0 {
                 continue;
@@ -159,7 +479,7 @@ fn render_layout_as_ref<P: Pixel>(
                         (x as u32, y as u32)
                     };
 
-                    let value = *value;
+                    let value = render_mode.alpha(*value, threshold);
                     if value == 0 {
                         continue;
                     }
@@ -177,6 +497,7 @@ fn render_layout<P: Pixel>(
     image: &mut Image<P>,
     fonts: &[&fontdue::Font],
     layout: &Layout<(P, OverlayMode)>,
+    (render_mode, threshold): (RenderMode, u8),
 ) {
     let glyphs = layout.glyphs();
     if glyphs.is_empty() {
@@ -189,7 +510,8 @@ fn render_layout<P: Pixel>(
         for glyph in &glyphs[line.glyph_start..=line.glyph_end] {
             let (fill, overlay) = glyph.user_data;
             let font = &fonts[glyph.font_index];
-            let (metrics, bitmap) = font.rasterize_config(glyph.key);
+            let glyph_bitmap = rasterize_cached(font, glyph.key);
+            let (metrics, bitmap) = (&glyph_bitmap.0, &glyph_bitmap.1);
 
             if metrics.width == 0 || glyph.char_data.is_whitespace() || metrics.height == 0 {
                 continue;
@@ -203,7 +525,7 @@ fn render_layout<P: Pixel>(
                         (x as u32, y as u32)
                     };
 
-                    let value = *value;
+                    let value = render_mode.alpha(*value, threshold);
                     if value == 0 {
                         continue;
                     }
@@ -226,6 +548,7 @@ fn render_layout_with_alignment<P: Pixel>(
     fx: f32,
     ox: f32,
     oy: f32,
+    (render_mode, threshold): (RenderMode, u8),
 ) {
     let glyphs = layout.glyphs();
     if glyphs.is_empty() {
@@ -240,7 +563,8 @@ fn render_layout_with_alignment<P: Pixel>(
         for glyph in &glyphs[line.glyph_start..=line.glyph_end] {
             let (fill, overlay) = glyph.user_data;
             let font = &fonts[glyph.font_index];
-            let (metrics, bitmap) = font.rasterize_config(glyph.key);
+            let glyph_bitmap = rasterize_cached(font, glyph.key);
+            let (metrics, bitmap) = (&glyph_bitmap.0, &glyph_bitmap.1);
 
             if metrics.width == 0 || glyph.char_data.is_whitespace() || metrics.height == 0 {
                 continue;
@@ -257,7 +581,7 @@ fn render_layout_with_alignment<P: Pixel>(
                         (x as u32, y as u32)
                     };
 
-                    let value = *value;
+                    let value = render_mode.alpha(*value, threshold);
                     if value == 0 {
                         continue;
                     }
@@ -273,7 +597,12 @@ fn render_layout_with_alignment<P: Pixel>(
 
 impl<P: Pixel> Draw<P> for OwnedTextSegment<P> {
     fn draw<I: DerefMut<Target = Image<P>>>(&self, mut image: I) {
-        render_layout_as_ref(&mut *image, self.font.inner(), &self.layout());
+        render_layout_as_ref(
+            &mut *image,
+            self.font.inner(),
+            &self.layout(),
+            (self.render_mode, self.mono_threshold),
+        );
     }
 }
 
@@ -298,6 +627,15 @@ pub struct OwnedTextLayout<P: Pixel> {
     pub(crate) settings: LayoutSettings,
     pub(crate) x_anchor: HorizontalAnchor,
     pub(crate) y_anchor: VerticalAnchor,
+    pub(crate) tab_width: usize,
+    pub(crate) render_mode: RenderMode,
+    pub(crate) mono_threshold: u8,
+    pub(crate) missing_glyph_policy: MissingGlyphPolicy,
+    pub(crate) replacement_char: Option<char>,
+    /// The measured dimensions, cleared whenever the text or the settings change.
+    dimensions: OnceLock<(u32, u32)>,
+    /// The text of every segment as it was appended, kept so it can be laid out again with [`Self::relayout`].
+    texts: Vec<(String, f32, (P, OverlayMode))>,
 }
 
 impl<P: Pixel> OwnedTextLayout<P> {
@@ -310,12 +648,22 @@ impl<P: Pixel> OwnedTextLayout<P> {
             settings: LayoutSettings::default(),
             x_anchor: HorizontalAnchor::default(),
             y_anchor: VerticalAnchor::default(),
+            tab_width: DEFAULT_TAB_WIDTH,
+            render_mode: RenderMode::Smooth,
+            mono_threshold: DEFAULT_MONO_THRESHOLD,
+            missing_glyph_policy: MissingGlyphPolicy::default(),
+            replacement_char: None,
+            dimensions: OnceLock::new(),
+            texts: Vec::new(),
         }
     }
 
     pub fn set_settings(&mut self, settings: LayoutSettings) {
+        // Resetting the layout clears its text as well.
         self.inner.reset(&settings);
+        self.texts.clear();
         self.settings = settings;
+        self.dimensions = OnceLock::new();
     }
 
     /// Sets the position of the text layout.
@@ -356,16 +704,36 @@ impl<P: Pixel> OwnedTextLayout<P> {
 
     /// Adds a text segment to the text layout.
     pub fn push_segment(&mut self, segment: OwnedTextSegment<P>) {
+        let text = apply_missing_glyph_policy(
+            segment.font.inner(),
+            normalize_text(&segment.text, self.tab_width),
+            self.missing_glyph_policy,
+            self.replacement_char,
+        );
         self.fonts.push(segment.font.into_inner());
         self.inner.append(
             &self.fonts,
-            &TextStyle::with_user_data(
-                &segment.text,
-                segment.size,
-                0,
-                (segment.fill, segment.overlay),
-            ),
+            &TextStyle::with_user_data(&text, segment.size, 0, (segment.fill, segment.overlay)),
         );
+        self.texts.push((
+            text.into_owned(),
+            segment.size,
+            (segment.fill, segment.overlay),
+        ));
+        self.dimensions = OnceLock::new();
+    }
+
+    /// Lays out the text of every segment added so far again from scratch, such as when a panic left the
+    /// layout half updated.
+    pub fn relayout(&mut self) {
+        self.inner.reset(&self.settings);
+        for (text, size, user_data) in &self.texts {
+            self.inner.append(
+                &self.fonts,
+                &TextStyle::with_user_data(text, *size, 0, *user_data),
+            );
+        }
+        self.dimensions = OnceLock::new();
     }
 
     /// Takes this text layout and returns it with the given text segment added to the text layout.
@@ -426,6 +794,11 @@ impl<P: Pixel> OwnedTextLayout<P> {
         self.y_anchor = VerticalAnchor::Center;
     }
 
+    /// Returns the fill of every glyph laid out so far.
+    pub fn fills(&self) -> impl Iterator<Item = &P> {
+        self.inner.glyphs().iter().map(|glyph| &glyph.user_data.0)
+    }
+
     fn line_widths(&self) -> (Vec<u32>, u32, u32) {
         let glyphs = self.inner.glyphs();
         if glyphs.is_empty() {
@@ -449,10 +822,14 @@ impl<P: Pixel> OwnedTextLayout<P> {
         (widths, max_width, self.inner.height() as u32)
     }
 
-    /// Returns the width and height of the text. This is a slightly expensive operation and should
-    /// be used sparingly - it is not a simple getter.
+    /// Returns the width and height of the text. They are measured on the first call and cached
+    /// until a text segment is added or the settings change.
     #[must_use]
     pub fn dimensions(&self) -> (u32, u32) {
+        *self.dimensions.get_or_init(|| self.measure())
+    }
+
+    fn measure(&self) -> (u32, u32) {
         let glyphs = self.inner.glyphs();
         if glyphs.is_empty() {
             return (0, 0);
@@ -480,8 +857,7 @@ impl<P: Pixel> OwnedTextLayout<P> {
         (width, self.inner.height() as u32)
     }
 
-    /// Returns the width of the text. This is a slightly expensive operation and is not a simple
-    /// getter.
+    /// Returns the width of the text, see [`dimensions`][TextLayout::dimensions].
     ///
     /// If you want both width and height, use [`dimensions`][TextLayout::dimensions].
     #[must_use]
@@ -489,8 +865,7 @@ impl<P: Pixel> OwnedTextLayout<P> {
         self.dimensions().0
     }
 
-    /// Returns the height of the text. This is a slightly expensive operation and is not a simple
-    /// getter.
+    /// Returns the height of the text, see [`dimensions`][TextLayout::dimensions].
     ///
     /// If you want both width and height, use [`dimensions`][TextLayout::dimensions].
     #[must_use]
@@ -521,6 +896,92 @@ impl<P: Pixel> OwnedTextLayout<P> {
         (x, y, x + width, y + height)
     }
 
+    /// Returns the amount of lines the text takes up, after wrapping.
+    #[must_use]
+    pub fn line_count(&self) -> usize {
+        self.inner.lines().map_or(0, Vec::len)
+    }
+
+    /// Returns the bounding box of every line, positioned the same way as
+    /// [`bounding_box`][TextLayout::bounding_box] and the rendered text.
+    #[must_use]
+    pub fn line_boxes(&self) -> Vec<(u32, u32, u32, u32)> {
+        let lines = match self.inner.lines() {
+            Some(lines) => lines,
+            None => return Vec::new(),
+        };
+
+        let (widths, max_width, fx, ox, oy) = self.calculate_offsets();
+        lines
+            .iter()
+            .zip(widths)
+            .map(|(line, width)| {
+                let left = self.settings.x + ((max_width - width) as f32).mul_add(fx, ox);
+                let top = line.baseline_y - line.max_ascent + oy;
+                let bottom = line.baseline_y - line.min_descent + oy;
+
+                (
+                    left.max(0.0) as u32,
+                    top.max(0.0) as u32,
+                    (left + width as f32).ceil().max(0.0) as u32,
+                    bottom.ceil().max(0.0) as u32,
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the position and alpha of every pixel the text covers when drawn, after anchoring.
+    #[must_use]
+    pub fn coverage(&self) -> Vec<(i64, i64, u8)> {
+        self.coverage_at((0.0, 0.0))
+    }
+
+    /// Returns the position and alpha of every pixel the text covers when drawn moved by the given offset,
+    /// see [`Self::draw_at`].
+    #[must_use]
+    pub fn coverage_at(&self, (dx, dy): (f32, f32)) -> Vec<(i64, i64, u8)> {
+        let (widths, max_width, fx, ox, oy) = self.calculate_offsets();
+        let (ox, oy) = (ox + dx, oy + dy);
+
+        coverage(
+            &self.fonts.iter().collect::<Vec<_>>(),
+            &self.inner,
+            |i| (((max_width - widths[i]) as f32).mul_add(fx, ox), oy),
+            (self.render_mode, self.mono_threshold),
+        )
+    }
+
+    /// Draws the text layout moved by the given offset, without laying out its text again.
+    pub fn draw_at(&self, image: &mut Image<P>, (dx, dy): (f32, f32)) {
+        let render_mode = (self.render_mode, self.mono_threshold);
+
+        // Skips the calculation of offsets
+        if self.x_anchor == HorizontalAnchor::Left
+            && self.y_anchor == VerticalAnchor::Top
+            && (dx, dy) == (0.0, 0.0)
+        {
+            render_layout(
+                image,
+                &self.fonts.iter().collect::<Vec<&fontdue::Font>>()[..],
+                &self.inner,
+                render_mode,
+            );
+        }
+
+        let (widths, max_width, fx, ox, oy) = self.calculate_offsets();
+        render_layout_with_alignment(
+            image,
+            &self.fonts,
+            &self.inner,
+            widths,
+            max_width,
+            fx,
+            ox + dx,
+            oy + dy,
+            render_mode,
+        );
+    }
+
     fn calculate_offsets(&self) -> (Vec<u32>, u32, f32, f32, f32) {
         let (widths, width, height) = self.line_widths();
 
@@ -541,24 +1002,7 @@ impl<P: Pixel> OwnedTextLayout<P> {
 
 impl<P: Pixel> Draw<P> for OwnedTextLayout<P> {
     fn draw<I: DerefMut<Target = Image<P>>>(&self, mut image: I) {
-        let image = &mut *image;
-
-        // Skips the calculation of offsets
-        if self.x_anchor == HorizontalAnchor::Left && self.y_anchor == VerticalAnchor::Top {
-            render_layout(image, &self.fonts.iter().collect::<Vec<&fontdue::Font>>()[..], &self.inner);
-        }
-
-        let (widths, max_width, fx, ox, oy) = self.calculate_offsets();
-        render_layout_with_alignment(
-            image,
-            &self.fonts,
-            &self.inner,
-            widths,
-            max_width,
-            fx,
-            ox,
-            oy,
-        );
+        self.draw_at(&mut *image, (0.0, 0.0));
     }
 }
 
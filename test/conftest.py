@@ -1,6 +1,11 @@
+import os
+from typing import Iterator, List
+
 import pytest
 import requests
 
+import ril
+
 BASE_URL: str = 'https://raw.githubusercontent.com/Cryptex-github/ril-py/main/test/images/'
 
 @pytest.fixture
@@ -9,3 +14,27 @@ def fetch_file():
         return requests.get(BASE_URL + filename).content
     
     return inner
+
+FONT_PATHS = (
+    '/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf',
+    '/Library/Fonts/Arial.ttf',
+    'C:\\Windows\\Fonts\\arial.ttf',
+)
+
+@pytest.fixture
+def font_path() -> str:
+    for path in FONT_PATHS:
+        if os.path.exists(path):
+            return path
+
+    pytest.skip('no system font available')
+
+@pytest.fixture
+def events() -> Iterator[List[dict]]:
+    recorded: List[dict] = []
+    ril.set_trace_hook(recorded.append)
+
+    try:
+        yield recorded
+    finally:
+        ril.set_trace_hook(None)
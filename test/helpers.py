@@ -0,0 +1,35 @@
+import random
+
+from ril import Image, Pixel
+
+def gradient(width: int = 64, height: int = 8, mode: str = 'L') -> Image:
+    """Returns an image ramping from black to white from left to right.
+
+    In the other modes the ramp is in the red channel, and green ramps up from top to bottom so no two rows are the same.
+    """
+    ramp = bytes(x * 255 // max(width - 1, 1) for x in range(width))
+    if mode == 'L':
+        return Image.from_buffer(width, height, 'L', ramp * height)
+
+    data = bytearray(width * height * 3)
+    data[0::3] = ramp * height
+    data[1::3] = b''.join(bytes([y * 255 // max(height - 1, 1)]) * width for y in range(height))
+    data[2::3] = b'\x80' * (width * height)
+
+    image = Image.from_buffer(width, height, 'RGB', data)
+    if mode != 'RGB':
+        image.convert(mode)
+
+    return image
+
+def noise(width: int = 24, height: int = 18, seed: int = 0, alpha: bool = False) -> Image:
+    """Returns an image of random pixels, which is the same for the same seed."""
+    rng = random.Random(seed)
+
+    def pixel() -> Pixel:
+        if alpha:
+            return Pixel.from_rgba(rng.randrange(256), rng.randrange(256), rng.randrange(256), rng.randrange(256))
+
+        return Pixel.from_rgb(rng.randrange(256), rng.randrange(256), rng.randrange(256))
+
+    return Image.from_pixels(width, [pixel() for _ in range(width * height)])
@@ -0,0 +1,80 @@
+from typing import List, Tuple
+
+import pytest
+
+import ril
+from ril import Image, Pixel
+
+Rect = Tuple[int, int, int, int]
+
+SIZES = [(5, 3), (2, 7), (6, 6), (1, 1), (4, 2), (3, 7), (8, 1), (2, 2)]
+
+
+def sprites() -> List[Image]:
+    return [
+        Image.from_pixels(w, [Pixel.from_rgba(i * 30, (x * 11) % 256, 200 - i * 20, 255) for x in range(w * h)])
+        for i, (w, h) in enumerate(SIZES)
+    ]
+
+def overlaps(a: Rect, b: Rect, padding: int = 0) -> bool:
+    return a[0] < b[2] + padding and b[0] < a[2] + padding and a[1] < b[3] + padding and b[1] < a[3] + padding
+
+def flat(image: Image) -> list:
+    return [pixel for row in image.pixels() for pixel in row]
+
+
+@pytest.mark.parametrize('padding', [0, 1, 3])
+def test_pack_atlas(padding: int) -> None:
+    images = sprites()
+    atlas, rects = ril.pack_atlas(images, max_width=12, padding=padding)
+
+    assert len(rects) == len(images)
+    assert atlas.width <= 12
+
+    for i, (rect, image) in enumerate(zip(rects, images)):
+        x1, y1, x2, y2 = rect
+        assert (x2 - x1, y2 - y1) == image.dimensions
+        assert x2 <= atlas.width and y2 <= atlas.height
+        assert atlas.view(*rect).pixels_flat() == flat(image)
+
+        for other in rects[i + 1:]:
+            assert not overlaps(rect, other, padding)
+
+def test_pack_atlas_deterministic() -> None:
+    atlas, rects = ril.pack_atlas(sprites(), max_width=10, padding=1)
+    again, again_rects = ril.pack_atlas(sprites(), max_width=10, padding=1)
+
+    assert rects == again_rects
+    assert atlas.pixels() == again.pixels()
+
+def test_pack_atlas_power_of_two() -> None:
+    atlas, rects = ril.pack_atlas(sprites(), max_width=20, power_of_two=True)
+
+    assert atlas.width == 16
+    assert atlas.height & (atlas.height - 1) == 0
+    assert all(x2 <= 16 for _, _, x2, _ in rects)
+    assert atlas.get_pixel(15, atlas.height - 1).a == 0
+
+def test_pack_atlas_modes() -> None:
+    gray = Image.new(2, 2, Pixel.from_l(80))
+    rgba = Image.new(3, 1, Pixel.from_rgba(10, 20, 30, 40))
+
+    atlas, rects = ril.pack_atlas([gray, rgba], max_width=4)
+
+    assert atlas.mode == 'RGBA'
+    assert atlas.dimensions == (3, 3)
+    assert rects == [(0, 0, 2, 2), (0, 2, 3, 3)]
+    assert atlas.get_pixel(1, 1) == ril.Rgba(80, 80, 80, 255)
+    assert atlas.get_pixel(2, 0).a == 0
+
+def test_pack_atlas_errors() -> None:
+    images = sprites()
+
+    with pytest.raises(ValueError, match='index 6'):
+        ril.pack_atlas(images, max_width=7)
+
+    with pytest.raises(ValueError, match='index 0'):
+        ril.pack_atlas(images, max_width=7, power_of_two=True)
+
+    with pytest.raises(ValueError):
+        ril.pack_atlas([], max_width=10)
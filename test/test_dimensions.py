@@ -0,0 +1,67 @@
+import pytest
+
+from ril import Frame, Image, ImageSequence, Pixel, ResizeAlgorithm
+
+FILL = Pixel.from_rgb(255, 0, 0)
+
+
+def image() -> Image:
+    return Image.new(4, 4, FILL)
+
+
+@pytest.mark.parametrize('width, height', [(0, 10), (10, 0), (0, 0)])
+def test_new_zero_dimensions(width: int, height: int) -> None:
+    with pytest.raises(ValueError):
+        Image.new(width, height, FILL)
+
+    with pytest.raises(ValueError):
+        Image.from_packed_bits(width, height, b'')
+
+    with pytest.raises(ValueError):
+        Image.from_buffer(width, height, 'L', b'')
+
+
+def test_from_pixels_zero_dimensions() -> None:
+    with pytest.raises(ValueError):
+        Image.from_pixels(0, [FILL])
+
+    with pytest.raises(ValueError):
+        Image.from_pixels(4, [])
+
+    with pytest.raises(ValueError):
+        Image.from_pixels(4, [FILL] * 6)
+
+    assert Image.from_pixels(2, [FILL] * 6).dimensions == (2, 3)
+
+
+@pytest.mark.parametrize('width, height', [(0, 10), (10, 0), (0, 0)])
+def test_resize_zero_dimensions(width: int, height: int) -> None:
+    target = image()
+
+    with pytest.raises(ValueError):
+        target.resize(width, height, ResizeAlgorithm.Nearest)
+
+    with pytest.raises(ValueError):
+        target.resize_region((0, 0, 2, 2), width, height, ResizeAlgorithm.Nearest)
+
+    seq = ImageSequence.from_frames([Frame(image())])
+    with pytest.raises(ValueError):
+        seq.resize(width, height, ResizeAlgorithm.Nearest)
+
+    assert target.dimensions == (4, 4)
+    assert target.mode == 'RGB'
+
+
+def test_empty_regions() -> None:
+    target = image()
+
+    with pytest.raises(ValueError):
+        target.crop(2, 2, 2, 4)
+
+    with pytest.raises(ValueError):
+        target.view(0, 0, 0, 0)
+
+    with pytest.raises(ValueError):
+        target.iter_tiles(0, 4)
+
+    assert target.dimensions == (4, 4)
@@ -0,0 +1,885 @@
+import copy
+import time
+from itertools import groupby
+
+import pytest
+
+from ril import Border, Ellipse, Image, Line, OverlayMode, Pixel, Polygon, Rectangle, Rgb, Rgba, Scene, Shadow, StrokeStyle
+
+
+def runs(row: list) -> list:
+    return [(on, len(list(group))) for on, group in groupby(p.value for p in row)]
+
+
+def draw_rectangle(style: StrokeStyle, width: int = 20, height: int = 5) -> Image:
+    image = Image.new(width, height, Pixel.from_bitpixel(False))
+    border = Border(color=Pixel.from_bitpixel(True), thickness=1, position='inset', style=style)
+    image.draw(Rectangle(position=(0, 0), size=(width, height), border=border))
+
+    return image
+
+
+def test_dashed_border() -> None:
+    row = draw_rectangle(StrokeStyle.dashed(4, 2)).pixels()[0]
+
+    assert runs(row) == [(True, 4), (False, 2)] * 3 + [(True, 2)]
+
+
+def test_dotted_border() -> None:
+    image = draw_rectangle(StrokeStyle.dotted(3))
+    row = image.pixels()[0]
+
+    # Dots are laid out from both corners, leaving out the one too close to the upper-right corner.
+    assert [p.value for p in row] == [x == 19 or (x % 3 == 0 and x + 3 <= 19) for x in range(20)]
+    # The interior is untouched
+    assert not any(p.value for p in image.pixels()[2][1:-1])
+
+@pytest.mark.parametrize('width, height', [(20, 5), (8, 8), (11, 7), (6, 13)])
+@pytest.mark.parametrize('spacing', [2, 3, 4])
+def test_dotted_border_corners(width: int, height: int, spacing: int) -> None:
+    image = draw_rectangle(StrokeStyle.dotted(spacing), width, height)
+    dots = {(x, y) for y, row in enumerate(image.pixels()) for x, p in enumerate(row) if p.value}
+
+    assert {(0, 0), (width - 1, 0), (0, height - 1), (width - 1, height - 1)} <= dots
+    for x, y in dots:
+        assert not any((x + dx, y + dy) in dots for dx in (-1, 0, 1) for dy in (-1, 0, 1) if dx or dy)
+
+def test_dashed_border_continues_around_corners() -> None:
+    image = draw_rectangle(StrokeStyle.dashed(4, 2), 10, 8)
+    pixels = image.pixels()
+
+    # The right side starts 9 pixels along the perimeter, and the bottom side 16 pixels along it.
+    assert [pixels[y][9].value for y in range(8)] == [(9 + y) % 6 < 4 for y in range(8)]
+    assert [pixels[7][x].value for x in range(10)] == [(16 + 9 - x) % 6 < 4 for x in range(10)]
+
+def test_dashed_border_huge_period() -> None:
+    row = draw_rectangle(StrokeStyle.dashed(2 ** 32 - 1, 2 ** 32 - 1)).pixels()[0]
+
+    assert runs(row) == [(True, 20)]
+
+
+def test_solid_border() -> None:
+    row = draw_rectangle(StrokeStyle.solid()).pixels()[0]
+
+    assert runs(row) == [(True, 20)]
+
+
+def test_stroke_style_getters() -> None:
+    style = StrokeStyle.dashed(4, 2)
+    assert (style.kind, style.length, style.gap, style.spacing) == ('dashed', 4, 2, None)
+
+    style = StrokeStyle.dotted(3)
+    assert (style.kind, style.length, style.gap, style.spacing) == ('dotted', None, None, 3)
+
+    border = Border(color=Pixel.from_rgb(0, 0, 0), thickness=1, position='inset', style=style)
+    assert Rectangle(position=(0, 0), size=(1, 1), border=border).border.style.kind == 'dotted'
+
+
+@pytest.mark.parametrize('make', [lambda: StrokeStyle.dashed(0, 2), lambda: StrokeStyle.dashed(2, 0), lambda: StrokeStyle.dotted(0)])
+def test_invalid_stroke_style(make) -> None:
+    with pytest.raises(ValueError):
+        make()
+
+
+def test_ellipse_ring() -> None:
+    background = Pixel.from_rgb(10, 20, 30)
+    fill = Pixel.from_rgb(255, 0, 0)
+    image = Image.new(21, 21, background)
+    image.draw(Ellipse(position=(10, 10), radii=(10, 10), inner_radii=(5, 5), fill=fill))
+
+    assert image.get_pixel(10, 10) == Image.new(1, 1, background).get_pixel(0, 0)
+    assert image.get_pixel(12, 10) == Image.new(1, 1, background).get_pixel(0, 0)
+    assert image.get_pixel(18, 10) == Image.new(1, 1, fill).get_pixel(0, 0)
+    assert image.get_pixel(10, 2) == Image.new(1, 1, fill).get_pixel(0, 0)
+    assert image.get_pixel(0, 0) == Image.new(1, 1, background).get_pixel(0, 0)
+
+
+def test_ellipse_ring_border() -> None:
+    background = Pixel.from_rgb(0, 0, 0)
+    border = Border(color=Pixel.from_rgb(0, 255, 0), thickness=1, position='inset')
+    image = Image.new(21, 21, background)
+    image.draw(Ellipse(position=(10, 10), radii=(10, 10), inner_radii=(5, 5), border=border))
+
+    green = Image.new(1, 1, Pixel.from_rgb(0, 255, 0)).get_pixel(0, 0)
+    row = image.pixels()[10]
+    assert green in row[:2]
+    assert green in row[3:7]
+    assert green not in row[7:14]
+
+
+def test_ellipse_invalid_inner_radii() -> None:
+    for inner_radii in [(10, 5), (5, 12), (0, 3)]:
+        with pytest.raises(ValueError):
+            Ellipse(position=(10, 10), radii=(10, 10), inner_radii=inner_radii)
+
+    ellipse = Ellipse(position=(10, 10), radii=(10, 10), inner_radii=(5, 5))
+    with pytest.raises(ValueError):
+        ellipse.radii = (4, 4)
+
+    ellipse.inner_radii = None
+    ellipse.radii = (4, 4)
+    assert ellipse.inner_radii is None
+
+
+MODE_PIXELS = {
+    'BITPIXEL': Pixel.from_bitpixel(True),
+    'L': Pixel.from_l(200),
+    'RGB': Pixel.from_rgb(0, 255, 0),
+    'RGBA': Pixel.from_rgba(0, 255, 0, 255),
+}
+
+
+@pytest.mark.parametrize('dest', MODE_PIXELS)
+@pytest.mark.parametrize('source', MODE_PIXELS)
+def test_cross_mode_paste_and_draw(dest: str, source: str) -> None:
+    image = Image.new(4, 4, Pixel.from_l(0), mode=dest)
+    image.paste(0, 0, Image.new(2, 2, MODE_PIXELS[source]))
+    image.draw(Rectangle(position=(2, 2), size=(2, 2), fill=MODE_PIXELS[source]))
+
+    assert image.mode == dest
+    assert image.get_pixel(0, 0) == image.get_pixel(3, 3)
+    assert image.get_pixel(0, 0) != image.get_pixel(3, 0)
+
+    if source != dest:
+        with pytest.raises(TypeError):
+            image.paste(0, 0, Image.new(2, 2, MODE_PIXELS[source]), strict_mode=True)
+
+        with pytest.raises(TypeError):
+            image.draw(Rectangle(position=(0, 0), size=(2, 2), fill=MODE_PIXELS[source]), strict_mode=True)
+
+
+def test_cross_mode_conversion() -> None:
+    image = Image.new(3, 1, Pixel.from_l(0), mode='L')
+    image.paste(0, 0, Image.new(1, 1, Pixel.from_rgb(0, 255, 0)))
+    image.draw(Rectangle(position=(1, 0), size=(1, 1), fill=Pixel.from_rgb(255, 255, 255)))
+
+    assert image.get_pixel(0, 0).value in (149, 150)
+    assert image.get_pixel(1, 0).value == 255
+
+    bits = Image.new(2, 1, Pixel.from_bitpixel(False))
+    bits.paste(0, 0, Image.new(1, 1, Pixel.from_l(200)))
+    bits.paste(1, 0, Image.new(1, 1, Pixel.from_l(50)))
+
+    assert [p.value for p in bits.pixels()[0]] == [True, False]
+
+
+WHITE = Pixel.from_rgba(255, 255, 255, 255)
+BLACK = Pixel.from_rgba(0, 0, 0, 255)
+
+
+def is_soft(pixel) -> bool:
+    return 0 < pixel.r < 255
+
+
+@pytest.mark.parametrize('shape', [
+    lambda **kwargs: Rectangle(position=(10, 10), size=(20, 20), fill=WHITE, **kwargs),
+    lambda **kwargs: Ellipse(position=(20, 20), radii=(10, 10), fill=WHITE, **kwargs),
+])
+def test_inner_shadow(shape) -> None:
+    image = Image.new(40, 40, WHITE)
+    image.draw(shape(inner_shadow=Shadow(color=BLACK, blur=6)))
+
+    assert is_soft(image.get_pixel(11, 20))
+    assert image.get_pixel(20, 20).r == 255
+    assert image.get_pixel(8, 20).r == 255
+
+
+@pytest.mark.parametrize('shape', [
+    lambda **kwargs: Rectangle(position=(10, 10), size=(20, 20), fill=WHITE, **kwargs),
+    lambda **kwargs: Ellipse(position=(20, 20), radii=(10, 10), fill=WHITE, **kwargs),
+])
+def test_outer_glow(shape) -> None:
+    image = Image.new(40, 40, WHITE)
+    image.draw(shape(outer_glow=Shadow(color=BLACK, blur=6)))
+
+    assert is_soft(image.get_pixel(8, 20))
+    assert image.get_pixel(0, 20).r == 255
+    assert image.get_pixel(15, 20).r == 255
+
+
+def test_shadow_offset() -> None:
+    image = Image.new(40, 40, WHITE)
+    glow = Shadow(color=BLACK, blur=0, offset=(3, 0))
+    image.draw(Rectangle(position=(10, 10), size=(20, 20), fill=WHITE, outer_glow=glow))
+
+    assert image.get_pixel(31, 20).r == 0
+    assert image.get_pixel(8, 20).r == 255
+    assert (glow.blur, glow.offset) == (0, (3, 0))
+
+def test_stroke_style_equality() -> None:
+    assert StrokeStyle.dashed(3, 2) == StrokeStyle.dashed(3, 2)
+    assert StrokeStyle.dashed(3, 2) != StrokeStyle.dashed(3, 1)
+    assert StrokeStyle.solid() != StrokeStyle.dotted(1)
+    assert len({StrokeStyle.solid(), StrokeStyle.solid(), StrokeStyle.dotted(2)}) == 2
+
+def test_draw_entity_equality() -> None:
+    def border(thickness: int = 1) -> Border:
+        return Border(color=Pixel.from_rgb(255, 0, 0), thickness=thickness, position='inset')
+
+    def shadow(blur: int = 4) -> Shadow:
+        return Shadow(color=Pixel.from_rgba(0, 0, 0, 128), blur=blur)
+
+    pairs = [
+        (border, lambda: border(2)),
+        (lambda: Border(color=Pixel.from_rgb(255, 0, 0), thickness=1, position='inset', style=StrokeStyle.dotted(2)), border),
+        (shadow, lambda: shadow(2)),
+        (
+            lambda: Rectangle(position=(1, 2), size=(3, 4), border=border(), fill=Pixel.from_rgb(0, 0, 255), inner_shadow=shadow()),
+            lambda: Rectangle(position=(1, 2), size=(3, 4), border=border(), fill=Pixel.from_rgb(0, 0, 254), inner_shadow=shadow()),
+        ),
+        (
+            lambda: Rectangle(position=(1, 2), size=(3, 4), overlay=OverlayMode.Merge),
+            lambda: Rectangle(position=(1, 2), size=(3, 4), overlay=OverlayMode.Replace),
+        ),
+        (
+            lambda: Ellipse(position=(5, 5), radii=(3, 3), border=border(), outer_glow=shadow()),
+            lambda: Ellipse(position=(5, 5), radii=(3, 3), border=border(), outer_glow=shadow(3)),
+        ),
+    ]
+
+    for make, make_other in pairs:
+        entity = make()
+
+        assert entity == make()
+        assert not entity != make()
+        assert entity != make_other()
+        assert entity != 'entity'
+
+        for duplicate in (entity.copy(), copy.copy(entity)):
+            assert duplicate == entity
+            assert duplicate is not entity
+
+def test_draw_entity_copy_is_independent() -> None:
+    rectangle = Rectangle(position=(0, 0), size=(4, 4), fill=Pixel.from_rgb(255, 0, 0))
+    duplicate = rectangle.copy()
+    duplicate.position = (1, 1)
+
+    assert rectangle.position == (0, 0)
+    assert duplicate != rectangle
+
+    with pytest.raises(TypeError):
+        hash(rectangle)
+
+
+def test_hline_vline_bounds() -> None:
+    image = Image.new(5, 5, Pixel.from_rgb(0, 0, 0))
+    image.draw_hline(2, -3, 10, WHITE)
+    image.draw_vline(4, 3, 1, WHITE)
+    image.draw_hline(7, 0, 4, WHITE)
+    image.draw_vline(-1, 0, 4, WHITE)
+
+    lit = {(x, y) for y, row in enumerate(image.pixels()) for x, pixel in enumerate(row) if pixel.r}
+    assert lit == {(x, 2) for x in range(5)} | {(4, y) for y in range(1, 4)}
+
+
+def test_hline_vline_thickness() -> None:
+    image = Image.new(9, 9, Pixel.from_l(0))
+    image.draw_hline(4, 0, 0, Pixel.from_rgb(255, 255, 255), thickness=3)
+    image.draw_vline(8, 0, 0, Pixel.from_l(255), thickness=2)
+
+    assert [image.get_pixel(0, y).value for y in range(9)] == [255 if y in (3, 4, 5) else 0 for y in range(9)]
+    assert image.get_pixel(8, 0).value == 255
+    assert image.get_pixel(7, 0).value == 0
+
+    with pytest.raises(ValueError):
+        image.draw_hline(0, 0, 8, Pixel.from_l(255), thickness=0)
+
+
+def test_hline_blends_alpha() -> None:
+    image = Image.new(4, 1, Pixel.from_rgb(0, 0, 0))
+    image.draw_hline(0, 0, 3, Pixel.from_rgba(255, 255, 255, 128))
+
+    assert all(100 < pixel.r < 155 for pixel in image.pixels()[0])
+
+
+def test_vline_faster_than_rectangle() -> None:
+    def bars(draw) -> float:
+        image = Image.new(1000, 200, Pixel.from_rgb(0, 0, 0))
+        start = time.perf_counter()
+        for x in range(0, 1000, 2):
+            draw(image, x)
+        return time.perf_counter() - start
+
+    rectangles = bars(lambda image, x: image.draw(Rectangle(position=(x, 0), size=(1, 200), fill=WHITE)))
+    lines = bars(lambda image, x: image.draw_vline(x, 0, 199, WHITE))
+
+    assert lines < rectangles
+
+
+def test_draw_list() -> None:
+    cells = [
+        Rectangle(position=(x, y), size=(1, 1), fill=Pixel.from_rgb(x * 30, y * 30, 0))
+        for y in range(6) for x in range(8) if (x + y) % 3
+    ]
+    cells.append(Ellipse.circle(4, 3, 2))
+
+    expected = Image.new(8, 6, Pixel.from_rgb(0, 0, 0))
+    for cell in cells:
+        expected.draw(cell)
+
+    for entities in (cells, tuple(cells)):
+        image = Image.new(8, 6, Pixel.from_rgb(0, 0, 0))
+        image.draw(entities)
+        assert image.pixels() == expected.pixels()
+
+    image = Image.new(8, 6, Pixel.from_rgb(0, 0, 0))
+    image.draw([])
+    image.push_transform(translate=(1, 0))
+    image.draw([Rectangle(position=(0, 0), size=(1, 1), fill=WHITE)])
+    assert image.get_pixel(1, 0) == Rgb(255, 255, 255)
+    assert image.get_pixel(0, 0) == Rgb(0, 0, 0)
+
+    with pytest.raises(RuntimeError):
+        image.draw([Rectangle(position=(2, 2), size=(1, 1), fill=WHITE), 'rectangle'])
+    assert image.get_pixel(2, 2) == Rgb(0, 0, 0)
+
+
+def test_draw_list_is_one_draw() -> None:
+    # The whole list is checked before anything is drawn, so a mismatch in its last entity leaves the image untouched.
+    cells = [Rectangle(position=(x, y), size=(1, 1), fill=WHITE) for y in range(4) for x in range(0, 8, 2)]
+    cells.append(Rectangle(position=(1, 1), size=(1, 1), fill=Pixel.from_l(255)))
+    image = Image.new(8, 4, Pixel.from_rgba(0, 0, 0, 255))
+
+    with pytest.raises(TypeError):
+        image.draw(cells, strict_mode=True)
+    assert image.pixels() == Image.new(8, 4, Pixel.from_rgba(0, 0, 0, 255)).pixels()
+
+    expected = Image.new(8, 4, Pixel.from_rgba(0, 0, 0, 255))
+    for cell in cells[:-1]:
+        expected.draw(cell)
+
+    image.draw(cells[:-1], strict_mode=True)
+    assert image.pixels() == expected.pixels()
+
+
+def test_scene_reflects_changes() -> None:
+    rectangle = Rectangle(position=(0, 0), size=(4, 4), fill=Pixel.from_rgb(255, 0, 0))
+    scene = Scene()
+    scene.add(rectangle)
+
+    before = scene.render(8, 8, Pixel.from_rgb(0, 0, 0))
+    rectangle.fill = Pixel.from_rgb(0, 0, 255)
+    rectangle.position = (4, 4)
+    after = scene.render(8, 8, Pixel.from_rgb(0, 0, 0))
+
+    assert before.get_pixel(1, 1) == Rgb(255, 0, 0)
+    assert before.get_pixel(5, 5) == Rgb(0, 0, 0)
+    assert after.get_pixel(1, 1) == Rgb(0, 0, 0)
+    assert after.get_pixel(5, 5) == Rgb(0, 0, 255)
+
+
+def test_scene_z_order() -> None:
+    scene = Scene()
+    scene.add(Rectangle(position=(0, 0), size=(4, 4), fill=Pixel.from_rgb(255, 0, 0)), z=1)
+    scene.add(Rectangle(position=(2, 2), size=(4, 4), fill=Pixel.from_rgb(0, 255, 0)))
+    scene.add(Rectangle(position=(3, 3), size=(4, 4), fill=Pixel.from_rgb(0, 0, 255)))
+
+    image = scene.render(8, 8, Pixel.from_rgb(0, 0, 0))
+    assert image.get_pixel(3, 3) == Rgb(255, 0, 0)
+    assert image.get_pixel(4, 4) == Rgb(0, 0, 255)
+    assert image.get_pixel(2, 5) == Rgb(0, 255, 0)
+
+
+def test_scene_draw() -> None:
+    circle = Ellipse.circle(4, 4, 3)
+    circle.fill = Pixel.from_rgb(200, 0, 0)
+    inner = Scene()
+    inner.add(circle)
+    inner.add(Rectangle(position=(0, 0), size=(2, 2), fill=Pixel.from_rgba(255, 255, 255, 255)))
+
+    scene = Scene()
+    scene.add(Rectangle(position=(0, 0), size=(8, 8), fill=Pixel.from_rgb(40, 40, 40)))
+    scene.add(inner, z=1)
+
+    image = Image.new(8, 8, Pixel.from_rgb(0, 0, 0))
+    image.draw(scene)
+
+    assert image.mode == 'RGB'
+    assert image.pixels() == scene.render(8, 8, Pixel.from_rgb(0, 0, 0)).pixels()
+    assert image.get_pixel(0, 0) == Rgb(255, 255, 255)
+    assert image.get_pixel(4, 4) == Rgb(200, 0, 0)
+    assert image.get_pixel(7, 7) == Rgb(40, 40, 40)
+
+
+def test_scene_entities() -> None:
+    first = Rectangle(position=(0, 0), size=(2, 2), fill=WHITE)
+    second = Ellipse.circle(4, 4, 2)
+    scene = Scene()
+    scene.add(first, z=5)
+    scene.add(second)
+
+    assert len(scene) == 2
+    assert [entity is expected for entity, expected in zip(scene, [first, second])] == [True, True]
+
+    assert scene.remove(-1) is second
+    assert list(scene) == [first]
+
+    with pytest.raises(IndexError):
+        scene.remove(1)
+
+    with pytest.raises(RuntimeError):
+        scene.add(5)
+
+    assert len(scene) == 1
+
+
+def test_scene_cycles() -> None:
+    outer = Scene()
+    inner = Scene()
+    outer.add(inner)
+
+    with pytest.raises(ValueError):
+        outer.add(outer)
+
+    with pytest.raises(ValueError):
+        inner.add(outer)
+
+    assert len(inner) == 0
+
+
+def covered(image: Image, color: Pixel) -> tuple:
+    points = [(x, y) for y, row in enumerate(image.pixels()) for x, pixel in enumerate(row) if pixel == color]
+    xs, ys = [x for x, _ in points], [y for _, y in points]
+
+    return min(xs), min(ys), max(xs) + 1, max(ys) + 1
+
+
+def test_draw_transform_translate() -> None:
+    red = Pixel.from_rgb(255, 0, 0)
+    rectangle = Rectangle(position=(1, 2), size=(3, 4), fill=red)
+
+    image = Image.new(16, 16, Pixel.from_rgb(0, 0, 0))
+    image.push_transform(translate=(5, 6))
+    image.draw(rectangle)
+    image.pop_transform()
+
+    assert covered(image, Rgb(255, 0, 0)) == (6, 8, 9, 12)
+    assert rectangle.position == (1, 2)
+
+    image.draw(rectangle)
+    assert image.get_pixel(1, 2) == Rgb(255, 0, 0)
+
+
+def test_draw_transform_scale() -> None:
+    image = Image.new(32, 32, Pixel.from_rgb(0, 0, 0))
+    image.push_transform(translate=(4, 2), scale=2.0)
+    image.draw(Rectangle(position=(1, 1), size=(3, 5), fill=Pixel.from_rgb(0, 255, 0)))
+    image.draw_hline(12, 0, 4, Pixel.from_rgb(0, 0, 255))
+
+    assert covered(image, Rgb(0, 255, 0)) == (6, 4, 12, 14)
+    assert covered(image, Rgb(0, 0, 255)) == (4, 26, 14, 28)
+
+
+def test_draw_transform_nested() -> None:
+    blue = Pixel.from_rgb(0, 0, 255)
+    image = Image.new(40, 40, Pixel.from_rgb(0, 0, 0))
+
+    image.push_transform(translate=(10, 10), scale=2.0)
+    image.push_transform(translate=(2, 3))
+    image.draw(Rectangle(position=(0, 0), size=(2, 2), fill=blue))
+    assert covered(image, Rgb(0, 0, 255)) == (14, 16, 18, 20)
+
+    image.pop_transform()
+    image.draw(Rectangle(position=(0, 0), size=(1, 1), fill=Pixel.from_rgb(255, 255, 0)))
+    assert covered(image, Rgb(255, 255, 0)) == (10, 10, 12, 12)
+
+    image.pop_transform()
+    image.draw(Rectangle(position=(0, 0), size=(1, 1), fill=Pixel.from_rgb(0, 255, 255)))
+    assert covered(image, Rgb(0, 255, 255)) == (0, 0, 1, 1)
+
+    with pytest.raises(IndexError):
+        image.pop_transform()
+
+
+def test_draw_transform_scene_and_paste() -> None:
+    scene = Scene()
+    scene.add(Rectangle(position=(0, 0), size=(2, 2), fill=Pixel.from_rgb(255, 0, 0)))
+    scene.add(Ellipse.circle(6, 6, 1))
+    stamp = Image.new(3, 3, Pixel.from_rgb(0, 255, 0))
+
+    image = Image.new(24, 24, Pixel.from_rgb(0, 0, 0))
+    image.push_transform(translate=(8, 4))
+    image.draw(scene)
+    image.paste(1, 1, stamp)
+
+    assert covered(image, Rgb(255, 0, 0)) == (8, 4, 10, 6)
+    assert covered(image, Rgb(0, 255, 0)) == (9, 5, 12, 8)
+
+    assert image.copy().pixels() == image.pixels()
+    copied = image.copy()
+    copied.paste(0, 0, stamp)
+    assert copied.get_pixel(0, 0) == Rgb(0, 255, 0)
+
+
+def test_draw_transform_errors() -> None:
+    image = Image.new(8, 8, Pixel.from_rgb(0, 0, 0))
+
+    for scale in (0.0, -1.0, float('nan')):
+        with pytest.raises(ValueError):
+            image.push_transform(scale=scale)
+
+    image.push_transform(translate=(-4, 0))
+    with pytest.raises(ValueError, match='out of bounds'):
+        image.draw(Rectangle(position=(2, 2), size=(2, 2), fill=Pixel.from_rgb(255, 0, 0)))
+
+    with pytest.raises(ValueError):
+        image.paste(0, 0, Image.new(1, 1, Pixel.from_rgb(0, 0, 0)))
+
+    image.draw(Rectangle(position=(4, 0), size=(2, 2), fill=Pixel.from_rgb(255, 0, 0)))
+    assert image.get_pixel(0, 0) == Rgb(255, 0, 0)
+
+
+def lit(image: Image) -> set:
+    return {(x, y) for y, row in enumerate(image.pixels()) for x, pixel in enumerate(row) if pixel == Rgb(255, 255, 255)}
+
+
+def draw_line(start: tuple, end: tuple, thickness: int = 1, size: int = 24, **kwargs) -> Image:
+    image = Image.new(size, size, Pixel.from_rgb(0, 0, 0))
+    image.draw(Line(start=start, end=end, color=WHITE, thickness=thickness, **kwargs))
+
+    return image
+
+
+@pytest.mark.parametrize('thickness', [1, 3])
+def test_line_horizontal(thickness: int) -> None:
+    expected = Image.new(24, 24, Pixel.from_rgb(0, 0, 0))
+    expected.draw_hline(10, 2, 20, WHITE, thickness)
+
+    assert lit(draw_line((2, 10), (20, 10), thickness)) == lit(expected)
+
+
+def test_line_diagonal_is_continuous() -> None:
+    points = lit(draw_line((1, 2), (22, 11)))
+
+    assert (1, 2) in points and (22, 11) in points
+    assert len(points) == 22
+    assert sorted(x for x, _ in points) == list(range(1, 23))
+
+    steep = lit(draw_line((3, 1), (9, 22)))
+    assert sorted(y for _, y in steep) == list(range(1, 23))
+
+
+@pytest.mark.parametrize('thickness', [1, 2, 4, 5])
+def test_line_same_in_either_direction(thickness: int) -> None:
+    for start, end in [((2, 3), (19, 14)), ((20, 2), (4, 21)), ((5, 5), (5, 18))]:
+        assert lit(draw_line(start, end, thickness)) == lit(draw_line(end, start, thickness))
+
+
+def test_line_thick_diagonal_has_no_gaps() -> None:
+    points = lit(draw_line((3, 3), (20, 20), thickness=4))
+
+    for i in range(4, 20):
+        across = [(x, y) for x, y in points if x + y == 2 * i]
+        # Every anti-diagonal crossing the middle of the line is covered without holes.
+        assert len(across) >= 3
+        xs = sorted(x for x, _ in across)
+        assert xs == list(range(xs[0], xs[-1] + 1))
+
+    assert (1, 1) not in points and (22, 22) not in points
+
+
+def test_line_point() -> None:
+    assert lit(draw_line((5, 5), (5, 5))) == {(5, 5)}
+    assert lit(draw_line((5, 5), (5, 5), thickness=3)) == {(x, y) for x in range(4, 7) for y in range(4, 7)}
+
+
+def test_line_clipped_and_transformed() -> None:
+    assert lit(draw_line((2, 2), (40, 2))) == {(x, 2) for x in range(2, 24)}
+
+    image = Image.new(24, 24, Pixel.from_rgb(0, 0, 0))
+    image.push_transform(translate=(3, 4), scale=2.0)
+    image.draw([Line(start=(0, 0), end=(5, 0), color=WHITE)])
+
+    # The thickness is scaled along with the end points.
+    assert lit(image) == {(x, y) for x in range(3, 14) for y in (4, 5)}
+
+
+@pytest.mark.parametrize('thickness', [1, 3])
+def test_line_dashed(thickness: int) -> None:
+    points = lit(draw_line((2, 10), (21, 10), thickness, style=StrokeStyle.dashed(4, 2)))
+    dashes = {x for x in range(2, 22) if (x - 2) % 6 < 4}
+
+    assert points == {(x, y) for x in dashes for y in range(10 - thickness // 2, 11 + thickness // 2)}
+    assert points == lit(draw_line((21, 10), (2, 10), thickness, style=StrokeStyle.dashed(4, 2)))
+
+
+def test_line_dotted_diagonal() -> None:
+    points = lit(draw_line((1, 1), (20, 20), style=StrokeStyle.dotted(3)))
+
+    # Diagonal steps count as a single pixel, the same as straight ones.
+    assert points == {(i, i) for i in range(1, 21) if (i - 1) % 3 == 0}
+
+
+def test_line_attributes() -> None:
+    line = Line(start=(0, 0), end=(3, 4), color=Pixel.from_rgb(255, 0, 0), thickness=2)
+
+    assert line.color == Rgb(255, 0, 0)
+    assert line == line.copy() == copy.copy(line)
+
+    line.end = (5, 5)
+    assert line.end == (5, 5)
+
+    with pytest.raises(ValueError):
+        Line(start=(0, 0), end=(1, 1), color=WHITE, thickness=0)
+
+    with pytest.raises(ValueError):
+        line.thickness = 0
+    assert line.thickness == 2
+
+    assert line.style == StrokeStyle.solid()
+    line.style = StrokeStyle.dotted(2)
+    assert line.style == StrokeStyle.dotted(2)
+    assert line != Line(start=(0, 0), end=(5, 5), color=Pixel.from_rgb(255, 0, 0), thickness=2)
+
+
+def draw_circle(x: float) -> set:
+    image = Image.new(24, 24, Pixel.from_rgb(0, 0, 0))
+    image.draw(Ellipse(position=(x, 12), radii=(5.0, 5.0), fill=WHITE))
+
+    return lit(image)
+
+
+def test_subpixel_positions_round_half_even() -> None:
+    ellipse = Ellipse(position=(10.25, 12), radii=(5.5, 4))
+
+    assert ellipse.position == (10.25, 12)
+    assert ellipse.radii == (5.5, 4)
+    assert Ellipse(position=(10.4, 12), radii=(5, 5)).position == pytest.approx((10.4, 12))
+
+    assert draw_circle(10.4) == draw_circle(10.0)
+    assert draw_circle(10.5) == draw_circle(10.0)
+    assert draw_circle(11.5) == draw_circle(12.0)
+    assert draw_circle(10.6) == draw_circle(11.0)
+    assert draw_circle(10.0) != draw_circle(11.0)
+
+
+def test_subpixel_rectangle_and_line() -> None:
+    image = Image.new(16, 16, Pixel.from_rgb(0, 0, 0))
+    image.draw(Rectangle(position=(2.5, 1.5), size=(3.5, 0.4), fill=WHITE))
+
+    assert covered(image, Rgb(255, 255, 255)) == (2, 2, 6, 3)
+
+    line = Line(start=(0.5, 3.5), end=(5.4, 3.5), color=WHITE)
+    assert line.start == (0.5, 3.5)
+    assert lit(draw_line(line.start, line.end)) == {(x, 4) for x in range(6)}
+
+    # Points that round to zero are still within bounds.
+    assert Rectangle(position=(-0.4, 0), size=(1, 1)).position == pytest.approx((-0.4, 0))
+    with pytest.raises(ValueError, match='out of bounds'):
+        Rectangle(position=(-0.6, 0), size=(1, 1))
+    with pytest.raises(ValueError):
+        line.end = (float('nan'), 0)
+
+
+def test_subpixel_positions_transformed() -> None:
+    image = Image.new(16, 16, Pixel.from_rgb(0, 0, 0))
+    image.push_transform(scale=2.0)
+    image.draw(Rectangle(position=(1.3, 0), size=(1, 1), fill=WHITE))
+
+    # The position is scaled before it is rounded, so it isn't snapped to (1, 0) first.
+    assert covered(image, Rgb(255, 255, 255)) == (3, 0, 5, 2)
+
+
+def draw_polygon(vertices: list, size: int = 12, **kwargs) -> Image:
+    image = Image.new(size, size, Pixel.from_rgb(0, 0, 0))
+    image.draw(Polygon(vertices=vertices, **kwargs))
+
+    return image
+
+
+def test_polygon_fill() -> None:
+    square = draw_polygon([(2, 3), (6, 3), (6, 8), (2, 8)], fill=WHITE)
+    expected = Image.new(12, 12, Pixel.from_rgb(0, 0, 0))
+    expected.draw(Rectangle(position=(2, 3), size=(4, 5), fill=WHITE))
+
+    assert square.pixels() == expected.pixels()
+
+    triangle = lit(draw_polygon([(0, 0), (8, 0), (0, 8)], fill=WHITE))
+    assert triangle == {(x, y) for y in range(7) for x in range(7 - y)}
+
+
+def test_polygon_concave() -> None:
+    shape = [(0, 0), (6, 0), (6, 2), (2, 2), (2, 6), (0, 6)]
+    expected = {(x, y) for y in range(6) for x in range(6) if x < 2 or y < 2}
+
+    assert lit(draw_polygon(shape, fill=WHITE)) == expected
+    # The direction the vertices are given in doesn't matter.
+    assert lit(draw_polygon(shape[::-1], fill=WHITE)) == expected
+
+
+@pytest.mark.parametrize('position, rings', [('inset', [2]), ('outset', [1]), ('center', [1, 2])])
+def test_polygon_border(position: str, rings: list) -> None:
+    border = Border(color=WHITE, thickness=len(rings), position=position)
+    points = lit(draw_polygon([(2, 2), (8, 2), (8, 8), (2, 8)], border=border))
+
+    # The outline of the square from (n, n) to (9 - n, 9 - n), where ring 2 lies just within the polygon.
+    def ring(n: int) -> set:
+        outer = {(x, y) for x in range(n, 10 - n) for y in range(n, 10 - n)}
+        return outer - {(x, y) for x in range(n + 1, 9 - n) for y in range(n + 1, 9 - n)}
+
+    assert points == set().union(*(ring(n) for n in rings))
+
+
+def test_polygon_dashed_border() -> None:
+    vertices = [(0, 0), (20, 0), (20, 5), (0, 5)]
+    dashed = Border(color=WHITE, thickness=1, position='inset', style=StrokeStyle.dashed(4, 2))
+    points = lit(draw_polygon(vertices, size=24, border=dashed))
+
+    # The same dashes as a rectangle along the top edge, where the perimeter starts.
+    assert {x for x, y in points if y == 0} == {x for x in range(20) if x % 6 < 4}
+    assert points < lit(draw_polygon(vertices, size=24, border=Border(color=WHITE, thickness=1, position='inset')))
+
+
+@pytest.mark.parametrize('vertices, spacing', [
+    ([(0, 0), (10, 0), (10, 7), (0, 7)], 2),
+    ([(0, 0), (10, 0), (10, 7), (0, 7)], 3),
+    ([(2, 2), (20, 2), (11, 18)], 3),
+    ([(2, 2), (20, 2), (11, 18)], 4),
+])
+def test_polygon_dotted_border_corners(vertices: list, spacing: int) -> None:
+    dotted = Border(color=WHITE, thickness=1, position='inset', style=StrokeStyle.dotted(spacing))
+    dots = lit(draw_polygon(vertices, size=24, border=dotted))
+
+    for x, y in dots:
+        assert not any((x + dx, y + dy) in dots for dx in (-1, 0, 1) for dy in (-1, 0, 1) if dx or dy)
+
+    if len(vertices) == 4:
+        assert {(0, 0), (9, 0), (9, 6), (0, 6)} <= dots
+
+def test_polygon_transformed_and_listed() -> None:
+    image = Image.new(12, 12, Pixel.from_rgb(0, 0, 0))
+    image.push_transform(translate=(2, 1), scale=2.0)
+    image.draw([Polygon(vertices=[(0, 0), (2, 0), (2, 1), (0, 1)], fill=WHITE)])
+
+    assert covered(image, Rgb(255, 255, 255)) == (2, 1, 6, 3)
+
+
+@pytest.mark.parametrize('vertices', [
+    [], [(0, 0), (4, 4)], [(0, 0), (2, 2), (5, 5)], [(3, 3)] * 4, [(0, 0), (4, 0), (-1, 4)],
+    [(0, 0), (0, 0), (2 ** 31, 2 ** 31), (2 ** 32 - 256, 2 ** 32 - 256)],
+])
+def test_polygon_degenerate(vertices: list) -> None:
+    with pytest.raises(ValueError):
+        Polygon(vertices=vertices, fill=WHITE)
+
+    polygon = Polygon(vertices=[(0, 0), (4, 0), (0, 4)])
+    with pytest.raises(ValueError):
+        polygon.vertices = vertices
+    assert polygon.vertices == [(0, 0), (4, 0), (0, 4)]
+
+
+def test_polygon_far_vertices() -> None:
+    # The collinearity check multiplies coordinates, which must not overflow for vertices far out.
+    image = draw_polygon([(0, 0), (4e9, 0), (0, 4e9)], fill=WHITE)
+    assert lit(image) == {(x, y) for y in range(12) for x in range(12)}
+
+
+def test_polygon_attributes() -> None:
+    polygon = Polygon(vertices=[(0, 0), (4, 0), (0, 4)], fill=Pixel.from_rgb(255, 0, 0))
+
+    assert polygon.fill == Rgb(255, 0, 0)
+    assert polygon.border is None
+    assert polygon == polygon.copy() == copy.copy(polygon)
+
+    duplicate = polygon.copy()
+    duplicate.vertices = [(0, 0), (5, 0), (0, 5)]
+    assert duplicate != polygon
+
+    polygon.border = Border(color=WHITE, thickness=1, position='inset', style=StrokeStyle.dashed(2, 1))
+    assert polygon.border.style == StrokeStyle.dashed(2, 1)
+
+
+def draw_rounded(radius, size: int = 10, **kwargs) -> Image:
+    image = Image.new(size, size, Pixel.from_rgb(0, 0, 0))
+    image.draw(Rectangle(position=(0, 0), size=(size, size), radius=radius, **kwargs))
+
+    return image
+
+
+def mirrored(points: set, size: int = 10) -> set:
+    return {(size - 1 - x, y) for x, y in points}
+
+
+def test_rounded_rectangle_fill() -> None:
+    points = lit(draw_rounded(5, fill=WHITE))
+
+    assert points == mirrored(points) == {(y, x) for x, y in points}
+    assert (0, 0) not in points and (2, 0) not in points
+    assert {(3, 0), (1, 1), (0, 4), (5, 5)} <= points
+
+
+def test_rounded_rectangle_clamped() -> None:
+    assert lit(draw_rounded(100, fill=WHITE)) == lit(draw_rounded(5, fill=WHITE))
+    assert lit(draw_rounded((5, 5, 5, 5), fill=WHITE)) == lit(draw_rounded(5, fill=WHITE))
+
+
+def test_rounded_rectangle_per_corner() -> None:
+    points = lit(draw_rounded((4, 0, 0, 0), fill=WHITE))
+
+    assert (0, 0) not in points
+    assert {(9, 0), (9, 9), (0, 9)} <= points
+
+
+@pytest.mark.parametrize('position, lit_points, unlit_points', [
+    ('inset', [(0, 5), (1, 1)], [(0, 0), (1, 5), (5, 5)]),
+    ('outset', [(1, 5), (3, 1), (2, 2)], [(1, 1), (2, 5), (5, 5)]),
+])
+def test_rounded_rectangle_border(position: str, lit_points: list, unlit_points: list) -> None:
+    image = Image.new(10, 10, Pixel.from_rgb(0, 0, 0))
+    border = Border(color=WHITE, thickness=1, position=position)
+    offset = 2 if position == 'outset' else 0
+    image.draw(Rectangle(position=(offset, offset), size=(10 - 2 * offset, 10 - 2 * offset), radius=3, border=border))
+    points = lit(image)
+
+    assert points == mirrored(points)
+    assert set(lit_points) <= points
+    assert not set(unlit_points) & points
+
+
+def test_rounded_rectangle_overlay() -> None:
+    image = Image.new(10, 10, Pixel.from_rgba(255, 0, 0, 255))
+    fill = Pixel.from_rgba(0, 0, 255, 100)
+    image.draw(Rectangle(position=(0, 0), size=(10, 10), radius=5, fill=fill, overlay=OverlayMode.Replace))
+
+    assert image.get_pixel(5, 5) == Rgba(0, 0, 255, 100)
+    assert image.get_pixel(0, 0) == Rgba(255, 0, 0, 255)
+
+
+def test_rounded_rectangle_shadow() -> None:
+    image = Image.new(40, 40, WHITE)
+    glow = Shadow(color=BLACK, blur=0, offset=(3, 0))
+    image.draw(Rectangle(position=(10, 10), size=(20, 20), radius=10, fill=WHITE, outer_glow=glow))
+
+    assert image.get_pixel(31, 20).r == 0
+    assert image.get_pixel(31, 11).r == 255
+
+
+def test_rounded_rectangle_transformed() -> None:
+    image = Image.new(10, 10, Pixel.from_rgb(0, 0, 0))
+    image.push_transform(scale=2.0)
+    image.draw(Rectangle(position=(0, 0), size=(5, 5), radius=2, fill=WHITE))
+
+    assert lit(image) == lit(draw_rounded(4, fill=WHITE))
+
+
+def test_rounded_rectangle_attributes() -> None:
+    rectangle = Rectangle(position=(0, 0), size=(10, 10), fill=WHITE)
+    assert rectangle.radius == (0, 0, 0, 0)
+
+    rectangle.radius = 3
+    assert rectangle.radius == (3, 3, 3, 3)
+    assert rectangle != Rectangle(position=(0, 0), size=(10, 10), fill=WHITE)
+    assert rectangle == rectangle.copy()
+
+    rectangle.radius = (1, 2, 3, 4)
+    assert rectangle.radius == (1, 2, 3, 4)
+
+    with pytest.raises(ValueError):
+        rectangle.radius = -1
+
+    dashed = Border(color=WHITE, thickness=1, position='inset', style=StrokeStyle.dashed(2, 1))
+    with pytest.raises(ValueError, match='solid'):
+        rectangle.border = dashed
+    with pytest.raises(ValueError, match='solid'):
+        Rectangle(position=(0, 0), size=(10, 10), border=dashed, radius=2)
+    assert rectangle.border is None
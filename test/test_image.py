@@ -1,4 +1,20 @@
-from ril import Image, ImageSequence, Pixel, Rgba
+import asyncio
+import copy
+import math
+import os
+import re
+import struct
+import subprocess
+import sys
+import time
+import warnings
+from typing import List
+
+import pytest
+
+import ril
+from helpers import gradient, noise
+from ril import WEBP, BufferTooSmallError, Colors, DisposalMethod, Frame, GifOptions, Image, ImageFormat, ImageSequence, JpegOptions, Mode, Pixel, PngOptions, Rectangle, ResizeAlgorithm, Rgb, Rgba, UnknownFormatError, UnsupportedFormatError, WebPOptions
 
 PIXELS = [
     Rgba(255, 0, 0, 255),
@@ -27,7 +43,2606 @@ def test_image_pixels() -> None:
 
     image.pixels()
 
+def test_image_pixels_raw() -> None:
+    image = Image.from_buffer(3, 2, 'RGBA', bytes(range(24)))
+
+    assert image.pixels(raw=True) == [bytes(range(12)), bytes(range(12, 24))]
+    assert image.pixels(raw=True) == [
+        bytes(channel for pixel in row for channel in (pixel.r, pixel.g, pixel.b, pixel.a)) for row in image.pixels()
+    ]
+    assert b''.join(image.pixels(raw=True)) == image.to_bytes()
+
+    image = Image.new(2, 1, Pixel.from_bitpixel(True))
+    assert image.pixels(raw=True) == [bytes([255, 255])]
+
+def test_image_pixels_raw_is_faster() -> None:
+    image = Image.new(1920, 1080, Pixel.from_rgba(10, 20, 30, 40))
+
+    start = time.perf_counter()
+    rows = image.pixels(raw=True)
+    raw = time.perf_counter() - start
+
+    start = time.perf_counter()
+    image.pixels()
+    objects = time.perf_counter() - start
+
+    assert len(rows) == 1080 and all(len(row) == 1920 * 4 for row in rows)
+    assert raw < objects
+
 def test_gif_decode(fetch_file) -> None:
     for i, frame in enumerate(ImageSequence.from_bytes(fetch_file('sample_rgba.gif'))):
         assert frame.dimensions == (256, 256)
         assert frame.image.get_pixel(0, 0) == PIXELS[i]
+
+def test_mode() -> None:
+    fills = {
+        Mode.BitPixel: Pixel.from_bitpixel(True),
+        Mode.L: Pixel.from_l(255),
+        Mode.Rgb: Pixel.from_rgb(255, 255, 255),
+        Mode.Rgba: Pixel.from_rgba(255, 255, 255, 255),
+    }
+
+    for mode, fill in fills.items():
+        image = Image.new(1, 1, fill)
+
+        assert image.mode_enum == mode
+        assert image.mode == image.mode.upper()
+
+def test_new_with_mode() -> None:
+    fill = Pixel.from_rgb(255, 255, 255)
+
+    assert Image.new(1, 1, fill, mode=Mode.L).mode_enum == Mode.L
+    assert Image.new(1, 1, fill, mode='l').mode_enum == Mode.L
+    assert Image.new(1, 1, fill, mode='RGBA').mode == 'RGBA'
+
+def test_from_pixels_mixed_modes() -> None:
+    image = Image.from_pixels(3, [Pixel.from_l(10), Pixel.from_rgb(1, 2, 3), Pixel.from_bitpixel(True)])
+
+    assert image.mode_enum == Mode.Rgb
+    assert image.get_pixel(0, 0) == Rgb(10, 10, 10)
+    assert image.get_pixel(1, 0) == Rgb(1, 2, 3)
+    assert image.get_pixel(2, 0) == Rgb(255, 255, 255)
+
+def test_convert_mode_enum_and_string() -> None:
+    names = {Mode.BitPixel: 'BITPIXEL', Mode.L: 'L', Mode.Rgb: 'RGB', Mode.Rgba: 'RGBA'}
+
+    for mode, name in names.items():
+        by_enum = Image.new(2, 2, Pixel.from_rgba(200, 100, 50, 255))
+        by_enum.convert(mode)
+
+        assert by_enum.mode_enum == mode
+        assert by_enum.mode == name
+
+        for target in (name, name.lower(), by_enum.mode):
+            by_name = Image.new(2, 2, Pixel.from_rgba(200, 100, 50, 255))
+            by_name.convert(target)
+
+            assert by_name.mode_enum == mode
+            assert by_name.pixels() == by_enum.pixels()
+
+def test_from_bytes_unknown_format() -> None:
+    data = bytes(range(1, 33))
+
+    for cls in (Image, ImageSequence):
+        with pytest.raises(UnknownFormatError) as exc:
+            cls.from_bytes(data)
+
+        assert '01 02 03 04' in str(exc.value)
+        assert '11' not in str(exc.value).split(':')[-1]
+
+def test_from_bytes_format_enum() -> None:
+    data = Image.new(2, 2, Pixel.from_rgb(1, 2, 3)).encode('png')
+
+    assert Image.from_bytes(data, ImageFormat.Png).dimensions == (2, 2)
+    assert Image.from_bytes(data, 'png').dimensions == (2, 2)
+
+def test_or_composites_over() -> None:
+    base = Image.new(2, 2, Pixel.from_rgba(0, 0, 255, 255))
+    top = Image.new(2, 2, Pixel.from_rgba(255, 0, 0, 128))
+
+    result = base | top
+    base.paste(0, 0, top)
+
+    assert result.pixels() == base.pixels()
+
+def test_and_masks_alpha() -> None:
+    image = Image.new(2, 2, Pixel.from_rgba(255, 0, 0, 255))
+    mask = Image.new(2, 2, Pixel.from_l(100))
+
+    result = image & mask
+    image.mask_alpha(mask)
+
+    assert result.pixels() == image.pixels()
+
+def test_operators_dimension_mismatch() -> None:
+    image = Image.new(2, 2, Pixel.from_rgba(255, 0, 0, 255))
+    other = Image.new(3, 2, Pixel.from_l(100))
+
+    with pytest.raises(ValueError):
+        image | other
+
+    with pytest.raises(ValueError):
+        image & other
+
+def checker_mask(width: int, height: int) -> Image:
+    return Image.from_pixels(width, [Pixel.from_bitpixel((x + y) % 2 == 0) for y in range(height) for x in range(width)])
+
+def test_paste_mask_dimensions() -> None:
+    white = Image.new(2, 2, Pixel.from_rgb(255, 255, 255))
+
+    exact = Image.new(4, 4, Pixel.from_rgb(0, 0, 0))
+    exact.paste(1, 1, white, checker_mask(2, 2))
+    assert [exact.get_pixel(x, y) for x, y in ((1, 1), (2, 1), (1, 2), (2, 2))] == [Rgb(255, 255, 255), Rgb(0, 0, 0), Rgb(0, 0, 0), Rgb(255, 255, 255)]
+
+    for mask in (checker_mask(1, 2), checker_mask(3, 3), checker_mask(3, 1)):
+        image = Image.new(4, 4, Pixel.from_rgb(0, 0, 0))
+
+        with pytest.raises(ValueError, match=rf'\(2, 2\).*\({mask.width}, {mask.height}\)'):
+            image.paste(1, 1, white, mask)
+
+        assert image.pixels() == Image.new(4, 4, Pixel.from_rgb(0, 0, 0)).pixels()
+
+    cropped = Image.new(4, 4, Pixel.from_rgb(0, 0, 0))
+    cropped.paste(1, 1, white, checker_mask(3, 3), crop_mask=True)
+    assert cropped.pixels() == exact.pixels()
+
+    with pytest.raises(ValueError):
+        cropped.paste(1, 1, white, checker_mask(1, 2), crop_mask=True)
+
+def paste_expected(x: int, y: int, mask: bool) -> list:
+    return [
+        [
+            Rgb(255, 255, 255) if x <= px < x + 3 and y <= py < y + 3 and (not mask or (px - x + py - y) % 2 == 0) else Rgb(0, 0, 0)
+            for px in range(4)
+        ]
+        for py in range(4)
+    ]
+
+@pytest.mark.parametrize('mask', [False, True])
+@pytest.mark.parametrize('x, y', [(0, 0), (1, 1), (2, 3), (3, 0), (0, 3), (3, 3), (4, 0), (0, 4), (10, 10)])
+def test_paste_clipped(x: int, y: int, mask: bool) -> None:
+    image = Image.new(4, 4, Pixel.from_rgb(0, 0, 0))
+    image.paste(x, y, Image.new(3, 3, Pixel.from_rgb(255, 255, 255)), checker_mask(3, 3) if mask else None)
+
+    assert image.pixels() == paste_expected(x, y, mask)
+
+def test_paste_larger_image() -> None:
+    image = Image.new(2, 2, Pixel.from_rgb(0, 0, 0))
+    image.paste(0, 0, Image.new(5, 5, Pixel.from_rgb(255, 255, 255)), checker_mask(5, 5))
+
+    assert image.pixels() == [[Rgb(255, 255, 255), Rgb(0, 0, 0)], [Rgb(0, 0, 0), Rgb(255, 255, 255)]]
+
+def test_mask_alpha_dimensions() -> None:
+    mask = Image.from_pixels(3, [Pixel.from_l(v) for v in (10, 20, 30, 40, 50, 60)])
+    image = Image.new(2, 1, Pixel.from_rgb(255, 0, 0))
+
+    with pytest.raises(ValueError, match=r'crop_mask=True'):
+        image.mask_alpha(mask)
+
+    with pytest.raises(ValueError):
+        Image.new(4, 1, Pixel.from_rgb(255, 0, 0)).mask_alpha(mask, crop_mask=True)
+
+    image.mask_alpha(mask, crop_mask=True)
+    assert [pixel.a for pixel in image.pixels()[0]] == [10, 20]
+
+    exact = Image.new(3, 2, Pixel.from_rgb(255, 0, 0))
+    exact.mask_alpha(mask)
+    assert [pixel.a for row in exact.pixels() for pixel in row] == [10, 20, 30, 40, 50, 60]
+
+@pytest.mark.parametrize('width', [8, 13])
+def test_packed_bits_round_trip(width: int) -> None:
+    pixels = [Pixel.from_bitpixel((x * 3 + y) % 5 == 0) for y in range(3) for x in range(width)]
+    image = Image.from_pixels(width, pixels)
+
+    data = image.to_packed_bits()
+    assert len(data) == 3 * ((width + 7) // 8)
+
+    unpacked = Image.from_packed_bits(width, 3, data)
+    assert unpacked.mode == 'BITPIXEL'
+    assert unpacked.pixels() == image.pixels()
+
+def test_packed_bits_row_align() -> None:
+    image = Image.new(13, 2, Pixel.from_bitpixel(True))
+
+    data = image.to_packed_bits(row_align=4)
+    assert data == b'\xff\xf8\x00\x00' * 2
+    assert Image.from_packed_bits(13, 2, data, row_align=4).pixels() == image.pixels()
+
+    with pytest.raises(ValueError):
+        Image.from_packed_bits(13, 2, data)
+
+    for width, height in ((0, 2), (13, 0), (0, 0)):
+        with pytest.raises(ValueError):
+            Image.from_packed_bits(width, height, b'')
+
+    with pytest.raises(ValueError):
+        image.to_packed_bits(row_align=0)
+
+@pytest.mark.parametrize('mode, fill', [
+    ('BITPIXEL', Pixel.from_bitpixel(True)),
+    ('L', Pixel.from_l(90)),
+    ('RGB', Pixel.from_rgb(10, 20, 30)),
+    ('RGBA', Pixel.from_rgba(10, 20, 30, 40)),
+])
+def test_buffer_round_trip(mode: str, fill: Pixel) -> None:
+    image = Image.new(5, 3, fill)
+    image.set_pixel(1, 2, Pixel.from_rgba(200, 150, 100, 50))
+
+    data = image.to_bytes()
+    channels = {'BITPIXEL': 1, 'L': 1, 'RGB': 3, 'RGBA': 4}[mode]
+    assert isinstance(data, bytes)
+    assert len(data) == 5 * 3 * channels
+    assert data == image.view(0, 0, 5, 3).to_bytes()
+
+    for buffer in (data, bytearray(data), memoryview(data)):
+        restored = Image.from_buffer(5, 3, mode, buffer)
+
+        assert restored.mode == mode
+        assert restored.pixels() == image.pixels()
+
+def test_from_buffer_layout() -> None:
+    image = Image.from_buffer(2, 1, 'rgba', bytes([1, 2, 3, 4, 5, 6, 7, 8]))
+    assert image.pixels() == [[Rgba(1, 2, 3, 4), Rgba(5, 6, 7, 8)]]
+
+    image = Image.from_buffer(3, 1, Mode.BitPixel, bytes([0, 128, 127]))
+    assert [p.value for p in image.pixels()[0]] == [False, True, False]
+
+    with pytest.raises(ValueError, match='Expected 8 bytes'):
+        Image.from_buffer(2, 1, 'RGBA', bytes(7))
+
+    with pytest.raises(ValueError):
+        Image.from_buffer(2, 1, 'CMYK', bytes(8))
+
+def test_buffer_numpy() -> None:
+    np = pytest.importorskip('numpy')
+    image = Image.from_pixels(3, [Pixel.from_rgba(x * 40, y * 60, 7, 255) for y in range(2) for x in range(3)])
+
+    array = np.frombuffer(image.to_bytes(), dtype=np.uint8).reshape(2, 3, 4)
+    assert array[1, 2].tolist() == [80, 60, 7, 255]
+
+    restored = Image.from_buffer(3, 2, 'RGBA', array)
+    assert restored.pixels() == image.pixels()
+
+    # A non-contiguous view, every other column.
+    strided = Image.from_buffer(2, 2, 'RGBA', array[:, ::2])
+    assert strided.get_pixel(1, 1) == image.get_pixel(2, 1)
+
+@pytest.mark.parametrize('mode, pixel', [
+    ('1', lambda x, y: Pixel.from_bitpixel((x + y) % 3 == 0)),
+    ('L', lambda x, y: Pixel.from_l(x * 40 + y)),
+    ('RGB', lambda x, y: Pixel.from_rgb(x * 40, y * 60, 7)),
+    ('RGBA', lambda x, y: Pixel.from_rgba(x * 40, y * 60, 7, 100 + x)),
+])
+def test_pillow_round_trip(mode: str, pixel) -> None:
+    pil = pytest.importorskip('PIL.Image')
+    image = Image.from_pixels(5, [pixel(x, y) for y in range(3) for x in range(5)])
+
+    converted = image.to_pillow()
+    assert isinstance(converted, pil.Image)
+    assert (converted.mode, converted.size) == (mode, (5, 3))
+
+    for x, y in ((0, 0), (4, 2), (2, 1)):
+        expected = image.get_pixel(x, y)
+        actual = converted.getpixel((x, y))
+        if mode == '1':
+            assert bool(actual) == expected.value
+        elif mode == 'L':
+            assert actual == expected.value
+        else:
+            assert actual == (expected.r, expected.g, expected.b) + ((expected.a,) if mode == 'RGBA' else ())
+
+    restored = Image.from_pillow(converted)
+    assert restored.mode == image.mode
+    assert restored.pixels() == image.pixels()
+
+@pytest.mark.parametrize('mode', ['P', 'CMYK', 'LA'])
+def test_pillow_unsupported_mode(mode: str) -> None:
+    pil = pytest.importorskip('PIL.Image')
+
+    with pytest.raises(ValueError, match=f"`{mode}`.*convert"):
+        Image.from_pillow(pil.new(mode, (2, 2)))
+
+WHITE = Pixel.from_rgb(255, 255, 255)
+BLACK = Pixel.from_rgb(0, 0, 0)
+
+@pytest.mark.parametrize('color, luminance, ratio', [
+    ((0, 0, 0), 0.0, 21.0),
+    ((255, 255, 255), 1.0, 1.0),
+    ((119, 119, 119), 0.1845, 4.478),
+    ((118, 118, 118), 0.1812, 4.542),
+    ((0, 0, 255), 0.0722, 8.593),
+    ((255, 0, 0), 0.2126, 3.998),
+    ((0, 128, 0), 0.1544, 5.137),
+])
+def test_contrast_ratio(color: tuple, luminance: float, ratio: float) -> None:
+    pixel = Pixel.from_rgb(*color)
+
+    assert pixel.relative_luminance() == pytest.approx(luminance, abs=1e-4)
+    assert Rgb(*color).relative_luminance() == pytest.approx(luminance, abs=1e-4)
+    assert ril.contrast_ratio(pixel, WHITE) == pytest.approx(ratio, abs=1e-3)
+    assert ril.contrast_ratio(WHITE, pixel) == pytest.approx(ratio, abs=1e-3)
+
+def test_contrast_ratio_alpha() -> None:
+    # Black at an alpha of 128 over white is a gray of 127.
+    translucent = Pixel.from_rgba(0, 0, 0, 128)
+    gray = Pixel.from_rgb(127, 127, 127)
+
+    assert translucent.relative_luminance() == pytest.approx(gray.relative_luminance())
+    assert Rgba(0, 0, 0, 128).relative_luminance() == pytest.approx(gray.relative_luminance())
+    assert translucent.relative_luminance(BLACK) == 0.0
+    assert ril.contrast_ratio(translucent, WHITE) == pytest.approx(ril.contrast_ratio(gray, WHITE))
+    assert ril.contrast_ratio(translucent, BLACK) == pytest.approx(1.0)
+    assert ril.contrast_ratio(BLACK, Pixel.from_rgba(0, 0, 0, 0)) == pytest.approx(21.0)
+    assert Pixel.from_l(255).relative_luminance() == pytest.approx(1.0)
+
+def test_average_color() -> None:
+    image = Image.from_pixels(4, [BLACK if x < 2 else WHITE for _ in range(2) for x in range(4)])
+
+    assert image.average_color() == Pixel.from_rgb(128, 128, 128)
+    assert image.average_color((0, 0, 2, 2)) == BLACK
+    assert image.average_color((2, 1, 4, 2)) == WHITE
+    assert ril.contrast_ratio(BLACK, image.average_color((2, 0, 4, 2))) == pytest.approx(21.0)
+
+    gray = Image.from_pixels(2, [Pixel.from_l(10), Pixel.from_l(30)])
+    assert gray.average_color() == Pixel.from_l(20)
+
+    with pytest.raises(ValueError):
+        image.average_color((0, 0, 5, 2))
+
+    with pytest.raises(ValueError):
+        image.average_color((1, 1, 1, 2))
+
+def test_average_color_alpha() -> None:
+    image = Image.from_pixels(2, [Pixel.from_rgba(255, 0, 0, 0), Pixel.from_rgba(0, 0, 255, 255)])
+    assert image.average_color() == Pixel.from_rgba(0, 0, 255, 128)
+
+    transparent = Image.new(2, 2, Pixel.from_rgba(255, 0, 0, 0))
+    assert transparent.average_color() == Pixel.from_rgba(0, 0, 0, 0)
+
+def checkerboard_gradient() -> Image:
+    return Image.from_pixels(10, [Pixel.from_rgb(x * 25, y * 30, (x + y) % 2 * 255) for y in range(8) for x in range(10)])
+
+def test_pixelate_region() -> None:
+    before = checkerboard_gradient()
+    image = before.copy()
+    image.pixelate(3, region=(2, 1, 9, 7))
+
+    for y in range(8):
+        for x in range(10):
+            if not (2 <= x < 9 and 1 <= y < 7):
+                assert image.get_pixel(x, y) == before.get_pixel(x, y)
+
+    # The blocks at the right edge are a single pixel wide.
+    for x1 in (2, 5, 8):
+        for y1 in (1, 4):
+            block = (x1, y1, min(x1 + 3, 9), y1 + 3)
+            pixel = image.get_pixel(x1, y1)
+
+            assert image.view(*block).pixels_flat() == [pixel] * ((block[2] - x1) * 3)
+            assert Pixel.from_rgb(pixel.r, pixel.g, pixel.b) == before.average_color(block)
+
+def test_mosaic() -> None:
+    image = checkerboard_gradient()
+    image.mosaic(4)
+
+    expected = checkerboard_gradient()
+    expected.pixelate(4)
+    assert image.pixels() == expected.pixels()
+    assert image.get_pixel(9, 7) == image.get_pixel(8, 4)
+
+    single = checkerboard_gradient()
+    single.mosaic(1)
+    assert single.pixels() == checkerboard_gradient().pixels()
+
+    for block_size, region in ((0, None), (2, (0, 0, 11, 8)), (2, (3, 3, 3, 5))):
+        with pytest.raises(ValueError):
+            image.pixelate(block_size, region)
+
+    with pytest.raises(ValueError):
+        image.mosaic(0)
+
+@pytest.mark.parametrize('name, expected', [
+    ('rebeccapurple', Pixel.from_rgb(102, 51, 153)),
+    ('White', Pixel.from_rgb(255, 255, 255)),
+    ('Rebecca Purple', Pixel.from_rgb(102, 51, 153)),
+    ('dark_slate_gray', Pixel.from_rgb(47, 79, 79)),
+    ('light-goldenrod-yellow', Pixel.from_rgb(250, 250, 210)),
+    ('transparent', Pixel.from_rgba(0, 0, 0, 0)),
+])
+def test_pixel_from_name(name: str, expected: Pixel) -> None:
+    assert Pixel.from_name(name) == expected
+
+def test_pixel_from_name_suggestions() -> None:
+    with pytest.raises(ValueError, match='did you mean `rebeccapurple`'):
+        Pixel.from_name('rebbecapurple')
+
+    with pytest.raises(ValueError, match='did you mean `white`'):
+        Pixel.from_name('whte')
+
+    with pytest.raises(ValueError, match='did you mean `darkgray`, `darkgreen` or `darkgrey`\\?'):
+        Pixel.from_name('darkgrye')
+
+    with pytest.raises(ValueError) as exc:
+        Pixel.from_name('definitely not a color')
+
+    assert 'did you mean' not in str(exc.value)
+
+def test_colors_constants() -> None:
+    assert Colors.WHITE == Pixel.from_rgb(255, 255, 255)
+    assert Colors.BLACK == Pixel.from_rgb(0, 0, 0)
+    assert Colors.REBECCAPURPLE == Pixel.from_rgb(102, 51, 153)
+    assert Colors.CORNFLOWERBLUE == Pixel.from_rgb(100, 149, 237)
+    assert Colors.TRANSPARENT == Pixel.from_rgba(0, 0, 0, 0)
+
+    names = [name for name in dir(Colors) if name.isupper()]
+    assert len(names) == 149
+
+    for name in names:
+        assert getattr(Colors, name) == Pixel.from_name(name)
+
+def test_pixel_distance() -> None:
+    black, white = Pixel.from_rgb(0, 0, 0), Pixel.from_rgb(255, 255, 255)
+
+    assert black.distance(black) == 0
+    assert black.distance(white) == pytest.approx(255 * math.sqrt(3))
+    assert white.distance(black) == black.distance(white)
+    assert Pixel.from_l(100).distance(Pixel.from_rgb(100, 100, 100)) == 0
+    assert Pixel.from_rgba(0, 0, 0, 0).distance(black) == 255
+
+    assert Pixel.from_rgb(255, 0, 0).distance(black, metric='redmean') == pytest.approx(403.0329, abs=1e-4)
+    assert Pixel.from_rgb(0, 255, 0).distance(black, metric='redmean') == pytest.approx(510)
+    assert Pixel.from_rgb(0, 0, 255).distance(black, metric='REDMEAN') == pytest.approx(403.0329, abs=1e-4)
+
+    with pytest.raises(ValueError):
+        black.distance(white, metric='manhattan')
+
+def test_nearest_color() -> None:
+    palette = [Colors.BLACK, Colors.WHITE, Colors.RED, Pixel.from_rgb(0, 128, 0), Colors.BLUE]
+
+    assert ril.nearest_color(Pixel.from_rgb(200, 30, 30), palette) == 2
+    assert ril.nearest_color(Pixel.from_rgb(20, 100, 10), palette) == 3
+    assert ril.nearest_color(Pixel.from_l(10), palette) == 0
+    assert ril.nearest_color(Pixel.from_rgba(250, 250, 250, 255), palette, metric='redmean') == 1
+    assert ril.nearest_color(Colors.WHITE, [Colors.BLACK, Colors.WHITE, Colors.WHITE]) == 1
+
+    with pytest.raises(ValueError):
+        ril.nearest_color(Colors.WHITE, [])
+
+def test_packed_bits_requires_bitpixel() -> None:
+    with pytest.raises(TypeError):
+        Image.new(8, 1, Pixel.from_l(255)).to_packed_bits()
+
+def test_sequence_seek(fetch_file) -> None:
+    seq = ImageSequence.from_bytes(fetch_file('sample_rgba.gif'))
+    assert seq.tell() == 0
+
+    seq.seek(5)
+    assert seq.tell() == 5
+    assert next(seq).image.get_pixel(0, 0) == PIXELS[5]
+    assert seq.tell() == 6
+
+    seq.seek(0)
+    assert next(seq).image.get_pixel(0, 0) == PIXELS[0]
+
+    seq.seek(len(seq))
+    with pytest.raises(StopIteration):
+        next(seq)
+
+    with pytest.raises(IndexError):
+        seq.seek(len(seq) + 1)
+
+def test_sequence_iterate_again(fetch_file) -> None:
+    seq = ImageSequence.from_bytes(fetch_file('sample_rgba.gif'))
+
+    first = [frame.image.get_pixel(0, 0) for frame in seq]
+    assert len(first) == len(seq)
+    assert [frame.image.get_pixel(0, 0) for frame in seq] == first
+
+    next(seq)
+    next(seq)
+    assert len(list(seq)) == len(seq)
+    assert seq.tell() == 2
+
+    a, b = iter(seq), iter(seq)
+    next(a)
+    assert next(b).image.get_pixel(0, 0) == PIXELS[0]
+    assert next(a).image.get_pixel(0, 0) == PIXELS[1]
+
+def test_sequence_setitem_while_iterating() -> None:
+    seq = ImageSequence.generate(4, lambda i: Image.new(2, 2, Pixel.from_l(i * 10)), delay=20)
+
+    for i, frame in enumerate(seq):
+        frame.image = Image.new(2, 2, Pixel.from_l(255 - i))
+        seq[i] = frame
+        if i == 0:
+            seq[1] = Frame(Image.new(2, 2, Pixel.from_l(7)))
+
+    assert [frame.image.get_pixel(0, 0).value for frame in seq] == [255, 254, 253, 252]
+
+@pytest.mark.parametrize('algorithm', [ResizeAlgorithm.Nearest, ResizeAlgorithm.Bilinear, ResizeAlgorithm.Lanczos3])
+@pytest.mark.parametrize('mode', ['BITPIXEL', 'L', 'RGB', 'RGBA'])
+def test_resize_region(algorithm: ResizeAlgorithm, mode: str) -> None:
+    image = Image.from_pixels(16, [Pixel.from_rgba(x * 16, y * 16, (x * y) % 256, 255 - x * 8) for y in range(16) for x in range(16)])
+    image.convert(mode)
+
+    expected = image.copy()
+    expected.crop(3, 2, 13, 12)
+    expected.resize(5, 7, algorithm)
+
+    region = image.resize_region((3, 2, 13, 12), 5, 7, algorithm)
+    assert region.dimensions == (5, 7)
+    assert region.pixels() == expected.pixels()
+    assert image.dimensions == (16, 16)
+
+def test_resize_nearest_picks_pixels() -> None:
+    image = Image.from_pixels(2, [Pixel.from_l(10), Pixel.from_l(20), Pixel.from_l(30), Pixel.from_l(40)])
+    image.resize(4, 4, ResizeAlgorithm.Nearest)
+
+    assert [[pixel.value for pixel in row] for row in image.pixels()] == [[10, 10, 20, 20]] * 2 + [[30, 30, 40, 40]] * 2
+
+    image.resize(2, 2, ResizeAlgorithm.Nearest)
+    assert [[pixel.value for pixel in row] for row in image.pixels()] == [[10, 20], [30, 40]]
+
+def test_resize_preserve_aspect() -> None:
+    image = Image.new(200, 100, Pixel.from_rgb(0, 0, 0))
+
+    image.copy().resize(100, 80, ResizeAlgorithm.Nearest)
+
+    for width, height in [(100, 50), (199, 100), (40, 20)]:
+        target = image.copy()
+        target.resize(width, height, ResizeAlgorithm.Nearest, preserve_aspect=True)
+        assert target.dimensions == (width, height)
+
+    for width, height in [(100, 51), (100, 80), (300, 100)]:
+        with pytest.raises(ValueError):
+            image.resize(width, height, ResizeAlgorithm.Nearest, preserve_aspect=True)
+
+    assert image.dimensions == (200, 100)
+
+def test_resize_aspect_tolerance() -> None:
+    image = Image.new(200, 100, Pixel.from_rgb(0, 0, 0))
+
+    with pytest.raises(ValueError):
+        image.resize(100, 51, ResizeAlgorithm.Nearest, preserve_aspect=True, tolerance=0.015)
+
+    image.resize(100, 51, ResizeAlgorithm.Nearest, preserve_aspect=True, tolerance=0.02)
+    assert image.dimensions == (100, 51)
+
+    target = Image.new(200, 100, Pixel.from_rgb(0, 0, 0))
+    with pytest.raises(ValueError):
+        target.resize(199, 100, ResizeAlgorithm.Nearest, preserve_aspect=True, tolerance=0)
+
+    with pytest.raises(ValueError):
+        target.resize(200, 100, ResizeAlgorithm.Nearest, preserve_aspect=True, tolerance=-0.1)
+
+    target.resize(200, 100, ResizeAlgorithm.Nearest, preserve_aspect=True, tolerance=0)
+    assert target.dimensions == (200, 100)
+
+@pytest.mark.parametrize('size, expected', [
+    ((100, 80), (100, 50)),
+    ((300, 100), (200, 100)),
+    ((100, 51), (100, 50)),
+    ((1, 100), (1, 1)),
+    ((100, 50), (100, 50)),
+])
+def test_resize_fit_aspect(size: tuple, expected: tuple) -> None:
+    image = Image.new(200, 100, Pixel.from_rgb(0, 0, 0))
+    image.resize(*size, ResizeAlgorithm.Nearest, preserve_aspect=True, exact=False)
+    assert image.dimensions == expected
+
+    image = Image.new(200, 100, Pixel.from_rgb(0, 0, 0))
+    image.resize(*size, ResizeAlgorithm.Nearest, exact=False)
+    assert image.dimensions == size
+
+def test_resize_region_invalid_box() -> None:
+    image = Image.new(4, 4, Pixel.from_rgb(0, 0, 0))
+
+    for src_box in [(0, 0, 5, 4), (2, 0, 2, 4), (3, 3, 1, 1)]:
+        with pytest.raises(ValueError):
+            image.resize_region(src_box, 2, 2, ResizeAlgorithm.Nearest)
+
+        with pytest.raises(ValueError):
+            image.crop(*src_box)
+
+def test_encode_optimized() -> None:
+    image = Image.new(256, 256, Pixel.from_rgb(40, 120, 200))
+
+    data, quality = image.encode_optimized(ImageFormat.Jpeg, target_kb=4)
+    assert len(data) <= 4 * 1024
+    assert 1 <= quality <= 100
+    assert Image.from_bytes(data).dimensions == (256, 256)
+
+    data, quality = image.encode_optimized('png', target_kb=4)
+    assert len(data) <= 4 * 1024
+    assert quality is None
+
+    _, quality = image.encode_optimized('jpeg')
+    assert quality == 85
+
+def test_encode_optimized_unreachable() -> None:
+    image = Image.new(16, 16, Pixel.from_rgb(40, 120, 200))
+
+    for format in ('jpeg', 'png'):
+        with pytest.raises(RuntimeError, match='smallest achieved'):
+            image.encode_optimized(format, target_kb=0)
+
+    with pytest.raises(ValueError):
+        image.encode_optimized('gif')
+
+@pytest.mark.skipif(not WEBP, reason='requires the webp feature')
+def test_encode_optimized_webp() -> None:
+    image = Image.new(256, 256, Pixel.from_rgb(40, 120, 200))
+
+    data, quality = image.encode_optimized(ImageFormat.WebP, target_kb=4)
+    assert len(data) <= 4 * 1024
+    assert 1 <= quality <= 100
+    assert Image.from_bytes(data).dimensions == (256, 256)
+
+    _, quality = image.encode_optimized('webp')
+    assert quality == 85
+
+    with pytest.raises(RuntimeError, match='smallest achieved'):
+        image.encode_optimized('webp', target_kb=0)
+
+@pytest.mark.skipif(WEBP, reason='requires ril to be built without the webp feature')
+def test_encode_optimized_webp_unsupported() -> None:
+    image = Image.new(16, 16, Pixel.from_rgb(40, 120, 200))
+
+    with pytest.raises(RuntimeError, match='webp'):
+        image.encode_optimized('webp')
+
+def make_frames() -> list:
+    return [
+        Frame(Image.new(4, 4, Pixel.from_rgba(255, 0, 0, 255))),
+        Frame(Image.new(4, 4, Pixel.from_rgba(255, 0, 0, 128))),
+        Frame(Image.new(4, 4, Pixel.from_rgb(0, 255, 0))),
+        Frame(Image.new(4, 4, Pixel.from_rgba(0, 0, 255, 0))),
+    ]
+
+def test_frame_has_transparency() -> None:
+    assert [frame.has_transparency for frame in make_frames()] == [False, True, False, True]
+
+def test_from_frames_fix_disposal() -> None:
+    with warnings.catch_warnings():
+        warnings.simplefilter('error')
+        seq = ImageSequence.from_frames(make_frames(), fix_disposal=True)
+
+    assert seq.adjusted_frames == [1, 3]
+    assert [frame.disposal for frame in seq] == [
+        DisposalMethod.Keep,
+        DisposalMethod.Background,
+        DisposalMethod.Keep,
+        DisposalMethod.Background,
+    ]
+
+    with pytest.warns(UserWarning, match=r'\[1, 3\]'):
+        ImageSequence.from_frames(make_frames(), fix_disposal=True, warn=True)
+
+def test_from_frames_warns_about_ghosting() -> None:
+    with pytest.warns(UserWarning, match='fix_disposal'):
+        seq = ImageSequence.from_frames(make_frames(), warn=True)
+
+    assert all(frame.disposal == DisposalMethod.Keep for frame in seq)
+    assert seq.adjusted_frames == []
+
+def make_tile() -> Image:
+    return Image.from_pixels(3, [Pixel.from_l(v) for v in (10, 20, 30, 40, 50, 60)])
+
+def test_paste_tiled_periodic() -> None:
+    image = Image.new(10, 5, Pixel.from_l(0))
+    image.paste_tiled(make_tile(), offset=(1, 1))
+
+    tile = make_tile()
+    for y, row in enumerate(image.pixels()):
+        for x, pixel in enumerate(row):
+            assert pixel == tile.get_pixel((x + 1) % 3, (y + 1) % 2)
+
+def test_paste_tiled_region() -> None:
+    image = Image.new(10, 5, Pixel.from_l(0))
+    image.paste_tiled(make_tile(), region=(2, 1, 9, 4))
+
+    tile = make_tile()
+    for y, row in enumerate(image.pixels()):
+        for x, pixel in enumerate(row):
+            if 2 <= x < 9 and 1 <= y < 4:
+                assert pixel == tile.get_pixel((x - 2) % 3, (y - 1) % 2)
+            else:
+                assert pixel.value == 0
+
+    with pytest.raises(ValueError):
+        image.paste_tiled(tile, region=(0, 0, 11, 5))
+
+@pytest.mark.parametrize('overlap, pad', [(0, False), (2, False), (0, True), (1, True)])
+def test_iter_tiles_reassemble(overlap: int, pad: bool) -> None:
+    image = gradient(11, 7, 'RGB')
+    result = Image.new(11, 7, Pixel.from_rgb(255, 255, 255))
+
+    tiles = list(image.iter_tiles(4, 3, overlap=overlap, pad=pad, fill=Pixel.from_rgb(1, 2, 3)))
+    for x, y, tile in tiles:
+        if pad:
+            assert tile.dimensions == (4, 3)
+        else:
+            assert tile.dimensions == (min(4, 11 - x), min(3, 7 - y))
+
+        result.blit(x, y, tile)
+
+    assert result.pixels() == image.pixels()
+
+    positions = [(x, y) for x, y, _ in tiles]
+    assert positions[0] == (0, 0)
+    assert len(positions) == len(set(positions))
+
+def test_iter_tiles_pad_fill() -> None:
+    tiles = list(gradient(5, 5, 'RGB').iter_tiles(4, 4, pad=True, fill=Pixel.from_rgb(1, 2, 3)))
+
+    assert [(x, y) for x, y, _ in tiles] == [(0, 0), (4, 0), (0, 4), (4, 4)]
+    assert tiles[3][2].get_pixel(0, 0) == gradient(5, 5, 'RGB').get_pixel(4, 4)
+    assert tiles[3][2].get_pixel(1, 1).r == 1
+
+def test_iter_tiles_invalid() -> None:
+    image = gradient(4, 4, 'RGB')
+
+    with pytest.raises(ValueError):
+        image.iter_tiles(0, 2)
+
+    with pytest.raises(ValueError):
+        image.iter_tiles(4, 2, overlap=2)
+
+def fade(i: int) -> Image:
+    return Image.new(8, 8, Pixel.from_rgba(255, 0, 0, 255 - i * 25))
+
+def test_sequence_generate() -> None:
+    seq = ImageSequence.generate(10, fade, delay=40)
+    assert len(seq) == 10
+
+    frames = list(seq)
+    assert [frame.delay for frame in frames] == [40] * 10
+    assert [frame.image.get_pixel(0, 0).a for frame in frames] == [255 - i * 25 for i in range(10)]
+
+    assert next(ImageSequence.generate(1, fade)).delay == 100
+
+def test_sequence_generate_errors() -> None:
+    def fail(i: int) -> Image:
+        if i == 3:
+            raise ZeroDivisionError
+        return fade(i)
+
+    with pytest.raises(RuntimeError, match='frame 3') as exc_info:
+        ImageSequence.generate(5, fail)
+    assert isinstance(exc_info.value.__cause__, ZeroDivisionError)
+
+    with pytest.raises(TypeError, match='frame 0'):
+        ImageSequence.generate(2, lambda i: None)
+
+    with pytest.raises(ValueError, match='frame 1'):
+        ImageSequence.generate(2, lambda i: Image.new(4 + i, 4, Pixel.from_l(0)))
+
+    with pytest.raises(ValueError):
+        ImageSequence.generate(0, fade)
+
+def test_encode_into() -> None:
+    image = Image.new(8, 8, Pixel.from_rgb(40, 120, 200))
+    expected = image.encode('png')
+
+    exact = bytearray(len(expected))
+    assert image.encode_into('png', exact) == len(expected)
+    assert exact == expected
+
+    oversized = bytearray(b'\xff' * (len(expected) + 16))
+    assert image.encode_into('png', memoryview(oversized)) == len(expected)
+    assert oversized[:len(expected)] == expected
+    assert oversized[len(expected):] == b'\xff' * 16
+
+def test_encode_into_undersized() -> None:
+    image = Image.new(8, 8, Pixel.from_rgb(40, 120, 200))
+    expected = image.encode('png')
+
+    with pytest.raises(BufferTooSmallError, match=str(len(expected))) as exc_info:
+        image.encode_into('png', bytearray(4))
+
+    assert isinstance(exc_info.value, BufferError)
+    assert exc_info.value.required_size == len(expected)
+
+    buffer = bytearray(exc_info.value.required_size)
+    assert image.encode_into('png', buffer) == len(expected)
+
+    with pytest.raises(BufferError):
+        image.encode_into('png', bytes(len(expected)))
+
+def make_gif() -> ImageSequence:
+    seq = ImageSequence.generate(5, lambda i: Image.new(8, 6, Pixel.from_rgb(i * 50, 0, 255)), delay=50)
+    return ImageSequence.from_bytes(seq.encode('gif'))
+
+def test_sequence_resize_invert() -> None:
+    seq = make_gif()
+    before = [frame.image.get_pixel(0, 0) for frame in seq]
+    seq.seek(0)
+
+    seq.resize(4, 3, ResizeAlgorithm.Nearest)
+    seq.invert()
+
+    frames = list(seq)
+    assert len(frames) == 5
+    assert all(frame.dimensions == (4, 3) for frame in frames)
+    assert [frame.delay for frame in frames] == [50] * 5
+
+    for pixel, frame in zip(before, frames):
+        inverted = frame.image.get_pixel(3, 2)
+        assert (inverted.r, inverted.g, inverted.b) == (255 - pixel.r, 255 - pixel.g, 255 - pixel.b)
+
+    assert len(ImageSequence.from_bytes(seq.encode('gif'))) == 5
+
+def test_sequence_frame_operations() -> None:
+    seq = make_gif()
+    next(seq)
+
+    seq.crop(1, 1, 5, 4)
+    seq.convert('L')
+    seq.mirror()
+    seq.flip()
+
+    assert seq.tell() == 1
+    assert [frame.dimensions for frame in seq] == [(4, 3)] * 5
+
+    seq.seek(0)
+    assert all(frame.image.mode == 'L' for frame in seq)
+
+    with pytest.raises(ValueError):
+        seq.crop(0, 0, 5, 4)
+
+def test_frame_set_image() -> None:
+    frame = Frame(Image.new(8, 6, Pixel.from_rgb(0, 0, 0)))
+    frame.delay = 70
+
+    image = frame.image
+    image.invert()
+    assert frame.image.get_pixel(0, 0) == Rgb(0, 0, 0)
+
+    frame.image = image
+    assert frame.image.get_pixel(0, 0) == Rgb(255, 255, 255)
+    assert frame.delay == 70
+
+    frame.image = Image.new(3, 2, Pixel.from_l(10))
+    assert frame.dimensions == (3, 2)
+
+    image.close()
+    with pytest.raises(ValueError):
+        frame.image = image
+
+def test_sequence_setitem() -> None:
+    seq = make_gif()
+    frame = seq[1]
+
+    image = frame.image
+    image.invert()
+    frame.image = image
+    seq[1] = frame
+    seq[-1] = Frame(Image.new(8, 6, Pixel.from_rgb(1, 2, 3)))
+
+    assert seq[1] == frame
+    assert seq[1].image.get_pixel(0, 0) == Rgb(205, 255, 0)
+    assert seq[4].image.get_pixel(0, 0) == Rgb(1, 2, 3)
+    assert [f.image.get_pixel(0, 0) for f in seq][1] == Rgb(205, 255, 0)
+    assert len(seq) == 5
+
+    for index in (5, -6):
+        with pytest.raises(IndexError):
+            seq[index] = frame
+
+def test_sequence_insert_append_remove() -> None:
+    seq = make_gif()
+    first, second = seq[0], seq[1]
+    marker = Frame(Image.new(8, 6, Pixel.from_rgb(1, 2, 3)))
+
+    assert next(seq) == first
+    seq.insert(0, marker)
+    assert seq.tell() == 2
+    assert next(seq) == second
+
+    seq.append(marker)
+    seq.insert(-1, marker)
+    seq.insert(100, marker)
+    assert len(seq) == 9
+    assert [frame == marker for frame in seq.frames] == [True, False, False, False, False, False, True, True, True]
+
+    removed = seq.remove(0)
+    assert removed == marker
+    assert seq.tell() == 2
+    assert seq.remove(-1) == marker
+    assert len(seq) == 7
+
+    with pytest.raises(IndexError):
+        seq.remove(7)
+
+    seq.seek(0)
+    assert len(list(seq)) == 7
+    assert len(ImageSequence.from_bytes(seq.encode('gif'))) == 7
+
+SAMPLE_GIF = os.path.join(os.path.dirname(__file__), 'images', 'sample_rgba.gif')
+
+async def run_with_ticker(coro):
+    ticks = 0
+
+    async def ticker() -> None:
+        nonlocal ticks
+        while True:
+            await asyncio.sleep(0.001)
+            ticks += 1
+
+    task = asyncio.create_task(ticker())
+    await asyncio.sleep(0)
+
+    try:
+        result = await coro
+    finally:
+        task.cancel()
+
+    return result, ticks
+
+def random_image(width: int, height: int) -> Image:
+    # Random pixels don't compress, so encoding and decoding them takes far longer than a tick of the ticker.
+    return Image.from_buffer(width, height, 'RGB', os.urandom(width * height * 3))
+
+def test_image_async_io(tmp_path) -> None:
+    path = tmp_path / 'async.png'
+    image = random_image(2048, 2048)
+
+    async def main():
+        await image.save_async(path)
+        return await Image.open_async(path)
+
+    opened, ticks = asyncio.run(run_with_ticker(main()))
+
+    assert opened.dimensions == (2048, 2048)
+    assert opened.get_pixel(2047, 2047) == image.get_pixel(2047, 2047)
+    assert ticks > 0
+
+def test_sequence_async_io(tmp_path) -> None:
+    source = tmp_path / 'source.gif'
+    path = tmp_path / 'async.gif'
+    ImageSequence.from_frames([Frame(random_image(512, 512)) for _ in range(8)]).save(source, 'gif')
+
+    async def main():
+        seq = await ImageSequence.open_async(source)
+        await seq.save_async(path, 'gif')
+        return await ImageSequence.open_async(path)
+
+    reopened, ticks = asyncio.run(run_with_ticker(main()))
+
+    assert len(reopened) == 8
+    assert ticks > 0
+
+def test_async_io_errors(tmp_path) -> None:
+    with pytest.raises(RuntimeError):
+        Image.open_async(tmp_path / 'missing.png')
+
+    async def main() -> None:
+        await Image.open_async(tmp_path / 'missing.png')
+
+    with pytest.raises(OSError):
+        asyncio.run(main())
+
+def test_view() -> None:
+    image = Image.new(6, 4, Pixel.from_rgb(0, 0, 0))
+    image.set_pixel(2, 1, Pixel.from_rgb(1, 2, 3))
+
+    view = image.view(2, 1, 5, 3)
+    copy = view.to_image()
+
+    assert (view.width, view.height) == (3, 2)
+    assert view.get_pixel(0, 0) == Rgb(1, 2, 3)
+    assert view.to_bytes()[:3] == bytes([1, 2, 3])
+    assert len(view.to_bytes()) == 3 * 2 * 3
+
+    image.set_pixel(4, 2, Pixel.from_rgb(9, 9, 9))
+
+    assert view.pixels_flat()[-1] == Rgb(9, 9, 9)
+    assert copy.get_pixel(2, 1) == Rgb(0, 0, 0)
+    assert copy.dimensions == (3, 2)
+
+def test_view_bounds() -> None:
+    image = Image.new(6, 4, Pixel.from_rgb(0, 0, 0))
+
+    with pytest.raises(ValueError):
+        image.view(0, 0, 7, 4)
+
+    view = image.view(2, 1, 5, 3)
+
+    with pytest.raises(IndexError):
+        view.get_pixel(3, 0)
+
+    del image
+    assert len(view.pixels_flat()) == 6
+
+def test_sequence_deduplicate() -> None:
+    shades = [0, 0, 0, 100, 101, 100, 0]
+    seq = ImageSequence.generate(len(shades), lambda i: Image.new(4, 4, Pixel.from_l(shades[i])), delay=40)
+    next(seq)
+
+    assert seq.deduplicate() == 2
+    assert seq.tell() == 0
+    assert [frame.delay for frame in seq] == [120, 40, 40, 40, 40]
+
+    assert seq.deduplicate(tolerance=1) == 2
+    seq.seek(0)
+    assert [frame.delay for frame in seq] == [120, 120, 40]
+    assert sum(frame.delay for frame in ImageSequence.from_bytes(seq.encode('gif'))) == 40 * len(shades)
+
+def test_sequence_deduplicate_keep_delays() -> None:
+    seq = ImageSequence.generate(3, lambda i: Image.new(4, 4, Pixel.from_l(0)), delay=40)
+
+    assert seq.deduplicate(merge_delays=False) == 2
+    assert [frame.delay for frame in seq] == [40]
+    assert seq.deduplicate() == 0
+
+def test_spritesheet_round_trip() -> None:
+    seq = ImageSequence.generate(5, lambda i: Image.from_pixels(3, [Pixel.from_rgb(i * 40, p * 20, 7) for p in range(6)]))
+    frames = [frame.image.pixels() for frame in seq]
+
+    strip = seq.to_spritesheet()
+    assert strip.dimensions == (3, 10)
+
+    sheet = seq.to_spritesheet(columns=2)
+    assert sheet.dimensions == (6, 6)
+    assert sheet.get_pixel(5, 5) == Rgb(0, 0, 0)
+
+    # Extra columns are dropped rather than multiplied into the width of the sheet.
+    assert seq.to_spritesheet(columns=2 ** 32 - 1).dimensions == (15, 2)
+
+    for image, kwargs in ((strip, {}), (sheet, {'count': 5})):
+        sliced = ImageSequence.from_spritesheet(image, 3, 2, delay=50, **kwargs)
+
+        assert len(sliced) == 5
+        assert [frame.image.pixels() for frame in sliced] == frames
+        assert all(frame.delay == 50 for frame in sliced)
+
+    assert len(ImageSequence.from_spritesheet(sheet, 3, 2)) == 6
+
+def test_sequence_frames() -> None:
+    seq = ImageSequence.generate(3, lambda i: Image.new(2, 2, Pixel.from_l(i * 50)), delay=20)
+
+    frames = seq.frames
+    assert len(frames) == 3
+    assert [frame.image.get_pixel(0, 0).value for frame in frames] == [0, 50, 100]
+
+    next(seq)
+    next(seq)
+    assert len(seq.frames) == 3
+    assert seq.tell() == 2
+
+    frames[0].delay = 500
+    assert seq.frames[0].delay == 20
+
+    assert frames[1] in seq
+    assert Image.new(2, 2, Pixel.from_l(100)) in seq
+    assert Image.new(2, 2, Pixel.from_l(101)) not in seq
+    assert Image.new(3, 2, Pixel.from_l(0)) not in seq
+    assert 'frame' not in seq
+
+def test_sequence_dimensions_and_duration() -> None:
+    seq = ImageSequence.generate(3, lambda i: Image.new(4, 2, Pixel.from_l(0)), delay=40)
+    assert seq.dimensions == (4, 2)
+    assert seq.total_duration == 120
+    assert repr(seq) == '<ImageSequence len=3 dimensions=(4, 2) total_duration=120ms>'
+
+    frame = seq.frames[0]
+    assert repr(frame) == '<Frame delay=40ms dimensions=(4, 2) disposal=DisposalMethod.Keep>'
+
+    empty = ImageSequence.from_frames([])
+    assert empty.total_duration == 0
+    assert repr(empty) == '<ImageSequence len=0>'
+    with pytest.raises(ValueError):
+        empty.dimensions
+
+def test_sequence_overlay() -> None:
+    base = ImageSequence.generate(4, lambda i: Image.new(4, 4, Pixel.from_rgb(i * 10, 0, 0)), delay=30)
+    sticker = ImageSequence.generate(2, lambda i: Image.new(2, 2, Pixel.from_rgb(0, 100 + i, 0)), delay=70)
+
+    looped = base.overlay(sticker, 1, 1)
+    frames = list(looped)
+
+    assert len(frames) == 4
+    assert [frame.delay for frame in frames] == [30] * 4
+    assert [frame.image.get_pixel(1, 1) for frame in frames] == [Rgb(0, 100 + i % 2, 0) for i in range(4)]
+    assert [frame.image.get_pixel(0, 0) for frame in frames] == [Rgb(i * 10, 0, 0) for i in range(4)]
+
+    frozen = list(base.overlay(sticker, 1, 1, loop_shorter=False))
+    assert [frame.image.get_pixel(2, 2) for frame in frozen] == [Rgb(0, 100, 0), Rgb(0, 101, 0), Rgb(0, 101, 0), Rgb(0, 101, 0)]
+
+    longer = list(sticker.overlay(base, 0, 0, loop_shorter=False))
+    assert len(longer) == 4
+    assert [frame.delay for frame in longer] == [70] * 4
+    assert [frame.image.get_pixel(0, 0) for frame in longer] == [Rgb(i * 10, 0, 0) for i in range(4)]
+
+def test_sequence_overlay_image() -> None:
+    base = ImageSequence.generate(3, lambda i: Image.new(4, 4, Pixel.from_rgb(0, 0, 0)))
+    stamp = Image.new(8, 8, Pixel.from_rgba(255, 255, 255, 255))
+
+    frames = list(base.overlay(stamp, 3, 3))
+    assert len(frames) == 3
+    assert all(frame.image.get_pixel(3, 3) == Rgb(255, 255, 255) and frame.image.get_pixel(2, 2) == Rgb(0, 0, 0) for frame in frames)
+
+    with pytest.raises(TypeError):
+        base.overlay('sticker', 0, 0)
+
+def test_spritesheet_errors() -> None:
+    sheet = Image.new(6, 6, Pixel.from_rgb(0, 0, 0))
+
+    for args, kwargs in (((4, 2), {}), ((0, 2), {}), ((3, 2), {'count': 0}), ((3, 2), {'count': 7})):
+        with pytest.raises(ValueError):
+            ImageSequence.from_spritesheet(sheet, *args, **kwargs)
+
+    with pytest.raises(ValueError):
+        ImageSequence.from_frames([Frame(sheet)]).to_spritesheet(columns=0)
+
+    with pytest.raises(ValueError):
+        ImageSequence.from_frames([Frame(sheet), Frame(Image.new(2, 2, Pixel.from_rgb(0, 0, 0)))]).to_spritesheet()
+
+def test_sequence_numpy_round_trip() -> None:
+    np = pytest.importorskip('numpy')
+
+    seq = ImageSequence.generate(3, lambda i: Image.from_pixels(4, [Pixel.from_rgba(i * 40, p * 20, 7, 255 - p) for p in range(8)]), delay=40)
+    array = seq.to_numpy()
+
+    assert array.shape == (3, 2, 4, 4)
+    assert array.dtype == np.uint8
+    assert array[2, 1, 3].tolist() == [80, 140, 7, 248]
+
+    restored = ImageSequence.from_numpy(array, delay=40)
+    assert len(restored) == 3
+    assert [frame.image.pixels() for frame in restored] == [frame.image.pixels() for frame in seq]
+    assert all(frame.delay == 40 for frame in restored)
+    assert np.array_equal(restored.to_numpy(), array)
+
+def test_sequence_numpy_modes() -> None:
+    np = pytest.importorskip('numpy')
+
+    seq = ImageSequence.generate(3, lambda i: Image.new(3, 2, Pixel.from_rgb(i * 50, 100, 200)))
+
+    rgb = seq.to_numpy('RGB')
+    assert rgb.shape == (3, 2, 3, 3)
+    assert rgb[1, 0, 0].tolist() == [50, 100, 200]
+
+    gray = seq.to_numpy(Mode.L)
+    assert gray.shape == (3, 2, 3, 1)
+    assert ImageSequence.from_numpy(gray).frames[0].image.mode == 'L'
+
+    bits = ImageSequence.generate(1, lambda i: Image.new(2, 2, Pixel.from_bitpixel(True))).to_numpy('BITPIXEL')
+    assert bits.tolist() == [[[[255], [255]], [[255], [255]]]]
+
+    restored = ImageSequence.from_numpy(rgb[:, :, ::-1])
+    assert restored.frames[2].image.mode == 'RGB'
+    assert restored.frames[2].image.get_pixel(0, 0) == Rgb(100, 100, 200)
+
+def test_sequence_numpy_errors() -> None:
+    np = pytest.importorskip('numpy')
+
+    with pytest.raises(ValueError):
+        ImageSequence.from_frames([Frame(Image.new(2, 2, Pixel.from_l(0))), Frame(Image.new(3, 2, Pixel.from_l(0)))]).to_numpy()
+
+    with pytest.raises(ValueError):
+        ImageSequence.from_frames([]).to_numpy()
+
+    for array in (np.zeros((2, 2, 3), np.uint8), np.zeros((0, 2, 2, 3), np.uint8), np.zeros((1, 0, 2, 3), np.uint8), np.zeros((1, 2, 2, 2), np.uint8)):
+        with pytest.raises(ValueError):
+            ImageSequence.from_numpy(array)
+
+    with pytest.raises(TypeError):
+        ImageSequence.from_numpy(np.zeros((1, 2, 2, 3), np.float32))
+
+@pytest.mark.parametrize('encoding', ['png', 'jpeg', 'gif', 'webp'])
+def test_peek_dimensions(encoding: str) -> None:
+    try:
+        data = Image.new(37, 21, Pixel.from_rgb(10, 20, 30)).encode(encoding)
+    except (ValueError, RuntimeError):
+        pytest.skip(f'{encoding} encoding is not available')
+
+    assert Image.peek_dimensions(data) == Image.from_bytes(data).dimensions == (37, 21)
+    assert ImageSequence.peek_frame_count(data) == 1
+
+    with pytest.raises(RuntimeError):
+        Image.peek_dimensions(data[:8])
+
+def test_peek_frame_count() -> None:
+    data = make_gif().encode('gif')
+
+    assert ImageSequence.peek_frame_count(data) == len(ImageSequence.from_bytes(data)) == 5
+
+    with open(SAMPLE_GIF, 'rb') as f:
+        data = f.read()
+
+    assert ImageSequence.peek_frame_count(data) == len(ImageSequence.from_bytes(data))
+    assert Image.peek_dimensions(data) == next(ImageSequence.from_bytes(data)).dimensions
+
+    with pytest.raises(UnknownFormatError):
+        ImageSequence.peek_frame_count(b'not an image')
+
+def test_convert_dither() -> None:
+    image = gradient()
+    image.convert('BITPIXEL')
+    middle = [row[24:40] for row in image.pixels()]
+
+    assert image.mode == 'BITPIXEL'
+    # A hard edge without dithering
+    assert all(row == middle[0] for row in middle)
+
+    image = gradient()
+    image.convert(Mode.BitPixel, dither=True)
+    values = [p.value for row in image.pixels() for p in row[24:40]]
+
+    assert 0.3 < sum(values) / len(values) < 0.7
+    assert not any(p.value for p in image.pixels()[0][:2])
+    assert all(p.value for p in image.pixels()[0][-2:])
+
+def test_convert_levels() -> None:
+    image = gradient()
+    image.convert('L', levels=4)
+
+    assert {p.value for row in image.pixels() for p in row} == {0, 85, 170, 255}
+
+    image = gradient()
+    image.convert('L', dither=True, levels=2)
+
+    assert {p.value for row in image.pixels() for p in row} == {0, 255}
+
+    with pytest.raises(ValueError):
+        image.convert('RGB', levels=4)
+
+    with pytest.raises(ValueError):
+        image.convert('L', levels=1)
+
+def test_converted() -> None:
+    image = Image.new(2, 2, Pixel.from_rgba(200, 100, 50, 128))
+    image.freeze()
+
+    gray = image.converted('l')
+    assert gray.mode == 'L'
+    assert not gray.frozen
+    assert image.mode == 'RGBA'
+
+    back = gray.converted(Mode.Rgba)
+    value = gray.get_pixel(1, 1).value
+    assert back.mode == 'RGBA'
+    assert back.get_pixel(1, 1) == ril.Rgba(value, value, value, 255)
+
+    assert image.converted('bitpixel').mode == 'BITPIXEL'
+    assert image.converted('RGB').get_pixel(0, 0) == ril.Rgb(200, 100, 50)
+
+    with pytest.raises(ValueError, match='Invalid mode'):
+        image.converted('CMYK')
+
+    with pytest.raises(ValueError):
+        image.converted('RGB', levels=4)
+
+def rotated_rows(rows: list, turns: int) -> list:
+    for _ in range(turns % 4):
+        rows = [list(row) for row in zip(*rows[::-1])]
+
+    return rows
+
+@pytest.mark.parametrize('degrees', [90, 180, 270, -90, 360, 450.0])
+def test_rotate_right_angles(degrees: float) -> None:
+    image = Image.from_pixels(3, [Pixel.from_rgba(i * 40, 255 - i * 40, i, 100 + i) for i in range(6)])
+    expected = rotated_rows(image.pixels(), int(degrees) // 90)
+
+    image.rotate(degrees)
+
+    assert image.dimensions == (len(expected[0]), len(expected))
+    assert image.pixels() == expected
+
+def test_rotate_swaps_dimensions() -> None:
+    image = Image.new(20, 10, Pixel.from_rgb(1, 2, 3))
+    image.rotate(90)
+    assert image.dimensions == (10, 20)
+
+    image.rotate(0)
+    assert image.dimensions == (10, 20)
+
+@pytest.mark.parametrize('algorithm', [None, ResizeAlgorithm.Nearest, ResizeAlgorithm.Bicubic])
+def test_rotate_arbitrary(algorithm) -> None:
+    image = Image.new(20, 10, Pixel.from_rgba(200, 100, 50, 128))
+    image.rotate(45, algorithm)
+
+    assert image.mode == 'RGBA'
+    assert image.dimensions == (22, 22)
+    assert image.get_pixel(11, 11) == ril.Rgba(200, 100, 50, 128)
+    assert image.get_pixel(0, 0) == ril.Rgba(0, 0, 0, 0)
+
+    if algorithm == ResizeAlgorithm.Nearest:
+        assert {p.a for row in image.pixels() for p in row} == {0, 128}
+
+def test_rotate_fill() -> None:
+    image = Image.new(10, 10, Pixel.from_rgb(255, 0, 0))
+    image.rotate(-30, fill=Pixel.from_rgb(0, 255, 0))
+
+    assert image.mode == 'RGB'
+    assert image.dimensions == (14, 14)
+    assert image.get_pixel(0, 0) == ril.Rgb(0, 255, 0)
+    assert image.get_pixel(7, 7) == ril.Rgb(255, 0, 0)
+
+    with pytest.raises(ValueError, match='finite'):
+        image.rotate(math.nan)
+
+PALETTE = [(0, 0, 0), (255, 0, 77), (41, 173, 255), (255, 241, 232)]
+
+def palette_gradient(alpha: bool = False) -> Image:
+    return Image.from_pixels(16, [
+        Pixel.from_rgba(x * 16, y * 32, 128, x * 16) if alpha else Pixel.from_rgb(x * 16, y * 32, 128)
+        for y in range(8)
+        for x in range(16)
+    ])
+
+def rgb_values(image: Image) -> list:
+    return [(p.r, p.g, p.b) for row in image.pixels() for p in row]
+
+def test_remap_palette() -> None:
+    palette = [Pixel.from_rgb(*color) for color in PALETTE]
+    image = palette_gradient()
+    remapped = image.remap_palette(palette)
+
+    assert remapped.mode == 'RGB'
+    assert remapped.dimensions == image.dimensions
+    assert rgb_values(remapped) == [
+        PALETTE[ril.nearest_color(Pixel.from_rgb(*color), palette, 'redmean')] for color in rgb_values(image)
+    ]
+
+    dithered = image.remap_palette(palette, dither=True)
+    assert set(rgb_values(dithered)) <= set(PALETTE)
+    assert rgb_values(dithered) != rgb_values(remapped)
+
+    gray = Image.new(2, 2, Pixel.from_l(250)).remap_palette(palette)
+    assert gray.mode == 'RGB'
+    assert set(rgb_values(gray)) == {PALETTE[3]}
+
+def test_remap_palette_alpha() -> None:
+    image = palette_gradient(alpha=True)
+    remapped = image.remap_palette([Pixel.from_rgba(*color, 0) for color in PALETTE], dither=True)
+
+    assert remapped.mode == 'RGBA'
+    assert set(rgb_values(remapped)) <= set(PALETTE)
+    assert [p.a for row in remapped.pixels() for p in row] == [p.a for row in image.pixels() for p in row]
+
+    with pytest.raises(ValueError, match='non-empty'):
+        image.remap_palette([])
+
+def test_set_pixel_converts_mode() -> None:
+    image = Image.new(2, 2, Pixel.from_l(0))
+    image.set_pixel(0, 0, Pixel.from_rgb(200, 100, 50))
+    image[1, 1] = Pixel.from_rgba(200, 100, 50, 10)
+
+    converted = Image.new(1, 1, Pixel.from_rgb(200, 100, 50))
+    converted.convert('L')
+
+    assert image.mode == 'L'
+    assert image.get_pixel(0, 0).value == round(0.299 * 200 + 0.587 * 100 + 0.114 * 50)
+    assert image.get_pixel(0, 0) == image.get_pixel(1, 1) == converted.get_pixel(0, 0)
+
+    bits = Image.new(2, 1, Pixel.from_bitpixel(False))
+    bits[0, 0] = Pixel.from_l(128)
+    bits[1, 0] = Pixel.from_l(127)
+    assert [p.value for p in bits.pixels()[0]] == [True, False]
+
+def test_set_pixel_strict_and_bounds() -> None:
+    image = Image.new(2, 2, Pixel.from_l(0))
+
+    with pytest.raises(TypeError):
+        image.set_pixel(0, 0, Pixel.from_rgb(1, 2, 3), strict=True)
+
+    image.set_pixel(0, 0, Pixel.from_l(9), strict=True)
+    assert image.get_pixel(0, 0).value == 9
+
+    with pytest.raises(IndexError):
+        image.set_pixel(2, 0, Pixel.from_l(0))
+
+    with pytest.raises(IndexError):
+        image[0, 2] = Pixel.from_l(0)
+
+
+def test_source_format() -> None:
+    image = Image.new(4, 4, Pixel.from_rgb(255, 0, 0))
+    assert image.source_format is None
+    assert image.format == 'none'
+
+    image.resize(8, 8, ResizeAlgorithm.Nearest)
+    assert image.source_format is None
+
+    decoded = Image.from_bytes(image.encode('png'))
+    assert decoded.source_format == ImageFormat.Png
+    assert decoded.format != 'none'
+
+    decoded.mirror()
+    assert decoded.source_format == ImageFormat.Png
+
+
+def test_save_source_format_default(tmp_path) -> None:
+    image = Image.new(4, 4, Pixel.from_rgb(255, 0, 0))
+
+    with pytest.raises(RuntimeError, match='extension'):
+        image.save(tmp_path / 'synthetic')
+
+    image.save(tmp_path / 'synthetic', 'png')
+
+    decoded = Image.from_bytes(image.encode('png'))
+    decoded.save(tmp_path / 'decoded')
+
+    with open(tmp_path / 'decoded', 'rb') as f:
+        assert Image.from_bytes(f.read()).source_format == ImageFormat.Png
+
+
+def test_color_space_round_trip() -> None:
+    linear = Image.from_pixels(256, [Pixel.from_rgba(v, 255 - v, v, 255 - v) for v in range(256)])
+    linear.color_space = 'linear'
+    linear.to_srgb()
+    assert linear.color_space == 'srgb'
+    linear.to_linear()
+    assert linear.color_space == 'linear'
+
+    for v, pixel in enumerate(linear.pixels()[0]):
+        assert abs(pixel.r - v) <= 1
+        assert abs(pixel.g - (255 - v)) <= 1
+        assert pixel.a == 255 - v
+
+    srgb = Image.from_pixels(192, [Pixel.from_rgb(v, v, v) for v in range(64, 256)])
+    srgb.to_linear()
+    srgb.to_srgb()
+
+    for v, pixel in enumerate(srgb.pixels()[0], 64):
+        assert abs(pixel.r - v) <= 1
+
+
+@pytest.mark.parametrize('algorithm', [ResizeAlgorithm.Bilinear, ResizeAlgorithm.Bicubic, ResizeAlgorithm.Lanczos3])
+def test_resize_linear_keeps_shadows(algorithm) -> None:
+    image = Image.from_pixels(256, [Pixel.from_rgb(v, v, v) for v in range(256)])
+    image.resize(256, 4, algorithm, linear=True)
+    assert image.color_space == 'srgb'
+
+    for row in image.pixels():
+        for v, pixel in enumerate(row):
+            assert abs(pixel.r - v) <= 1
+
+
+def test_resize_linear_blends_in_linear_light() -> None:
+    image = Image.from_pixels(2, [Pixel.from_rgb(0, 0, 0), Pixel.from_rgb(255, 255, 255)])
+    image.resize(1, 1, ResizeAlgorithm.Box, linear=True)
+    assert abs(image.get_pixel(0, 0).r - 188) <= 1
+
+    image = Image.from_pixels(2, [Pixel.from_rgba(255, 0, 0, 255), Pixel.from_rgba(0, 0, 255, 0)])
+    image.resize(1, 1, ResizeAlgorithm.Box, linear=True)
+    assert image.get_pixel(0, 0) == Pixel.from_rgba(255, 0, 0, 128)
+
+
+def test_color_space_errors() -> None:
+    image = Image.new(2, 2, Pixel.from_rgb(128, 128, 128))
+    assert image.color_space == 'srgb'
+
+    with pytest.raises(ValueError):
+        image.to_srgb()
+
+    image.to_linear()
+    with pytest.raises(ValueError):
+        image.to_linear()
+
+    with pytest.raises(ValueError):
+        image.color_space = 'cmyk'
+
+
+def test_paste_color_space_mismatch() -> None:
+    image = Image.new(2, 2, Pixel.from_rgb(0, 0, 0))
+    linear = Image.new(1, 1, Pixel.from_rgb(128, 128, 128))
+    linear.to_linear()
+    value = linear.get_pixel(0, 0).r
+
+    with pytest.warns(UserWarning):
+        image.paste(0, 0, linear)
+    assert image.get_pixel(0, 0).r == value
+
+    image.paste(1, 1, linear, match_color_space=True)
+    assert abs(image.get_pixel(1, 1).r - 128) <= 1
+    assert linear.color_space == 'linear'
+
+
+def step_image() -> Image:
+    return Image.from_pixels(16, [Pixel.from_rgba(60 if x < 8 else 180, 100, 100, 200) for _ in range(4) for x in range(16)])
+
+
+def test_unsharp_mask_identity() -> None:
+    image = step_image()
+    image.unsharp_mask(amount=0)
+
+    assert image.pixels() == step_image().pixels()
+
+
+def test_unsharp_mask_steepens_edges() -> None:
+    image = step_image()
+    image.unsharp_mask(radius=2.0, amount=1.0, threshold=4)
+    row = image.pixels()[1]
+
+    assert row[7].r < 60 and row[8].r > 180
+    assert row[0].r == 60 and row[15].r == 180
+    assert all(pixel.g == 100 and pixel.a == 200 for pixel in row)
+
+    for radius in (-1, float('inf'), float('nan')):
+        with pytest.raises(ValueError):
+            image.unsharp_mask(radius=radius)
+
+    with pytest.raises(TypeError):
+        Image.new(2, 2, Pixel.from_bitpixel(True)).unsharp_mask()
+
+
+def test_unsharp_mask_huge_radius() -> None:
+    # The blur can't reach further than the image, so a huge radius is as wide as the image.
+    image = step_image()
+    image.unsharp_mask(radius=1e30, amount=1.0)
+    wide = step_image()
+    wide.unsharp_mask(radius=1e6, amount=1.0)
+
+    assert image.pixels() == wide.pixels()
+
+
+def test_box_blur() -> None:
+    image = Image.from_pixels(5, [Pixel.from_l(value) for value in [0, 0, 240, 0, 0]])
+    image.box_blur(1)
+
+    assert [pixel.value for pixel in image.pixels_flat()] == [0, 80, 80, 80, 0]
+
+    image.box_blur(0)
+    assert [pixel.value for pixel in image.pixels_flat()] == [0, 80, 80, 80, 0]
+
+    with pytest.raises(TypeError):
+        Image.new(2, 2, Pixel.from_bitpixel(True)).box_blur(1)
+
+
+def test_blur_softens_edges() -> None:
+    image = step_image()
+    image.blur(2.0)
+    row = image.pixels()[1]
+
+    assert 60 < row[7].r < row[8].r < 180
+    assert row[0].r == 60 and row[15].r == 180
+    assert all(pixel.g == 100 and pixel.a == 200 for pixel in row)
+
+    uniform = Image.new(8, 8, Pixel.from_rgb(100, 150, 200))
+    uniform.blur(3.0)
+    assert uniform.pixels() == Image.new(8, 8, Pixel.from_rgb(100, 150, 200)).pixels()
+
+    for sigma in (-1.0, float('nan'), float('inf')):
+        with pytest.raises(ValueError):
+            image.blur(sigma)
+
+    with pytest.raises(TypeError):
+        Image.new(2, 2, Pixel.from_bitpixel(True)).blur(1.0)
+
+
+def test_blur_alpha() -> None:
+    image = Image.from_pixels(21, [
+        Pixel.from_rgba(255, 0, 0, 255) if 8 <= x < 13 and 8 <= y < 13 else Pixel.from_rgba(0, 0, 0, 0)
+        for y in range(21) for x in range(21)
+    ])
+    image.blur(2.0)
+
+    soft = image.get_pixel(7, 10)
+    assert 0 < soft.a < 255
+    assert (soft.r, soft.g, soft.b) == (255, 0, 0)
+    assert image.get_pixel(0, 0) == Rgba(0, 0, 0, 0)
+    assert image.get_pixel(10, 10).a > soft.a
+
+
+def test_blur_time_independent_of_sigma() -> None:
+    image = Image.new(1920, 1080, Pixel.from_rgba(10, 20, 30, 40))
+
+    def elapsed(sigma: float) -> float:
+        # The fastest of a few runs, which is the least affected by whatever else the machine is doing.
+        timings = []
+        for _ in range(3):
+            copy = image.copy()
+            start = time.perf_counter()
+            copy.blur(sigma)
+            timings.append(time.perf_counter() - start)
+
+        return min(timings)
+
+    wide = elapsed(40.0)
+    assert wide < 3 * elapsed(2.0)
+    # Generous enough for a debug build on a slow machine, while still catching a blur that slows down with sigma.
+    assert wide < 5.0
+
+
+def test_vignette() -> None:
+    image = Image.new(21, 21, Pixel.from_rgba(200, 200, 200, 150))
+    image.vignette(strength=0.5, radius=0.5)
+
+    center, edge, corner = image.get_pixel(10, 10), image.get_pixel(0, 10), image.get_pixel(0, 0)
+    assert center == Rgba(200, 200, 200, 150)
+    assert 100 <= corner.r < edge.r < 200
+    assert corner.a == 150
+
+    unchanged = Image.new(21, 21, Pixel.from_rgb(200, 200, 200))
+    unchanged.vignette(radius=1.0)
+    assert unchanged.pixels() == Image.new(21, 21, Pixel.from_rgb(200, 200, 200)).pixels()
+
+    for kwargs in ({'strength': 1.5}, {'strength': -0.1}, {'radius': -1.0}):
+        with pytest.raises(ValueError):
+            image.vignette(**kwargs)
+
+
+@pytest.mark.parametrize('direction, far', [(0, (7, 1)), (90, (2, 4)), (45, (7, 4))])
+def test_fill_gradient(direction: float, far: tuple) -> None:
+    start, end = Pixel.from_rgb(10, 20, 30), Pixel.from_rgb(250, 120, 30)
+    image = Image.new(10, 6, Pixel.from_rgb(0, 0, 0))
+    image.fill_gradient((2, 1, 8, 5), start, end, direction)
+
+    assert image.get_pixel(2, 1) == Rgb(10, 20, 30)
+    assert image.get_pixel(7, 4) == Rgb(250, 120, 30)
+    assert image.get_pixel(*far) == Rgb(250, 120, 30)
+    assert 10 < image.get_pixel(4, 2).r < 250
+
+    for x, y in ((1, 1), (8, 4), (2, 0), (7, 5)):
+        assert image.get_pixel(x, y) == Rgb(0, 0, 0)
+
+
+def test_fill_gradient_modes() -> None:
+    image = Image.new(4, 1, Pixel.from_l(0))
+    image.fill_gradient((0, 0, 4, 1), Pixel.from_l(0), Pixel.from_rgb(255, 255, 255), 0)
+
+    assert image.mode == 'L'
+    assert [pixel.value for pixel in image.pixels()[0]] == [0, 85, 170, 255]
+
+    with pytest.raises(ValueError):
+        image.fill_gradient((0, 0, 5, 1), Pixel.from_l(0), Pixel.from_l(255), 0)
+
+    with pytest.raises(ValueError):
+        image.fill_gradient((2, 0, 2, 1), Pixel.from_l(0), Pixel.from_l(255), 0)
+
+
+def test_match_histogram_identity() -> None:
+    image = Image.from_pixels(16, [Pixel.from_rgba(i, (i * 7) % 256, 255 - i // 2, i // 3) for i in range(256)])
+    reference = image.copy()
+    image.match_histogram(reference)
+
+    assert image.pixels() == reference.pixels()
+
+
+def test_match_histogram_brightens() -> None:
+    def mean(image: Image) -> float:
+        return sum(pixel.r + pixel.g + pixel.b for row in image.pixels() for pixel in row) / (3 * len(image))
+
+    dark = Image.from_pixels(16, [Pixel.from_rgba(i // 4, i // 5, i // 6, 100 + i // 4) for i in range(256)])
+    bright = Image.from_pixels(8, [Pixel.from_l(160 + i) for i in range(64)])
+    before = mean(dark)
+
+    dark.match_histogram(bright)
+
+    assert dark.mode == 'RGBA'
+    assert mean(dark) > before + 100
+    assert [pixel.a for row in dark.pixels() for pixel in row] == [100 + i // 4 for i in range(256)]
+
+    gray = Image.from_pixels(2, [Pixel.from_l(10), Pixel.from_l(20)])
+    gray.match_histogram(Image.new(3, 3, Pixel.from_rgb(200, 100, 50)))
+    assert gray.mode == 'RGB'
+    assert gray.get_pixel(0, 0) == Rgb(200, 100, 50)
+
+
+def test_copy_is_independent() -> None:
+    image = Image.new(4, 4, Pixel.from_rgb(0, 0, 0))
+    copied = image.copy()
+
+    copied.set_pixel(0, 0, Pixel.from_rgb(255, 0, 0))
+    assert image.get_pixel(0, 0) == Rgb(0, 0, 0)
+
+    image.invert()
+    assert copied.get_pixel(1, 1) == Rgb(0, 0, 0)
+    assert copy.copy(image).get_pixel(1, 1) == Rgb(255, 255, 255)
+
+    frame = Frame(image)
+    frame_image = frame.image
+    frame_image.mirror()
+    frame_image.set_pixel(0, 0, Pixel.from_rgb(1, 2, 3))
+    assert frame.image.get_pixel(0, 0) == Rgb(255, 255, 255)
+
+    image.set_pixel(3, 3, Pixel.from_rgb(0, 0, 0))
+    assert frame.image.get_pixel(3, 3) == Rgb(255, 255, 255)
+
+
+def test_unmutated_copies_are_cheap() -> None:
+    image = Image.new(2048, 2048, Pixel.from_rgba(0, 0, 0, 255))
+
+    start = time.perf_counter()
+    image.invert()
+    invert = time.perf_counter() - start
+
+    start = time.perf_counter()
+    copies = [image.copy() for _ in range(100)]
+    elapsed = time.perf_counter() - start
+
+    assert len(copies) == 100
+    assert elapsed < invert
+
+
+@pytest.mark.parametrize('operation, expected', [
+    (lambda image, region: image.invert(region=region), 155),
+    (lambda image, region: image.brighten(50, region=region), 150),
+    (lambda image, region: image.darken(150, region=region), 0),
+    (lambda image, region: image.contrast(2.0, region=region), 72),
+    (lambda image, region: image.gamma(0.5, region=region), 39),
+])
+def test_tone_region(operation, expected: int) -> None:
+    image = Image.new(6, 5, Pixel.from_rgba(100, 100, 100, 100))
+    operation(image, (1, 1, 4, 3))
+
+    for y, row in enumerate(image.pixels()):
+        for x, pixel in enumerate(row):
+            inside = 1 <= x < 4 and 1 <= y < 3
+            assert pixel.r == (expected if inside else 100)
+
+    with pytest.raises(ValueError):
+        operation(image, (4, 1, 2, 3))
+
+    with pytest.raises(ValueError):
+        operation(image, (0, 0, 7, 5))
+
+    whole = Image.new(2, 2, Pixel.from_rgba(100, 100, 100, 100))
+    operation(whole, None)
+    assert {pixel.r for row in whole.pixels() for pixel in row} == {expected}
+
+
+def test_tone_keeps_alpha() -> None:
+    image = Image.new(2, 2, Pixel.from_rgba(100, 100, 100, 100))
+    image.brighten(50)
+    image.gamma(2.2)
+
+    assert image.get_pixel(0, 0).a == 100
+
+
+def test_flatten() -> None:
+    image = Image.new(2, 2, Pixel.from_rgba(0, 0, 0, 128))
+    original = image.pixels()
+
+    flattened = image.flatten()
+    assert flattened.mode == 'RGB'
+    assert flattened.get_pixel(0, 0) == Rgb(127, 127, 127)
+    assert image.pixels() == original
+
+    assert image.flatten(Pixel.from_rgb(255, 0, 0)).get_pixel(0, 0) == Rgb(127, 0, 0)
+
+    opaque = noise()
+    opaque.convert('RGBA')
+    flattened = opaque.flatten(Pixel.from_rgb(255, 0, 0))
+    assert flattened.mode == 'RGB'
+    assert flattened.pixels() == noise().pixels()
+
+    gray = Image.new(2, 2, Pixel.from_rgb(10, 10, 10), mode='L')
+    assert gray.flatten().mode == 'L'
+    assert gray.flatten().pixels() == gray.pixels()
+
+
+IDENTITY = list(range(256))
+
+MUTATORS = {
+    'crop': lambda image: image.crop(0, 0, 2, 2),
+    'add_border': lambda image: image.add_border(1, Pixel.from_rgb(0, 0, 0)),
+    'draw': lambda image: image.draw(Rectangle(position=(0, 0), size=(2, 2), fill=Pixel.from_rgb(0, 0, 0))),
+    'draw_hline': lambda image: image.draw_hline(0, 0, 3, Pixel.from_rgb(0, 0, 0)),
+    'draw_vline': lambda image: image.draw_vline(0, 0, 3, Pixel.from_rgb(0, 0, 0)),
+    'resize': lambda image: image.resize(2, 2, ResizeAlgorithm.Nearest),
+    'paste': lambda image: image.paste(0, 0, Image.new(2, 2, Pixel.from_rgb(0, 0, 0))),
+    'paste_tiled': lambda image: image.paste_tiled(Image.new(2, 2, Pixel.from_rgb(0, 0, 0))),
+    'blit': lambda image: image.blit(0, 0, Image.new(2, 2, Pixel.from_rgb(0, 0, 0))),
+    'mask_alpha': lambda image: image.mask_alpha(Image.new(4, 4, Pixel.from_rgb(0, 0, 0), mode='L')),
+    'convert': lambda image: image.convert('L'),
+    'color_space': lambda image: setattr(image, 'color_space', 'linear'),
+    'to_linear': lambda image: image.to_linear(),
+    'mirror': lambda image: image.mirror(),
+    'flip': lambda image: image.flip(),
+    'rotate': lambda image: image.rotate(90),
+    'set_pixel': lambda image: image.set_pixel(0, 0, Pixel.from_rgb(0, 0, 0)),
+    '__setitem__': lambda image: image.__setitem__((0, 0), Pixel.from_rgb(0, 0, 0)),
+    'invert': lambda image: image.invert(),
+    'brighten': lambda image: image.brighten(10),
+    'darken': lambda image: image.darken(10),
+    'contrast': lambda image: image.contrast(2.0),
+    'gamma': lambda image: image.gamma(2.2),
+    'pixelate': lambda image: image.pixelate(2),
+    'mosaic': lambda image: image.mosaic(2),
+    'map_channel': lambda image: image.map_channel('r', IDENTITY),
+    'map_channels': lambda image: image.map_channels({'r': IDENTITY}),
+    'match_histogram': lambda image: image.match_histogram(Image.new(4, 4, Pixel.from_rgb(0, 0, 0))),
+    'unsharp_mask': lambda image: image.unsharp_mask(),
+    'vignette': lambda image: image.vignette(),
+    'blur': lambda image: image.blur(1.0),
+    'box_blur': lambda image: image.box_blur(1),
+    'fill_gradient': lambda image: image.fill_gradient((0, 0, 2, 2), Pixel.from_rgb(0, 0, 0), Pixel.from_rgb(255, 255, 255), 0),
+}
+
+# Methods that modify the image but need their own setup, see the tests below test_frozen_image.
+SETUP_MUTATORS = {'to_srgb', 'close', '__exit__'}
+
+NON_MUTATORS = {
+    'new', 'from_bytes', 'peek_dimensions', 'from_pixels', 'from_packed_bits', 'to_packed_bits', 'from_buffer',
+    'to_bytes', 'from_pillow', 'to_pillow', 'open', 'open_async', 'overlay_mode', 'mode', 'mode_enum', 'width',
+    'height', 'bands', 'from_bands', 'copy', 'pipeline', 'freeze', 'thaw', 'frozen', 'closed', 'view',
+    'push_transform', 'pop_transform', 'resize_region', 'encode', 'encode_into', 'encode_optimized', 'save',
+    'save_async', 'pixels', 'iter_tiles', 'converted', 'flatten', 'remap_palette', 'format', 'source_format',
+    'source_color_model', 'decode_warnings', 'dimensions', 'color_count', 'average_color', 'get_pixel',
+}
+
+# The dunder methods that could modify an image in place.
+MUTATING_DUNDERS = ('__setitem__', '__delitem__', '__exit__', '__ior__', '__iand__', '__ixor__')
+
+
+def test_mutators_cover_image() -> None:
+    names = {name for name in dir(Image) if not name.startswith('_')}
+    names |= {name for name in MUTATING_DUNDERS if hasattr(Image, name)}
+
+    # A new method has to be listed in MUTATORS, which tests that it is guarded, or in NON_MUTATORS.
+    assert names == set(MUTATORS) | SETUP_MUTATORS | NON_MUTATORS
+    assert not set(MUTATORS) & NON_MUTATORS
+
+
+@pytest.mark.parametrize('mutate', MUTATORS.values(), ids=list(MUTATORS))
+def test_frozen_image(mutate) -> None:
+    image = Image.new(4, 4, Pixel.from_rgb(100, 150, 200))
+    image.freeze()
+    assert image.frozen
+
+    with pytest.raises(RuntimeError, match='image is frozen'):
+        mutate(image)
+
+    assert image.pixels() == Image.new(4, 4, Pixel.from_rgb(100, 150, 200)).pixels()
+    assert image.color_space == 'srgb'
+
+    copied = image.copy()
+    assert not copied.frozen and not copy.copy(image).frozen
+    mutate(copied)
+
+    image.thaw()
+    assert not image.frozen
+    mutate(image)
+
+
+def test_frozen_image_reads() -> None:
+    image = noise()
+    image.freeze()
+
+    assert image.get_pixel(0, 0) == noise().get_pixel(0, 0)
+    assert image.flatten().pixels() == noise().pixels()
+    assert Image.from_bytes(image.encode('png')).pixels() == noise().pixels()
+
+
+def test_frozen_image_to_srgb() -> None:
+    image = noise()
+    image.to_linear()
+    linear = image.pixels()
+    image.freeze()
+
+    with pytest.raises(RuntimeError, match='image is frozen'):
+        image.to_srgb()
+    assert image.color_space == 'linear'
+    assert image.pixels() == linear
+
+    image.thaw()
+    image.to_srgb()
+    assert image.color_space == 'srgb'
+
+
+def test_frozen_image_close() -> None:
+    image = Image.new(4, 4, Pixel.from_rgb(100, 150, 200))
+    image.freeze()
+
+    with pytest.raises(RuntimeError, match='image is frozen'):
+        image.close()
+    with pytest.raises(RuntimeError, match='image is frozen'):
+        with image:
+            pass
+    assert not image.closed
+    assert image.pixels() == Image.new(4, 4, Pixel.from_rgb(100, 150, 200)).pixels()
+
+    image.thaw()
+    image.close()
+    assert image.closed
+
+
+READERS = [
+    lambda image: image.dimensions,
+    lambda image: image.mode,
+    lambda image: image.frozen,
+    lambda image: image.pixels(),
+    lambda image: image.pixels(raw=True),
+    lambda image: image.get_pixel(0, 0),
+    lambda image: image.copy(),
+    lambda image: image.flatten(),
+    lambda image: image.encode('png'),
+    lambda image: image.view(0, 0, 2, 2),
+    lambda image: image.pipeline(),
+    lambda image: image.to_bytes(),
+    lambda image: image.average_color(),
+    lambda image: image.converted('L'),
+    lambda image: image.remap_palette([Pixel.from_rgb(0, 0, 0)]),
+    lambda image: image.freeze(),
+    lambda image: len(image),
+    lambda image: image | Image.new(4, 4, Pixel.from_rgb(0, 0, 0)),
+]
+
+
+@pytest.mark.parametrize('operation', [*MUTATORS.values(), *READERS])
+def test_closed_image(operation) -> None:
+    image = Image.new(4, 4, Pixel.from_rgb(100, 150, 200))
+    copied = image.copy()
+
+    image.close()
+    assert image.closed
+
+    with pytest.raises(ValueError, match='operation on closed image'):
+        operation(image)
+
+    operation(copied)
+    assert not copied.closed
+
+    image.close()
+    assert repr(image) == '<Image closed>'
+
+
+def test_closed_image_arguments() -> None:
+    closed = Image.new(4, 4, Pixel.from_rgb(0, 0, 0), mode='L')
+    closed.close()
+    image = Image.new(4, 4, Pixel.from_rgb(100, 150, 200))
+
+    for operation in (
+        lambda: image.paste(0, 0, closed),
+        lambda: image.mask_alpha(closed),
+        lambda: image.match_histogram(closed),
+        lambda: Frame(closed),
+    ):
+        with pytest.raises(ValueError, match='operation on closed image'):
+            operation()
+
+    assert image.pixels() == Image.new(4, 4, Pixel.from_rgb(100, 150, 200)).pixels()
+
+
+def test_image_context_manager(tmp_path) -> None:
+    path = tmp_path / 'image.png'
+    Image.new(4, 4, Pixel.from_rgb(1, 2, 3)).save(str(path))
+
+    with Image.open(str(path)) as image:
+        assert not image.closed
+        assert image.dimensions == (4, 4)
+        view = image.view(0, 0, 2, 2)
+
+    assert image.closed
+    with pytest.raises(ValueError, match='operation on closed image'):
+        view.pixels_flat()
+
+    with pytest.raises(ValueError, match='operation on closed image'):
+        with image:
+            pass
+
+    with pytest.raises(KeyError):
+        with Image.new(2, 2, Pixel.from_rgb(0, 0, 0)) as other:
+            raise KeyError('failed')
+
+    assert other.closed
+
+
+def test_closed_sequence() -> None:
+    with ImageSequence.open(SAMPLE_GIF) as seq:
+        assert not seq.closed
+        frame = next(seq)
+        frames = len(seq)
+
+    assert seq.closed
+    assert repr(seq) == '<ImageSequence closed>'
+
+    for operation in (
+        len,
+        next,
+        list,
+        lambda seq: seq.frames,
+        lambda seq: seq.dimensions,
+        lambda seq: seq.encode('gif'),
+        lambda seq: seq.invert(),
+        lambda seq: seq.seek(0),
+        lambda seq: seq[0],
+        lambda seq: seq.remove(0),
+        lambda seq: seq.append(frame),
+    ):
+        with pytest.raises(ValueError, match='operation on closed image sequence'):
+            operation(seq)
+
+    seq.close()
+    assert frames > 1
+    assert frame.image.dimensions == ImageSequence.open(SAMPLE_GIF).dimensions
+
+
+def test_map_channel() -> None:
+    image = noise()
+    original = image.pixels()
+
+    image.map_channel('r', list(range(256)))
+    image.map_channels({'g': list(range(256)), 'b': list(range(256))})
+    assert image.pixels() == original
+
+    image.map_channel('r', [255 - v for v in range(256)])
+    for row, expected in zip(image.pixels(), original):
+        for pixel, before in zip(row, expected):
+            assert (pixel.r, pixel.g, pixel.b) == (255 - before.r, before.g, before.b)
+
+    rgba = Image.new(2, 2, Pixel.from_rgba(100, 100, 100, 100))
+    rgba.map_channels({'G': [0] * 256, 'a': [255] * 256})
+    assert rgba.get_pixel(0, 0) == Rgba(100, 0, 100, 255)
+
+    gray = Image.new(2, 2, Pixel.from_rgb(10, 10, 10), mode='L')
+    gray.map_channel('l', [v * 2 if v < 128 else 255 for v in range(256)])
+    assert gray.get_pixel(0, 0).value == 20
+
+    for channel, lut in (('a', list(range(256))), ('x', list(range(256))), ('r', list(range(255))), ('r', [256] * 256), ('r', [-1] * 256)):
+        with pytest.raises(ValueError):
+            image.map_channel(channel, lut)
+
+    with pytest.raises(ValueError):
+        gray.map_channel('r', list(range(256)))
+
+    with pytest.raises(ValueError):
+        image.map_channels({'r': list(range(256)), 'R': list(range(256))})
+
+
+def test_png_options() -> None:
+    image = noise()
+
+    fast = image.encode(options=PngOptions(compression='fast', filter='none'))
+    best = image.encode(options=PngOptions(compression='best', filter='paeth'))
+    assert fast != best
+    assert Image.from_bytes(fast).pixels() == Image.from_bytes(best).pixels() == image.pixels()
+
+    for kwargs in ({'compression': 'fastest'}, {'filter': 'diagonal'}, {'interlaced': True}):
+        with pytest.raises(ValueError):
+            PngOptions(**kwargs)
+
+
+def test_jpeg_options() -> None:
+    image = noise()
+
+    baseline = image.encode('jpeg', options=JpegOptions(quality=95))
+    progressive = image.encode(options=JpegOptions(quality=95, progressive=True))
+    assert b'\xff\xc0' in baseline and b'\xff\xc2' not in baseline
+    assert b'\xff\xc2' in progressive
+    assert len(image.encode(options=JpegOptions(quality=10))) < len(baseline)
+    assert len(image.encode(options=JpegOptions(quality=95, subsampling='4:4:4'))) > len(baseline)
+    assert Image.from_bytes(progressive).dimensions == image.dimensions
+
+    for kwargs in ({'quality': 0}, {'quality': 101}, {'subsampling': '4:1:1'}):
+        with pytest.raises(ValueError):
+            JpegOptions(**kwargs)
+
+
+def test_jpeg_options_flatten_alpha() -> None:
+    # Transparent pixels become white rather than the color hidden behind them.
+    image = Image.new(16, 16, Pixel.from_rgba(0, 0, 0, 0))
+    decoded = Image.from_bytes(image.encode('jpeg', options=JpegOptions(quality=95)))
+
+    assert all(pixel.r > 250 and pixel.g > 250 and pixel.b > 250 for row in decoded.pixels() for pixel in row)
+
+
+@pytest.mark.parametrize('subsampling, factors', [('444', 0x11), ('422', 0x21), ('420', 0x22)])
+def test_jpeg_subsampling(subsampling: str, factors: int) -> None:
+    image = Image.from_pixels(48, [Pixel.from_rgb(x * 5, y * 7, 128) for y in range(32) for x in range(48)])
+    data = image.encode('jpeg', options=JpegOptions(quality=95, subsampling=subsampling))
+
+    # The sampling factors of the luma component within the start of frame segment
+    frame = data.index(b'\xff\xc0')
+    assert data[frame + 11] == factors
+
+    decoded = Image.from_bytes(data)
+    assert decoded.dimensions == image.dimensions
+    assert decoded.source_color_model is None
+
+    errors = [
+        abs(a - b)
+        for expected, actual in zip(sum(image.pixels(), []), sum(decoded.pixels(), []))
+        for a, b in zip((expected.r, expected.g, expected.b), (actual.r, actual.g, actual.b))
+    ]
+    assert sum(errors) / len(errors) < 4
+
+
+DC_BITS = [0, 1, 5, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0]
+
+
+def four_component_jpeg(width: int, height: int, components: List[int], transform: int = 0) -> bytes:
+    """Encodes a solid color as a baseline JPEG with four components, as stored with the given Adobe transform."""
+    codes, code, index = {}, 0, 0
+    for length, count in enumerate(DC_BITS, 1):
+        for _ in range(count):
+            codes[index] = format(code, f'0{length}b')
+            code += 1
+            index += 1
+        code <<= 1
+
+    # Every block only has a DC coefficient, followed by an end of block coded as `0`.
+    bits, previous = '', [0] * 4
+    for _ in range(-(-width // 8) * -(-height // 8)):
+        for i, value in enumerate(components):
+            dc = 8 * (value - 128)
+            diff, previous[i] = dc - previous[i], dc
+            size = abs(diff).bit_length()
+            bits += codes[size]
+            if size:
+                bits += format(diff if diff > 0 else diff + (1 << size) - 1, f'0{size}b')
+            bits += '0'
+
+    bits += '1' * (-len(bits) % 8)
+    scan = bytes(int(bits[i:i + 8], 2) for i in range(0, len(bits), 8)).replace(b'\xff', b'\xff\x00')
+
+    def segment(marker: int, payload: bytes) -> bytes:
+        return bytes([0xFF, marker]) + struct.pack('>H', len(payload) + 2) + payload
+
+    return (
+        b'\xff\xd8'
+        + segment(0xEE, b'Adobe' + struct.pack('>HHHB', 100, 0, 0, transform))
+        + segment(0xDB, bytes([0] + [1] * 64))
+        + segment(0xC0, struct.pack('>BHHB', 8, height, width, 4) + b''.join(bytes([i + 1, 0x11, 0]) for i in range(4)))
+        + segment(0xC4, bytes([0x00] + DC_BITS + list(range(12)) + [0x10, 1] + [0] * 15 + [0]))
+        + segment(0xDA, bytes([4]) + b''.join(bytes([i + 1, 0]) for i in range(4)) + bytes([0, 63, 0]))
+        + scan
+        + b'\xff\xd9'
+    )
+
+
+def test_decode_cmyk_jpeg(tmp_path) -> None:
+    # Adobe software stores inverted CMYK, so this is red: no cyan, full magenta and yellow, no black.
+    data = four_component_jpeg(16, 8, [255, 0, 0, 255])
+
+    image = Image.from_bytes(data)
+    assert image.dimensions == (16, 8)
+    assert image.mode == 'RGB'
+    assert image.source_color_model == 'cmyk'
+    assert all(abs(p.r - 255) <= 2 and p.g <= 2 and p.b <= 2 for row in image.pixels() for p in row)
+
+    path = tmp_path / 'print.jpg'
+    path.write_bytes(data)
+    assert Image.open(str(path)).source_color_model == 'cmyk'
+    assert image.copy().source_color_model == 'cmyk'
+
+def test_decode_ycck_jpeg() -> None:
+    # White with 200 inverted black, which is 55 black ink.
+    image = Image.from_bytes(four_component_jpeg(9, 9, [255, 128, 128, 200], transform=2))
+
+    assert image.dimensions == (9, 9)
+    assert image.source_color_model == 'ycck'
+    assert all(abs(p.r - 200) <= 2 and abs(p.g - 200) <= 2 and abs(p.b - 200) <= 2 for row in image.pixels() for p in row)
+
+@pytest.fixture
+def sample_jpeg() -> bytes:
+    image = Image.from_pixels(64, [Pixel.from_rgb(x * 4, y * 4, (x * y) % 256) for y in range(64) for x in range(64)])
+    return image.encode('jpeg', options=JpegOptions(quality=90))
+
+def truncated(data: bytes, fraction: float) -> bytes:
+    scan = data.index(b'\xff\xda')
+    return data[:scan + int((len(data) - scan) * fraction)]
+
+def recovered_rows(image: Image) -> int:
+    match = re.search(r'decoded (\d+) of', image.decode_warnings[0])
+    assert match is not None
+
+    return int(match.group(1))
+
+@pytest.mark.parametrize('fraction', [0.2, 0.5, 0.8])
+def test_from_bytes_tolerant(sample_jpeg: bytes, fraction: float) -> None:
+    data = truncated(sample_jpeg, fraction)
+    fill = Pixel.from_rgb(255, 0, 255)
+
+    with pytest.raises(RuntimeError):
+        Image.from_bytes(data)
+
+    image = Image.from_bytes(data, tolerant=True, fill=fill)
+    assert image.dimensions == (64, 64)
+    assert image.source_format == ImageFormat.Jpeg
+    assert len(image.decode_warnings) == 1
+    assert 'of 64 rows' in image.decode_warnings[0]
+
+    rows = image.pixels()
+    decoded = recovered_rows(image)
+    assert decoded < 64
+    assert all(pixel == Rgb(255, 0, 255) for row in rows[decoded:] for pixel in row)
+    assert rows[:decoded] == Image.from_bytes(sample_jpeg).pixels()[:decoded]
+
+def test_from_bytes_tolerant_more_data_more_rows(sample_jpeg: bytes) -> None:
+    recovered = [recovered_rows(Image.from_bytes(truncated(sample_jpeg, fraction), tolerant=True)) for fraction in (0.2, 0.5, 0.8)]
+
+    assert recovered == sorted(recovered)
+    assert recovered[-1] > 0
+
+def test_from_bytes_tolerant_intact(sample_jpeg: bytes) -> None:
+    image = Image.from_bytes(sample_jpeg, tolerant=True)
+    assert image.decode_warnings == []
+    assert image.pixels() == Image.from_bytes(sample_jpeg).pixels()
+
+    missing_end = Image.from_bytes(sample_jpeg[:-2], tolerant=True)
+    assert len(missing_end.decode_warnings) == 1
+    assert missing_end.pixels() == image.pixels()
+
+    assert Image.new(2, 2, Pixel.from_rgb(0, 0, 0)).decode_warnings == []
+
+def test_from_bytes_tolerant_unrecoverable(sample_jpeg: bytes) -> None:
+    with pytest.raises(RuntimeError):
+        Image.from_bytes(sample_jpeg[:20], format='jpeg', tolerant=True)
+
+    png = Image.new(4, 4, Pixel.from_rgb(1, 2, 3)).encode('png')
+    with pytest.raises(RuntimeError):
+        Image.from_bytes(png[:len(png) // 2], tolerant=True)
+
+def test_sequence_from_bytes_tolerant(sample_jpeg: bytes) -> None:
+    data = truncated(sample_jpeg, 0.5)
+
+    with pytest.raises(RuntimeError):
+        ImageSequence.from_bytes(data)
+
+    seq = ImageSequence.from_bytes(data, tolerant=True)
+    assert len(seq) == 1
+    assert seq.dimensions == (64, 64)
+    assert seq.decode_warnings == Image.from_bytes(data, tolerant=True).decode_warnings
+
+    assert ImageSequence.from_bytes(sample_jpeg, tolerant=True).decode_warnings == []
+
+
+def test_gif_options() -> None:
+    seq = ImageSequence.generate(3, lambda i: noise(), delay=50)
+
+    assert b'NETSCAPE2.0\x03\x01\x03\x00' in seq.encode(options=GifOptions(loop_count=3))
+    assert b'NETSCAPE2.0\x03\x01\x00\x00' in seq.encode(options=GifOptions(loop_count=0))
+    assert seq.encode(options=GifOptions()) == seq.encode('gif')
+    assert seq.encode(options=GifOptions(dither=True)) != seq.encode(options=GifOptions())
+
+    data = noise().encode(options=GifOptions(quality=100))
+    assert ImageSequence.from_bytes(data).dimensions == (24, 18)
+
+    with pytest.raises(ValueError):
+        GifOptions(quality=0)
+
+def soft_circle() -> Image:
+    def alpha(x: int, y: int) -> int:
+        distance = math.hypot(x - 15.5, y - 15.5)
+        return round(min(max((14 - distance) / 6, 0), 1) * 255)
+
+    return Image.from_pixels(32, [Pixel.from_rgba(255, 0, 0, alpha(x, y)) for y in range(32) for x in range(32)])
+
+def close_to(pixel: Rgba, expected: tuple, tolerance: int = 12) -> bool:
+    return all(abs(a - b) <= tolerance for a, b in zip((pixel.r, pixel.g, pixel.b), expected))
+
+@pytest.mark.parametrize('matte', [(0, 0, 255), (255, 255, 255)])
+def test_gif_options_matte(matte: tuple) -> None:
+    circle = soft_circle()
+    options = GifOptions(matte=Pixel.from_rgb(*matte))
+
+    for data in (circle.encode(options=options), ImageSequence.from_frames([Frame(circle)]).encode(options=options)):
+        decoded = ImageSequence.from_bytes(data).frames[0].image
+
+        for x, y in ((15, 15), (15, 5), (3, 15), (0, 0)):
+            a = circle.get_pixel(x, y).a
+            pixel = decoded.get_pixel(x, y)
+
+            if a < 128:
+                assert pixel.a == 0
+            else:
+                assert pixel.a == 255
+                expected = tuple(round((c * a + m * (255 - a)) / 255) for c, m in zip((255, 0, 0), matte))
+                assert close_to(pixel, expected)
+
+    assert 128 <= circle.get_pixel(15, 5).a < 255
+    assert 0 < circle.get_pixel(3, 15).a < 128
+    assert circle.get_pixel(15, 15).a == 255
+
+def test_gif_options_alpha_threshold() -> None:
+    circle = soft_circle()
+
+    def transparent(options: GifOptions) -> int:
+        image = ImageSequence.from_bytes(circle.encode(options=options)).frames[0].image
+        return sum(pixel.a == 0 for row in image.pixels() for pixel in row)
+
+    assert transparent(GifOptions(alpha_threshold=1)) < transparent(GifOptions()) < transparent(GifOptions(alpha_threshold=255))
+    assert transparent(GifOptions(alpha_threshold=0)) == 0
+
+    plain = ImageSequence.from_bytes(circle.encode(options=GifOptions())).frames[0].image
+    assert close_to(plain.get_pixel(15, 5), (255, 0, 0))
+
+    options = GifOptions(alpha_threshold=64, matte=Pixel.from_rgba(1, 2, 3, 4))
+    assert (options.alpha_threshold, options.matte) == (64, Pixel.from_rgb(1, 2, 3))
+    assert GifOptions().matte is None
+
+
+def test_sequence_supported_encodings(tmp_path) -> None:
+    supported = ImageSequence.supported_encodings()
+
+    assert 'gif' in supported and 'png' in supported
+    assert 'jpeg' not in supported and 'webp' not in supported
+    assert ImageSequence.supported_encodings(2) == supported
+    assert ImageSequence.supported_encodings(1) == supported + ['jpeg'] + (['webp'] if WEBP else [])
+
+    seq = ImageSequence.generate(2, lambda i: noise(), delay=50)
+    for encoding in supported:
+        assert seq.encode(encoding)
+
+    for encoding in ('jpeg', 'mp4'):
+        with pytest.raises(UnsupportedFormatError, match=f'`{encoding}`') as info:
+            seq.encode(encoding)
+
+        assert ', '.join(supported) in str(info.value)
+        assert info.value.supported == supported
+
+    with pytest.raises(UnsupportedFormatError, match='`webm`'):
+        seq.save(str(tmp_path / 'clip.webm'))
+
+    with pytest.raises(UnsupportedFormatError):
+        seq.save(str(tmp_path / 'clip.gif'), encoding='bmp')
+
+    assert not (tmp_path / 'clip.webm').exists()
+    assert issubclass(UnsupportedFormatError, ValueError)
+
+def test_sequence_single_frame_encodings() -> None:
+    single = ImageSequence.generate(1, lambda i: noise(), delay=50)
+
+    for encoding in ImageSequence.supported_encodings(1):
+        assert ImageSequence.from_bytes(single.encode(encoding)).dimensions == noise().dimensions
+
+    assert single.encode(options=JpegOptions(quality=90))[:2] == b'\xff\xd8'
+
+
+def test_single_image_gif(tmp_path) -> None:
+    image = Image.new(6, 4, Pixel.from_rgb(200, 40, 90))
+
+    data = image.encode('gif')
+    assert data.startswith(b'GIF89a')
+    assert b'NETSCAPE2.0' not in data
+    assert data == image.encode(options=GifOptions())
+    assert b'NETSCAPE2.0\x03\x01\x00\x00' in image.encode(options=GifOptions(loop_count=0))
+
+    seq = ImageSequence.from_bytes(data)
+    assert len(seq) == 1
+    assert next(seq).delay == 0
+
+    decoded = Image.from_bytes(data)
+    assert decoded.dimensions == (6, 4)
+    pixel = decoded.get_pixel(3, 2)
+    assert all(abs(a - b) <= 4 for a, b in zip((pixel.r, pixel.g, pixel.b), (200, 40, 90)))
+
+    path = tmp_path / 'still.gif'
+    image.save(str(path))
+    assert path.read_bytes() == data
+    assert Image.open(str(path)).pixels() == decoded.pixels()
+
+def test_open_gif_first_frame() -> None:
+    image = Image.open(SAMPLE_GIF)
+    first = next(ImageSequence.open(SAMPLE_GIF)).image
+
+    assert image.dimensions == first.dimensions
+    assert image.pixels() == first.pixels()
+
+    with open(SAMPLE_GIF, 'rb') as f:
+        assert Image.from_bytes(f.read()).pixels() == first.pixels()
+
+
+def test_gif_reuse_palette(tmp_path) -> None:
+    with open(SAMPLE_GIF, 'rb') as f:
+        data = f.read()
+
+    seq = ImageSequence.from_bytes(data)
+    reused = seq.encode('gif', reuse_palette=True)
+    assert len(reused) <= len(seq.encode('gif'))
+
+    original = [frame.image.pixels() for frame in ImageSequence.from_bytes(data)]
+    assert [frame.image.pixels() for frame in ImageSequence.from_bytes(reused)] == original
+
+    path = tmp_path / 'reused.gif'
+    seq.save(str(path), reuse_palette=True)
+    assert path.read_bytes() == reused
+
+    seq.invert()
+    assert seq.encode('gif', reuse_palette=True) == seq.encode('gif')
+
+    frames = ImageSequence.from_frames([Frame(Image.new(4, 4, Pixel.from_rgb(i * 60, 0, 0))) for i in range(3)])
+    assert frames.encode('gif', reuse_palette=True) == frames.encode('gif')
+
+def test_gif_reuse_palette_after_setitem() -> None:
+    with open(SAMPLE_GIF, 'rb') as f:
+        data = f.read()
+
+    seq = ImageSequence.from_bytes(data)
+    frame = seq[1]
+    image = frame.image
+    image.invert()
+    frame.image = image
+    seq[1] = frame
+
+    reused = ImageSequence.from_bytes(seq.encode('gif', reuse_palette=True)).frames
+    fresh = ImageSequence.from_bytes(seq.encode('gif')).frames
+
+    assert reused[0].image.pixels() == ImageSequence.from_bytes(data)[0].image.pixels()
+    assert [f.image.pixels() for f in reused[1:]] == [f.image.pixels() for f in fresh[1:]]
+
+@pytest.mark.skipif(not WEBP, reason='requires the webp feature')
+def test_webp_options() -> None:
+    image = noise()
+
+    assert b'VP8L' in image.encode(options=WebPOptions(lossless=True))
+    assert b'VP8 ' in image.encode(options=WebPOptions(quality=50))
+
+    with pytest.raises(ValueError):
+        WebPOptions(quality=101)
+
+
+@pytest.mark.parametrize('options', [JpegOptions, GifOptions, WebPOptions])
+@pytest.mark.parametrize('quality', [0, 101])
+def test_options_quality_out_of_range(options: type, quality: int) -> None:
+    with pytest.raises(ValueError, match=str(quality)):
+        options(quality=quality)
+
+
+def test_options_format_mismatch(tmp_path) -> None:
+    image = noise()
+
+    with pytest.raises(ValueError):
+        image.encode('png', options=JpegOptions())
+
+    with pytest.raises(ValueError):
+        image.encode()
+
+    path = tmp_path / 'image'
+    image.save(path, options=PngOptions(compression='best'))
+    assert Image.from_bytes(path.read_bytes()).source_format == ImageFormat.Png
+
+    with pytest.raises(ValueError):
+        ImageSequence.from_frames([Frame(image)]).save(tmp_path / 'image.gif', options=PngOptions())
+
+
+DETERMINISTIC_SCRIPT = '''
+import sys
+from ril import Image, ImageSequence
+
+image = Image.from_buffer(int(sys.argv[1]), int(sys.argv[2]), 'RGB', bytes.fromhex(sys.argv[3]))
+seq = ImageSequence.generate(3, lambda i: image, delay=50)
+sys.stdout.write(image.encode('png', deterministic=True).hex() + ' ' + seq.encode('gif', deterministic=True).hex())
+'''
+
+
+def test_deterministic_encoding(tmp_path) -> None:
+    image = noise()
+    seq = ImageSequence.generate(3, lambda i: image, delay=50)
+
+    png = image.encode('png', deterministic=True)
+    gif = seq.encode('gif', deterministic=True)
+    assert png == image.encode('png', deterministic=True)
+    assert png == image.encode(options=PngOptions.deterministic())
+    assert gif == seq.encode('gif', deterministic=True, workers=1)
+    assert gif == seq.encode(options=GifOptions.deterministic())
+
+    args = [str(image.width), str(image.height), image.to_bytes().hex()]
+    output = subprocess.run([sys.executable, '-c', DETERMINISTIC_SCRIPT, *args], capture_output=True, check=True, text=True).stdout
+    assert output == png.hex() + ' ' + gif.hex()
+
+    image.save(tmp_path / 'image.png', deterministic=True)
+    assert (tmp_path / 'image.png').read_bytes() == png
+
+    assert PngOptions.deterministic().filter != 'adaptive'
+    assert image.encode(options=PngOptions(filter='up'), deterministic=True) == image.encode(options=PngOptions(filter='up'))
+
+    with pytest.raises(ValueError):
+        image.encode('jpeg', deterministic=True)
+
+    with pytest.raises(ValueError):
+        image.encode(options=PngOptions(), deterministic=True)
+
+
+def test_add_border() -> None:
+    image = Image.new(4, 3, Pixel.from_rgb(255, 0, 0))
+    image.add_border(2, Pixel.from_rgb(0, 0, 255))
+
+    assert image.dimensions == (8, 7)
+    for x, y in [(0, 0), (7, 0), (0, 6), (7, 6), (1, 3)]:
+        assert image.get_pixel(x, y) == Rgb(0, 0, 255)
+    for x, y in [(2, 2), (5, 2), (2, 4), (5, 4)]:
+        assert image.get_pixel(x, y) == Rgb(255, 0, 0)
+
+    image = Image.new(4, 3, Pixel.from_rgb(255, 0, 0))
+    image.add_border((1, 0, 3, 2), Pixel.from_rgb(0, 255, 0))
+
+    assert image.dimensions == (8, 5)
+    assert image.get_pixel(0, 0) == Rgb(0, 255, 0)
+    assert image.get_pixel(1, 0) == Rgb(255, 0, 0)
+    assert image.get_pixel(4, 2) == Rgb(255, 0, 0)
+    assert image.get_pixel(5, 2) == Rgb(0, 255, 0)
+    assert image.get_pixel(1, 3) == Rgb(0, 255, 0)
+
+    image.add_border(0, Pixel.from_rgb(0, 0, 0))
+    assert image.dimensions == (8, 5)
+
+    for thickness in (-1, (0, 0, -2, 0)):
+        with pytest.raises(ValueError):
+            image.add_border(thickness, Pixel.from_rgb(0, 0, 0))
+
+
+def test_frame_equality() -> None:
+    frame = Frame(Image.new(6, 4, Pixel.from_rgb(10, 20, 30)))
+    same = Frame(Image.new(6, 4, Pixel.from_rgb(10, 20, 30)))
+
+    assert frame == same
+    assert frame.diff(same) == (False, None)
+
+    same.delay = 40
+    assert frame != same
+    assert frame.diff(same) == (False, None)
+
+    image = Image.new(6, 4, Pixel.from_rgb(10, 20, 30))
+    image.set_pixel(1, 2, Pixel.from_rgb(0, 0, 0))
+    image.set_pixel(4, 1, Pixel.from_rgb(0, 0, 0))
+    changed = Frame(image)
+
+    assert frame != changed
+    assert frame.diff(changed) == (True, (1, 1, 5, 3))
+
+    resized = Frame(Image.new(4, 4, Pixel.from_rgb(10, 20, 30)))
+    assert frame != resized
+    assert frame.diff(resized) == (True, None)
+
+    assert frame != 'frame'
+    with pytest.raises(TypeError):
+        hash(frame)
+
+
+def test_color_count() -> None:
+    assert Image.new(4, 4, Pixel.from_rgb(1, 2, 3)).color_count() == 1
+    assert gradient().color_count() == 64
+    assert gradient().color_count(max_count=256) == 64
+    assert gradient().color_count(max_count=10) == 11
+    assert gradient().color_count(max_count=64) == 64
+
+    # Pixels are compared as RGBA, so differing alpha makes a distinct color.
+    image = Image.from_pixels(2, [Pixel.from_rgba(0, 0, 0, 0), Pixel.from_rgba(0, 0, 0, 255)])
+    assert image.color_count() == 2
+
+
+# A 1x1 GIF whose global color table holds red and green.
+PALETTE_GIF = b'GIF89a\x01\x00\x01\x00\x80\x00\x00\xff\x00\x00\x00\xff\x00,\x00\x00\x00\x00\x01\x00\x01\x00\x00\x02\x02D\x01\x00;'
+
+
+def test_sequence_palette() -> None:
+    seq = ImageSequence.from_bytes(PALETTE_GIF)
+    assert seq.palette() == [Pixel.from_rgb(255, 0, 0), Pixel.from_rgb(0, 255, 0)]
+
+    # Encoded GIFs only have local color tables.
+    assert make_gif().palette() is None
+    assert ImageSequence.from_frames(make_frames()).palette() is None
+
+
+def test_sequence_subsequence() -> None:
+    seq = ImageSequence.generate(10, fade, delay=40)
+    frames = seq.frames
+
+    assert len(seq.subsequence(2, 5)) == 3
+    assert len(seq.subsequence(0, step=3)) == 4
+    assert len(seq.subsequence(-3)) == 3
+    assert len(seq.subsequence(5, 100)) == 5
+    assert len(seq.subsequence(-100, 2)) == 2
+    assert len(seq.subsequence(8, 2)) == 0
+
+    assert seq[2:8:2].frames == seq.subsequence(2, 8, 2).frames == frames[2:8:2]
+    assert seq[::-1].frames == frames[::-1]
+    assert seq[-1] == frames[-1]
+
+    with pytest.raises(IndexError):
+        seq[10]
+
+    with pytest.raises(ValueError):
+        seq.subsequence(0, step=0)
+
+
+def test_sequence_trim() -> None:
+    seq = ImageSequence.generate(10, fade, delay=40)
+
+    assert seq.trim(0, seq.total_duration).frames == seq.frames
+    assert seq.trim(-100, 1000).frames == seq.frames
+
+    trimmed = seq.trim(50, 130)
+    assert [frame.delay for frame in trimmed] == [30, 40, 10]
+    assert trimmed.frames[0].image.get_pixel(0, 0) == seq.frames[1].image.get_pixel(0, 0)
+    assert trimmed.total_duration == 80
+
+    for start, stop in ((200, 200), (300, 100), (500, 600)):
+        with pytest.raises(ValueError):
+            seq.trim(start, stop)
@@ -0,0 +1,178 @@
+import struct
+import zlib
+
+import pytest
+
+from ril import Image, ImageSequence, Pixel, ResizeAlgorithm
+
+TEXT = b'Comment\x00secret location'
+EXIF = b'Exif\x00\x00MM\x00\x2a\x00\x00\x00\x08GPS'
+
+
+def png_chunk(kind: bytes, data: bytes) -> bytes:
+    return struct.pack('>I', len(data)) + kind + data + struct.pack('>I', zlib.crc32(kind + data))
+
+
+def tiff_exif(width: int, height: int, orientation: int) -> bytes:
+    def ifd(entries, next_ifd: int) -> bytes:
+        body = b''.join(
+            struct.pack('>HHI', tag, kind, 1) + (struct.pack('>H2x', value) if kind == 3 else struct.pack('>I', value))
+            for tag, kind, value in entries
+        )
+        return struct.pack('>H', len(entries)) + body + struct.pack('>I', next_ifd)
+
+    # IFD0 at 8 (4 entries), the Exif IFD at 62 (2 entries) and the thumbnail IFD at 92 (1 entry)
+    return (
+        b'MM\x00\x2a' + struct.pack('>I', 8)
+        + ifd([(0x0100, 3, width), (0x0101, 4, height), (0x0112, 3, orientation), (0x8769, 4, 62)], 92)
+        + ifd([(0xA002, 3, width), (0xA003, 4, height)], 0)
+        + ifd([(0x0201, 4, 0)], 0)
+    )
+
+
+def read_exif(data: bytes) -> dict:
+    def entries(offset: int) -> dict:
+        count, = struct.unpack_from('>H', data, offset)
+        tags = {}
+        for i in range(count):
+            tag, kind, _ = struct.unpack_from('>HHI', data, offset + 2 + 12 * i)
+            tags[tag], = struct.unpack_from('>H' if kind == 3 else '>I', data, offset + 10 + 12 * i)
+        tags['next'], = struct.unpack_from('>I', data, offset + 2 + 12 * count)
+        return tags
+
+    tags = entries(struct.unpack_from('>I', data, 4)[0])
+    exif = entries(tags[0x8769])
+    del exif['next']
+    return {**tags, **exif}
+
+
+def with_png_exif(data: bytes, exif: bytes) -> bytes:
+    return data[:33] + png_chunk(b'eXIf', exif) + data[33:]
+
+
+def png_exif(data: bytes) -> bytes:
+    offset = data.index(b'eXIf')
+    length, = struct.unpack_from('>I', data, offset - 4)
+    return data[offset + 4:offset + 4 + length]
+
+
+def with_png_metadata(data: bytes) -> bytes:
+    # Signature (8) and IHDR (25)
+    return data[:33] + png_chunk(b'tEXt', TEXT) + png_chunk(b'eXIf', EXIF[6:]) + data[33:]
+
+
+def with_jpeg_metadata(data: bytes) -> bytes:
+    app1 = b'\xff\xe1' + struct.pack('>H', len(EXIF) + 2) + EXIF
+    com = b'\xff\xfe' + struct.pack('>H', len(TEXT) + 2) + TEXT
+
+    return data[:2] + app1 + com + data[2:]
+
+
+def with_gif_metadata(data: bytes) -> bytes:
+    flags = data[10]
+    offset = 13 + (3 * (1 << ((flags & 0x07) + 1)) if flags & 0x80 else 0)
+
+    return data[:offset] + b'\x21\xfe' + bytes([len(TEXT)]) + TEXT + b'\x00' + data[offset:]
+
+
+@pytest.fixture
+def image() -> Image:
+    return Image.new(4, 4, Pixel.from_rgb(255, 0, 0))
+
+
+def test_png_metadata(image: Image) -> None:
+    image = Image.from_bytes(with_png_metadata(image.encode('png')))
+
+    stripped = image.encode('png')
+    assert TEXT not in stripped
+    assert b'eXIf' not in stripped
+
+    kept = image.encode('png', strip_metadata=False)
+    assert TEXT in kept
+    assert b'eXIf' in kept
+    assert Image.from_bytes(kept).get_pixel(0, 0) == image.get_pixel(0, 0)
+
+
+def test_jpeg_metadata(image: Image) -> None:
+    image = Image.from_bytes(with_jpeg_metadata(image.encode('jpeg')))
+
+    stripped = image.encode('jpeg')
+    assert EXIF not in stripped
+    assert TEXT not in stripped
+
+    kept = image.encode('jpeg', strip_metadata=False)
+    assert EXIF in kept
+    assert TEXT in kept
+    assert Image.from_bytes(kept).dimensions == (4, 4)
+
+
+def test_metadata_not_written_across_formats(image: Image) -> None:
+    image = Image.from_bytes(with_png_metadata(image.encode('png')))
+
+    assert EXIF[6:] not in image.encode('jpeg', strip_metadata=False)
+
+
+def test_save_metadata(image: Image, tmp_path) -> None:
+    image = Image.from_bytes(with_png_metadata(image.encode('png')))
+
+    image.save(tmp_path / 'stripped.png')
+    image.save(tmp_path / 'kept.png', strip_metadata=False)
+
+    assert TEXT not in (tmp_path / 'stripped.png').read_bytes()
+    assert TEXT in (tmp_path / 'kept.png').read_bytes()
+    assert TEXT in Image.open(tmp_path / 'kept.png').encode('png', strip_metadata=False)
+
+
+def test_sequence_metadata(fetch_file) -> None:
+    seq = ImageSequence.from_bytes(with_gif_metadata(fetch_file('sample_rgba.gif')))
+
+    assert TEXT not in seq.encode('gif')
+
+    kept = seq.encode('gif', strip_metadata=False)
+    assert TEXT in kept
+    assert len(ImageSequence.from_bytes(kept)) == len(seq)
+
+
+def test_exif_dimensions_after_resize(image: Image) -> None:
+    image = Image.from_bytes(with_png_exif(image.encode('png'), tiff_exif(4, 4, 6)))
+    image.resize(2, 3, ResizeAlgorithm.Nearest)
+
+    kept = image.encode('png', strip_metadata=False)
+    tags = read_exif(png_exif(kept))
+    assert (tags[0x0100], tags[0x0101], tags[0xA002], tags[0xA003]) == (2, 3, 2, 3)
+    # Resizing keeps the orientation, but unlinks the thumbnail.
+    assert tags[0x0112] == 6
+    assert tags['next'] == 0
+    # The chunk is written with a valid CRC.
+    assert Image.from_bytes(kept).dimensions == (2, 3)
+
+
+def test_exif_dimensions_after_crop(image: Image) -> None:
+    exif = b'Exif\x00\x00' + tiff_exif(4, 4, 3)
+    app1 = b'\xff\xe1' + struct.pack('>H', len(exif) + 2) + exif
+    data = image.encode('jpeg')
+    image = Image.from_bytes(data[:2] + app1 + data[2:])
+    image.crop(1, 0, 4, 2)
+
+    kept = image.encode('jpeg', strip_metadata=False)
+    tags = read_exif(kept[kept.index(b'Exif\x00\x00') + 6:])
+    assert (tags[0x0100], tags[0x0101], tags[0xA002], tags[0xA003]) == (3, 2, 3, 2)
+    assert tags[0x0112] == 3
+
+
+@pytest.mark.parametrize('transform', [lambda image: image.rotate(90), Image.mirror, Image.flip])
+def test_exif_orientation_reset(image: Image, transform) -> None:
+    image = Image.from_bytes(with_png_exif(Image.new(4, 2, Pixel.from_rgb(0, 0, 0)).encode('png'), tiff_exif(4, 2, 6)))
+    transform(image)
+
+    tags = read_exif(png_exif(image.encode('png', strip_metadata=False)))
+    assert tags[0x0112] == 1
+    assert (tags[0x0100], tags[0x0101]) == image.dimensions
+
+
+def test_exif_dimensions_widened(image: Image) -> None:
+    image = Image.from_bytes(with_png_exif(image.encode('png'), tiff_exif(4, 4, 1)))
+    image.resize(70000, 1, ResizeAlgorithm.Nearest)
+
+    tags = read_exif(png_exif(image.encode('png', strip_metadata=False)))
+    assert (tags[0x0100], tags[0xA002]) == (70000, 70000)
@@ -0,0 +1,83 @@
+import inspect
+import re
+
+import pytest
+
+import ril
+from ril import HorizontalAnchor, Image, OverlayMode, Pixel, Rectangle, Rgba, TextLayout, VerticalAnchor, WrapStyle
+
+REFERENCE = re.compile(r':class:`\.?(\w+)`')
+
+
+def docstrings():
+    yield ril.__doc__ or ''
+
+    for name in ril.__all__:
+        obj = getattr(ril, name)
+        yield getattr(obj, '__doc__', None) or ''
+
+        if inspect.isclass(obj):
+            for attr in vars(obj).values():
+                yield getattr(attr, '__doc__', None) or ''
+
+
+def test_all_is_complete() -> None:
+    public = sorted(name for name in dir(ril) if not name.startswith('_'))
+
+    assert sorted(ril.__all__) == public
+    assert len(set(ril.__all__)) == len(ril.__all__)
+
+
+def test_referenced_names_are_exported() -> None:
+    referenced = {name for doc in docstrings() for name in REFERENCE.findall(doc)}
+
+    assert {'OverlayMode', 'WrapStyle', 'HorizontalAnchor', 'VerticalAnchor'} <= referenced
+    assert sorted(referenced - set(ril.__all__)) == []
+
+    for name in referenced:
+        exec(f'from ril import {name}', {})
+
+
+@pytest.mark.parametrize('enum, variants', [
+    (OverlayMode, ['Replace', 'Merge']),
+    (WrapStyle, ['NoWrap', 'Word', 'Character']),
+    (HorizontalAnchor, ['Left', 'Center', 'Right']),
+    (VerticalAnchor, ['Top', 'Center', 'Bottom']),
+])
+def test_enum_variants(enum, variants: list) -> None:
+    for variant in variants:
+        assert isinstance(getattr(enum, variant), enum)
+
+
+@pytest.mark.parametrize('overlay, expected', [
+    (OverlayMode.Replace, Rgba(0, 0, 255, 100)),
+    (OverlayMode.Merge, None),
+])
+def test_overlay_mode_argument(overlay: OverlayMode, expected) -> None:
+    image = Image.new(4, 4, Pixel.from_rgba(255, 0, 0, 255))
+    rectangle = Rectangle(position=(0, 0), size=(4, 4), fill=Pixel.from_rgba(0, 0, 255, 100), overlay=overlay)
+    image.draw(rectangle)
+
+    assert rectangle.overlay == overlay
+    if expected is None:
+        assert image.get_pixel(1, 1) not in (Rgba(0, 0, 255, 100), Rgba(255, 0, 0, 255))
+    else:
+        assert image.get_pixel(1, 1) == expected
+
+
+def test_text_enum_arguments() -> None:
+    layout = TextLayout(
+        position=(0, 0),
+        width=100,
+        horizontal_anchor=HorizontalAnchor.Center,
+        vertical_anchor=VerticalAnchor.Center,
+        wrap=WrapStyle.Word,
+    )
+
+    assert layout.anchor == (HorizontalAnchor.Center, VerticalAnchor.Center)
+
+    layout.wrap = WrapStyle.Character
+    layout.horizontal_anchor = HorizontalAnchor.Right
+    layout.vertical_anchor = VerticalAnchor.Bottom
+
+    assert layout.anchor == (HorizontalAnchor.Right, VerticalAnchor.Bottom)
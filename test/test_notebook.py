@@ -0,0 +1,27 @@
+import ril
+from ril import Frame, Image, ImageSequence, Pixel
+
+PNG_MAGIC = b'\x89PNG\r\n\x1a\n'
+
+
+def test_image_repr_png() -> None:
+    image = Image.new(4, 4, Pixel.from_rgb(255, 0, 0))
+
+    assert image._repr_png_().startswith(PNG_MAGIC)
+
+def test_image_repr_png_downscales() -> None:
+    ril.set_notebook_preview_size(32)
+
+    try:
+        preview = Image.from_bytes(Image.new(256, 128, Pixel.from_rgb(0, 0, 0))._repr_png_())
+    finally:
+        ril.set_notebook_preview_size(512)
+
+    assert preview.dimensions == (32, 16)
+
+def test_sequence_repr_html() -> None:
+    frames = [Frame(Image.new(4, 4, Pixel.from_rgba(i * 60, 0, 0, 255))) for i in range(3)]
+    html = ImageSequence.from_frames(frames)._repr_html_()
+
+    assert html.startswith('<img src="data:image/gif;base64,')
+    assert html.endswith('" />')
@@ -0,0 +1,103 @@
+import os
+import time
+from concurrent.futures import ThreadPoolExecutor
+
+import pytest
+
+import ril
+from helpers import gradient
+from ril import Frame, Image, ImageSequence, Pixel
+
+
+def noise_sequence(frames: int, size: int) -> ImageSequence:
+    return ImageSequence.from_frames([
+        Frame(Image.from_pixels(
+            size,
+            [Pixel.from_rgb((x * 7 + i * 31) % 256, (y * 13 + i * 17) % 256, (x * y + i) % 256) for y in range(size) for x in range(size)],
+        ))
+        for i in range(frames)
+    ])
+
+def invert_with_threshold(image: Image, threshold: int) -> bytes:
+    ril.set_parallel_threshold(threshold)
+    image.invert()
+    image.mask_alpha(Image.new(image.width, image.height, Pixel.from_l(200)))
+
+    return image.encode('png')
+
+def test_parallel_matches_serial() -> None:
+    serial = invert_with_threshold(gradient(300, 200, 'RGBA'), 2 ** 62)
+    parallel = invert_with_threshold(gradient(300, 200, 'RGBA'), 0)
+
+    assert serial == parallel
+
+def test_parallel_paste_matches_serial() -> None:
+    pasted = []
+    for threshold in (2 ** 62, 0):
+        ril.set_parallel_threshold(threshold)
+        image = gradient(300, 200)
+        image.paste(20, 10, gradient(120, 90))
+        pasted.append(image.encode('png'))
+
+    assert pasted[0] == pasted[1]
+
+def test_mask_alpha_dimensions() -> None:
+    image = gradient(4, 4)
+
+    for width, height in ((3, 4), (4, 5)):
+        with pytest.raises(ValueError):
+            image.mask_alpha(Image.new(width, height, Pixel.from_l(200)))
+
+@pytest.mark.skipif(not ril.PARALLEL or (os.cpu_count() or 1) < 2, reason='requires the parallel feature and multiple cores')
+def test_parallel_is_faster() -> None:
+    image = Image.new(4000, 4000, Pixel.from_rgb(10, 20, 30))
+
+    ril.set_parallel_threshold(2 ** 62)
+    start = time.perf_counter()
+    image.invert()
+    serial = time.perf_counter() - start
+
+    ril.set_parallel_threshold(0)
+    start = time.perf_counter()
+    image.invert()
+    parallel = time.perf_counter() - start
+
+    assert parallel < serial
+
+def test_parallel_gif_matches_serial() -> None:
+    seq = noise_sequence(6, 32)
+    serial = seq.encode('gif', workers=1)
+
+    for workers in (2, 4, None):
+        assert seq.encode('gif', workers=workers) == serial
+
+    assert len(ImageSequence.from_bytes(serial)) == 6
+
+    with pytest.raises(ValueError):
+        seq.encode('gif', workers=0)
+
+def test_parallel_gif_matches_ril_encoder() -> None:
+    # A single worker leaves encoding to ril, which the frames quantized in parallel must match byte for byte.
+    seq = noise_sequence(24, 128)
+    serial = seq.encode('gif', workers=1)
+
+    for _ in range(2):
+        assert seq.encode('gif', workers=3) == serial
+        assert seq.encode('gif') == serial
+
+@pytest.mark.skipif((os.cpu_count() or 1) < 2, reason='requires multiple cores')
+def test_threads_decode_concurrently() -> None:
+    data = Image.new(2000, 2000, Pixel.from_rgb(10, 20, 30)).encode('png')
+
+    start = time.perf_counter()
+    for _ in range(4):
+        Image.from_bytes(data)
+    serial = time.perf_counter() - start
+
+    with ThreadPoolExecutor(max_workers=2) as executor:
+        start = time.perf_counter()
+        images = list(executor.map(Image.from_bytes, [data] * 4))
+        concurrent = time.perf_counter() - start
+
+    assert all(image.dimensions == (2000, 2000) for image in images)
+    assert concurrent < serial
@@ -0,0 +1,175 @@
+import random
+from typing import List
+
+import pytest
+
+import ril
+from helpers import noise
+from ril import Image, Mode, ResizeAlgorithm
+
+ALGORITHMS = [
+    ResizeAlgorithm.Nearest,
+    ResizeAlgorithm.Box,
+    ResizeAlgorithm.Bilinear,
+    ResizeAlgorithm.Hamming,
+    ResizeAlgorithm.Bicubic,
+    ResizeAlgorithm.Mitchell,
+    ResizeAlgorithm.Lanczos3,
+]
+
+
+def rotated(image: Image, degrees: int) -> Image:
+    for _ in range(degrees // 90 % 4):
+        rows = image.pixels()
+        height = len(rows)
+        image = Image.from_pixels(height, [rows[height - 1 - x][y] for y in range(image.width) for x in range(height)])
+
+    return image
+
+def random_ops(rng: random.Random, dimensions: tuple) -> list:
+    width, height = dimensions
+    ops = []
+
+    for _ in range(rng.randrange(1, 7)):
+        kind = rng.choice(['resize', 'crop', 'rotate', 'convert', 'flip', 'mirror'])
+
+        if kind == 'resize':
+            factor = rng.choice([1, 2, 3])
+            if width % factor == 0 and height % factor == 0 and rng.random() < 0.7:
+                width, height = width // factor, height // factor
+            else:
+                width, height = rng.randrange(1, 30), rng.randrange(1, 30)
+            ops.append(('resize', width, height, rng.choice(ALGORITHMS)))
+        elif kind == 'crop':
+            x1, y1 = rng.randrange(width), rng.randrange(height)
+            x2, y2 = rng.randrange(x1 + 1, width + 1), rng.randrange(y1 + 1, height + 1)
+            width, height = x2 - x1, y2 - y1
+            ops.append(('crop', x1, y1, x2, y2))
+        elif kind == 'rotate':
+            degrees = rng.choice([-90, 90, 180, 270, 360])
+            if degrees % 180:
+                width, height = height, width
+            ops.append(('rotate', degrees))
+        elif kind == 'convert':
+            ops.append(('convert', rng.choice([Mode.L, Mode.Rgb, Mode.Rgba])))
+        else:
+            ops.append((kind,))
+
+    return ops
+
+def eager(image: Image, ops: list) -> Image:
+    image = image.copy()
+
+    for name, *args in ops:
+        if name == 'rotate':
+            image = rotated(image, *args)
+        else:
+            getattr(image, name)(*args)
+
+    return image
+
+def lazy(image: Image, ops: list) -> ril.Pipeline:
+    pipeline = image.pipeline()
+
+    for name, *args in ops:
+        getattr(pipeline, name)(*args)
+
+    return pipeline
+
+
+@pytest.mark.parametrize('seed', range(40))
+def test_pipeline_matches_eager(seed: int) -> None:
+    rng = random.Random(seed)
+    image = noise(seed=seed, alpha=True)
+    ops = random_ops(rng, image.dimensions)
+
+    pipeline = lazy(image, ops)
+    expected = eager(image, ops)
+    result = pipeline.execute()
+
+    assert pipeline.dimensions == expected.dimensions
+    assert result.mode == expected.mode
+    assert result.pixels() == expected.pixels()
+
+@pytest.mark.parametrize('algorithm', ALGORITHMS)
+def test_pipeline_crop_before_resize(events: List[dict], algorithm: ResizeAlgorithm) -> None:
+    image = noise(64, 64, alpha=True)
+    expected = eager(image, [('resize', 16, 16, algorithm), ('crop', 6, 6, 10, 10)])
+
+    events.clear()
+    result = image.pipeline().resize(16, 16, algorithm).crop(6, 6, 10, 10).execute()
+
+    resizes = [event['dimensions'] for event in events if event['operation'] == 'resize']
+    assert len(resizes) == 2
+    assert resizes[0][0] < 64 and resizes[0][1] < 64
+    assert resizes[1][0] < 16 and resizes[1][1] < 16
+
+    assert result.pixels() == expected.pixels()
+
+def test_pipeline_fusion_is_exact() -> None:
+    image = noise(48, 36, alpha=True)
+
+    for algorithm in ALGORITHMS:
+        for x1, y1, x2, y2 in [(0, 0, 3, 3), (20, 14, 24, 18), (5, 2, 19, 16), (0, 0, 24, 18)]:
+            ops = [('resize', 24, 18, algorithm), ('crop', x1, y1, x2, y2)]
+            assert lazy(image, ops).execute().pixels() == eager(image, ops).pixels()
+
+def test_pipeline_cancelling_transforms() -> None:
+    image = noise(alpha=True)
+    pipeline = image.pipeline().flip().flip().mirror().rotate(90).rotate(-90).mirror().rotate(180).rotate(180)
+
+    assert pipeline.execute().pixels() == image.pixels()
+    assert len(pipeline) == 8
+
+def test_pipeline_ops() -> None:
+    pipeline = (
+        noise(alpha=True)
+        .pipeline()
+        .resize(12, 9, ResizeAlgorithm.Bilinear)
+        .crop(1, 2, 7, 6)
+        .rotate(-90)
+        .rotate(360)
+        .convert('L')
+        .flip()
+        .mirror()
+    )
+
+    assert pipeline.ops == [
+        ('resize', 12, 9, ResizeAlgorithm.Bilinear),
+        ('crop', 1, 2, 7, 6),
+        ('rotate', 270),
+        ('convert', Mode.L),
+        ('flip',),
+        ('mirror',),
+    ]
+    assert pipeline.dimensions == (4, 6)
+    assert repr(pipeline) == '<Pipeline ops=6 dimensions=(4, 6)>'
+
+def test_pipeline_leaves_source_untouched() -> None:
+    image = noise(alpha=True)
+    original = image.pixels()
+    pipeline = image.pipeline().crop(0, 0, 4, 4).convert('L')
+
+    image.invert()
+    result = pipeline.execute()
+
+    assert image.pixels() != original
+    assert result.pixels() == eager(Image.from_pixels(24, [p for row in original for p in row]), pipeline.ops).pixels()
+    assert pipeline.execute().pixels() == result.pixels()
+
+def test_pipeline_errors() -> None:
+    pipeline = noise(alpha=True).pipeline().resize(10, 10, ResizeAlgorithm.Nearest)
+
+    with pytest.raises(ValueError):
+        pipeline.crop(0, 0, 11, 5)
+
+    with pytest.raises(ValueError):
+        pipeline.crop(5, 5, 5, 8)
+
+    with pytest.raises(ValueError):
+        pipeline.resize(0, 4, ResizeAlgorithm.Nearest)
+
+    with pytest.raises(ValueError, match='multiple of 90'):
+        pipeline.rotate(45)
+
+    assert len(pipeline) == 1
@@ -0,0 +1,247 @@
+import inspect
+
+import pytest
+
+import ril
+
+CLASSES = [
+    ril.Border,
+    ril.Ellipse,
+    ril.Font,
+    ril.Frame,
+    ril.GifOptions,
+    ril.Image,
+    ril.ImageSequence,
+    ril.ImageSequenceIterator,
+    ril.ImageView,
+    ril.JpegOptions,
+    ril.Line,
+    ril.Pipeline,
+    ril.PngOptions,
+    ril.Polygon,
+    ril.Rectangle,
+    ril.Scene,
+    ril.Shadow,
+    ril.StrokeStyle,
+    ril.TextLayout,
+    ril.TextSegment,
+    ril.TileIterator,
+    ril.WebPOptions,
+]
+CONSTRUCTABLE = [
+    ril.Border,
+    ril.Ellipse,
+    ril.Frame,
+    ril.GifOptions,
+    ril.JpegOptions,
+    ril.Line,
+    ril.PngOptions,
+    ril.Polygon,
+    ril.Rectangle,
+    ril.Scene,
+    ril.Shadow,
+    ril.TextLayout,
+    ril.TextSegment,
+    ril.WebPOptions,
+]
+FUNCTIONS = [ril.contrast_ratio, ril.nearest_color, ril.pack_atlas, ril.set_notebook_preview_size, ril.set_parallel_threshold, ril.set_trace_hook, ril.thumbnail]
+
+GOLDEN = {
+    'Border': '(*, color, thickness, position, style=None)',
+    'Border.copy': '()',
+    'Ellipse': '(*, position, radii, inner_radii=None, border=None, fill=None, overlay=None, inner_shadow=None, outer_glow=None)',
+    'Ellipse.circle': '(x, y, radius)',
+    'Ellipse.copy': '()',
+    'Ellipse.from_bounding_box': '(x1, y1, x2, y2)',
+    'Font.cache_stats': '()',
+    'Font.from_bytes': '(bytes, optimal_size)',
+    'Font.missing_glyphs': '(text)',
+    'Font.open': '(path, optimal_size)',
+    'Font.prewarm': '(text, size)',
+    'Frame': '(image)',
+    'Frame.diff': '(other)',
+    'GifOptions': '(quality=70, dither=False, loop_count=None, alpha_threshold=128, matte=None)',
+    'GifOptions.deterministic': '()',
+    'Image.add_border': '(thickness, color)',
+    'Image.average_color': '(region=None)',
+    'Image.bands': '()',
+    'Image.blit': '(x, y, tile)',
+    'Image.blur': '(sigma)',
+    'Image.box_blur': '(radius)',
+    'Image.brighten': '(amount, region=None)',
+    'Image.close': '()',
+    'Image.color_count': '(max_count=None)',
+    'Image.contrast': '(factor, region=None)',
+    'Image.convert': '(mode, dither=False, levels=None)',
+    'Image.converted': '(mode, dither=False, levels=None)',
+    'Image.copy': '()',
+    'Image.crop': '(x1, y1, x2, y2)',
+    'Image.darken': '(amount, region=None)',
+    'Image.draw': '(entity, strict_mode=False)',
+    'Image.draw_hline': '(y, x1, x2, color, thickness=1)',
+    'Image.draw_vline': '(x, y1, y2, color, thickness=1)',
+    'Image.encode': '(encoding=None, strip_metadata=True, options=None, deterministic=False)',
+    'Image.encode_into': '(encoding, buffer, strip_metadata=True)',
+    'Image.encode_optimized': '(format, target_kb=None)',
+    'Image.fill_gradient': '(region, start, end, direction)',
+    'Image.flatten': '(background=None)',
+    'Image.flip': '()',
+    'Image.freeze': '()',
+    'Image.from_bands': '(*bands)',
+    'Image.from_buffer': '(width, height, mode, data)',
+    'Image.from_bytes': '(bytes, format=None, *, tolerant=False, fill=None)',
+    'Image.from_packed_bits': '(width, height, data, row_align=1)',
+    'Image.from_pillow': '(image)',
+    'Image.from_pixels': '(width, pixels)',
+    'Image.gamma': '(gamma, region=None)',
+    'Image.get_pixel': '(x, y)',
+    'Image.invert': '(region=None)',
+    'Image.iter_tiles': '(tile_w, tile_h, overlap=0, pad=False, fill=None)',
+    'Image.map_channel': '(channel, lut)',
+    'Image.map_channels': '(luts)',
+    'Image.mask_alpha': '(mask, crop_mask=False)',
+    'Image.match_histogram': '(reference)',
+    'Image.mirror': '()',
+    'Image.mosaic': '(block_size)',
+    'Image.new': '(width, height, fill, mode=None)',
+    'Image.open': '(path)',
+    'Image.open_async': '(path)',
+    'Image.paste': '(x, y, image, mask=None, strict_mode=False, match_color_space=False, crop_mask=False)',
+    'Image.paste_tiled': '(image, region=None, offset=(0, 0))',
+    'Image.peek_dimensions': '(bytes)',
+    'Image.pipeline': '()',
+    'Image.pixelate': '(block_size, region=None)',
+    'Image.pixels': '(raw=False)',
+    'Image.pop_transform': '()',
+    'Image.push_transform': '(translate=(0, 0), scale=1.0)',
+    'Image.remap_palette': '(palette, dither=False)',
+    'Image.resize': '(width, height, algorithm, linear=False, preserve_aspect=False, exact=True, tolerance=0.01)',
+    'Image.resize_region': '(src_box, width, height, algorithm)',
+    'Image.rotate': '(degrees, algorithm=None, fill=None)',
+    'Image.save': '(path, encoding=None, strip_metadata=True, options=None, deterministic=False)',
+    'Image.save_async': '(path, encoding=None, strip_metadata=True, options=None, deterministic=False)',
+    'Image.set_pixel': '(x, y, pixel, strict=False)',
+    'Image.thaw': '()',
+    'Image.to_bytes': '()',
+    'Image.to_linear': '()',
+    'Image.to_packed_bits': '(row_align=1)',
+    'Image.to_pillow': '()',
+    'Image.to_srgb': '()',
+    'Image.unsharp_mask': '(radius=2.0, amount=0.5, threshold=0)',
+    'Image.view': '(x1, y1, x2, y2)',
+    'Image.vignette': '(strength=0.5, radius=0.75)',
+    'ImageSequence.append': '(frame)',
+    'ImageSequence.close': '()',
+    'ImageSequence.convert': '(mode)',
+    'ImageSequence.crop': '(x1, y1, x2, y2)',
+    'ImageSequence.deduplicate': '(merge_delays=True, tolerance=0)',
+    'ImageSequence.encode': '(encoding=None, strip_metadata=True, workers=None, options=None, deterministic=False, reuse_palette=False)',
+    'ImageSequence.flip': '()',
+    'ImageSequence.from_bytes': '(bytes, format=None, *, tolerant=False, fill=None)',
+    'ImageSequence.from_frames': '(frames, fix_disposal=False, warn=False)',
+    'ImageSequence.from_numpy': '(array, delay=100)',
+    'ImageSequence.from_spritesheet': '(image, frame_width, frame_height, count=None, delay=100)',
+    'ImageSequence.generate': '(frame_count, func, delay=100)',
+    'ImageSequence.insert': '(index, frame)',
+    'ImageSequence.invert': '()',
+    'ImageSequence.mirror': '()',
+    'ImageSequence.open': '(path)',
+    'ImageSequence.open_async': '(path)',
+    'ImageSequence.overlay': '(other, x, y, *, loop_shorter=True)',
+    'ImageSequence.palette': '()',
+    'ImageSequence.peek_frame_count': '(bytes)',
+    'ImageSequence.remove': '(index)',
+    'ImageSequence.resize': '(width, height, algorithm)',
+    'ImageSequence.save': '(path, encoding=None, strip_metadata=True, options=None, deterministic=False, reuse_palette=False)',
+    'ImageSequence.save_async': '(path, encoding=None, strip_metadata=True, options=None, deterministic=False, reuse_palette=False)',
+    'ImageSequence.seek': '(index)',
+    'ImageSequence.subsequence': '(start, stop=None, step=1)',
+    'ImageSequence.supported_encodings': '(frames=None)',
+    'ImageSequence.tell': '()',
+    'ImageSequence.to_numpy': "(mode='RGBA')",
+    'ImageSequence.to_spritesheet': '(columns=1)',
+    'ImageSequence.trim': '(start_ms, stop_ms)',
+    'ImageSequence.typewriter': '(background, font, text, *, position, fill, chars_per_frame=1, delay=50, size=None, width=None, wrap=None)',
+    'ImageView.get_pixel': '(x, y)',
+    'ImageView.pixels_flat': '()',
+    'ImageView.to_bytes': '()',
+    'ImageView.to_image': '()',
+    'JpegOptions': "(quality=90, progressive=False, subsampling='4:2:0')",
+    'Line': '(*, start, end, color, thickness=1, overlay=None, style=None)',
+    'Line.copy': '()',
+    'Pipeline.convert': '(mode)',
+    'Pipeline.crop': '(x1, y1, x2, y2)',
+    'Pipeline.execute': '()',
+    'Pipeline.flip': '()',
+    'Pipeline.mirror': '()',
+    'Pipeline.resize': '(width, height, algorithm)',
+    'Pipeline.rotate': '(degrees)',
+    'PngOptions': "(compression='default', interlaced=False, filter='adaptive')",
+    'PngOptions.deterministic': '()',
+    'Polygon': '(*, vertices, fill=None, border=None, overlay=None)',
+    'Polygon.copy': '()',
+    'Rectangle': '(*, position, size, border=None, fill=None, overlay=None, inner_shadow=None, outer_glow=None, radius=0)',
+    'Rectangle.copy': '()',
+    'Rectangle.from_bounding_box': '(x1, y1, x2, y2)',
+    'Scene': '()',
+    'Scene.add': '(entity, z=0)',
+    'Scene.remove': '(index)',
+    'Scene.render': '(width, height, background)',
+    'Shadow': '(*, color, blur=4, offset=(0, 0))',
+    'Shadow.copy': '()',
+    'StrokeStyle.dashed': '(length, gap)',
+    'StrokeStyle.dotted': '(spacing)',
+    'StrokeStyle.solid': '()',
+    'TextLayout': "(position=None, width=None, horizontal_anchor=None, vertical_anchor=None, wrap=None, tab_width=4, render_mode='smooth', mono_threshold=128, missing_glyph_policy='replace', replacement_char=None, shadow=None)",
+    'TextLayout.centered': '()',
+    'TextLayout.get_bounding_box': '(include_effects=False)',
+    'TextLayout.get_dimensions': '(include_effects=False)',
+    'TextLayout.lines': '()',
+    'TextLayout.push_basic_text': '(font, text, fill)',
+    'TextLayout.push_segment': '(segment)',
+    'TextLayout.recover': '()',
+    'TextSegment': "(font, text, fill, position=None, size=None, overlay=None, width=None, wrap=None, tab_width=4, render_mode='smooth', mono_threshold=128, missing_glyph_policy='replace', replacement_char=None, shadow=None)",
+    'TextSegment.copy': '()',
+    'WebPOptions': '(quality=80, lossless=False)',
+    'contrast_ratio': '(a, b)',
+    'nearest_color': "(pixel, palette, metric='euclidean')",
+    'pack_atlas': '(images, *, max_width, padding=0, power_of_two=False)',
+    'set_notebook_preview_size': '(size)',
+    'set_parallel_threshold': '(pixels)',
+    'set_trace_hook': '(hook)',
+    'thumbnail': "(data, max_size, format='jpeg', quality=80)",
+}
+
+
+def signature(obj) -> str:
+    sig = inspect.signature(obj)
+    params = list(sig.parameters.values())
+
+    # Unbound methods report `self` as a positional-only parameter
+    if params and params[0].name == 'self':
+        params = params[1:]
+
+    return str(sig.replace(parameters=params))
+
+
+def public_callables():
+    for cls in CONSTRUCTABLE:
+        yield cls.__name__, cls
+
+    for cls in CLASSES:
+        for name in vars(cls):
+            attr = getattr(cls, name)
+            if not name.startswith('_') and callable(attr):
+                yield f'{cls.__name__}.{name}', attr
+
+    for func in FUNCTIONS:
+        yield func.__name__, func
+
+
+@pytest.mark.parametrize('name, obj', list(public_callables()))
+def test_signature(name: str, obj) -> None:
+    assert signature(obj) == GOLDEN[name]
+
+
+def test_golden_is_complete() -> None:
+    assert sorted(name for name, _ in public_callables()) == sorted(GOLDEN)
@@ -0,0 +1,430 @@
+import copy
+import pickle
+import time
+from typing import Union
+
+import pytest
+
+from ril import Font, HorizontalAnchor, Image, ImageSequence, Pixel, Shadow, TextLayout, TextSegment, VerticalAnchor, WrapStyle
+
+
+def render(segment: Union[TextSegment, TextLayout]) -> list:
+    image = Image.new(120, 40, Pixel.from_rgb(0, 0, 0))
+    image.draw(segment)
+
+    return image.pixels()
+
+def test_font_pickle(font_path) -> None:
+    font = Font.open(font_path, 20)
+    restored = pickle.loads(pickle.dumps(font))
+
+    assert restored.optimal_size == font.optimal_size
+
+    def segment(font: Font) -> TextSegment:
+        return TextSegment(font, 'Hello', Pixel.from_rgb(255, 255, 255), position=(4, 4))
+
+    pixels = render(segment(font))
+    assert pixels != Image.new(120, 40, Pixel.from_rgb(0, 0, 0)).pixels()
+    assert render(segment(restored)) == pixels
+
+def test_text_segment_pickle(font_path) -> None:
+    font = Font.open(font_path, 20)
+    segment = TextSegment(font, 'Hello', Pixel.from_rgb(255, 255, 255), position=(4, 4))
+    restored = pickle.loads(pickle.dumps(segment))
+
+    assert restored.text == segment.text
+    assert restored.position == segment.position
+    assert render(restored) == render(segment)
+
+def test_text_segment_equality(font_path) -> None:
+    font = Font.open(font_path, 20)
+    segment = TextSegment(font, 'Hello', Pixel.from_rgb(255, 255, 255), position=(4, 4))
+
+    assert segment == TextSegment(font, 'Hello', Pixel.from_rgb(255, 255, 255), position=(4, 4))
+    assert segment != TextSegment(font, 'Hello', Pixel.from_rgb(255, 255, 254), position=(4, 4))
+    assert segment != TextSegment(font, 'Hello', Pixel.from_rgb(255, 255, 255), position=(4, 4), wrap=WrapStyle.Character)
+    assert segment != TextSegment(Font.open(font_path, 20), 'Hello', Pixel.from_rgb(255, 255, 255), position=(4, 4))
+
+    for duplicate in (segment.copy(), copy.copy(segment)):
+        assert duplicate == segment
+        assert duplicate.font is segment.font
+
+        duplicate.text = 'World'
+        assert duplicate != segment
+
+def test_shared_font_pickled_once(font_path) -> None:
+    font = Font.open(font_path, 20)
+    fill = Pixel.from_rgb(255, 255, 255)
+    single = pickle.dumps([TextSegment(font, 'a', fill)])
+    many = pickle.dumps([TextSegment(font, 'a', fill) for _ in range(10)])
+
+    assert len(many) < len(single) * 2
+
+def test_font_prewarm(font_path) -> None:
+    font = Font.open(font_path, 20)
+    font.prewarm('Prewarmed text', 31.5)
+
+    stats = font.cache_stats()
+    assert stats['glyphs'] > 0
+    assert stats['bytes'] > 0
+
+    render(TextSegment(font, 'Prewarmed text', Pixel.from_rgb(255, 255, 255), size=31.5))
+
+    assert font.cache_stats() == stats
+
+def test_font_cache_bounded(font_path) -> None:
+    font = Font.open(font_path, 20)
+    for size in range(600, 1000, 40):
+        font.prewarm('abcdefghijklmnopqrstuvwxyz', size)
+
+    # The glyphs rasterized last are kept, while the least recently used ones are evicted.
+    stats = font.cache_stats()
+    assert 0 < stats['bytes'] <= 32 * 1024 * 1024
+    assert stats['glyphs'] < 26 * 10
+def test_text_normalization(font_path) -> None:
+    font = Font.open(font_path, 12)
+    fill = Pixel.from_rgb(255, 255, 255)
+
+    assert render(TextSegment(font, 'a\tb\r\nc\rd', fill)) == render(TextSegment(font, 'a    b\nc\nd', fill))
+    assert render(TextSegment(font, 'a\tb', fill, tab_width=2)) == render(TextSegment(font, 'a  b', fill))
+
+def test_text_layout_normalization(font_path) -> None:
+    font = Font.open(font_path, 12)
+    fill = Pixel.from_rgb(255, 255, 255)
+
+    def layout(text: str, tab_width: int = 4) -> TextLayout:
+        layout = TextLayout(tab_width=tab_width)
+        layout.push_basic_text(font, text, fill)
+        return layout
+
+    assert layout('a\tb\r\nc').dimensions == layout('a    b\nc').dimensions
+    assert render(layout('a\tb\r\nc', tab_width=1)) == render(layout('a b\nc'))
+    assert layout('', tab_width=2).tab_width == 2
+
+def test_text_layout_lines(font_path) -> None:
+    font = Font.open(font_path, 12)
+    words = 'alpha beta gamma'
+
+    single = TextLayout(position=(0, 0))
+    single.push_basic_text(font, 'alpha', Pixel.from_rgb(255, 255, 255))
+    width = single.width + 4
+
+    layout = TextLayout(position=(10, 20), width=width, wrap=WrapStyle.Word)
+    layout.push_basic_text(font, words, Pixel.from_rgb(255, 255, 255))
+
+    assert layout.line_count == 3
+
+    lines = layout.lines()
+    assert len(lines) == 3
+    assert [y1 for _, y1, _, _ in lines] == sorted({y1 for _, y1, _, _ in lines})
+    assert all(x1 < x2 and y1 < y2 for x1, y1, x2, y2 in lines)
+    assert lines[0][1] >= 20
+
+    left, top, right, bottom = layout.bounding_box
+    assert all(left <= x1 and x2 <= right + 1 for x1, _, x2, _ in lines)
+
+def test_text_layout_cached_dimensions(font_path) -> None:
+    font = Font.open(font_path, 12)
+    fill = Pixel.from_rgb(255, 255, 255)
+
+    def build(*texts: str, **kwargs) -> TextLayout:
+        layout = TextLayout(**kwargs)
+        for text in texts:
+            layout.push_basic_text(font, text, fill)
+        return layout
+
+    layout = build('alpha', position=(10, 20))
+    assert layout.dimensions == build('alpha', position=(10, 20)).dimensions
+    assert (layout.width, layout.height) == layout.dimensions
+
+    layout.push_basic_text(font, ' beta\ngamma', fill)
+    assert layout.dimensions == build('alpha', ' beta\ngamma', position=(10, 20)).dimensions
+
+    layout.horizontal_anchor = HorizontalAnchor.Right
+    layout.vertical_anchor = VerticalAnchor.Bottom
+    expected = build('alpha', ' beta\ngamma', position=(10, 20))
+    expected.horizontal_anchor = HorizontalAnchor.Right
+    expected.vertical_anchor = VerticalAnchor.Bottom
+    assert layout.bounding_box == expected.bounding_box
+
+    wrapped = build()
+    wrapped.dimensions
+    wrapped.width = 10
+    wrapped.push_basic_text(font, 'alpha beta', fill)
+    assert wrapped.dimensions == build('alpha beta', width=10).dimensions
+
+    moved = build()
+    moved.dimensions
+    moved.position = (30, 40)
+    moved.wrap = WrapStyle.Character
+    moved.push_basic_text(font, 'alpha', fill)
+    assert moved.dimensions == build('alpha', position=(30, 40), wrap=WrapStyle.Character).dimensions
+
+
+def test_text_layout_dimensions_cached(font_path) -> None:
+    font = Font.open(font_path, 12)
+    fill = Pixel.from_rgb(255, 255, 255)
+
+    layout = TextLayout(width=400)
+    layout.push_basic_text(font, 'word ' * 5000, fill)
+
+    start = time.perf_counter()
+    for _ in range(20):
+        # Pushing empty text leaves the layout as is, but forces it to be measured again.
+        layout.push_basic_text(font, '', fill)
+        layout.dimensions
+    measured = time.perf_counter() - start
+
+    start = time.perf_counter()
+    for _ in range(20):
+        layout.dimensions
+    cached = time.perf_counter() - start
+
+    assert cached < measured
+
+def test_text_layout_lines_anchor(font_path) -> None:
+    font = Font.open(font_path, 12)
+
+    def layout(anchor: HorizontalAnchor) -> TextLayout:
+        layout = TextLayout(position=(100, 0), horizontal_anchor=anchor)
+        layout.push_basic_text(font, 'line\nlonger line', Pixel.from_rgb(255, 255, 255))
+        return layout
+
+    left = layout(HorizontalAnchor.Left).lines()
+    right = layout(HorizontalAnchor.Right).lines()
+
+    assert len(left) == len(right) == 2
+    assert [x1 for x1, _, _, _ in left] == [100, 100]
+    assert all(x2 <= 101 for _, _, x2, _ in right)
+
+    assert TextLayout().line_count == 0
+    assert TextLayout().lines() == []
+
+
+def test_layout_anchor_getters():
+    layout = TextLayout(
+        position=(10, 20),
+        horizontal_anchor=HorizontalAnchor.Right,
+        vertical_anchor=VerticalAnchor.Bottom,
+    )
+
+    assert layout.position == (10, 20)
+    assert layout.horizontal_anchor == HorizontalAnchor.Right
+    assert layout.vertical_anchor == VerticalAnchor.Bottom
+    assert layout.anchor == (HorizontalAnchor.Right, VerticalAnchor.Bottom)
+
+    layout.position = (5, 6)
+    layout.horizontal_anchor = HorizontalAnchor.Left
+    layout.vertical_anchor = VerticalAnchor.Top
+
+    assert layout.position == (5, 6)
+    assert layout.anchor == (HorizontalAnchor.Left, VerticalAnchor.Top)
+
+    assert layout.centered() is layout
+    assert layout.anchor == (HorizontalAnchor.Center, VerticalAnchor.Center)
+
+
+def alphas(segment: Union[TextSegment, TextLayout]) -> set:
+    image = Image.new(60, 40, Pixel.from_rgba(0, 0, 0, 0))
+    image.draw(segment)
+
+    return {pixel.a for row in image.pixels() for pixel in row}
+
+
+def test_render_mode(font_path) -> None:
+    font = Font.open(font_path, 32)
+    segment = TextSegment(font, 'a', Pixel.from_rgba(255, 255, 255, 255), position=(4, 0))
+    assert segment.render_mode == 'smooth'
+    assert alphas(segment) - {0, 255}
+
+    segment.render_mode = 'mono'
+    assert alphas(segment) == {0, 255}
+    assert pickle.loads(pickle.dumps(segment)).render_mode == 'mono'
+    assert segment != TextSegment(font, 'a', Pixel.from_rgba(255, 255, 255, 255), position=(4, 0))
+
+    layout = TextLayout(position=(4, 0), render_mode='mono', mono_threshold=1)
+    layout.push_segment(TextSegment(font, 'a', Pixel.from_rgba(255, 255, 255, 255)))
+    assert layout.mono_threshold == 1
+    assert alphas(layout) == {0, 255}
+
+    layout.render_mode = 'smooth'
+    assert alphas(layout) - {0, 255}
+
+    with pytest.raises(ValueError):
+        segment.render_mode = 'subpixel'
+
+
+# Private use characters, which no system font has a glyph for.
+MISSING = '\U000f0000'
+
+
+def test_missing_glyph_policy(font_path) -> None:
+    font = Font.open(font_path, 20)
+    fill = Pixel.from_rgb(255, 255, 255)
+    text = f'a{MISSING}b {MISSING}'
+
+    assert font.missing_glyphs(text) == [MISSING]
+    assert font.missing_glyphs('a\tb') == []
+
+    segment = TextSegment(font, text, fill, position=(4, 4))
+    assert segment.missing_glyph_policy == 'replace'
+    assert segment.replacement_char is None
+    assert render(segment) != render(TextSegment(font, 'ab ', fill, position=(4, 4)))
+
+    segment.missing_glyph_policy = 'skip'
+    assert render(segment) == render(TextSegment(font, 'ab ', fill, position=(4, 4)))
+
+    segment.missing_glyph_policy = 'replace'
+    segment.replacement_char = '?'
+    assert render(segment) == render(TextSegment(font, 'a?b ?', fill, position=(4, 4)))
+
+    restored = pickle.loads(pickle.dumps(segment))
+    assert restored == segment
+    assert restored.replacement_char == '?'
+
+    segment.missing_glyph_policy = 'error'
+    with pytest.raises(ValueError, match='index 1'):
+        render(segment)
+
+    with pytest.raises(ValueError):
+        segment.missing_glyph_policy = 'ignore'
+
+    with pytest.raises(ValueError):
+        segment.replacement_char = '??'
+
+
+def test_layout_missing_glyph_policy(font_path) -> None:
+    font = Font.open(font_path, 20)
+    fill = Pixel.from_rgb(255, 255, 255)
+
+    skipped = TextLayout(position=(4, 4), missing_glyph_policy='skip')
+    skipped.push_basic_text(font, f'a{MISSING}b', fill)
+    expected = TextLayout(position=(4, 4))
+    expected.push_basic_text(font, 'ab', fill)
+    assert render(skipped) == render(expected)
+
+    # The policy of the layout overrides the one of the segment.
+    replaced = TextLayout(position=(4, 4), replacement_char='?')
+    replaced.push_segment(TextSegment(font, f'a{MISSING}b', fill, missing_glyph_policy='error'))
+    expected = TextLayout(position=(4, 4))
+    expected.push_basic_text(font, 'a?b', fill)
+    assert render(replaced) == render(expected)
+
+    strict = TextLayout(missing_glyph_policy='error')
+    with pytest.raises(ValueError, match='index 2'):
+        strict.push_basic_text(font, f'ab{MISSING}', fill)
+    with pytest.raises(ValueError):
+        strict.push_segment(TextSegment(font, f'{MISSING}', fill))
+
+    strict.push_basic_text(font, 'ab', fill)
+    assert strict.line_count == 1
+
+
+def painted(segment: Union[TextSegment, TextLayout]) -> dict:
+    image = Image.new(60, 40, Pixel.from_rgb(0, 0, 0))
+    image.draw(segment)
+
+    return {(x, y): (pixel.r, pixel.g, pixel.b) for y, row in enumerate(image.pixels()) for x, pixel in enumerate(row) if (pixel.r, pixel.g, pixel.b) != (0, 0, 0)}
+
+
+def test_text_shadow(font_path) -> None:
+    font = Font.open(font_path, 20)
+    fill = Pixel.from_rgb(255, 255, 255)
+    shadow = Shadow(color=Pixel.from_rgb(255, 0, 0), blur=0, offset=(6, 6))
+
+    segment = TextSegment(font, 'I', fill, position=(4, 4))
+    glyph = painted(segment)
+    segment.shadow = shadow
+    shadowed = painted(segment)
+
+    red = [position for position, color in shadowed.items() if color == (255, 0, 0)]
+    assert red
+    for x, y in red:
+        assert (x, y) not in glyph
+        assert (x - 6, y - 6) in glyph
+
+    restored = pickle.loads(pickle.dumps(segment))
+    assert restored.shadow == shadow
+    assert restored == segment
+
+    layout = TextLayout(position=(4, 4), shadow=shadow)
+    layout.push_basic_text(font, 'I', fill)
+    assert [position for position, color in painted(layout).items() if color == (255, 0, 0)] == red
+
+    left, top, right, bottom = layout.bounding_box
+    assert layout.get_bounding_box() == layout.bounding_box
+    assert layout.get_bounding_box(include_effects=True) == (left, top, right + 6, bottom + 6)
+    assert layout.get_dimensions(include_effects=True) == (right - left + 6, bottom - top + 6)
+
+    layout.shadow = None
+    assert layout.get_bounding_box(include_effects=True) == layout.bounding_box
+
+
+def test_typewriter(font_path) -> None:
+    font = Font.open(font_path, 20)
+    background = Image.new(120, 40, Pixel.from_rgb(0, 0, 0))
+    fill = Pixel.from_rgb(255, 255, 255)
+
+    seq = ImageSequence.typewriter(background, font, 'Hello', position=(4, 4), fill=fill)
+    assert len(seq) == 5
+    assert [frame.delay for frame in seq.frames] == [50] * 5
+
+    frames = seq.frames
+    assert frames[0] != frames[-1]
+    assert frames[-1].image.pixels() == render(TextSegment(font, 'Hello', fill, position=(4, 4)))
+    assert background.pixels() == Image.new(120, 40, Pixel.from_rgb(0, 0, 0)).pixels()
+
+    assert len(ImageSequence.typewriter(background, font, 'Hello', position=(4, 4), fill=fill, chars_per_frame=2, delay=20)) == 3
+    assert len(ImageSequence.typewriter(background, font, 'Hi there', position=(4, 4), fill=fill, width=30, wrap=WrapStyle.Word)) == 8
+
+    for text, chars_per_frame in (('', 1), ('Hello', 0)):
+        with pytest.raises(ValueError):
+            ImageSequence.typewriter(background, font, text, position=(4, 4), fill=fill, chars_per_frame=chars_per_frame)
+
+def test_text_segment_subpixel_position(font_path) -> None:
+    font = Font.open(font_path, 20)
+    segment = TextSegment(font, 'Hello', Pixel.from_rgb(255, 255, 255), position=(4.5, 5.5))
+
+    assert segment.position == (4.5, 5.5)
+    assert render(segment) == render(TextSegment(font, 'Hello', Pixel.from_rgb(255, 255, 255), position=(4, 6)))
+
+    segment.position = (4.0, 6.0)
+    assert segment == TextSegment(font, 'Hello', Pixel.from_rgb(255, 255, 255), position=(4, 6))
+
+def test_text_segment_smooth_position_rounds(font_path) -> None:
+    # Smooth text isn't positioned between pixels, so positions between pixels round like shapes do.
+    font = Font.open(font_path, 20)
+
+    def at(x: float) -> list:
+        return render(TextSegment(font, 'Hello', Pixel.from_rgb(255, 255, 255), position=(x, 4), render_mode='smooth'))
+
+    assert at(10.0) == at(10.4) == at(10.5)
+    assert at(10.6) == at(11) != at(10)
+
+def test_text_layout_recover(font_path) -> None:
+    if not hasattr(TextLayout, '_poison'):
+        pytest.skip('Poisoning a text layout requires the test-hooks feature')
+
+    font = Font.open(font_path, 12)
+    layout = TextLayout(position=(4, 4), width=80)
+    layout.push_basic_text(font, 'Hello\nWorld', Pixel.from_rgb(255, 255, 255))
+    expected = render(layout)
+    dimensions = layout.dimensions
+
+    assert not layout.is_poisoned
+    layout.recover()
+
+    with pytest.raises(BaseException, match='on purpose'):
+        layout._poison()
+
+    assert layout.is_poisoned
+    with pytest.raises(RuntimeError, match='recover'):
+        layout.dimensions
+
+    layout.recover()
+    assert not layout.is_poisoned
+    assert layout.dimensions == dimensions
+    assert render(layout) == expected
+
+    layout.push_basic_text(font, '!', Pixel.from_rgb(255, 255, 255))
+    assert render(layout) != expected
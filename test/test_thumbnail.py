@@ -0,0 +1,72 @@
+from typing import List
+
+import pytest
+
+import ril
+from helpers import gradient
+from ril import Image, JpegOptions, ResizeAlgorithm
+
+
+def raw(image: Image) -> bytes:
+    return image.view(0, 0, *image.dimensions).to_bytes()
+
+def mean_difference(a: Image, b: Image) -> float:
+    assert a.dimensions == b.dimensions
+    a, b = raw(a), raw(b)
+
+    return sum(abs(x - y) for x, y in zip(a, b)) / len(a)
+
+def step_by_step(data: bytes, width: int, height: int, encoding: str, **kwargs) -> Image:
+    image = Image.from_bytes(data)
+    image.resize(width, height, ResizeAlgorithm.Lanczos3)
+
+    return Image.from_bytes(image.encode(encoding, **kwargs))
+
+
+@pytest.mark.parametrize('dimensions, expected', [((400, 300), (100, 75)), ((90, 360), (25, 100)), ((60, 40), (60, 40))])
+def test_thumbnail_dimensions(dimensions, expected) -> None:
+    data = gradient(*dimensions, 'RGB').encode('png')
+    thumbnail = Image.from_bytes(ril.thumbnail(data, 100, format='png'))
+
+    assert thumbnail.dimensions == expected
+
+def test_thumbnail_matches_step_by_step() -> None:
+    data = gradient(400, 300, 'RGB').encode('png')
+
+    assert raw(Image.from_bytes(ril.thumbnail(data, 100, format='png'))) == raw(step_by_step(data, 100, 75, 'png'))
+
+    thumbnail = Image.from_bytes(ril.thumbnail(data, 100))
+    expected = step_by_step(data, 100, 75, 'jpeg', options=JpegOptions(quality=80))
+
+    assert mean_difference(thumbnail, expected) < 1
+
+def test_thumbnail_jpeg_dct_scaling() -> None:
+    data = gradient(2400, 1600, 'RGB').encode('jpeg', options=JpegOptions(quality=95))
+    events: List[dict] = []
+
+    ril.set_trace_hook(events.append)
+    try:
+        thumbnail = Image.from_bytes(ril.thumbnail(data, 200, quality=90))
+    finally:
+        ril.set_trace_hook(None)
+
+    decoded = [event['dimensions'] for event in events if event['operation'] == 'decode' and event['event'] == 'end']
+    assert decoded == [(300, 200)]
+
+    assert thumbnail.dimensions == (200, 133)
+    assert mean_difference(thumbnail, step_by_step(data, 200, 133, 'jpeg', options=JpegOptions(quality=90))) < 3
+
+def test_thumbnail_errors() -> None:
+    data = gradient(40, 30, 'RGB').encode('png')
+
+    with pytest.raises(ValueError):
+        ril.thumbnail(data, 0)
+
+    with pytest.raises(ValueError):
+        ril.thumbnail(data, 10, quality=0)
+
+    with pytest.raises(ValueError):
+        ril.thumbnail(data, 10, format='nope')
+
+    with pytest.raises(ril.UnknownFormatError):
+        ril.thumbnail(b'not an image', 10)
@@ -0,0 +1,68 @@
+from typing import List
+
+import ril
+from ril import Frame, GifOptions, Image, ImageSequence, Pixel, ResizeAlgorithm
+
+
+def milestones(events: List[dict]) -> list:
+    return [(event['operation'], event['event'], event['dimensions']) for event in events]
+
+def test_trace_pipeline(tmp_path, events: List[dict]) -> None:
+    path = tmp_path / 'source.png'
+    Image.new(40, 30, Pixel.from_rgb(10, 20, 30)).save(str(path))
+    events.clear()
+
+    image = Image.open(str(path))
+    image.resize(20, 15, ResizeAlgorithm.Bilinear)
+    image.encode('png')
+
+    assert milestones(events) == [
+        ('decode', 'start', None),
+        ('decode', 'end', (40, 30)),
+        ('resize', 'start', (40, 30)),
+        ('resize', 'end', (20, 15)),
+        ('encode', 'start', (20, 15)),
+        ('encode', 'end', (20, 15)),
+    ]
+
+    assert all(event['elapsed_ms'] >= 0 for event in events)
+
+def test_trace_sequence_progress(events: List[dict]) -> None:
+    seq = ImageSequence.from_frames([Frame(Image.new(8, 6, Pixel.from_rgb(i * 40, 0, 0))) for i in range(3)])
+    seq.encode('gif', options=GifOptions())
+
+    progress = [(event['frame'], event['frames']) for event in events if event['event'] == 'progress']
+    assert progress == [(1, 3), (2, 3), (3, 3)]
+
+    assert [(operation, event) for operation, event, _ in milestones(events)] == [
+        ('encode', 'start'),
+        ('encode_frames', 'start'),
+        ('encode_frames', 'progress'),
+        ('encode_frames', 'progress'),
+        ('encode_frames', 'progress'),
+        ('encode_frames', 'end'),
+        ('encode', 'end'),
+    ]
+
+def test_trace_hook_disabled(events: List[dict]) -> None:
+    ril.set_trace_hook(None)
+
+    image = Image.new(4, 4, Pixel.from_rgb(0, 0, 0))
+    image.resize(2, 2, ResizeAlgorithm.Nearest)
+    image.encode('png')
+
+    assert events == []
+
+def test_trace_hook_errors_are_ignored(capsys) -> None:
+    def hook(event: dict) -> None:
+        raise RuntimeError('broken hook')
+
+    ril.set_trace_hook(hook)
+    try:
+        image = Image.new(4, 4, Pixel.from_rgb(0, 0, 0))
+        image.resize(2, 2, ResizeAlgorithm.Nearest)
+    finally:
+        ril.set_trace_hook(None)
+
+    assert image.dimensions == (2, 2)
+    assert 'broken hook' in capsys.readouterr().err